@@ -33,6 +33,10 @@ enum ListenerError {
     NoResponseError,
     #[error("Provided index is too large! (Max 64 bytes)")]
     IndexTooLarge,
+    #[error("The traversal exceeds the limit of {0} messages!")]
+    TraversalLimit(usize),
+    #[error("The milestone is {0} milestones above the latest ledger checkpoint, over the limit of {1}!")]
+    ReplayLimit(u32, u32),
     #[error("Invalid hexidecimal encoding!")]
     InvalidHex,
     #[error("Specified keyspace ({0}) is not configured!")]
@@ -51,7 +55,11 @@ impl ListenerError {
     pub fn status(&self) -> Status {
         match self {
             ListenerError::NoResults | ListenerError::InvalidKeyspace(_) => Status::NotFound,
-            ListenerError::IndexTooLarge | ListenerError::InvalidHex | ListenerError::BadParse(_) => Status::BadRequest,
+            ListenerError::IndexTooLarge
+            | ListenerError::TraversalLimit(_)
+            | ListenerError::ReplayLimit(..)
+            | ListenerError::InvalidHex
+            | ListenerError::BadParse(_) => Status::BadRequest,
             _ => Status::InternalServerError,
         }
     }
@@ -8,6 +8,7 @@ use bee_message::{
     milestone::Milestone,
     payload::Payload,
     prelude::{
+        Address,
         Ed25519Address,
         Message,
         MessageId,
@@ -17,8 +18,18 @@ use bee_message::{
     },
 };
 use chronicle_broker::{
+    archive::ArchiveReader,
+    ledger::{
+        self,
+        LedgerCheckpoints,
+        StateAtError,
+    },
+    reconstruct_milestone_data,
     AnalyticsData,
+    AnalyticsTimeseries,
     SyncData,
+    SyncReport,
+    SyncStatus,
 };
 use chronicle_common::{
     config::PartitionConfig,
@@ -41,6 +52,8 @@ use chronicle_storage::{
         OutputRes,
         PartitionId,
         Partitioned,
+        RollupInterval,
+        RollupRange,
     },
     keyspaces::ChronicleKeyspace,
 };
@@ -79,6 +92,7 @@ use std::{
     io::Cursor,
     path::PathBuf,
     str::FromStr,
+    sync::Arc,
     time::SystemTime,
 };
 use tokio::sync::mpsc;
@@ -99,7 +113,8 @@ impl<H: ChronicleAPIScope> EventLoop<ChronicleAPISender<H>> for Listener<RocketL
                 .map_err(|_| Need::Abort)?;
         }
 
-        let storage_config = get_config_async().await.storage_config;
+        let config = get_config_async().await;
+        let storage_config = config.storage_config;
 
         let keyspaces = storage_config
             .keyspaces
@@ -108,6 +123,50 @@ impl<H: ChronicleAPIScope> EventLoop<ChronicleAPISender<H>> for Listener<RocketL
             .map(|k| k.name)
             .collect::<HashSet<_>>();
 
+        let default_keyspace = storage_config
+            .keyspaces
+            .first()
+            .map(|keyspace| keyspace.name.clone())
+            .unwrap_or("permanode".to_owned());
+        let archive_readers = ArchiveReaders(
+            config
+                .broker_config
+                .network_profiles(&default_keyspace)
+                .into_iter()
+                .filter_map(|network| {
+                    network
+                        .logs_dir
+                        .map(|logs_dir| (network.keyspace, ArchiveReader::new(PathBuf::from(logs_dir))))
+                })
+                .collect(),
+        );
+        let sync_ranges = SyncRanges(
+            config
+                .broker_config
+                .network_profiles(&default_keyspace)
+                .into_iter()
+                .map(|network| (network.keyspace, network.sync_range.unwrap_or_default()))
+                .collect(),
+        );
+        let ledgers = Ledgers(
+            config
+                .broker_config
+                .ledger
+                .iter()
+                .flat_map(|ledger_config| {
+                    keyspaces.iter().map(move |keyspace| {
+                        (
+                            keyspace.clone(),
+                            Ledger {
+                                checkpoints: LedgerCheckpoints::of_keyspace(&ledger_config.checkpoints_dir, keyspace),
+                                max_replay_distance: ledger_config.replay_distance_limit(),
+                            },
+                        )
+                    })
+                })
+                .collect(),
+        );
+
         construct_rocket(
             self.data
                 .rocket
@@ -115,6 +174,9 @@ impl<H: ChronicleAPIScope> EventLoop<ChronicleAPISender<H>> for Listener<RocketL
                 .ok_or_else(|| Need::Abort)?
                 .manage(storage_config.partition_config.clone())
                 .manage(keyspaces)
+                .manage(archive_readers)
+                .manage(ledgers)
+                .manage(sync_ranges)
                 .register(catchers![internal_error, not_found]),
         )
         .launch()
@@ -133,9 +195,13 @@ fn construct_rocket(rocket: Rocket) -> Rocket {
                 metrics,
                 service,
                 sync,
+                sync_report,
                 get_message,
                 get_message_metadata,
                 get_message_children,
+                get_message_ancestors,
+                get_message_descendants,
+                get_message_path_to_milestone,
                 get_message_by_index,
                 get_output_by_transaction_id,
                 get_output,
@@ -144,13 +210,48 @@ fn construct_rocket(rocket: Rocket) -> Rocket {
                 get_transaction_for_message,
                 get_transaction_included_message,
                 get_milestone,
-                get_analytics
+                get_archived_milestone,
+                get_ed25519_balance,
+                get_analytics,
+                get_analytics_timeseries
             ],
         )
         .attach(CORS)
         .attach(RequestTimer)
 }
 
+/// The default depth of the tangle traversals
+const DEFAULT_TRAVERSAL_DEPTH: u32 = 10;
+/// The maximum depth of the tangle traversals
+const MAX_TRAVERSAL_DEPTH: u32 = 50;
+/// The default number of messages of the tangle traversals
+const DEFAULT_TRAVERSAL_NODES: usize = 100;
+/// The maximum number of messages of the tangle traversals
+const MAX_TRAVERSAL_NODES: usize = 1000;
+/// The number of messages fetched concurrently by the tangle traversals
+const TRAVERSAL_CONCURRENCY: usize = 16;
+/// The number of messages fetched concurrently to reconstruct the milestones replayed by a balance query
+const LEDGER_REPLAY_CONCURRENCY: usize = 16;
+
+/// The archive readers of the networks which have a logs directory, by keyspace
+#[derive(Default)]
+struct ArchiveReaders(HashMap<String, ArchiveReader>);
+
+/// The ledgers of the keyspaces, if the ledger replay is configured
+#[derive(Default)]
+struct Ledgers(HashMap<String, Ledger>);
+
+/// The ledger checkpoints of a keyspace
+struct Ledger {
+    checkpoints: LedgerCheckpoints,
+    /// The maximum number of milestones replayed above a checkpoint by a balance query
+    max_replay_distance: u32,
+}
+
+/// The configured sync ranges of the networks, by keyspace
+#[derive(Default)]
+struct SyncRanges(HashMap<String, SyncRange>);
+
 struct CORS;
 
 #[rocket::async_trait]
@@ -310,6 +411,22 @@ async fn sync(keyspaces: State<'_, HashSet<String>>, keyspace: String) -> Result
         .map_err(|e| ListenerError::Other(e.into()))
 }
 
+#[get("/<keyspace>/sync/report")]
+async fn sync_report(
+    keyspaces: State<'_, HashSet<String>>,
+    sync_ranges: State<'_, SyncRanges>,
+    keyspace: String,
+) -> Result<Json<SyncReport>, ListenerError> {
+    if !keyspaces.contains(&keyspace) {
+        return Err(ListenerError::InvalidKeyspace(keyspace));
+    }
+    let sync_range = sync_ranges.0.get(&keyspace).copied().unwrap_or_default();
+    let sync_data = SyncData::try_fetch(&ChronicleKeyspace::new(keyspace.clone()), &sync_range, 3)
+        .await
+        .map_err(|e| ListenerError::Other(e.into()))?;
+    Ok(Json(SyncStatus::of(&keyspace).report(&keyspace, &sync_data)))
+}
+
 async fn query<V, S, K>(
     keyspace: S,
     key: K,
@@ -738,6 +855,345 @@ async fn get_message_children(
     }
 }
 
+/// Clamp the requested depth and number of messages of a tangle traversal to the server-side limits
+fn traversal_limits(depth: Option<u32>, max_nodes: Option<usize>) -> (u32, usize) {
+    (
+        depth.unwrap_or(DEFAULT_TRAVERSAL_DEPTH).min(MAX_TRAVERSAL_DEPTH),
+        max_nodes
+            .unwrap_or(DEFAULT_TRAVERSAL_NODES)
+            .clamp(1, MAX_TRAVERSAL_NODES),
+    )
+}
+
+/// Fetch the metadata of a message, if it's stored
+async fn try_metadata(
+    keyspace: &ChronicleKeyspace,
+    message_id: MessageId,
+) -> Result<Option<MessageMetadata>, ListenerError> {
+    match query::<MessageMetadata, _, _>(keyspace.clone(), message_id, None, None).await {
+        Ok(metadata) => Ok(Some(metadata)),
+        Err(ListenerError::NoResults) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Fetch up to `limit` children of a message from the `parents` table, along with whether some were left out
+async fn try_children(
+    keyspace: &str,
+    message_id: MessageId,
+    limit: usize,
+    partition_config: &PartitionConfig,
+) -> Result<(Vec<Partitioned<ParentRecord>>, bool), ListenerError> {
+    let mut state = None;
+    match page::<_, ParentRecord>(
+        keyspace.to_owned(),
+        Hint::parent(message_id.to_string()),
+        limit,
+        &mut state,
+        partition_config,
+        message_id,
+    )
+    .await
+    {
+        Ok(mut children) => {
+            let more = state.is_some() || children.len() > limit;
+            children.truncate(limit);
+            Ok((children, more))
+        }
+        Err(ListenerError::NoResults) => Ok((Vec::new(), false)),
+        Err(e) => Err(e),
+    }
+}
+
+/// The messages and references reached by a tangle traversal
+struct Traversal {
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+    /// Whether some messages were left out at the depth or node limit
+    truncated: bool,
+}
+
+impl Traversal {
+    fn new(message_id: &MessageId, metadata: &MessageMetadata) -> Self {
+        Self {
+            nodes: vec![GraphNode::new(message_id, Some(metadata), 0)],
+            edges: Vec::new(),
+            truncated: false,
+        }
+    }
+    fn into_response(self, message_id: &MessageId) -> ListenerResponse {
+        ListenerResponse::MessageGraph {
+            message_id: message_id.to_string(),
+            nodes: self.nodes,
+            edges: self.edges,
+            truncated: self.truncated,
+        }
+    }
+}
+
+/// Walk the parents of a message breadth first, up to `max_depth` hops and `max_nodes` messages, fetching the
+/// metadata of the parents with `fetch`
+async fn traverse_ancestors<F, Fut>(
+    metadata: MessageMetadata,
+    max_depth: u32,
+    max_nodes: usize,
+    fetch: F,
+) -> Result<Traversal, ListenerError>
+where
+    F: Fn(MessageId) -> Fut,
+    Fut: std::future::Future<Output = Result<Option<MessageMetadata>, ListenerError>>,
+{
+    let mut traversal = Traversal::new(&metadata.message_id, &metadata);
+    let mut visited = HashSet::new();
+    visited.insert(metadata.message_id);
+    let mut frontier = vec![metadata];
+    for depth in 1..=max_depth {
+        let mut parents = Vec::new();
+        for metadata in frontier.drain(..) {
+            for parent in metadata.parent_message_ids.iter() {
+                if !visited.contains(parent) {
+                    if visited.len() >= max_nodes {
+                        traversal.truncated = true;
+                        continue;
+                    }
+                    visited.insert(*parent);
+                    parents.push(*parent);
+                }
+                traversal.edges.push(GraphEdge::new(&metadata.message_id, parent));
+            }
+        }
+        let mut fetched = futures::stream::iter(parents.into_iter().map(|parent| {
+            let metadata = fetch(parent);
+            async move { (parent, metadata.await) }
+        }))
+        .buffer_unordered(TRAVERSAL_CONCURRENCY);
+        while let Some((parent, metadata)) = fetched.next().await {
+            // The parents which aren't stored, like the solid entry points, end the traversal
+            let metadata = metadata?;
+            traversal.nodes.push(GraphNode::new(&parent, metadata.as_ref(), depth));
+            frontier.extend(metadata);
+        }
+        if frontier.is_empty() {
+            break;
+        }
+    }
+    // The parents of the messages at the depth limit weren't traversed
+    traversal.truncated |= frontier.iter().any(|metadata| {
+        metadata
+            .parent_message_ids
+            .iter()
+            .any(|parent| !visited.contains(parent))
+    });
+    Ok(traversal)
+}
+
+/// Walk the children of a message breadth first, up to `max_depth` hops and `max_nodes` messages. `fetch` returns up
+/// to the given number of children of a message, along with whether some were left out.
+async fn traverse_descendants<F, Fut>(
+    metadata: MessageMetadata,
+    max_depth: u32,
+    max_nodes: usize,
+    fetch: F,
+) -> Result<Traversal, ListenerError>
+where
+    F: Fn(MessageId, usize) -> Fut,
+    Fut: std::future::Future<Output = Result<(Vec<Partitioned<ParentRecord>>, bool), ListenerError>>,
+{
+    let mut traversal = Traversal::new(&metadata.message_id, &metadata);
+    let mut visited = HashSet::new();
+    visited.insert(metadata.message_id);
+    let mut frontier = vec![metadata.message_id];
+    for depth in 1..=max_depth {
+        // No message fetches more children than the node limit has room for
+        let remaining = max_nodes.saturating_sub(visited.len());
+        if remaining == 0 {
+            break;
+        }
+        let mut fetched = futures::stream::iter(std::mem::take(&mut frontier).into_iter().map(|parent| {
+            let children = fetch(parent, remaining);
+            async move { (parent, children.await) }
+        }))
+        .buffer_unordered(TRAVERSAL_CONCURRENCY);
+        let mut children_ids = Vec::new();
+        while let Some((parent, children)) = fetched.next().await {
+            let (children, more) = children?;
+            traversal.truncated |= more;
+            for child in children {
+                let child_id = child.message_id;
+                if !visited.contains(&child_id) {
+                    if visited.len() >= max_nodes {
+                        traversal.truncated = true;
+                        continue;
+                    }
+                    visited.insert(child_id);
+                    children_ids.push(child_id);
+                    traversal.nodes.push((child, depth).into());
+                }
+                traversal.edges.push(GraphEdge::new(&child_id, &parent));
+            }
+        }
+        frontier = children_ids;
+        if frontier.is_empty() {
+            break;
+        }
+    }
+    // The children of the messages at the depth or node limit weren't traversed, a single one tells whether any is left
+    if !traversal.truncated {
+        let mut fetched = futures::stream::iter(frontier.into_iter().map(|parent| fetch(parent, 1)))
+            .buffer_unordered(TRAVERSAL_CONCURRENCY);
+        while let Some(children) = fetched.next().await {
+            let (children, more) = children?;
+            if more || children.iter().any(|child| !visited.contains(&child.message_id)) {
+                traversal.truncated = true;
+                break;
+            }
+        }
+    }
+    Ok(traversal)
+}
+
+/// Walk the parents of the milestone message breadth first, through the messages referenced by the milestone only,
+/// until the message is reached, fetching the metadata of the messages with `fetch`. Returns the shortest path from
+/// the message up to the milestone message, along with the metadata of its messages.
+async fn find_milestone_path<F, Fut>(
+    metadata: MessageMetadata,
+    milestone_message_id: MessageId,
+    max_nodes: usize,
+    fetch: F,
+) -> Result<Vec<(MessageId, Option<MessageMetadata>)>, ListenerError>
+where
+    F: Fn(MessageId) -> Fut,
+    Fut: std::future::Future<Output = Result<Option<MessageMetadata>, ListenerError>>,
+{
+    let message_id = metadata.message_id;
+    // The message isn't confirmed yet
+    let milestone_index = metadata.referenced_by_milestone_index.ok_or(ListenerError::NoResults)?;
+    let mut metadatas = HashMap::new();
+    metadatas.insert(message_id, metadata);
+    // Keep the child which led to each message to follow the shortest path back
+    let mut led_by = HashMap::new();
+    let mut visited = HashSet::new();
+    visited.insert(milestone_message_id);
+    let mut found = message_id == milestone_message_id;
+    let mut frontier = vec![milestone_message_id];
+    while !found && !frontier.is_empty() {
+        let mut fetched = futures::stream::iter(std::mem::take(&mut frontier).into_iter().map(|id| {
+            let metadata = fetch(id);
+            async move { (id, metadata.await) }
+        }))
+        .buffer_unordered(TRAVERSAL_CONCURRENCY);
+        let mut parents = Vec::new();
+        while let Some((id, metadata)) = fetched.next().await {
+            let metadata = match metadata? {
+                Some(metadata) if id == milestone_message_id => metadata,
+                Some(metadata) if metadata.referenced_by_milestone_index == Some(milestone_index) => metadata,
+                _ => continue,
+            };
+            for parent in metadata.parent_message_ids.iter() {
+                if visited.insert(*parent) {
+                    if visited.len() > max_nodes {
+                        return Err(ListenerError::TraversalLimit(max_nodes));
+                    }
+                    led_by.insert(*parent, id);
+                    found |= *parent == message_id;
+                    parents.push(*parent);
+                }
+            }
+            metadatas.insert(id, metadata);
+        }
+        frontier = parents;
+    }
+    if !found {
+        return Err(ListenerError::NoResults);
+    }
+    let mut path = vec![message_id];
+    while let Some(child) = path.last().and_then(|id| led_by.get(id)) {
+        path.push(*child);
+    }
+    Ok(path.into_iter().map(|id| (id, metadatas.remove(&id))).collect())
+}
+
+#[get("/<keyspace>/messages/<message_id>/ancestors?<depth>&<max_nodes>")]
+async fn get_message_ancestors(
+    keyspace: String,
+    message_id: String,
+    depth: Option<u32>,
+    max_nodes: Option<usize>,
+    keyspaces: State<'_, HashSet<String>>,
+) -> ListenerResult {
+    if !keyspaces.contains(&keyspace) {
+        return Err(ListenerError::InvalidKeyspace(keyspace));
+    }
+    let keyspace = ChronicleKeyspace::new(keyspace);
+    let message_id = MessageId::from_str(&message_id).map_err(|e| ListenerError::BadParse(e.into()))?;
+    let (max_depth, max_nodes) = traversal_limits(depth, max_nodes);
+    let metadata = query::<MessageMetadata, _, _>(keyspace.clone(), message_id, None, None).await?;
+    let traversal =
+        traverse_ancestors(metadata, max_depth, max_nodes, |parent| try_metadata(&keyspace, parent)).await?;
+    Ok(traversal.into_response(&message_id))
+}
+
+#[get("/<keyspace>/messages/<message_id>/descendants?<depth>&<max_nodes>")]
+async fn get_message_descendants(
+    keyspace: String,
+    message_id: String,
+    depth: Option<u32>,
+    max_nodes: Option<usize>,
+    partition_config: State<'_, PartitionConfig>,
+    keyspaces: State<'_, HashSet<String>>,
+) -> ListenerResult {
+    if !keyspaces.contains(&keyspace) {
+        return Err(ListenerError::InvalidKeyspace(keyspace));
+    }
+    let message_id = MessageId::from_str(&message_id).map_err(|e| ListenerError::BadParse(e.into()))?;
+    let (max_depth, max_nodes) = traversal_limits(depth, max_nodes);
+    let metadata =
+        query::<MessageMetadata, _, _>(ChronicleKeyspace::new(keyspace.clone()), message_id, None, None).await?;
+    let traversal = traverse_descendants(metadata, max_depth, max_nodes, |parent, limit| {
+        try_children(&keyspace, parent, limit, partition_config.borrow())
+    })
+    .await?;
+    Ok(traversal.into_response(&message_id))
+}
+
+#[get("/<keyspace>/messages/<message_id>/path-to-milestone?<max_nodes>")]
+async fn get_message_path_to_milestone(
+    keyspace: String,
+    message_id: String,
+    max_nodes: Option<usize>,
+    keyspaces: State<'_, HashSet<String>>,
+) -> ListenerResult {
+    if !keyspaces.contains(&keyspace) {
+        return Err(ListenerError::InvalidKeyspace(keyspace));
+    }
+    let keyspace = ChronicleKeyspace::new(keyspace);
+    let message_id = MessageId::from_str(&message_id).map_err(|e| ListenerError::BadParse(e.into()))?;
+    let (_, max_nodes) = traversal_limits(None, max_nodes);
+    let metadata = query::<MessageMetadata, _, _>(keyspace.clone(), message_id, None, None).await?;
+    // The message isn't confirmed yet
+    let milestone_index = metadata.referenced_by_milestone_index.ok_or(ListenerError::NoResults)?;
+    let milestone = query::<Milestone, _, _>(keyspace.clone(), MilestoneIndex(milestone_index), None, None).await?;
+    let milestone_message_id = *milestone.message_id();
+    let path = find_milestone_path(metadata, milestone_message_id, max_nodes, |id| {
+        try_metadata(&keyspace, id)
+    })
+    .await?;
+    Ok(ListenerResponse::MilestonePath {
+        message_id: message_id.to_string(),
+        milestone_index,
+        milestone_message_id: milestone_message_id.to_string(),
+        nodes: path
+            .iter()
+            .enumerate()
+            .map(|(depth, (id, metadata))| GraphNode::new(id, metadata.as_ref(), depth as u32))
+            .collect(),
+        edges: path
+            .windows(2)
+            .map(|ids| GraphEdge::new(&ids[1].0, &ids[0].0))
+            .collect(),
+    })
+}
+
 #[get("/<keyspace>/messages?<index>&<page_size>&<utf8>&<expanded>&<state>")]
 async fn get_message_by_index(
     keyspace: String,
@@ -1067,6 +1523,80 @@ async fn get_milestone(keyspace: String, index: u32, keyspaces: State<'_, HashSe
         })
 }
 
+#[get("/<keyspace>/archive/milestones/<index>")]
+async fn get_archived_milestone(
+    keyspace: String,
+    index: u32,
+    keyspaces: State<'_, HashSet<String>>,
+    archive_readers: State<'_, ArchiveReaders>,
+) -> ListenerResult {
+    if !keyspaces.contains(&keyspace) {
+        return Err(ListenerError::InvalidKeyspace(keyspace));
+    }
+    // The network of this keyspace is not archived
+    let reader = archive_readers.0.get(&keyspace).ok_or(ListenerError::NoResults)?;
+    let milestone_data = reader.get(index).await?.ok_or(ListenerError::NoResults)?;
+    serde_json::to_value(&milestone_data)
+        .map(ListenerResponse::ArchivedMilestone)
+        .map_err(|e| ListenerError::Other(e.into()))
+}
+
+#[get("/<keyspace>/ledger/balances/ed25519/<address>?<milestone>")]
+async fn get_ed25519_balance(
+    keyspace: String,
+    address: String,
+    milestone: Option<u32>,
+    keyspaces: State<'_, HashSet<String>>,
+    ledgers: State<'_, Ledgers>,
+    archive_readers: State<'_, ArchiveReaders>,
+) -> ListenerResult {
+    if !keyspaces.contains(&keyspace) {
+        return Err(ListenerError::InvalidKeyspace(keyspace));
+    }
+    let ed25519_address = Ed25519Address::from_str(&address).map_err(|e| ListenerError::BadParse(e.into()))?;
+    // The ledger replay is not configured
+    let ledger = ledgers.0.get(&keyspace).ok_or(ListenerError::NoResults)?;
+    let state = match milestone {
+        // The ledger is replayed from the latest checkpoint through the archived milestones, or the stored ones
+        Some(milestone) => {
+            let reader = archive_readers.0.get(&keyspace);
+            let chronicle_keyspace = ChronicleKeyspace::new(keyspace);
+            let fetch = |milestone_index| {
+                let keyspace = chronicle_keyspace.clone();
+                async move {
+                    if let Some(reader) = reader {
+                        if let Some(milestone_data) = reader.get(milestone_index).await? {
+                            return Ok(Some(milestone_data));
+                        }
+                    }
+                    reconstruct_milestone_data(keyspace, milestone_index, 0, LEDGER_REPLAY_CONCURRENCY).await
+                }
+            };
+            let state = ledger::state_at(&ledger.checkpoints, milestone, ledger.max_replay_distance, fetch)
+                .await
+                .map_err(|e| match e {
+                    StateAtError::NoCheckpoint(_) => ListenerError::NoResults,
+                    StateAtError::TooDistant {
+                        distance, max_distance, ..
+                    } => ListenerError::ReplayLimit(distance, max_distance),
+                    StateAtError::Other(e) => ListenerError::Other(e),
+                })?;
+            Arc::new(state)
+        }
+        // The latest checkpoint is cached until a newer one is written
+        None => ledger.checkpoints.latest().await?.ok_or(ListenerError::NoResults)?,
+    };
+    let balance = state.balance(&Address::Ed25519(ed25519_address));
+    Ok(ListenerResponse::Balance {
+        address_type: 1,
+        address,
+        ledger_index: state.ledger_index(),
+        balance: balance.amount,
+        dust_allowance: balance.dust_allowance,
+        output_count: balance.output_count,
+    })
+}
+
 #[get("/<keyspace>/analytics?<start>&<end>")]
 async fn get_analytics(
     keyspace: String,
@@ -1088,6 +1618,58 @@ async fn get_analytics(
     Ok(ListenerResponse::Analytics { ranges })
 }
 
+/// The maximum number of buckets of an analytics timeseries
+const MAX_TIMESERIES_BUCKETS: u64 = 10_000;
+/// The number of buckets of an analytics timeseries without start
+const DEFAULT_TIMESERIES_BUCKETS: u64 = 30;
+
+#[get("/<keyspace>/analytics/timeseries?<interval>&<from>&<to>")]
+async fn get_analytics_timeseries(
+    keyspace: String,
+    interval: Option<String>,
+    from: Option<u64>,
+    to: Option<u64>,
+    keyspaces: State<'_, HashSet<String>>,
+) -> ListenerResult {
+    if !keyspaces.contains(&keyspace) {
+        return Err(ListenerError::InvalidKeyspace(keyspace));
+    }
+    let keyspace = ChronicleKeyspace::new(keyspace);
+    let interval = interval
+        .as_deref()
+        .map(RollupInterval::from_str)
+        .transpose()
+        .map_err(ListenerError::BadParse)?
+        .unwrap_or(RollupInterval::Day);
+    let to = match to {
+        Some(to) => to,
+        None => SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|e| ListenerError::Other(e.into()))?
+            .as_secs(),
+    };
+    let from = from.unwrap_or_else(|| {
+        interval
+            .bucket(to)
+            .saturating_sub((DEFAULT_TIMESERIES_BUCKETS - 1) * interval.secs())
+    });
+    if from >= to {
+        return Err(ListenerError::BadParse(anyhow!(
+            "The timeseries start must be before its end"
+        )));
+    }
+    if (to - interval.bucket(from)) / interval.secs() > MAX_TIMESERIES_BUCKETS {
+        return Err(ListenerError::BadParse(anyhow!(
+            "The timeseries exceeds {} buckets",
+            MAX_TIMESERIES_BUCKETS
+        )));
+    }
+    let AnalyticsTimeseries { interval, series } =
+        AnalyticsTimeseries::try_fetch(&keyspace, RollupRange { interval, from, to }, 1, 5000).await?;
+
+    Ok(ListenerResponse::AnalyticsTimeseries { interval, series })
+}
+
 #[catch(500)]
 fn internal_error() -> ListenerError {
     ListenerError::Other(anyhow!("Internal server error!"))
@@ -1115,11 +1697,22 @@ mod tests {
     use serde_json::Value;
 
     async fn construct_client() -> Client {
+        construct_archive_client(ArchiveReaders::default()).await
+    }
+
+    async fn construct_archive_client(archive_readers: ArchiveReaders) -> Client {
+        construct_ledger_client(archive_readers, Ledgers::default()).await
+    }
+
+    async fn construct_ledger_client(archive_readers: ArchiveReaders, ledgers: Ledgers) -> Client {
         let mut keyspaces = HashSet::new();
         keyspaces.insert("permanode".to_string());
         let rocket = construct_rocket(rocket::ignite())
             .manage(PartitionConfig::default())
-            .manage(keyspaces);
+            .manage(keyspaces)
+            .manage(archive_readers)
+            .manage(ledgers)
+            .manage(SyncRanges::default());
         Client::tracked(rocket).await.expect("Invalid rocket instance!")
     }
 
@@ -1197,4 +1790,411 @@ mod tests {
             .expect("Failed to deserialize response!");
         assert_eq!(body.get("message").and_then(Value::as_str), Some("Worker NoRing"));
     }
+    #[rocket::async_test]
+    async fn get_message_ancestors() {
+        let client = construct_client().await;
+
+        let res = client
+            .get("/api/permanode/messages/91515c13d2025f79ded3758abe5dc640591c3b6d58b1c52cd51d1fa0585774bc/ancestors?depth=100")
+            .dispatch()
+            .await;
+        assert_eq!(res.status(), Status::InternalServerError);
+        assert_eq!(res.content_type(), Some(ContentType::JSON));
+        check_cors_headers(&res);
+
+        let res = client.get("/api/permanode/messages/invalid/ancestors").dispatch().await;
+        assert_eq!(res.status(), Status::BadRequest);
+        assert_eq!(res.content_type(), Some(ContentType::JSON));
+        check_cors_headers(&res);
+    }
+
+    #[rocket::async_test]
+    async fn get_message_descendants() {
+        let client = construct_client().await;
+
+        // the metadata of the message is queried first, like for its ancestors
+        let res = client
+            .get("/api/permanode/messages/91515c13d2025f79ded3758abe5dc640591c3b6d58b1c52cd51d1fa0585774bc/descendants?depth=100")
+            .dispatch()
+            .await;
+        assert_eq!(res.status(), Status::InternalServerError);
+        assert_eq!(res.content_type(), Some(ContentType::JSON));
+        check_cors_headers(&res);
+
+        let res = client
+            .get("/api/permanode/messages/invalid/descendants")
+            .dispatch()
+            .await;
+        assert_eq!(res.status(), Status::BadRequest);
+        assert_eq!(res.content_type(), Some(ContentType::JSON));
+        check_cors_headers(&res);
+    }
+
+    #[rocket::async_test]
+    async fn get_message_path_to_milestone() {
+        let client = construct_client().await;
+
+        let res = client
+            .get("/api/permanode/messages/91515c13d2025f79ded3758abe5dc640591c3b6d58b1c52cd51d1fa0585774bc/path-to-milestone")
+            .dispatch()
+            .await;
+        assert_eq!(res.status(), Status::InternalServerError);
+        assert_eq!(res.content_type(), Some(ContentType::JSON));
+        check_cors_headers(&res);
+
+        let res = client
+            .get("/api/permanode/messages/invalid/path-to-milestone")
+            .dispatch()
+            .await;
+        assert_eq!(res.status(), Status::BadRequest);
+        assert_eq!(res.content_type(), Some(ContentType::JSON));
+        check_cors_headers(&res);
+    }
+
+    fn id(byte: u8) -> MessageId {
+        MessageId::new([byte; 32])
+    }
+
+    /// The tangle below the milestone message 1, where the solid entry point 0 isn't stored and 7 is confirmed by
+    /// the next milestone:
+    /// 1 -> 2, 3; 2 -> 4; 3 -> 4, 5; 4 -> 6; 5 -> 6; 6 -> 0; 7 -> 1
+    fn tangle() -> HashMap<MessageId, MessageMetadata> {
+        vec![
+            (1, vec![2, 3], 10),
+            (2, vec![4], 10),
+            (3, vec![4, 5], 10),
+            (4, vec![6], 10),
+            (5, vec![6], 10),
+            (6, vec![0], 10),
+            (7, vec![1], 11),
+        ]
+        .into_iter()
+        .map(|(message, parents, milestone_index): (u8, Vec<u8>, u32)| {
+            let metadata = MessageMetadata {
+                message_id: id(message),
+                parent_message_ids: parents.into_iter().map(id).collect(),
+                is_solid: true,
+                referenced_by_milestone_index: Some(milestone_index),
+                ledger_inclusion_state: Some(LedgerInclusionState::NoTransaction),
+                should_promote: Some(false),
+                should_reattach: Some(false),
+                conflict_reason: None,
+                white_flag_index: Some(0),
+                milestone_timestamp_referenced: Some(0),
+            };
+            (id(message), metadata)
+        })
+        .collect()
+    }
+
+    fn node_ids(traversal: &Traversal) -> Vec<String> {
+        let mut ids = traversal.nodes.iter().map(|node| node.id.clone()).collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    fn ids(bytes: &[u8]) -> Vec<String> {
+        let mut ids = bytes.iter().map(|byte| id(*byte).to_string()).collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    #[rocket::async_test]
+    async fn traverse_message_ancestors() {
+        let tangle = tangle();
+        let fetch = |message_id| {
+            let metadata = tangle.get(&message_id).cloned();
+            async move { Ok(metadata) }
+        };
+        let traversal = traverse_ancestors(tangle[&id(1)].clone(), 10, 100, fetch)
+            .await
+            .unwrap();
+        // the solid entry point ends the traversal
+        assert_eq!(node_ids(&traversal), ids(&[0, 1, 2, 3, 4, 5, 6]));
+        assert_eq!(traversal.edges.len(), 8);
+        assert!(!traversal.truncated);
+
+        let traversal = traverse_ancestors(tangle[&id(1)].clone(), 1, 100, fetch).await.unwrap();
+        assert_eq!(node_ids(&traversal), ids(&[1, 2, 3]));
+        assert!(traversal.truncated);
+        let traversal = traverse_ancestors(tangle[&id(1)].clone(), 10, 2, fetch).await.unwrap();
+        assert_eq!(traversal.nodes.len(), 2);
+        assert!(traversal.truncated);
+        // nothing is left above the depth limit
+        let traversal = traverse_ancestors(tangle[&id(4)].clone(), 2, 100, fetch).await.unwrap();
+        assert_eq!(node_ids(&traversal), ids(&[0, 4, 6]));
+        assert!(!traversal.truncated);
+    }
+
+    #[rocket::async_test]
+    async fn traverse_message_descendants() {
+        let tangle = tangle();
+        let limits = std::sync::Mutex::new(Vec::new());
+        let fetch = |message_id, limit| {
+            limits.lock().unwrap().push(limit);
+            let mut children = tangle
+                .values()
+                .filter(|metadata| metadata.parent_message_ids.contains(&message_id))
+                .map(|metadata| {
+                    Partitioned::new(
+                        ParentRecord::new(metadata.message_id, metadata.ledger_inclusion_state),
+                        0,
+                        metadata.referenced_by_milestone_index.unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            children.sort_by_key(|child| child.message_id);
+            let more = children.len() > limit;
+            children.truncate(limit);
+            async move { Ok((children, more)) }
+        };
+        let traversal = traverse_descendants(tangle[&id(6)].clone(), 10, 100, &fetch)
+            .await
+            .unwrap();
+        assert_eq!(node_ids(&traversal), ids(&[1, 2, 3, 4, 5, 6, 7]));
+        assert_eq!(traversal.edges.len(), 8);
+        assert!(!traversal.truncated);
+
+        // the children of the messages at the depth limit are left
+        let traversal = traverse_descendants(tangle[&id(6)].clone(), 2, 100, &fetch)
+            .await
+            .unwrap();
+        assert_eq!(node_ids(&traversal), ids(&[2, 3, 4, 5, 6]));
+        assert!(traversal.truncated);
+        // while the messages without children leave nothing
+        let traversal = traverse_descendants(tangle[&id(1)].clone(), 1, 100, &fetch)
+            .await
+            .unwrap();
+        assert_eq!(node_ids(&traversal), ids(&[1, 7]));
+        assert!(!traversal.truncated);
+        // the node limit is just enough
+        let traversal = traverse_descendants(tangle[&id(6)].clone(), 10, 7, &fetch)
+            .await
+            .unwrap();
+        assert_eq!(traversal.nodes.len(), 7);
+        assert!(!traversal.truncated);
+
+        // the children are fetched up to the remaining node budget
+        limits.lock().unwrap().clear();
+        let traversal = traverse_descendants(tangle[&id(6)].clone(), 10, 2, &fetch)
+            .await
+            .unwrap();
+        assert_eq!(node_ids(&traversal), ids(&[4, 6]));
+        assert!(traversal.truncated);
+        assert_eq!(*limits.lock().unwrap(), vec![1]);
+        limits.lock().unwrap().clear();
+        let traversal = traverse_descendants(tangle[&id(6)].clone(), 10, 4, &fetch)
+            .await
+            .unwrap();
+        assert_eq!(traversal.nodes.len(), 4);
+        assert!(traversal.truncated);
+        assert_eq!(*limits.lock().unwrap(), vec![3, 1, 1]);
+    }
+
+    #[rocket::async_test]
+    async fn find_message_path_to_milestone() {
+        let tangle = tangle();
+        let fetch = |message_id| {
+            let metadata = tangle.get(&message_id).cloned();
+            async move { Ok(metadata) }
+        };
+        let path = find_milestone_path(tangle[&id(6)].clone(), id(1), 100, fetch)
+            .await
+            .unwrap();
+        // one of the shortest paths, through 2 or 3
+        let path_ids = path.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        assert_eq!(path_ids.len(), 4);
+        assert_eq!((path_ids[0], path_ids[1], path_ids[3]), (id(6), id(4), id(1)));
+        assert!(path.iter().all(|(_, metadata)| metadata.is_some()));
+        let path = find_milestone_path(tangle[&id(1)].clone(), id(1), 100, fetch)
+            .await
+            .unwrap();
+        assert_eq!(path.len(), 1);
+
+        // the message isn't referenced by the milestone
+        assert!(matches!(
+            find_milestone_path(tangle[&id(7)].clone(), id(1), 100, fetch).await,
+            Err(ListenerError::NoResults)
+        ));
+        let mut unconfirmed = tangle[&id(7)].clone();
+        unconfirmed.referenced_by_milestone_index = None;
+        assert!(matches!(
+            find_milestone_path(unconfirmed, id(1), 100, fetch).await,
+            Err(ListenerError::NoResults)
+        ));
+        assert!(matches!(
+            find_milestone_path(tangle[&id(6)].clone(), id(1), 3, fetch).await,
+            Err(ListenerError::TraversalLimit(3))
+        ));
+    }
+
+    #[rocket::async_test]
+    async fn get_archived_milestone() {
+        let client = construct_client().await;
+
+        let res = client.get("/api/permanode/archive/milestones/1").dispatch().await;
+        assert_eq!(res.status(), Status::NotFound);
+        assert_eq!(res.content_type(), Some(ContentType::JSON));
+        check_cors_headers(&res);
+    }
+
+    #[rocket::async_test]
+    async fn get_archived_milestone_from_logs() {
+        use chronicle_broker::{
+            archive::{
+                manifest::Manifest,
+                ArchiveFormat,
+                ArchiveRecord,
+                LogEncoder,
+            },
+            CreatedBy,
+            MilestoneData,
+        };
+        let logs_dir = std::env::temp_dir().join(format!("chronicle-api-archive-{}", std::process::id()));
+        tokio::fs::create_dir_all(&logs_dir).await.unwrap();
+        let mut encoder = LogEncoder::new(ArchiveFormat::Zstd, 3, 4);
+        let mut bytes = Vec::new();
+        for milestone_index in 10..20 {
+            let milestone_data = MilestoneData::new(milestone_index, CreatedBy::Incoming);
+            bytes.extend(
+                encoder
+                    .encode(&ArchiveRecord::new(&milestone_data, ArchiveFormat::Zstd).unwrap())
+                    .unwrap(),
+            );
+        }
+        bytes.extend(encoder.finish().unwrap());
+        let log_path = logs_dir.join("10to20.log");
+        tokio::fs::write(&log_path, bytes).await.unwrap();
+        Manifest::record(&log_path).await.unwrap();
+        let mut archive_readers = ArchiveReaders::default();
+        archive_readers
+            .0
+            .insert("permanode".to_string(), ArchiveReader::new(logs_dir.clone()));
+        let client = construct_archive_client(archive_readers).await;
+
+        let res = client.get("/api/permanode/archive/milestones/15").dispatch().await;
+        assert_eq!(res.status(), Status::Ok);
+        assert_eq!(res.content_type(), Some(ContentType::JSON));
+        check_cors_headers(&res);
+        let body: Value = serde_json::from_str(&res.into_string().await.expect("No body returned!"))
+            .expect("Failed to deserialize Archived Milestone Response!");
+        assert_eq!(body["data"]["milestone_index"], 15);
+
+        let res = client.get("/api/permanode/archive/milestones/20").dispatch().await;
+        assert_eq!(res.status(), Status::NotFound);
+        // the api doesn't write the sidecar indexes into the logs directory
+        let mut entries = tokio::fs::read_dir(&logs_dir).await.unwrap();
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            assert!(!entry.file_name().to_string_lossy().ends_with(".idx"));
+        }
+        tokio::fs::remove_dir_all(&logs_dir).await.unwrap();
+    }
+
+    #[rocket::async_test]
+    async fn get_ed25519_balance() {
+        let client = construct_client().await;
+
+        let res = client
+            .get("/api/permanode/ledger/balances/ed25519/1a1ce1b4da4dbcb0c8cb1d0a42393f1c5c2e8e1b9a69c2dcd1b2ad9e58e8b4a2?milestone=1")
+            .dispatch()
+            .await;
+        assert_eq!(res.status(), Status::NotFound);
+        assert_eq!(res.content_type(), Some(ContentType::JSON));
+        check_cors_headers(&res);
+    }
+
+    #[rocket::async_test]
+    async fn get_ed25519_balance_from_checkpoints() {
+        use bee_message::prelude::SignatureLockedSingleOutput;
+        use chronicle_broker::ledger::LedgerState;
+        let dir = std::env::temp_dir().join(format!("chronicle-api-ledger-{}", std::process::id()));
+        let logs_dir = dir.join("logs");
+        tokio::fs::create_dir_all(&logs_dir).await.unwrap();
+        let checkpoints = LedgerCheckpoints::new(dir.join("checkpoints"));
+        let address = Ed25519Address::new([0x1a; 32]);
+        let output = |index, amount| {
+            (
+                OutputId::new(TransactionId::new([1; 32]), index).unwrap(),
+                SignatureLockedSingleOutput::new(Address::Ed25519(address), amount)
+                    .unwrap()
+                    .into(),
+            )
+        };
+        checkpoints
+            .write(&LedgerState::new(10, vec![output(0, 1_000_000)]).unwrap())
+            .await
+            .unwrap();
+        let mut archive_readers = ArchiveReaders::default();
+        archive_readers
+            .0
+            .insert("permanode".to_string(), ArchiveReader::new(logs_dir));
+        let mut ledgers = Ledgers::default();
+        ledgers.0.insert(
+            "permanode".to_string(),
+            Ledger {
+                checkpoints: checkpoints.clone(),
+                max_replay_distance: 5,
+            },
+        );
+        let client = construct_ledger_client(archive_readers, ledgers).await;
+        let balance = |milestone: Option<u32>| {
+            let uri = format!(
+                "/api/permanode/ledger/balances/ed25519/{}{}",
+                address,
+                milestone
+                    .map(|milestone| format!("?milestone={}", milestone))
+                    .unwrap_or_default()
+            );
+            let client = &client;
+            async move {
+                let res = client.get(uri).dispatch().await;
+                let status = res.status();
+                let body: Value =
+                    serde_json::from_str(&res.into_string().await.expect("No body returned!")).unwrap_or_default();
+                (status, body)
+            }
+        };
+
+        let (status, body) = balance(None).await;
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body["data"]["ledgerIndex"], 10);
+        assert_eq!(body["data"]["balance"], 1_000_000);
+        assert_eq!(body["data"]["outputCount"], 1);
+        // the balance at the milestone of a checkpoint needs no archived milestone
+        let (status, body) = balance(Some(10)).await;
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body["data"]["ledgerIndex"], 10);
+
+        // the latest state is loaded again once a newer checkpoint is written
+        checkpoints
+            .write(&LedgerState::new(12, vec![output(0, 1_000_000), output(1, 500_000)]).unwrap())
+            .await
+            .unwrap();
+        let (status, body) = balance(None).await;
+        assert_eq!(status, Status::Ok);
+        assert_eq!(body["data"]["ledgerIndex"], 12);
+        assert_eq!(body["data"]["balance"], 1_500_000);
+        assert_eq!(body["data"]["outputCount"], 2);
+
+        // the milestones above the checkpoint must be archived or stored
+        let (status, _) = balance(Some(11)).await;
+        assert_ne!(status, Status::Ok);
+        // no checkpoint covers the milestone
+        let (status, _) = balance(Some(5)).await;
+        assert_eq!(status, Status::NotFound);
+        // the milestone is too far above the latest checkpoint
+        let (status, body) = balance(Some(30)).await;
+        assert_eq!(status, Status::BadRequest);
+        assert!(body["message"]
+            .as_str()
+            .unwrap_or_default()
+            .contains("over the limit of 5"));
+        // the address is hex encoded
+        let res = client
+            .get("/api/permanode/ledger/balances/ed25519/invalid")
+            .dispatch()
+            .await;
+        assert_eq!(res.status(), Status::BadRequest);
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
 }
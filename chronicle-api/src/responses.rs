@@ -9,6 +9,7 @@ use bee_message::{
         OutputId,
     },
     Message,
+    MessageId,
 };
 use bee_rest_api::types::dtos::{
     InputDto,
@@ -19,12 +20,14 @@ use bee_rest_api::types::dtos::{
 use chronicle_broker::AnalyticData;
 use chronicle_storage::access::{
     AddressRecord,
+    AnalyticRollup,
     IndexationRecord,
     InputData,
     LedgerInclusionState,
     MessageMetadata,
     ParentRecord,
     Partitioned,
+    RollupInterval,
     TransactionRes,
     UnlockRes,
 };
@@ -80,6 +83,15 @@ pub(crate) enum ListenerResponse {
         #[serde(skip_serializing_if = "Option::is_none")]
         #[serde(rename = "shouldReattach")]
         should_reattach: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "conflictReason")]
+        conflict_reason: Option<u8>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "whiteFlagIndex")]
+        white_flag_index: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(rename = "milestoneTimestampReferenced")]
+        milestone_timestamp_referenced: Option<u64>,
     },
     /// Response of GET /api/<keyspace>/messages/<message_id>/children
     MessageChildren {
@@ -178,6 +190,48 @@ pub(crate) enum ListenerResponse {
     },
     /// Response of GET /api/<keyspace>/analytics[?start=<u32>&end=<u32>]
     Analytics { ranges: Vec<AnalyticData> },
+    /// Response of GET /api/<keyspace>/analytics/timeseries[?interval=<hour|day>&from=<u64>&to=<u64>]
+    AnalyticsTimeseries {
+        interval: RollupInterval,
+        series: Vec<AnalyticRollup>,
+    },
+    /// Response of GET /api/<keyspace>/archive/milestones/<index>
+    ArchivedMilestone(serde_json::Value),
+    /// Response of GET /api/<keyspace>/ledger/balances/ed25519/<address>[?milestone=<u32>]
+    Balance {
+        // The type of the address (1=Ed25519).
+        #[serde(rename = "addressType")]
+        address_type: u8,
+        address: String,
+        #[serde(rename = "ledgerIndex")]
+        ledger_index: u32,
+        balance: u64,
+        #[serde(rename = "dustAllowance")]
+        dust_allowance: u64,
+        #[serde(rename = "outputCount")]
+        output_count: u64,
+    },
+    /// Response of GET /api/<keyspace>/messages/<message_id>/ancestors and
+    /// GET /api/<keyspace>/messages/<message_id>/descendants
+    MessageGraph {
+        #[serde(rename = "messageId")]
+        message_id: String,
+        nodes: Vec<GraphNode>,
+        edges: Vec<GraphEdge>,
+        // Whether the traversal stopped at the depth or node limit
+        truncated: bool,
+    },
+    /// Response of GET /api/<keyspace>/messages/<message_id>/path-to-milestone
+    MilestonePath {
+        #[serde(rename = "messageId")]
+        message_id: String,
+        #[serde(rename = "milestoneIndex")]
+        milestone_index: u32,
+        #[serde(rename = "milestoneMessageId")]
+        milestone_message_id: String,
+        nodes: Vec<GraphNode>,
+        edges: Vec<GraphEdge>,
+    },
 }
 
 impl TryFrom<Message> for ListenerResponse {
@@ -207,6 +261,9 @@ impl From<MessageMetadata> for ListenerResponse {
             ledger_inclusion_state: metadata.ledger_inclusion_state,
             should_promote: metadata.should_promote,
             should_reattach: metadata.should_reattach,
+            conflict_reason: metadata.conflict_reason,
+            white_flag_index: metadata.white_flag_index,
+            milestone_timestamp_referenced: metadata.milestone_timestamp_referenced,
         }
     }
 }
@@ -252,6 +309,56 @@ impl From<Partitioned<ParentRecord>> for Record {
     }
 }
 
+/// A message of a tangle traversal
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct GraphNode {
+    pub id: String,
+    #[serde(rename = "inclusionState")]
+    pub inclusion_state: Option<LedgerInclusionState>,
+    #[serde(rename = "milestoneIndex")]
+    pub milestone_index: Option<u32>,
+    /// The number of hops from the message the traversal started at
+    pub depth: u32,
+}
+
+impl GraphNode {
+    pub fn new(message_id: &MessageId, metadata: Option<&MessageMetadata>, depth: u32) -> Self {
+        GraphNode {
+            id: message_id.to_string(),
+            inclusion_state: metadata.and_then(|metadata| metadata.ledger_inclusion_state),
+            milestone_index: metadata.and_then(|metadata| metadata.referenced_by_milestone_index),
+            depth,
+        }
+    }
+}
+
+impl From<(Partitioned<ParentRecord>, u32)> for GraphNode {
+    fn from((record, depth): (Partitioned<ParentRecord>, u32)) -> Self {
+        GraphNode {
+            id: record.message_id.to_string(),
+            inclusion_state: record.ledger_inclusion_state,
+            milestone_index: Some(record.milestone_index()),
+            depth,
+        }
+    }
+}
+
+/// A reference from a message to one of its parents
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct GraphEdge {
+    pub child: String,
+    pub parent: String,
+}
+
+impl GraphEdge {
+    pub fn new(child: &MessageId, parent: &MessageId) -> Self {
+        GraphEdge {
+            child: child.to_string(),
+            parent: parent.to_string(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub(crate) struct Transaction {
     /// The created output's message id
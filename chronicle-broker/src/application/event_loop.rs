@@ -13,9 +13,49 @@ impl<H: ChronicleBrokerScope> EventLoop<H> for ChronicleBroker<H> {
             self.service.update_status(ServiceStatus::Running);
             while let Some(event) = self.inbox.recv().await {
                 match event {
+                    BrokerEvent::Importer(ImporterSession::Verified(report)) => {
+                        // the shard reports are merged and sent once the session is closed
+                        self.import_scheduler.verified(report);
+                    }
                     BrokerEvent::Importer(importer_session) => {
+                        let mut report = None;
+                        if let ImporterSession::ProgressBar {
+                            from_ms,
+                            to_ms,
+                            milestone_index,
+                            skipped,
+                            ..
+                        } = importer_session
+                        {
+                            self.import_scheduler.progress(from_ms..to_ms, milestone_index, skipped);
+                            report = self.import_scheduler.report(false);
+                        }
                         let socket_msg = BrokerSocketMsg::ChronicleBroker(importer_session);
                         self.response_to_sockets(&socket_msg).await;
+                        if let Some(report) = report {
+                            let socket_msg = BrokerSocketMsg::ChronicleBroker(report);
+                            self.response_to_sockets(&socket_msg).await;
+                        }
+                    }
+                    BrokerEvent::Exporter(exporter_session) => {
+                        let socket_msg = BrokerSocketMsg::ChronicleBroker(exporter_session);
+                        self.response_to_sockets(&socket_msg).await;
+                    }
+                    BrokerEvent::Resync(resync_session) => {
+                        let socket_msg = BrokerSocketMsg::ChronicleBroker(resync_session);
+                        self.response_to_sockets(&socket_msg).await;
+                    }
+                    BrokerEvent::Ledger(ledger_session) => {
+                        let socket_msg = BrokerSocketMsg::ChronicleBroker(ledger_session);
+                        self.response_to_sockets(&socket_msg).await;
+                    }
+                    BrokerEvent::Maintenance(report) => {
+                        let socket_msg = BrokerSocketMsg::ChronicleBroker(report);
+                        self.response_to_sockets(&socket_msg).await;
+                    }
+                    BrokerEvent::SyncReports(reports) => {
+                        let socket_msg = BrokerSocketMsg::ChronicleBroker(reports);
+                        self.response_to_sockets(&socket_msg).await;
                     }
                     BrokerEvent::Passthrough(passthrough_events) => {
                         match passthrough_events.try_get_my_event() {
@@ -25,74 +65,14 @@ impl<H: ChronicleBrokerScope> EventLoop<H> for ChronicleBroker<H> {
                                     // ensure to drop handle
                                     self.handle.take();
                                 }
-                                ChronicleBrokerThrough::Topology(mut topology) => {
-                                    if self.service.is_stopping() {
-                                        // response that should not change the topology while is_stopping
-                                        error!("Not supposed to dynamically change the topology while broker service is_stopped");
-                                        let socket_msg = BrokerSocketMsg::ChronicleBroker(Err(topology));
-                                        self.response_to_sockets::<Result<BrokerTopology, BrokerTopology>>(&socket_msg)
-                                            .await;
-                                        continue;
-                                    }
-                                    match topology {
-                                        BrokerTopology::AddMqttMessages(url) => {
-                                            if let Some(mqtt) = self.add_mqtt(Messages, MqttType::Messages, url) {
-                                                tokio::spawn(mqtt.start(self.handle.clone()));
-                                            }
-                                        }
-                                        BrokerTopology::AddMqttMessagesReferenced(url) => {
-                                            if let Some(mqtt) =
-                                                self.add_mqtt(MessagesReferenced, MqttType::MessagesReferenced, url)
-                                            {
-                                                tokio::spawn(mqtt.start(self.handle.clone()));
-                                            }
-                                        }
-                                        BrokerTopology::RemoveMqttMessagesReferenced(url) => {
-                                            self.remove_mqtt::<MessagesReferenced>(MqttType::MessagesReferenced, url)
-                                        }
-                                        BrokerTopology::RemoveMqttMessages(url) => {
-                                            self.remove_mqtt::<Messages>(MqttType::Messages, url)
-                                        }
-                                        BrokerTopology::Import { .. } => {
-                                            self.handle_import(topology).await;
-                                            self.try_close_importer_session().await;
-                                        }
-                                        BrokerTopology::Requesters(ref mut requester_topology) => {
-                                            match requester_topology {
-                                                RequesterTopology::AddEndpoint(ref url) => {
-                                                    let reqwest_client = reqwest::Client::new();
-                                                    if let Some(url) = BrokerConfig::adjust_api_endpoint(url.clone()) {
-                                                        if let Err(e) =
-                                                            BrokerConfig::verify_endpoint(&reqwest_client, &url).await
-                                                        {
-                                                            error!("{}", e);
-                                                            let socket_msg =
-                                                                BrokerSocketMsg::ChronicleBroker(Err(topology.clone()));
-                                                            self.response_to_sockets::<Result<BrokerTopology, BrokerTopology>>(&socket_msg).await;
-                                                        } else {
-                                                            *requester_topology =
-                                                                RequesterTopology::AddEndpoint(url.clone());
-                                                            self.collector_handles.values().for_each(|h| {
-                                                                h.send_requester_topology(requester_topology.clone());
-                                                            });
-                                                            let socket_msg =
-                                                                BrokerSocketMsg::ChronicleBroker(Ok(topology.clone()));
-                                                            self.response_to_sockets::<Result<BrokerTopology, BrokerTopology>>(&socket_msg).await;
-                                                        }
-                                                    } else {
-                                                        let socket_msg =
-                                                            BrokerSocketMsg::ChronicleBroker(Err(topology.clone()));
-                                                        self.response_to_sockets::<Result<BrokerTopology, BrokerTopology>>(&socket_msg).await;
-                                                    };
-                                                }
-                                                RequesterTopology::RemoveEndpoint(_) => {
-                                                    self.collector_handles.values().for_each(|h| {
-                                                        h.send_requester_topology(requester_topology.clone());
-                                                    });
-                                                }
-                                            }
-                                        }
-                                    }
+                                ChronicleBrokerThrough::Topology(topology) => {
+                                    self.handle_topology(None, topology).await;
+                                }
+                                ChronicleBrokerThrough::NetworkTopology { keyspace, topology } => {
+                                    self.handle_topology(Some(keyspace), topology).await;
+                                }
+                                ChronicleBrokerThrough::SyncReport => {
+                                    self.spawn_sync_reports();
                                 }
                                 ChronicleBrokerThrough::ExitProgram => {
                                     supervisor.exit_program(false);
@@ -127,10 +107,9 @@ impl<H: ChronicleBrokerScope> EventLoop<H> for ChronicleBroker<H> {
                                     self.service.delete_microservice(&service.get_name());
                                     // return parallelism
                                     self.parallelism_points += parallelism;
-                                    // check if there are any pending
-                                    if let Some(import_topology) = self.pending_imports.pop() {
-                                        self.handle_import(import_topology).await;
-                                    }
+                                    // import the pending shards, if any
+                                    self.import_scheduler.finished();
+                                    self.dispatch_imports().await;
                                     // remove its handle from importer_handles
                                     self.importer_handles.remove(&service.get_name());
                                     // check if we finished all in_progress_importers
@@ -139,6 +118,21 @@ impl<H: ChronicleBrokerScope> EventLoop<H> for ChronicleBroker<H> {
                                     self.service.update_microservice(service.get_name(), service.clone());
                                 }
                             }
+                            BrokerChild::Exporter(service, _status) => {
+                                if service.is_stopped() {
+                                    self.service.delete_microservice(&service.get_name());
+                                    self.exporter_handles.remove(&service.get_name());
+                                } else {
+                                    self.service.update_microservice(service.get_name(), service.clone());
+                                }
+                            }
+                            BrokerChild::ArchiveMaintainer(service, _status) => {
+                                if service.is_stopped() {
+                                    self.service.delete_microservice(&service.get_name());
+                                } else {
+                                    self.service.update_microservice(service.get_name(), service.clone());
+                                }
+                            }
                             BrokerChild::Solidifier(service, solidifier_status) => {
                                 // Handle abort
                                 if let Err(Need::Abort) = solidifier_status {
@@ -211,23 +205,30 @@ impl<H: ChronicleBrokerScope> EventLoop<H> for ChronicleBroker<H> {
                                         if !self.service.is_stopping() && service.is_stopped() && !asked_to_shutdown {
                                             // restart it by re-adding it, first we delete it
                                             self.service.delete_microservice(&microservice_name);
-                                            // extract the url and topic from the name (topic@url)
-                                            let mut name = microservice_name.split("@");
-                                            let topic = name.next().unwrap();
-                                            let url = Url::parse(name.next().unwrap()).unwrap();
+                                            // extract the network, topic and url from the name (network.topic@url)
+                                            let (scoped_topic, url) = microservice_name.split_once("@").unwrap();
+                                            let (network_name, topic) =
+                                                scoped_topic.rsplit_once(".").unwrap_or(("", scoped_topic));
+                                            let url = Url::parse(url).unwrap();
                                             let restart_after = std::time::Duration::from_secs(5);
                                             warn!("Restarting Mqtt: {}, after: {:?}", microservice_name, restart_after);
                                             match Topics::try_from(topic).unwrap() {
                                                 Topics::Messages => {
-                                                    let new_mqtt =
-                                                        self.add_mqtt(Messages, MqttType::Messages, url).unwrap();
+                                                    let new_mqtt = self
+                                                        .add_mqtt(network_name, Messages, MqttType::Messages, url)
+                                                        .unwrap();
                                                     tokio::spawn(
                                                         new_mqtt.start_after(restart_after, self.handle.clone()),
                                                     );
                                                 }
                                                 Topics::MessagesReferenced => {
                                                     let new_mqtt = self
-                                                        .add_mqtt(MessagesReferenced, MqttType::MessagesReferenced, url)
+                                                        .add_mqtt(
+                                                            network_name,
+                                                            MessagesReferenced,
+                                                            MqttType::MessagesReferenced,
+                                                            url,
+                                                        )
                                                         .unwrap();
                                                     tokio::spawn(
                                                         new_mqtt.start_after(restart_after, self.handle.clone()),
@@ -274,8 +275,103 @@ impl<H: ChronicleBrokerScope> EventLoop<H> for ChronicleBroker<H> {
 }
 
 impl<H: ChronicleBrokerScope> ChronicleBroker<H> {
-    pub(crate) fn remove_mqtt<T: Topic>(&mut self, mqtt_type: MqttType, url: Url) {
-        let microservice_name = format!("{}@{}", T::name(), url.as_str());
+    /// Alter the topology of the network which stores in the given keyspace, or of the first network if none is
+    /// provided
+    async fn handle_topology(&mut self, keyspace: Option<String>, mut topology: BrokerTopology) {
+        if self.service.is_stopping() {
+            // response that should not change the topology while is_stopping
+            error!("Not supposed to dynamically change the topology while broker service is_stopped");
+            let socket_msg = BrokerSocketMsg::ChronicleBroker(Err(topology));
+            self.response_to_sockets::<Result<BrokerTopology, BrokerTopology>>(&socket_msg)
+                .await;
+            return;
+        }
+        let network = match self.network_index(keyspace.as_deref()) {
+            Some(network) => network,
+            None => {
+                error!("No network stores in the keyspace: {:?}", keyspace);
+                let socket_msg = BrokerSocketMsg::ChronicleBroker(Err(topology));
+                self.response_to_sockets::<Result<BrokerTopology, BrokerTopology>>(&socket_msg)
+                    .await;
+                return;
+            }
+        };
+        let network_name = self.networks[network].name.clone();
+        match topology {
+            BrokerTopology::AddMqttMessages(url) => {
+                if let Some(mqtt) = self.add_mqtt(&network_name, Messages, MqttType::Messages, url) {
+                    tokio::spawn(mqtt.start(self.handle.clone()));
+                }
+            }
+            BrokerTopology::AddMqttMessagesReferenced(url) => {
+                if let Some(mqtt) = self.add_mqtt(&network_name, MessagesReferenced, MqttType::MessagesReferenced, url)
+                {
+                    tokio::spawn(mqtt.start(self.handle.clone()));
+                }
+            }
+            BrokerTopology::RemoveMqttMessagesReferenced(url) => {
+                self.remove_mqtt::<MessagesReferenced>(&network_name, MqttType::MessagesReferenced, url)
+            }
+            BrokerTopology::RemoveMqttMessages(url) => {
+                self.remove_mqtt::<Messages>(&network_name, MqttType::Messages, url)
+            }
+            BrokerTopology::Import { .. } => {
+                self.handle_import(network, topology).await;
+                self.try_close_importer_session().await;
+            }
+            BrokerTopology::Export { .. } => {
+                self.handle_export(network, topology).await;
+            }
+            BrokerTopology::Resync { .. } => {
+                self.handle_resync(network, topology).await;
+            }
+            BrokerTopology::ReplayLedger { .. } => {
+                self.handle_replay_ledger(network, topology).await;
+            }
+            BrokerTopology::Requesters(ref mut requester_topology) => match requester_topology {
+                RequesterTopology::AddEndpoint(ref url) => {
+                    let reqwest_client = reqwest::Client::new();
+                    if let Some(url) = BrokerConfig::adjust_api_endpoint(url.clone()) {
+                        if let Err(e) = BrokerConfig::verify_endpoint(&reqwest_client, &url).await {
+                            error!("{}", e);
+                            let socket_msg = BrokerSocketMsg::ChronicleBroker(Err(topology.clone()));
+                            self.response_to_sockets::<Result<BrokerTopology, BrokerTopology>>(&socket_msg)
+                                .await;
+                        } else {
+                            *requester_topology = RequesterTopology::AddEndpoint(url.clone());
+                            self.networks[network].collector_handles.values().for_each(|h| {
+                                h.send_requester_topology(requester_topology.clone());
+                            });
+                            let socket_msg = BrokerSocketMsg::ChronicleBroker(Ok(topology.clone()));
+                            self.response_to_sockets::<Result<BrokerTopology, BrokerTopology>>(&socket_msg)
+                                .await;
+                        }
+                    } else {
+                        let socket_msg = BrokerSocketMsg::ChronicleBroker(Err(topology.clone()));
+                        self.response_to_sockets::<Result<BrokerTopology, BrokerTopology>>(&socket_msg)
+                            .await;
+                    };
+                }
+                RequesterTopology::RemoveEndpoint(_) => {
+                    self.networks[network].collector_handles.values().for_each(|h| {
+                        h.send_requester_topology(requester_topology.clone());
+                    });
+                }
+            },
+        }
+    }
+    /// Get the index of the network which stores in the given keyspace, or of the first network if none is provided
+    pub(crate) fn network_index(&self, keyspace: Option<&str>) -> Option<usize> {
+        match keyspace {
+            Some(keyspace) => self
+                .networks
+                .iter()
+                .position(|network| network.keyspace.name() == keyspace),
+            None => (!self.networks.is_empty()).then(|| 0),
+        }
+    }
+    pub(crate) fn remove_mqtt<T: Topic>(&mut self, network: &str, mqtt_type: MqttType, url: Url) {
+        let microservice_name = format!("{}@{}", network_scoped_name(network, T::name()), url.as_str());
         if let Some(service) = self.service.microservices.get(&microservice_name) {
             // add it to asked_to_shutdown hashmap
             self.asked_to_shutdown.insert(microservice_name.clone(), ());
@@ -283,7 +379,11 @@ impl<H: ChronicleBrokerScope> ChronicleBroker<H> {
                 mqtt_handle.shutdown();
                 let config = get_config();
                 let mut new_config = config.clone();
-                if let Some(list) = new_config.broker_config.mqtt_brokers.get_mut(&mqtt_type) {
+                if let Some(list) = new_config
+                    .broker_config
+                    .mqtt_brokers_mut(network)
+                    .and_then(|mqtt_brokers| mqtt_brokers.get_mut(&mqtt_type))
+                {
                     list.remove(&url);
                 }
                 if new_config != config {
@@ -307,10 +407,27 @@ impl<H: ChronicleBrokerScope> ChronicleBroker<H> {
             // Maybe TODO response with something?;
         };
     }
-    pub(crate) fn add_mqtt<T: Topic>(&mut self, topic: T, mqtt_type: MqttType, url: Url) -> Option<Mqtt<T>> {
+    pub(crate) fn add_mqtt<T: Topic>(
+        &mut self,
+        network: &str,
+        topic: T,
+        mqtt_type: MqttType,
+        url: Url,
+    ) -> Option<Mqtt<T>> {
         let config = get_config();
+        let collector_handles = match self.networks.iter().find(|n| n.name == network) {
+            Some(network) => network.collector_handles.clone(),
+            None => {
+                error!(
+                    "The Mqtt: {}, you're trying to add belongs to an unknown network: {}",
+                    url, network
+                );
+                return None;
+            }
+        };
         let mqtt = MqttBuilder::new()
-            .collectors_handles(self.collector_handles.clone())
+            .collectors_handles(collector_handles)
+            .network(network.to_owned())
             .topic(topic)
             .url(url.clone())
             .stream_capacity(config.broker_config.mqtt_stream_capacity)
@@ -320,7 +437,11 @@ impl<H: ChronicleBrokerScope> ChronicleBroker<H> {
         if let None = self.service.microservices.get(&microservice_name) {
             self.service.update_microservice(microservice_name, microservice);
             let mut new_config = config.clone();
-            if let Some(list) = new_config.broker_config.mqtt_brokers.get_mut(&mqtt_type) {
+            if let Some(list) = new_config
+                .broker_config
+                .mqtt_brokers_mut(network)
+                .and_then(|mqtt_brokers| mqtt_brokers.get_mut(&mqtt_type))
+            {
                 list.insert(url);
             }
             if new_config != config {
@@ -337,68 +458,275 @@ impl<H: ChronicleBrokerScope> ChronicleBroker<H> {
             None
         }
     }
-    async fn handle_import(&mut self, import_topology: BrokerTopology) {
+    async fn handle_import(&mut self, network: usize, import_topology: BrokerTopology) {
         if let BrokerTopology::Import {
-            ref path,
+            path,
             resume,
-            ref import_range,
+            import_range,
             import_type,
+            from_sink,
+            mirror,
+            filter,
         } = import_topology
         {
             // don't do anything if the service is shutting down
             if self.service.is_stopping() {
                 return ();
             }
-            // check if we have enough parallelism points
-            if self.parallelism_points == 0 {
-                // add it to pending list
-                self.pending_imports.push(import_topology);
+            let import_type = match import_type {
+                ImportType::All => LogImportType::All,
+                ImportType::Analytics => LogImportType::Analytics,
+                ImportType::Verify => LogImportType::Verify,
+                ImportType::Snapshot => return self.handle_import_snapshots(network, path).await,
+            };
+            let mirror = match mirror {
+                Some(url) => match ArchiveMirror::connect(&url).await {
+                    Ok(mirror) => Some(mirror),
+                    Err(e) => {
+                        let event = ImporterSession::PathError {
+                            path,
+                            msg: e.to_string(),
+                        };
+                        let socket_msg = BrokerSocketMsg::ChronicleBroker(event);
+                        self.response_to_sockets(&socket_msg).await;
+                        return ();
+                    }
+                },
+                None => None,
+            };
+            let files = match self.import_files(network, &path, from_sink, mirror.as_ref()).await {
+                Ok(files) if !files.is_empty() => files,
+                Ok(_) => Err("No LogFiles in the provided path".to_owned()),
+                Err(msg) => Err(msg),
+            };
+            let files = match files {
+                Ok(files) => files,
+                Err(msg) => {
+                    let event = ImporterSession::PathError { path, msg };
+                    let socket_msg = BrokerSocketMsg::ChronicleBroker(event);
+                    self.response_to_sockets(&socket_msg).await;
+                    return ();
+                }
+            };
+            let import_range = import_range.unwrap_or(1..i32::MAX as u32);
+            // a dry-run verifies the imported milestones too
+            let resume = resume && import_type != LogImportType::Verify;
+            // resume the shards from the sync table, the importers skip the imported milestones within them too
+            let completed = if resume {
+                self.completed_ranges(network, &files, &import_range).await
+            } else {
+                Vec::new()
+            };
+            let shards = plan_shards(files, &import_range, self.import_shard_size, &completed);
+            if shards.is_empty() {
+                let event = ImporterSession::PathError {
+                    path,
+                    msg: "No milestones left to import in the provided range".into(),
+                };
+                let socket_msg = BrokerSocketMsg::ChronicleBroker(event);
+                self.response_to_sockets(&socket_msg).await;
                 return ();
             }
-            if path.is_file() {
-                // build importer
-                self.spawn_importer(
-                    path.clone(),
-                    resume,
-                    import_range.clone(),
-                    import_type,
-                    self.parallelism_points,
-                )
-                .await;
-            } else if path.is_dir() {
-                self.spawn_importers(path.clone(), resume, import_range.clone(), import_type)
-                    .await;
-            } else {
+            let job = ImportJob {
+                network,
+                resume,
+                import_type,
+                from_sink,
+                mirror,
+                filter,
+            };
+            self.import_scheduler.schedule(shards, job);
+            self.dispatch_imports().await;
+        }
+    }
+    /// Import the snapshot files of the path into the keyspace of the network, one after another in the background
+    async fn handle_import_snapshots(&mut self, network: usize, path: PathBuf) {
+        let files = match snapshot_files(&path).await {
+            Ok(files) => files,
+            Err(e) => {
                 let event = ImporterSession::PathError {
-                    path: path.clone(),
-                    msg: "Invalid path".into(),
+                    path,
+                    msg: e.to_string(),
                 };
                 let socket_msg = BrokerSocketMsg::ChronicleBroker(event);
                 self.response_to_sockets(&socket_msg).await;
+                return ();
+            }
+        };
+        let keyspace = self.networks[network].keyspace.clone();
+        let parallelism = self.parallelism as usize;
+        let handle = self.handle.clone();
+        tokio::spawn(async move {
+            for file_path in files {
+                let progress = |imported, total| {
+                    if let Some(handle) = handle.as_ref() {
+                        let event = ImporterSession::SnapshotProgress {
+                            path: file_path.clone(),
+                            imported,
+                            total,
+                        };
+                        handle.send(BrokerEvent::Importer(event)).ok();
+                    }
+                };
+                let event = match import_snapshot(keyspace.clone(), file_path.clone(), parallelism, progress).await {
+                    Ok(report) => {
+                        info!(
+                            "Imported the snapshot {} of milestone {}",
+                            file_path.to_string_lossy(),
+                            report.ledger_index
+                        );
+                        ImporterSession::SnapshotImported(report)
+                    }
+                    Err(e) => {
+                        error!("Unable to import the snapshot {}: {}", file_path.to_string_lossy(), e);
+                        ImporterSession::PathError {
+                            path: file_path,
+                            msg: format!("Unable to import the snapshot: {}", e),
+                        }
+                    }
+                };
+                let failed = matches!(event, ImporterSession::PathError { .. });
+                if let Some(handle) = handle.as_ref() {
+                    handle.send(BrokerEvent::Importer(event)).ok();
+                }
+                // the delta snapshots build on the previous ones
+                if failed {
+                    break;
+                }
+            }
+            if let Some(handle) = handle.as_ref() {
+                handle.send(BrokerEvent::Importer(ImporterSession::Close)).ok();
+            }
+        });
+    }
+    /// Collect the log files of the import path along with their milestone ranges,
+    /// the log files are listed from the archive mirror or the sink of the network if requested
+    async fn import_files(
+        &self,
+        network: usize,
+        path: &PathBuf,
+        from_sink: bool,
+        mirror: Option<&ArchiveMirror>,
+    ) -> Result<Vec<(PathBuf, Range<u32>)>, String> {
+        let is_log_file = |path: &PathBuf| path.extension().map_or(false, |ext| ext == "log");
+        let mut import_files = Vec::new();
+        if let Some(mirror) = mirror {
+            tokio::fs::create_dir_all(path).await.map_err(|e| e.to_string())?;
+            import_files.extend(mirror.files().into_iter().map(|(name, _)| path.join(name)));
+        } else if from_sink {
+            let sink = self.networks[network]
+                .archive_sink
+                .as_ref()
+                .ok_or_else(|| "No archive sink is configured".to_owned())?;
+            if is_log_file(path) {
+                import_files.push(path.clone());
+            } else {
+                tokio::fs::create_dir_all(path).await.map_err(|e| e.to_string())?;
+                let names = sink.list().await.map_err(|e| e.to_string())?;
+                import_files.extend(names.into_iter().map(|name| path.join(name)));
+            }
+        } else if path.is_file() {
+            import_files.push(path.clone());
+        } else if path.is_dir() {
+            if let Ok(mut dir_entry) = tokio::fs::read_dir(path).await {
+                while let Ok(Some(p)) = dir_entry.next_entry().await {
+                    let file_path = p.path();
+                    // only the log files are imported, not their sidecar indexes nor the manifest
+                    if file_path.is_file() && is_log_file(&file_path) {
+                        import_files.push(file_path);
+                    }
+                }
+            };
+        } else {
+            return Err("Invalid path".into());
+        }
+        Ok(import_files
+            .into_iter()
+            .filter_map(|file_path| {
+                let range = file_path
+                    .file_name()
+                    .and_then(|file_name| file_name.to_str())
+                    .and_then(log_file_range);
+                if range.is_none() {
+                    warn!("Skipping LogFile with invalid name: {}", file_path.to_string_lossy());
+                }
+                range.map(|range| (file_path, range))
+            })
+            .collect())
+    }
+    /// Fetch the completed ranges of the import from the sync table
+    async fn completed_ranges(
+        &self,
+        network: usize,
+        files: &Vec<(PathBuf, Range<u32>)>,
+        import_range: &Range<u32>,
+    ) -> Vec<Range<u32>> {
+        let keyspace = self.networks[network].keyspace.clone();
+        let sync_range = SyncRange {
+            from: files
+                .iter()
+                .map(|(_, range)| range.start)
+                .min()
+                .unwrap_or_default()
+                .max(import_range.start),
+            to: files
+                .iter()
+                .map(|(_, range)| range.end)
+                .max()
+                .unwrap_or_default()
+                .min(import_range.end),
+        };
+        if sync_range.from >= sync_range.to {
+            return Vec::new();
+        }
+        match SyncData::try_fetch(&keyspace, &sync_range, 10).await {
+            Ok(sync_data) => sync_data.completed,
+            Err(e) => {
+                warn!("Unable to fetch SyncData to plan the import shards, error: {}", e);
+                Vec::new()
             }
         }
     }
+    /// Spawn importers for the pending shards, as long as workers and parallelism points are available
+    async fn dispatch_imports(&mut self) {
+        while let Some((shard, job, parallelism)) =
+            self.import_scheduler.next(self.parallelism_points, self.parallelism)
+        {
+            self.spawn_importer(shard, job, parallelism).await;
+        }
+    }
     async fn try_close_importer_session(&mut self) {
-        if self.in_progress_importers == 0 {
+        if self.in_progress_importers == 0 && self.import_scheduler.is_idle() {
+            if let Some(report) = self.import_scheduler.report(true) {
+                let socket_msg = BrokerSocketMsg::ChronicleBroker(report);
+                self.response_to_sockets(&socket_msg).await;
+            }
+            if let Some(verify_report) = self.import_scheduler.take_verify_report() {
+                let socket_msg = BrokerSocketMsg::ChronicleBroker(verify_report);
+                self.response_to_sockets(&socket_msg).await;
+            }
+            self.import_scheduler.reset();
             let event = ImporterSession::Close;
             let socket_msg = BrokerSocketMsg::ChronicleBroker(event);
             self.response_to_sockets(&socket_msg).await;
         }
     }
-    fn build_and_start_importer<T: ImportMode>(
-        &mut self,
-        file_path: PathBuf,
-        resume: bool,
-        import_range: Option<Range<u32>>,
-        parallelism: u8,
-    ) {
-        let mut importer_builder = ImporterBuilder::<T>::new();
-        if let Some(import_range) = import_range {
-            importer_builder = importer_builder.import_range(import_range);
-        };
+    fn build_and_start_importer<T: ImportMode>(&mut self, shard: ImportShard, job: ImportJob, parallelism: u8) {
+        let network = &self.networks[job.network];
+        let mut importer_builder = ImporterBuilder::<T>::new().keyspace(network.keyspace.clone());
+        if let Some(sink) = network.archive_sink.clone().filter(|_| job.from_sink) {
+            importer_builder = importer_builder.sink(sink);
+        }
+        if let Some(mirror) = job.mirror {
+            importer_builder = importer_builder.mirror(mirror);
+        }
+        if let Some(filter) = job.filter {
+            importer_builder = importer_builder.filter(filter);
+        }
         let importer = importer_builder
-            .file_path(file_path)
-            .resume(resume)
+            .file_path(shard.path)
+            .import_range(shard.range)
+            .resume(job.resume)
             .parallelism(parallelism)
             .retries_per_query(50) // TODO get it from config
             .chronicle_id(0) // TODO get it from config
@@ -409,95 +737,257 @@ impl<H: ChronicleBrokerScope> ChronicleBroker<H> {
         self.service.update_microservice(importer.get_name(), service);
         tokio::spawn(importer.start(self.handle.clone()));
     }
-    async fn spawn_importer(
-        &mut self,
-        file_path: PathBuf,
-        resume: bool,
-        import_range: Option<Range<u32>>,
-        import_type: ImportType,
-        parallelism: u8,
-    ) {
+    async fn spawn_importer(&mut self, shard: ImportShard, job: ImportJob, parallelism: u8) {
         // don't do anything if the service is shutting down
-        if self.service.is_stopping() {
+        if self.service.is_stopping() || self.service.microservices.get(&shard.importer_name()).is_some() {
+            self.import_scheduler.finished();
             return ();
         }
-        if let Some(path_str) = file_path.to_str() {
-            if self.service.microservices.get(&path_str.to_owned()).is_some() {
-                return ();
-            }
-            match import_type {
-                ImportType::All => {
-                    self.build_and_start_importer::<All>(file_path, resume, import_range, parallelism);
+        if shard.path.to_str().is_some() {
+            match job.import_type {
+                LogImportType::All => {
+                    self.build_and_start_importer::<All>(shard, job, parallelism);
+                }
+                LogImportType::Analytics => {
+                    self.build_and_start_importer::<Analytics>(shard, job, parallelism);
                 }
-                ImportType::Analytics => {
-                    self.build_and_start_importer::<Analytics>(file_path, resume, import_range, parallelism);
+                LogImportType::Verify => {
+                    self.build_and_start_importer::<Verify>(shard, job, parallelism);
                 }
             }
             self.in_progress_importers += 1;
             self.parallelism_points -= parallelism;
         } else {
-            self.parallelism_points += parallelism;
+            self.import_scheduler.finished();
             let event = ImporterSession::PathError {
-                path: file_path,
+                path: shard.path,
                 msg: "Unable to convert path to string".into(),
             };
             let socket_msg = BrokerSocketMsg::ChronicleBroker(event);
             self.response_to_sockets(&socket_msg).await;
         }
     }
-    async fn spawn_importers(
-        &mut self,
-        path: PathBuf,
-        resume: bool,
-        import_range: Option<Range<u32>>,
-        import_type: ImportType,
-    ) {
-        let mut import_files = Vec::new();
-        if let Ok(mut dir_entry) = tokio::fs::read_dir(&path).await {
-            while let Ok(Some(p)) = dir_entry.next_entry().await {
-                let file_path = p.path();
-                if file_path.is_file() {
-                    import_files.push(file_path);
-                }
+    async fn handle_export(&mut self, network: usize, export_topology: BrokerTopology) {
+        if let BrokerTopology::Export { path, export_range } = export_topology {
+            // don't do anything if the service is shutting down
+            if self.service.is_stopping() {
+                return ();
             }
-        };
-        let import_files_len = import_files.len();
-        if import_files_len == 0 {
-            let event = ImporterSession::PathError {
-                path,
-                msg: "No LogFiles in the provided path".into(),
+            let name = exporter_name(&export_range);
+            let msg = if self.service.microservices.get(&name).is_some() {
+                Some("The milestone range is already being exported")
+            } else if export_range.is_empty() {
+                Some("Empty export range")
+            } else {
+                None
             };
-            let socket_msg = BrokerSocketMsg::ChronicleBroker(event);
-            self.response_to_sockets(&socket_msg).await;
-            return ();
+            if let Some(msg) = msg {
+                let event = ExporterSession::Finish {
+                    from_ms: export_range.start,
+                    to_ms: export_range.end,
+                    exported: 0,
+                    missing: MilestoneRanges::default(),
+                    msg: msg.into(),
+                };
+                let socket_msg = BrokerSocketMsg::ChronicleBroker(event);
+                self.response_to_sockets(&socket_msg).await;
+                return ();
+            }
+            let mut exporter_builder = ExporterBuilder::new();
+            if let Some(max_log_size) = self.networks[network].max_log_size {
+                exporter_builder = exporter_builder.max_log_size(max_log_size);
+            }
+            if let Some(compression_level) = self.networks[network].log_compression_level {
+                exporter_builder = exporter_builder.compression_level(compression_level);
+            }
+            let exporter = exporter_builder
+                .dir_path(path)
+                .export_range(export_range)
+                .keyspace(self.networks[network].keyspace.clone())
+                .parallelism(self.parallelism)
+                .retries_per_query(50) // TODO get it from config
+                .build();
+            let handle = exporter.clone_handle().expect("Expected existing exporter handle");
+            self.exporter_handles.insert(exporter.get_name(), handle);
+            let service = Service::new();
+            self.service.update_microservice(exporter.get_name(), service);
+            tokio::spawn(exporter.start(self.handle.clone()));
         }
-        if self.parallelism_points as usize > import_files_len {
-            let parallelism = (self.parallelism_points as usize / import_files_len) as u8;
-            for file_path in import_files {
-                self.spawn_importer(file_path, resume, import_range.clone(), import_type, parallelism)
-                    .await
+    }
+    async fn handle_resync(&mut self, network: usize, resync_topology: BrokerTopology) {
+        if let BrokerTopology::Resync { range, relog, purge } = resync_topology {
+            let network = &self.networks[network];
+            let mut msg = if range.is_empty() {
+                Some("Empty resync range".to_owned())
+            } else if range.start < network.sync_range.from || range.end > network.sync_range.to {
+                Some("The resync range is outside of the sync range".to_owned())
+            } else if network.syncer_handle.is_none() {
+                Some("The syncer is not running".to_owned())
+            } else {
+                None
+            };
+            // the relogged milestones would be written again into new log files
+            if let (None, true, Some(logs_dir)) = (msg.as_ref(), relog, network.logs_dir_path.as_ref()) {
+                match resync::archived_overlaps(logs_dir, &range).await {
+                    Ok(overlaps) if !overlaps.is_empty() => {
+                        msg.replace(format!(
+                            "The resync range overlaps the archived log files: {}, resync it without relogging",
+                            overlaps.join(", ")
+                        ));
+                    }
+                    Ok(_) => (),
+                    Err(e) => {
+                        msg.replace(format!("Unable to list the archived log files: {}", e));
+                    }
+                }
             }
-        } else {
-            // unwrap is safe
-            let file_path = import_files.pop().expect("Expected import file");
-            self.spawn_importer(
-                file_path,
-                resume,
-                import_range.clone(),
-                import_type,
-                self.parallelism_points,
-            )
-            .await;
-            // convert any remaining into pending_imports
-            for file_path in import_files {
-                let topology = BrokerTopology::Import {
-                    path: file_path,
-                    resume,
-                    import_range: import_range.clone(),
-                    import_type,
+            if let Some(msg) = msg {
+                let event = ResyncSession::Finish {
+                    from_ms: range.start,
+                    to_ms: range.end,
+                    resynced: 0,
+                    msg,
                 };
-                self.pending_imports.push(topology);
+                let socket_msg = BrokerSocketMsg::ChronicleBroker(event);
+                self.response_to_sockets(&socket_msg).await;
+                return ();
             }
+            let keyspace = network.keyspace.clone();
+            let syncer_handle = network.syncer_handle.clone().expect("Expected syncer handle");
+            let handle = self.handle.clone();
+            let indexer = crate::indexer::MessageIndexer::new(get_config().storage_config.partition_config);
+            let parallelism = self.parallelism as usize;
+            // the range is invalidated in the background, then the syncer solidifies it again
+            tokio::spawn(async move {
+                match resync::invalidate(&keyspace, &indexer, &range, purge, parallelism).await {
+                    Ok(()) => {
+                        info!("Invalidated the milestone range {:?}, purge: {}", range, purge);
+                        let _ = syncer_handle.send(SyncerEvent::Resync { range, relog }).await;
+                    }
+                    Err(e) => {
+                        error!("Unable to invalidate the milestone range {:?}: {}", range, e);
+                        if let Some(handle) = handle {
+                            let event = ResyncSession::Finish {
+                                from_ms: range.start,
+                                to_ms: range.end,
+                                resynced: 0,
+                                msg: format!("Unable to invalidate the range: {}", e),
+                            };
+                            handle.send(BrokerEvent::Resync(event)).ok();
+                        }
+                    }
+                }
+            });
+        }
+    }
+    async fn handle_replay_ledger(&mut self, network: usize, ledger_topology: BrokerTopology) {
+        if let BrokerTopology::ReplayLedger { to, snapshot } = ledger_topology {
+            let ledger_config = match get_config().broker_config.ledger {
+                Some(ledger_config) => ledger_config,
+                None => {
+                    let event = LedgerSession::Finish {
+                        ledger_index: None,
+                        msg: "The ledger replay is not configured".into(),
+                    };
+                    let socket_msg = BrokerSocketMsg::ChronicleBroker(event);
+                    self.response_to_sockets(&socket_msg).await;
+                    return ();
+                }
+            };
+            let network = &self.networks[network];
+            let keyspace = network.keyspace.clone();
+            let checkpoints = LedgerCheckpoints::of_keyspace(&ledger_config.checkpoints_dir, keyspace.name());
+            let reader = network.logs_dir_path.clone().map(ArchiveReader::new);
+            let parallelism = self.parallelism as usize;
+            let handle = self.handle.clone();
+            // the ledger is replayed in the background, the archived milestones are preferred over the database
+            tokio::spawn(async move {
+                let progress_handle = handle.clone();
+                let replay = async {
+                    let mut state = match snapshot {
+                        Some(snapshot) => {
+                            let state = Snapshot::from_file(&snapshot)?.ledger_state()?;
+                            checkpoints.write(&state).await?;
+                            state
+                        }
+                        None => checkpoints
+                            .load_latest(to.unwrap_or(u32::MAX))
+                            .await?
+                            .ok_or_else(|| anyhow!("There is no ledger checkpoint, please seed it with a snapshot"))?,
+                    };
+                    let fetch = |milestone_index| {
+                        let reader = reader.clone();
+                        let keyspace = keyspace.clone();
+                        async move {
+                            if let Some(reader) = reader {
+                                if let Some(milestone_data) = reader.get(milestone_index).await? {
+                                    return Ok(Some(milestone_data));
+                                }
+                            }
+                            reconstruct_milestone_data(keyspace, milestone_index, 50, parallelism).await
+                        }
+                    };
+                    let progress = |ledger_index| {
+                        if let Some(handle) = progress_handle.as_ref() {
+                            handle
+                                .send(BrokerEvent::Ledger(LedgerSession::Progress { ledger_index }))
+                                .ok();
+                        }
+                    };
+                    state
+                        .replay(
+                            to,
+                            fetch,
+                            Some((&checkpoints, ledger_config.checkpoint_interval)),
+                            progress,
+                        )
+                        .await?;
+                    // a failed replay keeps its periodic checkpoints only, as its state is partially applied
+                    checkpoints.write(&state).await?;
+                    Ok::<_, anyhow::Error>(state.ledger_index())
+                };
+                let event = match replay.await {
+                    Ok(ledger_index) => {
+                        info!("Replayed the ledger up to milestone {}", ledger_index);
+                        LedgerSession::Finish {
+                            ledger_index: Some(ledger_index),
+                            msg: "ok".into(),
+                        }
+                    }
+                    Err(e) => {
+                        error!("Unable to replay the ledger: {}", e);
+                        LedgerSession::Finish {
+                            ledger_index: None,
+                            msg: format!("Unable to replay the ledger: {}", e),
+                        }
+                    }
+                };
+                if let Some(handle) = handle {
+                    handle.send(BrokerEvent::Ledger(event)).ok();
+                }
+            });
+        }
+    }
+    /// Fetch the sync data of the networks without blocking the event loop, the reports are sent to the sockets
+    fn spawn_sync_reports(&self) {
+        if let Some(handle) = self.handle.clone() {
+            let networks: Vec<(ChronicleKeyspace, SyncRange)> = self
+                .networks
+                .iter()
+                .map(|network| (network.keyspace.clone(), network.sync_range))
+                .collect();
+            tokio::spawn(async move {
+                let mut reports = Vec::new();
+                for (keyspace, sync_range) in networks {
+                    match SyncData::try_fetch(&keyspace, &sync_range, 10).await {
+                        Ok(sync_data) => {
+                            reports.push(SyncStatus::of(keyspace.name()).report(keyspace.name(), &sync_data));
+                        }
+                        Err(e) => error!("Unable to fetch the sync data of keyspace {}: {}", keyspace.name(), e),
+                    }
+                }
+                handle.send(BrokerEvent::SyncReports(reports)).ok();
+            });
         }
     }
     pub(crate) async fn response_to_sockets<T: Serialize>(&mut self, msg: &BrokerSocketMsg<T>) {
@@ -527,29 +1017,41 @@ impl<H: ChronicleBrokerScope> ChronicleBroker<H> {
                 info!("Shutting down Mqtt: {}", mqtt_name);
                 mqtt_handle.shutdown();
             }
-            // shutdown collectors
-            for (collector_name, collector_handle) in self.collector_handles.drain() {
-                info!("Shutting down Collector: {}", collector_name);
-                collector_handle.shutdown();
-            }
-            // shutdown solidifiers
-            for (solidifier_name, solidifier_handle) in self.solidifier_handles.drain() {
-                info!("Shutting down Solidifier: {}", solidifier_name);
-                solidifier_handle.shutdown();
+            for network in self.networks.iter_mut() {
+                // shutdown collectors
+                for (collector_name, collector_handle) in network.collector_handles.drain() {
+                    info!("Shutting down Collector: {}", collector_name);
+                    collector_handle.shutdown();
+                }
+                // shutdown solidifiers
+                for (solidifier_name, solidifier_handle) in network.solidifier_handles.drain() {
+                    info!("Shutting down Solidifier: {}", solidifier_name);
+                    solidifier_handle.shutdown();
+                }
             }
             // Shutdown the websockets
             for (_, ws) in &mut self.websockets {
                 let _ = ws.close().await;
             }
-            // shutdown syncer
-            if let Some(syncer) = self.syncer_handle.take() {
-                syncer.shutdown();
+            // shutdown syncers
+            for network in self.networks.iter_mut() {
+                if let Some(syncer) = network.syncer_handle.take() {
+                    syncer.shutdown();
+                }
+                if let Some(maintainer) = network.maintainer_handle.take() {
+                    maintainer.shutdown();
+                }
             }
             // shutdown importers
             for (importer_name, importer_handle) in self.importer_handles.drain() {
                 info!("Shutting down importer: {}", importer_name);
                 importer_handle.shutdown();
             }
+            // shutdown exporters
+            for (exporter_name, exporter_handle) in self.exporter_handles.drain() {
+                info!("Shutting down exporter: {}", exporter_name);
+                exporter_handle.shutdown();
+            }
             // drop self handler
             if drop_handle {
                 self.handle.take();
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
+use chronicle_common::config::Config;
 
 #[async_trait]
 impl<H: ChronicleBrokerScope> Init<H> for ChronicleBroker<H> {
@@ -10,154 +11,242 @@ impl<H: ChronicleBrokerScope> Init<H> for ChronicleBroker<H> {
         if let Some(ref mut supervisor) = supervisor {
             let config = get_config_async().await;
             supervisor.status_change(self.service.clone());
-            // Query sync table
-            self.query_sync_table().await?;
-            info!("Current: {:#?}", self.sync_data);
-            // Get the gap_start
-            let gap_start = self.sync_data.gaps.first().unwrap().start;
-            // create syncer_builder
-            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-            let syncer_handle = SyncerHandle { tx };
-            let syncer_inbox = SyncerInbox { rx };
-            let (one, recv) = tokio::sync::oneshot::channel();
-            let mut syncer_builder = SyncerBuilder::new()
-                .sync_data(self.sync_data.clone())
-                .handle(syncer_handle.clone())
-                .first_ask(AskSyncer::FillGaps)
-                .oneshot(one)
-                .inbox(syncer_inbox);
-            let archiver_handle;
-            if let Some(dir_path) = self.logs_dir_path.as_ref() {
-                let max_log_size = config.broker_config.max_log_size.unwrap_or(MAX_LOG_SIZE);
-                // create archiver_builder
-                let mut archiver = ArchiverBuilder::new()
-                    .dir_path(dir_path.clone())
-                    .keyspace(self.default_keyspace.clone())
-                    .solidifiers_count(self.collector_count)
-                    .max_log_size(max_log_size)
-                    .oneshot(recv)
-                    .build();
-                archiver_handle = archiver.take_handle();
-                syncer_builder = syncer_builder
-                    .first_ask(AskSyncer::Complete)
-                    .archiver_handle(archiver_handle.clone().expect("Expected archiver handle"));
-                // start archiver
-                tokio::spawn(archiver.start(self.handle.clone()));
-            } else {
-                info!("Initializing Broker without Archiver");
-                archiver_handle = None;
-            }
-            let mut collector_builders: Vec<CollectorBuilder> = Vec::new();
-            let mut solidifier_builders: Vec<SolidifierBuilder> = Vec::new();
             let reqwest_client = reqwest::Client::builder()
                 .timeout(Duration::from_secs(config.broker_config.request_timeout_secs))
                 .build()
                 .expect("Expected reqwest client to build correctly");
-            for partition_id in 0..self.collector_count {
-                // create requesters senders
-                let mut requesters_senders = Vec::new();
-                let mut requesters_channels = Vec::new();
-                for _ in 0..config.broker_config.requester_count {
-                    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-                    requesters_senders.push(tx.clone());
-                    requesters_channels.push((tx, rx));
+            let default_keyspace = config
+                .storage_config
+                .keyspaces
+                .first()
+                .and_then(|keyspace| Some(keyspace.name.clone()))
+                .unwrap_or("permanode".to_owned());
+            // Spawn a separate actor tree for each network profile
+            for profile in config.broker_config.network_profiles(&default_keyspace) {
+                if let Some(network_index) = self.networks.iter().position(|n| n.name == profile.name) {
+                    self.init_network(network_index, profile, &config, &reqwest_client)
+                        .await?;
                 }
-                // create collector_builder
-                let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-                let collector_handle = CollectorHandle { tx, requesters_senders };
-                let collector_inbox = CollectorInbox { rx };
-                self.collector_handles.insert(partition_id, collector_handle.clone());
-                let collector_builder = CollectorBuilder::new()
-                    .collector_count(self.collector_count)
-                    .requester_count(config.broker_config.requester_count)
-                    .handle(collector_handle)
-                    .inbox(collector_inbox)
-                    .api_endpoints(config.broker_config.api_endpoints.iter().cloned().collect())
-                    .storage_config(config.storage_config.clone())
-                    .reqwest_client(reqwest_client.clone())
-                    .retries_per_query(config.broker_config.retries_per_query)
-                    .retries_per_endpoint(config.broker_config.retries_per_endpoint)
-                    .requesters_channels(requesters_channels)
-                    .partition_id(partition_id);
+            }
+            status
+        } else {
+            Err(Need::Abort)
+        }
+    }
+}
 
-                collector_builders.push(collector_builder);
-                // create solidifier_builder
-                let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
-                let solidifier_handle = SolidifierHandle { tx };
-                let solidifier_inbox = SolidifierInbox { rx };
-                self.solidifier_handles.insert(partition_id, solidifier_handle.clone());
-                let mut solidifier_builder = SolidifierBuilder::new()
-                    .collector_count(self.collector_count)
-                    .syncer_handle(syncer_handle.clone());
-                if let Some(archiver_handle) = archiver_handle.clone().take() {
-                    solidifier_builder = solidifier_builder.archiver_handle(archiver_handle);
-                }
-                solidifier_builder = solidifier_builder
-                    .gap_start(gap_start)
-                    .keyspace(self.default_keyspace.clone())
-                    .handle(solidifier_handle)
-                    .inbox(solidifier_inbox)
-                    .partition_id(partition_id);
-                solidifier_builders.push(solidifier_builder);
+impl<H: ChronicleBrokerScope> ChronicleBroker<H> {
+    async fn init_network(
+        &mut self,
+        network_index: usize,
+        profile: NetworkConfig,
+        config: &Config,
+        reqwest_client: &reqwest::Client,
+    ) -> Result<(), Need> {
+        let network = &mut self.networks[network_index];
+        let network_name = network.name.clone();
+        // Query sync table
+        network.query_sync_table().await?;
+        info!(
+            "Current {}: {:#?}",
+            network_scoped_name(&network_name, "SyncData"),
+            network.sync_data
+        );
+        // Get the gap_start
+        let gap_start = network.sync_data.gaps.first().unwrap().start;
+        let inbox_capacities = profile.inbox_capacities.unwrap_or_default();
+        let snapshot_dir = config.broker_config.snapshot_dir.as_ref().map(PathBuf::from);
+        // create syncer_builder
+        let (tx, rx) = bounded_channel(inbox_capacities.syncer, &network_scoped_name(&network_name, "Syncer"));
+        let syncer_handle = SyncerHandle { tx };
+        let syncer_inbox = SyncerInbox { rx };
+        let (one, recv) = tokio::sync::oneshot::channel();
+        let mut syncer_builder = SyncerBuilder::new()
+            .sync_data(network.sync_data.clone())
+            .keyspace(network.keyspace.clone())
+            .network(network_name.clone())
+            .handle(syncer_handle.clone())
+            .first_ask(AskSyncer::FillGaps)
+            .sync_strategy(profile.sync_strategy.unwrap_or_default())
+            .oneshot(one)
+            .inbox(syncer_inbox);
+        let archiver_handle;
+        if let Some(dir_path) = network.logs_dir_path.as_ref() {
+            let max_log_size = profile.max_log_size.unwrap_or(MAX_LOG_SIZE);
+            // create archiver_builder
+            let mut archiver_builder = ArchiverBuilder::new()
+                .dir_path(dir_path.clone())
+                .keyspace(network.keyspace.clone())
+                .network(network_name.clone())
+                .solidifiers_count(self.collector_count)
+                .max_log_size(max_log_size)
+                .inbox_capacity(inbox_capacities.archiver)
+                .oneshot(recv);
+            if let Some(compression_level) = profile.log_compression_level {
+                archiver_builder = archiver_builder.compression_level(compression_level);
             }
-            // store copy of syncer_handle in broker state in order to be able to shut it down
-            self.syncer_handle.replace(syncer_handle);
-            // Finalize and Spawn Syncer
-            let syncer = syncer_builder
-                .solidifier_handles(self.solidifier_handles.clone())
-                .sync_range(self.sync_range)
-                .parallelism(self.parallelism)
-                .update_sync_data_every(self.complete_gaps_interval)
-                .build();
-            tokio::spawn(syncer.start(self.handle.clone()));
-            // Spawn mqtt brokers
-            for broker_url in config
-                .broker_config
-                .mqtt_brokers
-                .get(&MqttType::Messages)
-                .iter()
-                .flat_map(|v| v.iter())
-                .cloned()
-            {
-                if let Some(mqtt) = self.add_mqtt(Messages, MqttType::Messages, broker_url) {
-                    tokio::spawn(mqtt.start(self.handle.clone()));
-                }
+            if let Some(sink) = network.archive_sink.clone() {
+                archiver_builder = archiver_builder.sink(sink);
             }
-            for broker_url in config
-                .broker_config
-                .mqtt_brokers
-                .get(&MqttType::MessagesReferenced)
-                .iter()
-                .flat_map(|v| v.iter())
-                .cloned()
-            {
-                if let Some(mqtt) = self.add_mqtt(MessagesReferenced, MqttType::MessagesReferenced, broker_url) {
-                    tokio::spawn(mqtt.start(self.handle.clone()));
+            if let Some(policies) = profile.archive_maintenance.clone() {
+                let mut maintainer_builder = ArchiveMaintainerBuilder::new()
+                    .logs_dir(dir_path.clone())
+                    .network(network_name.clone())
+                    .policies(policies)
+                    .max_log_size(max_log_size);
+                if let Some(compression_level) = profile.log_compression_level {
+                    maintainer_builder = maintainer_builder.compression_level(compression_level);
+                }
+                if let Some(sink) = network.archive_sink.clone() {
+                    maintainer_builder = maintainer_builder.sink(sink);
                 }
+                let maintainer = maintainer_builder.build();
+                network.maintainer_handle = maintainer.clone_handle();
+                tokio::spawn(maintainer.start(self.handle.clone()));
             }
-            // we finalize them
-            for collector_builder in collector_builders {
-                let collector = collector_builder
-                    .solidifier_handles(self.solidifier_handles.clone())
-                    .build();
-                tokio::spawn(collector.start(self.handle.clone()));
+            let mut archiver = archiver_builder.build();
+            archiver_handle = archiver.take_handle();
+            syncer_builder = syncer_builder
+                .first_ask(AskSyncer::Complete)
+                .archiver_handle(archiver_handle.clone().expect("Expected archiver handle"));
+            // start archiver
+            tokio::spawn(archiver.start(self.handle.clone()));
+        } else {
+            info!("Initializing Broker without Archiver");
+            archiver_handle = None;
+        }
+        let mut collector_builders: Vec<CollectorBuilder> = Vec::new();
+        let mut solidifier_builders: Vec<SolidifierBuilder> = Vec::new();
+        for partition_id in 0..self.collector_count {
+            // create requesters senders
+            let mut requesters_senders = Vec::new();
+            let mut requesters_channels = Vec::new();
+            for _ in 0..config.broker_config.requester_count {
+                let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+                requesters_senders.push(tx.clone());
+                requesters_channels.push((tx, rx));
             }
-            for solidifier_builder in solidifier_builders {
-                let solidifier = solidifier_builder
-                    .collector_handles(self.collector_handles.clone())
-                    .build();
-                tokio::spawn(solidifier.start(self.handle.clone()));
+            // create collector_builder
+            let (tx, rx) = bounded_channel(
+                inbox_capacities.collector,
+                &network_scoped_name(&network_name, &format!("Collector_{}", partition_id)),
+            );
+            let collector_handle = CollectorHandle { tx, requesters_senders };
+            let collector_inbox = CollectorInbox { rx };
+            network.collector_handles.insert(partition_id, collector_handle.clone());
+            let mut collector_builder = CollectorBuilder::new()
+                .collector_count(self.collector_count)
+                .requester_count(config.broker_config.requester_count)
+                .handle(collector_handle)
+                .inbox(collector_inbox)
+                .api_endpoints(
+                    profile
+                        .api_endpoints
+                        .iter()
+                        .chain(profile.chronicle_endpoints.iter())
+                        .cloned()
+                        .collect(),
+                )
+                .chronicle_endpoints(profile.chronicle_endpoints.clone())
+                .peer_cones(network.peer_cones.clone())
+                .storage_config(config.storage_config.clone())
+                .keyspace(network.keyspace.clone())
+                .network(network_name.clone())
+                .reqwest_client(reqwest_client.clone())
+                .retries_per_query(config.broker_config.retries_per_query)
+                .retries_per_endpoint(config.broker_config.retries_per_endpoint)
+                .requesters_channels(requesters_channels)
+                .partition_id(partition_id);
+            if let Some(snapshot_dir) = snapshot_dir.clone() {
+                collector_builder = collector_builder.snapshot_dir(snapshot_dir);
             }
-            status
-        } else {
-            Err(Need::Abort)
+
+            collector_builders.push(collector_builder);
+            // create solidifier_builder
+            let (tx, rx) = bounded_channel(
+                inbox_capacities.solidifier,
+                &network_scoped_name(&network_name, &format!("Solidifier_{}", partition_id)),
+            );
+            let solidifier_handle = SolidifierHandle { tx };
+            let solidifier_inbox = SolidifierInbox { rx };
+            network
+                .solidifier_handles
+                .insert(partition_id, solidifier_handle.clone());
+            let mut solidifier_builder = SolidifierBuilder::new()
+                .collector_count(self.collector_count)
+                .syncer_handle(syncer_handle.clone());
+            if let Some(archiver_handle) = archiver_handle.clone().take() {
+                solidifier_builder = solidifier_builder.archiver_handle(archiver_handle);
+            }
+            solidifier_builder = solidifier_builder
+                .gap_start(gap_start)
+                .keyspace(network.keyspace.clone())
+                .network(network_name.clone())
+                .handle(solidifier_handle)
+                .inbox(solidifier_inbox)
+                .partition_id(partition_id)
+                .validation(profile.validation.clone());
+            if let Some(snapshot_dir) = snapshot_dir.clone() {
+                solidifier_builder = solidifier_builder.snapshot_dir(snapshot_dir);
+            }
+            solidifier_builders.push(solidifier_builder);
         }
+        // store copy of syncer_handle in broker state in order to be able to shut it down
+        network.syncer_handle.replace(syncer_handle);
+        // Finalize and Spawn Syncer
+        let syncer = syncer_builder
+            .solidifier_handles(network.solidifier_handles.clone())
+            .sync_range(network.sync_range)
+            .parallelism(self.parallelism)
+            .update_sync_data_every(self.complete_gaps_interval)
+            .build();
+        tokio::spawn(syncer.start(self.handle.clone()));
+        let collector_handles = network.collector_handles.clone();
+        let solidifier_handles = network.solidifier_handles.clone();
+        // Spawn mqtt brokers
+        for broker_url in profile
+            .mqtt_brokers
+            .get(&MqttType::Messages)
+            .iter()
+            .flat_map(|v| v.iter())
+            .cloned()
+        {
+            if let Some(mqtt) = self.add_mqtt(&network_name, Messages, MqttType::Messages, broker_url) {
+                tokio::spawn(mqtt.start(self.handle.clone()));
+            }
+        }
+        for broker_url in profile
+            .mqtt_brokers
+            .get(&MqttType::MessagesReferenced)
+            .iter()
+            .flat_map(|v| v.iter())
+            .cloned()
+        {
+            if let Some(mqtt) = self.add_mqtt(
+                &network_name,
+                MessagesReferenced,
+                MqttType::MessagesReferenced,
+                broker_url,
+            ) {
+                tokio::spawn(mqtt.start(self.handle.clone()));
+            }
+        }
+        // we finalize them
+        for collector_builder in collector_builders {
+            let collector = collector_builder.solidifier_handles(solidifier_handles.clone()).build();
+            tokio::spawn(collector.start(self.handle.clone()));
+        }
+        for solidifier_builder in solidifier_builders {
+            let solidifier = solidifier_builder.collector_handles(collector_handles.clone()).build();
+            tokio::spawn(solidifier.start(self.handle.clone()));
+        }
+        Ok(())
     }
 }
 
-impl<H: ChronicleBrokerScope> ChronicleBroker<H> {
+impl Network {
     pub(crate) async fn query_sync_table(&mut self) -> Result<(), Need> {
-        self.sync_data = SyncData::try_fetch(&self.default_keyspace, &self.sync_range, 10)
+        self.sync_data = SyncData::try_fetch(&self.keyspace, &self.sync_range, 10)
             .await
             .map_err(|e| {
                 error!("{}", e);
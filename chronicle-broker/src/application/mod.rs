@@ -2,17 +2,37 @@
 // SPDX-License-Identifier: Apache-2.0
 use super::*;
 use crate::{
+    archive::{
+        manifest::log_file_range,
+        mirror::ArchiveMirror,
+        sink::ArchiveSink,
+        ArchiveReader,
+    },
     archiver::*,
     collector::*,
-    importer::*,
+    exporter::*,
+    importer::{
+        scheduler::*,
+        *,
+    },
+    ledger::{
+        snapshot::Snapshot,
+        LedgerCheckpoints,
+    },
     listener::*,
+    maintainer::*,
     mqtt::*,
+    peer::PeerCones,
+    resync,
     solidifier::*,
     syncer::*,
     websocket::*,
 };
 use async_trait::async_trait;
-use chronicle_common::config::BrokerConfig;
+use chronicle_common::config::{
+    BrokerConfig,
+    NetworkConfig,
+};
 use std::{
     ops::Range,
     str::FromStr,
@@ -60,22 +80,80 @@ pub struct ChronicleBroker<H: ChronicleBrokerScope> {
     listener_handle: Option<ListenerHandle>,
     mqtt_handles: HashMap<String, MqttHandle>,
     importer_handles: HashMap<String, ImporterHandle>,
+    exporter_handles: HashMap<String, ExporterHandle>,
     asked_to_shutdown: HashMap<String, ()>,
     parallelism: u8,
     complete_gaps_interval: Duration,
     parallelism_points: u8,
-    pending_imports: Vec<BrokerTopology>,
+    /// The scheduler of the import shards
+    import_scheduler: ImportScheduler,
+    /// The maximum number of milestones of an import shard
+    import_shard_size: Option<u32>,
     in_progress_importers: usize,
     collector_count: u8,
-    collector_handles: HashMap<u8, CollectorHandle>,
-    solidifier_handles: HashMap<u8, SolidifierHandle>,
-    logs_dir_path: Option<PathBuf>,
+    /// The supervised networks, the first one is the target of the topology changes
+    networks: Vec<Network>,
     handle: Option<BrokerHandle<H>>,
     inbox: BrokerInbox<H>,
-    default_keyspace: ChronicleKeyspace,
+}
+
+/// The actor tree of a network profile
+pub(crate) struct Network {
+    /// The network name, empty for the network described by the top level broker config
+    name: String,
+    keyspace: ChronicleKeyspace,
     sync_range: SyncRange,
     sync_data: SyncData,
+    logs_dir_path: Option<PathBuf>,
+    /// The maximum size of the log files exported from the network
+    max_log_size: Option<u64>,
+    /// The zstd compression level of the log files exported from the network
+    log_compression_level: Option<i32>,
+    /// The archive sink of the network log files, which the importers may fetch them from
+    archive_sink: Option<ArchiveSink>,
+    collector_handles: HashMap<u8, CollectorHandle>,
+    solidifier_handles: HashMap<u8, SolidifierHandle>,
     syncer_handle: Option<SyncerHandle>,
+    maintainer_handle: Option<ArchiveMaintainerHandle>,
+    /// The milestone cones fetched from the peer Chronicle endpoints of the network
+    peer_cones: PeerCones,
+}
+
+impl From<NetworkConfig> for Network {
+    fn from(network_config: NetworkConfig) -> Self {
+        let logs_dir_path = network_config
+            .logs_dir
+            .map(|logs_dir| PathBuf::from_str(&logs_dir).expect("Failed to parse configured logs path!"));
+        let keyspace = ChronicleKeyspace::new(network_config.keyspace);
+        Self {
+            name: network_config.name,
+            keyspace,
+            sync_range: network_config.sync_range.unwrap_or(SyncRange::default()),
+            sync_data: SyncData {
+                completed: Vec::new(),
+                synced_but_unlogged: Vec::new(),
+                gaps: Vec::new(),
+            },
+            logs_dir_path,
+            max_log_size: network_config.max_log_size,
+            log_compression_level: network_config.log_compression_level,
+            archive_sink: None,
+            collector_handles: HashMap::new(),
+            solidifier_handles: HashMap::new(),
+            syncer_handle: None,
+            maintainer_handle: None,
+            peer_cones: PeerCones::default(),
+        }
+    }
+}
+
+/// Scope an actor name by the network it belongs to, the unnamed network keeps the plain actor names
+pub fn network_scoped_name(network: &str, name: &str) -> String {
+    if network.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{}.{}", network, name)
+    }
 }
 
 /// SubEvent type, indicates the children
@@ -94,6 +172,10 @@ pub enum BrokerChild {
     Syncer(Service, Result<(), Need>),
     /// Used by Importer to keep Broker up to date with its service, u8 is parallelism
     Importer(Service, Result<(), Need>, u8),
+    /// Used by Exporter to keep Broker up to date with its service
+    Exporter(Service, Result<(), Need>),
+    /// Used by ArchiveMaintainer to keep Broker up to date with its service
+    ArchiveMaintainer(Service, Result<(), Need>),
     /// Used by Websocket to keep Broker up to date with its service
     Websocket(Service, Option<WsTx>),
 }
@@ -102,6 +184,16 @@ pub enum BrokerChild {
 pub enum BrokerEvent<T> {
     /// Importer Session
     Importer(ImporterSession),
+    /// Exporter Session
+    Exporter(ExporterSession),
+    /// Resync Session
+    Resync(ResyncSession),
+    /// Ledger replay Session
+    Ledger(LedgerSession),
+    /// Archive maintenance report
+    Maintenance(MaintenanceReport),
+    /// The requested sync reports of the networks
+    SyncReports(Vec<SyncReport>),
     /// It's the passthrough event, which the scylla application will receive from
     Passthrough(T),
     /// Used by broker children to push their service
@@ -126,30 +218,26 @@ impl<H: ChronicleBrokerScope> Builder for ChronicleBrokerBuilder<H> {
         let handle = Some(BrokerHandle { tx });
         let inbox = BrokerInbox { rx };
         let config = get_config();
-        let default_keyspace = ChronicleKeyspace::new(
-            config
-                .storage_config
-                .keyspaces
-                .first()
-                .and_then(|keyspace| Some(keyspace.name.clone()))
-                .unwrap_or("permanode".to_owned()),
-        );
-        let sync_range = config
+        let default_keyspace = config
+            .storage_config
+            .keyspaces
+            .first()
+            .and_then(|keyspace| Some(keyspace.name.clone()))
+            .unwrap_or("permanode".to_owned());
+        let networks: Vec<Network> = config
             .broker_config
-            .sync_range
-            .and_then(|range| Some(range))
-            .unwrap_or(SyncRange::default());
-        let sync_data = SyncData {
-            completed: Vec::new(),
-            synced_but_unlogged: Vec::new(),
-            gaps: Vec::new(),
-        };
-        let logs_dir_path;
-        if let Some(logs_dir) = config.broker_config.logs_dir {
-            logs_dir_path = Some(PathBuf::from_str(&logs_dir).expect("Failed to parse configured logs path!"));
-        } else {
-            logs_dir_path = None;
-        }
+            .network_profiles(&default_keyspace)
+            .into_iter()
+            .map(|network_config| {
+                let mut network = Network::from(network_config);
+                network.archive_sink = config
+                    .broker_config
+                    .archive_sink
+                    .as_ref()
+                    .map(|sink_config| ArchiveSink::new(sink_config, &network.name));
+                network
+            })
+            .collect();
         let parallelism = self.parallelism.unwrap_or(25);
         ChronicleBroker::<H> {
             service: Service::new(),
@@ -157,21 +245,19 @@ impl<H: ChronicleBrokerScope> Builder for ChronicleBrokerBuilder<H> {
             listener_handle: self.listener_handle,
             mqtt_handles: HashMap::new(),
             importer_handles: HashMap::new(),
+            exporter_handles: HashMap::new(),
             asked_to_shutdown: HashMap::new(),
             collector_count: self.collector_count.unwrap_or(10),
-            collector_handles: HashMap::new(),
-            solidifier_handles: HashMap::new(),
-            syncer_handle: None,
+            networks,
             parallelism,
             parallelism_points: parallelism,
-            pending_imports: Vec::new(),
+            import_scheduler: ImportScheduler::new(
+                config.broker_config.import_workers.unwrap_or(DEFAULT_IMPORT_WORKERS),
+            ),
+            import_shard_size: config.broker_config.import_shard_size,
             in_progress_importers: 0,
-            logs_dir_path,
             handle,
             inbox,
-            default_keyspace,
-            sync_range,
-            sync_data,
             complete_gaps_interval: Duration::from_secs(self.complete_gaps_interval_secs.unwrap()),
         }
         .set_name()
@@ -0,0 +1,333 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use serde::Serialize;
+use sha2::{
+    Digest,
+    Sha256,
+};
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        Arc,
+        Mutex as StdMutex,
+    },
+};
+use tokio::sync::Mutex;
+
+/// The file name of the manifest within a logs directory
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+/// The previous hash of the first manifest entry
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+lazy_static::lazy_static! {
+    /// The locks which serialise the updates of the manifests, by logs directory
+    static ref MANIFEST_LOCKS: StdMutex<HashMap<PathBuf, Arc<Mutex<()>>>> = Default::default();
+}
+/// The counter of the temporary files of the manifests, which keeps their names unique
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// The operation of a manifest entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestOp {
+    /// A finalized log file was recorded, which supersedes the log files of the same name or of an overlapping range
+    Add,
+    /// The recorded log file was stored in the archive sink
+    Store,
+    /// The recorded log file was removed from the logs directory
+    Remove,
+}
+
+impl Default for ManifestOp {
+    fn default() -> Self {
+        ManifestOp::Add
+    }
+}
+
+impl ManifestOp {
+    /// The name of the operation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ManifestOp::Add => "add",
+            ManifestOp::Store => "store",
+            ManifestOp::Remove => "remove",
+        }
+    }
+}
+
+/// An operation on a finalized log file, appended to the manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// The operation
+    #[serde(default)]
+    pub op: ManifestOp,
+    /// The file name of the log file
+    pub file_name: String,
+    /// The first milestone index of the log file
+    pub start: u32,
+    /// The milestone index after the last one of the log file
+    pub end: u32,
+    /// The hex encoded SHA-256 checksum of the log file
+    pub sha256: String,
+    /// The hash of the previous entry
+    pub prev_hash: String,
+    /// The hash of this entry, which covers the previous hash
+    pub hash: String,
+}
+
+impl ManifestEntry {
+    /// Compute the chained hash of an entry.
+    /// The add operation isn't hashed, so the entries recorded before the operations were introduced still link.
+    pub fn chain_hash(prev_hash: &str, op: ManifestOp, file_name: &str, start: u32, end: u32, sha256: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(prev_hash.as_bytes());
+        if op != ManifestOp::Add {
+            hasher.update(op.as_str().as_bytes());
+        }
+        hasher.update(file_name.as_bytes());
+        hasher.update(start.to_le_bytes());
+        hasher.update(end.to_le_bytes());
+        hasher.update(sha256.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    /// Check whether the entry hash matches its content and the given previous hash
+    pub fn is_linked_to(&self, prev_hash: &str) -> bool {
+        self.prev_hash == prev_hash
+            && self.hash == Self::chain_hash(prev_hash, self.op, &self.file_name, self.start, self.end, &self.sha256)
+    }
+}
+
+/// The tamper-evident manifest of the finalized log files of a logs directory.
+/// It's an append-only log of the operations on the log files, each entry being chained onto the previous one, so
+/// that a superseded, stored or removed log file is recorded by a new entry and the earlier links are never
+/// rewritten. Any deletion or replacement of an entry breaks the chain.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    /// Get the path of the manifest within the given logs directory
+    pub fn path(logs_dir: &Path) -> PathBuf {
+        logs_dir.join(MANIFEST_FILE_NAME)
+    }
+
+    /// Load the manifest of the given logs directory, which is empty if it doesn't exist yet
+    pub async fn load(logs_dir: &Path) -> anyhow::Result<Self> {
+        let path = Self::path(logs_dir);
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| anyhow!("Malformatted manifest {}: {}", path.to_string_lossy(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => bail!(e),
+        }
+    }
+
+    /// Write the manifest into the given logs directory, through a uniquely named temporary file.
+    /// Use `update` to modify the manifest of a logs directory which may be written concurrently.
+    pub async fn save(&self, logs_dir: &Path) -> anyhow::Result<()> {
+        let path = Self::path(logs_dir);
+        let tmp_path = PathBuf::from(format!(
+            "{}.{}.{}.tmp",
+            path.to_string_lossy(),
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut file = File::create(&tmp_path).await?;
+        file.write_all(&serde_json::to_vec_pretty(self)?).await?;
+        file.sync_all().await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+        Ok(())
+    }
+
+    /// Load the manifest of the given logs directory, apply the update and save it if the update returns true.
+    /// The updates of a manifest are serialised, so that the concurrent ones don't get lost.
+    pub async fn update(logs_dir: &Path, update: impl FnOnce(&mut Self) -> bool) -> anyhow::Result<()> {
+        let lock = {
+            let key = logs_dir.canonicalize().unwrap_or_else(|_| logs_dir.to_owned());
+            MANIFEST_LOCKS.lock().unwrap().entry(key).or_default().clone()
+        };
+        let _guard = lock.lock().await;
+        let mut manifest = Self::load(logs_dir).await?;
+        if update(&mut manifest) {
+            manifest.save(logs_dir).await?;
+        }
+        Ok(())
+    }
+
+    /// All the entries, in appending order
+    pub fn entries(&self) -> &[ManifestEntry] {
+        &self.entries
+    }
+
+    /// The add entries of the log files which are currently recorded, sorted by milestone range
+    pub fn files(&self) -> Vec<&ManifestEntry> {
+        self.replay().into_iter().map(|(file, _)| file).collect()
+    }
+
+    /// The hash of the last entry, which covers the whole chain
+    pub fn head(&self) -> &str {
+        self.entries.last().map_or(GENESIS_HASH, |entry| entry.hash.as_str())
+    }
+
+    /// Replay the entries into the recorded log files, sorted by milestone range, along with whether they're stored
+    /// in the archive sink
+    fn replay(&self) -> Vec<(&ManifestEntry, bool)> {
+        let mut files: Vec<(&ManifestEntry, bool)> = Vec::new();
+        for entry in self.entries.iter() {
+            match entry.op {
+                ManifestOp::Add => {
+                    // the same file is still stored in the archive sink
+                    let stored = files.iter().any(|(file, stored)| {
+                        *stored && file.file_name == entry.file_name && file.sha256 == entry.sha256
+                    });
+                    files.retain(|(file, _)| {
+                        file.file_name != entry.file_name && (file.end <= entry.start || entry.end <= file.start)
+                    });
+                    files.push((entry, stored));
+                }
+                ManifestOp::Store => {
+                    if let Some((_, stored)) = files
+                        .iter_mut()
+                        .find(|(file, _)| file.file_name == entry.file_name && file.sha256 == entry.sha256)
+                    {
+                        *stored = true;
+                    }
+                }
+                ManifestOp::Remove => files.retain(|(file, _)| file.file_name != entry.file_name),
+            }
+        }
+        files.sort_by_key(|(file, _)| file.start);
+        files
+    }
+
+    /// Append an entry, chained onto the head
+    fn append(&mut self, op: ManifestOp, file_name: String, range: Range<u32>, sha256: String) {
+        let prev_hash = self.head().to_owned();
+        let hash = ManifestEntry::chain_hash(&prev_hash, op, &file_name, range.start, range.end, &sha256);
+        self.entries.push(ManifestEntry {
+            op,
+            file_name,
+            start: range.start,
+            end: range.end,
+            sha256,
+            prev_hash,
+            hash,
+        });
+    }
+
+    /// Record a log file, which supersedes the log files of the same name or of an overlapping range.
+    /// Returns false if the same log file is recorded already.
+    pub fn insert(&mut self, file_name: String, range: Range<u32>, sha256: String) -> bool {
+        let recorded = self
+            .replay()
+            .into_iter()
+            .any(|(file, _)| file.file_name == file_name && (file.start..file.end) == range && file.sha256 == sha256);
+        if !recorded {
+            self.append(ManifestOp::Add, file_name, range, sha256);
+        }
+        !recorded
+    }
+
+    /// Record that a log file with the given checksum is stored in the archive sink, returning whether it's recorded
+    /// and wasn't marked as stored yet
+    pub fn set_stored(&mut self, file_name: &str, sha256: &str) -> bool {
+        let unstored = self
+            .replay()
+            .into_iter()
+            .find(|(file, stored)| !stored && file.file_name == file_name && file.sha256 == sha256)
+            .map(|(file, _)| file.start..file.end);
+        match unstored {
+            Some(range) => {
+                self.append(ManifestOp::Store, file_name.to_owned(), range, sha256.to_owned());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Check whether a log file is marked as stored in the archive sink
+    pub fn is_stored(&self, file_name: &str) -> bool {
+        self.replay()
+            .into_iter()
+            .any(|(file, stored)| file.file_name == file_name && stored)
+    }
+
+    /// Record that a log file was removed, returning whether it was recorded
+    pub fn remove(&mut self, file_name: &str) -> bool {
+        let removed = self
+            .replay()
+            .into_iter()
+            .find(|(file, _)| file.file_name == file_name)
+            .map(|(file, _)| (file.start..file.end, file.sha256.clone()));
+        match removed {
+            Some((range, sha256)) => {
+                self.append(ManifestOp::Remove, file_name.to_owned(), range, sha256);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Hash a finalized log file and record it in the manifest of its directory
+    pub async fn record(log_path: &Path) -> anyhow::Result<()> {
+        let (logs_dir, file_name) = split_log_path(log_path)?;
+        let range = log_file_range(&file_name)
+            .ok_or_else(|| anyhow!("Invalid log file name: {}", log_path.to_string_lossy()))?;
+        let sha256 = file_checksum(log_path).await?;
+        Self::update(logs_dir, |manifest| manifest.insert(file_name, range, sha256)).await
+    }
+
+    /// Mark a log file with the given checksum as stored in the archive sink, in the manifest of its directory
+    pub async fn mark_stored(log_path: &Path, sha256: &str) -> anyhow::Result<()> {
+        let (logs_dir, file_name) = split_log_path(log_path)?;
+        Self::update(logs_dir, |manifest| manifest.set_stored(&file_name, sha256)).await
+    }
+
+    /// Remove a log file from the manifest of its directory, if it's recorded
+    pub async fn forget(log_path: &Path) -> anyhow::Result<()> {
+        let (logs_dir, file_name) = split_log_path(log_path)?;
+        if !Self::path(logs_dir).exists() {
+            return Ok(());
+        }
+        Self::update(logs_dir, |manifest| manifest.remove(&file_name)).await
+    }
+}
+
+fn split_log_path(log_path: &Path) -> anyhow::Result<(&Path, String)> {
+    match (
+        log_path.parent(),
+        log_path.file_name().and_then(|file_name| file_name.to_str()),
+    ) {
+        (Some(logs_dir), Some(file_name)) => Ok((logs_dir, file_name.to_owned())),
+        _ => bail!("Invalid log file path: {}", log_path.to_string_lossy()),
+    }
+}
+
+/// Parse the milestone range of a finalized log file name, ie `XtoY.log` or `XtoY.log.fin`
+pub fn log_file_range(file_name: &str) -> Option<Range<u32>> {
+    let (start, end) = file_name.split('.').next()?.split_once("to")?;
+    Some(start.parse().ok()?..end.parse().ok()?)
+}
+
+/// Compute the hex encoded SHA-256 checksum of a file
+pub async fn file_checksum(path: &Path) -> anyhow::Result<String> {
+    let mut file = File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 1 << 16];
+    loop {
+        let n = file.read(&mut buffer).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
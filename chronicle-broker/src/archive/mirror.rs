@@ -0,0 +1,187 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{
+    manifest::{
+        file_checksum,
+        log_file_range,
+        Manifest,
+        ManifestEntry,
+        GENESIS_HASH,
+        MANIFEST_FILE_NAME,
+    },
+    FileLocks,
+};
+use anyhow::{
+    anyhow,
+    bail,
+    ensure,
+};
+use futures::StreamExt;
+use reqwest::{
+    header::{
+        CONTENT_RANGE,
+        RANGE,
+    },
+    Client,
+    StatusCode,
+};
+use std::{
+    collections::HashMap,
+    ops::Range,
+    path::{
+        Path,
+        PathBuf,
+    },
+    sync::Arc,
+};
+use tokio::{
+    fs::{
+        File,
+        OpenOptions,
+    },
+    io::AsyncWriteExt,
+};
+use url::Url;
+
+/// The number of attempts to download a log file, each of them resuming the previous one
+const DOWNLOAD_ATTEMPTS: usize = 5;
+
+/// An HTTP(S) archive mirror, which serves the finalized log files next to their manifest
+#[derive(Debug, Clone)]
+pub struct ArchiveMirror {
+    client: Client,
+    /// The url of the manifest
+    index_url: Url,
+    /// The manifest entries by log file name
+    entries: Arc<HashMap<String, ManifestEntry>>,
+    /// The download lock of each log file, as the shards of a log file are imported concurrently
+    downloads: FileLocks,
+}
+
+impl ArchiveMirror {
+    /// Fetch the manifest of a mirror and verify its hash chain.
+    /// The url is either the manifest itself or the directory which serves it.
+    pub async fn connect(url: &Url) -> anyhow::Result<Self> {
+        let index_url = if url.path().ends_with(".json") {
+            url.clone()
+        } else if url.path().ends_with('/') {
+            url.join(MANIFEST_FILE_NAME)?
+        } else {
+            Url::parse(&format!("{}/", url))?.join(MANIFEST_FILE_NAME)?
+        };
+        let client = Client::new();
+        let res = client.get(index_url.clone()).send().await?;
+        if !res.status().is_success() {
+            bail!("Unable to fetch the mirror manifest {}: {}", index_url, res.status());
+        }
+        let manifest: Manifest = serde_json::from_slice(&res.bytes().await?)
+            .map_err(|e| anyhow!("Malformatted mirror manifest {}: {}", index_url, e))?;
+        let mut prev_hash = GENESIS_HASH;
+        for entry in manifest.entries() {
+            ensure!(
+                entry.is_linked_to(prev_hash),
+                "Broken hash chain of the mirror manifest {} at {}",
+                index_url,
+                entry.file_name
+            );
+            // the log files are downloaded into a local directory, so their names are checked too
+            ensure!(
+                !entry.file_name.contains(|c| c == '/' || c == '\\')
+                    && log_file_range(&entry.file_name) == Some(entry.start..entry.end),
+                "Invalid log file name {} in the mirror manifest {}",
+                entry.file_name,
+                index_url
+            );
+            prev_hash = &entry.hash;
+        }
+        let entries = manifest
+            .files()
+            .into_iter()
+            .map(|entry| (entry.file_name.clone(), entry.clone()))
+            .collect();
+        Ok(Self {
+            client,
+            index_url,
+            entries: Arc::new(entries),
+            downloads: Default::default(),
+        })
+    }
+
+    /// List the log files of the mirror along with their milestone ranges, sorted by milestone range
+    pub fn files(&self) -> Vec<(String, Range<u32>)> {
+        let mut files = self
+            .entries
+            .values()
+            .map(|entry| (entry.file_name.clone(), entry.start..entry.end))
+            .collect::<Vec<_>>();
+        files.sort_by_key(|(_, range)| range.start);
+        files
+    }
+
+    /// Download a log file to the given path, unless it's already there with the expected checksum.
+    /// A previous partial download is resumed using range requests, and the checksum is verified before the
+    /// log file is moved to the given path.
+    pub async fn download(&self, name: &str, dest: &Path) -> anyhow::Result<()> {
+        let entry = self
+            .entries
+            .get(name)
+            .ok_or_else(|| anyhow!("No log file {} in the mirror manifest {}", name, self.index_url))?;
+        let _guard = self.downloads.lock(name).await?;
+        if dest.exists() && file_checksum(dest).await? == entry.sha256 {
+            return Ok(());
+        }
+        let part_path = PathBuf::from(format!("{}.part", dest.to_string_lossy()));
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match self.download_part(name, &part_path).await {
+                Ok(()) => break,
+                Err(e) if attempt < DOWNLOAD_ATTEMPTS => {
+                    log::warn!("Resuming the download of {}, error: {}", name, e);
+                }
+                Err(e) => bail!("Unable to download {}: {}", name, e),
+            }
+        }
+        if file_checksum(&part_path).await? != entry.sha256 {
+            // the next download starts over
+            tokio::fs::remove_file(&part_path).await.ok();
+            bail!("Checksum mismatch of the downloaded {}", name);
+        }
+        tokio::fs::rename(&part_path, dest).await?;
+        Ok(())
+    }
+
+    /// Download the rest of a log file into its part file
+    async fn download_part(&self, name: &str, part_path: &Path) -> anyhow::Result<()> {
+        let offset = tokio::fs::metadata(part_path)
+            .await
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        let mut request = self.client.get(self.index_url.join(name)?);
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={}-", offset));
+        }
+        let res = request.send().await?;
+        let resumed = res.status() == StatusCode::PARTIAL_CONTENT
+            && res
+                .headers()
+                .get(CONTENT_RANGE)
+                .and_then(|value| value.to_str().ok())
+                .map_or(false, |value| value.starts_with(&format!("bytes {}-", offset)));
+        let mut file = match res.status() {
+            // the part file already holds the whole log file
+            StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => return Ok(()),
+            StatusCode::PARTIAL_CONTENT if resumed => OpenOptions::new().append(true).open(part_path).await?,
+            // the mirror doesn't support range requests, so the download starts over
+            StatusCode::OK => File::create(part_path).await?,
+            status => bail!("Unexpected response status {}", status),
+        };
+        let mut stream = res.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            file.write_all(&chunk?).await?;
+        }
+        file.sync_all().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,995 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    CreatedBy,
+    MilestoneData,
+};
+use anyhow::{
+    anyhow,
+    bail,
+    ensure,
+};
+use bee_common::packable::Packable;
+use bee_message::{
+    prelude::MilestonePayload,
+    MessageId,
+};
+use chronicle_storage::access::FullMessage;
+use serde::{
+    de::IgnoredAny,
+    Deserialize,
+};
+use std::{
+    collections::{
+        HashMap,
+        VecDeque,
+    },
+    convert::TryInto,
+    fmt::Display,
+    io::{
+        Read,
+        Seek,
+        SeekFrom,
+    },
+    path::{
+        Path,
+        PathBuf,
+    },
+    str::FromStr,
+    sync::Arc,
+    time::SystemTime,
+};
+use tokio::{
+    fs::{
+        File,
+        OpenOptions,
+    },
+    io::{
+        AsyncBufRead,
+        AsyncBufReadExt,
+        AsyncRead,
+        AsyncReadExt,
+        AsyncSeek,
+        AsyncSeekExt,
+        AsyncWriteExt,
+        BufReader,
+    },
+    sync::{
+        Mutex,
+        OwnedMutexGuard,
+        RwLock,
+    },
+};
+
+/// The tamper-evident manifest of the finalized log files
+pub mod manifest;
+#[cfg(feature = "application")]
+/// The remote HTTP(S) mirrors which serve the finalized log files
+pub mod mirror;
+#[cfg(feature = "application")]
+/// The sinks which store the finalized log files
+pub mod sink;
+
+/// The magic bytes which start and end a zstd log file
+pub const ZSTD_LOG_MAGIC: [u8; 8] = *b"CHRNZLOG";
+/// The version of the zstd log file layout
+pub const ZSTD_LOG_VERSION: u8 = 1;
+/// The length of the zstd log file header, which is the magic followed by the version
+pub const ZSTD_LOG_HEADER_LEN: u64 = 9;
+/// The default zstd compression level
+pub const DEFAULT_COMPRESSION_LEVEL: i32 = 3;
+/// The number of milestones compressed together in a block by the merger and the converter
+pub const BLOCK_MILESTONES: u32 = 16;
+/// The magic bytes which start a sidecar index file
+pub const SIDECAR_INDEX_MAGIC: [u8; 8] = *b"CHRNLIDX";
+
+const BLOCK_TAG: u8 = 0;
+const INDEX_TAG: u8 = 1;
+/// The block frame header: tag, milestones count, first milestone index and compressed length
+const BLOCK_HEADER_LEN: u64 = 13;
+/// The footer trailer: index offset followed by the magic
+const TRAILER_LEN: u64 = 16;
+
+/// The formats of the archive log files.
+///
+/// A zstd log file is laid out as follows, with little endian integers:
+/// - header: the magic and the layout version;
+/// - blocks: a tag, the milestones count, the first milestone index and the compressed length, followed by the zstd
+///   compressed records, each one being a length prefixed packed milestone data;
+/// - footer: a tag and the number of index entries, followed by the (milestone index, block offset) entries, then the
+///   index offset and the magic.
+///
+/// The footer is written once the file is finished, so the blocks of an unfinished file can still be read in order.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// Newline delimited json milestone data
+    Json,
+    /// Zstd compressed blocks of packed milestone data, with a footer index
+    Zstd,
+}
+
+impl ArchiveFormat {
+    /// Detect the format of a log file from its leading bytes, leaving the file positioned at its start
+    pub fn detect<F: Read + Seek>(file: &mut F) -> std::io::Result<Self> {
+        let mut magic = Vec::with_capacity(ZSTD_LOG_MAGIC.len());
+        Read::by_ref(file)
+            .take(ZSTD_LOG_MAGIC.len() as u64)
+            .read_to_end(&mut magic)?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(Self::from_magic(&magic))
+    }
+
+    /// Detect the format of a log file from its leading bytes, leaving the file positioned at its start
+    pub async fn detect_async<F: AsyncRead + AsyncSeek + Unpin>(file: &mut F) -> std::io::Result<Self> {
+        let mut magic = Vec::with_capacity(ZSTD_LOG_MAGIC.len());
+        (&mut *file)
+            .take(ZSTD_LOG_MAGIC.len() as u64)
+            .read_to_end(&mut magic)
+            .await?;
+        file.seek(SeekFrom::Start(0)).await?;
+        Ok(Self::from_magic(&magic))
+    }
+
+    fn from_magic(magic: &[u8]) -> Self {
+        if magic == &ZSTD_LOG_MAGIC[..] {
+            ArchiveFormat::Zstd
+        } else {
+            ArchiveFormat::Json
+        }
+    }
+
+    /// The format of the log files written by the archiver for the configured compression level
+    pub fn from_compression_level(compression_level: Option<i32>) -> Self {
+        if compression_level.is_some() {
+            ArchiveFormat::Zstd
+        } else {
+            ArchiveFormat::Json
+        }
+    }
+}
+
+impl Display for ArchiveFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveFormat::Json => write!(f, "Json"),
+            ArchiveFormat::Zstd => write!(f, "Zstd"),
+        }
+    }
+}
+
+impl FromStr for ArchiveFormat {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Json" => Ok(ArchiveFormat::Json),
+            "Zstd" => Ok(ArchiveFormat::Zstd),
+            _ => bail!("Invalid archive format: {}", s),
+        }
+    }
+}
+
+impl Packable for MilestoneData {
+    type Error = anyhow::Error;
+    fn packed_len(&self) -> usize {
+        self.milestone_index.packed_len()
+            + 0u8.packed_len()
+            + 0u8.packed_len()
+            + self.milestone.as_ref().map(|m| m.packed_len()).unwrap_or_default()
+            + 0u32.packed_len()
+            + self.messages.values().map(Packable::packed_len).sum::<usize>()
+            + 0u32.packed_len()
+            + self.pending.keys().map(Packable::packed_len).sum::<usize>()
+    }
+    fn pack<W: std::io::Write>(&self, writer: &mut W) -> Result<(), Self::Error> {
+        self.milestone_index.pack(writer)?;
+        u8::from(self.created_by).pack(writer)?;
+        if let Some(milestone) = self.milestone.as_ref() {
+            1u8.pack(writer)?;
+            milestone.pack(writer)?;
+        } else {
+            0u8.pack(writer)?;
+        }
+        (self.messages.len() as u32).pack(writer)?;
+        for full_message in self.messages.values() {
+            full_message.pack(writer)?;
+        }
+        (self.pending.len() as u32).pack(writer)?;
+        for message_id in self.pending.keys() {
+            message_id.pack(writer)?;
+        }
+        Ok(())
+    }
+    fn unpack_inner<R: std::io::Read + ?Sized, const CHECK: bool>(reader: &mut R) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        let milestone_index = u32::unpack(reader)?;
+        let created_by = match u8::unpack(reader)? {
+            0 => CreatedBy::Incoming,
+            1 => CreatedBy::Expected,
+            2 => CreatedBy::Syncer,
+            3 => CreatedBy::Exporter,
+            v => bail!("Tried to unpack an invalid created by variant: {}", v),
+        };
+        let milestone = match u8::unpack(reader)? {
+            0 => None,
+            1 => Some(Box::new(MilestonePayload::unpack(reader)?)),
+            v => bail!("Tried to unpack an invalid milestone option: {}", v),
+        };
+        let messages_len = u32::unpack(reader)?;
+        let mut messages = HashMap::new();
+        for _ in 0..messages_len {
+            let full_message = FullMessage::unpack(reader)?;
+            messages.insert(*full_message.message_id(), full_message);
+        }
+        let pending_len = u32::unpack(reader)?;
+        let mut pending = HashMap::new();
+        for _ in 0..pending_len {
+            pending.insert(MessageId::unpack(reader)?, ());
+        }
+        Ok(Self {
+            milestone_index,
+            milestone,
+            messages,
+            pending,
+            created_by,
+            known_addresses: None,
+        })
+    }
+}
+
+/// Only the milestone index of a json record is deserialized, the rest is checked to be present
+#[derive(Debug, Deserialize)]
+struct LightMilestoneData {
+    milestone_index: u32,
+    #[allow(unused)]
+    milestone: IgnoredAny,
+    #[allow(unused)]
+    messages: IgnoredAny,
+    #[allow(unused)]
+    pending: IgnoredAny,
+    #[allow(unused)]
+    created_by: IgnoredAny,
+}
+
+/// A milestone data record of a log file
+#[derive(Debug, Clone)]
+pub enum ArchiveRecord {
+    /// A json line, including its newline
+    Json(String),
+    /// A packed milestone data
+    Packed(Vec<u8>),
+}
+
+impl ArchiveRecord {
+    /// Create a record of the milestone data in the given format
+    pub fn new(milestone_data: &MilestoneData, format: ArchiveFormat) -> anyhow::Result<Self> {
+        Ok(match format {
+            ArchiveFormat::Json => {
+                let mut line = serde_json::to_string(milestone_data)?;
+                line.push('\n');
+                ArchiveRecord::Json(line)
+            }
+            ArchiveFormat::Zstd => ArchiveRecord::Packed(milestone_data.pack_new()),
+        })
+    }
+
+    /// Get the milestone index of the record, without decoding the whole milestone data
+    pub fn milestone_index(&self) -> anyhow::Result<u32> {
+        match self {
+            ArchiveRecord::Json(line) => Ok(serde_json::from_str::<LightMilestoneData>(line)?.milestone_index),
+            ArchiveRecord::Packed(bytes) => packed_milestone_index(bytes),
+        }
+    }
+
+    /// Decode the milestone data
+    pub fn milestone_data(&self) -> anyhow::Result<MilestoneData> {
+        match self {
+            ArchiveRecord::Json(line) => Ok(serde_json::from_str(line)?),
+            ArchiveRecord::Packed(bytes) => MilestoneData::unpack(&mut bytes.as_slice()),
+        }
+    }
+
+    /// Get the uncompressed bytes length of the record
+    pub fn encoded_len(&self) -> usize {
+        match self {
+            ArchiveRecord::Json(line) => line.len(),
+            ArchiveRecord::Packed(bytes) => bytes.len(),
+        }
+    }
+}
+
+fn packed_milestone_index(bytes: &[u8]) -> anyhow::Result<u32> {
+    ensure!(
+        bytes.len() >= 4,
+        "Packed milestone data is too short: {} bytes",
+        bytes.len()
+    );
+    Ok(u32::from_le_bytes(bytes[..4].try_into()?))
+}
+
+/// Split the decompressed bytes of a zstd log block into its packed records
+fn block_records(mut bytes: &[u8]) -> anyhow::Result<Vec<&[u8]>> {
+    let mut records = Vec::new();
+    while !bytes.is_empty() {
+        ensure!(bytes.len() >= 4, "Truncated zstd log record length");
+        let (len, rest) = bytes.split_at(4);
+        let len = u32::from_le_bytes(len.try_into()?) as usize;
+        ensure!(rest.len() >= len, "Truncated zstd log record");
+        let (record, rest) = rest.split_at(len);
+        records.push(record);
+        bytes = rest;
+    }
+    Ok(records)
+}
+
+fn invalid_data<E: Display>(error: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, error.to_string())
+}
+
+/// Buffers packed milestone data records and compresses them into a block frame
+#[derive(Debug)]
+struct BlockEncoder {
+    compression_level: i32,
+    milestone_indexes: Vec<u32>,
+    buffer: Vec<u8>,
+}
+
+impl BlockEncoder {
+    fn new(compression_level: i32) -> Self {
+        Self {
+            compression_level,
+            milestone_indexes: Vec::new(),
+            buffer: Vec::new(),
+        }
+    }
+    fn push(&mut self, packed: &[u8]) -> anyhow::Result<()> {
+        self.milestone_indexes.push(packed_milestone_index(packed)?);
+        self.buffer.extend(&(packed.len() as u32).to_le_bytes());
+        self.buffer.extend(packed);
+        Ok(())
+    }
+    fn milestones(&self) -> u32 {
+        self.milestone_indexes.len() as u32
+    }
+    fn buffered_len(&self) -> usize {
+        self.buffer.len()
+    }
+    /// Compress the buffered records into a block frame, returning the milestone indexes it contains
+    fn finish(&mut self) -> anyhow::Result<(Vec<u32>, Vec<u8>)> {
+        let compressed = zstd::bulk::compress(&self.buffer, self.compression_level)?;
+        let milestone_indexes = std::mem::take(&mut self.milestone_indexes);
+        let mut frame = Vec::with_capacity(BLOCK_HEADER_LEN as usize + compressed.len());
+        frame.push(BLOCK_TAG);
+        frame.extend(&(milestone_indexes.len() as u32).to_le_bytes());
+        frame.extend(&milestone_indexes.first().copied().unwrap_or_default().to_le_bytes());
+        frame.extend(&(compressed.len() as u32).to_le_bytes());
+        frame.extend(compressed);
+        self.buffer.clear();
+        Ok((milestone_indexes, frame))
+    }
+}
+
+/// The index of a log file, which maps each milestone index to the offset of its json line or zstd block.
+///
+/// The index is embedded as the footer of a zstd log file, and is also written next to any finished log file as a
+/// sidecar `<log file>.idx`, which holds the magic, the length of the indexed log file and the entries count,
+/// followed by the (milestone index, offset) entries.
+#[derive(Debug, Default, Clone)]
+pub struct BlockIndex {
+    entries: Vec<(u32, u64)>,
+}
+
+impl BlockIndex {
+    fn push(&mut self, milestone_indexes: &[u32], block_offset: u64) {
+        self.entries.extend(
+            milestone_indexes
+                .iter()
+                .map(|milestone_index| (*milestone_index, block_offset)),
+        );
+    }
+
+    /// Get the offset of the block which contains the given milestone index
+    pub fn block_offset(&self, milestone_index: u32) -> Option<u64> {
+        self.entries
+            .iter()
+            .find(|(index, _)| *index == milestone_index)
+            .map(|(_, offset)| *offset)
+    }
+
+    /// Get the (milestone index, block offset) entries
+    pub fn entries(&self) -> &Vec<(u32, u64)> {
+        &self.entries
+    }
+
+    fn encode(&self, index_offset: u64) -> Vec<u8> {
+        let mut footer = Vec::with_capacity(5 + self.entries.len() * 12 + TRAILER_LEN as usize);
+        footer.push(INDEX_TAG);
+        footer.extend(&(self.entries.len() as u32).to_le_bytes());
+        for (milestone_index, block_offset) in self.entries.iter() {
+            footer.extend(&milestone_index.to_le_bytes());
+            footer.extend(&block_offset.to_le_bytes());
+        }
+        footer.extend(&index_offset.to_le_bytes());
+        footer.extend(&ZSTD_LOG_MAGIC);
+        footer
+    }
+
+    /// Get the path of the sidecar index of a log file
+    pub fn sidecar_path(log_path: &Path) -> PathBuf {
+        PathBuf::from(format!("{}.idx", log_path.to_string_lossy()))
+    }
+
+    /// Write the sidecar index of a log file with the given length
+    pub async fn write_sidecar(&self, log_path: &Path, log_len: u64) -> anyhow::Result<()> {
+        let mut bytes = Vec::with_capacity(20 + self.entries.len() * 12);
+        bytes.extend(&SIDECAR_INDEX_MAGIC);
+        bytes.extend(&log_len.to_le_bytes());
+        bytes.extend(&(self.entries.len() as u32).to_le_bytes());
+        for (milestone_index, offset) in self.entries.iter() {
+            bytes.extend(&milestone_index.to_le_bytes());
+            bytes.extend(&offset.to_le_bytes());
+        }
+        let path = Self::sidecar_path(log_path);
+        tokio::fs::write(&path, bytes)
+            .await
+            .map_err(|e| anyhow!("Unable to write sidecar index {}: {}", path.to_string_lossy(), e))
+    }
+
+    /// Read the sidecar index of a log file with the given length.
+    /// Returns none if there is no sidecar index, or if it was written for another version of the log file.
+    pub async fn read_sidecar(log_path: &Path, log_len: u64) -> anyhow::Result<Option<Self>> {
+        let bytes = match tokio::fs::read(Self::sidecar_path(log_path)).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => bail!(e),
+        };
+        if bytes.len() < 20 || bytes[..8] != SIDECAR_INDEX_MAGIC {
+            bail!("Invalid sidecar index of {}", log_path.to_string_lossy());
+        }
+        if u64::from_le_bytes(bytes[8..16].try_into()?) != log_len {
+            return Ok(None);
+        }
+        let entries_len = u32::from_le_bytes(bytes[16..20].try_into()?) as usize;
+        ensure!(
+            bytes.len() == 20 + entries_len * 12,
+            "Truncated sidecar index of {}",
+            log_path.to_string_lossy()
+        );
+        let entries = bytes[20..]
+            .chunks_exact(12)
+            .map(|entry| {
+                (
+                    u32::from_le_bytes(entry[..4].try_into().unwrap()),
+                    u64::from_le_bytes(entry[4..].try_into().unwrap()),
+                )
+            })
+            .collect();
+        Ok(Some(Self { entries }))
+    }
+
+    /// Remove the sidecar index of a log file, if any
+    pub async fn remove_sidecar(log_path: &Path) -> std::io::Result<()> {
+        match tokio::fs::remove_file(Self::sidecar_path(log_path)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Load the index of a log file from its sidecar index if it's up to date,
+    /// or from the file itself otherwise, which leaves the file at an unspecified position
+    pub async fn open<F: AsyncRead + AsyncSeek + Unpin>(
+        log_path: &Path,
+        file: &mut F,
+        format: ArchiveFormat,
+    ) -> anyhow::Result<Self> {
+        let len = file.seek(SeekFrom::End(0)).await?;
+        if let Some(index) = Self::read_sidecar(log_path, len).await? {
+            return Ok(index);
+        }
+        Ok(Self::scan(file, format).await?.0)
+    }
+
+    /// Build the index of a log file by reading it, along with the offset where its records end.
+    /// A zstd log file is indexed by its footer if it has one.
+    pub async fn scan<F: AsyncRead + AsyncSeek + Unpin>(
+        file: &mut F,
+        format: ArchiveFormat,
+    ) -> anyhow::Result<(Self, u64)> {
+        match format {
+            ArchiveFormat::Zstd => Self::load(file).await,
+            ArchiveFormat::Json => {
+                file.seek(SeekFrom::Start(0)).await?;
+                let mut reader = BufReader::new(file);
+                let mut index = Self::default();
+                let mut offset = 0;
+                let mut line = String::new();
+                loop {
+                    line.clear();
+                    let bytes = reader.read_line(&mut line).await? as u64;
+                    // a partially written trailing line is left out
+                    if bytes == 0 || !line.ends_with('\n') {
+                        break;
+                    }
+                    let milestone_index = serde_json::from_str::<LightMilestoneData>(&line)?.milestone_index;
+                    index.push(&[milestone_index], offset);
+                    offset += bytes;
+                }
+                Ok((index, offset))
+            }
+        }
+    }
+
+    /// Load the index of a zstd log file, along with the offset where its blocks end.
+    /// If the file has no footer, as it was not finished, the index is rebuilt by decoding the records of its blocks
+    /// and a partially written trailing block is left out.
+    pub async fn load<F: AsyncRead + AsyncSeek + Unpin>(file: &mut F) -> anyhow::Result<(Self, u64)> {
+        let len = file.seek(SeekFrom::End(0)).await?;
+        if len >= ZSTD_LOG_HEADER_LEN + TRAILER_LEN {
+            let mut trailer = [0; TRAILER_LEN as usize];
+            file.seek(SeekFrom::Start(len - TRAILER_LEN)).await?;
+            file.read_exact(&mut trailer).await?;
+            if trailer[8..] == ZSTD_LOG_MAGIC {
+                let index_offset = u64::from_le_bytes(trailer[..8].try_into()?);
+                ensure!(
+                    index_offset >= ZSTD_LOG_HEADER_LEN && index_offset < len - TRAILER_LEN,
+                    "Invalid zstd log index offset: {}",
+                    index_offset
+                );
+                file.seek(SeekFrom::Start(index_offset)).await?;
+                ensure!(file.read_u8().await? == INDEX_TAG, "Missing zstd log index tag");
+                let entries_len = file.read_u32_le().await?;
+                let mut entries = Vec::with_capacity(entries_len as usize);
+                for _ in 0..entries_len {
+                    entries.push((file.read_u32_le().await?, file.read_u64_le().await?));
+                }
+                return Ok((Self { entries }, index_offset));
+            }
+        }
+        let mut index = Self::default();
+        let mut offset = ZSTD_LOG_HEADER_LEN;
+        while offset + BLOCK_HEADER_LEN <= len {
+            file.seek(SeekFrom::Start(offset)).await?;
+            if file.read_u8().await? != BLOCK_TAG {
+                break;
+            }
+            let milestones = file.read_u32_le().await?;
+            let first_milestone_index = file.read_u32_le().await?;
+            let compressed_len = file.read_u32_le().await?;
+            let block_end = offset + BLOCK_HEADER_LEN + compressed_len as u64;
+            if block_end > len {
+                break;
+            }
+            // the header is not trusted, the milestone indexes are read from the records of the block
+            let mut compressed = vec![0; compressed_len as usize];
+            file.read_exact(&mut compressed).await?;
+            let decompressed = zstd::stream::decode_all(compressed.as_slice())
+                .map_err(|e| anyhow!("Corrupt zstd log block at offset {}: {}", offset, e))?;
+            let milestone_indexes = block_records(&decompressed)?
+                .into_iter()
+                .map(packed_milestone_index)
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            ensure!(
+                milestone_indexes.len() == milestones as usize
+                    && milestone_indexes.first().copied() == Some(first_milestone_index),
+                "Corrupt zstd log block header at offset {}",
+                offset
+            );
+            index.push(&milestone_indexes, offset);
+            offset = block_end;
+        }
+        Ok((index, offset))
+    }
+}
+
+/// Encodes milestone data records into the bytes to be appended to a log file of a given format
+#[derive(Debug)]
+pub struct LogEncoder {
+    format: ArchiveFormat,
+    block: BlockEncoder,
+    block_milestones: u32,
+    index: BlockIndex,
+    /// The encoded bytes which are yet to be returned, ie the header of a new zstd log file
+    output: Vec<u8>,
+    /// The length of the encoded bytes, excluding the buffered records
+    encoded_len: u64,
+    /// The uncompressed length of the zstd blocks encoded so far
+    raw_blocks_len: u64,
+    /// The compressed length of the zstd blocks encoded so far
+    compressed_blocks_len: u64,
+}
+
+impl LogEncoder {
+    /// Create an encoder for a new log file, a zstd block is written every `block_milestones` records
+    pub fn new(format: ArchiveFormat, compression_level: i32, block_milestones: u32) -> Self {
+        let mut output = Vec::new();
+        if let ArchiveFormat::Zstd = format {
+            output.extend(&ZSTD_LOG_MAGIC);
+            output.push(ZSTD_LOG_VERSION);
+        }
+        Self {
+            format,
+            block: BlockEncoder::new(compression_level),
+            block_milestones: block_milestones.max(1),
+            index: BlockIndex::default(),
+            encoded_len: output.len() as u64,
+            output,
+            raw_blocks_len: 0,
+            compressed_blocks_len: 0,
+        }
+    }
+
+    /// Create an encoder which resumes appending to an existing log file, whose blocks end at `len`
+    pub fn resume(
+        format: ArchiveFormat,
+        compression_level: i32,
+        block_milestones: u32,
+        index: BlockIndex,
+        len: u64,
+    ) -> Self {
+        Self {
+            format,
+            block: BlockEncoder::new(compression_level),
+            block_milestones: block_milestones.max(1),
+            index,
+            output: Vec::new(),
+            encoded_len: len,
+            raw_blocks_len: 0,
+            compressed_blocks_len: 0,
+        }
+    }
+
+    /// Get the format of the encoded log file
+    pub fn format(&self) -> ArchiveFormat {
+        self.format
+    }
+
+    /// Get the index of the encoded records, excluding the buffered ones
+    pub fn index(&self) -> &BlockIndex {
+        &self.index
+    }
+
+    /// Get the length of the log file, including the buffered records
+    pub fn encoded_len(&self) -> u64 {
+        self.projected_len(0)
+    }
+
+    /// Get the number of buffered zstd records, which are not returned as bytes yet
+    pub fn buffered_milestones(&self) -> u32 {
+        self.block.milestones()
+    }
+
+    /// Estimate the length of the log file once the buffered records and a new record of the given length are
+    /// encoded. The zstd records are estimated with the compression ratio of the blocks encoded so far.
+    pub fn projected_len(&self, record_len: usize) -> u64 {
+        let raw_len = (self.block.buffered_len() + record_len) as u64;
+        let estimated_len = match self.format {
+            ArchiveFormat::Zstd if self.raw_blocks_len > 0 => {
+                (raw_len as u128 * self.compressed_blocks_len as u128 / self.raw_blocks_len as u128) as u64
+            }
+            _ => raw_len,
+        };
+        self.encoded_len + estimated_len
+    }
+
+    /// Encode a record, returning the bytes to be appended to the log file.
+    /// Zstd records are buffered until a block is full.
+    pub fn encode(&mut self, record: &ArchiveRecord) -> anyhow::Result<Vec<u8>> {
+        match self.format {
+            ArchiveFormat::Json => {
+                let mut line = match record {
+                    ArchiveRecord::Json(line) => line.clone(),
+                    ArchiveRecord::Packed(_) => serde_json::to_string(&record.milestone_data()?)?,
+                };
+                if !line.ends_with('\n') {
+                    line.push('\n');
+                }
+                self.index.push(&[record.milestone_index()?], self.encoded_len);
+                self.encoded_len += line.len() as u64;
+                self.output.extend(line.into_bytes());
+            }
+            ArchiveFormat::Zstd => {
+                match record {
+                    ArchiveRecord::Packed(bytes) => self.block.push(bytes)?,
+                    ArchiveRecord::Json(_) => self.block.push(&record.milestone_data()?.pack_new())?,
+                }
+                if self.block.milestones() >= self.block_milestones {
+                    self.flush_block()?;
+                }
+            }
+        }
+        Ok(std::mem::take(&mut self.output))
+    }
+
+    fn flush_block(&mut self) -> anyhow::Result<()> {
+        if self.block.milestones() > 0 {
+            self.raw_blocks_len += self.block.buffered_len() as u64;
+            let (milestone_indexes, frame) = self.block.finish()?;
+            self.compressed_blocks_len += frame.len() as u64;
+            self.index.push(&milestone_indexes, self.encoded_len);
+            self.encoded_len += frame.len() as u64;
+            self.output.extend(frame);
+        }
+        Ok(())
+    }
+
+    /// Flush the buffered records into a block, returning the bytes to be appended
+    pub fn flush(&mut self) -> anyhow::Result<Vec<u8>> {
+        self.flush_block()?;
+        Ok(std::mem::take(&mut self.output))
+    }
+
+    /// Flush the buffered records and encode the footer index, returning the last bytes to be appended
+    pub fn finish(&mut self) -> anyhow::Result<Vec<u8>> {
+        if let ArchiveFormat::Zstd = self.format {
+            self.flush_block()?;
+            let footer = self.index.encode(self.encoded_len);
+            self.encoded_len += footer.len() as u64;
+            self.output.extend(footer);
+        }
+        Ok(std::mem::take(&mut self.output))
+    }
+}
+
+/// Decodes the milestone data records of a log file from its start
+#[derive(Debug)]
+pub struct RecordDecoder {
+    format: ArchiveFormat,
+    started: bool,
+    block: VecDeque<ArchiveRecord>,
+}
+
+impl RecordDecoder {
+    /// Create a decoder for a log file of the given format
+    pub fn new(format: ArchiveFormat) -> Self {
+        Self {
+            format,
+            started: false,
+            block: VecDeque::new(),
+        }
+    }
+
+    /// Create a decoder for a log file which was seeked to the offset of a record or block, as found in its index
+    pub fn at_offset(format: ArchiveFormat) -> Self {
+        Self {
+            format,
+            started: true,
+            block: VecDeque::new(),
+        }
+    }
+
+    /// Get the format of the decoded log file
+    pub fn format(&self) -> ArchiveFormat {
+        self.format
+    }
+
+    /// Read the next record, along with the number of bytes it consumed from the file.
+    /// The bytes of a zstd block are attributed to its first record.
+    pub async fn next_record<R: AsyncBufRead + Unpin>(
+        &mut self,
+        reader: &mut R,
+    ) -> std::io::Result<Option<(ArchiveRecord, u64)>> {
+        match self.format {
+            ArchiveFormat::Json => {
+                let mut line = String::new();
+                let bytes = reader.read_line(&mut line).await?;
+                if bytes == 0 {
+                    Ok(None)
+                } else {
+                    Ok(Some((ArchiveRecord::Json(line), bytes as u64)))
+                }
+            }
+            ArchiveFormat::Zstd => {
+                let mut consumed = 0;
+                if !self.started {
+                    let mut header = [0; ZSTD_LOG_HEADER_LEN as usize];
+                    reader.read_exact(&mut header).await?;
+                    if header[..8] != ZSTD_LOG_MAGIC || header[8] != ZSTD_LOG_VERSION {
+                        return Err(invalid_data("Invalid zstd log header"));
+                    }
+                    self.started = true;
+                    consumed += ZSTD_LOG_HEADER_LEN;
+                }
+                if let Some(record) = self.block.pop_front() {
+                    return Ok(Some((record, consumed)));
+                }
+                let mut tag = [0; 1];
+                // the file ends after the last block if it was not finished
+                if reader.read(&mut tag).await? == 0 {
+                    return Ok(None);
+                }
+                match tag[0] {
+                    INDEX_TAG => return Ok(None),
+                    BLOCK_TAG => (),
+                    tag => return Err(invalid_data(format!("Invalid zstd log frame tag: {}", tag))),
+                }
+                let _milestones = reader.read_u32_le().await?;
+                let _first_milestone_index = reader.read_u32_le().await?;
+                let compressed_len = reader.read_u32_le().await?;
+                let mut compressed = vec![0; compressed_len as usize];
+                reader.read_exact(&mut compressed).await?;
+                consumed += BLOCK_HEADER_LEN + compressed_len as u64;
+                let decompressed = zstd::stream::decode_all(compressed.as_slice())?;
+                for record in block_records(&decompressed).map_err(invalid_data)? {
+                    self.block.push_back(ArchiveRecord::Packed(record.to_vec()));
+                }
+                self.block
+                    .pop_front()
+                    .map(|record| Some((record, consumed)))
+                    .ok_or_else(|| invalid_data("Empty zstd log block"))
+            }
+        }
+    }
+}
+
+/// Convert a log file to the given format in place, going through a temporary file.
+/// Returns false if the file is already in the requested format, in which case it's left untouched.
+pub async fn convert(path: &Path, format: ArchiveFormat, compression_level: i32) -> anyhow::Result<bool> {
+    let mut file = File::open(path).await?;
+    let source_format = ArchiveFormat::detect_async(&mut file).await?;
+    if source_format == format {
+        return Ok(false);
+    }
+    let tmp_path = PathBuf::from(format!("{}.convert", path.to_string_lossy()));
+    let mut tmp_file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&tmp_path)
+        .await
+        .map_err(|e| anyhow!("Unable to create {}: {}", tmp_path.to_string_lossy(), e))?;
+    let mut reader = BufReader::new(file);
+    let mut decoder = RecordDecoder::new(source_format);
+    let mut encoder = LogEncoder::new(format, compression_level, BLOCK_MILESTONES);
+    while let Some((record, _)) = decoder.next_record(&mut reader).await? {
+        tmp_file.write_all(&encoder.encode(&record)?).await?;
+    }
+    tmp_file.write_all(&encoder.finish()?).await?;
+    tmp_file.sync_all().await?;
+    tokio::fs::rename(&tmp_path, path).await?;
+    encoder.index().write_sidecar(path, encoder.encoded_len()).await?;
+    // the checksum of a finalized log file changed, so it's recorded again
+    if path.extension().map_or(false, |ext| ext != "active") {
+        manifest::Manifest::record(path).await?;
+    }
+    Ok(true)
+}
+
+/// The locks of the log files which are fetched concurrently, by name
+#[derive(Debug, Clone, Default)]
+pub struct FileLocks(Arc<std::sync::Mutex<HashMap<String, Arc<Mutex<()>>>>>);
+
+impl FileLocks {
+    /// Lock a log file until the returned guard is dropped
+    pub async fn lock(&self, name: &str) -> anyhow::Result<OwnedMutexGuard<()>> {
+        let lock = self
+            .0
+            .lock()
+            .map_err(|_| anyhow!("Poisoned file locks"))?
+            .entry(name.to_owned())
+            .or_default()
+            .clone();
+        Ok(lock.lock_owned().await)
+    }
+}
+
+/// Write the sidecar indexes of the log files recorded in the manifest of a logs directory which don't have an up
+/// to date one, returning the number of written indexes
+pub async fn index_log_files(logs_dir: &Path) -> anyhow::Result<usize> {
+    let mut written = 0;
+    for entry in manifest::Manifest::load(logs_dir).await?.files() {
+        let path = logs_dir.join(&entry.file_name);
+        let mut file = match File::open(&path).await {
+            Ok(file) => file,
+            // the log file was removed after it was uploaded
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => bail!("Unable to open {}: {}", path.to_string_lossy(), e),
+        };
+        let len = file.metadata().await?.len();
+        if BlockIndex::read_sidecar(&path, len).await?.is_none() {
+            let format = ArchiveFormat::detect_async(&mut file).await?;
+            let (index, _) = BlockIndex::scan(&mut file, format).await?;
+            index.write_sidecar(&path, len).await?;
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+/// Random access reads of single milestones from the log files of an archive directory,
+/// which seek to the milestone record using the log file index
+#[cfg(feature = "merge")]
+#[derive(Debug, Clone)]
+pub struct ArchiveReader {
+    logs_dir: PathBuf,
+    logs: Arc<RwLock<Option<(Option<(SystemTime, u64)>, Arc<Vec<(u32, u32, PathBuf)>>)>>>,
+}
+
+#[cfg(feature = "merge")]
+impl ArchiveReader {
+    /// Create a reader of the log files in the given directory
+    pub fn new(logs_dir: PathBuf) -> Self {
+        Self {
+            logs_dir,
+            logs: Default::default(),
+        }
+    }
+
+    /// Get the log files along with their milestone ranges, from the manifest written by the archiver.
+    /// They are loaded again only once the manifest is modified, and a logs directory without manifest is listed once.
+    async fn logs(&self) -> anyhow::Result<Arc<Vec<(u32, u32, PathBuf)>>> {
+        let modified = tokio::fs::metadata(manifest::Manifest::path(&self.logs_dir))
+            .await
+            .and_then(|metadata| Ok((metadata.modified()?, metadata.len())))
+            .ok();
+        if let Some((loaded, logs)) = self.logs.read().await.as_ref() {
+            if *loaded == modified {
+                return Ok(logs.clone());
+            }
+        }
+        let logs = Arc::new(match modified {
+            Some(_) => manifest::Manifest::load(&self.logs_dir)
+                .await?
+                .files()
+                .into_iter()
+                .map(|entry| (entry.start, entry.end, self.logs_dir.join(&entry.file_name)))
+                .collect(),
+            None => crate::merge::LogPaths::new(&self.logs_dir, true)?.into_iter().collect(),
+        });
+        self.logs.write().await.replace((modified, logs.clone()));
+        Ok(logs)
+    }
+
+    /// Get the path of the log file which contains the given milestone, if any
+    pub async fn log_path(&self, milestone_index: u32) -> anyhow::Result<Option<PathBuf>> {
+        Ok(self
+            .logs()
+            .await?
+            .iter()
+            .find(|(start, end, _)| (*start..*end).contains(&milestone_index))
+            .map(|(_, _, path)| path.clone()))
+    }
+
+    /// Read the record of the given milestone, if it's archived.
+    /// The sidecar indexes are written by the archiver, so a log file without an up to date one is scanned
+    /// without writing into the logs directory.
+    pub async fn get_record(&self, milestone_index: u32) -> anyhow::Result<Option<ArchiveRecord>> {
+        let path = match self.log_path(milestone_index).await? {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let mut file = File::open(&path).await?;
+        let format = ArchiveFormat::detect_async(&mut file).await?;
+        let index = BlockIndex::open(&path, &mut file, format).await?;
+        let offset = match index.block_offset(milestone_index) {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+        file.seek(SeekFrom::Start(offset)).await?;
+        let mut reader = BufReader::new(file);
+        let mut decoder = RecordDecoder::at_offset(format);
+        while let Some((record, _)) = decoder.next_record(&mut reader).await? {
+            let record_milestone_index = record.milestone_index()?;
+            if record_milestone_index == milestone_index {
+                return Ok(Some(record));
+            } else if record_milestone_index > milestone_index {
+                break;
+            }
+        }
+        bail!(
+            "Milestone {} not found at its indexed offset {} in {}",
+            milestone_index,
+            offset,
+            path.to_string_lossy()
+        )
+    }
+
+    /// Read the milestone data of the given milestone, if it's archived
+    pub async fn get(&self, milestone_index: u32) -> anyhow::Result<Option<MilestoneData>> {
+        self.get_record(milestone_index)
+            .await?
+            .map(|record| record.milestone_data())
+            .transpose()
+    }
+}
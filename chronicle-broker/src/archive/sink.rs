@@ -0,0 +1,580 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+pub use super::manifest::file_checksum;
+use super::{
+    manifest::Manifest,
+    BlockIndex,
+    FileLocks,
+};
+use anyhow::{
+    anyhow,
+    bail,
+    ensure,
+};
+use chronicle_common::config::{
+    ArchiveSinkConfig,
+    ArchiveSinkTarget,
+    S3Config,
+};
+use futures::StreamExt;
+use hmac::{
+    Hmac,
+    Mac,
+    NewMac,
+};
+use reqwest::{
+    header::HeaderMap,
+    Body,
+    Client,
+    Method,
+    StatusCode,
+};
+use sha2::{
+    Digest,
+    Sha256,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
+use tokio::{
+    fs::File,
+    io::AsyncWriteExt,
+    sync::mpsc::{
+        unbounded_channel,
+        UnboundedSender,
+    },
+    task::JoinHandle,
+};
+use tokio_util::io::ReaderStream;
+use url::Url;
+
+/// The object metadata header which holds the hex encoded SHA-256 checksum of a log file
+const CHECKSUM_HEADER: &str = "x-amz-meta-sha256";
+/// The SHA-256 of an empty payload
+const EMPTY_PAYLOAD_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+/// The sink where the finalized log files are stored
+#[derive(Debug, Clone)]
+pub struct ArchiveSink {
+    target: SinkTarget,
+    delete_local: bool,
+    /// The fetch lock of each log file, as the shards of a log file are imported concurrently
+    fetches: FileLocks,
+}
+
+#[derive(Debug, Clone)]
+enum SinkTarget {
+    Local(PathBuf),
+    S3(S3Sink),
+}
+
+impl ArchiveSink {
+    /// Create the sink of the given network, whose log files are stored under its name if it's a named network
+    pub fn new(config: &ArchiveSinkConfig, network: &str) -> Self {
+        let target = match &config.target {
+            ArchiveSinkTarget::Local(dir) => {
+                let dir = PathBuf::from(dir);
+                SinkTarget::Local(if network.is_empty() { dir } else { dir.join(network) })
+            }
+            ArchiveSinkTarget::S3(s3_config) => SinkTarget::S3(S3Sink::new(s3_config, network)),
+        };
+        Self {
+            target,
+            delete_local: config.delete_local,
+            fetches: Default::default(),
+        }
+    }
+
+    /// Store a finalized log file, unless it's already stored with the same checksum.
+    /// The stored checksum is verified before the local log file is deleted along with its manifest entry, if asked,
+    /// otherwise the manifest entry is marked as stored.
+    pub async fn store(&self, path: &Path) -> anyhow::Result<()> {
+        let name = file_name(path)?;
+        let checksum = file_checksum(path).await?;
+        let stored = match &self.target {
+            SinkTarget::Local(dir) => local_checksum(dir, name).await?,
+            SinkTarget::S3(s3) => s3.checksum(name).await?,
+        };
+        if stored.as_ref() != Some(&checksum) {
+            match &self.target {
+                SinkTarget::Local(dir) => {
+                    tokio::fs::create_dir_all(dir).await?;
+                    let part_path = dir.join(format!("{}.part", name));
+                    tokio::fs::copy(path, &part_path).await?;
+                    ensure!(
+                        file_checksum(&part_path).await? == checksum,
+                        "Checksum mismatch of the copy of {}",
+                        path.to_string_lossy()
+                    );
+                    tokio::fs::write(dir.join(format!("{}.sha256", name)), &checksum).await?;
+                    tokio::fs::rename(&part_path, dir.join(name)).await?;
+                }
+                SinkTarget::S3(s3) => {
+                    s3.put(name, path, &checksum).await?;
+                    ensure!(
+                        s3.checksum(name).await?.as_ref() == Some(&checksum),
+                        "Checksum mismatch of the uploaded {}",
+                        path.to_string_lossy()
+                    );
+                }
+            }
+        }
+        if self.delete_local {
+            tokio::fs::remove_file(path).await?;
+            BlockIndex::remove_sidecar(path).await?;
+            Manifest::forget(path).await?;
+        } else {
+            Manifest::mark_stored(path, &checksum).await?;
+        }
+        Ok(())
+    }
+
+    /// Check whether a log file is stored with the same checksum as the local one
+    pub async fn is_stored(&self, path: &Path) -> anyhow::Result<bool> {
+        let name = file_name(path)?;
+        let stored = match &self.target {
+            SinkTarget::Local(dir) => local_checksum(dir, name).await?,
+            SinkTarget::S3(s3) => s3.checksum(name).await?,
+        };
+        Ok(stored.is_some() && stored == Some(file_checksum(path).await?))
+    }
+
+    /// List the names of the stored log files
+    pub async fn list(&self) -> anyhow::Result<Vec<String>> {
+        let mut names = match &self.target {
+            SinkTarget::Local(dir) => {
+                let mut names = Vec::new();
+                if let Ok(mut entries) = tokio::fs::read_dir(dir).await {
+                    while let Some(entry) = entries.next_entry().await? {
+                        if let Some(name) = entry.file_name().to_str() {
+                            names.push(name.to_owned());
+                        }
+                    }
+                }
+                names
+            }
+            SinkTarget::S3(s3) => s3.list().await?,
+        };
+        names.retain(|name| name.ends_with(".log"));
+        names.sort();
+        Ok(names)
+    }
+
+    /// Fetch a stored log file to the given path, verifying its checksum.
+    /// A log file which was fetched by a concurrent fetch of the same path is not fetched again.
+    pub async fn fetch(&self, name: &str, dest: &Path) -> anyhow::Result<()> {
+        let _guard = self.fetches.lock(&dest.to_string_lossy()).await?;
+        // the fetched log files are only moved to their path once their checksum is verified
+        if dest.exists() {
+            return Ok(());
+        }
+        let part_path = PathBuf::from(format!("{}.part", dest.to_string_lossy()));
+        let checksum = match &self.target {
+            SinkTarget::Local(dir) => {
+                tokio::fs::copy(dir.join(name), &part_path).await?;
+                local_checksum(dir, name).await?
+            }
+            SinkTarget::S3(s3) => s3.get(name, &part_path).await?,
+        }
+        .ok_or_else(|| anyhow!("No checksum stored for {}", name))?;
+        if file_checksum(&part_path).await? != checksum {
+            tokio::fs::remove_file(&part_path).await.ok();
+            bail!("Checksum mismatch of the fetched {}", name);
+        }
+        tokio::fs::rename(&part_path, dest).await?;
+        Ok(())
+    }
+}
+
+/// Stores the finalized log files in a sink one after another, in the background.
+/// A failed store is retried with a backoff, and the log file is stored again on the next start if it still fails.
+pub struct SinkUploader {
+    tx: UnboundedSender<PathBuf>,
+    task: JoinHandle<()>,
+}
+
+impl SinkUploader {
+    /// The maximum number of attempts to store a log file
+    pub const MAX_ATTEMPTS: u32 = 5;
+
+    /// Spawn the uploader of the given sink
+    pub fn spawn(sink: ArchiveSink) -> Self {
+        let (tx, mut rx) = unbounded_channel::<PathBuf>();
+        let task = tokio::spawn(async move {
+            while let Some(path) = rx.recv().await {
+                for attempt in 1..=Self::MAX_ATTEMPTS {
+                    match sink.store(&path).await {
+                        Ok(()) => {
+                            log::info!("Stored LogFile: {} in the archive sink", path.to_string_lossy());
+                            break;
+                        }
+                        Err(e) if attempt < Self::MAX_ATTEMPTS => {
+                            log::warn!(
+                                "Unable to store LogFile: {} in the archive sink, attempt: {}, error: {}",
+                                path.to_string_lossy(),
+                                attempt,
+                                e
+                            );
+                            tokio::time::sleep(std::time::Duration::from_secs(1 << attempt)).await;
+                        }
+                        Err(e) => log::error!(
+                            "Unable to store LogFile: {} in the archive sink, it's stored again on the next start, error: {}",
+                            path.to_string_lossy(),
+                            e
+                        ),
+                    }
+                }
+            }
+        });
+        Self { tx, task }
+    }
+
+    /// Queue a finalized log file to be stored
+    pub fn upload(&self, path: PathBuf) {
+        self.tx.send(path).ok();
+    }
+
+    /// Wait for the queued log files to be stored
+    pub async fn finish(self) {
+        drop(self.tx);
+        self.task.await.ok();
+    }
+}
+
+fn file_name(path: &Path) -> anyhow::Result<&str> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow!("Invalid log file path: {}", path.to_string_lossy()))
+}
+
+async fn local_checksum(dir: &Path, name: &str) -> anyhow::Result<Option<String>> {
+    match tokio::fs::read_to_string(dir.join(format!("{}.sha256", name))).await {
+        Ok(checksum) => Ok(Some(checksum.trim().to_owned())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => bail!(e),
+    }
+}
+
+/// A bucket of an S3 compatible object store, whose requests are signed with AWS signature version 4
+#[derive(Debug, Clone)]
+struct S3Sink {
+    client: Client,
+    endpoint: Url,
+    region: String,
+    bucket: String,
+    prefix: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+impl S3Sink {
+    fn new(config: &S3Config, network: &str) -> Self {
+        let mut prefix = config.prefix.clone();
+        if !network.is_empty() {
+            if !prefix.is_empty() && !prefix.ends_with('/') {
+                prefix.push('/');
+            }
+            prefix.push_str(network);
+            prefix.push('/');
+        }
+        Self {
+            client: Client::new(),
+            endpoint: config.endpoint.clone(),
+            region: config.region.clone(),
+            bucket: config.bucket.clone(),
+            prefix,
+            access_key_id: config.access_key_id.clone(),
+            secret_access_key: config.secret_access_key.clone(),
+        }
+    }
+
+    /// Get the checksum of a stored object, if it exists
+    async fn checksum(&self, name: &str) -> anyhow::Result<Option<String>> {
+        let res = self
+            .request(Method::HEAD, &self.object_path(name), &[], &[], EMPTY_PAYLOAD_SHA256)?
+            .send()
+            .await?;
+        match res.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => Ok(checksum_header(res.headers())),
+            status => bail!("Unable to head {}: {}", name, status),
+        }
+    }
+
+    async fn put(&self, name: &str, path: &Path, checksum: &str) -> anyhow::Result<()> {
+        let file = File::open(path).await?;
+        let len = file.metadata().await?.len();
+        let res = self
+            .request(
+                Method::PUT,
+                &self.object_path(name),
+                &[],
+                &[(CHECKSUM_HEADER, checksum)],
+                checksum,
+            )?
+            .header(reqwest::header::CONTENT_LENGTH, len)
+            .body(Body::wrap_stream(ReaderStream::new(file)))
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            bail!("Unable to upload {}: {} {}", name, res.status(), res.text().await?);
+        }
+        Ok(())
+    }
+
+    /// Download a stored object to the given path, returning its stored checksum
+    async fn get(&self, name: &str, dest: &Path) -> anyhow::Result<Option<String>> {
+        let res = self
+            .request(Method::GET, &self.object_path(name), &[], &[], EMPTY_PAYLOAD_SHA256)?
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            bail!("Unable to download {}: {}", name, res.status());
+        }
+        let checksum = checksum_header(res.headers());
+        let mut file = File::create(dest).await?;
+        let mut stream = res.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            file.write_all(&chunk?).await?;
+        }
+        file.sync_all().await?;
+        Ok(checksum)
+    }
+
+    /// List the object names under the prefix
+    async fn list(&self) -> anyhow::Result<Vec<String>> {
+        let mut names = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut query = vec![("list-type", "2".to_owned()), ("prefix", self.prefix.clone())];
+            if let Some(token) = continuation_token.take() {
+                query.push(("continuation-token", token));
+            }
+            let query = query.iter().map(|(k, v)| (*k, v.as_str())).collect::<Vec<_>>();
+            let res = self
+                .request(
+                    Method::GET,
+                    &format!("/{}", self.bucket),
+                    &query,
+                    &[],
+                    EMPTY_PAYLOAD_SHA256,
+                )?
+                .send()
+                .await?;
+            if !res.status().is_success() {
+                bail!("Unable to list the bucket {}: {}", self.bucket, res.status());
+            }
+            let body = res.text().await?;
+            for key in xml_values(&body, "Key") {
+                if let Some(name) = key.strip_prefix(&self.prefix) {
+                    // the objects of the nested network prefixes are not listed
+                    if !name.contains('/') {
+                        names.push(name.to_owned());
+                    }
+                }
+            }
+            match xml_values(&body, "NextContinuationToken").pop() {
+                Some(token) if xml_values(&body, "IsTruncated").first().map(String::as_str) == Some("true") => {
+                    continuation_token = Some(token)
+                }
+                _ => break,
+            }
+        }
+        Ok(names)
+    }
+
+    fn object_path(&self, name: &str) -> String {
+        format!("/{}/{}{}", self.bucket, self.prefix, name)
+    }
+
+    /// Build a signed request, the query and the extra headers are signed along with the payload hash
+    fn request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        headers: &[(&str, &str)],
+        payload_sha256: &str,
+    ) -> anyhow::Result<reqwest::RequestBuilder> {
+        let host = match self.endpoint.port() {
+            Some(port) => format!("{}:{}", self.endpoint.host_str().unwrap_or_default(), port),
+            None => self.endpoint.host_str().unwrap_or_default().to_owned(),
+        };
+        let base_path = self.endpoint.path().trim_end_matches('/');
+        let signature = SigV4Request {
+            method: method.as_str(),
+            host: &host,
+            path: &format!("{}{}", base_path, path),
+            query,
+            headers,
+            payload_sha256,
+        }
+        .sign(
+            &self.region,
+            &self.access_key_id,
+            &self.secret_access_key,
+            chrono::Utc::now(),
+        )?;
+        let mut url = self.endpoint.clone();
+        url.set_path(&signature.canonical_uri);
+        url.set_query((!signature.canonical_query.is_empty()).then(|| signature.canonical_query.as_str()));
+        let mut builder = self
+            .client
+            .request(method, url)
+            .header("authorization", signature.authorization);
+        for (name, value) in signature.headers.into_iter().filter(|(name, _)| name != "host") {
+            builder = builder.header(name.as_str(), value);
+        }
+        Ok(builder)
+    }
+}
+
+/// The parts of an S3 request which are signed with AWS signature version 4
+#[derive(Debug, Clone)]
+pub struct SigV4Request<'a> {
+    /// The HTTP method
+    pub method: &'a str,
+    /// The host, along with its port if it's not the default one
+    pub host: &'a str,
+    /// The unencoded path
+    pub path: &'a str,
+    /// The unencoded query parameters
+    pub query: &'a [(&'a str, &'a str)],
+    /// The extra signed headers, besides the host and the x-amz ones
+    pub headers: &'a [(&'a str, &'a str)],
+    /// The hex encoded SHA-256 of the payload
+    pub payload_sha256: &'a str,
+}
+
+/// A request signed with AWS signature version 4
+#[derive(Debug, Clone)]
+pub struct SigV4Signature {
+    /// The value of the authorization header
+    pub authorization: String,
+    /// The encoded path of the request
+    pub canonical_uri: String,
+    /// The encoded and sorted query of the request
+    pub canonical_query: String,
+    /// The signed headers to send, with lowercase names
+    pub headers: Vec<(String, String)>,
+}
+
+impl SigV4Request<'_> {
+    /// Sign the request at the given time, with the credentials of the given region
+    pub fn sign(
+        &self,
+        region: &str,
+        access_key_id: &str,
+        secret_access_key: &str,
+        time: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<SigV4Signature> {
+        let amz_date = time.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = time.format("%Y%m%d").to_string();
+        let canonical_uri = uri_encode(self.path, false);
+        let mut query = self
+            .query
+            .iter()
+            .map(|(k, v)| (uri_encode(k, true), uri_encode(v, true)))
+            .collect::<Vec<_>>();
+        query.sort();
+        let canonical_query = query
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join("&");
+        let mut headers = vec![
+            ("host".to_owned(), self.host.to_owned()),
+            ("x-amz-content-sha256".to_owned(), self.payload_sha256.to_owned()),
+            ("x-amz-date".to_owned(), amz_date.clone()),
+        ];
+        headers.extend(
+            self.headers
+                .iter()
+                .map(|(k, v)| (k.to_lowercase(), v.trim().to_owned())),
+        );
+        headers.sort();
+        let canonical_headers = headers
+            .iter()
+            .map(|(k, v)| format!("{}:{}\n", k, v))
+            .collect::<String>();
+        let signed_header_names = headers.iter().map(|(k, _)| k.as_str()).collect::<Vec<_>>().join(";");
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            self.method, canonical_uri, canonical_query, canonical_headers, signed_header_names, self.payload_sha256
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let mut key = hmac_sha256(format!("AWS4{}", secret_access_key).as_bytes(), date.as_bytes())?;
+        for part in [region, "s3", "aws4_request"] {
+            key = hmac_sha256(&key, part.as_bytes())?;
+        }
+        let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes())?);
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            access_key_id, scope, signed_header_names, signature
+        );
+        Ok(SigV4Signature {
+            authorization,
+            canonical_uri,
+            canonical_query,
+            headers,
+        })
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).map_err(|e| anyhow!("Invalid hmac key: {}", e))?;
+    mac.update(data);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+/// Percent encode everything but the unreserved characters, and the slashes of a path
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn checksum_header(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(CHECKSUM_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned)
+}
+
+/// Get the text values of the given xml element, which are unescaped
+fn xml_values(body: &str, tag: &str) -> Vec<String> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    let mut values = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        if let Some(end) = rest.find(&close) {
+            values.push(
+                rest[..end]
+                    .replace("&lt;", "<")
+                    .replace("&gt;", ">")
+                    .replace("&quot;", "\"")
+                    .replace("&apos;", "'")
+                    .replace("&amp;", "&"),
+            );
+            rest = &rest[end + close.len()..];
+        } else {
+            break;
+        }
+    }
+    values
+}
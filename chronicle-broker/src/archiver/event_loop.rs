@@ -125,7 +125,14 @@ impl Archiver {
             .enumerate()
             .find(|(_, log)| log.to_ms_index == milestone_index)
         {
-            Self::finish_log_file(log_file, &self.dir_path).await?;
+            Self::finish_log_file(
+                log_file,
+                &self.dir_path,
+                &self.uploader,
+                &self.keyspace,
+                self.retries_per_query,
+            )
+            .await?;
             // remove finished log file
             let log_file = self.logs.remove(i);
             self.push_to_processed(log_file);
@@ -138,35 +145,40 @@ impl Archiver {
         mut opt_upper_limit: Option<u32>,
     ) -> anyhow::Result<()> {
         let milestone_index = milestone_data.milestone_index();
-        let mut milestone_data_json = serde_json::to_string(&milestone_data).unwrap();
-        milestone_data_json.push('\n');
-        let milestone_data_line: Vec<u8> = milestone_data_json.into();
+        let record = ArchiveRecord::new(&milestone_data, self.format)?;
         // check the logs files to find if any has already existing log file
         if let Some(log_file) = self
             .logs
             .iter_mut()
             .find(|log| log.to_ms_index == milestone_index && log.upper_ms_limit > milestone_index)
         {
-            // append milestone data to the log file if the file_size still less than max limit
-            if (milestone_data_line.len() as u64) + log_file.len() < self.max_log_size {
-                Self::append(
-                    log_file,
-                    &milestone_data_line,
-                    milestone_index,
-                    &self.keyspace,
-                    self.retries_per_query,
-                )
-                .await?;
+            // append milestone data to the log file if the compressed file size still less than max limit
+            if log_file.projected_len(&record) < self.max_log_size {
+                Self::append(log_file, &record, &self.keyspace, self.retries_per_query).await?;
                 // check if now the log_file reached an upper limit to finish the file
                 if log_file.upper_ms_limit == log_file.to_ms_index {
                     self.cleanup.push(log_file.from_ms_index);
-                    Self::finish_log_file(log_file, &self.dir_path).await?;
+                    Self::finish_log_file(
+                        log_file,
+                        &self.dir_path,
+                        &self.uploader,
+                        &self.keyspace,
+                        self.retries_per_query,
+                    )
+                    .await?;
                 }
             } else {
                 // push it into cleanup
                 self.cleanup.push(log_file.from_ms_index);
                 // Finish it;
-                Self::finish_log_file(log_file, &self.dir_path).await?;
+                Self::finish_log_file(
+                    log_file,
+                    &self.dir_path,
+                    &self.uploader,
+                    &self.keyspace,
+                    self.retries_per_query,
+                )
+                .await?;
                 info!(
                     "{} hits filesize limit: {} bytes, contains: {} milestones data",
                     log_file.filename,
@@ -182,7 +194,7 @@ impl Archiver {
                         milestone_index
                     );
                     opt_upper_limit.replace(log_file.upper_ms_limit);
-                    self.create_and_append(milestone_index, &milestone_data_line, opt_upper_limit)
+                    self.create_and_append(milestone_index, &record, opt_upper_limit)
                         .await?;
                 }
             }
@@ -193,7 +205,7 @@ impl Archiver {
                     "Creating new log file starting from milestone index: {}",
                     milestone_index
                 );
-                self.create_and_append(milestone_index, &milestone_data_line, opt_upper_limit)
+                self.create_and_append(milestone_index, &record, opt_upper_limit)
                     .await?;
             };
         };
@@ -212,22 +224,29 @@ impl Archiver {
     async fn create_and_append(
         &mut self,
         milestone_index: u32,
-        milestone_data_line: &Vec<u8>,
+        record: &ArchiveRecord,
         opt_upper_limit: Option<u32>,
     ) -> anyhow::Result<()> {
-        let mut log_file = LogFile::create(&self.dir_path, milestone_index, opt_upper_limit).await?;
-        Self::append(
-            &mut log_file,
-            milestone_data_line,
+        let mut log_file = LogFile::create(
+            &self.dir_path,
             milestone_index,
-            &self.keyspace,
-            self.retries_per_query,
+            opt_upper_limit,
+            self.format,
+            self.compression_level,
         )
         .await?;
+        Self::append(&mut log_file, record, &self.keyspace, self.retries_per_query).await?;
         // check if we hit an upper_ms_limit, as this is possible when the log_file only needs 1 milestone data.
         if log_file.upper_ms_limit == log_file.to_ms_index {
             // finish it
-            Self::finish_log_file(&mut log_file, &self.dir_path).await?;
+            Self::finish_log_file(
+                &mut log_file,
+                &self.dir_path,
+                &self.uploader,
+                &self.keyspace,
+                self.retries_per_query,
+            )
+            .await?;
             // add it to processed
             self.push_to_processed(log_file);
         } else {
@@ -248,7 +267,14 @@ impl Archiver {
                         // push it into cleanup to get removed and pushed to processed
                         self.cleanup.push(l.from_ms_index);
                         // finish the file
-                        Self::finish_log_file(l, &self.dir_path).await?;
+                        Self::finish_log_file(
+                            l,
+                            &self.dir_path,
+                            &self.uploader,
+                            &self.keyspace,
+                            self.retries_per_query,
+                        )
+                        .await?;
                     }
 
                     prev_log = l;
@@ -268,34 +294,53 @@ impl Archiver {
     }
     async fn append(
         log_file: &mut LogFile,
-        milestone_data_line: &Vec<u8>,
-        ms_index: u32,
+        record: &ArchiveRecord,
+        keyspace: &ChronicleKeyspace,
+        retries_per_query: usize,
+    ) -> anyhow::Result<()> {
+        log_file.append(record).await?;
+        Self::mark_logged(log_file.take_written(), keyspace, retries_per_query)
+    }
+    /// Mark the milestones written to a log file as logged
+    pub(crate) fn mark_logged(
+        milestone_indexes: Vec<u32>,
         keyspace: &ChronicleKeyspace,
         retries_per_query: usize,
     ) -> anyhow::Result<()> {
-        log_file.append_line(&milestone_data_line).await?;
-        // insert into the DB, without caring about the response
-        let sync_key = chronicle_common::Synckey;
-        let synced_record = SyncRecord::new(MilestoneIndex(ms_index), None, Some(0));
-        keyspace
-            .insert(&sync_key, &synced_record)
-            .consistency(Consistency::One)
-            .build()?
-            .send_local(InsertWorker::boxed(
-                keyspace.clone(),
-                sync_key,
-                synced_record,
-                retries_per_query,
-            ));
+        for ms_index in milestone_indexes {
+            // insert into the DB, without caring about the response
+            let sync_key = chronicle_common::Synckey;
+            let synced_record = SyncRecord::new(MilestoneIndex(ms_index), None, Some(0));
+            keyspace
+                .insert(&sync_key, &synced_record)
+                .consistency(Consistency::One)
+                .build()?
+                .send_local(InsertWorker::boxed(
+                    keyspace.clone(),
+                    sync_key,
+                    synced_record,
+                    retries_per_query,
+                ));
+        }
         Ok(())
     }
-    async fn finish_log_file(log_file: &mut LogFile, dir_path: &PathBuf) -> anyhow::Result<()> {
-        log_file.finish(dir_path).await?;
+    async fn finish_log_file(
+        log_file: &mut LogFile,
+        dir_path: &PathBuf,
+        uploader: &Option<SinkUploader>,
+        keyspace: &ChronicleKeyspace,
+        retries_per_query: usize,
+    ) -> anyhow::Result<()> {
+        let path = log_file.finish(dir_path).await?;
+        Self::mark_logged(log_file.take_written(), keyspace, retries_per_query)?;
         log_file.set_finished();
         info!(
             "Finished {}.part, LogFile: {}to{}.log",
             log_file.from_ms_index, log_file.from_ms_index, log_file.to_ms_index
         );
+        if let Some(uploader) = uploader.as_ref() {
+            uploader.upload(path);
+        }
         Ok(())
     }
 }
@@ -16,6 +16,30 @@ impl<H: ChronicleBrokerScope> Init<BrokerHandle<H>> for Archiver {
                 return Err(Need::Abort);
             }
         };
+        // index the log files which were finished without a sidecar index, for the random access reads of the api
+        match index_log_files(&self.dir_path).await {
+            Ok(0) => (),
+            Ok(written) => info!("Indexed {} log files", written),
+            Err(e) => warn!("Unable to index the log files: {}", e),
+        }
+        // store the log files which were finished before a restart, unless the manifest marks them as stored
+        if let Some(sink) = self.sink.clone() {
+            let uploader = SinkUploader::spawn(sink);
+            let manifest = Manifest::load(&self.dir_path).await.unwrap_or_else(|e| {
+                warn!("{}", e);
+                Manifest::default()
+            });
+            if let Ok(mut entries) = tokio::fs::read_dir(&self.dir_path).await {
+                while let Ok(Some(entry)) = entries.next_entry().await {
+                    let path = entry.path();
+                    let is_stored = entry.file_name().to_str().map_or(true, |name| manifest.is_stored(name));
+                    if path.extension().map_or(false, |ext| ext == "log") && !is_stored {
+                        uploader.upload(path);
+                    }
+                }
+            }
+            self.uploader.replace(uploader);
+        }
         self.service.update_status(ServiceStatus::Initializing);
         let event = BrokerEvent::Children(BrokerChild::Archiver(self.service.clone(), Ok(())));
         let _ = _supervisor
@@ -9,6 +9,21 @@ use crate::{
         BrokerHandle,
         ChronicleBrokerScope,
     },
+    archive::{
+        index_log_files,
+        manifest::Manifest,
+        sink::{
+            ArchiveSink,
+            SinkUploader,
+        },
+        ArchiveFormat,
+        ArchiveRecord,
+        BlockIndex,
+        LogEncoder,
+        RecordDecoder,
+        BLOCK_MILESTONES,
+        DEFAULT_COMPRESSION_LEVEL,
+    },
     syncer::Ascending,
 };
 use anyhow::bail;
@@ -17,11 +32,16 @@ use chronicle_storage::access::ChronicleKeyspace;
 use std::{
     collections::BinaryHeap,
     convert::TryFrom,
+    io::SeekFrom,
     ops::{
         Deref,
         DerefMut,
+        Range,
+    },
+    path::{
+        Path,
+        PathBuf,
     },
-    path::PathBuf,
 };
 use tokio::{
     fs::{
@@ -29,7 +49,7 @@ use tokio::{
         OpenOptions,
     },
     io::{
-        AsyncBufReadExt,
+        AsyncSeekExt,
         AsyncWriteExt,
         BufReader,
     },
@@ -46,23 +66,27 @@ pub const MAX_LOG_SIZE: u64 = u32::MAX as u64;
 builder!(ArchiverBuilder {
     keyspace: ChronicleKeyspace,
     max_log_size: u64,
+    compression_level: i32,
     oneshot: Receiver<u32>,
     solidifiers_count: u8,
     retries_per_query: usize,
+    inbox_capacity: usize,
+    network: String,
+    sink: ArchiveSink,
     dir_path: PathBuf
 });
 
 /// ArchiverHandle to be passed to the supervisor and solidifers
 #[derive(Clone)]
 pub struct ArchiverHandle {
-    pub(crate) tx: tokio::sync::mpsc::UnboundedSender<ArchiverEvent>,
+    pub(crate) tx: BoundedSender<ArchiverEvent>,
 }
 /// ArchiverInbox is used to recv events from solidifier(s)
 pub struct ArchiverInbox {
-    pub(crate) rx: tokio::sync::mpsc::UnboundedReceiver<ArchiverEvent>,
+    pub(crate) rx: BoundedReceiver<ArchiverEvent>,
 }
 impl Deref for ArchiverHandle {
-    type Target = tokio::sync::mpsc::UnboundedSender<ArchiverEvent>;
+    type Target = BoundedSender<ArchiverEvent>;
 
     fn deref(&self) -> &Self::Target {
         &self.tx
@@ -104,17 +128,25 @@ pub struct LogFile {
     to_ms_index: u32,
     upper_ms_limit: u32,
     file: BufReader<File>,
+    /// The encoder of the appended milestone data, none if the file was opened to be imported
+    encoder: Option<LogEncoder>,
+    /// The appended milestones which are not taken as written yet
+    appended: Vec<u32>,
+    /// The decoder of the imported milestone data
+    decoder: RecordDecoder,
     /// Identifier if it had io error
     maybe_corrupted: bool,
     finished: bool,
 }
 
 impl LogFile {
-    /// Create a new Write-ahead-log file for a starting milestone index
+    /// Create a new Write-ahead-log file for a starting milestone index, in the given format
     pub async fn create(
         dir_path: &PathBuf,
         milestone_index: u32,
         opt_upper_limit: Option<u32>,
+        format: ArchiveFormat,
+        compression_level: i32,
     ) -> anyhow::Result<LogFile> {
         let filename = format!("{}.part", milestone_index);
         let file_path = dir_path.join(&filename);
@@ -139,17 +171,32 @@ impl LogFile {
             to_ms_index: milestone_index,
             upper_ms_limit: opt_upper_limit.unwrap_or(u32::MAX),
             file: BufReader::new(file),
+            // the zstd records are buffered into blocks, the milestones are taken as written once their block is
+            encoder: Some(LogEncoder::new(format, compression_level, BLOCK_MILESTONES)),
+            appended: Vec::new(),
+            decoder: RecordDecoder::new(format),
             maybe_corrupted: false,
             finished: false,
         })
     }
 
-    /// Complete a log file and save it to the given directory
-    pub async fn finish(&mut self, dir_path: &PathBuf) -> anyhow::Result<()> {
+    /// Complete a log file and save it to the given directory, returning its path
+    pub async fn finish(&mut self, dir_path: &PathBuf) -> anyhow::Result<PathBuf> {
         let new_file_name = format!("{}to{}.log", self.from_ms_index, self.to_ms_index);
         let new_file_path = dir_path.join(&new_file_name);
         let old_file_path = dir_path.join(&self.filename);
-        if let Err(e) = tokio::fs::rename(old_file_path, new_file_path).await {
+        // write the footer index, if any
+        let mut index = None;
+        if let Some(mut encoder) = self.encoder.take() {
+            let footer = encoder.finish()?;
+            if let Err(e) = self.file.write_all(&footer).await {
+                self.maybe_corrupted = true;
+                bail!(e)
+            };
+            self.len += footer.len() as u64;
+            index = Some(encoder.index().clone());
+        }
+        if let Err(e) = tokio::fs::rename(old_file_path, &new_file_path).await {
             self.maybe_corrupted = true;
             bail!(e)
         };
@@ -157,13 +204,27 @@ impl LogFile {
             self.maybe_corrupted = true;
             bail!(e)
         };
-        Ok(())
+        // the sidecar index only speeds up the reads, so the log file is finished even if it's not written
+        if let Some(index) = index {
+            if let Err(e) = index.write_sidecar(&new_file_path, self.len).await {
+                warn!("{}", e);
+            }
+        }
+        if let Err(e) = Manifest::record(&new_file_path).await {
+            error!("Unable to record {} in the manifest: {}", new_file_name, e);
+        }
+        Ok(new_file_path)
     }
 
-    /// Append a new line to the log file
-    pub async fn append_line(&mut self, line: &Vec<u8>) -> anyhow::Result<()> {
+    /// Append a new milestone data record to the log file
+    pub async fn append(&mut self, record: &ArchiveRecord) -> anyhow::Result<()> {
+        let bytes = self
+            .encoder
+            .as_mut()
+            .ok_or_else(|| anyhow::anyhow!("LogFile: {} is not opened for appends", self.filename))?
+            .encode(record)?;
         // append to the file
-        if let Err(e) = self.file.write_all(line.as_ref()).await {
+        if let Err(e) = self.file.write_all(&bytes).await {
             self.maybe_corrupted = true;
             // Check if the error was because of disk overflow
             if let std::io::ErrorKind::WriteZero = e.kind() {
@@ -179,11 +240,27 @@ impl LogFile {
                 e
             );
         };
+        self.appended.push(self.to_ms_index);
         self.to_ms_index += 1;
         // update bytes size length;
-        self.len += line.len() as u64;
+        self.len += bytes.len() as u64;
         Ok(())
     }
+    /// Take the appended milestones which are written to the file, the buffered zstd records are only written once
+    /// their block is full or the file is finished
+    pub fn take_written(&mut self) -> Vec<u32> {
+        match self.encoder.as_ref() {
+            Some(encoder) if encoder.buffered_milestones() > 0 => Vec::new(),
+            _ => std::mem::take(&mut self.appended),
+        }
+    }
+    /// Estimate the length of the log file once the record is appended, see `LogEncoder::projected_len`
+    pub fn projected_len(&self, record: &ArchiveRecord) -> u64 {
+        match self.encoder.as_ref() {
+            Some(encoder) => encoder.projected_len(record.encoded_len()),
+            None => self.len + record.encoded_len() as u64,
+        }
+    }
     /// Fetch the next milestone data from the log file.
     /// Note: this supposed to be used by importer
     pub async fn next(&mut self) -> Result<Option<MilestoneData>, std::io::Error> {
@@ -193,30 +270,50 @@ impl LogFile {
                 "Cannot fetch next milestone data from maybe corrupted LogFile",
             ));
         }
-        if self.len == 0 {
-            self.finished = true;
-            return Ok(None);
-        }
-        let mut milestone_data_line: String = String::new();
-        match self.file.read_line(&mut milestone_data_line).await {
-            Ok(n) => {
-                if n == 0 {
-                    self.finished = true;
-                    return Ok(None);
-                }
-                let milestone_data: MilestoneData = serde_json::from_str(&milestone_data_line).map_err(|e| {
+        match self.decoder.next_record(&mut self.file).await {
+            Ok(Some((record, bytes))) => {
+                let milestone_data = record.milestone_data().map_err(|e| {
                     self.maybe_corrupted = true;
                     let error_fmt = format!("Unable to deserialize milestone data bytes. Error: {}", e);
                     std::io::Error::new(std::io::ErrorKind::InvalidData, error_fmt)
                 })?;
-                self.len -= milestone_data_line.len() as u64;
+                self.len = self.len.saturating_sub(bytes);
                 Ok(Some(milestone_data))
             }
+            Ok(None) => {
+                self.finished = true;
+                Ok(None)
+            }
             Err(err) => {
                 self.maybe_corrupted = true;
-                return Err(err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Seek to the block of the first milestone of the range using the log file index,
+    /// so the preceding milestone data is not read, and narrow the file length to the bytes of the range.
+    /// Note: this supposed to be used by importer, before the first milestone data is fetched
+    pub async fn seek_range(&mut self, file_path: &Path, range: &Range<u32>) -> anyhow::Result<()> {
+        let format = self.decoder.format();
+        let index = BlockIndex::open(file_path, self.file.get_mut(), format).await?;
+        let file_len = self.file.get_mut().seek(SeekFrom::End(0)).await?;
+        match index.block_offset(range.start) {
+            Some(start) => {
+                let end = index
+                    .entries()
+                    .iter()
+                    .find(|(milestone_index, offset)| *milestone_index >= range.end && *offset > start)
+                    .map_or(file_len, |(_, offset)| *offset);
+                self.file.seek(SeekFrom::Start(start)).await?;
+                self.decoder = RecordDecoder::at_offset(format);
+                self.len = end - start;
+            }
+            None => {
+                self.file.seek(SeekFrom::Start(0)).await?;
             }
         }
+        Ok(())
     }
 
     /// Get the file length
@@ -248,6 +345,13 @@ pub struct Archiver {
     dir_path: PathBuf,
     logs: Vec<LogFile>,
     max_log_size: u64,
+    /// The format of the written log files
+    format: ArchiveFormat,
+    compression_level: i32,
+    /// The sink which stores the finished log files
+    sink: Option<ArchiveSink>,
+    /// The uploader of the finished log files to the sink
+    uploader: Option<SinkUploader>,
     cleanup: Vec<u32>,
     processed: Vec<std::ops::Range<u32>>,
     milestones_data: BinaryHeap<Ascending<MilestoneData>>,
@@ -257,6 +361,8 @@ pub struct Archiver {
     solidifiers_count: u8,
     handle: Option<ArchiverHandle>,
     inbox: ArchiverInbox,
+    /// The network this archiver belongs to
+    network: String,
 }
 impl Archiver {
     /// Take the held archiver handle, leaving None in its place
@@ -270,7 +376,11 @@ impl<H: ChronicleBrokerScope> ActorBuilder<BrokerHandle<H>> for ArchiverBuilder
 impl Builder for ArchiverBuilder {
     type State = Archiver;
     fn build(self) -> Self::State {
-        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let network = self.network.unwrap_or_default();
+        let (tx, rx) = bounded_channel(
+            self.inbox_capacity.unwrap_or(1000),
+            &network_scoped_name(&network, "Archiver"),
+        );
         let handle = Some(ArchiverHandle { tx });
         let inbox = ArchiverInbox { rx };
         let dir_path = self.dir_path.expect("Expected log dictionary path");
@@ -280,6 +390,10 @@ impl Builder for ArchiverBuilder {
             logs: Vec::new(),
             cleanup: Vec::with_capacity(2),
             max_log_size: self.max_log_size.unwrap_or(MAX_LOG_SIZE),
+            format: ArchiveFormat::from_compression_level(self.compression_level),
+            compression_level: self.compression_level.unwrap_or(DEFAULT_COMPRESSION_LEVEL),
+            sink: self.sink,
+            uploader: None,
             processed: Vec::new(),
             keyspace: self.keyspace.unwrap(),
             solidifiers_count: self.solidifiers_count.unwrap(),
@@ -288,6 +402,7 @@ impl Builder for ArchiverBuilder {
             retries_per_query: self.retries_per_query.unwrap_or(10),
             handle,
             inbox,
+            network,
         }
         .set_name()
     }
@@ -296,7 +411,7 @@ impl Builder for ArchiverBuilder {
 /// impl name of the Archiver
 impl Name for Archiver {
     fn set_name(mut self) -> Self {
-        self.service.update_name("Archiver".to_string());
+        self.service.update_name(network_scoped_name(&self.network, "Archiver"));
         self
     }
     fn get_name(&self) -> String {
@@ -323,8 +438,9 @@ impl TryFrom<PathBuf> for LogFile {
             let split = filename.split("to").collect::<Vec<_>>();
             anyhow::ensure!(split.len() == 2, "Invalid filename!");
             let (from_ms_index, to_ms_index) = (split[0].parse()?, split[1].parse()?);
-            let std_file = std::fs::OpenOptions::new().write(false).read(true).open(file_path)?;
+            let mut std_file = std::fs::OpenOptions::new().write(false).read(true).open(file_path)?;
             let len = std_file.metadata()?.len();
+            let format = ArchiveFormat::detect(&mut std_file)?;
             let file = tokio::fs::File::from_std(std_file);
             Ok(LogFile {
                 len,
@@ -333,6 +449,9 @@ impl TryFrom<PathBuf> for LogFile {
                 to_ms_index,
                 upper_ms_limit: to_ms_index,
                 file: BufReader::new(file),
+                encoder: None,
+                appended: Vec::new(),
+                decoder: RecordDecoder::new(format),
                 maybe_corrupted: false,
                 finished: false,
             })
@@ -15,11 +15,20 @@ impl<H: ChronicleBrokerScope> Terminating<BrokerHandle<H>> for Archiver {
         let _ = supervisor.as_mut().unwrap().send(event);
         // finialize in progress logs
         for log in self.logs.iter_mut() {
-            if let Err(e) = log.finish(&self.dir_path).await {
-                info!("Unable to finish in progress log file: {}, error: {}", log.filename, e);
-            } else {
-                info!("Finished in progress log file: {}", log.filename);
-            };
+            match log.finish(&self.dir_path).await {
+                Err(e) => info!("Unable to finish in progress log file: {}, error: {}", log.filename, e),
+                Ok(path) => {
+                    info!("Finished in progress log file: {}", log.filename);
+                    Self::mark_logged(log.take_written(), &self.keyspace, self.retries_per_query).ok();
+                    if let Some(uploader) = self.uploader.as_ref() {
+                        uploader.upload(path);
+                    }
+                }
+            }
+        }
+        // wait for the pending uploads to the archive sink
+        if let Some(uploader) = self.uploader.take() {
+            uploader.finish().await;
         }
         _status
     }
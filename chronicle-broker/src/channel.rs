@@ -0,0 +1,201 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use chronicle_common::metrics::{
+    prometheus::{
+        IntCounter,
+        IntGauge,
+    },
+    INBOX_DROPPED_EVENTS,
+    INBOX_QUEUE_DEPTH,
+};
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+use tokio::sync::mpsc::{
+    error::{
+        SendError,
+        TrySendError,
+    },
+    Receiver,
+    Sender,
+    UnboundedReceiver,
+    UnboundedSender,
+};
+
+/// Create a bounded actor channel whose queue depth is exported under the given actor label
+pub fn bounded_channel<T>(capacity: usize, actor: &str) -> (BoundedSender<T>, BoundedReceiver<T>) {
+    let (tx, rx) = tokio::sync::mpsc::channel(capacity);
+    let (control_tx, control_rx) = tokio::sync::mpsc::unbounded_channel();
+    let depth = INBOX_QUEUE_DEPTH.with_label_values(&[actor]);
+    (
+        BoundedSender {
+            tx,
+            control: control_tx,
+            depth: depth.clone(),
+            dropped: INBOX_DROPPED_EVENTS.with_label_values(&[actor]),
+            deferred: Arc::new(Mutex::new(VecDeque::new())),
+            capacity,
+        },
+        BoundedReceiver {
+            rx,
+            control: control_rx,
+            depth,
+        },
+    )
+}
+
+/// The sending half of a bounded actor inbox
+pub struct BoundedSender<T> {
+    tx: Sender<T>,
+    /// The unbounded lane of the completion and control events, which are never dropped
+    control: UnboundedSender<T>,
+    depth: IntGauge,
+    dropped: IntCounter,
+    /// The events deferred while the inbox is full, in sending order, shared by all the senders of the inbox
+    deferred: Arc<Mutex<VecDeque<T>>>,
+    /// The inbox capacity, which also bounds the deferred events
+    capacity: usize,
+}
+
+impl<T> Clone for BoundedSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            control: self.control.clone(),
+            depth: self.depth.clone(),
+            dropped: self.dropped.clone(),
+            deferred: self.deferred.clone(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+impl<T: Send + 'static> BoundedSender<T> {
+    /// Send an event, waiting for capacity if the inbox is full
+    pub async fn send(&self, event: T) -> Result<(), SendError<T>> {
+        self.depth.inc();
+        self.tx.send(event).await.map_err(|e| {
+            self.depth.dec();
+            e
+        })
+    }
+    /// Send a completion or control event, like a query result or a shutdown, on the unbounded control lane of the
+    /// inbox. It never waits and never drops the event, so it fits any context, even without a runtime, and the
+    /// feedback events flowing against the pipeline, where waiting could deadlock two actors. The control events are
+    /// received ahead of the bounded ones.
+    /// Returns false if the inbox is closed.
+    pub fn send_control(&self, event: T) -> bool {
+        if self.control.send(event).is_ok() {
+            self.depth.inc();
+            true
+        } else {
+            false
+        }
+    }
+    /// Send a droppable event without waiting for capacity.
+    /// If the inbox is full, the event is deferred behind the already deferred ones, which a
+    /// single task forwards in order as soon as the inbox has capacity. At most `capacity`
+    /// events are deferred, any further event is dropped and counted. Deferring needs a tokio
+    /// runtime to forward the events, so without one the event is dropped as well.
+    /// The events which must not be lost are sent with `send` or `send_control` instead.
+    /// Returns false if the event was dropped or the inbox is closed.
+    pub fn send_or_defer(&self, event: T) -> bool {
+        let mut deferred = self.deferred.lock().expect("Poisoned deferred events");
+        if deferred.is_empty() {
+            match self.tx.try_send(event) {
+                Ok(()) => {
+                    self.depth.inc();
+                    return true;
+                }
+                Err(TrySendError::Closed(_)) => return false,
+                Err(TrySendError::Full(event)) => {
+                    let runtime = match tokio::runtime::Handle::try_current() {
+                        Ok(runtime) => runtime,
+                        Err(_) => {
+                            self.drop_event("there's no runtime to forward it");
+                            return false;
+                        }
+                    };
+                    deferred.push_back(event);
+                    self.depth.inc();
+                    self.spawn_forwarder(&runtime);
+                    return true;
+                }
+            }
+        }
+        if deferred.len() >= self.capacity {
+            self.drop_event("the inbox and its deferred events are full");
+            return false;
+        }
+        // keep the order, the event must not overtake the deferred ones
+        deferred.push_back(event);
+        self.depth.inc();
+        true
+    }
+    fn drop_event(&self, reason: &str) {
+        self.dropped.inc();
+        log::warn!("Dropped an event, {}", reason);
+    }
+    /// Forward the deferred events in order, until none is left
+    fn spawn_forwarder(&self, runtime: &tokio::runtime::Handle) {
+        let tx = self.tx.clone();
+        let depth = self.depth.clone();
+        let deferred = self.deferred.clone();
+        runtime.spawn(async move {
+            loop {
+                // the event to forward stays at the front while waiting, so the next events queue behind it
+                let permit = tx.reserve().await;
+                let mut deferred = deferred.lock().expect("Poisoned deferred events");
+                match permit {
+                    Ok(permit) => {
+                        if let Some(event) = deferred.pop_front() {
+                            permit.send(event);
+                        }
+                    }
+                    Err(_) => {
+                        depth.sub(deferred.len() as i64);
+                        deferred.clear();
+                    }
+                }
+                if deferred.is_empty() {
+                    break;
+                }
+            }
+        });
+    }
+    /// The number of events deferred while the inbox is full
+    pub fn deferred(&self) -> usize {
+        self.deferred.lock().expect("Poisoned deferred events").len()
+    }
+}
+
+/// The receiving half of a bounded actor inbox
+pub struct BoundedReceiver<T> {
+    rx: Receiver<T>,
+    control: UnboundedReceiver<T>,
+    depth: IntGauge,
+}
+
+impl<T> BoundedReceiver<T> {
+    /// Receive the next event, the control events first
+    pub async fn recv(&mut self) -> Option<T> {
+        let event = tokio::select! {
+            biased;
+            Some(event) = self.control.recv() => Some(event),
+            event = self.rx.recv() => match event {
+                Some(event) => Some(event),
+                // the senders of both lanes are dropped together, so only the control events may be left
+                None => self.control.recv().await,
+            },
+        };
+        if event.is_some() {
+            self.depth.dec();
+        }
+        event
+    }
+}
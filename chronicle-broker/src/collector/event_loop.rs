@@ -4,7 +4,6 @@
 use super::*;
 use bee_message::{
     address::Address,
-    input::Input,
     parents::Parents,
     payload::Payload,
     prelude::TransactionId,
@@ -39,11 +38,13 @@ impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Collector {
                         // check if the requested message actually belongs to the expected milestone_index
                         if ref_ms.eq(&try_ms_index) {
                             // push full message to solidifier;
-                            self.push_fullmsg_to_solidifier(partition_id, message.clone(), metadata.clone());
+                            self.push_fullmsg_to_solidifier(partition_id, message.clone(), metadata.clone())
+                                .await;
                             // proceed to insert the message and put it in the cache.
                         } else {
                             // close the request
-                            self.push_close_to_solidifier(partition_id, message_id, try_ms_index);
+                            self.push_close_to_solidifier(partition_id, message_id, try_ms_index)
+                                .await;
                         }
                         // set the ref_ms to be the current requested message ref_ms
                         self.ref_ms.0 = *ref_ms;
@@ -83,7 +84,7 @@ impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Collector {
                             try_ms_index
                         );
                         // inform solidifier
-                        self.send_err_solidifiy(try_ms_index);
+                        self.send_err_solidifiy(try_ms_index).await;
                     }
                 }
                 CollectorEvent::Message(message_id, mut message) => {
@@ -139,7 +140,7 @@ impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Collector {
                             if let Some(solidifier_handle) = self.solidifier_handles.get(&_partition_id) {
                                 let full_message = FullMessage::new(message.clone(), metadata.clone());
                                 let full_msg_event = SolidifierEvent::Message(full_message);
-                                let _ = solidifier_handle.send(full_msg_event);
+                                let _ = solidifier_handle.send(full_msg_event).await;
                             };
                             // however the message_id might had been requested,
                             if let Some((requested_by_this_ms, _)) = self.pending_requests.remove(&message_id) {
@@ -147,7 +148,8 @@ impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Collector {
                                 if !requested_by_this_ms.eq(&*ref_ms) {
                                     // close it
                                     let solidifier_id = (requested_by_this_ms % (self.collector_count as u32)) as u8;
-                                    self.push_close_to_solidifier(solidifier_id, message_id, requested_by_this_ms);
+                                    self.push_close_to_solidifier(solidifier_id, message_id, requested_by_this_ms)
+                                        .await;
                                 }
                             }
                             // request all pending_requests with less than the received milestone index
@@ -159,10 +161,12 @@ impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Collector {
                                 if requested_by_this_ms.eq(&*ref_ms) {
                                     // push full message
                                     self.push_fullmsg_to_solidifier(_partition_id, message.clone(), metadata.clone())
+                                        .await
                                 } else {
                                     // close it
                                     let solidifier_id = (requested_by_this_ms % (self.collector_count as u32)) as u8;
-                                    self.push_close_to_solidifier(solidifier_id, message_id, requested_by_this_ms);
+                                    self.push_close_to_solidifier(solidifier_id, message_id, requested_by_this_ms)
+                                        .await;
                                 }
                                 cached_msg = Some(message);
                             } else {
@@ -199,12 +203,13 @@ impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Collector {
                                     // metadata exist means we already pushed the full message to the solidifier,
                                     // or the message doesn't belong to the solidifier
                                     if !metadata.referenced_by_milestone_index.unwrap().eq(&try_ms_index) {
-                                        self.push_close_to_solidifier(solidifier_id, message_id, try_ms_index);
+                                        self.push_close_to_solidifier(solidifier_id, message_id, try_ms_index)
+                                            .await;
                                     } else {
                                         if let Some(solidifier_handle) = self.solidifier_handles.get(&solidifier_id) {
                                             let full_message = FullMessage::new(message.clone(), metadata.clone());
                                             let full_msg_event = SolidifierEvent::Message(full_message);
-                                            let _ = solidifier_handle.send(full_msg_event);
+                                            let _ = solidifier_handle.send(full_msg_event).await;
                                         }
                                         // make sure to insert the message if it's requested from syncer
                                         if created_by == CreatedBy::Syncer {
@@ -228,7 +233,8 @@ impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Collector {
                                                         solidifier_id,
                                                         message_id,
                                                         try_ms_index,
-                                                    );
+                                                    )
+                                                    .await;
                                                 } else {
                                                     // overwrite pre_ms_index by try_ms_index, which it will be
                                                     // eventually processed;
@@ -236,7 +242,8 @@ impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Collector {
                                                     // close pre_ms_index(old_ms) as it's greater than what we have atm
                                                     // (try_ms_index).
                                                     let solidifier_id = (old_ms % (self.collector_count as u32)) as u8;
-                                                    self.push_close_to_solidifier(solidifier_id, message_id, old_ms);
+                                                    self.push_close_to_solidifier(solidifier_id, message_id, old_ms)
+                                                        .await;
                                                 }
                                             } else {
                                                 // add it to back_pressured requests
@@ -298,11 +305,13 @@ impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Collector {
 
 impl Collector {
     /// Send an error event to the solidifier for a given milestone index
-    fn send_err_solidifiy(&self, try_ms_index: u32) {
+    async fn send_err_solidifiy(&self, try_ms_index: u32) {
         // inform solidifier
         let solidifier_id = (try_ms_index % (self.collector_count as u32)) as u8;
         let solidifier_handle = self.solidifier_handles.get(&solidifier_id).unwrap();
-        let _ = solidifier_handle.send(SolidifierEvent::Solidify(Err(try_ms_index)));
+        let _ = solidifier_handle
+            .send(SolidifierEvent::Solidify(Err(try_ms_index)))
+            .await;
     }
     /// Process the pending requests for a given milestone index
     fn process_pending_requests(&mut self, milestone_index: u32) {
@@ -374,19 +383,19 @@ impl Collector {
         Ok(())
     }
     /// Push the full message (both the message and message metadata) to a given partiion of the solidifier
-    fn push_fullmsg_to_solidifier(&self, partition_id: u8, message: Message, metadata: MessageMetadata) {
+    async fn push_fullmsg_to_solidifier(&self, partition_id: u8, message: Message, metadata: MessageMetadata) {
         if let Some(solidifier_handle) = self.solidifier_handles.get(&partition_id) {
             let full_message = FullMessage::new(message, metadata);
             let full_msg_event = SolidifierEvent::Message(full_message);
-            let _ = solidifier_handle.send(full_msg_event);
+            let _ = solidifier_handle.send(full_msg_event).await;
         };
     }
     /// Push a `Close` message_id (which doesn't belong to all solidifiers with a given milestone index) to the
     /// solidifier
-    fn push_close_to_solidifier(&self, partition_id: u8, message_id: MessageId, try_ms_index: u32) {
+    async fn push_close_to_solidifier(&self, partition_id: u8, message_id: MessageId, try_ms_index: u32) {
         if let Some(solidifier_handle) = self.solidifier_handles.get(&partition_id) {
             let full_msg_event = SolidifierEvent::Close(message_id, try_ms_index);
-            let _ = solidifier_handle.send(full_msg_event);
+            let _ = solidifier_handle.send(full_msg_event).await;
         };
     }
     /// Get the `Chronicle` keyspace of a message
@@ -405,188 +414,82 @@ impl Collector {
     }
     /// Insert the message id and message to the table
     fn insert_message(&mut self, message_id: &MessageId, message: &mut Message) -> anyhow::Result<()> {
-        // Check if metadata already exist in the cache
-        let ledger_inclusion_state;
-
         #[cfg(feature = "filter")]
         let keyspace = self.get_keyspace_for_message(message);
         #[cfg(not(feature = "filter"))]
         let keyspace = self.get_keyspace();
-        let metadata;
-        if let Some(meta) = self.lru_msg_ref.get(message_id) {
-            metadata = Some(meta.clone());
-            ledger_inclusion_state = meta.ledger_inclusion_state.clone();
+        // Check if metadata already exist in the cache
+        if let Some(meta) = self.lru_msg_ref.get(message_id).cloned() {
             let milestone_index = MilestoneIndex(*meta.referenced_by_milestone_index.as_ref().unwrap());
             let solidifier_id = (*milestone_index % (self.collector_count as u32)) as u8;
             let solidifier_handle = self.solidifier_handles.get(&solidifier_id).unwrap().clone();
             let inherent_worker =
                 AtomicWorker::new(solidifier_handle, *milestone_index, *message_id, self.retries_per_query);
-            let message_tuple = (message.clone(), meta.clone());
-            // store message and metadata
-            self.insert(&inherent_worker, &keyspace, *message_id, message_tuple)?;
-            // Insert parents/children
-            self.insert_parents(
+            // store message and metadata along with its index rows
+            self.insert_indexed(
                 &inherent_worker,
-                &message_id,
-                &message.parents(),
+                &keyspace,
+                message_id,
+                message,
                 milestone_index,
-                ledger_inclusion_state.clone(),
-            )?;
-            // insert payload (if any)
-            if let Some(payload) = message.payload() {
-                self.insert_payload(
-                    &inherent_worker,
-                    &message_id,
-                    &message,
-                    &payload,
-                    milestone_index,
-                    ledger_inclusion_state,
-                    metadata,
-                )?;
-            }
+                Some(&meta),
+            )
         } else {
-            metadata = None;
-            ledger_inclusion_state = None;
             let inherent_worker = SimpleWorker {
                 retries: self.retries_per_query,
             };
-            // store message only
-            self.insert(&inherent_worker, &keyspace, *message_id, message.clone())?;
-            // Insert parents/children
-            self.insert_parents(
-                &inherent_worker,
-                &message_id,
-                &message.parents(),
-                self.est_ms,
-                ledger_inclusion_state.clone(),
-            )?;
-            // insert payload (if any)
-            if let Some(payload) = message.payload() {
-                self.insert_payload(
-                    &inherent_worker,
-                    &message_id,
-                    &message,
-                    &payload,
-                    self.est_ms,
-                    ledger_inclusion_state,
-                    metadata,
-                )?;
-            }
-        };
-        Ok(())
-    }
-    /// Insert the parents' message ids of a given message id to the table
-    fn insert_parents<I: Inherent>(
-        &self,
-        inherent_worker: &I,
-        message_id: &MessageId,
-        parents: &[MessageId],
-        milestone_index: MilestoneIndex,
-        inclusion_state: Option<LedgerInclusionState>,
-    ) -> anyhow::Result<()> {
-        let partition_id = self.get_partition_id(milestone_index);
-        for parent_id in parents {
-            let partitioned = Partitioned::new(*parent_id, partition_id, milestone_index.0);
-            let parent_record = ParentRecord::new(*message_id, inclusion_state);
-            self.insert(inherent_worker, &self.get_keyspace(), partitioned, parent_record)?;
-            // insert hint record
-            let hint = Hint::parent(parent_id.to_string());
-            let partition = Partition::new(partition_id, *milestone_index);
-            self.insert(inherent_worker, &self.get_keyspace(), hint, partition)?
+            // store message only along with its index rows
+            self.insert_indexed(&inherent_worker, &keyspace, message_id, message, self.est_ms, None)
         }
-        Ok(())
     }
-    // NOT complete, TODO finish it.
-    fn insert_payload<I: Inherent>(
-        &mut self,
+    /// Insert the storage writes of a message, the `messages` table row is inserted into the given keyspace.
+    /// A referenced milestone message is pushed to the right solidifier too.
+    fn insert_indexed<I: Inherent>(
+        &self,
         inherent_worker: &I,
+        keyspace: &ChronicleKeyspace,
         message_id: &MessageId,
         message: &Message,
-        payload: &Payload,
         milestone_index: MilestoneIndex,
-        inclusion_state: Option<LedgerInclusionState>,
-        metadata: Option<MessageMetadata>,
+        metadata: Option<&MessageMetadata>,
     ) -> anyhow::Result<()> {
-        match payload {
-            Payload::Indexation(indexation) => {
-                self.insert_index(
-                    inherent_worker,
-                    message_id,
-                    Indexation(hex::encode(indexation.index())),
-                    milestone_index,
-                    inclusion_state,
-                )?;
+        for row in self.indexer.index(message_id, message, milestone_index, metadata)? {
+            if let IndexRow::Milestone(ms_index, (_, milestone)) = &row {
+                // push to the right solidifier
+                let solidifier_id = (ms_index.0 % (self.collector_count as u32)) as u8;
+                if let Some(solidifier_handle) = self.solidifier_handles.get(&solidifier_id) {
+                    let ms_message =
+                        MilestoneMessage::new(*message_id, milestone.clone(), message.clone(), metadata.cloned());
+                    // a shed milestone is left unsynced, so the syncer solidifies it later
+                    solidifier_handle.send_or_defer(SolidifierEvent::Milestone(ms_message));
+                };
             }
-            Payload::Transaction(transaction) => self.insert_transaction(
+            let keyspace = if matches!(row, IndexRow::Message(..)) {
+                keyspace
+            } else {
+                &self.default_keyspace
+            };
+            row.insert(&KeyspaceInserter {
                 inherent_worker,
-                message_id,
-                message,
-                transaction,
-                inclusion_state,
-                milestone_index,
-                metadata,
-            )?,
-            Payload::Milestone(milestone) => {
-                let ms_index = milestone.essence().index();
-                let parents_check = message.parents().eq(milestone.essence().parents());
-                if metadata.is_some() && parents_check {
-                    // push to the right solidifier
-                    let solidifier_id = (ms_index.0 % (self.collector_count as u32)) as u8;
-                    if let Some(solidifier_handle) = self.solidifier_handles.get(&solidifier_id) {
-                        let ms_message =
-                            MilestoneMessage::new(*message_id, milestone.clone(), message.clone(), metadata);
-                        let _ = solidifier_handle.send(SolidifierEvent::Milestone(ms_message));
-                    };
-                    self.insert(
-                        inherent_worker,
-                        &self.get_keyspace(),
-                        ms_index,
-                        (*message_id, milestone.clone()),
-                    )?
-                }
-            }
-            // remaining payload types
-            e => {
-                warn!("Skipping unsupported payload variant: {:?}", e);
-            }
+                keyspace,
+            })?;
         }
         Ok(())
     }
-    /// Insert the `Indexation` of a given message id to the table
-    fn insert_index<I: Inherent>(
-        &self,
-        inherent_worker: &I,
-        message_id: &MessageId,
-        index: Indexation,
-        milestone_index: MilestoneIndex,
-        inclusion_state: Option<LedgerInclusionState>,
-    ) -> anyhow::Result<()> {
-        let partition_id = self.get_partition_id(milestone_index);
-        let partitioned = Partitioned::new(index.clone(), partition_id, milestone_index.0);
-        let index_record = IndexationRecord::new(*message_id, inclusion_state);
-        self.insert(inherent_worker, &self.get_keyspace(), partitioned, index_record)?;
-        // insert hint record
-        let hint = Hint::index(index.0);
-        let partition = Partition::new(partition_id, *milestone_index);
-        self.insert(inherent_worker, &self.get_keyspace(), hint, partition)
-    }
     /// Insert the message metadata to the table
     fn insert_message_metadata(&self, metadata: MessageMetadata) -> anyhow::Result<()> {
-        let message_id = metadata.message_id;
         let inherent_worker = SimpleWorker {
             retries: self.retries_per_query,
         };
-        // store message and metadata
-        self.insert(&inherent_worker, &self.get_keyspace(), message_id, metadata.clone())?;
-        // Insert parents/children
-        let parents = metadata.parent_message_ids;
-        self.insert_parents(
-            &inherent_worker,
-            &message_id,
-            &parents.as_slice(),
-            self.ref_ms,
-            metadata.ledger_inclusion_state.clone(),
-        )
+        let inserter = KeyspaceInserter {
+            inherent_worker: &inherent_worker,
+            keyspace: &self.default_keyspace,
+        };
+        // store metadata along with the parents/children
+        for row in self.indexer.index_metadata(&metadata, self.ref_ms) {
+            row.insert(&inserter)?;
+        }
+        Ok(())
     }
     /// Insert the message with the associated metadata of a given message id to the table
     #[allow(unused_mut)]
@@ -602,238 +505,14 @@ impl Collector {
         let keyspace = self.get_keyspace();
         let solidifier_handle = self.clone_solidifier_handle(*self.ref_ms);
         let inherent_worker = AtomicWorker::new(solidifier_handle, *self.ref_ms, message_id, self.retries_per_query);
-        // Insert parents/children
-        self.insert_parents(
+        self.insert_indexed(
             &inherent_worker,
+            &keyspace,
             &message_id,
-            &message.parents(),
+            &message,
             self.ref_ms,
-            metadata.ledger_inclusion_state.clone(),
-        )?;
-        // insert payload (if any)
-        if let Some(payload) = message.payload() {
-            self.insert_payload(
-                &inherent_worker,
-                &message_id,
-                &message,
-                &payload,
-                self.ref_ms,
-                metadata.ledger_inclusion_state.clone(),
-                Some(metadata.clone()),
-            )?;
-        }
-        let message_tuple = (message, metadata);
-        // store message and metadata
-        self.insert(&inherent_worker, &keyspace, message_id, message_tuple)
-    }
-    /// Insert the transaction to the table
-    fn insert_transaction<I: Inherent>(
-        &mut self,
-        inherent_worker: &I,
-        message_id: &MessageId,
-        message: &Message,
-        transaction: &Box<TransactionPayload>,
-        ledger_inclusion_state: Option<LedgerInclusionState>,
-        milestone_index: MilestoneIndex,
-        metadata: Option<MessageMetadata>,
-    ) -> anyhow::Result<()> {
-        let transaction_id = transaction.id();
-        let unlock_blocks = transaction.unlock_blocks();
-        let confirmed_milestone_index;
-        if ledger_inclusion_state.is_some() {
-            confirmed_milestone_index = Some(milestone_index);
-        } else {
-            confirmed_milestone_index = None;
-        }
-        let Essence::Regular(regular) = transaction.essence();
-        {
-            for (input_index, input) in regular.inputs().iter().enumerate() {
-                // insert utxoinput row along with input row
-                if let Input::Utxo(utxo_input) = input {
-                    let unlock_block = &unlock_blocks[input_index];
-                    let input_data = InputData::utxo(utxo_input.clone(), unlock_block.clone());
-                    // insert input row
-                    self.insert_input(
-                        inherent_worker,
-                        message_id,
-                        &transaction_id,
-                        input_index as u16,
-                        input_data,
-                        ledger_inclusion_state,
-                        confirmed_milestone_index,
-                    )?;
-                    // this is the spent_output which the input is spending from
-                    let output_id = utxo_input.output_id();
-                    // therefore we insert utxo_input.output_id() -> unlock_block to indicate that this output is_spent;
-                    let unlock_data = UnlockData::new(transaction_id, input_index as u16, unlock_block.clone());
-                    self.insert_unlock(
-                        inherent_worker,
-                        &message_id,
-                        output_id.transaction_id(),
-                        output_id.index(),
-                        unlock_data,
-                        ledger_inclusion_state,
-                        confirmed_milestone_index,
-                    )?;
-                } else if let Input::Treasury(treasury_input) = input {
-                    let input_data = InputData::treasury(treasury_input.clone());
-                    // insert input row
-                    self.insert_input(
-                        inherent_worker,
-                        message_id,
-                        &transaction_id,
-                        input_index as u16,
-                        input_data,
-                        ledger_inclusion_state,
-                        confirmed_milestone_index,
-                    )?;
-                } else {
-                    error!("A new input variant was added to this type!")
-                }
-            }
-            for (output_index, output) in regular.outputs().iter().enumerate() {
-                // insert output row
-                self.insert_output(
-                    inherent_worker,
-                    message_id,
-                    &transaction_id,
-                    output_index as u16,
-                    output.clone(),
-                    ledger_inclusion_state,
-                    confirmed_milestone_index,
-                )?;
-                // insert address row
-                self.insert_address(
-                    inherent_worker,
-                    output,
-                    &transaction_id,
-                    output_index as u16,
-                    milestone_index,
-                    ledger_inclusion_state,
-                )?;
-            }
-            if let Some(payload) = regular.payload() {
-                self.insert_payload(
-                    inherent_worker,
-                    message_id,
-                    message,
-                    payload,
-                    milestone_index,
-                    ledger_inclusion_state,
-                    metadata,
-                )?
-            }
-        };
-        Ok(())
-    }
-    /// Insert the `InputData` to the table
-    fn insert_input<I: Inherent>(
-        &self,
-        inherent_worker: &I,
-        message_id: &MessageId,
-        transaction_id: &TransactionId,
-        index: u16,
-        input_data: InputData,
-        inclusion_state: Option<LedgerInclusionState>,
-        milestone_index: Option<MilestoneIndex>,
-    ) -> anyhow::Result<()> {
-        // -input variant: (InputTransactionId, InputIndex) -> UTXOInput data column
-        let input_id = (*transaction_id, index);
-        let transaction_record = TransactionRecord::input(*message_id, input_data, inclusion_state, milestone_index);
-        self.insert(inherent_worker, &self.get_keyspace(), input_id, transaction_record)
-    }
-    /// Insert the `UnlockData` to the table
-    fn insert_unlock<I: Inherent>(
-        &self,
-        inherent_worker: &I,
-        message_id: &MessageId,
-        utxo_transaction_id: &TransactionId,
-        utxo_index: u16,
-        unlock_data: UnlockData,
-        inclusion_state: Option<LedgerInclusionState>,
-        milestone_index: Option<MilestoneIndex>,
-    ) -> anyhow::Result<()> {
-        // -unlock variant: (UtxoInputTransactionId, UtxoInputOutputIndex) -> Unlock data column
-        let utxo_id = (*utxo_transaction_id, utxo_index);
-        let transaction_record = TransactionRecord::unlock(*message_id, unlock_data, inclusion_state, milestone_index);
-        self.insert(inherent_worker, &self.get_keyspace(), utxo_id, transaction_record)
-    }
-    /// Insert the `Output` to the table
-    fn insert_output<I: Inherent>(
-        &self,
-        inherent_worker: &I,
-        message_id: &MessageId,
-        transaction_id: &TransactionId,
-        index: u16,
-        output: Output,
-        inclusion_state: Option<LedgerInclusionState>,
-        milestone_index: Option<MilestoneIndex>,
-    ) -> anyhow::Result<()> {
-        // -output variant: (OutputTransactionId, OutputIndex) -> Output data column
-        let output_id = (*transaction_id, index);
-        let transaction_record = TransactionRecord::output(*message_id, output, inclusion_state, milestone_index);
-        self.insert(inherent_worker, &self.get_keyspace(), output_id, transaction_record)
-    }
-    /// Insert the `Address` to the table
-    fn insert_address<I: Inherent>(
-        &self,
-        inherent_worker: &I,
-        output: &Output,
-        transaction_id: &TransactionId,
-        index: u16,
-        milestone_index: MilestoneIndex,
-        inclusion_state: Option<LedgerInclusionState>,
-    ) -> anyhow::Result<()> {
-        let partition_id = self.get_partition_id(milestone_index);
-        let output_type = output.kind();
-        match output {
-            Output::SignatureLockedSingle(sls) => {
-                let Address::Ed25519(ed_address) = sls.address();
-                {
-                    let partitioned = Partitioned::new(*ed_address, partition_id, milestone_index.0);
-                    let address_record =
-                        AddressRecord::new(output_type, *transaction_id, index, sls.amount(), inclusion_state);
-                    self.insert(inherent_worker, &self.get_keyspace(), partitioned, address_record)?;
-                    // insert hint record
-                    let hint = Hint::address(ed_address.to_string());
-                    let partition = Partition::new(partition_id, *milestone_index);
-                    self.insert(inherent_worker, &self.get_keyspace(), hint, partition)
-                }
-            }
-            Output::SignatureLockedDustAllowance(slda) => {
-                let Address::Ed25519(ed_address) = slda.address();
-                {
-                    let partitioned = Partitioned::new(*ed_address, partition_id, milestone_index.0);
-                    let address_record =
-                        AddressRecord::new(output_type, *transaction_id, index, slda.amount(), inclusion_state);
-                    self.insert(inherent_worker, &self.get_keyspace(), partitioned, address_record)?;
-                    // insert hint record
-                    let hint = Hint::address(ed_address.to_string());
-                    let partition = Partition::new(partition_id, *milestone_index);
-                    self.insert(inherent_worker, &self.get_keyspace(), hint, partition)
-                }
-            }
-            e => {
-                if let Output::Treasury(_) = e {
-                    Ok(())
-                } else {
-                    bail!("Unexpected new output variant {:?}", e);
-                }
-            }
-        }
-    }
-    /// The low-level insert function to insert a key/value pair through an inherent worker
-    fn insert<I, S, K, V>(&self, inherent_worker: &I, keyspace: &S, key: K, value: V) -> anyhow::Result<()>
-    where
-        I: Inherent,
-        S: 'static + Insert<K, V>,
-        K: 'static + Send + Sync + Clone,
-        V: 'static + Send + Sync + Clone,
-    {
-        let insert_req = keyspace.insert(&key, &value).consistency(Consistency::One).build()?;
-        let worker = inherent_worker.inherent_boxed(keyspace.clone(), key, value);
-        insert_req.send_local(worker);
-        Ok(())
+            Some(&metadata),
+        )
     }
     /// Delete the `Parents` of a given message id in the table
     fn delete_parents(
@@ -971,35 +650,26 @@ pub struct SimpleWorker {
     retries: usize,
 }
 
-/// The inherent trait to return a boxed worker for a given key/value pair in a keyspace
-trait Inherent {
-    fn inherent_boxed<S, K, V>(&self, keyspace: S, key: K, value: V) -> Box<dyn Worker>
-    where
-        S: 'static + Insert<K, V>,
-        K: 'static + Send + Sync + Clone,
-        V: 'static + Send + Sync + Clone;
-}
-
 /// Implement the `Inherent` trait for the simple worker
 impl Inherent for SimpleWorker {
-    fn inherent_boxed<S, K, V>(&self, keyspace: S, key: K, value: V) -> Box<dyn Worker>
+    fn inherent_boxed<K, V>(&self, keyspace: &ChronicleKeyspace, key: K, value: V) -> Box<dyn Worker>
     where
-        S: 'static + Insert<K, V>,
+        ChronicleKeyspace: 'static + Insert<K, V>,
         K: 'static + Send + Sync + Clone,
         V: 'static + Send + Sync + Clone,
     {
-        InsertWorker::boxed(keyspace, key, value, self.retries)
+        InsertWorker::boxed(keyspace.clone(), key, value, self.retries)
     }
 }
 
 /// Implement the `Inherent` trait for the atomic solidifier worker
 impl Inherent for AtomicWorker {
-    fn inherent_boxed<S, K, V>(&self, keyspace: S, key: K, value: V) -> Box<dyn Worker>
+    fn inherent_boxed<K, V>(&self, keyspace: &ChronicleKeyspace, key: K, value: V) -> Box<dyn Worker>
     where
-        S: 'static + Insert<K, V>,
+        ChronicleKeyspace: 'static + Insert<K, V>,
         K: 'static + Send + Sync + Clone,
         V: 'static + Send + Sync + Clone,
     {
-        AtomicSolidifierWorker::boxed(self.arc_handle.clone(), keyspace, key, value, self.retries)
+        AtomicSolidifierWorker::boxed(self.arc_handle.clone(), keyspace.clone(), key, value, self.retries)
     }
 }
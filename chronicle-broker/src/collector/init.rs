@@ -19,13 +19,45 @@ impl<H: ChronicleBrokerScope> Init<BrokerHandle<H>> for Collector {
             .expect("Collector expected BrokerHandle")
             .send(event);
         self.spawn_requester();
+        self.restore_snapshot().await;
         status
     }
 }
 
 impl Collector {
+    /// Restore the caches from the snapshot of the previous graceful shutdown (if any)
+    async fn restore_snapshot(&mut self) {
+        if let Some(dir) = self.snapshot_dir.as_ref() {
+            match snapshot::take::<CollectorSnapshot>(dir, &self.get_name()).await {
+                Ok(Some(snapshot)) => {
+                    let (collector_count, est_ms, ref_ms) =
+                        (snapshot.collector_count, snapshot.est_ms, snapshot.ref_ms);
+                    let (messages, metadata) = (snapshot.messages.len(), snapshot.metadata.len());
+                    if !snapshot.restore(self.collector_count, &mut self.lru_msg, &mut self.lru_msg_ref) {
+                        warn!(
+                            "{} ignored its snapshot, as it was taken with collector count: {}",
+                            self.get_name(),
+                            collector_count
+                        );
+                        return;
+                    }
+                    info!(
+                        "{} restored {} messages and {} metadata from its snapshot",
+                        self.get_name(),
+                        messages,
+                        metadata
+                    );
+                    self.est_ms = MilestoneIndex(est_ms);
+                    self.ref_ms = MilestoneIndex(ref_ms);
+                }
+                Ok(None) => (),
+                Err(e) => error!("{} unable to restore its snapshot: {}", self.get_name(), e),
+            }
+        }
+    }
     /// Spawn a number of collector requester handles
     fn spawn_requester(&mut self) {
+        let sync_status = SyncStatus::of(self.default_keyspace.name());
         for id in 0..self.requester_count {
             let (tx, rx) = self.requesters_channels.pop().expect("Expected requester channels");
             let inbox = RequesterInbox { rx };
@@ -34,8 +66,11 @@ impl Collector {
                 .inbox(inbox)
                 .requester_id(id)
                 .api_endpoints(self.api_endpoints.iter().cloned().collect())
+                .chronicle_endpoints(self.chronicle_endpoints.clone())
+                .peer_cones(self.peer_cones.clone())
                 .retries_per_endpoint(self.retries_per_endpoint)
                 .reqwest_client(reqwest_client)
+                .sync_status(sync_status.clone())
                 .build();
             let (abort_handle, abort_registration) = futures::future::AbortHandle::new_pair();
             let handle = RequesterHandle {
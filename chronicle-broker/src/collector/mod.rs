@@ -3,10 +3,20 @@
 use super::*;
 use crate::{
     application::*,
+    indexer::{
+        IndexRow,
+        Inherent,
+        KeyspaceInserter,
+        MessageIndexer,
+    },
+    peer::PeerCones,
     requester::*,
+    snapshot::{
+        self,
+        CollectorSnapshot,
+    },
     solidifier::*,
 };
-use anyhow::bail;
 use bee_message::{
     output::Output,
     payload::transaction::{
@@ -17,6 +27,7 @@ use bee_message::{
 };
 use std::collections::{
     BinaryHeap,
+    HashSet,
     VecDeque,
 };
 
@@ -43,13 +54,18 @@ builder!(CollectorBuilder {
     solidifier_handles: HashMap<u8, SolidifierHandle>,
     reqwest_client: Client,
     api_endpoints: VecDeque<Url>,
+    chronicle_endpoints: HashSet<Url>,
+    peer_cones: PeerCones,
     collector_count: u8,
     requester_count: u8,
     retries_per_query: usize,
     retries_per_endpoint: usize,
     requesters_channels: Vec<(RequesterSender, RequesterReceiver)>,
     handle: CollectorHandle,
-    storage_config: StorageConfig
+    storage_config: StorageConfig,
+    keyspace: ChronicleKeyspace,
+    network: String,
+    snapshot_dir: PathBuf
 });
 
 /// Collector events
@@ -83,7 +99,7 @@ pub enum AskCollector {
 /// CollectorHandle to be passed to siblings(feed sources) and the supervisor(in order to shutdown)
 #[derive(Clone)]
 pub struct CollectorHandle {
-    pub(crate) tx: tokio::sync::mpsc::UnboundedSender<CollectorEvent>,
+    pub(crate) tx: BoundedSender<CollectorEvent>,
     pub(crate) requesters_senders: Vec<RequesterSender>,
 }
 
@@ -109,10 +125,10 @@ impl MessageIdPartitioner {
 }
 /// CollectorInbox is used to recv events
 pub struct CollectorInbox {
-    pub(crate) rx: tokio::sync::mpsc::UnboundedReceiver<CollectorEvent>,
+    pub(crate) rx: BoundedReceiver<CollectorEvent>,
 }
 impl Deref for CollectorHandle {
-    type Target = tokio::sync::mpsc::UnboundedSender<CollectorEvent>;
+    type Target = BoundedSender<CollectorEvent>;
 
     fn deref(&self) -> &Self::Target {
         &self.tx
@@ -126,7 +142,7 @@ impl DerefMut for CollectorHandle {
 }
 
 impl Deref for CollectorInbox {
-    type Target = tokio::sync::mpsc::UnboundedReceiver<CollectorEvent>;
+    type Target = BoundedReceiver<CollectorEvent>;
 
     fn deref(&self) -> &Self::Target {
         &self.rx
@@ -145,7 +161,7 @@ impl Shutdown for CollectorHandle {
         Self: Sized,
     {
         let shutdown_event = CollectorEvent::Internal(Internal::Shutdown);
-        self.send(shutdown_event).ok();
+        self.send_control(shutdown_event);
         None
     }
 }
@@ -188,12 +204,22 @@ pub struct Collector {
     pending_requests: HashMap<MessageId, (u32, Message)>,
     /// The double ended queue stores the api endpoints
     api_endpoints: VecDeque<Url>,
+    /// The api endpoints which are peer Chronicle instances
+    chronicle_endpoints: HashSet<Url>,
+    /// The milestone cones fetched from the peer Chronicle instances, shared by the network requesters
+    peer_cones: PeerCones,
     /// The http client
     reqwest_client: Client,
     /// The partition configure
     partition_config: PartitionConfig,
+    /// The indexer of the collected messages
+    indexer: MessageIndexer,
     /// The `Chronicle` keyspace
     default_keyspace: ChronicleKeyspace,
+    /// The network this collector belongs to
+    network: String,
+    /// The optional directory to snapshot the caches on graceful shutdown
+    snapshot_dir: Option<PathBuf>,
 }
 
 impl<H: ChronicleBrokerScope> ActorBuilder<BrokerHandle<H>> for CollectorBuilder {}
@@ -203,20 +229,22 @@ impl Builder for CollectorBuilder {
     type State = Collector;
     fn build(self) -> Self::State {
         let lru_cap = self.lru_capacity.unwrap_or(10000);
-        // Get the first keyspace or default to "permanode"
+        // Get the network keyspace, otherwise the first keyspace or default to "permanode"
         // In order to use multiple keyspaces, the user must
         // use filters to determine where records go
-        let default_keyspace = ChronicleKeyspace::new(
-            self.storage_config
-                .as_ref()
-                .and_then(|config| {
-                    config
-                        .keyspaces
-                        .first()
-                        .and_then(|keyspace| Some(keyspace.name.clone()))
-                })
-                .unwrap_or("permanode".to_owned()),
-        );
+        let default_keyspace = self.keyspace.unwrap_or_else(|| {
+            ChronicleKeyspace::new(
+                self.storage_config
+                    .as_ref()
+                    .and_then(|config| {
+                        config
+                            .keyspaces
+                            .first()
+                            .and_then(|keyspace| Some(keyspace.name.clone()))
+                    })
+                    .unwrap_or("permanode".to_owned()),
+            )
+        });
         let partition_config = self
             .storage_config
             .as_ref()
@@ -242,9 +270,14 @@ impl Builder for CollectorBuilder {
             inbox: self.inbox.unwrap(),
             pending_requests: HashMap::new(),
             api_endpoints: self.api_endpoints.unwrap(),
+            chronicle_endpoints: self.chronicle_endpoints.unwrap_or_default(),
+            peer_cones: self.peer_cones.unwrap_or_default(),
             reqwest_client: self.reqwest_client.unwrap(),
+            indexer: MessageIndexer::new(partition_config.clone()),
             partition_config,
             default_keyspace,
+            network: self.network.unwrap_or_default(),
+            snapshot_dir: self.snapshot_dir,
         }
         .set_name()
     }
@@ -253,7 +286,7 @@ impl Builder for CollectorBuilder {
 /// Implement the `Name` trait of the `Collector`
 impl Name for Collector {
     fn set_name(mut self) -> Self {
-        let name = format!("Collector_{}", self.partition_id);
+        let name = network_scoped_name(&self.network, &format!("Collector_{}", self.partition_id));
         self.service.update_name(name);
         self
     }
@@ -13,6 +13,29 @@ impl<H: ChronicleBrokerScope> Terminating<BrokerHandle<H>> for Collector {
         self.service.update_status(ServiceStatus::Stopping);
         let event = BrokerEvent::Children(BrokerChild::Collector(self.service.clone()));
         let _ = _supervisor.as_mut().unwrap().send(event);
+        if _status.is_ok() {
+            self.save_snapshot().await;
+        }
         _status
     }
 }
+
+impl Collector {
+    /// Snapshot the caches, so the next start doesn't persist or request the recent messages again
+    async fn save_snapshot(&mut self) {
+        if let Some(dir) = self.snapshot_dir.as_ref() {
+            let snapshot = CollectorSnapshot::capture(
+                self.collector_count,
+                self.est_ms.0,
+                self.ref_ms.0,
+                &self.lru_msg,
+                &self.lru_msg_ref,
+            );
+            if let Err(e) = snapshot::save(dir, &self.get_name(), &snapshot).await {
+                error!("{} unable to save its snapshot: {}", self.get_name(), e);
+            } else {
+                info!("{} saved its snapshot", self.get_name());
+            }
+        }
+    }
+}
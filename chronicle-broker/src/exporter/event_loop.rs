@@ -0,0 +1,114 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+
+#[async_trait::async_trait]
+impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Exporter {
+    async fn event_loop(
+        &mut self,
+        mut status: Result<(), Need>,
+        supervisor: &mut Option<BrokerHandle<H>>,
+    ) -> Result<(), Need> {
+        status?;
+        info!("{} is running", self.get_name());
+        if self.export_range.is_empty() {
+            warn!("Skipped empty export range: {}", self.get_name());
+            return Ok(());
+        }
+        self.service.update_status(ServiceStatus::Running);
+        let event = BrokerEvent::Children(BrokerChild::Exporter(self.service.clone(), status));
+        if let Some(supervisor) = supervisor {
+            supervisor.send(event).ok();
+            while let Some(event) = self.inbox.recv().await {
+                match event {
+                    ExporterEvent::MilestoneData(milestone_index, result) => match result {
+                        Ok(milestone_data) => {
+                            self.fetched.insert(milestone_index, milestone_data);
+                            if self.handle_fetched(supervisor).await? {
+                                return Ok(());
+                            }
+                        }
+                        Err(e) => {
+                            // an outage in scylla so we abort
+                            error!(
+                                "Unable to reconstruct milestone index {}. Error: {}",
+                                milestone_index, e
+                            );
+                            return Err(Need::Abort);
+                        }
+                    },
+                    ExporterEvent::Shutdown => {
+                        self.service.update_status(ServiceStatus::Stopping);
+                        self.handle.take();
+                        status = Err(Need::Abort);
+                        break;
+                    }
+                }
+            }
+            status
+        } else {
+            Err(Need::Abort)
+        }
+    }
+}
+
+impl Exporter {
+    /// Write the fetched milestones in order and fetch the next ones, returning whether the whole range got exported
+    async fn handle_fetched<H: ChronicleBrokerScope>(&mut self, supervisor: &BrokerHandle<H>) -> Result<bool, Need> {
+        while let Some(milestone_data) = self.fetched.remove(&self.next_to_write) {
+            let milestone_index = self.next_to_write;
+            self.next_to_write += 1;
+            let exported = milestone_data.is_some();
+            if let Some(milestone_data) = milestone_data {
+                let finished = self.writer.write(&milestone_data).await.map_err(|e| {
+                    error!("Unable to export milestone index {}. Error: {}", milestone_index, e);
+                    Need::Abort
+                })?;
+                if let Some(path) = finished {
+                    supervisor
+                        .send(BrokerEvent::Exporter(ExporterSession::LogFile { path }))
+                        .ok();
+                }
+                self.exported += 1;
+            } else {
+                // a log file only holds contiguous milestones, so the gap ends it
+                warn!("Unable to reconstruct milestone index: {}", milestone_index);
+                self.missing.add(milestone_index);
+                self.finalize(supervisor).await?;
+            }
+            let exporter_session = ExporterSession::Progress {
+                from_ms: self.export_range.start,
+                to_ms: self.export_range.end,
+                milestone_index,
+                exported,
+            };
+            supervisor.send(BrokerEvent::Exporter(exporter_session)).ok();
+            if self.next_to_fetch < self.export_range.end {
+                self.fetch_next().map_err(|e| {
+                    error!("Unable to fetch milestone data. Error: {}", e);
+                    Need::Abort
+                })?;
+            }
+        }
+        if self.next_to_write >= self.export_range.end {
+            info!("Exported the milestone range: {}", self.get_name());
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Finalize the log file being written and report it
+    pub(crate) async fn finalize<H: ChronicleBrokerScope>(&mut self, supervisor: &BrokerHandle<H>) -> Result<(), Need> {
+        let path = self.writer.finish().await.map_err(|e| {
+            error!("Unable to finalize the exported log file. Error: {}", e);
+            Need::Abort
+        })?;
+        if let Some(path) = path {
+            supervisor
+                .send(BrokerEvent::Exporter(ExporterSession::LogFile { path }))
+                .ok();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+
+#[async_trait::async_trait]
+impl<H: ChronicleBrokerScope> Init<BrokerHandle<H>> for Exporter {
+    async fn init(&mut self, status: Result<(), Need>, supervisor: &mut Option<BrokerHandle<H>>) -> Result<(), Need> {
+        info!(
+            "{} is Initializing, with permanode keyspace: {}",
+            self.get_name(),
+            self.keyspace.name()
+        );
+        if let Some(supervisor) = supervisor {
+            self.service.update_status(ServiceStatus::Initializing);
+            let event = BrokerEvent::Children(BrokerChild::Exporter(self.service.clone(), Ok(())));
+            supervisor.send(event).ok();
+            create_export_dir(self.writer.dir_path()).await.map_err(|e| {
+                error!("{}", e);
+                Need::Abort
+            })?;
+            for _ in 0..self.parallelism {
+                if self.next_to_fetch >= self.export_range.end {
+                    break;
+                }
+                self.fetch_next().map_err(|e| {
+                    error!("Unable to fetch milestone data. Error: {}", e);
+                    Need::Abort
+                })?;
+            }
+            status
+        } else {
+            Err(Need::Abort)
+        }
+    }
+}
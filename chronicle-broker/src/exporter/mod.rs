@@ -0,0 +1,329 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use crate::{
+    application::{
+        BrokerChild,
+        BrokerEvent,
+        BrokerHandle,
+        ChronicleBrokerScope,
+    },
+    archive::{
+        manifest::Manifest,
+        ArchiveFormat,
+        ArchiveRecord,
+        LogEncoder,
+        BLOCK_MILESTONES,
+        DEFAULT_COMPRESSION_LEVEL,
+    },
+    archiver::MAX_LOG_SIZE,
+};
+use std::{
+    collections::BTreeMap,
+    ops::Range,
+    path::Path,
+};
+use tokio::{
+    fs::File,
+    io::AsyncWriteExt,
+};
+
+mod event_loop;
+mod init;
+mod terminating;
+
+// Exporter builder
+builder!(ExporterBuilder {
+    dir_path: PathBuf,
+    export_range: Range<u32>,
+    keyspace: ChronicleKeyspace,
+    max_log_size: u64,
+    compression_level: i32,
+    retries_per_query: usize,
+    parallelism: u8
+});
+
+/// Exporter events
+pub enum ExporterEvent {
+    /// The milestone data reconstructed from the database, if the milestone could be reconstructed
+    MilestoneData(u32, anyhow::Result<Option<MilestoneData>>),
+    /// Shutdown the exporter
+    Shutdown,
+}
+
+/// ExporterHandle to be passed to the supervisor in order to shutdown
+#[derive(Clone)]
+pub struct ExporterHandle {
+    pub(crate) tx: BoundedSender<ExporterEvent>,
+}
+
+/// ExporterInbox is used to recv events
+pub struct ExporterInbox {
+    pub(crate) rx: BoundedReceiver<ExporterEvent>,
+}
+impl Deref for ExporterHandle {
+    type Target = BoundedSender<ExporterEvent>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tx
+    }
+}
+
+impl DerefMut for ExporterHandle {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.tx
+    }
+}
+
+impl Deref for ExporterInbox {
+    type Target = BoundedReceiver<ExporterEvent>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.rx
+    }
+}
+
+impl DerefMut for ExporterInbox {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.rx
+    }
+}
+
+impl Shutdown for ExporterHandle {
+    fn shutdown(self) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        self.send_control(ExporterEvent::Shutdown);
+        None
+    }
+}
+
+/// The log file being exported, which is named after its first milestone until it's finalized
+struct ExportLog {
+    start: u32,
+    end: u32,
+    path: PathBuf,
+    file: File,
+    encoder: LogEncoder,
+}
+
+/// The writer of the exported log files, which holds contiguous milestones up to the log file size limit
+pub struct ExportWriter {
+    /// The directory of the exported log files
+    dir_path: PathBuf,
+    /// The upper limit of the exported log file size
+    max_log_size: u64,
+    /// The format of the exported log files
+    format: ArchiveFormat,
+    compression_level: i32,
+    /// The log file being written
+    log: Option<ExportLog>,
+}
+
+/// Exporter state
+pub struct Exporter {
+    /// The exporter service
+    service: Service,
+    /// The milestone range to export
+    export_range: Range<u32>,
+    /// The keyspace the milestone data is reconstructed from
+    keyspace: ChronicleKeyspace,
+    /// The writer of the exported log files
+    writer: ExportWriter,
+    /// The number of retires per query
+    retries_per_query: usize,
+    /// The number of milestones, and messages per milestone, fetched concurrently
+    parallelism: u8,
+    /// The next milestone index to fetch
+    next_to_fetch: u32,
+    /// The next milestone index to write
+    next_to_write: u32,
+    /// The fetched milestones data, which are written in order
+    fetched: BTreeMap<u32, Option<MilestoneData>>,
+    /// The number of exported milestones
+    exported: u64,
+    /// The milestones which couldn't be reconstructed
+    missing: MilestoneRanges,
+    /// The exporter handle
+    handle: Option<ExporterHandle>,
+    /// The exporter inbox to receive events
+    inbox: ExporterInbox,
+}
+
+impl<H: ChronicleBrokerScope> ActorBuilder<BrokerHandle<H>> for ExporterBuilder {}
+
+/// Implementation of builder
+impl Builder for ExporterBuilder {
+    type State = Exporter;
+    fn build(self) -> Self::State {
+        let config = chronicle_common::get_config();
+        let (tx, rx) = bounded_channel(config.broker_config.inbox_capacities.exporter, "Exporter");
+        let handle = Some(ExporterHandle { tx });
+        let inbox = ExporterInbox { rx };
+        let export_range = self.export_range.expect("Expected export range");
+        Self::State {
+            service: Service::new(),
+            keyspace: self.keyspace.expect("Expected keyspace"),
+            writer: ExportWriter::new(
+                self.dir_path.expect("Expected export directory path"),
+                self.max_log_size.unwrap_or(MAX_LOG_SIZE),
+                self.compression_level,
+            ),
+            retries_per_query: self.retries_per_query.unwrap_or(10),
+            parallelism: self.parallelism.unwrap_or(10).max(1),
+            next_to_fetch: export_range.start,
+            next_to_write: export_range.start,
+            export_range,
+            fetched: BTreeMap::new(),
+            exported: 0,
+            missing: MilestoneRanges::default(),
+            handle,
+            inbox,
+        }
+        .set_name()
+    }
+}
+
+impl Exporter {
+    pub(crate) fn clone_handle(&self) -> Option<ExporterHandle> {
+        self.handle.clone()
+    }
+}
+
+/// Get the name of the exporter of a milestone range
+pub(crate) fn exporter_name(export_range: &Range<u32>) -> String {
+    format!("Exporter#{}to{}", export_range.start, export_range.end)
+}
+
+/// Implement `Name` trait of the Exporter
+impl Name for Exporter {
+    fn set_name(mut self) -> Self {
+        let name = exporter_name(&self.export_range);
+        self.service.update_name(name);
+        self
+    }
+    fn get_name(&self) -> String {
+        self.service.get_name()
+    }
+}
+
+#[async_trait::async_trait]
+impl<H: ChronicleBrokerScope> AknShutdown<Exporter> for BrokerHandle<H> {
+    async fn aknowledge_shutdown(self, mut state: Exporter, status: Result<(), Need>) {
+        state.service.update_status(ServiceStatus::Stopped);
+        let event = BrokerEvent::Children(BrokerChild::Exporter(state.service.clone(), status));
+        let _ = self.send(event);
+    }
+}
+
+impl Exporter {
+    /// Fetch the milestone data of the next milestone index in the background
+    fn fetch_next(&mut self) -> anyhow::Result<()> {
+        let handle = self
+            .handle
+            .clone()
+            .ok_or_else(|| anyhow!("No exporter handle available!"))?;
+        let milestone_index = self.next_to_fetch;
+        self.next_to_fetch += 1;
+        let keyspace = self.keyspace.clone();
+        let retries = self.retries_per_query;
+        let parallelism = self.parallelism as usize;
+        tokio::spawn(async move {
+            let result = reconstruct_milestone_data(keyspace, milestone_index, retries, parallelism).await;
+            handle
+                .send(ExporterEvent::MilestoneData(milestone_index, result))
+                .await
+                .ok();
+        });
+        Ok(())
+    }
+}
+
+impl ExportWriter {
+    /// Create a writer of log files into the given directory, in the format of the compression level
+    pub fn new(dir_path: PathBuf, max_log_size: u64, compression_level: Option<i32>) -> Self {
+        Self {
+            dir_path,
+            max_log_size,
+            format: ArchiveFormat::from_compression_level(compression_level),
+            compression_level: compression_level.unwrap_or(DEFAULT_COMPRESSION_LEVEL),
+            log: None,
+        }
+    }
+    /// Get the directory of the exported log files
+    pub fn dir_path(&self) -> &Path {
+        &self.dir_path
+    }
+    /// Write the milestone data into the log file, starting a new one if it would exceed the size limit.
+    /// Returns the path of the log file which got finalized to make room, if any.
+    pub async fn write(&mut self, milestone_data: &MilestoneData) -> anyhow::Result<Option<PathBuf>> {
+        let milestone_index = milestone_data.milestone_index();
+        let record = ArchiveRecord::new(milestone_data, self.format)?;
+        let mut finished = None;
+        if let Some(log) = self.log.as_ref() {
+            if log.end != milestone_index {
+                bail!(
+                    "Unable to write milestone index {} after milestone index {}",
+                    milestone_index,
+                    log.end - 1
+                );
+            }
+            if log.encoder.projected_len(record.encoded_len()) > self.max_log_size {
+                finished = self.finish().await?;
+            }
+        }
+        if self.log.is_none() {
+            let path = self.dir_path.join(format!("{}.export", milestone_index));
+            let file = File::create(&path)
+                .await
+                .map_err(|e| anyhow!("Unable to create {}: {}", path.to_string_lossy(), e))?;
+            self.log.replace(ExportLog {
+                start: milestone_index,
+                end: milestone_index,
+                path,
+                file,
+                encoder: LogEncoder::new(self.format, self.compression_level, BLOCK_MILESTONES),
+            });
+        }
+        let log = self
+            .log
+            .as_mut()
+            .ok_or_else(|| anyhow!("No export log in export writer"))?;
+        log.file.write_all(&log.encoder.encode(&record)?).await?;
+        log.end = milestone_index + 1;
+        Ok(finished)
+    }
+
+    /// Finalize the log file being written, if any, along with its sidecar index and manifest entry
+    pub async fn finish(&mut self) -> anyhow::Result<Option<PathBuf>> {
+        let mut log = match self.log.take() {
+            Some(log) => log,
+            None => return Ok(None),
+        };
+        log.file.write_all(&log.encoder.finish()?).await?;
+        log.file.sync_all().await?;
+        let path = self.dir_path.join(format!("{}to{}.log", log.start, log.end));
+        if path.exists() {
+            bail!(
+                "Unable to finalize {}, as {} already exists",
+                log.path.to_string_lossy(),
+                path.to_string_lossy()
+            );
+        }
+        tokio::fs::rename(&log.path, &path).await?;
+        log.encoder
+            .index()
+            .write_sidecar(&path, log.encoder.encoded_len())
+            .await?;
+        Manifest::record(&path).await?;
+        Ok(Some(path))
+    }
+}
+
+/// Create the export directory if it doesn't exist
+pub(crate) async fn create_export_dir(dir_path: &Path) -> anyhow::Result<()> {
+    tokio::fs::create_dir_all(dir_path)
+        .await
+        .map_err(|e| anyhow!("Unable to create {}: {}", dir_path.to_string_lossy(), e))
+}
@@ -0,0 +1,33 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+
+#[async_trait::async_trait]
+impl<H: ChronicleBrokerScope> Terminating<BrokerHandle<H>> for Exporter {
+    async fn terminating(
+        &mut self,
+        mut status: Result<(), Need>,
+        supervisor: &mut Option<BrokerHandle<H>>,
+    ) -> Result<(), Need> {
+        info!("{} is terminating", self.get_name());
+        let supervisor = supervisor.as_mut().expect("Expected BrokerHandle");
+        // the milestones written so far still make a valid log file
+        if let Err(need) = self.finalize(supervisor).await {
+            status = Err(need);
+        }
+        let msg = if status.is_ok() { "done" } else { "failed" };
+        let exporter_session = ExporterSession::Finish {
+            from_ms: self.export_range.start,
+            to_ms: self.export_range.end,
+            exported: self.exported,
+            missing: self.missing.clone(),
+            msg: msg.into(),
+        };
+        supervisor.send(BrokerEvent::Exporter(exporter_session)).ok();
+        self.service.update_status(ServiceStatus::Stopping);
+        let event = BrokerEvent::Children(BrokerChild::Exporter(self.service.clone(), status));
+        let _ = supervisor.send(event);
+        status
+    }
+}
@@ -1,15 +1,7 @@
 // Copyright 2021 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use bee_message::{
-    address::Address,
-    input::Input,
-    payload::Payload,
-    prelude::{
-        MilestoneIndex,
-        TransactionId,
-    },
-};
+use bee_message::prelude::MilestoneIndex;
 
 use super::*;
 #[async_trait::async_trait]
@@ -35,47 +27,8 @@ impl<H: ChronicleBrokerScope, T: ImportMode> EventLoop<BrokerHandle<H>> for Impo
                     ImporterEvent::CqlResult(result) => {
                         match result {
                             Ok(milestone_index) => {
-                                // remove it from in_progress
-                                let _ = self
-                                    .in_progress_milestones_data
-                                    .remove(&milestone_index)
-                                    .expect("Expected entry for a milestone data");
-                                info!("Imported milestone data for milestone index: {}", milestone_index);
-                                let ms_bytes_size = self
-                                    .in_progress_milestones_data_bytes_size
-                                    .remove(&milestone_index)
-                                    .expect("Expected size-entry for a milestone data");
-                                let skipped = false;
-                                Self::imported(
-                                    supervisor,
-                                    self.from_ms,
-                                    self.to_ms,
-                                    self.log_file_size,
-                                    milestone_index,
-                                    ms_bytes_size,
-                                    skipped,
-                                );
-                                // check if we should process more
-                                if !self.service.is_stopping() {
-                                    // process one more
-                                    if let Some(milestone_data) =
-                                        self.next_milestone_data(supervisor).await.map_err(|e| {
-                                            error!("Unable to fetch next milestone data. Error: {}", e);
-                                            Need::Abort
-                                        })?
-                                    {
-                                        T::handle_milestone_data(milestone_data, self).map_err(|e| {
-                                            error!("{}", e);
-                                            Need::Abort
-                                        })?;
-                                    } else {
-                                        // no more milestone data.
-                                        if self.in_progress_milestones_data.is_empty() {
-                                            // shut it down
-                                            info!("Imported the LogFile: {}", self.get_name());
-                                            return Ok(());
-                                        }
-                                    };
+                                if self.handle_imported(milestone_index, supervisor).await? {
+                                    return Ok(());
                                 }
                             }
                             Err(_milestone_index) => {
@@ -84,6 +37,21 @@ impl<H: ChronicleBrokerScope, T: ImportMode> EventLoop<BrokerHandle<H>> for Impo
                             }
                         }
                     }
+                    // note: we receive this variant in Verify mode.
+                    ImporterEvent::Verified(milestone_index, result) => match result {
+                        Ok(report) => {
+                            if let Some(verification) = self.verification.as_mut() {
+                                verification.merge(report);
+                            }
+                            if self.handle_imported(milestone_index, supervisor).await? {
+                                return Ok(());
+                            }
+                        }
+                        Err(e) => {
+                            error!("Unable to verify milestone index {}. Error: {}", milestone_index, e);
+                            return Err(Need::Abort);
+                        }
+                    },
                     // note: we receive this variant in All mode.
                     ImporterEvent::ProcessMore(milestone_index) => {
                         if self.service.is_stopping() {
@@ -140,17 +108,88 @@ impl<H: ChronicleBrokerScope, T: ImportMode> EventLoop<BrokerHandle<H>> for Impo
         }
     }
 }
+impl<T: ImportMode> Importer<T> {
+    /// Report an imported milestone and process the next one, returning whether the whole LogFile got imported
+    async fn handle_imported<H: ChronicleBrokerScope>(
+        &mut self,
+        milestone_index: u32,
+        supervisor: &BrokerHandle<H>,
+    ) -> Result<bool, Need> {
+        // remove it from in_progress
+        let _ = self
+            .in_progress_milestones_data
+            .remove(&milestone_index)
+            .expect("Expected entry for a milestone data");
+        info!("Imported milestone data for milestone index: {}", milestone_index);
+        let ms_bytes_size = self
+            .in_progress_milestones_data_bytes_size
+            .remove(&milestone_index)
+            .expect("Expected size-entry for a milestone data");
+        let skipped = false;
+        Self::imported(
+            supervisor,
+            self.from_ms,
+            self.to_ms,
+            self.log_file_size,
+            milestone_index,
+            ms_bytes_size,
+            skipped,
+        );
+        // check if we should process more
+        if !self.service.is_stopping() {
+            // process one more
+            if let Some(milestone_data) = self.next_milestone_data(supervisor).await.map_err(|e| {
+                error!("Unable to fetch next milestone data. Error: {}", e);
+                Need::Abort
+            })? {
+                T::handle_milestone_data(milestone_data, self).map_err(|e| {
+                    error!("{}", e);
+                    Need::Abort
+                })?;
+            } else {
+                // no more milestone data.
+                if self.in_progress_milestones_data.is_empty() {
+                    // shut it down
+                    info!("Imported the LogFile: {}", self.get_name());
+                    return Ok(true);
+                }
+            };
+        }
+        Ok(false)
+    }
+}
 impl<T> Importer<T> {
     pub(crate) fn get_keyspace(&self) -> ChronicleKeyspace {
         self.default_keyspace.clone()
     }
-    fn get_partition_id(&self, milestone_index: MilestoneIndex) -> u16 {
-        self.partition_config.partition_id(milestone_index.0)
+    /// Look up which receivers of a milestone had outputs before it, so its analytics tell the new addresses apart
+    pub(crate) async fn lookup_address_history(&self, milestone_data: &mut MilestoneData) -> anyhow::Result<()> {
+        let milestone_index = MilestoneIndex(milestone_data.milestone_index());
+        let parallelism = self.parallelism.max(1) as usize;
+        crate::indexer::lookup_address_history(milestone_data, parallelism, |address| {
+            self.indexer
+                .address_seen_before(&self.default_keyspace, address, milestone_index, self.retries_per_query)
+        })
+        .await
+        .map_err(|e| {
+            error!(
+                "Unable to look up the address history of milestone index: {}. Error: {}",
+                milestone_index.0, e
+            );
+            e
+        })
+    }
+    /// Add the analytics of an imported milestone to the hour and day rollups
+    pub(crate) fn update_rollups(&self, milestone_data: &MilestoneData, analytic_record: &AnalyticRecord) {
+        if let Some(timestamp) = milestone_data.milestone_timestamp() {
+            self.rollups.queue_update(analytic_record.clone(), timestamp);
+        }
     }
 }
 impl<T: ImportMode> Importer<T> {
+    /// Insert the message with its metadata along with all its index rows
     pub(crate) fn insert_message_with_metadata<I: Inherent>(
-        &mut self,
+        &self,
         inherent_worker: &I,
         message_id: MessageId,
         message: Message,
@@ -159,320 +198,16 @@ impl<T: ImportMode> Importer<T> {
         let milestone_index = metadata
             .referenced_by_milestone_index
             .expect("Expected referenced milestone index in metadata");
-        // Insert parents/children
-        self.insert_parents(
+        let inserter = KeyspaceInserter {
             inherent_worker,
-            &message_id,
-            &message.parents(),
-            MilestoneIndex(milestone_index),
-            metadata.ledger_inclusion_state.clone(),
-        )?;
-        // insert payload (if any)
-        if let Some(payload) = message.payload() {
-            self.insert_payload(
-                inherent_worker,
-                &message_id,
-                &message,
-                &payload,
-                MilestoneIndex(milestone_index),
-                metadata.ledger_inclusion_state.clone(),
-                Some(metadata.clone()),
-            )?;
-        }
-        let message_tuple = (message, metadata);
-        // store message and metadata
-        self.insert(inherent_worker, message_id, message_tuple)
-    }
-
-    fn insert_parents<I: Inherent>(
-        &self,
-        inherent_worker: &I,
-        message_id: &MessageId,
-        parents: &[MessageId],
-        milestone_index: MilestoneIndex,
-        inclusion_state: Option<LedgerInclusionState>,
-    ) -> anyhow::Result<()> {
-        let partition_id = self.get_partition_id(milestone_index);
-        for parent_id in parents {
-            let partitioned = Partitioned::new(*parent_id, partition_id, milestone_index.0);
-            let parent_record = ParentRecord::new(*message_id, inclusion_state);
-            self.insert(inherent_worker, partitioned, parent_record)?;
-            // insert hint record
-            let hint = Hint::parent(parent_id.to_string());
-            let partition = Partition::new(partition_id, *milestone_index);
-            self.insert(inherent_worker, hint, partition)?;
-        }
-        Ok(())
-    }
-    fn insert_payload<I: Inherent>(
-        &mut self,
-        inherent_worker: &I,
-        message_id: &MessageId,
-        message: &Message,
-        payload: &Payload,
-        milestone_index: MilestoneIndex,
-        inclusion_state: Option<LedgerInclusionState>,
-        metadata: Option<MessageMetadata>,
-    ) -> anyhow::Result<()> {
-        match payload {
-            Payload::Indexation(indexation) => {
-                self.insert_index(
-                    inherent_worker,
-                    message_id,
-                    Indexation(hex::encode(indexation.index())),
-                    milestone_index,
-                    inclusion_state,
-                )?;
-            }
-            Payload::Transaction(transaction) => {
-                self.insert_transaction(
-                    inherent_worker,
-                    message_id,
-                    message,
-                    transaction,
-                    inclusion_state,
-                    milestone_index,
-                    metadata,
-                )?;
-            }
-            Payload::Milestone(milestone) => {
-                let ms_index = *milestone.essence().index();
-                let parents_check = message.parents().eq(milestone.essence().parents());
-                if metadata.is_some() && parents_check {
-                    self.insert(
-                        inherent_worker,
-                        MilestoneIndex(ms_index),
-                        (*message_id, milestone.clone()),
-                    )?;
-                }
-            }
-            e => {
-                // Skip remaining payload types.
-                warn!("Skipping unsupported payload variant: {:?}", e);
-            }
-        }
-        Ok(())
-    }
-    fn insert_index<I: Inherent>(
-        &self,
-        inherent_worker: &I,
-        message_id: &MessageId,
-        index: Indexation,
-        milestone_index: MilestoneIndex,
-        inclusion_state: Option<LedgerInclusionState>,
-    ) -> anyhow::Result<()> {
-        let partition_id = self.get_partition_id(milestone_index);
-        let partitioned = Partitioned::new(index.clone(), partition_id, milestone_index.0);
-        let index_record = IndexationRecord::new(*message_id, inclusion_state);
-        self.insert(inherent_worker, partitioned, index_record)?;
-        // insert hint record
-        let hint = Hint::index(index.0);
-        let partition = Partition::new(partition_id, *milestone_index);
-        self.insert(inherent_worker, hint, partition)
-    }
-    fn insert_transaction<I: Inherent>(
-        &mut self,
-        inherent_worker: &I,
-        message_id: &MessageId,
-        message: &Message,
-        transaction: &Box<TransactionPayload>,
-        ledger_inclusion_state: Option<LedgerInclusionState>,
-        milestone_index: MilestoneIndex,
-        metadata: Option<MessageMetadata>,
-    ) -> anyhow::Result<()> {
-        let transaction_id = transaction.id();
-        let unlock_blocks = transaction.unlock_blocks();
-        let confirmed_milestone_index;
-        if ledger_inclusion_state.is_some() {
-            confirmed_milestone_index = Some(milestone_index);
-        } else {
-            confirmed_milestone_index = None;
-        }
-        let Essence::Regular(regular) = transaction.essence();
-        {
-            for (input_index, input) in regular.inputs().iter().enumerate() {
-                // insert utxoinput row along with input row
-                if let Input::Utxo(utxo_input) = input {
-                    let unlock_block = &unlock_blocks[input_index];
-                    let input_data = InputData::utxo(utxo_input.clone(), unlock_block.clone());
-                    // insert input row
-                    self.insert_input(
-                        inherent_worker,
-                        message_id,
-                        &transaction_id,
-                        input_index as u16,
-                        input_data,
-                        ledger_inclusion_state,
-                        confirmed_milestone_index,
-                    )?;
-                    // this is the spent_output which the input is spending from
-                    let output_id = utxo_input.output_id();
-                    // therefore we insert utxo_input.output_id() -> unlock_block to indicate that this output is_spent;
-                    let unlock_data = UnlockData::new(transaction_id, input_index as u16, unlock_block.clone());
-                    self.insert_unlock(
-                        inherent_worker,
-                        &message_id,
-                        output_id.transaction_id(),
-                        output_id.index(),
-                        unlock_data,
-                        ledger_inclusion_state,
-                        confirmed_milestone_index,
-                    )?;
-                } else if let Input::Treasury(treasury_input) = input {
-                    let input_data = InputData::treasury(treasury_input.clone());
-                    // insert input row
-                    self.insert_input(
-                        inherent_worker,
-                        message_id,
-                        &transaction_id,
-                        input_index as u16,
-                        input_data,
-                        ledger_inclusion_state,
-                        confirmed_milestone_index,
-                    )?;
-                };
-            }
-            for (output_index, output) in regular.outputs().iter().enumerate() {
-                // insert output row
-                self.insert_output(
-                    inherent_worker,
-                    message_id,
-                    &transaction_id,
-                    output_index as u16,
-                    output.clone(),
-                    ledger_inclusion_state,
-                    confirmed_milestone_index,
-                )?;
-                // insert address row
-                self.insert_address(
-                    inherent_worker,
-                    output,
-                    &transaction_id,
-                    output_index as u16,
-                    milestone_index,
-                    ledger_inclusion_state,
-                )?;
-            }
-            if let Some(payload) = regular.payload() {
-                self.insert_payload(
-                    inherent_worker,
-                    message_id,
-                    message,
-                    payload,
-                    milestone_index,
-                    ledger_inclusion_state,
-                    metadata,
-                )?;
-            }
+            keyspace: &self.default_keyspace,
         };
-        Ok(())
-    }
-    fn insert_input<I: Inherent>(
-        &self,
-        inherent_worker: &I,
-        message_id: &MessageId,
-        transaction_id: &TransactionId,
-        index: u16,
-        input_data: InputData,
-        inclusion_state: Option<LedgerInclusionState>,
-        milestone_index: Option<MilestoneIndex>,
-    ) -> anyhow::Result<()> {
-        // -input variant: (InputTransactionId, InputIndex) -> UTXOInput data column
-        let input_id = (*transaction_id, index);
-        let transaction_record = TransactionRecord::input(*message_id, input_data, inclusion_state, milestone_index);
-        self.insert(inherent_worker, input_id, transaction_record)
-    }
-    fn insert_unlock<I: Inherent>(
-        &self,
-        inherent_worker: &I,
-        message_id: &MessageId,
-        utxo_transaction_id: &TransactionId,
-        utxo_index: u16,
-        unlock_data: UnlockData,
-        inclusion_state: Option<LedgerInclusionState>,
-        milestone_index: Option<MilestoneIndex>,
-    ) -> anyhow::Result<()> {
-        // -unlock variant: (UtxoInputTransactionId, UtxoInputOutputIndex) -> Unlock data column
-        let utxo_id = (*utxo_transaction_id, utxo_index);
-        let transaction_record = TransactionRecord::unlock(*message_id, unlock_data, inclusion_state, milestone_index);
-        self.insert(inherent_worker, utxo_id, transaction_record)
-    }
-    fn insert_output<I: Inherent>(
-        &self,
-        inherent_worker: &I,
-        message_id: &MessageId,
-        transaction_id: &TransactionId,
-        index: u16,
-        output: Output,
-        inclusion_state: Option<LedgerInclusionState>,
-        milestone_index: Option<MilestoneIndex>,
-    ) -> anyhow::Result<()> {
-        // -output variant: (OutputTransactionId, OutputIndex) -> Output data column
-        let output_id = (*transaction_id, index);
-        let transaction_record = TransactionRecord::output(*message_id, output, inclusion_state, milestone_index);
-        self.insert(inherent_worker, output_id, transaction_record)
-    }
-    fn insert_address<I: Inherent>(
-        &self,
-        inherent_worker: &I,
-        output: &Output,
-        transaction_id: &TransactionId,
-        index: u16,
-        milestone_index: MilestoneIndex,
-        inclusion_state: Option<LedgerInclusionState>,
-    ) -> anyhow::Result<()> {
-        let partition_id = self.get_partition_id(milestone_index);
-        let output_type = output.kind();
-        match output {
-            Output::SignatureLockedSingle(sls) => {
-                let Address::Ed25519(ed_address) = sls.address();
-                {
-                    let partitioned = Partitioned::new(*ed_address, partition_id, milestone_index.0);
-                    let address_record =
-                        AddressRecord::new(output_type, *transaction_id, index, sls.amount(), inclusion_state);
-                    self.insert(inherent_worker, partitioned, address_record)?;
-                    // insert hint record
-                    let hint = Hint::address(ed_address.to_string());
-                    let partition = Partition::new(partition_id, *milestone_index);
-                    self.insert(inherent_worker, hint, partition)?;
-                };
-            }
-            Output::SignatureLockedDustAllowance(slda) => {
-                let Address::Ed25519(ed_address) = slda.address();
-                {
-                    let partitioned = Partitioned::new(*ed_address, partition_id, milestone_index.0);
-                    let address_record =
-                        AddressRecord::new(output_type, *transaction_id, index, slda.amount(), inclusion_state);
-                    self.insert(inherent_worker, partitioned, address_record)?;
-                    // insert hint record
-                    let hint = Hint::address(ed_address.to_string());
-                    let partition = Partition::new(partition_id, *milestone_index);
-                    self.insert(inherent_worker, hint, partition)?;
-                };
-            }
-            e => {
-                if let Output::Treasury(_) = e {
-                } else {
-                    error!("Unexpected new output variant {:?}", e);
-                }
-            }
+        for row in self
+            .indexer
+            .index(&message_id, &message, MilestoneIndex(milestone_index), Some(&metadata))?
+        {
+            row.insert(&inserter)?;
         }
         Ok(())
     }
-    fn insert<I, K, V>(&self, inherent_worker: &I, key: K, value: V) -> anyhow::Result<()>
-    where
-        I: Inherent,
-        K: 'static + Send + Clone,
-        V: 'static + Send + Clone,
-        ChronicleKeyspace: Insert<K, V>,
-    {
-        let req = self
-            .default_keyspace
-            .insert(&key, &value)
-            .consistency(Consistency::One)
-            .build()?;
-        let worker = inherent_worker.inherent_boxed(key, value);
-        req.send_local(worker);
-        Ok(())
-    }
 }
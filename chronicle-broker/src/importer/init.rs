@@ -15,12 +15,45 @@ impl<H: ChronicleBrokerScope, T: ImportMode> Init<BrokerHandle<H>> for Importer<
             self.service.update_status(ServiceStatus::Initializing);
             let event = BrokerEvent::Children(BrokerChild::Importer(self.service.clone(), Ok(()), self.parallelism));
             supervisor.send(event).ok();
-            let log_file = LogFile::try_from(self.file_path.clone()).map_err(|e| {
+            if let Some(sink) = self.sink.as_ref() {
+                if !self.file_path.exists() {
+                    let name = self
+                        .file_path
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .ok_or(Need::Abort)?;
+                    sink.fetch(name, &self.file_path).await.map_err(|e| {
+                        error!("Unable to fetch LogFile from the archive sink. Error: {}", e);
+                        Need::Abort
+                    })?;
+                }
+            }
+            if let Some(mirror) = self.mirror.as_ref() {
+                if !self.file_path.exists() {
+                    let name = self
+                        .file_path
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .ok_or(Need::Abort)?;
+                    mirror.download(name, &self.file_path).await.map_err(|e| {
+                        error!("Unable to download LogFile from the archive mirror. Error: {}", e);
+                        Need::Abort
+                    })?;
+                }
+            }
+            let mut log_file = LogFile::try_from(self.file_path.clone()).map_err(|e| {
                 error!("Unable to create LogFile. Error: {}", e);
                 Need::Abort
             })?;
-            let from = log_file.from_ms_index();
-            let to = log_file.to_ms_index();
+            // the importer of a shard only covers the part of the log file within its import range
+            let from = log_file.from_ms_index().max(self.import_range.start);
+            let to = log_file.to_ms_index().min(self.import_range.end).max(from);
+            if from > log_file.from_ms_index() {
+                log_file.seek_range(&self.file_path, &(from..to)).await.map_err(|e| {
+                    error!("Unable to seek LogFile to milestone index {}. Error: {}", from, e);
+                    Need::Abort
+                })?;
+            }
             self.log_file_size = log_file.len();
             self.from_ms = from;
             self.to_ms = to;
@@ -33,7 +66,7 @@ impl<H: ChronicleBrokerScope, T: ImportMode> Init<BrokerHandle<H>> for Importer<
                 skipped: true,
             };
             // fetch sync data from the keyspace
-            if self.resume {
+            if self.resume || T::DRY_RUN {
                 let sync_range = SyncRange { from, to };
                 self.sync_data = SyncData::try_fetch(&self.default_keyspace, &sync_range, 10)
                     .await
@@ -42,6 +75,17 @@ impl<H: ChronicleBrokerScope, T: ImportMode> Init<BrokerHandle<H>> for Importer<
                         Need::Abort
                     })?;
             }
+            // a dry-run verifies every milestone, so nothing is skipped
+            if T::DRY_RUN {
+                self.resume = false;
+                let verification = Verification::try_fetch(&self.default_keyspace, from..to, 10)
+                    .await
+                    .map_err(|e| {
+                        error!("Unable to fetch the analytics records to verify. Error: {}", e);
+                        Need::Abort
+                    })?;
+                self.verification.replace(verification);
+            }
             self.log_file.replace(log_file);
             self.init_importing(supervisor).await.map_err(|e| {
                 error!("Unable to init importing process. Error: {}", e);
@@ -78,8 +122,12 @@ impl<T: ImportMode> Importer<T> {
         let mut scan_budget: usize = 100;
         loop {
             let pre_len = log_file.len();
-            if let Some(milestone_data) = log_file.next().await? {
+            if let Some(mut milestone_data) = log_file.next().await? {
                 let milestone_index = milestone_data.milestone_index();
+                // the log file is ordered, so the rest of it is beyond the import range
+                if milestone_index >= self.import_range.end {
+                    return Ok(None);
+                }
                 let not_in_import_range = !self.import_range.contains(&milestone_index);
                 let resume = self.resume && self.sync_data.completed.iter().any(|r| r.contains(&milestone_index));
                 if resume || not_in_import_range {
@@ -91,8 +139,8 @@ impl<T: ImportMode> Importer<T> {
                     let ms_bytes_size = (pre_len - log_file.len()) as usize;
                     Self::imported(
                         supervisor,
-                        log_file.from_ms_index(),
-                        log_file.to_ms_index(),
+                        self.from_ms,
+                        self.to_ms,
                         self.log_file_size,
                         milestone_index,
                         ms_bytes_size,
@@ -109,6 +157,7 @@ impl<T: ImportMode> Importer<T> {
                     let ms_bytes_size = (pre_len - log_file.len()) as usize;
                     self.in_progress_milestones_data_bytes_size
                         .insert(milestone_index, ms_bytes_size);
+                    self.lookup_address_history(&mut milestone_data).await?;
                     return Ok(Some(milestone_data));
                 }
             } else {
@@ -8,19 +8,19 @@ use crate::{
         BrokerHandle,
         ChronicleBrokerScope,
     },
+    archive::{
+        mirror::ArchiveMirror,
+        sink::ArchiveSink,
+    },
     archiver::LogFile,
-};
-use bee_message::{
-    output::Output,
-    payload::transaction::{
-        Essence,
-        TransactionPayload,
+    indexer::{
+        Inherent,
+        KeyspaceInserter,
+        MessageIndexer,
     },
+    rollup::AnalyticsRollups,
 };
-use chronicle_common::{
-    config::PartitionConfig,
-    Synckey,
-};
+use chronicle_common::Synckey;
 use chronicle_storage::access::SyncRecord;
 use scylla_rs::{
     app::worker::handle_insert_unprepared_error,
@@ -33,30 +33,51 @@ use std::{
         DerefMut,
         Range,
     },
-    path::PathBuf,
+    path::{
+        Path,
+        PathBuf,
+    },
     sync::atomic::Ordering,
 };
 
 mod event_loop;
 mod init;
+/// The scheduler which shards the imports across concurrent importers
+pub mod scheduler;
+/// The import of node snapshot files, cross-checked with the stored transactions and milestones
+pub mod snapshot;
 mod terminating;
+/// The verification of the archived milestones against the database, by a dry-run import
+pub mod verify;
+
+pub(crate) use snapshot::{
+    import_snapshot,
+    snapshot_files,
+};
+pub(crate) use verify::Verification;
 
 /// Import all records to all tables
 pub struct All;
 /// Import analytics records only which are stored in analytics table
 pub struct Analytics;
+/// Compare the milestone data with the database records without writing anything
+pub struct Verify;
 
 /// Defines the Importer Mode
 pub trait ImportMode: Sized + Send + 'static {
+    /// Whether the mode only reads the database
+    const DRY_RUN: bool = false;
     /// Instruct how to import the milestone data
     fn handle_milestone_data(milestone_data: MilestoneData, importer: &mut Importer<Self>) -> anyhow::Result<()>;
 }
 impl ImportMode for All {
-    fn handle_milestone_data(milestone_data: MilestoneData, importer: &mut Importer<All>) -> anyhow::Result<()> {
+    fn handle_milestone_data(mut milestone_data: MilestoneData, importer: &mut Importer<All>) -> anyhow::Result<()> {
+        importer.apply_filter(&mut milestone_data);
         let analytic_record = milestone_data.get_analytic_record().map_err(|e| {
             error!("Unable to get analytic record for milestone data. Error: {}", e);
             e
         })?;
+        importer.update_rollups(&milestone_data, &analytic_record);
         let milestone_index = milestone_data.milestone_index();
         let mut iterator = milestone_data.into_iter();
         importer.insert_some_messages(milestone_index, &mut iterator)?;
@@ -68,11 +89,16 @@ impl ImportMode for All {
 }
 
 impl ImportMode for Analytics {
-    fn handle_milestone_data(milestone_data: MilestoneData, importer: &mut Importer<Analytics>) -> anyhow::Result<()> {
+    fn handle_milestone_data(
+        mut milestone_data: MilestoneData,
+        importer: &mut Importer<Analytics>,
+    ) -> anyhow::Result<()> {
+        importer.apply_filter(&mut milestone_data);
         let analytic_record = milestone_data.get_analytic_record().map_err(|e| {
             error!("Unable to get analytic record for milestone data. Error: {}", e);
             e
         })?;
+        importer.update_rollups(&milestone_data, &analytic_record);
         let milestone_index = milestone_data.milestone_index();
         let iterator = milestone_data.into_iter();
         importer.insert_analytic_record(&analytic_record)?;
@@ -91,6 +117,11 @@ builder!(ImporterBuilder<T> {
     resume: bool,
     import_range: Range<u32>,
     parallelism: u8,
+    inbox_capacity: usize,
+    sink: ArchiveSink,
+    mirror: ArchiveMirror,
+    filter: ImportFilter,
+    keyspace: ChronicleKeyspace,
     chronicle_id: u8
 });
 
@@ -100,6 +131,8 @@ pub enum ImporterEvent {
     CqlResult(Result<u32, u32>),
     /// Indicator to continue processing
     ProcessMore(u32),
+    /// The message differences of a verified milestone
+    Verified(u32, anyhow::Result<VerifyReport>),
     /// Shutdown the importer
     Shutdown,
 }
@@ -107,15 +140,15 @@ pub enum ImporterEvent {
 /// ImporterHandle to be passed to children(Inserter) and the supervisor(in order to shutdown)
 #[derive(Clone)]
 pub struct ImporterHandle {
-    pub(crate) tx: tokio::sync::mpsc::UnboundedSender<ImporterEvent>,
+    pub(crate) tx: BoundedSender<ImporterEvent>,
 }
 
 /// ImporterInbox is used to recv events
 pub struct ImporterInbox {
-    pub(crate) rx: tokio::sync::mpsc::UnboundedReceiver<ImporterEvent>,
+    pub(crate) rx: BoundedReceiver<ImporterEvent>,
 }
 impl Deref for ImporterHandle {
-    type Target = tokio::sync::mpsc::UnboundedSender<ImporterEvent>;
+    type Target = BoundedSender<ImporterEvent>;
 
     fn deref(&self) -> &Self::Target {
         &self.tx
@@ -129,7 +162,7 @@ impl DerefMut for ImporterHandle {
 }
 
 impl Deref for ImporterInbox {
-    type Target = tokio::sync::mpsc::UnboundedReceiver<ImporterEvent>;
+    type Target = BoundedReceiver<ImporterEvent>;
 
     fn deref(&self) -> &Self::Target {
         &self.rx
@@ -148,7 +181,7 @@ impl Shutdown for ImporterHandle {
         Self: Sized,
     {
         let shutdown_event = ImporterEvent::Shutdown;
-        self.send(shutdown_event).ok();
+        self.send_control(shutdown_event);
         None
     }
 }
@@ -158,6 +191,12 @@ pub struct Importer<T> {
     service: Service,
     /// The file path of the importer
     file_path: PathBuf,
+    /// The sink the log file is fetched from, if it's not found locally
+    sink: Option<ArchiveSink>,
+    /// The mirror the log file is downloaded from, if it's not found locally
+    mirror: Option<ArchiveMirror>,
+    /// The filter of the messages to import
+    filter: Option<ImportFilter>,
     /// The log file
     log_file: Option<LogFile>,
     /// LogFile total_size,
@@ -168,8 +207,10 @@ pub struct Importer<T> {
     to_ms: u32,
     /// The default Chronicle keyspace
     default_keyspace: ChronicleKeyspace,
-    /// The partition configuration
-    partition_config: PartitionConfig,
+    /// The indexer of the imported messages
+    indexer: MessageIndexer,
+    /// The analytics rollups of the keyspace
+    rollups: AnalyticsRollups,
     /// The number of retires per query
     retries_per_query: usize,
     /// The chronicle id
@@ -182,6 +223,8 @@ pub struct Importer<T> {
     import_range: Range<u32>,
     /// The database sync data
     sync_data: SyncData,
+    /// The verification state of a dry-run import
+    verification: Option<Verification>,
     /// In progress milestones data
     in_progress_milestones_data: HashMap<u32, (IntoIter<MessageId, FullMessage>, AnalyticRecord)>,
     in_progress_milestones_data_bytes_size: HashMap<u32, usize>,
@@ -201,20 +244,25 @@ impl<H: ChronicleBrokerScope, T: ImportMode> ActorBuilder<BrokerHandle<H>> for I
 impl<T: ImportMode> Builder for ImporterBuilder<T> {
     type State = Importer<T>;
     fn build(self) -> Self::State {
-        // Get the first keyspace or default to "permanode"
-        // In order to use multiple keyspaces, the user must
-        // use filters to determine where records go
+        // Get the keyspace of the network, or the first keyspace or default to "permanode"
         let config = chronicle_common::get_config();
-        let default_keyspace = ChronicleKeyspace::new(
-            config
-                .storage_config
-                .keyspaces
-                .first()
-                .and_then(|keyspace| Some(keyspace.name.clone()))
-                .unwrap_or("permanode".to_owned()),
-        );
-        let partition_config = config.storage_config.partition_config;
-        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let default_keyspace = self.keyspace.unwrap_or_else(|| {
+            ChronicleKeyspace::new(
+                config
+                    .storage_config
+                    .keyspaces
+                    .first()
+                    .and_then(|keyspace| Some(keyspace.name.clone()))
+                    .unwrap_or("permanode".to_owned()),
+            )
+        });
+        let indexer = MessageIndexer::new(config.storage_config.partition_config);
+        let rollups = AnalyticsRollups::of(&default_keyspace);
+        let inbox_capacity = self
+            .inbox_capacity
+            .unwrap_or(config.broker_config.inbox_capacities.importer);
+        // importers share a single gauge, as they are spawned per log file
+        let (tx, rx) = bounded_channel(inbox_capacity, "Importer");
         let handle = Some(ImporterHandle { tx });
         let inbox = ImporterInbox { rx };
         let import_range = self.import_range.unwrap_or(Range {
@@ -224,12 +272,16 @@ impl<T: ImportMode> Builder for ImporterBuilder<T> {
         Self::State {
             service: Service::new(),
             file_path: self.file_path.unwrap(),
+            sink: self.sink,
+            mirror: self.mirror,
+            filter: self.filter.filter(|filter| !filter.is_empty()),
             log_file: None,
             log_file_size: 0,
             from_ms: 0,
             to_ms: 0,
             default_keyspace,
-            partition_config,
+            indexer,
+            rollups,
             parallelism: self.parallelism.unwrap_or(10),
             chronicle_id: self.chronicle_id.unwrap(),
             in_progress_milestones_data: HashMap::new(),
@@ -238,6 +290,7 @@ impl<T: ImportMode> Builder for ImporterBuilder<T> {
             resume: self.resume.unwrap_or(true),
             import_range,
             sync_data: SyncData::default(),
+            verification: None,
             handle,
             inbox,
             eof: false,
@@ -251,11 +304,27 @@ impl<T> Importer<T> {
     pub(crate) fn clone_handle(&self) -> Option<ImporterHandle> {
         self.handle.clone()
     }
+    /// Drop the messages of the milestone data which don't match the import filter
+    pub(crate) fn apply_filter(&self, milestone_data: &mut MilestoneData) {
+        if let Some(filter) = self.filter.as_ref() {
+            filter.apply(milestone_data);
+        }
+    }
 }
+/// Get the name of the importer of a milestone range of a log file
+pub(crate) fn importer_name(file_path: &Path, import_range: &Range<u32>) -> String {
+    format!(
+        "{}#{}to{}",
+        file_path.to_string_lossy(),
+        import_range.start,
+        import_range.end
+    )
+}
+
 /// Implement `Name` trait of the Importer
 impl<T> Name for Importer<T> {
     fn set_name(mut self) -> Self {
-        let name = format!("{}", self.file_path.to_str().unwrap());
+        let name = importer_name(&self.file_path, &self.import_range);
         self.service.update_name(name);
         self
     }
@@ -418,10 +487,12 @@ where
     fn drop(&mut self) {
         let any_error = self.any_error.load(Ordering::Relaxed);
         if any_error {
-            let _ = self.handle.send(ImporterEvent::CqlResult(Err(self.milestone_index)));
+            self.handle
+                .send_control(ImporterEvent::CqlResult(Err(self.milestone_index)));
         } else {
             // tell importer to process more
-            let _ = self.handle.send(ImporterEvent::ProcessMore(self.milestone_index));
+            self.handle
+                .send_control(ImporterEvent::ProcessMore(self.milestone_index));
         }
     }
 }
@@ -491,7 +562,7 @@ where
         let milestone_index = *self.synced_record.milestone_index;
         if self.analyzed {
             // tell importer
-            self.handle.send(ImporterEvent::CqlResult(Ok(milestone_index))).ok();
+            self.handle.send_control(ImporterEvent::CqlResult(Ok(milestone_index)));
         } else {
             // set it to be analyzed, as this response is for analytic record
             self.analyzed = true;
@@ -552,7 +623,7 @@ where
             // no more retries
             // respond with error
             let milestone_index = *self.synced_record.milestone_index;
-            let _ = self.handle.send(ImporterEvent::CqlResult(Err(milestone_index)));
+            self.handle.send_control(ImporterEvent::CqlResult(Err(milestone_index)));
         }
         Ok(())
     }
@@ -582,23 +653,14 @@ where
     }
 }
 
-/// The inherent trait to return a boxed worker for a given key/value pair
-pub(crate) trait Inherent {
-    fn inherent_boxed<K, V>(&self, key: K, value: V) -> Box<dyn Worker>
-    where
-        ChronicleKeyspace: 'static + Insert<K, V> + Insert<Synckey, SyncRecord>,
-        K: 'static + Send + Clone,
-        V: 'static + Send + Clone;
-}
-
 /// Implement the `Inherent` trait for the milestone data worker, so we can get the atomic importer worker
-/// which contains the atomic importer handle of the milestone data worker
+/// which contains the atomic importer handle of the milestone data worker, along with its keyspace
 impl Inherent for MilestoneDataWorker<ChronicleKeyspace> {
-    fn inherent_boxed<K, V>(&self, key: K, value: V) -> Box<dyn Worker>
+    fn inherent_boxed<K, V>(&self, _keyspace: &ChronicleKeyspace, key: K, value: V) -> Box<dyn Worker>
     where
-        ChronicleKeyspace: 'static + Insert<K, V> + Insert<Synckey, SyncRecord>,
-        K: 'static + Send + Clone,
-        V: 'static + Send + Clone,
+        ChronicleKeyspace: 'static + Insert<K, V>,
+        K: 'static + Send + Sync + Clone,
+        V: 'static + Send + Sync + Clone,
     {
         AtomicImporterWorker::boxed(self.arc_handle.clone(), key, value)
     }
@@ -649,7 +711,8 @@ where
     fn handle_response(self: Box<Self>, giveload: Vec<u8>) -> anyhow::Result<()> {
         Decoder::from(giveload.try_into()?).get_void()?;
         let milestone_index = self.analytic_record.milestone_index();
-        self.handle.send(ImporterEvent::CqlResult(Ok(**milestone_index))).ok();
+        self.handle
+            .send_control(ImporterEvent::CqlResult(Ok(**milestone_index)));
         Ok(())
     }
     fn handle_error(
@@ -677,7 +740,8 @@ where
             // no more retries
             // respond with error
             let milestone_index = self.analytic_record.milestone_index();
-            let _ = self.handle.send(ImporterEvent::CqlResult(Err(**milestone_index)));
+            self.handle
+                .send_control(ImporterEvent::CqlResult(Err(**milestone_index)));
         }
         Ok(())
     }
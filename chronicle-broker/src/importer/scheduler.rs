@@ -0,0 +1,236 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use std::{
+    collections::VecDeque,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+/// The default number of importers which run concurrently
+pub const DEFAULT_IMPORT_WORKERS: u8 = 4;
+/// The minimum interval between two aggregate progress reports
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A milestone range of a log file, which is imported by a single importer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportShard {
+    /// The path of the log file
+    pub path: PathBuf,
+    /// The milestone range imported from the log file
+    pub range: Range<u32>,
+}
+
+impl ImportShard {
+    /// Get the name of the importer of this shard
+    pub fn importer_name(&self) -> String {
+        importer_name(&self.path, &self.range)
+    }
+}
+
+/// Split the import range across the given log files, along with their milestone ranges,
+/// into shards of at most `shard_size` milestones sorted by milestone index.
+/// The completed ranges, as found in the sync table, are left out of the shards.
+pub fn plan_shards(
+    mut files: Vec<(PathBuf, Range<u32>)>,
+    import_range: &Range<u32>,
+    shard_size: Option<u32>,
+    completed: &[Range<u32>],
+) -> Vec<ImportShard> {
+    files.sort_by_key(|(_, range)| range.start);
+    let shard_size = shard_size.unwrap_or(u32::MAX).max(1);
+    let mut shards = Vec::new();
+    for (path, range) in files {
+        let end = range.end.min(import_range.end);
+        let mut start = range.start.max(import_range.start);
+        while start < end {
+            let shard_end = start.saturating_add(shard_size).min(end);
+            let mut shard_range = start..shard_end;
+            // skip the imported milestones at both ends of the shard
+            while let Some(synced) = completed
+                .iter()
+                .find(|r| !shard_range.is_empty() && r.contains(&shard_range.start))
+            {
+                shard_range.start = synced.end.min(shard_range.end);
+            }
+            while let Some(synced) = completed
+                .iter()
+                .find(|r| !shard_range.is_empty() && r.contains(&(shard_range.end - 1)))
+            {
+                shard_range.end = synced.start.max(shard_range.start);
+            }
+            if !shard_range.is_empty() {
+                shards.push(ImportShard {
+                    path: path.clone(),
+                    range: shard_range,
+                });
+            }
+            start = shard_end;
+        }
+    }
+    shards
+}
+
+/// The import modes of the log files, the snapshot files are imported apart from the scheduled shards
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LogImportType {
+    /// Import everything
+    All,
+    /// Import only Analytics data
+    Analytics,
+    /// Compare the milestone data with the database without writing anything
+    Verify,
+}
+
+/// The options of the import request a shard belongs to
+#[derive(Debug, Clone)]
+pub struct ImportJob {
+    /// The index of the network whose keyspace the log files are imported into
+    pub network: usize,
+    /// Skip the milestones which are already imported
+    pub resume: bool,
+    /// The import mode
+    pub import_type: LogImportType,
+    /// Fetch the log files from the archive sink of the network
+    pub from_sink: bool,
+    /// The mirror the log files are downloaded from
+    pub mirror: Option<ArchiveMirror>,
+    /// The filter of the messages to import
+    pub filter: Option<ImportFilter>,
+}
+
+/// Schedules the import shards over a bounded number of concurrent importers,
+/// which share the insert concurrency budget of the broker, and aggregates their progress
+pub struct ImportScheduler {
+    workers: u8,
+    pending: VecDeque<(ImportShard, ImportJob)>,
+    running: usize,
+    total: u64,
+    imported: u64,
+    skipped: u64,
+    started_at: Instant,
+    reported_at: Option<Instant>,
+    verify_report: Option<VerifyReport>,
+}
+
+impl ImportScheduler {
+    /// Create a scheduler which runs at most the given number of importers concurrently
+    pub fn new(workers: u8) -> Self {
+        Self {
+            workers: workers.max(1),
+            pending: VecDeque::new(),
+            running: 0,
+            total: 0,
+            imported: 0,
+            skipped: 0,
+            started_at: Instant::now(),
+            reported_at: None,
+            verify_report: None,
+        }
+    }
+
+    /// Queue the shards of an import request
+    pub fn schedule(&mut self, shards: Vec<ImportShard>, job: ImportJob) {
+        if self.is_idle() {
+            self.started_at = Instant::now();
+        }
+        for shard in shards {
+            self.total += (shard.range.end - shard.range.start) as u64;
+            self.pending.push_back((shard, job.clone()));
+        }
+    }
+
+    /// Get the next shard to import along with its share of the insert concurrency budget,
+    /// if a worker and enough parallelism points are available
+    pub fn next(&mut self, parallelism_points: u8, parallelism: u8) -> Option<(ImportShard, ImportJob, u8)> {
+        if self.pending.is_empty() || self.running >= self.workers as usize || parallelism_points == 0 {
+            return None;
+        }
+        let slots = (self.workers as usize).min(self.pending.len() + self.running);
+        let share = ((parallelism as usize / slots) as u8).max(1);
+        let share = if parallelism_points >= share {
+            share
+        } else if self.running == 0 {
+            parallelism_points
+        } else {
+            return None;
+        };
+        self.running += 1;
+        self.pending.pop_front().map(|(shard, job)| (shard, job, share))
+    }
+
+    /// Release the worker of a stopped importer
+    pub fn finished(&mut self) {
+        self.running = self.running.saturating_sub(1);
+    }
+
+    /// Check whether all the scheduled shards were imported
+    pub fn is_idle(&self) -> bool {
+        self.pending.is_empty() && self.running == 0
+    }
+
+    /// Account the progress of a shard importer, the milestones outside of the shard range are not counted.
+    /// The bytes of a zstd block are attributed to its first milestone, so the milestones are counted regardless
+    /// of their bytes, but the initial progress of an importer has no milestone.
+    pub fn progress(&mut self, shard_range: Range<u32>, milestone_index: u32, skipped: bool) {
+        if milestone_index == 0 || !shard_range.contains(&milestone_index) {
+            return;
+        }
+        if skipped {
+            self.skipped += 1;
+        } else {
+            self.imported += 1;
+        }
+    }
+
+    /// Merge the report of a verified shard into the report of the session
+    pub fn verified(&mut self, report: VerifyReport) {
+        match self.verify_report.as_mut() {
+            Some(verify_report) => verify_report.merge(report),
+            None => self.verify_report = Some(report),
+        }
+    }
+
+    /// Take the merged report of the verified shards, if any
+    pub fn take_verify_report(&mut self) -> Option<ImporterSession> {
+        self.verify_report.take().map(ImporterSession::Verified)
+    }
+
+    /// Build the aggregate progress report, at most once per report interval unless forced
+    pub fn report(&mut self, force: bool) -> Option<ImporterSession> {
+        let now = Instant::now();
+        if !force && self.reported_at.map_or(false, |at| now - at < REPORT_INTERVAL) {
+            return None;
+        }
+        self.reported_at = Some(now);
+        let elapsed = (now - self.started_at).as_secs_f64();
+        let milestones_per_sec = if elapsed > 0.0 {
+            self.imported as f64 / elapsed
+        } else {
+            0.0
+        };
+        let remaining = self.total.saturating_sub(self.imported + self.skipped);
+        let eta_secs = (milestones_per_sec > 0.0).then(|| (remaining as f64 / milestones_per_sec) as u64);
+        Some(ImporterSession::Progress {
+            imported: self.imported,
+            skipped: self.skipped,
+            total: self.total,
+            running_shards: self.running,
+            pending_shards: self.pending.len(),
+            milestones_per_sec,
+            eta_secs,
+        })
+    }
+
+    /// Reset the aggregate progress once the import session is closed
+    pub fn reset(&mut self) {
+        self.total = 0;
+        self.imported = 0;
+        self.skipped = 0;
+        self.reported_at = None;
+        self.verify_report = None;
+    }
+}
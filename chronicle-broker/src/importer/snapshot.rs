@@ -0,0 +1,351 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::ledger::snapshot::{
+    Snapshot,
+    SnapshotOutput,
+    SnapshotReader,
+};
+use bee_message::{
+    milestone::Milestone,
+    prelude::{
+        MilestoneIndex,
+        TransactionId,
+    },
+};
+use futures::stream::{
+    self,
+    StreamExt,
+};
+use tokio::sync::mpsc::{
+    Sender,
+    UnboundedSender,
+};
+
+/// The number of retries of the snapshot queries
+const SNAPSHOT_RETRIES: usize = 10;
+/// The maximum number of mismatches described by the report
+const MAX_MISMATCHES: usize = 100;
+/// The number of outputs and milestone diffs imported between the progress updates
+const PROGRESS_INTERVAL: u64 = 10_000;
+/// The number of snapshot records read ahead of their imports
+const READ_AHEAD: usize = 1_000;
+
+/// The outcome of cross-checking a snapshot record with the database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Check {
+    /// The record matches the database
+    Matched,
+    /// The record isn't stored yet
+    Unknown,
+    /// The record differs from the database
+    Mismatched(String),
+}
+
+/// A snapshot record to import
+#[derive(Debug, Clone)]
+pub enum SnapshotRecord {
+    /// An unspent output of a full snapshot
+    Output(SnapshotOutput),
+    /// The milestone of a milestone diff, along with its timestamp
+    Milestone(u32, u64),
+    /// An output created or consumed by a milestone diff
+    Diff(u32, SnapshotOutput, Option<TransactionId>),
+}
+
+/// List the snapshot files of the import path, the full snapshots are imported first and then the delta ones, both
+/// ordered by their ledger index
+pub(crate) async fn snapshot_files(path: &PathBuf) -> anyhow::Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.clone()]);
+    }
+    ensure!(path.is_dir(), "Invalid path");
+    let mut files = Vec::new();
+    let mut dir_entry = tokio::fs::read_dir(path).await?;
+    while let Some(entry) = dir_entry.next_entry().await? {
+        let file_path = entry.path();
+        if file_path.is_file() && file_path.extension().map_or(false, |ext| ext == "bin") {
+            files.push(file_path);
+        }
+    }
+    ensure!(!files.is_empty(), "No snapshot files in the provided path");
+    let mut headers = Vec::new();
+    for file_path in files {
+        let peek_path = file_path.clone();
+        let (kind, ledger_index) = tokio::task::spawn_blocking(move || Snapshot::peek(&peek_path)).await??;
+        headers.push(((kind == SnapshotKind::Delta, ledger_index), file_path));
+    }
+    headers.sort();
+    Ok(headers.into_iter().map(|(_, file_path)| file_path).collect())
+}
+
+/// Import a snapshot file into the `snapshots`, `snapshot_outputs` and `snapshot_diffs` tables, and cross-check its
+/// outputs and milestones with the stored transactions and milestones. The records are read from the file while they
+/// are imported, and the progress counts the imported outputs and milestone diffs.
+pub(crate) async fn import_snapshot(
+    keyspace: ChronicleKeyspace,
+    path: PathBuf,
+    parallelism: usize,
+    progress: impl Fn(u64, u64),
+) -> anyhow::Result<SnapshotReport> {
+    let reader_path = path.clone();
+    let snapshot_reader = tokio::task::spawn_blocking(move || SnapshotReader::open(&reader_path)).await??;
+    let info = snapshot_reader.info().clone();
+    let mut report = SnapshotReport {
+        path,
+        kind: info.kind,
+        ledger_index: info.ledger_index,
+        sep_index: info.sep_index,
+        outputs: info.output_count,
+        milestone_diffs: info.milestone_diff_count,
+        matched: 0,
+        unknown: 0,
+        mismatched: 0,
+        mismatches: Vec::new(),
+    };
+    let ledger_index = info.ledger_index;
+    let (tx, rx) = tokio::sync::mpsc::channel(READ_AHEAD);
+    let reading = tokio::task::spawn_blocking(move || read_records(snapshot_reader, tx));
+    let records = stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|record| (record, rx)) });
+    let mut checks = records
+        .map(|record| {
+            let keyspace = keyspace.clone();
+            async move {
+                let record = record?;
+                // the records of a milestone diff are counted by its milestone
+                let counted = !matches!(record, SnapshotRecord::Diff(..));
+                import_record(&keyspace, ledger_index, record)
+                    .await
+                    .map(|check| (counted, check))
+            }
+        })
+        .buffer_unordered(parallelism.max(1));
+    let total = info.output_count + info.milestone_diff_count;
+    let mut imported = 0;
+    while let Some(check) = checks.next().await {
+        let (counted, check) = check?;
+        match check {
+            Check::Matched => report.matched += 1,
+            Check::Unknown => report.unknown += 1,
+            Check::Mismatched(mismatch) => {
+                report.mismatched += 1;
+                if report.mismatches.len() < MAX_MISMATCHES {
+                    report.mismatches.push(mismatch);
+                }
+            }
+        }
+        if counted {
+            imported += 1;
+            if imported % PROGRESS_INTERVAL == 0 {
+                progress(imported, total);
+            }
+        }
+    }
+    drop(checks);
+    reading.await?;
+    // the header is inserted last, so it only lists the fully imported snapshots
+    insert(&keyspace, Synckey, info).await?;
+    progress(imported, total);
+    Ok(report)
+}
+
+/// Read the records of a snapshot one after another, until the import stops receiving them
+fn read_records<R: std::io::Read>(mut snapshot_reader: SnapshotReader<R>, tx: Sender<anyhow::Result<SnapshotRecord>>) {
+    let send = |record: anyhow::Result<SnapshotRecord>| tx.blocking_send(record).is_ok();
+    loop {
+        match snapshot_reader.next_output() {
+            Ok(Some(output)) => {
+                if !send(Ok(SnapshotRecord::Output(output))) {
+                    return;
+                }
+            }
+            Ok(None) => break,
+            Err(e) => {
+                send(Err(e));
+                return;
+            }
+        }
+    }
+    loop {
+        match snapshot_reader.next_milestone_diff() {
+            Ok(Some(milestone_diff)) => {
+                let milestone_index = milestone_diff.milestone_index();
+                let timestamp = milestone_diff.milestone.essence().timestamp();
+                let records =
+                    std::iter::once(SnapshotRecord::Milestone(milestone_index, timestamp))
+                        .chain(
+                            milestone_diff
+                                .created
+                                .into_iter()
+                                .map(|output| SnapshotRecord::Diff(milestone_index, output, None)),
+                        )
+                        .chain(milestone_diff.consumed.into_iter().map(|(output, consumed_by)| {
+                            SnapshotRecord::Diff(milestone_index, output, Some(consumed_by))
+                        }));
+                for record in records {
+                    if !send(Ok(record)) {
+                        return;
+                    }
+                }
+            }
+            Ok(None) => return,
+            Err(e) => {
+                send(Err(e));
+                return;
+            }
+        }
+    }
+}
+
+/// Insert a snapshot record and cross-check it with the database
+async fn import_record(
+    keyspace: &ChronicleKeyspace,
+    ledger_index: u32,
+    record: SnapshotRecord,
+) -> anyhow::Result<Check> {
+    match record {
+        SnapshotRecord::Output(output) => {
+            let value = SnapshotOutputRecord {
+                ledger_index,
+                message_id: output.message_id,
+                output: output.output.clone(),
+            };
+            insert(keyspace, output.output_id, value).await?;
+            let stored: Option<OutputRes> = select_one(keyspace, output.output_id, SNAPSHOT_RETRIES).await?;
+            Ok(check_output(&output, None, stored.as_ref(), None))
+        }
+        SnapshotRecord::Milestone(milestone_index, timestamp) => {
+            let stored: Option<Milestone> =
+                select_one(keyspace, MilestoneIndex(milestone_index), SNAPSHOT_RETRIES).await?;
+            Ok(check_milestone(milestone_index, timestamp, stored.as_ref()))
+        }
+        SnapshotRecord::Diff(milestone_index, output, consumed_by) => {
+            let value = SnapshotDiffRecord {
+                output_id: output.output_id,
+                message_id: output.message_id,
+                output: output.output.clone(),
+                consumed_by,
+            };
+            insert(keyspace, MilestoneIndex(milestone_index), value).await?;
+            let stored: Option<OutputRes> = select_one(keyspace, output.output_id, SNAPSHOT_RETRIES).await?;
+            // the included message of the spending transaction
+            let spent_by: Option<MessageId> = match consumed_by {
+                Some(transaction_id) => select_one(keyspace, transaction_id, SNAPSHOT_RETRIES).await?,
+                None => None,
+            };
+            Ok(check_output(&output, consumed_by, stored.as_ref(), spent_by))
+        }
+    }
+}
+
+/// Compare a snapshot milestone with the stored one
+pub fn check_milestone(milestone_index: u32, timestamp: u64, stored: Option<&Milestone>) -> Check {
+    match stored {
+        Some(milestone) if milestone.timestamp() == timestamp => Check::Matched,
+        Some(_) => Check::Mismatched(format!("Milestone {} has a different timestamp", milestone_index)),
+        None => Check::Unknown,
+    }
+}
+
+/// Compare a snapshot output with the stored one. A consumed output must be unlocked by `spent_by`, the included
+/// message of the transaction `consumed_by` which spent it.
+pub fn check_output(
+    output: &SnapshotOutput,
+    consumed_by: Option<TransactionId>,
+    stored: Option<&OutputRes>,
+    spent_by: Option<MessageId>,
+) -> Check {
+    let output_id = output.output_id;
+    match (stored, consumed_by) {
+        (Some(stored), _) if stored.message_id != output.message_id || stored.output != output.output => {
+            Check::Mismatched(format!("Output {} differs from the stored one", output_id))
+        }
+        (Some(stored), Some(consumed_by)) => {
+            let spent = stored.unlock_blocks.iter().any(|unlock| {
+                Some(unlock.message_id) == spent_by && unlock.inclusion_state == Some(LedgerInclusionState::Included)
+            });
+            if spent {
+                Check::Matched
+            } else {
+                Check::Mismatched(format!(
+                    "Output {} is consumed by {} but not spent by its included transaction",
+                    output_id, consumed_by
+                ))
+            }
+        }
+        (Some(_), None) => Check::Matched,
+        (None, _) => Check::Unknown,
+    }
+}
+
+/// Insert a row and wait for its response
+async fn insert<K, V>(keyspace: &ChronicleKeyspace, key: K, value: V) -> anyhow::Result<()>
+where
+    ChronicleKeyspace: Insert<K, V>,
+    K: 'static + Send + Sync + Clone,
+    V: 'static + Send + Sync + Clone,
+{
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    keyspace
+        .insert(&key, &value)
+        .consistency(Consistency::One)
+        .build()?
+        .send_local(Box::new(SnapshotWorker {
+            tx,
+            keyspace: keyspace.clone(),
+            key,
+            value,
+            retries: SNAPSHOT_RETRIES,
+        }));
+    rx.recv()
+        .await
+        .ok_or_else(|| anyhow!("No response for the snapshot insert query"))?
+        .map_err(|e| anyhow!("{:?}", e))
+}
+
+/// Inserts a snapshot row and reports the outcome
+#[derive(Clone)]
+struct SnapshotWorker<K, V> {
+    tx: UnboundedSender<Result<(), WorkerError>>,
+    keyspace: ChronicleKeyspace,
+    key: K,
+    value: V,
+    retries: usize,
+}
+
+impl<K, V> Worker for SnapshotWorker<K, V>
+where
+    ChronicleKeyspace: Insert<K, V>,
+    K: 'static + Send + Sync + Clone,
+    V: 'static + Send + Sync + Clone,
+{
+    fn handle_response(self: Box<Self>, giveload: Vec<u8>) -> anyhow::Result<()> {
+        Decoder::try_from(giveload).and_then(|decoder| decoder.get_void())?;
+        self.tx.send(Ok(())).ok();
+        Ok(())
+    }
+    fn handle_error(
+        mut self: Box<Self>,
+        mut error: WorkerError,
+        reporter: &Option<ReporterHandle>,
+    ) -> anyhow::Result<()> {
+        if let WorkerError::Cql(ref mut cql_error) = error {
+            if let (Some(id), Some(reporter)) = (cql_error.take_unprepared_id(), reporter) {
+                handle_insert_unprepared_error(&self, &self.keyspace, &self.key, &self.value, id, reporter)?;
+            }
+        }
+        if self.retries > 0 {
+            self.retries -= 1;
+            let req = self
+                .keyspace
+                .insert_query(&self.key, &self.value)
+                .consistency(Consistency::One)
+                .build()?;
+            tokio::spawn(async { req.send_global(self) });
+        } else {
+            self.tx.send(Err(error)).ok();
+        }
+        Ok(())
+    }
+}
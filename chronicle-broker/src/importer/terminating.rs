@@ -17,10 +17,15 @@ impl<H: ChronicleBrokerScope, T: Send + ImportMode> Terminating<BrokerHandle<H>>
         } else {
             msg = "failed".into();
         }
-        if let Some(log_file) = self.log_file.as_ref() {
+        if self.log_file.is_some() {
+            if let (true, Some(verification)) = (status.is_ok(), self.verification.take()) {
+                let report = verification.finish(&self.sync_data);
+                let event = BrokerEvent::Importer(ImporterSession::Verified(report));
+                supervisor.as_mut().expect("Expected BrokerHandle").send(event).ok();
+            }
             let importer_session = ImporterSession::Finish {
-                from_ms: log_file.from_ms_index(),
-                to_ms: log_file.to_ms_index(),
+                from_ms: self.from_ms,
+                to_ms: self.to_ms,
                 msg,
             };
             let event = BrokerEvent::Importer(importer_session);
@@ -0,0 +1,260 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use futures::{
+    stream::{
+        self,
+        StreamExt,
+    },
+    FutureExt,
+};
+use std::collections::HashSet;
+
+/// The page size of the `analytics` table query
+const PAGE_SIZE: i32 = 5000;
+
+/// The state of a verify import, which compares the milestone data with the database
+pub(crate) struct Verification {
+    /// The differences found so far
+    report: VerifyReport,
+    /// The `analytics` table rows of the import range which are not verified yet
+    analytic_records: HashMap<u32, AnalyticRecord>,
+    /// The verified milestones
+    verified: MilestoneRanges,
+}
+
+impl Verification {
+    /// Fetch the `analytics` table rows of the milestone range to verify
+    pub(crate) async fn try_fetch(
+        keyspace: &ChronicleKeyspace,
+        range: Range<u32>,
+        retries: usize,
+    ) -> anyhow::Result<Self> {
+        let sync_range = SyncRange {
+            from: range.start,
+            to: range.end,
+        };
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let query = |paging_state: Option<Vec<u8>>| -> anyhow::Result<()> {
+            let worker = ValueWorker::new(
+                tx.clone(),
+                keyspace.clone(),
+                sync_range.clone(),
+                retries,
+                std::marker::PhantomData,
+            )
+            .with_paging(PAGE_SIZE, paging_state.clone());
+            keyspace
+                .select::<Iter<AnalyticRecord>>(&sync_range)
+                .consistency(Consistency::One)
+                .page_size(PAGE_SIZE)
+                .paging_state(&paging_state)
+                .build()?
+                .send_local(Box::new(worker));
+            Ok(())
+        };
+        query(None)?;
+        let mut analytic_records = HashMap::new();
+        while let Some(mut records) = rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow!("Unable to fetch the analytics response"))??
+        {
+            let has_more_pages = records.has_more_pages();
+            if has_more_pages {
+                query(records.take_paging_state())?;
+            }
+            for record in records {
+                analytic_records.insert(**record.milestone_index(), record);
+            }
+            if !has_more_pages {
+                break;
+            }
+        }
+        Ok(Self {
+            report: VerifyReport::new(range),
+            analytic_records,
+            verified: MilestoneRanges::default(),
+        })
+    }
+
+    /// Compare the sync and analytics records of an archived milestone with the database ones
+    fn verify_records(&mut self, milestone_index: u32, analytic_record: &AnalyticRecord, sync_data: &SyncData) {
+        self.verified.add(milestone_index);
+        // the archived milestones are synced and logged
+        if sync_data.completed.iter().any(|r| r.contains(&milestone_index)) {
+            self.report.sync.matched += 1;
+        } else if sync_data
+            .synced_but_unlogged
+            .iter()
+            .any(|r| r.contains(&milestone_index))
+        {
+            self.report.sync.mismatched.add(milestone_index);
+        } else {
+            self.report.sync.missing.add(milestone_index);
+        }
+        match self.analytic_records.remove(&milestone_index) {
+            Some(record) => {
+                if same_analytics(&record, analytic_record) {
+                    self.report.analytics.matched += 1;
+                } else {
+                    self.report.analytics.mismatched.add(milestone_index);
+                }
+            }
+            None => self.report.analytics.missing.add(milestone_index),
+        }
+    }
+
+    /// Merge the message differences of a verified milestone
+    pub(crate) fn merge(&mut self, report: VerifyReport) {
+        self.report.merge(report);
+    }
+
+    /// Finish the report, where the database records of the milestones missing from the archive are extra
+    pub(crate) fn finish(mut self, sync_data: &SyncData) -> VerifyReport {
+        for range in sync_data.completed.iter().chain(sync_data.synced_but_unlogged.iter()) {
+            for milestone_index in range.clone() {
+                if !self.verified.contains(milestone_index) {
+                    self.report.sync.extra.add(milestone_index);
+                }
+            }
+        }
+        let mut extra_analytics = self.analytic_records.keys().copied().collect::<Vec<_>>();
+        extra_analytics.sort_unstable();
+        for milestone_index in extra_analytics {
+            self.report.analytics.extra.add(milestone_index);
+        }
+        self.report
+    }
+}
+
+impl ImportMode for Verify {
+    const DRY_RUN: bool = true;
+    fn handle_milestone_data(mut milestone_data: MilestoneData, importer: &mut Importer<Verify>) -> anyhow::Result<()> {
+        // only the messages which would be imported are verified
+        importer.apply_filter(&mut milestone_data);
+        let analytic_record = milestone_data.get_analytic_record().map_err(|e| {
+            error!("Unable to get analytic record for milestone data. Error: {}", e);
+            e
+        })?;
+        let milestone_index = milestone_data.milestone_index();
+        let verification = importer
+            .verification
+            .as_mut()
+            .ok_or_else(|| anyhow!("No verification in importer state"))?;
+        verification.verify_records(milestone_index, &analytic_record, &importer.sync_data);
+        let handle = importer
+            .handle
+            .clone()
+            .ok_or_else(|| anyhow!("No importer handle available!"))?;
+        let keyspace = importer.get_keyspace();
+        let retries = importer.retries_per_query;
+        let parallelism = importer.parallelism.max(1) as usize;
+        let messages = milestone_data.into_iter();
+        tokio::spawn(async move {
+            let result = verify_messages(keyspace, milestone_index, messages, retries, parallelism).await;
+            handle.send(ImporterEvent::Verified(milestone_index, result)).await.ok();
+        });
+        // the messages are consumed by the verification task
+        importer
+            .in_progress_milestones_data
+            .insert(milestone_index, (HashMap::new().into_iter(), analytic_record));
+        Ok(())
+    }
+}
+
+/// Compare the messages and their metadata of an archived milestone with the database rows
+async fn verify_messages(
+    keyspace: ChronicleKeyspace,
+    milestone_index: u32,
+    messages: IntoIter<MessageId, FullMessage>,
+    retries: usize,
+    parallelism: usize,
+) -> anyhow::Result<VerifyReport> {
+    verify_cone(milestone_index, messages.collect(), parallelism, |message_id| {
+        let keyspace = keyspace.clone();
+        async move { select_one::<_, _, (Option<Message>, Option<MessageMetadata>)>(&keyspace, message_id, retries).await }
+    })
+    .await
+}
+
+/// Compare the archived cone of a milestone with the rows returned by `fetch`, which are fetched concurrently.
+/// The parents of the compared messages are fetched too, so the stored messages referenced by the milestone which
+/// are not archived are reported as extra.
+pub async fn verify_cone<F, Fut>(
+    milestone_index: u32,
+    mut archived: HashMap<MessageId, FullMessage>,
+    parallelism: usize,
+    fetch: F,
+) -> anyhow::Result<VerifyReport>
+where
+    F: Fn(MessageId) -> Fut,
+    Fut: std::future::Future<Output = anyhow::Result<Option<(Option<Message>, Option<MessageMetadata>)>>>,
+{
+    let mut report = VerifyReport::new(milestone_index..milestone_index + 1);
+    let mut visited = archived.keys().copied().collect::<HashSet<_>>();
+    let mut pending = visited.iter().copied().collect::<Vec<_>>();
+    while !pending.is_empty() {
+        let mut rows = stream::iter(
+            std::mem::take(&mut pending)
+                .into_iter()
+                .map(|message_id| fetch(message_id).map(move |row| row.map(|row| (message_id, row)))),
+        )
+        .buffer_unordered(parallelism.max(1));
+        while let Some(result) = rows.next().await {
+            let (message_id, row) = result?;
+            let (stored_message, stored_metadata) = row.unwrap_or((None, None));
+            let parents = match archived.remove(&message_id) {
+                Some(FullMessage(message, metadata)) => {
+                    match stored_message {
+                        Some(stored_message) if stored_message == message => report.messages.matched += 1,
+                        Some(_) => report.messages.mismatched.add(milestone_index),
+                        None => report.messages.missing.add(milestone_index),
+                    }
+                    match stored_metadata {
+                        Some(stored_metadata) if same_metadata(&stored_metadata, &metadata) => {
+                            report.metadata.matched += 1
+                        }
+                        Some(_) => report.metadata.mismatched.add(milestone_index),
+                        None => report.metadata.missing.add(milestone_index),
+                    }
+                    metadata.parent_message_ids
+                }
+                None => match stored_metadata {
+                    // the cone of the milestone ends at the messages referenced by other milestones
+                    Some(stored_metadata) if stored_metadata.referenced_by_milestone_index == Some(milestone_index) => {
+                        if stored_message.is_some() {
+                            report.messages.extra.add(milestone_index);
+                        }
+                        report.metadata.extra.add(milestone_index);
+                        stored_metadata.parent_message_ids
+                    }
+                    _ => Vec::new(),
+                },
+            };
+            pending.extend(parents.into_iter().filter(|parent| visited.insert(*parent)));
+        }
+    }
+    Ok(report)
+}
+
+/// Compare the metadata which is final once a message is referenced by a milestone,
+/// the solidity and the promotion hints depend on the node which provided them
+fn same_metadata(stored: &MessageMetadata, archived: &MessageMetadata) -> bool {
+    stored.message_id == archived.message_id
+        && stored.parent_message_ids == archived.parent_message_ids
+        && stored.referenced_by_milestone_index == archived.referenced_by_milestone_index
+        && stored.ledger_inclusion_state == archived.ledger_inclusion_state
+        && stored.conflict_reason == archived.conflict_reason
+}
+
+/// Compare the analytics, the metrics are only compared if they were stored already
+fn same_analytics(stored: &AnalyticRecord, archived: &AnalyticRecord) -> bool {
+    **stored.message_count() == **archived.message_count()
+        && **stored.transaction_count() == **archived.transaction_count()
+        && **stored.transferred_tokens() == **archived.transferred_tokens()
+        && stored
+            .metrics()
+            .map_or(true, |metrics| Some(metrics) == archived.metrics())
+}
@@ -0,0 +1,411 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use bee_message::{
+    address::{
+        Address,
+        Ed25519Address,
+    },
+    input::Input,
+    output::Output,
+    payload::{
+        milestone::MilestonePayload,
+        transaction::{
+            Essence,
+            TransactionPayload,
+        },
+        Payload,
+    },
+    prelude::{
+        MilestoneIndex,
+        TransactionId,
+    },
+};
+use chronicle_common::config::PartitionConfig;
+use futures::{
+    stream::{
+        self,
+        StreamExt,
+    },
+    FutureExt,
+};
+use std::collections::VecDeque;
+
+/// A storage write produced by indexing a message
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexRow {
+    /// A `messages` table row, along with the message metadata once the message is referenced
+    Message(MessageId, Message, Option<MessageMetadata>),
+    /// A `messages` table row of the metadata only
+    Metadata(MessageId, MessageMetadata),
+    /// A `parents` table row
+    Parent(Partitioned<MessageId>, ParentRecord),
+    /// An `indexes` table row
+    Index(Partitioned<Indexation>, IndexationRecord),
+    /// An `addresses` table row
+    Address(Partitioned<Ed25519Address>, AddressRecord),
+    /// A `transactions` table row
+    Transaction((TransactionId, Index), TransactionRecord),
+    /// A `milestones` table row
+    Milestone(MilestoneIndex, (MessageId, Box<MilestonePayload>)),
+    /// A `hints` table row
+    Hint(Hint, Partition),
+}
+
+/// Inserts the storage writes of the indexed messages, ie through the workers of an actor
+pub(crate) trait RowInserter {
+    /// Insert a key/value pair
+    fn insert<K, V>(&self, key: K, value: V) -> anyhow::Result<()>
+    where
+        ChronicleKeyspace: Insert<K, V>,
+        K: 'static + Send + Sync + Clone,
+        V: 'static + Send + Sync + Clone;
+}
+
+/// The inherent trait to return a boxed worker for a given key/value pair in a keyspace
+pub(crate) trait Inherent {
+    fn inherent_boxed<K, V>(&self, keyspace: &ChronicleKeyspace, key: K, value: V) -> Box<dyn Worker>
+    where
+        ChronicleKeyspace: 'static + Insert<K, V>,
+        K: 'static + Send + Sync + Clone,
+        V: 'static + Send + Sync + Clone;
+}
+
+/// Inserts the rows of the indexed messages into a keyspace through an inherent worker
+pub(crate) struct KeyspaceInserter<'a, I> {
+    pub(crate) inherent_worker: &'a I,
+    pub(crate) keyspace: &'a ChronicleKeyspace,
+}
+
+impl<I: Inherent> RowInserter for KeyspaceInserter<'_, I> {
+    fn insert<K, V>(&self, key: K, value: V) -> anyhow::Result<()>
+    where
+        ChronicleKeyspace: Insert<K, V>,
+        K: 'static + Send + Sync + Clone,
+        V: 'static + Send + Sync + Clone,
+    {
+        let insert_req = self
+            .keyspace
+            .insert(&key, &value)
+            .consistency(Consistency::One)
+            .build()?;
+        let worker = self.inherent_worker.inherent_boxed(self.keyspace, key, value);
+        insert_req.send_local(worker);
+        Ok(())
+    }
+}
+
+impl IndexRow {
+    /// Insert the row through the given inserter
+    pub(crate) fn insert<I: RowInserter>(self, inserter: &I) -> anyhow::Result<()> {
+        match self {
+            IndexRow::Message(message_id, message, Some(metadata)) => inserter.insert(message_id, (message, metadata)),
+            IndexRow::Message(message_id, message, None) => inserter.insert(message_id, message),
+            IndexRow::Metadata(message_id, metadata) => inserter.insert(message_id, metadata),
+            IndexRow::Parent(key, value) => inserter.insert(key, value),
+            IndexRow::Index(key, value) => inserter.insert(key, value),
+            IndexRow::Address(key, value) => inserter.insert(key, value),
+            IndexRow::Transaction(key, value) => inserter.insert(key, value),
+            IndexRow::Milestone(key, value) => inserter.insert(key, value),
+            IndexRow::Hint(key, value) => inserter.insert(key, value),
+        }
+    }
+}
+
+/// Turns the messages into their complete set of storage writes.
+/// It's shared by the collector and the importer, so every table is written the same way whatever the source.
+#[derive(Debug, Clone)]
+pub struct MessageIndexer {
+    partition_config: PartitionConfig,
+}
+
+impl MessageIndexer {
+    /// Create a message indexer which partitions the rows with the given partition config
+    pub fn new(partition_config: PartitionConfig) -> Self {
+        Self { partition_config }
+    }
+    /// Get the partition id of a given milestone index
+    fn partition_id(&self, milestone_index: MilestoneIndex) -> u16 {
+        self.partition_config.partition_id(milestone_index.0)
+    }
+    /// Index a message along with its metadata, if it's referenced, within the partition of the given milestone
+    /// index. The `messages` table row comes last, so it's only written once its index rows are.
+    pub fn index(
+        &self,
+        message_id: &MessageId,
+        message: &Message,
+        milestone_index: MilestoneIndex,
+        metadata: Option<&MessageMetadata>,
+    ) -> anyhow::Result<Vec<IndexRow>> {
+        let inclusion_state = metadata.and_then(|metadata| metadata.ledger_inclusion_state);
+        let mut rows = Vec::new();
+        self.index_parents(
+            &mut rows,
+            message_id,
+            message.parents(),
+            milestone_index,
+            inclusion_state,
+        );
+        if let Some(payload) = message.payload() {
+            self.index_payload(
+                &mut rows,
+                message_id,
+                message,
+                payload,
+                milestone_index,
+                inclusion_state,
+                metadata.is_some(),
+            )?;
+        }
+        rows.push(IndexRow::Message(*message_id, message.clone(), metadata.cloned()));
+        Ok(rows)
+    }
+    /// Index the metadata of a message whose message is not known yet
+    pub fn index_metadata(&self, metadata: &MessageMetadata, milestone_index: MilestoneIndex) -> Vec<IndexRow> {
+        let mut rows = Vec::new();
+        self.index_parents(
+            &mut rows,
+            &metadata.message_id,
+            &metadata.parent_message_ids,
+            milestone_index,
+            metadata.ledger_inclusion_state,
+        );
+        rows.push(IndexRow::Metadata(metadata.message_id, metadata.clone()));
+        rows
+    }
+    /// Index the parents' message ids of a given message id
+    fn index_parents(
+        &self,
+        rows: &mut Vec<IndexRow>,
+        message_id: &MessageId,
+        parents: &[MessageId],
+        milestone_index: MilestoneIndex,
+        inclusion_state: Option<LedgerInclusionState>,
+    ) {
+        let partition_id = self.partition_id(milestone_index);
+        for parent_id in parents {
+            let partitioned = Partitioned::new(*parent_id, partition_id, milestone_index.0);
+            rows.push(IndexRow::Parent(
+                partitioned,
+                ParentRecord::new(*message_id, inclusion_state),
+            ));
+            let hint = Hint::parent(parent_id.to_string());
+            rows.push(IndexRow::Hint(hint, Partition::new(partition_id, *milestone_index)));
+        }
+    }
+    /// Index the payload of a given message id
+    fn index_payload(
+        &self,
+        rows: &mut Vec<IndexRow>,
+        message_id: &MessageId,
+        message: &Message,
+        payload: &Payload,
+        milestone_index: MilestoneIndex,
+        inclusion_state: Option<LedgerInclusionState>,
+        referenced: bool,
+    ) -> anyhow::Result<()> {
+        match payload {
+            Payload::Indexation(indexation) => {
+                let partition_id = self.partition_id(milestone_index);
+                let index = Indexation(hex::encode(indexation.index()));
+                let partitioned = Partitioned::new(index.clone(), partition_id, milestone_index.0);
+                rows.push(IndexRow::Index(
+                    partitioned,
+                    IndexationRecord::new(*message_id, inclusion_state),
+                ));
+                let hint = Hint::index(index.0);
+                rows.push(IndexRow::Hint(hint, Partition::new(partition_id, *milestone_index)));
+            }
+            Payload::Transaction(transaction) => self.index_transaction(
+                rows,
+                message_id,
+                message,
+                transaction,
+                milestone_index,
+                inclusion_state,
+                referenced,
+            )?,
+            Payload::Milestone(milestone) => {
+                // only the referenced milestone messages which match their milestone are stored
+                if referenced && message.parents().eq(milestone.essence().parents()) {
+                    rows.push(IndexRow::Milestone(
+                        milestone.essence().index(),
+                        (*message_id, milestone.clone()),
+                    ));
+                }
+            }
+            e => {
+                warn!("Skipping unsupported payload variant: {:?}", e);
+            }
+        }
+        Ok(())
+    }
+    /// Index the inputs, unlocks, outputs, addresses and inner payload of a transaction
+    fn index_transaction(
+        &self,
+        rows: &mut Vec<IndexRow>,
+        message_id: &MessageId,
+        message: &Message,
+        transaction: &Box<TransactionPayload>,
+        milestone_index: MilestoneIndex,
+        inclusion_state: Option<LedgerInclusionState>,
+        referenced: bool,
+    ) -> anyhow::Result<()> {
+        let transaction_id = transaction.id();
+        let unlock_blocks = transaction.unlock_blocks();
+        let confirmed_milestone_index = inclusion_state.map(|_| milestone_index);
+        let Essence::Regular(regular) = transaction.essence();
+        for (input_index, input) in regular.inputs().iter().enumerate() {
+            let input_index = input_index as u16;
+            if let Input::Utxo(utxo_input) = input {
+                let unlock_block = &unlock_blocks[input_index as usize];
+                let input_data = InputData::utxo(utxo_input.clone(), unlock_block.clone());
+                rows.push(IndexRow::Transaction(
+                    (transaction_id, input_index),
+                    TransactionRecord::input(*message_id, input_data, inclusion_state, confirmed_milestone_index),
+                ));
+                // this is the spent output, so it's marked as spent by the unlock block
+                let output_id = utxo_input.output_id();
+                let unlock_data = UnlockData::new(transaction_id, input_index, unlock_block.clone());
+                rows.push(IndexRow::Transaction(
+                    (*output_id.transaction_id(), output_id.index()),
+                    TransactionRecord::unlock(*message_id, unlock_data, inclusion_state, confirmed_milestone_index),
+                ));
+            } else if let Input::Treasury(treasury_input) = input {
+                let input_data = InputData::treasury(treasury_input.clone());
+                rows.push(IndexRow::Transaction(
+                    (transaction_id, input_index),
+                    TransactionRecord::input(*message_id, input_data, inclusion_state, confirmed_milestone_index),
+                ));
+            } else {
+                error!("A new input variant was added to this type!");
+            }
+        }
+        for (output_index, output) in regular.outputs().iter().enumerate() {
+            let output_index = output_index as u16;
+            rows.push(IndexRow::Transaction(
+                (transaction_id, output_index),
+                TransactionRecord::output(*message_id, output.clone(), inclusion_state, confirmed_milestone_index),
+            ));
+            self.index_address(
+                rows,
+                output,
+                &transaction_id,
+                output_index,
+                milestone_index,
+                inclusion_state,
+            )?;
+        }
+        if let Some(payload) = regular.payload() {
+            self.index_payload(
+                rows,
+                message_id,
+                message,
+                payload,
+                milestone_index,
+                inclusion_state,
+                referenced,
+            )?;
+        }
+        Ok(())
+    }
+    /// Index the address of an output
+    fn index_address(
+        &self,
+        rows: &mut Vec<IndexRow>,
+        output: &Output,
+        transaction_id: &TransactionId,
+        index: u16,
+        milestone_index: MilestoneIndex,
+        inclusion_state: Option<LedgerInclusionState>,
+    ) -> anyhow::Result<()> {
+        let (address, amount) = match output {
+            Output::SignatureLockedSingle(sls) => (sls.address(), sls.amount()),
+            Output::SignatureLockedDustAllowance(slda) => (slda.address(), slda.amount()),
+            Output::Treasury(_) => return Ok(()),
+            e => bail!("Unexpected new output variant {:?}", e),
+        };
+        let Address::Ed25519(ed_address) = address;
+        let partition_id = self.partition_id(milestone_index);
+        let partitioned = Partitioned::new(*ed_address, partition_id, milestone_index.0);
+        rows.push(IndexRow::Address(
+            partitioned,
+            AddressRecord::new(output.kind(), *transaction_id, index, amount, inclusion_state),
+        ));
+        let hint = Hint::address(ed_address.to_string());
+        rows.push(IndexRow::Hint(hint, Partition::new(partition_id, *milestone_index)));
+        Ok(())
+    }
+    /// Whether an address received any output before the given milestone, looking up the latest milestone of each
+    /// partition in its `hints` rows, then its `addresses` rows within the partition of the milestone
+    pub async fn address_seen_before(
+        &self,
+        keyspace: &ChronicleKeyspace,
+        address: Ed25519Address,
+        milestone_index: MilestoneIndex,
+        retries: usize,
+    ) -> anyhow::Result<bool> {
+        let hint = Hint::address(address.to_string());
+        let partitions = select_one::<_, _, Vec<(MilestoneIndex, PartitionId)>>(keyspace, hint, retries)
+            .await?
+            .unwrap_or_default();
+        let chunk = |milestone_index: MilestoneIndex| milestone_index.0 / self.partition_config.milestone_chunk_size;
+        if partitions
+            .iter()
+            .any(|(index, _)| chunk(*index) < chunk(milestone_index))
+        {
+            return Ok(true);
+        }
+        let partition_id = self.partition_id(milestone_index);
+        if milestone_index.0 == 0 || !partitions.iter().any(|(_, id)| *id == partition_id) {
+            return Ok(false);
+        }
+        // a single row of the partition before the milestone is enough
+        let key = Partitioned::new(address, partition_id, milestone_index.0 - 1);
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        keyspace
+            .select::<Paged<VecDeque<Partitioned<AddressRecord>>>>(&key)
+            .consistency(Consistency::One)
+            .page_size(1)
+            .build()?
+            .send_local(Box::new(
+                ValueWorker::new(tx, keyspace.clone(), key, retries, std::marker::PhantomData).with_paging(1, None),
+            ));
+        let records = rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow!("No response for the address history query"))??;
+        Ok(records.map(|records| !records.is_empty()).unwrap_or_default())
+    }
+}
+
+/// Look up which receivers of the included transactions of a milestone cone received any output before the
+/// milestone with `seen_before`, concurrently, and set them as its known addresses, so that its analytics count the
+/// other receivers as new addresses
+pub async fn lookup_address_history<F, Fut>(
+    milestone_data: &mut MilestoneData,
+    parallelism: usize,
+    seen_before: F,
+) -> anyhow::Result<()>
+where
+    F: Fn(Ed25519Address) -> Fut,
+    Fut: std::future::Future<Output = anyhow::Result<bool>>,
+{
+    let mut lookups = stream::iter(
+        milestone_data
+            .receivers()
+            .into_iter()
+            .map(|address| seen_before(address).map(move |seen| seen.map(|seen| (address, seen)))),
+    )
+    .buffer_unordered(parallelism.max(1));
+    let mut known_addresses = Vec::new();
+    while let Some(result) = lookups.next().await {
+        let (address, seen) = result?;
+        if seen {
+            known_addresses.push(address);
+        }
+    }
+    milestone_data.set_known_addresses(known_addresses);
+    Ok(())
+}
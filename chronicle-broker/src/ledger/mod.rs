@@ -0,0 +1,452 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::MilestoneData;
+use anyhow::{
+    anyhow,
+    bail,
+    ensure,
+};
+use bee_common::packable::Packable;
+use bee_message::prelude::{
+    Address,
+    Essence,
+    Input,
+    Output,
+    OutputId,
+    Payload,
+    TransactionId,
+};
+use chronicle_storage::access::{
+    FullMessage,
+    LedgerInclusionState,
+};
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    future::Future,
+    path::{
+        Path,
+        PathBuf,
+    },
+    sync::{
+        atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        Arc,
+    },
+    time::SystemTime,
+};
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+/// The bee-style snapshot files, which seed and validate the replayed ledger
+pub mod snapshot;
+
+/// The magic bytes of a ledger checkpoint file
+const CHECKPOINT_MAGIC: [u8; 8] = *b"CHLEDGER";
+/// The version of the ledger checkpoint format
+const CHECKPOINT_VERSION: u8 = 1;
+/// The zstd level of the ledger checkpoint files
+const CHECKPOINT_COMPRESSION_LEVEL: i32 = 3;
+
+/// The counter of the temporary files of the checkpoints, which keeps their names unique
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// The balance of an address
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Balance {
+    /// The amount of the unspent outputs
+    pub amount: u64,
+    /// The amount of the unspent dust allowance outputs, which is included in the amount
+    pub dust_allowance: u64,
+    /// The number of unspent outputs
+    pub output_count: u64,
+}
+
+/// The unspent outputs and the address balances confirmed by a milestone
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LedgerState {
+    ledger_index: u32,
+    outputs: HashMap<OutputId, Output>,
+    balances: HashMap<Address, Balance>,
+}
+
+impl LedgerState {
+    /// Create the ledger state of the given unspent outputs, confirmed by the ledger index milestone
+    pub fn new(ledger_index: u32, outputs: impl IntoIterator<Item = (OutputId, Output)>) -> anyhow::Result<Self> {
+        let mut state = Self {
+            ledger_index,
+            ..Default::default()
+        };
+        for (output_id, output) in outputs {
+            state.create(output_id, output)?;
+        }
+        Ok(state)
+    }
+    /// Get the index of the milestone which confirmed the ledger state
+    pub fn ledger_index(&self) -> u32 {
+        self.ledger_index
+    }
+    /// Get the unspent outputs
+    pub fn outputs(&self) -> &HashMap<OutputId, Output> {
+        &self.outputs
+    }
+    /// Get the balance of an address
+    pub fn balance(&self, address: &Address) -> Balance {
+        self.balances.get(address).copied().unwrap_or_default()
+    }
+    /// Get the total amount of the unspent outputs
+    pub fn supply(&self) -> u64 {
+        self.balances.values().map(|balance| balance.amount).sum()
+    }
+    /// Apply the confirmed transactions and the receipt of the next milestone.
+    /// The state is left partially applied if it fails, and must be discarded.
+    pub fn apply(&mut self, milestone_data: &MilestoneData) -> anyhow::Result<()> {
+        let milestone_index = milestone_data.milestone_index();
+        ensure!(
+            milestone_index == self.ledger_index + 1,
+            "Unable to apply milestone {} to the ledger confirmed by milestone {}",
+            milestone_index,
+            self.ledger_index
+        );
+        ensure!(
+            milestone_data.check_if_completed(),
+            "Unable to apply the incomplete milestone data of {}",
+            milestone_index
+        );
+        let mut created = Vec::new();
+        let mut consumed = Vec::new();
+        for FullMessage(message, metadata) in milestone_data.messages().values() {
+            if let (Some(LedgerInclusionState::Included), Some(Payload::Transaction(transaction))) =
+                (metadata.ledger_inclusion_state, message.payload())
+            {
+                let transaction_id = transaction.id();
+                let Essence::Regular(essence) = transaction.essence();
+                for input in essence.inputs() {
+                    if let Input::Utxo(utxo_input) = input {
+                        consumed.push(*utxo_input.output_id());
+                    }
+                }
+                for (index, output) in essence.outputs().iter().enumerate() {
+                    created.push((OutputId::new(transaction_id, index as u16)?, output.clone()));
+                }
+            }
+        }
+        if let Some(milestone) = milestone_data.milestone.as_ref() {
+            // the migrated funds are created by the milestone which includes their receipt
+            if let Some(Payload::Receipt(receipt)) = milestone.essence().receipt() {
+                let transaction_id = TransactionId::new(*milestone.id());
+                for (index, funds) in receipt.funds().iter().enumerate() {
+                    created.push((
+                        OutputId::new(transaction_id, index as u16)?,
+                        Output::SignatureLockedSingle(funds.output().clone()),
+                    ));
+                }
+            }
+        }
+        // the confirmed transactions of a milestone don't conflict, so they're applied regardless of their order
+        for (output_id, output) in created {
+            self.create(output_id, output)?;
+        }
+        for output_id in consumed.iter() {
+            self.consume(output_id)?;
+        }
+        self.ledger_index = milestone_index;
+        Ok(())
+    }
+    /// Replay the milestones fetched from the source, up to the given milestone index or the first missing milestone
+    /// if none is provided. A checkpoint is written every `interval` milestones if checkpoints are provided, and the
+    /// ledger index is reported after each milestone.
+    pub async fn replay<F, Fut>(
+        &mut self,
+        to: Option<u32>,
+        mut fetch: F,
+        checkpoints: Option<(&LedgerCheckpoints, u32)>,
+        mut progress: impl FnMut(u32),
+    ) -> anyhow::Result<()>
+    where
+        F: FnMut(u32) -> Fut,
+        Fut: Future<Output = anyhow::Result<Option<MilestoneData>>>,
+    {
+        while to.map_or(true, |to| self.ledger_index < to) {
+            let milestone_index = self.ledger_index + 1;
+            let milestone_data = match fetch(milestone_index).await? {
+                Some(milestone_data) => milestone_data,
+                None if to.is_none() => break,
+                None => bail!("Milestone {} is not available to replay the ledger", milestone_index),
+            };
+            self.apply(&milestone_data)?;
+            if let Some((checkpoints, interval)) = checkpoints {
+                if milestone_index % interval == 0 {
+                    checkpoints.write(self).await?;
+                }
+            }
+            progress(milestone_index);
+        }
+        Ok(())
+    }
+    /// Encode the ledger state into a checkpoint
+    pub fn to_checkpoint(&self) -> anyhow::Result<Vec<u8>> {
+        let mut packed = Vec::new();
+        (self.outputs.len() as u64).pack(&mut packed)?;
+        for (output_id, output) in self.outputs.iter() {
+            output_id.pack(&mut packed)?;
+            output.pack(&mut packed)?;
+        }
+        let mut bytes = Vec::with_capacity(CHECKPOINT_MAGIC.len() + 5);
+        bytes.extend(&CHECKPOINT_MAGIC);
+        bytes.push(CHECKPOINT_VERSION);
+        bytes.extend(&self.ledger_index.to_le_bytes());
+        bytes.extend(zstd::bulk::compress(&packed, CHECKPOINT_COMPRESSION_LEVEL)?);
+        Ok(bytes)
+    }
+    /// Decode the ledger state of a checkpoint
+    pub fn from_checkpoint(bytes: &[u8]) -> anyhow::Result<Self> {
+        ensure!(
+            bytes.len() >= CHECKPOINT_MAGIC.len() + 5 && bytes[..CHECKPOINT_MAGIC.len()] == CHECKPOINT_MAGIC,
+            "Invalid ledger checkpoint"
+        );
+        let version = bytes[CHECKPOINT_MAGIC.len()];
+        ensure!(
+            version == CHECKPOINT_VERSION,
+            "Unsupported ledger checkpoint version {}",
+            version
+        );
+        let ledger_index =
+            u32::from_le_bytes(bytes[CHECKPOINT_MAGIC.len() + 1..CHECKPOINT_MAGIC.len() + 5].try_into()?);
+        let packed = zstd::stream::decode_all(&bytes[CHECKPOINT_MAGIC.len() + 5..])?;
+        let reader = &mut packed.as_slice();
+        let outputs_len = u64::unpack(reader)?;
+        let mut outputs = Vec::with_capacity(outputs_len as usize);
+        for _ in 0..outputs_len {
+            outputs.push((OutputId::unpack(reader)?, Output::unpack(reader)?));
+        }
+        ensure!(reader.is_empty(), "Trailing bytes in the ledger checkpoint");
+        Self::new(ledger_index, outputs)
+    }
+    fn create(&mut self, output_id: OutputId, output: Output) -> anyhow::Result<()> {
+        let (address, amount, dust_allowance) = output_balance(&output)?;
+        ensure!(
+            !self.outputs.contains_key(&output_id),
+            "The output {} is created twice",
+            output_id
+        );
+        let balance = self.balances.entry(address).or_default();
+        balance.amount += amount;
+        balance.output_count += 1;
+        if dust_allowance {
+            balance.dust_allowance += amount;
+        }
+        self.outputs.insert(output_id, output);
+        Ok(())
+    }
+    fn consume(&mut self, output_id: &OutputId) -> anyhow::Result<()> {
+        let output = self.outputs.remove(output_id).ok_or_else(|| {
+            anyhow!(
+                "The output {} is consumed but it's not unspent at milestone {}",
+                output_id,
+                self.ledger_index
+            )
+        })?;
+        let (address, amount, dust_allowance) = output_balance(&output)?;
+        let balance = self.balances.entry(address).or_default();
+        balance.amount -= amount;
+        balance.output_count -= 1;
+        if dust_allowance {
+            balance.dust_allowance -= amount;
+        }
+        if balance.output_count == 0 {
+            self.balances.remove(&address);
+        }
+        Ok(())
+    }
+}
+
+/// Get the address, the amount and whether it's a dust allowance of an unspent output
+fn output_balance(output: &Output) -> anyhow::Result<(Address, u64, bool)> {
+    match output {
+        Output::SignatureLockedSingle(output) => Ok((*output.address(), output.amount(), false)),
+        Output::SignatureLockedDustAllowance(output) => Ok((*output.address(), output.amount(), true)),
+        e => bail!("Unexpected unspent output variant {:?}", e),
+    }
+}
+
+/// The latest checkpoint loaded from a checkpoints directory, along with the modification time of its file
+type LatestCheckpoint = Option<(Option<SystemTime>, Arc<LedgerState>)>;
+
+/// The ledger checkpoints of a network, stored as `<ledger index>.ledger` files in a directory
+#[derive(Debug, Clone)]
+pub struct LedgerCheckpoints {
+    dir: PathBuf,
+    /// The latest checkpoint, shared by the clones
+    latest: Arc<RwLock<LatestCheckpoint>>,
+}
+
+impl LedgerCheckpoints {
+    /// Create the checkpoints stored in the given directory
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            latest: Default::default(),
+        }
+    }
+    /// Create the checkpoints of a keyspace, stored in its sub directory of the configured checkpoints dir
+    pub fn of_keyspace(checkpoints_dir: &str, keyspace: &str) -> Self {
+        Self::new(Path::new(checkpoints_dir).join(keyspace))
+    }
+    /// Get the path of the checkpoint of a ledger index
+    pub fn path(&self, ledger_index: u32) -> PathBuf {
+        self.dir.join(format!("{}.ledger", ledger_index))
+    }
+    /// Get the ledger indexes of the checkpoints, in ascending order
+    pub async fn list(&self) -> anyhow::Result<Vec<u32>> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => bail!(
+                "Unable to read the ledger checkpoints {}: {}",
+                self.dir.to_string_lossy(),
+                e
+            ),
+        };
+        let mut ledger_indexes = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            if let Some(ledger_index) = entry
+                .file_name()
+                .to_str()
+                .and_then(|file_name| file_name.strip_suffix(".ledger"))
+                .and_then(|ledger_index| ledger_index.parse::<u32>().ok())
+            {
+                ledger_indexes.push(ledger_index);
+            }
+        }
+        ledger_indexes.sort_unstable();
+        Ok(ledger_indexes)
+    }
+    /// Load the checkpoint of a ledger index
+    pub async fn load(&self, ledger_index: u32) -> anyhow::Result<LedgerState> {
+        let path = self.path(ledger_index);
+        let bytes = tokio::fs::read(&path)
+            .await
+            .map_err(|e| anyhow!("Unable to read the ledger checkpoint {}: {}", path.to_string_lossy(), e))?;
+        let state = LedgerState::from_checkpoint(&bytes)?;
+        ensure!(
+            state.ledger_index() == ledger_index,
+            "The ledger checkpoint {} is confirmed by milestone {}",
+            path.to_string_lossy(),
+            state.ledger_index()
+        );
+        Ok(state)
+    }
+    /// Get the ledger index of the latest checkpoint at or below the milestone index, if any
+    pub async fn latest_at(&self, milestone_index: u32) -> anyhow::Result<Option<u32>> {
+        Ok(self
+            .list()
+            .await?
+            .into_iter()
+            .rev()
+            .find(|ledger_index| *ledger_index <= milestone_index))
+    }
+    /// Load the latest checkpoint at or below the milestone index, if any
+    pub async fn load_latest(&self, milestone_index: u32) -> anyhow::Result<Option<LedgerState>> {
+        match self.latest_at(milestone_index).await? {
+            Some(ledger_index) => self.load(ledger_index).await.map(Some),
+            None => Ok(None),
+        }
+    }
+    /// Get the state of the latest checkpoint, if any.
+    /// It's loaded again only once a newer checkpoint is written, or once its file is modified.
+    pub async fn latest(&self) -> anyhow::Result<Option<Arc<LedgerState>>> {
+        let ledger_index = match self.list().await?.last() {
+            Some(ledger_index) => *ledger_index,
+            None => return Ok(None),
+        };
+        let modified = tokio::fs::metadata(self.path(ledger_index))
+            .await
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if let Some((loaded, state)) = self.latest.read().await.as_ref() {
+            if state.ledger_index() == ledger_index && *loaded == modified {
+                return Ok(Some(state.clone()));
+            }
+        }
+        let state = Arc::new(self.load(ledger_index).await?);
+        self.latest.write().await.replace((modified, state.clone()));
+        Ok(Some(state))
+    }
+    /// Write the checkpoint of a ledger state
+    pub async fn write(&self, state: &LedgerState) -> anyhow::Result<PathBuf> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let path = self.path(state.ledger_index());
+        let tmp_path = tmp_path(&path);
+        tokio::fs::write(&tmp_path, state.to_checkpoint()?).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+        Ok(path)
+    }
+}
+
+/// Get a uniquely named temporary path of a checkpoint, so the concurrent writes of a checkpoint never share it
+fn tmp_path(path: &Path) -> PathBuf {
+    PathBuf::from(format!(
+        "{}.{}.{}.tmp",
+        path.to_string_lossy(),
+        std::process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// The reasons why the ledger state at a milestone is not available
+#[derive(Error, Debug)]
+pub enum StateAtError {
+    /// There is no checkpoint to replay the milestone from
+    #[error("There is no ledger checkpoint at or below milestone {0}")]
+    NoCheckpoint(u32),
+    /// The milestone is too far above the latest checkpoint at or below it
+    #[error("Milestone {milestone_index} is {distance} milestones above the latest ledger checkpoint, the maximum replay distance is {max_distance}")]
+    TooDistant {
+        /// The requested milestone index
+        milestone_index: u32,
+        /// The number of milestones to replay above the checkpoint
+        distance: u32,
+        /// The maximum number of milestones replayed above a checkpoint
+        max_distance: u32,
+    },
+    /// The checkpoint or the milestones could not be read or applied
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// Get the ledger state at the milestone index, which is replayed from the latest checkpoint at or below it through
+/// the fetched milestones. At most `max_distance` milestones are replayed above the checkpoint.
+pub async fn state_at<F, Fut>(
+    checkpoints: &LedgerCheckpoints,
+    milestone_index: u32,
+    max_distance: u32,
+    fetch: F,
+) -> Result<LedgerState, StateAtError>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = anyhow::Result<Option<MilestoneData>>>,
+{
+    let ledger_index = checkpoints
+        .latest_at(milestone_index)
+        .await?
+        .ok_or(StateAtError::NoCheckpoint(milestone_index))?;
+    let distance = milestone_index - ledger_index;
+    if distance > max_distance {
+        return Err(StateAtError::TooDistant {
+            milestone_index,
+            distance,
+            max_distance,
+        });
+    }
+    let mut state = checkpoints.load(ledger_index).await?;
+    state.replay(Some(milestone_index), fetch, None, |_| ()).await?;
+    Ok(state)
+}
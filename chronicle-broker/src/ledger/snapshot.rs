@@ -0,0 +1,365 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::LedgerState;
+use anyhow::{
+    anyhow,
+    bail,
+    ensure,
+};
+use bee_common::packable::Packable;
+use bee_message::{
+    prelude::{
+        MilestonePayload,
+        Output,
+        OutputId,
+        Payload,
+        TransactionId,
+    },
+    MessageId,
+};
+use chronicle_storage::access::{
+    SnapshotInfo,
+    SnapshotKind,
+};
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    io::Read,
+    path::Path,
+};
+
+/// The supported version of the node snapshot files
+const SNAPSHOT_VERSION: u8 = 1;
+
+/// An output of a snapshot, along with the message which created it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotOutput {
+    /// The message which created the output
+    pub message_id: MessageId,
+    /// The id of the output
+    pub output_id: OutputId,
+    /// The output
+    pub output: Output,
+}
+
+impl SnapshotOutput {
+    fn read<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        Ok(Self {
+            message_id: MessageId::unpack(reader)?,
+            output_id: OutputId::unpack(reader)?,
+            output: Output::unpack(reader)?,
+        })
+    }
+}
+
+/// The outputs created and consumed by a milestone
+#[derive(Debug, Clone)]
+pub struct MilestoneDiff {
+    /// The milestone
+    pub milestone: Box<MilestonePayload>,
+    /// The amount of the treasury output consumed by the receipt of the milestone, if any
+    pub consumed_treasury: Option<u64>,
+    /// The outputs created by the milestone
+    pub created: Vec<SnapshotOutput>,
+    /// The outputs consumed by the milestone, along with the transactions which consumed them
+    pub consumed: Vec<(SnapshotOutput, TransactionId)>,
+}
+
+impl MilestoneDiff {
+    /// Get the index of the milestone
+    pub fn milestone_index(&self) -> u32 {
+        *self.milestone.essence().index()
+    }
+    fn read<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        let milestone_len = u32::unpack(reader)? as usize;
+        let mut milestone_bytes = vec![0; milestone_len];
+        reader.read_exact(&mut milestone_bytes)?;
+        let milestone = match Payload::unpack(&mut milestone_bytes.as_slice())? {
+            Payload::Milestone(milestone) => milestone,
+            _ => bail!("Expected a milestone payload in the milestone diff"),
+        };
+        // the treasury output consumed by a receipt is preceded by the id of the milestone which created it
+        let consumed_treasury = if milestone.essence().receipt().is_some() {
+            <[u8; 32]>::unpack(reader)?;
+            Some(u64::unpack(reader)?)
+        } else {
+            None
+        };
+        let created_count = u64::unpack(reader)?;
+        let mut created = Vec::new();
+        for _ in 0..created_count {
+            created.push(SnapshotOutput::read(reader)?);
+        }
+        let consumed_count = u64::unpack(reader)?;
+        let mut consumed = Vec::new();
+        for _ in 0..consumed_count {
+            consumed.push((SnapshotOutput::read(reader)?, TransactionId::unpack(reader)?));
+        }
+        Ok(Self {
+            milestone,
+            consumed_treasury,
+            created,
+            consumed,
+        })
+    }
+}
+
+/// A Hornet/Bee snapshot file. A full snapshot holds the ledger state confirmed by the ledger index milestone and the
+/// milestone diffs which lead back to the solid entry point index, a delta snapshot holds the milestone diffs from
+/// the ledger index of its full snapshot up to its solid entry point index.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// The kind of the snapshot
+    pub kind: SnapshotKind,
+    /// The timestamp of the snapshot
+    pub timestamp: u64,
+    /// The network id of the snapshot
+    pub network_id: u64,
+    /// The solid entry point index of the snapshot
+    pub sep_index: u32,
+    /// The index of the milestone which confirmed the ledger state
+    pub ledger_index: u32,
+    /// The amount of the treasury output, which is only part of the full snapshots
+    pub treasury_amount: Option<u64>,
+    /// The solid entry points
+    pub solid_entry_points: Vec<MessageId>,
+    /// The unspent outputs, which are only part of the full snapshots
+    pub outputs: Vec<SnapshotOutput>,
+    /// The milestone diffs
+    pub milestone_diffs: Vec<MilestoneDiff>,
+}
+
+impl Snapshot {
+    /// Read the version and kind of a snapshot
+    fn read_kind<R: Read>(reader: &mut R) -> anyhow::Result<SnapshotKind> {
+        let version = u8::unpack(reader)?;
+        ensure!(version == SNAPSHOT_VERSION, "Unsupported snapshot version {}", version);
+        Ok(match u8::unpack(reader)? {
+            0 => SnapshotKind::Full,
+            1 => SnapshotKind::Delta,
+            kind => bail!("Unknown snapshot kind {}", kind),
+        })
+    }
+    /// Read the kind and the ledger index of a snapshot file, without reading its outputs and milestone diffs
+    pub fn peek(path: &Path) -> anyhow::Result<(SnapshotKind, u32)> {
+        let file = std::fs::File::open(path)
+            .map_err(|e| anyhow!("Unable to open the snapshot {}: {}", path.to_string_lossy(), e))?;
+        let reader = &mut std::io::BufReader::new(file);
+        let kind = Self::read_kind(reader)?;
+        // skip the timestamp, the network id and the solid entry point index
+        u64::unpack(reader)?;
+        u64::unpack(reader)?;
+        u32::unpack(reader)?;
+        Ok((kind, u32::unpack(reader)?))
+    }
+    /// Read a snapshot from its bytes
+    pub fn read<R: Read>(reader: &mut R) -> anyhow::Result<Self> {
+        let mut snapshot_reader = SnapshotReader::new(reader)?;
+        let mut outputs = Vec::new();
+        while let Some(output) = snapshot_reader.next_output()? {
+            outputs.push(output);
+        }
+        let mut milestone_diffs = Vec::new();
+        while let Some(milestone_diff) = snapshot_reader.next_milestone_diff()? {
+            milestone_diffs.push(milestone_diff);
+        }
+        let SnapshotReader {
+            info,
+            solid_entry_points,
+            ..
+        } = snapshot_reader;
+        Ok(Self {
+            kind: info.kind,
+            timestamp: info.timestamp,
+            network_id: info.network_id,
+            sep_index: info.sep_index,
+            ledger_index: info.ledger_index,
+            treasury_amount: info.treasury_amount,
+            solid_entry_points,
+            outputs,
+            milestone_diffs,
+        })
+    }
+    /// Read a snapshot file
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let file = std::fs::File::open(path)
+            .map_err(|e| anyhow!("Unable to open the snapshot {}: {}", path.to_string_lossy(), e))?;
+        Self::read(&mut std::io::BufReader::new(file))
+    }
+    /// Get the header of the snapshot, as stored in the `snapshots` table
+    pub fn info(&self) -> SnapshotInfo {
+        SnapshotInfo {
+            kind: self.kind,
+            ledger_index: self.ledger_index,
+            sep_index: self.sep_index,
+            timestamp: self.timestamp,
+            network_id: self.network_id,
+            treasury_amount: self.treasury_amount,
+            output_count: self.outputs.len() as u64,
+            milestone_diff_count: self.milestone_diffs.len() as u64,
+        }
+    }
+    /// Get the ledger state of a full snapshot
+    pub fn ledger_state(&self) -> anyhow::Result<LedgerState> {
+        ensure!(
+            self.kind == SnapshotKind::Full,
+            "Only a full snapshot holds a ledger state"
+        );
+        LedgerState::new(
+            self.ledger_index,
+            self.outputs
+                .iter()
+                .map(|output| (output.output_id, output.output.clone())),
+        )
+    }
+}
+
+/// Reads a snapshot one output and one milestone diff at a time, so the snapshot is never held in memory as a whole
+pub struct SnapshotReader<R> {
+    reader: R,
+    info: SnapshotInfo,
+    solid_entry_points: Vec<MessageId>,
+    outputs_left: u64,
+    milestone_diffs_left: u64,
+}
+
+impl SnapshotReader<std::io::BufReader<std::fs::File>> {
+    /// Open a snapshot file and read its header
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let file = std::fs::File::open(path)
+            .map_err(|e| anyhow!("Unable to open the snapshot {}: {}", path.to_string_lossy(), e))?;
+        Self::new(std::io::BufReader::new(file))
+    }
+}
+
+impl<R: Read> SnapshotReader<R> {
+    /// Read the header and the solid entry points of a snapshot
+    pub fn new(mut reader: R) -> anyhow::Result<Self> {
+        let kind = Snapshot::read_kind(&mut reader)?;
+        let timestamp = u64::unpack(&mut reader)?;
+        let network_id = u64::unpack(&mut reader)?;
+        let sep_index = u32::unpack(&mut reader)?;
+        let ledger_index = u32::unpack(&mut reader)?;
+        let sep_count = u64::unpack(&mut reader)?;
+        let output_count = match kind {
+            SnapshotKind::Full => u64::unpack(&mut reader)?,
+            SnapshotKind::Delta => 0,
+        };
+        let milestone_diff_count = u64::unpack(&mut reader)?;
+        // the treasury output is preceded by the id of the milestone which created it
+        let treasury_amount = match kind {
+            SnapshotKind::Full => {
+                <[u8; 32]>::unpack(&mut reader)?;
+                Some(u64::unpack(&mut reader)?)
+            }
+            SnapshotKind::Delta => None,
+        };
+        let mut solid_entry_points = Vec::new();
+        for _ in 0..sep_count {
+            solid_entry_points.push(MessageId::unpack(&mut reader)?);
+        }
+        Ok(Self {
+            reader,
+            info: SnapshotInfo {
+                kind,
+                ledger_index,
+                sep_index,
+                timestamp,
+                network_id,
+                treasury_amount,
+                output_count,
+                milestone_diff_count,
+            },
+            solid_entry_points,
+            outputs_left: output_count,
+            milestone_diffs_left: milestone_diff_count,
+        })
+    }
+    /// Get the header of the snapshot, as stored in the `snapshots` table
+    pub fn info(&self) -> &SnapshotInfo {
+        &self.info
+    }
+    /// Get the solid entry points of the snapshot
+    pub fn solid_entry_points(&self) -> &[MessageId] {
+        &self.solid_entry_points
+    }
+    /// Read the next unspent output, or `None` once all of them are read
+    pub fn next_output(&mut self) -> anyhow::Result<Option<SnapshotOutput>> {
+        if self.outputs_left == 0 {
+            return Ok(None);
+        }
+        self.outputs_left -= 1;
+        SnapshotOutput::read(&mut self.reader).map(Some)
+    }
+    /// Read the next milestone diff, or `None` once all of them are read. The milestone diffs follow the unspent
+    /// outputs, so they must be read first.
+    pub fn next_milestone_diff(&mut self) -> anyhow::Result<Option<MilestoneDiff>> {
+        ensure!(
+            self.outputs_left == 0,
+            "The unspent outputs must be read before the milestone diffs"
+        );
+        if self.milestone_diffs_left == 0 {
+            return Ok(None);
+        }
+        self.milestone_diffs_left -= 1;
+        MilestoneDiff::read(&mut self.reader).map(Some)
+    }
+}
+
+/// The differences between a replayed ledger state and a snapshot of the same milestone
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LedgerDiff {
+    /// The index of the milestone which confirmed both ledger states
+    pub ledger_index: u32,
+    /// The unspent outputs of the snapshot which are missing from the replayed state
+    pub missing: Vec<String>,
+    /// The unspent outputs of the replayed state which are not in the snapshot
+    pub unexpected: Vec<String>,
+    /// The outputs which differ between the snapshot and the replayed state
+    pub mismatched: Vec<String>,
+}
+
+impl LedgerDiff {
+    /// Check if the replayed state matches the snapshot
+    pub fn is_consistent(&self) -> bool {
+        self.missing.is_empty() && self.unexpected.is_empty() && self.mismatched.is_empty()
+    }
+}
+
+impl LedgerState {
+    /// Compare the ledger state with a full snapshot of the same milestone
+    pub fn diff(&self, snapshot: &Snapshot) -> anyhow::Result<LedgerDiff> {
+        ensure!(
+            snapshot.kind == SnapshotKind::Full,
+            "Only a full snapshot holds a ledger state"
+        );
+        if self.ledger_index() != snapshot.ledger_index {
+            bail!(
+                "Unable to compare the ledger confirmed by milestone {} with the snapshot of milestone {}",
+                self.ledger_index(),
+                snapshot.ledger_index
+            );
+        }
+        let mut diff = LedgerDiff {
+            ledger_index: snapshot.ledger_index,
+            ..Default::default()
+        };
+        let mut snapshot_ids = HashSet::with_capacity(snapshot.outputs.len());
+        for SnapshotOutput { output_id, output, .. } in snapshot.outputs.iter() {
+            snapshot_ids.insert(output_id);
+            match self.outputs().get(output_id) {
+                Some(replayed) if replayed == output => (),
+                Some(_) => diff.mismatched.push(output_id.to_string()),
+                None => diff.missing.push(output_id.to_string()),
+            }
+        }
+        for output_id in self.outputs().keys() {
+            if !snapshot_ids.contains(output_id) {
+                diff.unexpected.push(output_id.to_string());
+            }
+        }
+        diff.unexpected.sort();
+        Ok(diff)
+    }
+}
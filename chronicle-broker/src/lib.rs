@@ -9,21 +9,45 @@ pub mod application;
 /// The archiver, which stores milestones and all data in write-ahead-logs
 #[cfg(feature = "application")]
 pub mod archiver;
+/// Bounded actor channels
+#[cfg(feature = "application")]
+pub mod channel;
 /// The collector, which gathers data from feeds and APIs on request
 #[cfg(feature = "application")]
 pub mod collector;
+/// The exporter, which reconstructs milestone ranges from the database into write-ahead-logs
+#[cfg(feature = "application")]
+pub mod exporter;
 /// The importer, which enables to import write-ahead-logs
 #[cfg(feature = "application")]
 pub mod importer;
+/// The message indexer, which turns the messages into their storage writes
+#[cfg(feature = "application")]
+pub mod indexer;
 /// The listener, which receives incoming connections
 #[cfg(feature = "application")]
 pub mod listener;
+/// The archive maintainer, which applies the retention policies to the finalized write-ahead-logs
+#[cfg(feature = "application")]
+pub mod maintainer;
 /// MQTT handler
 #[cfg(feature = "application")]
 pub mod mqtt;
+/// The milestone cones fetched from peer Chronicle endpoints
+#[cfg(feature = "application")]
+pub mod peer;
 /// Missing data requester
 #[cfg(feature = "application")]
 pub mod requester;
+/// The invalidation of the milestone ranges to resync
+#[cfg(feature = "application")]
+pub mod resync;
+/// The analytics rollups, which aggregate the analyzed milestones by hour and day
+#[cfg(feature = "application")]
+pub mod rollup;
+/// The snapshots of the collectors and solidifiers caches, which warm up the next start
+#[cfg(feature = "application")]
+pub mod snapshot;
 /// Data solidifier
 #[cfg(feature = "application")]
 pub mod solidifier;
@@ -42,6 +66,7 @@ mod app {
         ensure,
     };
     pub use application::{
+        network_scoped_name,
         BrokerChild,
         BrokerEvent,
         BrokerHandle,
@@ -52,6 +77,11 @@ mod app {
         Message,
         MessageId,
     };
+    pub use channel::{
+        bounded_channel,
+        BoundedReceiver,
+        BoundedSender,
+    };
     pub use chronicle_common::{
         config::MqttType,
         get_config,
@@ -86,6 +116,12 @@ mod app {
 #[cfg(feature = "application")]
 use app::*;
 
+#[cfg(any(feature = "merge", feature = "application"))]
+/// The archive log file formats
+pub mod archive;
+#[cfg(feature = "merge")]
+/// The ledger state replayed from the confirmed transactions
+pub mod ledger;
 #[cfg(feature = "merge")]
 /// Provide the archive file merger functionality;
 pub mod merge;
@@ -0,0 +1,38 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+
+#[async_trait::async_trait]
+impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for ArchiveMaintainer {
+    async fn event_loop(
+        &mut self,
+        status: Result<(), Need>,
+        supervisor: &mut Option<BrokerHandle<H>>,
+    ) -> Result<(), Need> {
+        status?;
+        info!("{} is running", self.get_name());
+        self.service.update_status(ServiceStatus::Running);
+        let event = BrokerEvent::Children(BrokerChild::ArchiveMaintainer(self.service.clone(), status));
+        if let Some(supervisor) = supervisor {
+            supervisor.send(event).ok();
+            while let Some(event) = self.inbox.recv().await {
+                match event {
+                    ArchiveMaintainerEvent::Maintain => {
+                        self.maintain(supervisor).await;
+                        self.schedule_maintenance();
+                    }
+                    ArchiveMaintainerEvent::Shutdown => {
+                        self.service.update_status(ServiceStatus::Stopping);
+                        // the pending maintenance run is dropped along with the inbox
+                        self.handle.take();
+                        break;
+                    }
+                }
+            }
+            Ok(())
+        } else {
+            Err(Need::Abort)
+        }
+    }
+}
@@ -0,0 +1,33 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+
+#[async_trait::async_trait]
+impl<H: ChronicleBrokerScope> Init<BrokerHandle<H>> for ArchiveMaintainer {
+    async fn init(&mut self, status: Result<(), Need>, supervisor: &mut Option<BrokerHandle<H>>) -> Result<(), Need> {
+        info!(
+            "{} is Initializing, with logs directory: {}",
+            self.get_name(),
+            self.logs_dir.to_string_lossy()
+        );
+        self.service.update_status(ServiceStatus::Initializing);
+        let event = BrokerEvent::Children(BrokerChild::ArchiveMaintainer(self.service.clone(), Ok(())));
+        let _ = supervisor
+            .as_mut()
+            .expect("ArchiveMaintainer expected BrokerHandle")
+            .send(event);
+        if self.sink.is_none() && (self.policies.delete_stored || self.policies.max_disk_usage.is_some()) {
+            warn!(
+                "{} has no archive sink, so no log file will be deleted",
+                self.get_name()
+            );
+        }
+        // the first maintenance run happens right away
+        self.handle
+            .as_ref()
+            .ok_or(Need::Abort)?
+            .send_control(ArchiveMaintainerEvent::Maintain);
+        status
+    }
+}
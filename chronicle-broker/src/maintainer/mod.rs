@@ -0,0 +1,589 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+use super::*;
+use crate::{
+    application::{
+        BrokerChild,
+        BrokerEvent,
+        BrokerHandle,
+        ChronicleBrokerScope,
+    },
+    archive::{
+        self,
+        manifest::{
+            log_file_range,
+            Manifest,
+        },
+        sink::ArchiveSink,
+        ArchiveFormat,
+        BlockIndex,
+        LogEncoder,
+        RecordDecoder,
+        BLOCK_MILESTONES,
+        DEFAULT_COMPRESSION_LEVEL,
+    },
+    archiver::MAX_LOG_SIZE,
+};
+use chronicle_common::{
+    alert,
+    config::ArchiveMaintenanceConfig,
+};
+use std::{
+    ops::Range,
+    path::Path,
+    time::{
+        Duration,
+        SystemTime,
+    },
+};
+use tokio::{
+    fs::File,
+    io::{
+        AsyncWriteExt,
+        BufReader,
+    },
+};
+
+mod event_loop;
+mod init;
+mod terminating;
+
+// ArchiveMaintainer builder
+builder!(ArchiveMaintainerBuilder {
+    logs_dir: PathBuf,
+    network: String,
+    policies: ArchiveMaintenanceConfig,
+    max_log_size: u64,
+    compression_level: i32,
+    sink: ArchiveSink
+});
+
+/// ArchiveMaintainer events
+pub enum ArchiveMaintainerEvent {
+    /// Apply the maintenance policies to the logs directory
+    Maintain,
+    /// Shutdown the archive maintainer
+    Shutdown,
+}
+
+/// ArchiveMaintainerHandle to be passed to the supervisor in order to shutdown
+#[derive(Clone)]
+pub struct ArchiveMaintainerHandle {
+    pub(crate) tx: BoundedSender<ArchiveMaintainerEvent>,
+}
+
+/// ArchiveMaintainerInbox is used to recv events
+pub struct ArchiveMaintainerInbox {
+    pub(crate) rx: BoundedReceiver<ArchiveMaintainerEvent>,
+}
+
+impl Deref for ArchiveMaintainerHandle {
+    type Target = BoundedSender<ArchiveMaintainerEvent>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tx
+    }
+}
+
+impl DerefMut for ArchiveMaintainerHandle {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.tx
+    }
+}
+
+impl Deref for ArchiveMaintainerInbox {
+    type Target = BoundedReceiver<ArchiveMaintainerEvent>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.rx
+    }
+}
+
+impl DerefMut for ArchiveMaintainerInbox {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.rx
+    }
+}
+
+impl Shutdown for ArchiveMaintainerHandle {
+    fn shutdown(self) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        self.send_control(ArchiveMaintainerEvent::Shutdown);
+        None
+    }
+}
+
+/// A finalized log file of a logs directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FinalizedLog {
+    /// The path of the log file
+    pub path: PathBuf,
+    /// The milestone range of the log file
+    pub range: Range<u32>,
+    /// The size of the log file in bytes
+    pub size: u64,
+}
+
+/// ArchiveMaintainer state, which applies the maintenance policies to the logs directory of a network
+pub struct ArchiveMaintainer {
+    /// The archive maintainer service
+    service: Service,
+    /// The logs directory
+    logs_dir: PathBuf,
+    /// The network of the logs directory
+    network: String,
+    /// The maintenance policies
+    policies: ArchiveMaintenanceConfig,
+    /// The upper limit of the merged log file size
+    max_log_size: u64,
+    /// The zstd compression level of the merged log files, which are written as json if none is provided
+    compression_level: Option<i32>,
+    /// The archive sink, where the merged and compressed log files are stored and the log files are verified before
+    /// they are deleted
+    sink: Option<ArchiveSink>,
+    /// The archive maintainer handle, used to schedule the maintenance runs
+    handle: Option<ArchiveMaintainerHandle>,
+    /// The archive maintainer inbox to receive events
+    inbox: ArchiveMaintainerInbox,
+}
+
+impl<H: ChronicleBrokerScope> ActorBuilder<BrokerHandle<H>> for ArchiveMaintainerBuilder {}
+
+/// Implementation of builder
+impl Builder for ArchiveMaintainerBuilder {
+    type State = ArchiveMaintainer;
+    fn build(self) -> Self::State {
+        let config = chronicle_common::get_config();
+        let network = self.network.unwrap_or_default();
+        let (tx, rx) = bounded_channel(
+            config.broker_config.inbox_capacities.archiver,
+            &network_scoped_name(&network, "ArchiveMaintainer"),
+        );
+        Self::State {
+            service: Service::new(),
+            logs_dir: self.logs_dir.expect("Expected logs directory path"),
+            network,
+            policies: self.policies.unwrap_or_default(),
+            max_log_size: self.max_log_size.unwrap_or(MAX_LOG_SIZE),
+            compression_level: self.compression_level,
+            sink: self.sink,
+            handle: Some(ArchiveMaintainerHandle { tx }),
+            inbox: ArchiveMaintainerInbox { rx },
+        }
+        .set_name()
+    }
+}
+
+impl ArchiveMaintainer {
+    pub(crate) fn clone_handle(&self) -> Option<ArchiveMaintainerHandle> {
+        self.handle.clone()
+    }
+}
+
+/// Implement `Name` trait of the ArchiveMaintainer
+impl Name for ArchiveMaintainer {
+    fn set_name(mut self) -> Self {
+        let name = network_scoped_name(&self.network, "ArchiveMaintainer");
+        self.service.update_name(name);
+        self
+    }
+    fn get_name(&self) -> String {
+        self.service.get_name()
+    }
+}
+
+#[async_trait::async_trait]
+impl<H: ChronicleBrokerScope> AknShutdown<ArchiveMaintainer> for BrokerHandle<H> {
+    async fn aknowledge_shutdown(self, mut state: ArchiveMaintainer, status: Result<(), Need>) {
+        state.service.update_status(ServiceStatus::Stopped);
+        let event = BrokerEvent::Children(BrokerChild::ArchiveMaintainer(state.service.clone(), status));
+        let _ = self.send(event);
+    }
+}
+
+impl ArchiveMaintainer {
+    /// Schedule the next maintenance run after the maintenance interval
+    fn schedule_maintenance(&self) {
+        if let Some(handle) = self.handle.clone() {
+            let interval = Duration::from_secs(self.policies.interval_secs);
+            tokio::spawn(async move {
+                tokio::time::sleep(interval).await;
+                let _ = handle.send(ArchiveMaintainerEvent::Maintain).await;
+            });
+        }
+    }
+
+    /// Apply the maintenance policies to the finalized log files
+    async fn maintain<H: ChronicleBrokerScope>(&mut self, supervisor: &BrokerHandle<H>) {
+        // the recently finalized log files may still be stored by the archiver, so they are left for the next run
+        let settle = Duration::from_secs(self.policies.interval_secs);
+        let (mut logs, newest) = match list_logs(&self.logs_dir, settle).await {
+            Ok(listing) => listing,
+            Err(e) => {
+                let msg = format!("Unable to list the log files: {}", e);
+                self.report(supervisor, MaintenanceAction::Failed { path: None, msg });
+                return;
+            }
+        };
+        if let Some(merge_below_size) = self.policies.merge_below_size {
+            for positions in plan_merges(&logs, merge_below_size, self.max_log_size)
+                .into_iter()
+                .rev()
+            {
+                let files = logs[positions.clone()].to_vec();
+                match merge_logs(&self.logs_dir, &files, self.compression_level).await {
+                    Ok(merged) => {
+                        let into = merged.path.clone();
+                        logs.splice(positions, std::iter::once(merged));
+                        let files = files.into_iter().map(|log| log.path).collect();
+                        self.report(supervisor, MaintenanceAction::Merged { files, into });
+                    }
+                    Err(e) => {
+                        let msg = format!("Unable to merge the log files: {}", e);
+                        self.report(supervisor, MaintenanceAction::Failed { path: None, msg });
+                    }
+                }
+            }
+        }
+        let old_after_milestones = self.policies.old_after_milestones;
+        let mut kept = Vec::with_capacity(logs.len());
+        for log in logs {
+            let is_old = old_after_milestones.map(|old_after| is_old(&log.range, newest, old_after));
+            match self.maintain_log(&log, is_old).await {
+                Ok(actions) => {
+                    let is_kept = !actions.iter().any(|action| {
+                        matches!(
+                            action,
+                            MaintenanceAction::Moved { .. } | MaintenanceAction::Deleted { .. }
+                        )
+                    });
+                    for action in actions {
+                        self.report(supervisor, action);
+                    }
+                    if is_kept {
+                        kept.push(log);
+                    }
+                }
+                Err(e) => {
+                    let path = Some(log.path.clone());
+                    let msg = e.to_string();
+                    self.report(supervisor, MaintenanceAction::Failed { path, msg });
+                    kept.push(log);
+                }
+            }
+        }
+        // the merged and compressed log files have new checksums, so they are stored again
+        if let Some(sink) = self.sink.as_ref() {
+            for action in store_unstored(sink, &self.logs_dir, &kept).await {
+                self.report(supervisor, action);
+            }
+        }
+        if let Some(max_disk_usage) = self.policies.max_disk_usage {
+            if let Err(e) = self.enforce_disk_usage(kept, max_disk_usage, supervisor).await {
+                let msg = format!("Unable to enforce the maximum disk usage: {}", e);
+                self.report(supervisor, MaintenanceAction::Failed { path: None, msg });
+            }
+        }
+    }
+
+    /// Apply the policies of a single log file, which is old if `is_old` is true, and all of them are old if it's None
+    async fn maintain_log(&self, log: &FinalizedLog, is_old: Option<bool>) -> anyhow::Result<Vec<MaintenanceAction>> {
+        let mut actions = Vec::new();
+        // the stored log files are deleted before they get compressed, as their checksum would change
+        if self.policies.delete_stored && is_old.unwrap_or(true) && self.delete_if_stored(&log.path).await? {
+            actions.push(MaintenanceAction::Deleted { path: log.path.clone() });
+            return Ok(actions);
+        }
+        if is_old != Some(true) {
+            return Ok(actions);
+        }
+        if self.policies.compress_old {
+            let compression_level = self.compression_level.unwrap_or(DEFAULT_COMPRESSION_LEVEL);
+            if archive::convert(&log.path, ArchiveFormat::Zstd, compression_level).await? {
+                actions.push(MaintenanceAction::Compressed { path: log.path.clone() });
+            }
+        }
+        if let Some(move_old_dir) = self.policies.move_old_dir.as_ref() {
+            let mut dir = PathBuf::from(move_old_dir);
+            if !self.network.is_empty() {
+                dir = dir.join(&self.network);
+            }
+            let to = move_log(&log.path, &dir).await?;
+            actions.push(MaintenanceAction::Moved {
+                from: log.path.clone(),
+                to,
+            });
+        }
+        Ok(actions)
+    }
+
+    /// Delete a log file if it's stored with the same checksum in the archive sink, returning whether it was deleted
+    async fn delete_if_stored(&self, path: &Path) -> anyhow::Result<bool> {
+        match self.sink.as_ref() {
+            Some(sink) if sink.is_stored(path).await? => {
+                // the manifest keeps the entry, as the log file is still part of the archive
+                tokio::fs::remove_file(path).await?;
+                BlockIndex::remove_sidecar(path).await?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Delete the oldest log files verified in the archive sink until the logs directory fits the maximum disk usage,
+    /// and send an alert if it's still exceeded
+    async fn enforce_disk_usage<H: ChronicleBrokerScope>(
+        &self,
+        logs: Vec<FinalizedLog>,
+        max_disk_usage: u64,
+        supervisor: &BrokerHandle<H>,
+    ) -> anyhow::Result<()> {
+        let mut used = disk_usage(&self.logs_dir).await?;
+        for log in logs {
+            if used <= max_disk_usage {
+                break;
+            }
+            if self.delete_if_stored(&log.path).await? {
+                used = used.saturating_sub(log.size);
+                self.report(supervisor, MaintenanceAction::Deleted { path: log.path });
+            }
+        }
+        if used > max_disk_usage {
+            alert!(
+                "The logs directory {} uses {} bytes, which exceeds the maximum disk usage of {} bytes",
+                self.logs_dir.to_string_lossy(),
+                used,
+                max_disk_usage
+            )
+            .await
+            .ok();
+            self.report(
+                supervisor,
+                MaintenanceAction::DiskUsageExceeded {
+                    used,
+                    max: max_disk_usage,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Report a maintenance action to the broker
+    fn report<H: ChronicleBrokerScope>(&self, supervisor: &BrokerHandle<H>, action: MaintenanceAction) {
+        match &action {
+            MaintenanceAction::Failed { msg, .. } => warn!("{}: {}", self.get_name(), msg),
+            _ => info!("{}: {:?}", self.get_name(), action),
+        }
+        let report = MaintenanceReport {
+            network: self.network.clone(),
+            action,
+        };
+        supervisor.send(BrokerEvent::Maintenance(report)).ok();
+    }
+}
+
+/// Merge contiguous log files of a logs directory into a single one, which is recorded in the manifest in place of
+/// the merged ones
+pub async fn merge_logs(
+    logs_dir: &Path,
+    files: &[FinalizedLog],
+    compression_level: Option<i32>,
+) -> anyhow::Result<FinalizedLog> {
+    let range = files[0].range.start..files[files.len() - 1].range.end;
+    let path = logs_dir.join(format!("{}to{}.log", range.start, range.end));
+    let tmp_path = PathBuf::from(format!("{}.merge", path.to_string_lossy()));
+    let mut tmp_file = File::create(&tmp_path)
+        .await
+        .map_err(|e| anyhow!("Unable to create {}: {}", tmp_path.to_string_lossy(), e))?;
+    let format = ArchiveFormat::from_compression_level(compression_level);
+    let compression_level = compression_level.unwrap_or(DEFAULT_COMPRESSION_LEVEL);
+    let mut encoder = LogEncoder::new(format, compression_level, BLOCK_MILESTONES);
+    for log in files {
+        let mut file = File::open(&log.path).await?;
+        let source_format = ArchiveFormat::detect_async(&mut file).await?;
+        let mut reader = BufReader::new(file);
+        let mut decoder = RecordDecoder::new(source_format);
+        while let Some((record, _)) = decoder.next_record(&mut reader).await? {
+            tmp_file.write_all(&encoder.encode(&record)?).await?;
+        }
+    }
+    tmp_file.write_all(&encoder.finish()?).await?;
+    tmp_file.sync_all().await?;
+    tokio::fs::rename(&tmp_path, &path).await?;
+    encoder.index().write_sidecar(&path, encoder.encoded_len()).await?;
+    // the entries of the merged log files overlap the new one, so they are replaced
+    Manifest::record(&path).await?;
+    for log in files {
+        tokio::fs::remove_file(&log.path).await?;
+        BlockIndex::remove_sidecar(&log.path).await?;
+    }
+    Ok(FinalizedLog {
+        path,
+        range,
+        size: encoder.encoded_len(),
+    })
+}
+
+/// Store the log files which aren't marked as stored in the manifest of the logs directory into the archive sink,
+/// returning the actions to report
+pub async fn store_unstored(sink: &ArchiveSink, logs_dir: &Path, logs: &[FinalizedLog]) -> Vec<MaintenanceAction> {
+    let manifest = match Manifest::load(logs_dir).await {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            let msg = format!("Unable to load the manifest: {}", e);
+            return vec![MaintenanceAction::Failed { path: None, msg }];
+        }
+    };
+    let mut actions = Vec::new();
+    for log in logs {
+        let is_stored = log
+            .path
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .map_or(false, |file_name| manifest.is_stored(file_name));
+        if is_stored {
+            continue;
+        }
+        match sink.store(&log.path).await {
+            Ok(()) => actions.push(MaintenanceAction::Stored { path: log.path.clone() }),
+            Err(e) => {
+                let path = Some(log.path.clone());
+                let msg = format!("Unable to store the log file in the archive sink: {}", e);
+                actions.push(MaintenanceAction::Failed { path, msg });
+            }
+        }
+    }
+    actions
+}
+
+/// Plan the merges of the contiguous finalized log files smaller than `merge_below_size`, whose merged size stays
+/// within `max_log_size`. The log files must be sorted by milestone range, and each merge is a range of their
+/// positions.
+pub fn plan_merges(logs: &[FinalizedLog], merge_below_size: u64, max_log_size: u64) -> Vec<Range<usize>> {
+    let mut merges = Vec::new();
+    // the first position and the size of the current group of small log files
+    let mut group: Option<(usize, u64)> = None;
+    for (position, log) in logs.iter().enumerate() {
+        let is_small = log.size < merge_below_size;
+        group = match group {
+            Some((start, size))
+                if is_small && logs[position - 1].range.end == log.range.start && size + log.size <= max_log_size =>
+            {
+                Some((start, size + log.size))
+            }
+            _ => {
+                if let Some((start, _)) = group.filter(|(start, _)| position - start > 1) {
+                    merges.push(start..position);
+                }
+                is_small.then(|| (position, log.size))
+            }
+        };
+    }
+    if let Some((start, _)) = group.filter(|(start, _)| logs.len() - start > 1) {
+        merges.push(start..logs.len());
+    }
+    merges
+}
+
+/// Check whether a log file ends more than `old_after_milestones` before the newest archived milestone
+pub fn is_old(range: &Range<u32>, newest: u32, old_after_milestones: u32) -> bool {
+    range.end.saturating_add(old_after_milestones) <= newest
+}
+
+/// List the settled finalized log files of a logs directory sorted by milestone range, along with the newest
+/// archived milestone index, which also accounts for the recently finalized and the active log files
+async fn list_logs(logs_dir: &Path, settle: Duration) -> anyhow::Result<(Vec<FinalizedLog>, u32)> {
+    let mut logs = Vec::new();
+    let mut newest = 0;
+    let mut entries = tokio::fs::read_dir(logs_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let file_name = match path.file_name().and_then(|file_name| file_name.to_str()) {
+            Some(file_name) => file_name.to_owned(),
+            None => continue,
+        };
+        if let Some(start) = file_name
+            .strip_suffix(".log.active")
+            .and_then(|start| start.parse::<u32>().ok())
+        {
+            newest = newest.max(start);
+            continue;
+        }
+        let range = match log_file_range(&file_name).filter(|_| file_name.ends_with(".log")) {
+            Some(range) => range,
+            None => continue,
+        };
+        newest = newest.max(range.end);
+        let metadata = entry.metadata().await?;
+        let is_settled = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .map_or(false, |age| age >= settle);
+        if is_settled {
+            logs.push(FinalizedLog {
+                path,
+                range,
+                size: metadata.len(),
+            });
+        }
+    }
+    logs.sort_by_key(|log| log.range.start);
+    Ok((logs, newest))
+}
+
+/// Move a log file along with its sidecar index and manifest entry into another directory
+async fn move_log(path: &Path, dir: &Path) -> anyhow::Result<PathBuf> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| anyhow!("Unable to create {}: {}", dir.to_string_lossy(), e))?;
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid log file path: {}", path.to_string_lossy()))?;
+    let to = dir.join(file_name);
+    if to.exists() {
+        bail!(
+            "Unable to move {}, as {} already exists",
+            path.to_string_lossy(),
+            to.to_string_lossy()
+        );
+    }
+    move_file(path, &to).await?;
+    let sidecar_path = BlockIndex::sidecar_path(path);
+    if sidecar_path.exists() {
+        move_file(&sidecar_path, &BlockIndex::sidecar_path(&to)).await?;
+    }
+    // the log file is recorded in its new directory first, so it's always listed in a manifest
+    Manifest::record(&to).await?;
+    Manifest::forget(path).await?;
+    Ok(to)
+}
+
+/// Move a file, which is copied if it's moved to another filesystem
+async fn move_file(from: &Path, to: &Path) -> anyhow::Result<()> {
+    if tokio::fs::rename(from, to).await.is_err() {
+        tokio::fs::copy(from, to).await?;
+        tokio::fs::remove_file(from).await?;
+    }
+    Ok(())
+}
+
+/// Compute the size of the files of a directory and its subdirectories in bytes
+pub async fn disk_usage(dir: &Path) -> anyhow::Result<u64> {
+    let mut used = 0;
+    let mut dirs = vec![dir.to_owned()];
+    while let Some(dir) = dirs.pop() {
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            if metadata.is_dir() {
+                dirs.push(entry.path());
+            } else if metadata.is_file() {
+                used += metadata.len();
+            }
+        }
+    }
+    Ok(used)
+}
@@ -0,0 +1,22 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+
+#[async_trait::async_trait]
+impl<H: ChronicleBrokerScope> Terminating<BrokerHandle<H>> for ArchiveMaintainer {
+    async fn terminating(
+        &mut self,
+        status: Result<(), Need>,
+        supervisor: &mut Option<BrokerHandle<H>>,
+    ) -> Result<(), Need> {
+        info!("{} is terminating", self.get_name());
+        self.service.update_status(ServiceStatus::Stopping);
+        let event = BrokerEvent::Children(BrokerChild::ArchiveMaintainer(self.service.clone(), status));
+        let _ = supervisor
+            .as_mut()
+            .expect("ArchiveMaintainer expected BrokerHandle")
+            .send(event);
+        status
+    }
+}
@@ -1,25 +1,40 @@
 // Copyright 2021 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::MilestoneData;
+use crate::archive::{
+    manifest::{
+        file_checksum,
+        Manifest,
+        GENESIS_HASH,
+    },
+    ArchiveFormat,
+    ArchiveRecord,
+    BlockIndex,
+    LogEncoder,
+    RecordDecoder,
+    BLOCK_MILESTONES,
+};
 use anyhow::{
     anyhow,
     bail,
+    ensure,
 };
 use indicatif::{
     ProgressBar,
     ProgressStyle,
 };
-use serde::Deserialize;
-use serde_json::Value;
 use std::{
     fmt::Display,
+    io::SeekFrom,
     ops::{
         Deref,
         DerefMut,
         Range,
     },
-    path::PathBuf,
+    path::{
+        Path,
+        PathBuf,
+    },
 };
 use thiserror::Error;
 use tokio::{
@@ -28,7 +43,6 @@ use tokio::{
         OpenOptions,
     },
     io::{
-        AsyncBufReadExt,
         AsyncSeekExt,
         AsyncWriteExt,
         BufReader,
@@ -53,6 +67,18 @@ enum LogFileError {
     InvalidRange { range: Range<u32>, path: PathBuf },
     #[error("File exceeds max file size of {max}: {path}")]
     TooBig { max: u64, path: PathBuf },
+    #[error("Checksum {actual} doesn't match the manifest checksum {expected}: {path}")]
+    ChecksumMismatch {
+        expected: String,
+        actual: String,
+        path: PathBuf,
+    },
+    #[error("Manifest hash chain is broken at: {0}")]
+    BrokenHashChain(PathBuf),
+    #[error("File listed in the manifest is missing: {0}")]
+    MissingFile(PathBuf),
+    #[error("File is not listed in the manifest: {0}")]
+    UnlistedFile(PathBuf),
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
@@ -89,6 +115,22 @@ impl LogFileError {
                 "This file exceeds the requested maximum file size.
                 The merger will skip this file."
             }
+            LogFileError::ChecksumMismatch { .. } => {
+                "The file was modified after it was recorded in the manifest, either by bit-rot or by tampering.
+                It should be restored from a backup or from the archive sink."
+            }
+            LogFileError::BrokenHashChain(_) => {
+                "The manifest entry of this file doesn't link to the previous one,
+                which means the manifest itself was modified outside of Chronicle."
+            }
+            LogFileError::MissingFile(_) => {
+                "The file was recorded in the manifest but is not found in the logs directory.
+                It was deleted or moved without its removal being recorded."
+            }
+            LogFileError::UnlistedFile(_) => {
+                "The file was finalized before the manifest was introduced or it was added manually.
+                It will be recorded once the merger rewrites it."
+            }
             LogFileError::Other(_) => "An unknown error occurred.",
         }
     }
@@ -124,41 +166,34 @@ impl Default for ValidationLevel {
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct LightMilestoneData {
-    milestone_index: u32,
-    #[allow(unused)]
-    milestone: Value,
-    #[allow(unused)]
-    messages: Value,
-    #[allow(unused)]
-    pending: Value,
-    #[allow(unused)]
-    created_by: Value,
-}
-
-impl LightMilestoneData {
-    /// Get the milestone index from this milestone data
-    pub fn milestone_index(&self) -> u32 {
-        self.milestone_index
-    }
-}
-
 struct LogFile {
     start: u32,
     end: u32,
     file_path: PathBuf,
     file: File,
     len: u64,
+    format: ArchiveFormat,
+    /// The encoder of the appended records, none if the file was opened for reads
+    encoder: Option<LogEncoder>,
     pub err: bool,
     pub finalized: bool,
 }
 
 impl LogFile {
-    pub fn new(start: u32, end: u32, file_path: PathBuf, file: File, len: u64) -> Self {
+    pub fn new(
+        start: u32,
+        end: u32,
+        file_path: PathBuf,
+        file: File,
+        len: u64,
+        format: ArchiveFormat,
+        encoder: Option<LogEncoder>,
+    ) -> Self {
         Self {
             file,
             len,
+            format,
+            encoder,
             start,
             end,
             err: file_path.extension().map(|ext| ext == "err").unwrap_or(false),
@@ -169,20 +204,42 @@ impl LogFile {
     pub fn len(&self) -> u64 {
         self.len
     }
-    /// Append a new line to the active log file
-    pub async fn append_line(&mut self, line: &String) -> anyhow::Result<()> {
-        let bytes = line.as_bytes();
+    /// Estimate the length of the log file once the record is appended, with the compressed length of zstd records
+    pub fn projected_len(&self, record: &ArchiveRecord) -> u64 {
+        match self.encoder.as_ref() {
+            Some(encoder) => encoder.projected_len(record.encoded_len()),
+            None => self.len + record.encoded_len() as u64,
+        }
+    }
+    /// Append a new record to the active log file, in its format
+    pub async fn append_record(&mut self, record: &ArchiveRecord) -> anyhow::Result<()> {
+        let encoder = self
+            .encoder
+            .as_mut()
+            .ok_or_else(|| anyhow!("Log file is not opened for writes: {:?}", self.file_path))?;
+        let bytes = encoder.encode(record)?;
         // append to the file
-        if let Err(e) = self.file.write_all(bytes).await {
+        if let Err(e) = self.file.write_all(&bytes).await {
             bail!(
-                "Unable to append milestone data line into the log file: {:?}, error: {:?}",
+                "Unable to append milestone data record into the log file: {:?}, error: {:?}",
                 self.file_path,
                 e
             );
         };
         self.end += 1;
-        // update bytes size length;
-        self.len += bytes.len() as u64;
+        // update bytes size length, including the buffered records
+        self.len = encoder.encoded_len();
+        Ok(())
+    }
+
+    /// Write the buffered records of the active log file
+    pub async fn flush(&mut self) -> anyhow::Result<()> {
+        if let Some(encoder) = self.encoder.as_mut() {
+            let bytes = encoder.flush()?;
+            self.file.write_all(&bytes).await?;
+            self.len = encoder.encoded_len();
+        }
+        self.file.flush().await?;
         Ok(())
     }
 
@@ -213,11 +270,26 @@ impl LogFile {
                     pb.set_message(format!("Validating {}", self.file_path.to_string_lossy()));
                 }
                 let path = self.file_path.clone();
-                let reader = BufReader::new(&mut self.file);
+                let mut reader = BufReader::new(&mut self.file);
+                let mut decoder = RecordDecoder::new(self.format);
                 let mut est_idx = self.start;
-                let mut lines = reader.lines();
                 let mut extra = 0;
-                while let Some(line) = lines.next_line().await.map_err(|e| anyhow!(e))? {
+                loop {
+                    let (record, bytes) = match decoder.next_record(&mut reader).await {
+                        Ok(Some(next)) => next,
+                        Ok(None) => break,
+                        Err(e)
+                            if e.kind() == std::io::ErrorKind::InvalidData
+                                || e.kind() == std::io::ErrorKind::UnexpectedEof =>
+                        {
+                            self.err = true;
+                            return Err(LogFileError::MalformattedMilestone {
+                                milestone: est_idx,
+                                path,
+                            });
+                        }
+                        Err(e) => return Err(anyhow!(e).into()),
+                    };
                     // If we've exceeded our claimed range, just add up the extras
                     if est_idx >= self.end {
                         extra += 1;
@@ -228,28 +300,20 @@ impl LogFile {
                         continue;
                     }
                     let milestone_index = match level {
-                        ValidationLevel::Light => match serde_json::from_str::<LightMilestoneData>(&line) {
-                            Ok(milestone) => milestone.milestone_index(),
-                            Err(_) => {
-                                self.err = true;
-                                return Err(LogFileError::MalformattedMilestone {
-                                    milestone: est_idx,
-                                    path,
-                                });
-                            }
-                        },
-                        ValidationLevel::Full => match serde_json::from_str::<MilestoneData>(&line) {
-                            Ok(milestone) => milestone.milestone_index(),
-                            Err(_) => {
-                                self.err = true;
-                                return Err(LogFileError::MalformattedMilestone {
-                                    milestone: est_idx,
-                                    path,
-                                });
-                            }
-                        },
+                        ValidationLevel::Light => record.milestone_index(),
+                        ValidationLevel::Full => record.milestone_data().map(|milestone| milestone.milestone_index()),
                         _ => panic!(),
                     };
+                    let milestone_index = match milestone_index {
+                        Ok(milestone_index) => milestone_index,
+                        Err(_) => {
+                            self.err = true;
+                            return Err(LogFileError::MalformattedMilestone {
+                                milestone: est_idx,
+                                path,
+                            });
+                        }
+                    };
                     if milestone_index > est_idx {
                         self.err = true;
                         return Err(LogFileError::MissingMilestones {
@@ -269,7 +333,7 @@ impl LogFile {
 
                     est_idx += 1;
                     if let Some(pb) = progress_bar.as_mut() {
-                        pb.inc(line.as_bytes().len() as u64);
+                        pb.inc(bytes);
                     }
                 }
                 if extra > 0 {
@@ -289,6 +353,13 @@ impl LogFile {
     }
 
     async fn close(&mut self) -> anyhow::Result<()> {
+        // write the buffered records and the footer index, if any
+        let mut index = None;
+        if let Some(mut encoder) = self.encoder.take() {
+            let bytes = encoder.finish()?;
+            self.file.write_all(&bytes).await?;
+            index = Some((encoder.index().clone(), encoder.encoded_len()));
+        }
         self.file.flush().await?;
         if self.file.metadata().await.is_ok() {
             let new_path = self.file_path.parent().unwrap().join(&format!(
@@ -304,7 +375,14 @@ impl LogFile {
                 }
             ));
             if self.file_path != new_path {
-                tokio::fs::rename(&self.file_path, new_path).await?;
+                tokio::fs::rename(&self.file_path, &new_path).await?;
+                BlockIndex::remove_sidecar(&self.file_path).await?;
+                Manifest::forget(&self.file_path).await?;
+            }
+            // index the written log file and record it in the manifest, unless it's invalid
+            if let Some((index, len)) = index.filter(|_| !self.err) {
+                index.write_sidecar(&new_path, len).await?;
+                Manifest::record(&new_path).await?;
             }
         }
         Ok(())
@@ -381,6 +459,7 @@ impl LogPaths {
             }
             pb.println("Validating logs...");
         }
+        let mut errors = 0;
         let mut prev_end = None;
         for (start, end, path) in self.0.into_iter().rev() {
             if let Some(prev_end) = prev_end {
@@ -389,28 +468,101 @@ impl LogPaths {
                         range: prev_end..start,
                         path: path.clone(),
                     };
-                    Self::handle_err(&mut progress_bar, e)?;
+                    Self::handle_err(&mut progress_bar, &mut errors, e)?;
                 }
             }
-            let file = OpenOptions::new().read(true).open(&path).await?;
+            let mut file = OpenOptions::new().read(true).open(&path).await?;
             let len = file.metadata().await?.len();
+            let format = ArchiveFormat::detect_async(&mut file).await?;
             if len > max_log_size {
                 let e = LogFileError::TooBig {
                     max: max_log_size,
                     path: path.clone(),
                 };
-                Self::handle_err(&mut progress_bar, e)?;
+                Self::handle_err(&mut progress_bar, &mut errors, e)?;
             }
-            let mut log = LogFile::new(start, end, path.clone(), file, len);
+            let mut log = LogFile::new(start, end, path.clone(), file, len, format, None);
             if let Err(e) = log.verify(ValidationLevel::Full, &mut progress_bar).await {
-                Self::handle_err(&mut progress_bar, e)?;
+                Self::handle_err(&mut progress_bar, &mut errors, e)?;
             }
             prev_end = Some(end);
         }
+        ensure!(errors == 0, "Found {} invalid log files", errors);
+        Ok(())
+    }
+
+    /// Verify the logs defined by these paths against the manifest of the logs directory,
+    /// by checking the hash chain of every manifest entry and re-hashing every recorded file
+    pub async fn verify_manifest(&self, logs_dir: &Path, progress_bar: bool) -> anyhow::Result<()> {
+        let manifest = Manifest::load(logs_dir).await?;
+        let files = manifest.files();
+        let mut progress_bar = progress_bar.then(|| {
+            let style = ProgressStyle::default_bar()
+                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg} ({eta})")
+                .progress_chars("##-");
+            ProgressBar::new(files.len() as u64).with_style(style)
+        });
+        if let Some(pb) = progress_bar.as_mut() {
+            pb.println("Verifying logs against the manifest...");
+        }
+        let mut errors = 0;
+        // the entries are only ever appended, so a deleted or replaced entry breaks the chain
+        let mut prev_hash = GENESIS_HASH;
+        for entry in manifest.entries() {
+            if !entry.is_linked_to(prev_hash) {
+                Self::handle_err(
+                    &mut progress_bar,
+                    &mut errors,
+                    LogFileError::BrokenHashChain(logs_dir.join(&entry.file_name)),
+                )?;
+            }
+            prev_hash = entry.hash.as_str();
+        }
+        for entry in files.iter() {
+            let path = logs_dir.join(&entry.file_name);
+            if let Some(pb) = progress_bar.as_mut() {
+                pb.set_message(format!("Hashing {}", entry.file_name));
+            }
+            if !path.exists() {
+                Self::handle_err(&mut progress_bar, &mut errors, LogFileError::MissingFile(path))?;
+            } else {
+                let actual = file_checksum(&path).await?;
+                if actual != entry.sha256 {
+                    let e = LogFileError::ChecksumMismatch {
+                        expected: entry.sha256.clone(),
+                        actual,
+                        path,
+                    };
+                    Self::handle_err(&mut progress_bar, &mut errors, e)?;
+                }
+            }
+            if let Some(pb) = progress_bar.as_mut() {
+                pb.inc(1);
+            }
+        }
+        for (_, _, path) in self.0.iter() {
+            let listed = path
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .map_or(false, |file_name| {
+                    files.iter().any(|entry| entry.file_name == file_name)
+                });
+            // the active log files are recorded once they're finalized
+            if !listed && path.extension().map_or(true, |ext| ext != "active") {
+                Self::handle_err(&mut progress_bar, &mut errors, LogFileError::UnlistedFile(path.clone()))?;
+            }
+        }
+        if let Some(pb) = progress_bar.as_ref() {
+            pb.finish_with_message(format!("Manifest head: {}", manifest.head()));
+        }
+        ensure!(errors == 0, "Found {} log files which don't match the manifest", errors);
         Ok(())
     }
 
-    fn handle_err(pb: &mut Option<ProgressBar>, e: LogFileError) -> anyhow::Result<()> {
+    /// Report a validation error, which fails right away without a progress bar,
+    /// otherwise it's printed and counted so the validation fails once every file is checked
+    fn handle_err(pb: &mut Option<ProgressBar>, errors: &mut usize, e: LogFileError) -> anyhow::Result<()> {
+        *errors += 1;
         if let Some(pb) = pb.as_mut() {
             pb.println(format!("Validation Error: {}\n\t{}", e, e.additional_info()));
         } else {
@@ -443,6 +595,7 @@ impl DerefMut for LogPaths {
 pub struct Merger {
     logs_dir: PathBuf,
     max_log_size: u64,
+    compression_level: i32,
     progress_bar: Option<ProgressBar>,
     backup_dir: Option<PathBuf>,
     validation_level: ValidationLevel,
@@ -451,10 +604,12 @@ pub struct Merger {
 }
 
 impl Merger {
-    /// Create new merger to merge the log files in the logs dir
+    /// Create new merger to merge the log files in the logs dir.
+    /// The zstd log files are written using the given compression level.
     pub fn new(
         logs_dir: PathBuf,
         max_log_size: u64,
+        compression_level: i32,
         backup_logs: bool,
         progress_bar: bool,
         validation_level: ValidationLevel,
@@ -473,6 +628,7 @@ impl Merger {
         Ok(Self {
             logs_dir,
             max_log_size,
+            compression_level,
             progress_bar,
             backup_dir,
             validation_level,
@@ -519,7 +675,7 @@ impl Merger {
                 {
                     match e {
                         LogFileError::EmptyFile(_) => {
-                            tokio::fs::remove_file(path).await?;
+                            remove_log(&path).await?;
                         }
                         _ => {
                             writer.err = true;
@@ -562,7 +718,7 @@ impl Merger {
                     {
                         match e {
                             LogFileError::EmptyFile(_) => {
-                                tokio::fs::remove_file(path).await?;
+                                remove_log(&path).await?;
                             }
                             _ => {
                                 writer.err = true;
@@ -592,7 +748,7 @@ impl Merger {
         {
             match e {
                 LogFileError::EmptyFile(_) => {
-                    tokio::fs::remove_file(&path).await?;
+                    remove_log(&path).await?;
                 }
                 _ => {
                     return self.handle_error(e, total_bytes).map(|_| active);
@@ -602,7 +758,7 @@ impl Merger {
         }
 
         let mut buf_reader = BufReader::new(&mut consumed_file.file);
-        let mut line_buffer = String::new();
+        let mut decoder = RecordDecoder::new(consumed_file.format);
         let mut milestone_index = start;
         let mut total_read_bytes = 0;
         if let Some(pb) = self.progress_bar.as_mut() {
@@ -611,92 +767,80 @@ impl Merger {
             pb.set_message(format!("Consuming {}", path.to_string_lossy()));
         }
         loop {
-            match buf_reader.read_line(&mut line_buffer).await {
-                Ok(bytes) => {
-                    total_read_bytes += bytes as u64;
-                    let ms_line = std::mem::take(&mut line_buffer);
-                    if bytes == 0 {
-                        // if let Some(pb) = self.progress_bar.as_mut() {
-                        //    pb.println(format!("Removing log file {}", path.to_string_lossy()));
-                        //}
-                        tokio::fs::remove_file(&path).await?;
-                        break;
-                    } else {
-                        // Perform validation if JIT is enabled or we are looking at an overlapping milestone
-                        if milestone_index < active.end || self.validation_level == ValidationLevel::JustInTime {
-                            if let Ok(idx) =
-                                serde_json::from_str::<MilestoneData>(&ms_line).map(|data| data.milestone_index())
-                            {
-                                if idx < start || idx >= end {
-                                    consumed_file.err = true;
-                                    let err = LogFileError::OutsideMilestone { milestone: idx, path };
-                                    return self.handle_error(err, total_bytes - total_read_bytes).map(|_| active);
-                                } else if milestone_index < idx {
-                                    consumed_file.err = true;
-                                    let err = LogFileError::MissingMilestones {
-                                        range: milestone_index..idx,
-                                        path,
-                                    };
-                                    return self.handle_error(err, total_bytes - total_read_bytes).map(|_| active);
-                                } else if milestone_index > idx {
-                                    consumed_file.err = true;
-                                    let err = LogFileError::DuplicateMilestone { milestone: idx, path };
-                                    return self.handle_error(err, total_bytes - total_read_bytes).map(|_| active);
-                                }
-                            } else {
+            match decoder.next_record(&mut buf_reader).await {
+                Ok(Some((record, bytes))) => {
+                    total_read_bytes += bytes;
+                    // Perform validation if JIT is enabled or we are looking at an overlapping milestone
+                    if milestone_index < active.end || self.validation_level == ValidationLevel::JustInTime {
+                        if let Ok(idx) = record.milestone_data().map(|data| data.milestone_index()) {
+                            if idx < start || idx >= end {
                                 consumed_file.err = true;
-                                let err = LogFileError::MalformattedMilestone {
-                                    milestone: milestone_index,
+                                let err = LogFileError::OutsideMilestone { milestone: idx, path };
+                                return self.handle_error(err, total_bytes - total_read_bytes).map(|_| active);
+                            } else if milestone_index < idx {
+                                consumed_file.err = true;
+                                let err = LogFileError::MissingMilestones {
+                                    range: milestone_index..idx,
                                     path,
                                 };
                                 return self.handle_error(err, total_bytes - total_read_bytes).map(|_| active);
+                            } else if milestone_index > idx {
+                                consumed_file.err = true;
+                                let err = LogFileError::DuplicateMilestone { milestone: idx, path };
+                                return self.handle_error(err, total_bytes - total_read_bytes).map(|_| active);
                             }
+                        } else {
+                            consumed_file.err = true;
+                            let err = LogFileError::MalformattedMilestone {
+                                milestone: milestone_index,
+                                path,
+                            };
+                            return self.handle_error(err, total_bytes - total_read_bytes).map(|_| active);
+                        }
+                    }
+                    // We can fit this record in the writer file
+                    if active.projected_len(&record) < self.max_log_size {
+                        // Handle overlapping files by skipping milestones until we reach
+                        // the end of the active log
+                        if milestone_index == active.end {
+                            active.append_record(&record).await?;
+                        }
+                        if let Some(pb) = self.progress_bar.as_mut() {
+                            pb.inc(bytes);
                         }
-                        // We can fit this line in the writer file
-                        if active.len() + (bytes as u64) < self.max_log_size {
-                            // if let Some(pb) = self.progress_bar.as_mut() {
-                            //    pb.println(format!("Appending to log file {}", active.file_path.to_string_lossy()));
-                            //}
-
-                            // Handle overlapping files by skipping milestones until we reach
-                            // the end of the active log
-                            if milestone_index == active.end {
-                                active.append_line(&ms_line).await?;
-                            }
-                            if let Some(pb) = self.progress_bar.as_mut() {
-                                pb.inc(bytes as u64);
-                            }
 
-                        // Adding this line would go over our limit
+                    // Adding this record would go over our limit
+                    } else {
+                        active.finalized = true;
+                        // If we read more than just a single record from the file
+                        if milestone_index != start {
+                            // Create a new file to funnel the remainder of the milestones to
+                            active = self.create_active(milestone_index, active.format).await?;
+                            // Add the record we just read
+                            active.append_record(&record).await?;
+
+                        // Otherwise we shouldn't copy it record-by-record, just set the active file
                         } else {
-                            // if let Some(pb) = self.progress_bar.as_mut() {
-                            //    pb.println("Exceeded file size!");
-                            //}
-                            active.finalized = true;
-                            // If we read more than just a single line from the file
-                            if total_read_bytes != bytes as u64 {
-                                // Create a new file to funnel the remainder of the milestones to
-                                active = self.create_active(milestone_index).await?;
-                                // Add the line we just read
-                                active.append_line(&ms_line).await?;
-
-                            // Otherwise we shouldn't copy it line-by-line, just set the active file
-                            } else {
-                                // Drop our reader file so we can access it as a writer
-                                drop(consumed_file);
-                                // Drop the writer ahead of reassignment so we don't conflict names
-                                drop(active);
-                                // We already validated the file above, so it's not needed here
-                                // Just return the new active file
-                                return Ok(self.open_write(&path, start, end).await?);
-                            }
-                            if let Some(pb) = self.progress_bar.as_mut() {
-                                pb.inc(bytes as u64);
-                            }
+                            // Drop our reader file so we can access it as a writer
+                            drop(consumed_file);
+                            // Drop the writer ahead of reassignment so we don't conflict names
+                            drop(active);
+                            // We already validated the file above, so it's not needed here
+                            // Just return the new active file
+                            return Ok(self.open_write(&path, start, end).await?);
+                        }
+                        if let Some(pb) = self.progress_bar.as_mut() {
+                            pb.inc(bytes);
                         }
                     }
                     milestone_index += 1;
                 }
+                Ok(None) => {
+                    // Write the buffered records before the consumed file is removed
+                    active.flush().await?;
+                    remove_log(&path).await?;
+                    break;
+                }
                 Err(e) => {
                     return self.handle_error(e, total_bytes - total_read_bytes).map(|_| active);
                 }
@@ -747,13 +891,42 @@ impl Merger {
             tokio::fs::copy(file_path, dir.join(file_path.file_name().unwrap())).await?;
         }
         tokio::fs::rename(file_path, &active_file_path).await?;
-        let active_file = OpenOptions::new()
+        // The file is recorded again once it's closed
+        Manifest::forget(file_path).await?;
+        let mut active_file = OpenOptions::new()
             .read(true)
             .append(true)
             .open(&active_file_path)
             .await?;
-        let active_len = active_file.metadata().await?.len();
-        Ok(LogFile::new(start, end, active_file_path, active_file, active_len))
+        let format = ArchiveFormat::detect_async(&mut active_file).await?;
+        let mut active_len = active_file.metadata().await?.len();
+        let index = match format {
+            ArchiveFormat::Json => {
+                // The sidecar index is still found next to the previous path
+                let index = BlockIndex::open(file_path, &mut active_file, format).await?;
+                active_file.seek(SeekFrom::Start(0)).await?;
+                index
+            }
+            ArchiveFormat::Zstd => {
+                // Drop the footer index, it's written back once the file is closed
+                let (index, blocks_end) = BlockIndex::load(&mut active_file).await?;
+                active_file.set_len(blocks_end).await?;
+                active_file.seek(SeekFrom::Start(0)).await?;
+                active_len = blocks_end;
+                index
+            }
+        };
+        BlockIndex::remove_sidecar(file_path).await?;
+        let encoder = LogEncoder::resume(format, self.compression_level, BLOCK_MILESTONES, index, active_len);
+        Ok(LogFile::new(
+            start,
+            end,
+            active_file_path,
+            active_file,
+            active_len,
+            format,
+            Some(encoder),
+        ))
     }
 
     async fn open_read(&mut self, file_path: &PathBuf, start: u32, end: u32) -> anyhow::Result<LogFile> {
@@ -764,12 +937,13 @@ impl Merger {
         if let Some(ref dir) = self.backup_dir {
             tokio::fs::copy(file_path, dir.join(file_path.file_name().unwrap())).await?;
         }
-        let file = OpenOptions::new().read(true).open(&file_path).await?;
+        let mut file = OpenOptions::new().read(true).open(&file_path).await?;
         let len = file.metadata().await?.len();
-        Ok(LogFile::new(start, end, file_path.clone(), file, len))
+        let format = ArchiveFormat::detect_async(&mut file).await?;
+        Ok(LogFile::new(start, end, file_path.clone(), file, len, format, None))
     }
 
-    async fn create_active(&mut self, milestone_index: u32) -> anyhow::Result<LogFile> {
+    async fn create_active(&mut self, milestone_index: u32, format: ArchiveFormat) -> anyhow::Result<LogFile> {
         let file_path = self.logs_dir.join(&format!("{}.log.active", milestone_index));
         let file: File = OpenOptions::new()
             .append(true)
@@ -784,6 +958,22 @@ impl Merger {
                 )
             })?;
         let len = file.metadata().await?.len();
-        Ok(LogFile::new(milestone_index, milestone_index, file_path, file, len))
+        let encoder = LogEncoder::new(format, self.compression_level, BLOCK_MILESTONES);
+        Ok(LogFile::new(
+            milestone_index,
+            milestone_index,
+            file_path,
+            file,
+            len,
+            format,
+            Some(encoder),
+        ))
     }
 }
+
+/// Remove a log file along with its sidecar index and manifest entry
+async fn remove_log(path: &Path) -> anyhow::Result<()> {
+    tokio::fs::remove_file(path).await?;
+    BlockIndex::remove_sidecar(path).await?;
+    Manifest::forget(path).await
+}
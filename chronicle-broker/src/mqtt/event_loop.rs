@@ -22,7 +22,8 @@ impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Mqtt<Messages> {
                     // partitioning based on first byte of the message_id
                     let collector_partition_id = self.partitioner.partition_id(&message_id);
                     if let Some(collector_handle) = self.collectors_handles.get(&collector_partition_id) {
-                        let _ = collector_handle.send(CollectorEvent::Message(message_id, msg));
+                        // awaiting the bounded collector inbox applies backpressure to the mqtt stream
+                        let _ = collector_handle.send(CollectorEvent::Message(message_id, msg)).await;
                     }
                 };
             } else {
@@ -52,7 +53,7 @@ impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Mqtt<MessagesRefere
                     // partitioning based on first byte of the message_id
                     let collector_partition_id = self.partitioner.partition_id(&msg_ref.message_id);
                     if let Some(collector_handle) = self.collectors_handles.get(&collector_partition_id) {
-                        let _ = collector_handle.send(CollectorEvent::MessageReferenced(msg_ref));
+                        let _ = collector_handle.send(CollectorEvent::MessageReferenced(msg_ref)).await;
                     }
                 };
             } else {
@@ -24,7 +24,8 @@ builder!(MqttBuilder<T> {
     url: Url,
     topic: T,
     collectors_handles: HashMap<u8, CollectorHandle>,
-    stream_capacity: usize
+    stream_capacity: usize,
+    network: String
 });
 
 /// MqttHandle to be passed to the supervisor in order to shutdown
@@ -55,6 +56,8 @@ pub struct Mqtt<T> {
     partitioner: MessageIdPartitioner,
     handle: Option<MqttHandle>,
     inbox: Option<MqttInbox>,
+    /// The network this feed source belongs to
+    network: String,
     _topic: T,
 }
 
@@ -144,6 +147,7 @@ impl<T: Topic> Builder for MqttBuilder<T> {
             stream_capacity: self.stream_capacity.unwrap_or(10000),
             handle: None,
             inbox: None,
+            network: self.network.unwrap_or_default(),
             _topic: self.topic.unwrap(),
         }
         .set_name()
@@ -153,7 +157,11 @@ impl<T: Topic> Builder for MqttBuilder<T> {
 /// impl name of the Mqtt<T>
 impl<T: Topic> Name for Mqtt<T> {
     fn set_name(mut self) -> Self {
-        let name = format!("{}@{}", T::name(), self.url.as_str());
+        let name = format!(
+            "{}@{}",
+            network_scoped_name(&self.network, T::name()),
+            self.url.as_str()
+        );
         self.service.update_name(name);
         self
     }
@@ -0,0 +1,139 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use bee_message::payload::Payload;
+use chronicle_common::Wrapper;
+use lru::LruCache;
+use reqwest::{
+    Client,
+    StatusCode,
+};
+use serde_json::Value;
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+/// The number of milestone cones cached per network
+const PEER_CONES_CAPACITY: usize = 100;
+
+/// A milestone cone fetched from a peer Chronicle
+enum Cone {
+    /// The messages of the cone
+    Fetched(HashMap<MessageId, FullMessage>),
+    /// The peer did not archive the milestone
+    Unavailable,
+}
+
+type ConeSlot = Arc<tokio::sync::Mutex<Option<Cone>>>;
+
+/// The milestone cones fetched from the archives of the peer Chronicle endpoints, shared by the requesters of a
+/// network. A cone is fetched once per peer, then the requested messages of the milestone are served from it.
+#[derive(Clone)]
+pub struct PeerCones {
+    slots: Arc<Mutex<LruCache<(Url, u32), ConeSlot>>>,
+}
+
+impl Default for PeerCones {
+    fn default() -> Self {
+        Self {
+            slots: Arc::new(Mutex::new(LruCache::new(PEER_CONES_CAPACITY))),
+        }
+    }
+}
+
+impl PeerCones {
+    /// Get a message of a milestone cone archived by the peer, or none if the peer did not archive the milestone
+    pub async fn message(
+        &self,
+        client: &Client,
+        peer: &Url,
+        milestone_index: u32,
+        message_id: &MessageId,
+    ) -> anyhow::Result<Option<FullMessage>> {
+        self.find(client, peer, milestone_index, |messages| {
+            messages.get(message_id).cloned()
+        })
+        .await
+    }
+    /// Get the milestone message of a milestone cone archived by the peer, or none if the peer did not archive the
+    /// milestone
+    pub async fn milestone(
+        &self,
+        client: &Client,
+        peer: &Url,
+        milestone_index: u32,
+    ) -> anyhow::Result<Option<FullMessage>> {
+        self.find(client, peer, milestone_index, |messages| {
+            messages
+                .values()
+                .find(|full_message| match full_message.0.payload() {
+                    Some(Payload::Milestone(milestone)) => *milestone.essence().index() == milestone_index,
+                    _ => false,
+                })
+                .cloned()
+        })
+        .await
+    }
+    async fn find<F>(
+        &self,
+        client: &Client,
+        peer: &Url,
+        milestone_index: u32,
+        f: F,
+    ) -> anyhow::Result<Option<FullMessage>>
+    where
+        F: FnOnce(&HashMap<MessageId, FullMessage>) -> Option<FullMessage>,
+    {
+        let slot = {
+            let mut slots = self.slots.lock().unwrap();
+            let key = (peer.clone(), milestone_index);
+            if let Some(slot) = slots.get(&key) {
+                slot.clone()
+            } else {
+                let slot = ConeSlot::default();
+                slots.put(key, slot.clone());
+                slot
+            }
+        };
+        // the requesters asking for the same cone wait for the first one to fetch it
+        let mut cone = slot.lock().await;
+        if cone.is_none() {
+            cone.replace(Self::fetch(client, peer, milestone_index).await?);
+        }
+        match cone.as_ref() {
+            Some(Cone::Fetched(messages)) => Ok(f(messages)),
+            _ => Ok(None),
+        }
+    }
+    async fn fetch(client: &Client, peer: &Url, milestone_index: u32) -> anyhow::Result<Cone> {
+        let get_cone_url = peer.join(&format!("archive/milestones/{}", milestone_index))?;
+        let cone_response = client
+            .get(get_cone_url)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Error sending request for milestone cone: {}", e))?;
+        if cone_response.status() == StatusCode::NOT_FOUND {
+            return Ok(Cone::Unavailable);
+        }
+        if !cone_response.status().is_success() {
+            let status = cone_response.status();
+            let err = cone_response.json::<Value>().await.unwrap_or_default();
+            bail!("Received error {} requesting milestone cone: {}", status, err);
+        }
+        // the peer wraps its responses in a `data` object, like the nodes
+        let milestone_data = cone_response
+            .json::<JsonData<MilestoneData>>()
+            .await
+            .map(|json| json.into_inner())
+            .map_err(|e| anyhow!("Error deserializing milestone cone: {}", e))?;
+        ensure!(
+            milestone_data.milestone_index == milestone_index,
+            "Received the cone of milestone {} instead of {}",
+            milestone_data.milestone_index,
+            milestone_index
+        );
+        Ok(Cone::Fetched(milestone_data.messages))
+    }
+}
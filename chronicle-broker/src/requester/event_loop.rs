@@ -16,7 +16,7 @@ impl EventLoop<CollectorHandle> for Requester {
         let collector_handle = supervisor.as_mut().expect("Requester expected collector handle");
         self.service.update_status(ServiceStatus::Running);
         let event = CollectorEvent::Internal(Internal::Service(self.service.clone()));
-        let _ = collector_handle.send(event);
+        let _ = collector_handle.send(event).await;
         while let Some(event) = self.inbox.recv().await {
             match event {
                 RequesterEvent::RequestFullMessage(message_id, try_ms_index) => {
@@ -62,23 +62,39 @@ impl Requester {
         loop {
             if retries > 0 {
                 if let Some(remote_url) = self.api_endpoints.pop_front() {
-                    if let Ok(full_message) = self.request_message_and_metadata(&remote_url, message_id).await {
-                        self.respond_to_collector(collector_handle, try_ms_index, Some(message_id), Some(full_message));
-                        self.api_endpoints.push_front(remote_url);
-                        break;
-                    } else {
-                        self.api_endpoints.push_back(remote_url);
-                        retries -= 1;
-                        // keep retrying, but yield to keep the system responsive
-                        tokio::task::yield_now().await;
-                        continue;
+                    match self
+                        .request_message_and_metadata(&remote_url, message_id, try_ms_index)
+                        .await
+                    {
+                        Ok(full_message) => {
+                            self.respond_to_collector(
+                                collector_handle,
+                                try_ms_index,
+                                Some(message_id),
+                                Some(full_message),
+                            )
+                            .await;
+                            self.api_endpoints.push_front(remote_url);
+                            break;
+                        }
+                        Err(e) => {
+                            error!("Error requesting message {}: {}", message_id, e);
+                            self.sync_status.failed(try_ms_index, &remote_url, e);
+                            self.api_endpoints.push_back(remote_url);
+                            retries -= 1;
+                            // keep retrying, but yield to keep the system responsive
+                            tokio::task::yield_now().await;
+                            continue;
+                        }
                     }
                 } else {
-                    self.respond_to_collector(collector_handle, try_ms_index, None, None);
+                    self.respond_to_collector(collector_handle, try_ms_index, None, None)
+                        .await;
                     break;
                 };
             } else {
-                self.respond_to_collector(collector_handle, try_ms_index, None, None);
+                self.respond_to_collector(collector_handle, try_ms_index, None, None)
+                    .await;
                 break;
             }
         }
@@ -92,33 +108,41 @@ impl Requester {
         loop {
             if retries > 0 {
                 if let Some(remote_url) = self.api_endpoints.pop_front() {
-                    if let Ok(full_message) = self.request_milestone_message(&remote_url, milestone_index).await {
-                        self.respond_to_collector(
-                            collector_handle,
-                            milestone_index,
-                            Some(full_message.metadata().message_id),
-                            Some(full_message),
-                        );
-                        self.api_endpoints.push_front(remote_url);
-                        break;
-                    } else {
-                        self.api_endpoints.push_back(remote_url);
-                        retries -= 1;
-                        // keep retrying, but yield to keep the system responsive
-                        tokio::task::yield_now().await;
-                        continue;
+                    match self.request_milestone_message(&remote_url, milestone_index).await {
+                        Ok(full_message) => {
+                            self.respond_to_collector(
+                                collector_handle,
+                                milestone_index,
+                                Some(full_message.metadata().message_id),
+                                Some(full_message),
+                            )
+                            .await;
+                            self.api_endpoints.push_front(remote_url);
+                            break;
+                        }
+                        Err(e) => {
+                            error!("Error requesting milestone {}: {}", milestone_index, e);
+                            self.sync_status.failed(milestone_index, &remote_url, e);
+                            self.api_endpoints.push_back(remote_url);
+                            retries -= 1;
+                            // keep retrying, but yield to keep the system responsive
+                            tokio::task::yield_now().await;
+                            continue;
+                        }
                     }
                 } else {
-                    self.respond_to_collector(collector_handle, milestone_index, None, None);
+                    self.respond_to_collector(collector_handle, milestone_index, None, None)
+                        .await;
                     break;
                 };
             } else {
-                self.respond_to_collector(collector_handle, milestone_index, None, None);
+                self.respond_to_collector(collector_handle, milestone_index, None, None)
+                    .await;
                 break;
             }
         }
     }
-    fn respond_to_collector(
+    async fn respond_to_collector(
         &self,
         collector_handle: &CollectorHandle,
         ms_index: u32,
@@ -127,88 +151,77 @@ impl Requester {
     ) {
         let collector_event =
             CollectorEvent::MessageAndMeta(self.requester_id, ms_index, opt_message_id, opt_full_message);
-        let _ = collector_handle.send(collector_event);
+        let _ = collector_handle.send(collector_event).await;
     }
-    async fn request_milestone_message(&mut self, remote_url: &Url, milestone_index: u32) -> Result<FullMessage, ()> {
-        let get_milestone_url = remote_url.join(&format!("milestones/{}", milestone_index)).unwrap();
-        let milestone_response = self
-            .reqwest_client
-            .get(get_milestone_url)
-            .send()
-            .await
-            .map_err(|e| error!("Error sending request for milestone: {}", e));
-        if let Ok(milestone_response) = milestone_response {
-            if milestone_response.status().is_success() {
-                let milestone = milestone_response
-                    .json::<JsonData<MilestoneResponse>>()
-                    .await
-                    .map_err(|e| error!("Error deserializing milestone: {}", e));
-                if let Ok(milestone) = milestone {
-                    let milestone = milestone.into_inner();
-                    let message_id = MessageId::from_str(&milestone.message_id).expect("Expected message_id as string");
-                    return self.request_message_and_metadata(remote_url, message_id).await;
-                }
-            } else {
-                if !milestone_response.status().is_success() {
-                    let url = milestone_response.url().clone();
-                    let err = milestone_response.json::<Value>().await;
-                    error!("Received error requesting milestone from {}:\n {:#?}", url, err);
-                }
+    async fn request_milestone_message(
+        &mut self,
+        remote_url: &Url,
+        milestone_index: u32,
+    ) -> anyhow::Result<FullMessage> {
+        if self.chronicle_endpoints.contains(remote_url) {
+            if let Some(full_message) = self
+                .peer_cones
+                .milestone(&self.reqwest_client, remote_url, milestone_index)
+                .await?
+            {
+                return Ok(full_message);
             }
         }
-        Err(())
+        let get_milestone_url = remote_url.join(&format!("milestones/{}", milestone_index))?;
+        let milestone = self
+            .request_json::<MilestoneResponse>(get_milestone_url, "milestone")
+            .await?;
+        let message_id = MessageId::from_str(&milestone.message_id)
+            .map_err(|e| anyhow!("Error parsing milestone message id: {:?}", e))?;
+        self.request_message_and_metadata(remote_url, message_id, milestone_index)
+            .await
     }
     async fn request_message_and_metadata(
         &mut self,
         remote_url: &Url,
         message_id: MessageId,
-    ) -> Result<FullMessage, ()> {
-        let get_message_url = remote_url.join(&format!("messages/{}", message_id)).unwrap();
-        let get_metadata_url = remote_url.join(&format!("messages/{}/metadata", message_id)).unwrap();
-        let message_response = self
-            .reqwest_client
-            .get(get_message_url)
-            .send()
-            .await
-            .map_err(|e| error!("Error sending request for message: {}", e));
-        let metadata_response = self
+        try_ms_index: u32,
+    ) -> anyhow::Result<FullMessage> {
+        if self.chronicle_endpoints.contains(remote_url) {
+            if let Some(full_message) = self
+                .peer_cones
+                .message(&self.reqwest_client, remote_url, try_ms_index, &message_id)
+                .await?
+            {
+                return Ok(full_message);
+            }
+        }
+        let get_message_url = remote_url.join(&format!("messages/{}", message_id))?;
+        let get_metadata_url = remote_url.join(&format!("messages/{}/metadata", message_id))?;
+        let message_dto = self.request_json::<MessageDto>(get_message_url, "message").await?;
+        let metadata = self
+            .request_json::<MessageMetadata>(get_metadata_url, "metadata")
+            .await?;
+        let message = Message::try_from(&message_dto).map_err(|e| anyhow!("Error converting message: {:?}", e))?;
+        ensure!(
+            metadata.referenced_by_milestone_index.is_some(),
+            "The message is not referenced by a milestone yet"
+        );
+        Ok(FullMessage::new(message, metadata))
+    }
+    /// Request a json resource, which is wrapped in a `data` object by the nodes and the peer Chronicle instances
+    /// alike
+    async fn request_json<T: serde::de::DeserializeOwned>(&self, url: Url, resource: &str) -> anyhow::Result<T> {
+        let response = self
             .reqwest_client
-            .get(get_metadata_url)
+            .get(url)
             .send()
             .await
-            .map_err(|e| error!("Error sending request for metadata: {}", e));
-        if let (Ok(message_response), Ok(metadata_response)) = (message_response, metadata_response) {
-            if message_response.status().is_success() && metadata_response.status().is_success() {
-                let message = message_response
-                    .json::<JsonData<MessageDto>>()
-                    .await
-                    .map_err(|e| error!("Error deserializing message: {}", e));
-                let metadata = metadata_response
-                    .json::<JsonData<MessageMetadata>>()
-                    .await
-                    .map_err(|e| error!("Error deserializing metadata: {}", e));
-                if let (Ok(message), Ok(metadata)) = (message, metadata) {
-                    let message_dto = message.into_inner();
-                    let message = Message::try_from(&message_dto).unwrap();
-                    let metadata = metadata.into_inner();
-                    if metadata.referenced_by_milestone_index.is_some() {
-                        let full_message = FullMessage::new(message, metadata);
-                        return Ok(full_message);
-                    }
-                }
-            } else {
-                if !message_response.status().is_success() {
-                    let url = message_response.url().clone();
-                    let err = message_response.json::<Value>().await;
-                    error!("Received error requesting message from {}:\n {:#?}", url, err);
-                }
-                if !metadata_response.status().is_success() {
-                    let url = metadata_response.url().clone();
-                    let err = metadata_response.json::<Value>().await;
-                    error!("Received error requesting metadata from {}:\n {:#?}", url, err);
-                }
-            }
+            .map_err(|e| anyhow!("Error sending request for {}: {}", resource, e))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let err = response.json::<Value>().await.unwrap_or_default();
+            bail!("Received error {} requesting {}: {}", status, resource, err);
         }
-        Err(())
+        response
+            .json::<JsonData<T>>()
+            .await
+            .map(|json| json.into_inner())
+            .map_err(|e| anyhow!("Error deserializing {}: {}", resource, e))
     }
 }
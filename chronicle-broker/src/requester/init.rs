@@ -14,7 +14,11 @@ impl Init<CollectorHandle> for Requester {
         self.service.update_status(ServiceStatus::Initializing);
         self.shuffle();
         let event = CollectorEvent::Internal(Internal::Service(self.service.clone()));
-        let _ = supervisor.as_mut().expect("Expected Collector handle").send(event);
+        let _ = supervisor
+            .as_mut()
+            .expect("Expected Collector handle")
+            .send(event)
+            .await;
         status
     }
 }
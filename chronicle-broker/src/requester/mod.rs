@@ -6,6 +6,7 @@ use super::{
         CollectorHandle,
         Internal,
     },
+    peer::PeerCones,
     *,
 };
 use bee_rest_api::types::{
@@ -14,7 +15,10 @@ use bee_rest_api::types::{
 };
 use reqwest::Client;
 use std::{
-    collections::VecDeque,
+    collections::{
+        HashSet,
+        VecDeque,
+    },
     convert::TryFrom,
     ops::{
         Deref,
@@ -37,8 +41,11 @@ builder!(RequesterBuilder {
     requester_id: u8,
     inbox: RequesterInbox,
     api_endpoints: VecDeque<Url>,
+    chronicle_endpoints: HashSet<Url>,
+    peer_cones: PeerCones,
     reqwest_client: Client,
-    retries_per_endpoint: usize
+    retries_per_endpoint: usize,
+    sync_status: SyncStatus
 });
 pub(crate) type RequesterId = u8;
 
@@ -125,8 +132,14 @@ pub struct Requester {
     requester_id: u8,
     inbox: RequesterInbox,
     api_endpoints: VecDeque<Url>,
+    /// The api endpoints which are peer Chronicle instances rather than nodes
+    chronicle_endpoints: HashSet<Url>,
+    /// The milestone cones fetched from the peer Chronicle instances
+    peer_cones: PeerCones,
     reqwest_client: Client,
     retries: usize,
+    /// Records the failed requests of the milestones
+    sync_status: SyncStatus,
 }
 
 impl ActorBuilder<CollectorHandle> for RequesterBuilder {}
@@ -144,8 +157,11 @@ impl Builder for RequesterBuilder {
             inbox: self.inbox.unwrap(),
             requester_id: self.requester_id.unwrap(),
             api_endpoints,
+            chronicle_endpoints: self.chronicle_endpoints.unwrap_or_default(),
+            peer_cones: self.peer_cones.unwrap_or_default(),
             reqwest_client: self.reqwest_client.unwrap(),
             retries,
+            sync_status: self.sync_status.unwrap_or_default(),
         }
         .set_name()
     }
@@ -12,7 +12,11 @@ impl Terminating<CollectorHandle> for Requester {
     ) -> Result<(), Need> {
         self.service.update_status(ServiceStatus::Stopping);
         let event = CollectorEvent::Internal(Internal::Service(self.service.clone()));
-        let _ = supervisor.as_mut().expect("Expected Collector handle").send(event);
+        let _ = supervisor
+            .as_mut()
+            .expect("Expected Collector handle")
+            .send(event)
+            .await;
         _status
     }
 }
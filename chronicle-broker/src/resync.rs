@@ -0,0 +1,269 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::{
+    archive::manifest::log_file_range,
+    indexer::{
+        IndexRow,
+        MessageIndexer,
+    },
+    rollup::AnalyticsRollups,
+};
+use bee_message::{
+    milestone::Milestone,
+    prelude::{
+        MilestoneIndex,
+        MilestonePayload,
+    },
+};
+use scylla_rs::prelude::stage::ReporterHandle;
+use std::{
+    ops::Range,
+    path::Path,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// The number of retries of the invalidation queries
+const INVALIDATE_RETRIES: usize = 10;
+
+/// A deletion of the invalidation of a milestone range
+#[derive(Debug, Clone, PartialEq)]
+pub enum Invalidation {
+    /// Delete a row written for the cone of a milestone
+    Row(IndexRow),
+    /// Remove the analytics of the milestone index with the given unix timestamp from the rollups
+    Rollups(u32, u64),
+    /// Delete the analytic records of a milestone range
+    Analytics(Range<u32>),
+    /// Delete the milestone rows of a milestone index
+    Milestone(u32),
+    /// Delete the sync records of a milestone range, which marks it unsynced
+    Sync(Range<u32>),
+}
+
+/// Invalidate a milestone range, so it's filled again even if its resync is interrupted.
+/// If `purge` is set, the rows written for the cones of its stored milestones are deleted first, along with their
+/// analytics and rollups, so nothing derived from the purged milestones is left behind.
+pub async fn invalidate(
+    keyspace: &ChronicleKeyspace,
+    indexer: &MessageIndexer,
+    range: &Range<u32>,
+    purge: bool,
+    parallelism: usize,
+) -> anyhow::Result<()> {
+    if purge {
+        for milestone_index in range.clone() {
+            let milestone: Milestone =
+                match select_one(keyspace, MilestoneIndex(milestone_index), INVALIDATE_RETRIES).await? {
+                    Some(milestone) => milestone,
+                    None => continue,
+                };
+            // an incomplete cone is purged as far as it's stored
+            let cone = collect_cone(keyspace, &milestone, milestone_index, INVALIDATE_RETRIES, parallelism).await?;
+            apply(keyspace, purge_cone(indexer, &cone, Some(milestone.timestamp()))?).await?;
+        }
+    }
+    apply(keyspace, unsync(range, purge)).await
+}
+
+/// Roll back the rows written for the cone of a rejected milestone, and mark the milestone unsynced so it's filled
+/// again.
+pub async fn rollback(
+    keyspace: &ChronicleKeyspace,
+    indexer: &MessageIndexer,
+    milestone_data: &MilestoneData,
+) -> anyhow::Result<()> {
+    let milestone_index = milestone_data.milestone_index();
+    apply(keyspace, purge_cone(indexer, milestone_data, None)?).await?;
+    apply(keyspace, unsync(&(milestone_index..milestone_index + 1), true)).await
+}
+
+/// The deletions of the rows written for the cone of a milestone, followed by the removal of its analytics from the
+/// rollups if its timestamp is given. The index rows are deleted from the partition of the milestone and the message
+/// rows go last, like they're inserted last. The transaction and hint rows are left as they're overwritten once the
+/// milestone is solidified again.
+pub fn purge_cone(
+    indexer: &MessageIndexer,
+    milestone_data: &MilestoneData,
+    timestamp: Option<u64>,
+) -> anyhow::Result<Vec<Invalidation>> {
+    let milestone_index = MilestoneIndex(milestone_data.milestone_index());
+    let mut invalidations = Vec::new();
+    let mut messages = Vec::new();
+    for (message_id, FullMessage(message, metadata)) in milestone_data.messages() {
+        for row in indexer.index(message_id, message, milestone_index, Some(metadata))? {
+            match row {
+                IndexRow::Parent(..) | IndexRow::Index(..) | IndexRow::Address(..) => {
+                    invalidations.push(Invalidation::Row(row))
+                }
+                IndexRow::Message(..) => messages.push(Invalidation::Row(row)),
+                _ => (),
+            }
+        }
+    }
+    invalidations.extend(messages);
+    invalidations.extend(timestamp.map(|timestamp| Invalidation::Rollups(milestone_index.0, timestamp)));
+    Ok(invalidations)
+}
+
+/// The deletions which mark a milestone range unsynced, preceded by the deletion of its analytics and milestone rows
+/// if `purge` is set. The sync records are deleted last, so a failed purge leaves the range synced.
+pub fn unsync(range: &Range<u32>, purge: bool) -> Vec<Invalidation> {
+    let mut invalidations = Vec::new();
+    if purge {
+        invalidations.push(Invalidation::Analytics(range.clone()));
+        invalidations.extend(range.clone().map(Invalidation::Milestone));
+    }
+    invalidations.push(Invalidation::Sync(range.clone()));
+    invalidations
+}
+
+/// Apply the deletions in order, each of them once the previous one is acknowledged
+pub async fn apply(keyspace: &ChronicleKeyspace, invalidations: Vec<Invalidation>) -> anyhow::Result<()> {
+    for invalidation in invalidations {
+        match invalidation {
+            Invalidation::Row(IndexRow::Parent(key, record)) => {
+                let key = ParentPK::new(
+                    *key,
+                    key.partition_id(),
+                    MilestoneIndex(key.milestone_index()),
+                    record.message_id,
+                );
+                delete::<_, ParentRecord>(keyspace, key).await?
+            }
+            Invalidation::Row(IndexRow::Index(key, record)) => {
+                let key = IndexationPK::new(
+                    (*key).clone(),
+                    key.partition_id(),
+                    MilestoneIndex(key.milestone_index()),
+                    record.message_id,
+                );
+                delete::<_, IndexationRecord>(keyspace, key).await?
+            }
+            Invalidation::Row(IndexRow::Address(key, record)) => {
+                let key = Ed25519AddressPK::new(
+                    *key,
+                    key.partition_id(),
+                    MilestoneIndex(key.milestone_index()),
+                    record.output_type,
+                    record.transaction_id,
+                    record.index,
+                );
+                delete::<_, AddressRecord>(keyspace, key).await?
+            }
+            Invalidation::Row(IndexRow::Message(message_id, _, _)) => {
+                delete::<_, Message>(keyspace, message_id).await?
+            }
+            Invalidation::Row(_) => (),
+            // the rollups are recomputed in order with the updates of the resynced milestones
+            Invalidation::Rollups(milestone_index, timestamp) => {
+                AnalyticsRollups::of(keyspace).queue_removal(milestone_index, timestamp)
+            }
+            Invalidation::Analytics(range) => delete::<_, AnalyticRecord>(keyspace, SyncRange::from(range)).await?,
+            Invalidation::Milestone(milestone_index) => {
+                delete::<_, (MessageId, Box<MilestonePayload>)>(keyspace, MilestoneIndex(milestone_index)).await?
+            }
+            Invalidation::Sync(range) => delete::<_, SyncRecord>(keyspace, SyncRange::from(range)).await?,
+        }
+    }
+    Ok(())
+}
+
+/// Get the file names of the log files of a logs directory which overlap a milestone range, including the active
+/// one which is appended from its start milestone on
+pub async fn archived_overlaps(logs_dir: &Path, range: &Range<u32>) -> anyhow::Result<Vec<String>> {
+    let mut overlaps = Vec::new();
+    let mut entries = match tokio::fs::read_dir(logs_dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(overlaps),
+        Err(e) => bail!(e),
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        let file_name = match entry.file_name().into_string() {
+            Ok(file_name) => file_name,
+            Err(_) => continue,
+        };
+        let log_range = if let Some(start) = file_name
+            .strip_suffix(".log.active")
+            .and_then(|start| start.parse::<u32>().ok())
+        {
+            start..u32::MAX
+        } else if file_name.ends_with(".log") || file_name.ends_with(".log.fin") {
+            match log_file_range(&file_name) {
+                Some(log_range) => log_range,
+                None => continue,
+            }
+        } else {
+            continue;
+        };
+        if log_range.start < range.end && range.start < log_range.end {
+            overlaps.push(file_name);
+        }
+    }
+    overlaps.sort();
+    Ok(overlaps)
+}
+
+/// Delete a row and wait for its response
+pub(crate) async fn delete<K, V>(keyspace: &ChronicleKeyspace, key: K) -> anyhow::Result<()>
+where
+    ChronicleKeyspace: Delete<K, V>,
+    K: 'static + Send + Sync + Clone,
+    V: 'static + Send + Sync + Clone,
+{
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    keyspace
+        .delete::<V>(&key)
+        .consistency(Consistency::One)
+        .build()?
+        .send_local(Box::new(InvalidateWorker {
+            tx,
+            keyspace: keyspace.clone(),
+            key,
+            retries: INVALIDATE_RETRIES,
+            _marker: std::marker::PhantomData::<V>,
+        }));
+    rx.recv()
+        .await
+        .ok_or_else(|| anyhow!("No response for the invalidation query"))?
+        .map_err(|e| anyhow!("{:?}", e))
+}
+
+/// Deletes the rows of a key and reports the outcome
+#[derive(Clone)]
+struct InvalidateWorker<K, V> {
+    tx: UnboundedSender<Result<(), WorkerError>>,
+    keyspace: ChronicleKeyspace,
+    key: K,
+    retries: usize,
+    _marker: std::marker::PhantomData<V>,
+}
+
+impl<K, V> Worker for InvalidateWorker<K, V>
+where
+    ChronicleKeyspace: Delete<K, V>,
+    K: 'static + Send + Sync + Clone,
+    V: 'static + Send + Sync + Clone,
+{
+    fn handle_response(self: Box<Self>, giveload: Vec<u8>) -> anyhow::Result<()> {
+        Decoder::try_from(giveload).and_then(|decoder| decoder.get_void())?;
+        self.tx.send(Ok(())).ok();
+        Ok(())
+    }
+    fn handle_error(mut self: Box<Self>, error: WorkerError, _reporter: &Option<ReporterHandle>) -> anyhow::Result<()> {
+        if self.retries > 0 {
+            self.retries -= 1;
+            // the retries use unprepared queries, which also recover from an unprepared statement
+            let req = self
+                .keyspace
+                .delete_query::<V>(&self.key)
+                .consistency(Consistency::One)
+                .build()?;
+            tokio::spawn(async { req.send_global(self) });
+        } else {
+            self.tx.send(Err(error)).ok();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use bee_message::prelude::MilestoneIndex;
+use scylla_rs::{
+    app::worker::handle_insert_unprepared_error,
+    prelude::stage::ReporterHandle,
+};
+use std::sync::{
+    Arc,
+    Mutex,
+};
+use tokio::sync::mpsc::{
+    error::TrySendError,
+    Receiver,
+    Sender,
+    UnboundedSender,
+};
+
+/// The number of retries of the rollup queries
+const ROLLUP_RETRIES: usize = 10;
+/// The number of analyzed milestones which may wait for their rollups update, per keyspace
+const ROLLUP_QUEUE_CAPACITY: usize = 1000;
+
+lazy_static::lazy_static! {
+    /// The analytics rollups of the keyspaces, shared by the actors of this process
+    static ref ROLLUPS: Mutex<HashMap<String, AnalyticsRollups>> = Default::default();
+}
+
+/// A queued update of the analytics rollups
+#[derive(Debug)]
+enum RollupUpdate {
+    /// Add the analytics of a milestone with the given unix timestamp
+    Add(AnalyticRecord, u64),
+    /// Remove the analytics of the milestone index with the given unix timestamp
+    Remove(u32, u64),
+}
+
+impl RollupUpdate {
+    fn milestone_index(&self) -> u32 {
+        match self {
+            RollupUpdate::Add(record, _) => **record.milestone_index(),
+            RollupUpdate::Remove(milestone_index, _) => *milestone_index,
+        }
+    }
+}
+
+/// Maintains the hourly and daily analytics rollups of a keyspace.
+/// The analytics of a milestone are written into its hour bucket, then the hour rollup is recomputed from the
+/// bucket and the day rollup from its hour rollups, so re-analyzing a milestone never counts it twice.
+/// The updates of a keyspace are queued and applied one at a time, so the recomputations never race.
+#[derive(Clone, Debug)]
+pub struct AnalyticsRollups {
+    keyspace: ChronicleKeyspace,
+    /// The queue of the rollups updates
+    sender: Sender<RollupUpdate>,
+    /// The receiving end of the queue, until the first update starts the task which drains it
+    receiver: Arc<Mutex<Option<Receiver<RollupUpdate>>>>,
+}
+
+impl AnalyticsRollups {
+    /// Get the shared rollups maintainer of a keyspace
+    pub fn of(keyspace: &ChronicleKeyspace) -> Self {
+        ROLLUPS
+            .lock()
+            .unwrap()
+            .entry(keyspace.name().to_string())
+            .or_insert_with(|| {
+                let (sender, receiver) = tokio::sync::mpsc::channel(ROLLUP_QUEUE_CAPACITY);
+                Self {
+                    keyspace: keyspace.clone(),
+                    sender,
+                    receiver: Arc::new(Mutex::new(Some(receiver))),
+                }
+            })
+            .clone()
+    }
+    /// Get the keyspace of the rollups
+    pub fn keyspace(&self) -> &ChronicleKeyspace {
+        &self.keyspace
+    }
+    /// Queue the rollups update of an analyzed milestone, waiting for room in the queue if it's full
+    pub fn queue_update(&self, record: AnalyticRecord, timestamp: u64) {
+        self.queue(RollupUpdate::Add(record, timestamp));
+    }
+    /// Queue the removal of a milestone from its rollups, ie once its analytics are purged
+    pub fn queue_removal(&self, milestone_index: u32, timestamp: u64) {
+        self.queue(RollupUpdate::Remove(milestone_index, timestamp));
+    }
+    fn queue(&self, update: RollupUpdate) {
+        if let Some(mut receiver) = self.receiver.lock().unwrap().take() {
+            let rollups = self.clone();
+            tokio::spawn(async move {
+                while let Some(update) = receiver.recv().await {
+                    let milestone_index = update.milestone_index();
+                    if let Err(e) = rollups.update(update).await {
+                        error!(
+                            "Unable to update the analytics rollups of milestone index: {}, error: {}",
+                            milestone_index, e
+                        );
+                    }
+                }
+            });
+        }
+        match self.sender.try_send(update) {
+            Ok(()) => (),
+            Err(TrySendError::Full(update)) => {
+                tokio::task::block_in_place(|| self.sender.blocking_send(update)).ok();
+            }
+            Err(TrySendError::Closed(_)) => error!("The analytics rollups queue of {} is closed", self.keyspace.name()),
+        }
+    }
+    /// Add or remove the analytics of a milestone in its hour bucket, then recompute its hour and day rollups
+    async fn update(&self, update: RollupUpdate) -> anyhow::Result<()> {
+        let timestamp = match update {
+            RollupUpdate::Add(record, timestamp) => {
+                self.insert(HourBucket(RollupInterval::Hour.bucket(timestamp)), record)
+                    .await?;
+                timestamp
+            }
+            RollupUpdate::Remove(milestone_index, timestamp) => {
+                let key = (
+                    HourBucket(RollupInterval::Hour.bucket(timestamp)),
+                    MilestoneIndex(milestone_index),
+                );
+                crate::resync::delete::<_, AnalyticRecord>(&self.keyspace, key).await?;
+                timestamp
+            }
+        };
+        let hour = RollupInterval::Hour.bucket(timestamp);
+        let mut hour_rollup = AnalyticRollup::new(hour);
+        // an hour bucket holds few enough milestones to fit a single page
+        if let Some(records) =
+            select_one::<_, _, Iter<AnalyticRecord>>(&self.keyspace, HourBucket(hour), ROLLUP_RETRIES).await?
+        {
+            for record in records {
+                hour_rollup.acc_record(&record);
+            }
+        }
+        self.insert(RollupInterval::Hour, hour_rollup).await?;
+        let day = RollupInterval::Day.bucket(timestamp);
+        let hours = RollupRange {
+            interval: RollupInterval::Hour,
+            from: day,
+            to: day + RollupInterval::Day.secs(),
+        };
+        let mut day_rollup = AnalyticRollup::new(day);
+        if let Some(rollups) = select_one::<_, _, Iter<AnalyticRollup>>(&self.keyspace, hours, ROLLUP_RETRIES).await? {
+            for rollup in rollups {
+                day_rollup.acc_rollup(&rollup);
+            }
+        }
+        self.insert(RollupInterval::Day, day_rollup).await
+    }
+    /// Insert a row and wait for its response
+    async fn insert<K, V>(&self, key: K, value: V) -> anyhow::Result<()>
+    where
+        ChronicleKeyspace: Insert<K, V>,
+        K: 'static + Send + Sync + Clone,
+        V: 'static + Send + Sync + Clone,
+    {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        self.keyspace
+            .insert(&key, &value)
+            .consistency(Consistency::One)
+            .build()?
+            .send_local(Box::new(RollupWorker {
+                tx,
+                keyspace: self.keyspace.clone(),
+                key,
+                value,
+                retries: ROLLUP_RETRIES,
+            }));
+        rx.recv()
+            .await
+            .ok_or_else(|| anyhow!("No response for the rollup insert query"))?
+            .map_err(|e| anyhow!("{:?}", e))
+    }
+}
+
+/// Inserts a rollup row and reports the outcome
+#[derive(Clone)]
+struct RollupWorker<K, V> {
+    tx: UnboundedSender<Result<(), WorkerError>>,
+    keyspace: ChronicleKeyspace,
+    key: K,
+    value: V,
+    retries: usize,
+}
+
+impl<K, V> Worker for RollupWorker<K, V>
+where
+    ChronicleKeyspace: Insert<K, V>,
+    K: 'static + Send + Sync + Clone,
+    V: 'static + Send + Sync + Clone,
+{
+    fn handle_response(self: Box<Self>, giveload: Vec<u8>) -> anyhow::Result<()> {
+        Decoder::try_from(giveload).and_then(|decoder| decoder.get_void())?;
+        self.tx.send(Ok(())).ok();
+        Ok(())
+    }
+    fn handle_error(
+        mut self: Box<Self>,
+        mut error: WorkerError,
+        reporter: &Option<ReporterHandle>,
+    ) -> anyhow::Result<()> {
+        if let WorkerError::Cql(ref mut cql_error) = error {
+            if let (Some(id), Some(reporter)) = (cql_error.take_unprepared_id(), reporter) {
+                handle_insert_unprepared_error(&self, &self.keyspace, &self.key, &self.value, id, reporter)?;
+            }
+        }
+        if self.retries > 0 {
+            self.retries -= 1;
+            let req = self
+                .keyspace
+                .insert_query(&self.key, &self.value)
+                .consistency(Consistency::One)
+                .build()?;
+            tokio::spawn(async { req.send_global(self) });
+        } else {
+            self.tx.send(Err(error)).ok();
+        }
+        Ok(())
+    }
+}
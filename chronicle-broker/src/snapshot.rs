@@ -0,0 +1,122 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use bee_message::prelude::MilestoneIndex;
+use lru::LruCache;
+use serde::de::DeserializeOwned;
+use std::{
+    path::Path,
+    time::Instant,
+};
+
+/// Snapshot of the most recent messages seen by a collector
+#[derive(Serialize, Deserialize)]
+pub struct CollectorSnapshot {
+    /// The collector count at the time of the snapshot, as it determines the message partitioning
+    pub collector_count: u8,
+    /// The estimated milestone index
+    pub est_ms: u32,
+    /// The referenced milestone index
+    pub ref_ms: u32,
+    /// Cached messages, ordered from the most to the least recently used
+    pub messages: Vec<(MessageId, u32, Message)>,
+    /// Cached messages metadata, ordered from the most to the least recently used
+    pub metadata: Vec<(MessageId, MessageMetadata)>,
+}
+
+impl CollectorSnapshot {
+    /// Snapshot the collector caches
+    pub fn capture(
+        collector_count: u8,
+        est_ms: u32,
+        ref_ms: u32,
+        lru_msg: &LruCache<MessageId, (Option<Instant>, MilestoneIndex, Message)>,
+        lru_msg_ref: &LruCache<MessageId, MessageMetadata>,
+    ) -> Self {
+        Self {
+            collector_count,
+            est_ms,
+            ref_ms,
+            messages: lru_msg
+                .iter()
+                .map(|(message_id, (_, est_ms, message))| (*message_id, est_ms.0, message.clone()))
+                .collect(),
+            metadata: lru_msg_ref
+                .iter()
+                .map(|(message_id, metadata)| (*message_id, metadata.clone()))
+                .collect(),
+        }
+    }
+    /// Restore the snapshot into the collector caches, preserving their order.
+    /// Nothing is restored if the snapshot was taken with another collector count.
+    pub fn restore(
+        self,
+        collector_count: u8,
+        lru_msg: &mut LruCache<MessageId, (Option<Instant>, MilestoneIndex, Message)>,
+        lru_msg_ref: &mut LruCache<MessageId, MessageMetadata>,
+    ) -> bool {
+        if self.collector_count != collector_count {
+            return false;
+        }
+        // put the least recently used first to preserve the cache order
+        for (message_id, est_ms, message) in self.messages.into_iter().rev() {
+            lru_msg.put(message_id, (None, MilestoneIndex(est_ms), message));
+        }
+        for (message_id, metadata) in self.metadata.into_iter().rev() {
+            lru_msg_ref.put(message_id, metadata);
+        }
+        true
+    }
+}
+
+/// Snapshot of the in-flight milestones data held by a solidifier
+#[derive(Serialize, Deserialize)]
+pub struct SolidifierSnapshot {
+    /// The collector count at the time of the snapshot, as it determines the milestone partitioning
+    pub collector_count: u8,
+    /// The in-flight milestones data
+    pub milestones_data: Vec<MilestoneData>,
+}
+
+impl SolidifierSnapshot {
+    /// Get the milestones data a solidifier should restore, nothing if the snapshot was taken with another
+    /// collector count. The syncer owns anything below the gap start and the milestones data it created,
+    /// and it will request them again.
+    pub fn restorable(self, collector_count: u8, gap_start: u32) -> Option<Vec<MilestoneData>> {
+        if self.collector_count != collector_count {
+            return None;
+        }
+        Some(
+            self.milestones_data
+                .into_iter()
+                .filter(|milestone_data| {
+                    milestone_data.milestone_index() >= gap_start && !milestone_data.created_by().eq(&CreatedBy::Syncer)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Write the snapshot to `{dir}/{name}.snapshot`, going through a temporary file so a crash never leaves a partial
+/// snapshot behind
+pub async fn save<T: Serialize>(dir: &Path, name: &str, snapshot: &T) -> anyhow::Result<()> {
+    tokio::fs::create_dir_all(dir).await?;
+    let bytes = serde_json::to_vec(snapshot)?;
+    let tmp_path = dir.join(format!("{}.snapshot.tmp", name));
+    tokio::fs::write(&tmp_path, bytes).await?;
+    tokio::fs::rename(tmp_path, dir.join(format!("{}.snapshot", name))).await?;
+    Ok(())
+}
+
+/// Read and remove the snapshot `{dir}/{name}.snapshot` if any, so it can only be restored once
+pub async fn take<T: DeserializeOwned>(dir: &Path, name: &str) -> anyhow::Result<Option<T>> {
+    let path = dir.join(format!("{}.snapshot", name));
+    let bytes = match tokio::fs::read(&path).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => bail!("Unable to read snapshot {:?}: {}", path, e),
+    };
+    tokio::fs::remove_file(&path).await?;
+    Ok(Some(serde_json::from_slice(&bytes)?))
+}
@@ -24,7 +24,7 @@ impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Solidifier {
         while let Some(event) = self.inbox.recv().await {
             match event {
                 SolidifierEvent::Message(full_message) => {
-                    self.handle_new_msg(full_message).unwrap_or_else(|e| {
+                    self.handle_new_msg(full_message).await.unwrap_or_else(|e| {
                         error!("{}", e);
                     });
                 }
@@ -108,12 +108,14 @@ impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Solidifier {
                     }
                 }
                 SolidifierEvent::Close(message_id, milestone_index) => {
-                    self.close_message_id(milestone_index, &message_id).unwrap_or_else(|e| {
-                        error!("{}", e);
-                    });
+                    self.close_message_id(milestone_index, &message_id)
+                        .await
+                        .unwrap_or_else(|e| {
+                            error!("{}", e);
+                        });
                 }
                 SolidifierEvent::Milestone(milestone_message) => {
-                    self.handle_milestone_msg(milestone_message).unwrap_or_else(|e| {
+                    self.handle_milestone_msg(milestone_message).await.unwrap_or_else(|e| {
                         error!("{}", e);
                     });
                 }
@@ -159,7 +161,8 @@ impl Solidifier {
                         "Solidifier id: {}, failed to solidify syncer requested index: {} milestone data",
                         self.partition_id, milestone_index
                     );
-                    let _ = self.syncer_handle.send(SyncerEvent::Unreachable(milestone_index));
+                    self.syncer_handle
+                        .send_control(SyncerEvent::Unreachable(milestone_index));
                 } else {
                     // there is a glitch in the new incoming data, however the archiver and syncer will take care of
                     // that.
@@ -179,7 +182,8 @@ impl Solidifier {
                 self.partition_id, milestone_index, self.expected
             );
             // tell syncer to skip this atm
-            let _ = self.syncer_handle.send(SyncerEvent::Unreachable(milestone_index));
+            self.syncer_handle
+                .send_control(SyncerEvent::Unreachable(milestone_index));
             return ();
         }
         // remove it from unreachable (if we already tried to solidify it before)
@@ -208,7 +212,8 @@ impl Solidifier {
                     .or_insert_with(|| InDatabase::new(milestone_index));
             } else {
                 // tell syncer to skip this atm
-                let _ = self.syncer_handle.send(SyncerEvent::Unreachable(milestone_index));
+                self.syncer_handle
+                    .send_control(SyncerEvent::Unreachable(milestone_index));
             }
         } else {
             // Asking any collector (as we don't know the message id of the milestone)
@@ -226,16 +231,16 @@ impl Solidifier {
                 .or_insert_with(|| InDatabase::new(milestone_index));
         }
     }
-    fn close_message_id(&mut self, milestone_index: u32, message_id: &MessageId) -> anyhow::Result<()> {
+    async fn close_message_id(&mut self, milestone_index: u32, message_id: &MessageId) -> anyhow::Result<()> {
         if let Some(milestone_data) = self.milestones_data.get_mut(&milestone_index) {
             // remove it from pending
             milestone_data.remove_from_pending(message_id);
             let check_if_completed = milestone_data.check_if_completed();
             let created_by = milestone_data.created_by;
             if check_if_completed && !created_by.eq(&CreatedBy::Syncer) {
-                self.push_to_logger(milestone_index)?;
+                self.push_to_logger(milestone_index).await?;
             } else if check_if_completed {
-                self.push_to_syncer(milestone_index)?;
+                self.push_to_syncer(milestone_index).await?;
             };
         } else {
             if milestone_index < self.expected {
@@ -249,14 +254,17 @@ impl Solidifier {
         }
         Ok(())
     }
-    fn push_to_logger(&mut self, milestone_index: u32) -> anyhow::Result<()> {
+    async fn push_to_logger(&mut self, milestone_index: u32) -> anyhow::Result<()> {
         // Remove milestoneData from self state and pass it to archiver
-        let milestone_data = self
+        let mut milestone_data = self
             .milestones_data
             .remove(&milestone_index)
             .expect("Expected milestone data for milestone_index");
-        let analytic_record = milestone_data.get_analytic_record()?;
-        self.insert_analytic(milestone_index, analytic_record)?;
+        if let Err(e) = self.validate(&milestone_data) {
+            return self.reject_milestone_data(milestone_data, e).await;
+        }
+        self.confirm(&mut milestone_data)?;
+        self.insert_analytic(&mut milestone_data).await?;
         // Update in_database
         let in_database = self
             .in_database
@@ -273,22 +281,25 @@ impl Solidifier {
                 self.partition_id, milestone_index
             );
             let archiver_event = ArchiverEvent::MilestoneData(milestone_data, None);
-            let _ = archiver_handle.send(archiver_event);
+            let _ = archiver_handle.send(archiver_event).await;
         };
         Ok(())
     }
-    fn push_to_syncer(&mut self, milestone_index: u32) -> anyhow::Result<()> {
+    async fn push_to_syncer(&mut self, milestone_index: u32) -> anyhow::Result<()> {
         info!(
             "Solidifier is pushing the milestone data for index: {}, to Syncer",
             milestone_index
         );
         // Remove milestoneData from self state and pass it to syncer
-        let milestone_data = self
+        let mut milestone_data = self
             .milestones_data
             .remove(&milestone_index)
             .expect("Expected milestone data for milestone_index");
-        let analytic_record = milestone_data.get_analytic_record()?;
-        self.insert_analytic(milestone_index, analytic_record)?;
+        if let Err(e) = self.validate(&milestone_data) {
+            return self.reject_milestone_data(milestone_data, e).await;
+        }
+        self.confirm(&mut milestone_data)?;
+        self.insert_analytic(&mut milestone_data).await?;
         // Update in_database
         let in_database = self
             .in_database
@@ -300,12 +311,50 @@ impl Solidifier {
             self.handle_in_database(milestone_index)?;
         }
         let syncer_event = SyncerEvent::MilestoneData(milestone_data);
-        let _ = self.syncer_handle.send(syncer_event);
+        let _ = self.syncer_handle.send(syncer_event).await;
+        Ok(())
+    }
+    async fn reject_milestone_data(
+        &mut self,
+        milestone_data: MilestoneData,
+        error: anyhow::Error,
+    ) -> anyhow::Result<()> {
+        let milestone_index = milestone_data.milestone_index();
+        alert!(
+            "Solidifier id: {}, rejected invalid milestone data for index: {}, error: {}",
+            self.partition_id,
+            milestone_index,
+            error
+        )
+        .await
+        .ok();
+        // the rejected milestone must never be marked as synced
+        self.in_database.remove(&milestone_index);
+        self.lru_in_database.pop(&milestone_index);
+        self.unreachable.put(milestone_index, ());
+        if milestone_data.created_by.eq(&CreatedBy::Syncer) {
+            // tell syncer to skip it
+            self.syncer_handle
+                .send_control(SyncerEvent::Unreachable(milestone_index));
+        }
+        // the collectors wrote the cone while it was collected, so its rows are rolled back
+        let keyspace = self.keyspace.clone();
+        let partition_id = self.partition_id;
+        tokio::spawn(async move {
+            let indexer = MessageIndexer::new(chronicle_common::get_config().storage_config.partition_config);
+            if let Err(e) = crate::resync::rollback(&keyspace, &indexer, &milestone_data).await {
+                error!(
+                    "Solidifier id: {}, unable to roll back the rejected milestone index: {}, error: {}",
+                    partition_id, milestone_index, e
+                );
+            }
+        });
         Ok(())
     }
     fn handle_in_database(&mut self, milestone_index: u32) -> anyhow::Result<()> {
         self.in_database.remove(&milestone_index);
         self.lru_in_database.put(milestone_index, ());
+        self.sync_status.synced(milestone_index);
         let sync_key = Synckey;
         let synced_by = Some(self.chronicle_id);
         let synced_record = SyncRecord::new(MilestoneIndex(milestone_index), synced_by, None);
@@ -325,7 +374,46 @@ impl Solidifier {
         request.send_local(worker);
         Ok(())
     }
-    fn insert_analytic(&self, milestone_index: u32, analytic_record: AnalyticRecord) -> anyhow::Result<()> {
+    /// Set the white-flag index and the referenced milestone timestamp of the completed cone, and update the stored
+    /// metadata of its messages
+    fn confirm(&self, milestone_data: &mut MilestoneData) -> anyhow::Result<()> {
+        milestone_data.confirm();
+        for (message_id, FullMessage(_, metadata)) in milestone_data.messages() {
+            self.keyspace
+                .insert(message_id, metadata)
+                .consistency(Consistency::One)
+                .build()?
+                .send_local(InsertWorker::boxed(
+                    self.keyspace.clone(),
+                    *message_id,
+                    metadata.clone(),
+                    self.retries as usize,
+                ));
+        }
+        Ok(())
+    }
+    async fn insert_analytic(&self, milestone_data: &mut MilestoneData) -> anyhow::Result<()> {
+        let milestone_index = milestone_data.milestone_index();
+        let indexer = MessageIndexer::new(chronicle_common::get_config().storage_config.partition_config);
+        let keyspace = &self.keyspace;
+        let retries = self.retries as usize;
+        let lookup = lookup_address_history(milestone_data, ADDRESS_LOOKUP_PARALLELISM, |address| {
+            indexer.address_seen_before(keyspace, address, MilestoneIndex(milestone_index), retries)
+        });
+        if let Err(e) = lookup.await {
+            // the milestone is left unanalyzed, as if its analytics insert failed
+            error!(
+                "Solidifier id: {}, unable to look up the address history of milestone index: {}, error: {}",
+                self.partition_id, milestone_index, e
+            );
+            let analyzed_ms = CqlResult::AnalyzedMilestone(milestone_index);
+            self.handle.send_control(SolidifierEvent::CqlResult(Err(analyzed_ms)));
+            return Ok(());
+        }
+        let analytic_record = milestone_data.get_analytic_record()?;
+        if let Some(timestamp) = milestone_data.milestone_timestamp() {
+            self.rollups.queue_update(analytic_record.clone(), timestamp);
+        }
         let sync_key = Synckey;
         let request = self
             .keyspace
@@ -343,7 +431,7 @@ impl Solidifier {
         request.send_local(worker);
         Ok(())
     }
-    fn handle_milestone_msg(
+    async fn handle_milestone_msg(
         &mut self,
         MilestoneMessage(_message_id, milestone_payload, message, metadata): MilestoneMessage,
     ) -> anyhow::Result<()> {
@@ -373,9 +461,9 @@ impl Solidifier {
                 let check_if_completed = milestone_data.check_if_completed();
                 let created_by = milestone_data.created_by;
                 if check_if_completed && !created_by.eq(&CreatedBy::Syncer) {
-                    self.push_to_logger(milestone_index)?;
+                    self.push_to_logger(milestone_index).await?;
                 } else if check_if_completed {
-                    self.push_to_syncer(milestone_index)?;
+                    self.push_to_syncer(milestone_index).await?;
                 };
             }
         } else {
@@ -418,7 +506,7 @@ impl Solidifier {
             };
         });
     }
-    fn request_full_message(
+    pub(crate) fn request_full_message(
         collectors_handles: &HashMap<u8, CollectorHandle>,
         partitioner: &MessageIdPartitioner,
         solidifier_id: u8,
@@ -435,20 +523,22 @@ impl Solidifier {
                 parent_id,
                 created_by,
             ));
-            let _ = collector_handle.send(ask_event);
+            // asks flow against the pipeline and a lost ask stalls the cone, so they go on the control lane rather than
+            // waiting on a collector which may be waiting on us
+            collector_handle.send_control(ask_event);
         }
     }
-    fn request_milestone_message(
+    pub(crate) fn request_milestone_message(
         collectors_handles: &HashMap<u8, CollectorHandle>,
         collector_id: u8,
         milestone_index: u32,
     ) {
         if let Some(collector_handle) = collectors_handles.get(&collector_id) {
             let ask_event = CollectorEvent::Ask(AskCollector::MilestoneMessage(milestone_index));
-            let _ = collector_handle.send(ask_event);
+            collector_handle.send_control(ask_event);
         }
     }
-    fn handle_new_msg(&mut self, full_message: FullMessage) -> anyhow::Result<()> {
+    async fn handle_new_msg(&mut self, full_message: FullMessage) -> anyhow::Result<()> {
         // check what milestone_index referenced this message
         let milestone_index = full_message.ref_ms().unwrap();
         let partitioner = &self.message_id_partitioner;
@@ -466,9 +556,9 @@ impl Solidifier {
             let check_if_completed = milestone_data.check_if_completed();
             let created_by = milestone_data.created_by;
             if check_if_completed && !created_by.eq(&CreatedBy::Syncer) {
-                self.push_to_logger(milestone_index)?;
+                self.push_to_logger(milestone_index).await?;
             } else if check_if_completed {
-                self.push_to_syncer(milestone_index)?;
+                self.push_to_syncer(milestone_index).await?;
             };
         } else {
             // We have to decide whether to insert entry for milestone_index or not.
@@ -13,6 +13,61 @@ impl<H: ChronicleBrokerScope> Init<BrokerHandle<H>> for Solidifier {
             .as_mut()
             .expect("Solidifier expected BrokerHandle")
             .send(event);
+        self.restore_snapshot().await;
         status
     }
 }
+
+impl Solidifier {
+    /// Restore the in-flight milestones data from the snapshot of the previous graceful shutdown (if any)
+    async fn restore_snapshot(&mut self) {
+        if let Some(dir) = self.snapshot_dir.as_ref() {
+            match snapshot::take::<SolidifierSnapshot>(dir, &self.get_name()).await {
+                Ok(Some(snapshot)) => {
+                    let collector_count = snapshot.collector_count;
+                    let milestones_data = match snapshot.restorable(self.collector_count, self.gap_start) {
+                        Some(milestones_data) => milestones_data,
+                        None => {
+                            warn!(
+                                "{} ignored its snapshot, as it was taken with collector count: {}",
+                                self.get_name(),
+                                collector_count
+                            );
+                            return;
+                        }
+                    };
+                    for milestone_data in milestones_data {
+                        let milestone_index = milestone_data.milestone_index();
+                        info!(
+                            "{} restored milestone data for index: {}, pending: {}",
+                            self.get_name(),
+                            milestone_index,
+                            milestone_data.pending().len()
+                        );
+                        // the collectors no longer know about our previous asks
+                        for message_id in milestone_data.pending().keys() {
+                            Self::request_full_message(
+                                &self.collector_handles,
+                                &self.message_id_partitioner,
+                                self.partition_id,
+                                milestone_index,
+                                *message_id,
+                                *milestone_data.created_by(),
+                            );
+                        }
+                        if !milestone_data.milestone_exist() {
+                            Self::request_milestone_message(
+                                &self.collector_handles,
+                                self.partition_id,
+                                milestone_index,
+                            );
+                        }
+                        self.milestones_data.insert(milestone_index, milestone_data);
+                    }
+                }
+                Ok(None) => (),
+                Err(e) => error!("{} unable to restore its snapshot: {}", self.get_name(), e),
+            }
+        }
+    }
+}
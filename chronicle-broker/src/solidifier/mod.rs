@@ -11,6 +11,15 @@ use super::{
         CollectorHandle,
         MessageIdPartitioner,
     },
+    indexer::{
+        lookup_address_history,
+        MessageIndexer,
+    },
+    rollup::AnalyticsRollups,
+    snapshot::{
+        self,
+        SolidifierSnapshot,
+    },
     syncer::{
         SyncerEvent,
         SyncerHandle,
@@ -18,6 +27,7 @@ use super::{
     *,
 };
 use bee_message::prelude::MilestonePayload;
+use chronicle_common::config::ValidationConfig;
 
 use std::{
     ops::{
@@ -30,6 +40,11 @@ use std::{
 mod event_loop;
 mod init;
 mod terminating;
+/// The validation of the completed milestones data
+pub mod validation;
+
+/// The number of concurrent address history lookups of a solidified milestone
+const ADDRESS_LOOKUP_PARALLELISM: usize = 16;
 
 // Solidifier builder
 builder!(SolidifierBuilder {
@@ -44,7 +59,10 @@ builder!(SolidifierBuilder {
     gap_start: u32,
     retries: u16,
     collector_handles: HashMap<u8, CollectorHandle>,
-    collector_count: u8
+    collector_count: u8,
+    snapshot_dir: PathBuf,
+    validation: ValidationConfig,
+    network: String
 });
 
 /// A milestone message payload
@@ -130,14 +148,14 @@ pub enum CqlResult {
 /// SolidifierHandle
 #[derive(Clone)]
 pub struct SolidifierHandle {
-    pub(crate) tx: tokio::sync::mpsc::UnboundedSender<SolidifierEvent>,
+    pub(crate) tx: BoundedSender<SolidifierEvent>,
 }
 /// SolidifierInbox is used to recv events
 pub struct SolidifierInbox {
-    pub(crate) rx: tokio::sync::mpsc::UnboundedReceiver<SolidifierEvent>,
+    pub(crate) rx: BoundedReceiver<SolidifierEvent>,
 }
 impl Deref for SolidifierHandle {
-    type Target = tokio::sync::mpsc::UnboundedSender<SolidifierEvent>;
+    type Target = BoundedSender<SolidifierEvent>;
 
     fn deref(&self) -> &Self::Target {
         &self.tx
@@ -151,7 +169,7 @@ impl DerefMut for SolidifierHandle {
 }
 
 impl Deref for SolidifierInbox {
-    type Target = tokio::sync::mpsc::UnboundedReceiver<SolidifierEvent>;
+    type Target = BoundedReceiver<SolidifierEvent>;
 
     fn deref(&self) -> &Self::Target {
         &self.rx
@@ -169,7 +187,7 @@ impl Shutdown for SolidifierHandle {
     where
         Self: Sized,
     {
-        let _ = self.tx.send(SolidifierEvent::Shutdown);
+        self.tx.send_control(SolidifierEvent::Shutdown);
         None
     }
 }
@@ -196,6 +214,15 @@ pub struct Solidifier {
     retries: u16,
     handle: SolidifierHandle,
     inbox: SolidifierInbox,
+    snapshot_dir: Option<PathBuf>,
+    /// The validation applied to completed milestones data
+    validation: ValidationConfig,
+    /// The analytics rollups of the keyspace, shared by the solidifiers
+    rollups: AnalyticsRollups,
+    /// The sync status of the keyspace
+    sync_status: SyncStatus,
+    /// The network this solidifier belongs to
+    network: String,
 }
 
 impl<H: ChronicleBrokerScope> ActorBuilder<BrokerHandle<H>> for SolidifierBuilder {}
@@ -205,10 +232,13 @@ impl Builder for SolidifierBuilder {
     type State = Solidifier;
     fn build(self) -> Self::State {
         let collector_count = self.collector_count.unwrap();
+        let keyspace = self.keyspace.unwrap();
+        let rollups = AnalyticsRollups::of(&keyspace);
+        let sync_status = SyncStatus::of(keyspace.name());
         Self::State {
             service: Service::new(),
             partition_id: self.partition_id.unwrap(),
-            keyspace: self.keyspace.unwrap(),
+            keyspace,
             chronicle_id: self.chronicle_id.unwrap_or(0),
             in_database: HashMap::new(),
             lru_in_database: lru::LruCache::new(100),
@@ -225,6 +255,11 @@ impl Builder for SolidifierBuilder {
             retries: self.retries.unwrap_or(100),
             handle: self.handle.unwrap(),
             inbox: self.inbox.unwrap(),
+            snapshot_dir: self.snapshot_dir,
+            validation: self.validation.unwrap_or_default(),
+            rollups,
+            sync_status,
+            network: self.network.unwrap_or_default(),
         }
         .set_name()
     }
@@ -233,7 +268,7 @@ impl Builder for SolidifierBuilder {
 /// impl name of the Collector
 impl Name for Solidifier {
     fn set_name(mut self) -> Self {
-        let name = format!("Solidifier_{}", self.partition_id);
+        let name = network_scoped_name(&self.network, &format!("Solidifier_{}", self.partition_id));
         self.service.update_name(name);
         self
     }
@@ -366,10 +401,10 @@ impl Drop for AtomicSolidifierHandle {
         let cql_result = CqlResult::PersistedMsg(self.message_id, self.milestone_index);
         let any_error = self.any_error.load(Ordering::Relaxed);
         if any_error {
-            self.handle.send(SolidifierEvent::CqlResult(Err(cql_result))).ok();
+            self.handle.send_control(SolidifierEvent::CqlResult(Err(cql_result)));
         } else {
             // respond with void
-            self.handle.send(SolidifierEvent::CqlResult(Ok(cql_result))).ok();
+            self.handle.send_control(SolidifierEvent::CqlResult(Ok(cql_result)));
         }
     }
 }
@@ -429,7 +464,7 @@ where
     fn handle_response(self: Box<Self>, giveload: Vec<u8>) -> anyhow::Result<()> {
         Decoder::try_from(giveload).and_then(|decoder| decoder.get_void())?;
         let synced_ms = CqlResult::SyncedMilestone(self.milestone_index);
-        let _ = self.handle.send(SolidifierEvent::CqlResult(Ok(synced_ms)));
+        self.handle.send_control(SolidifierEvent::CqlResult(Ok(synced_ms)));
         Ok(())
     }
     fn handle_error(
@@ -463,14 +498,14 @@ where
                 Err(e) => {
                     error!("{}", e);
                     let synced_ms = CqlResult::SyncedMilestone(self.milestone_index);
-                    let _ = self.handle.send(SolidifierEvent::CqlResult(Err(synced_ms)));
+                    self.handle.send_control(SolidifierEvent::CqlResult(Err(synced_ms)));
                 }
             }
         } else {
             // no more retries
             // respond with error
             let synced_ms = CqlResult::SyncedMilestone(self.milestone_index);
-            let _ = self.handle.send(SolidifierEvent::CqlResult(Err(synced_ms)));
+            self.handle.send_control(SolidifierEvent::CqlResult(Err(synced_ms)));
         }
         Ok(())
     }
@@ -531,7 +566,7 @@ where
     fn handle_response(self: Box<Self>, giveload: Vec<u8>) -> anyhow::Result<()> {
         Decoder::try_from(giveload).and_then(|decoder| decoder.get_void())?;
         let analyzed_ms = CqlResult::AnalyzedMilestone(self.milestone_index);
-        let _ = self.handle.send(SolidifierEvent::CqlResult(Ok(analyzed_ms)));
+        self.handle.send_control(SolidifierEvent::CqlResult(Ok(analyzed_ms)));
         Ok(())
     }
     fn handle_error(
@@ -565,14 +600,14 @@ where
                 Err(e) => {
                     error!("{}", e);
                     let analyzed_ms = CqlResult::AnalyzedMilestone(self.milestone_index);
-                    let _ = self.handle.send(SolidifierEvent::CqlResult(Err(analyzed_ms)));
+                    self.handle.send_control(SolidifierEvent::CqlResult(Err(analyzed_ms)));
                 }
             }
         } else {
             // no more retries
             // respond with error
             let analyzed_ms = CqlResult::AnalyzedMilestone(self.milestone_index);
-            let _ = self.handle.send(SolidifierEvent::CqlResult(Err(analyzed_ms)));
+            self.handle.send_control(SolidifierEvent::CqlResult(Err(analyzed_ms)));
         }
         Ok(())
     }
@@ -13,6 +13,34 @@ impl<H: ChronicleBrokerScope> Terminating<BrokerHandle<H>> for Solidifier {
         self.service.update_status(ServiceStatus::Stopping);
         let event = BrokerEvent::Children(BrokerChild::Solidifier(self.service.clone(), _status));
         let _ = _supervisor.as_mut().unwrap().send(event);
+        if _status.is_ok() {
+            self.save_snapshot().await;
+        }
         _status
     }
 }
+
+impl Solidifier {
+    /// Snapshot the in-flight milestones data, so the next start doesn't collect them from scratch
+    async fn save_snapshot(&mut self) {
+        if let Some(dir) = self.snapshot_dir.as_ref() {
+            let snapshot = SolidifierSnapshot {
+                collector_count: self.collector_count,
+                milestones_data: self
+                    .milestones_data
+                    .drain()
+                    .map(|(_, milestone_data)| milestone_data)
+                    .collect(),
+            };
+            if let Err(e) = snapshot::save(dir, &self.get_name(), &snapshot).await {
+                error!("{} unable to save its snapshot: {}", self.get_name(), e);
+            } else {
+                info!(
+                    "{} saved its snapshot with {} milestones data",
+                    self.get_name(),
+                    snapshot.milestones_data.len()
+                );
+            }
+        }
+    }
+}
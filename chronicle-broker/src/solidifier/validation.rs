@@ -0,0 +1,137 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crypto::hashes::{
+    blake2b::Blake2b256,
+    Digest,
+};
+use std::collections::HashSet;
+
+const LEAF_HASH_PREFIX: u8 = 0;
+const NODE_HASH_PREFIX: u8 = 1;
+
+impl Solidifier {
+    /// Validate a completed milestone data against the configured coordinator public keys,
+    /// merkle proof and minimum PoW score.
+    pub(crate) fn validate(&self, milestone_data: &MilestoneData) -> anyhow::Result<()> {
+        let milestone_index = milestone_data.milestone_index();
+        let milestone = milestone_data
+            .milestone
+            .as_ref()
+            .ok_or_else(|| anyhow!("Missing milestone payload for milestone index: {}", milestone_index))?;
+        if !self.validation.coordinator_public_keys.is_empty() {
+            let applicable_public_keys = self.validation.applicable_public_keys(milestone_index);
+            milestone
+                .validate(&applicable_public_keys, self.validation.coordinator_threshold)
+                .map_err(|e| anyhow!("Invalid milestone index: {} signatures: {:?}", milestone_index, e))?;
+        }
+        if self.validation.verify_merkle_proof {
+            let included_messages =
+                white_flag_included_messages(milestone.essence().parents(), milestone_data.messages());
+            let merkle_proof = MerkleHasher::new().digest(&included_messages);
+            ensure!(
+                milestone.essence().merkle_proof() == merkle_proof.as_slice(),
+                "Milestone index: {} merkle proof doesn't match the {} included messages of the collected cone",
+                milestone_index,
+                included_messages.len()
+            );
+        }
+        if let Some(min_pow_score) = self.validation.min_pow_score {
+            for (message_id, FullMessage(message, _)) in milestone_data.messages() {
+                let pow_score = bee_pow::score::compute_pow_score(&message.pack_new());
+                ensure!(
+                    pow_score >= min_pow_score as f64,
+                    "Message id: {} referenced by milestone index: {} has a PoW score: {:.2} lower than: {}",
+                    message_id,
+                    milestone_index,
+                    pow_score,
+                    min_pow_score
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Traverse the milestone cone in white-flag order (post-order depth first, parents from left to right),
+/// and return the ids of the messages whose transaction got included in the ledger.
+/// Parents which are not part of the collected cone belong to older milestones and are skipped.
+pub fn white_flag_included_messages(
+    parents: &[MessageId],
+    messages: &HashMap<MessageId, FullMessage>,
+) -> Vec<MessageId> {
+    let mut included_messages = Vec::new();
+    let mut visited = HashSet::new();
+    let mut stack: Vec<MessageId> = parents.iter().rev().copied().collect();
+    while let Some(message_id) = stack.last().copied() {
+        if visited.contains(&message_id) {
+            stack.pop();
+            continue;
+        }
+        let full_message = match messages.get(&message_id) {
+            Some(full_message) => full_message,
+            None => {
+                visited.insert(message_id);
+                stack.pop();
+                continue;
+            }
+        };
+        if let Some(unvisited_parent) = full_message
+            .metadata()
+            .parent_message_ids
+            .iter()
+            .find(|parent_id| !visited.contains(*parent_id))
+        {
+            stack.push(*unvisited_parent);
+        } else {
+            visited.insert(message_id);
+            stack.pop();
+            if let Some(LedgerInclusionState::Included) = full_message.metadata().ledger_inclusion_state {
+                included_messages.push(message_id);
+            }
+        }
+    }
+    included_messages
+}
+
+/// Computes the merkle tree hash of the included messages as defined by the milestone merkle validation RFC
+pub struct MerkleHasher {
+    hasher: Blake2b256,
+}
+
+impl Default for MerkleHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MerkleHasher {
+    /// Create a new merkle hasher
+    pub fn new() -> Self {
+        Self {
+            hasher: Blake2b256::new(),
+        }
+    }
+    /// Compute the merkle tree hash of the message ids, in their given order
+    pub fn digest(&mut self, message_ids: &[MessageId]) -> Vec<u8> {
+        match message_ids.len() {
+            0 => self.hasher.finalize_reset().to_vec(),
+            1 => {
+                self.hasher.update([LEAF_HASH_PREFIX]);
+                self.hasher.update(message_ids[0]);
+                self.hasher.finalize_reset().to_vec()
+            }
+            len => {
+                // split at the largest power of two strictly lower than len
+                let split = 1 << (usize::BITS - (len - 1).leading_zeros() - 1);
+                let left = self.digest(&message_ids[..split]);
+                let right = self.digest(&message_ids[split..]);
+                self.hasher.update([NODE_HASH_PREFIX]);
+                self.hasher.update(left);
+                self.hasher.update(right);
+                self.hasher.finalize_reset().to_vec()
+            }
+        }
+    }
+}
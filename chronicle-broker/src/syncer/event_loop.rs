@@ -51,6 +51,8 @@ impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Syncer {
                 }
                 SyncerEvent::Unreachable(milestone_index) => {
                     self.pending -= 1;
+                    self.sync_status.unreachable(milestone_index);
+                    self.resyncs.progress(milestone_index, false);
                     // This happens when all the peers don't have the requested milestone_index
                     alert!(
                         "Chronicle syncer is unable to reach milestone index {} because no peers were able to provide it!",
@@ -59,8 +61,22 @@ impl<H: ChronicleBrokerScope> EventLoop<BrokerHandle<H>> for Syncer {
                     self.handle_skip();
                     self.trigger_process_more();
                 }
+                SyncerEvent::Resync { range, relog } => {
+                    info!("Queued the resync of the milestone range {:?}", range);
+                    self.resyncs.push(range, relog);
+                    // an idle syncer starts right away, otherwise once the current range is processed
+                    if self.active.is_none() && !self.highest.eq(&0) {
+                        self.start_resync();
+                    }
+                }
                 SyncerEvent::Shutdown => break,
             }
+            self.flush_archiver_outbox().await;
+            if let Some(supervisor) = _supervisor.as_ref() {
+                while let Some(resync_session) = self.resyncs.pop_session() {
+                    supervisor.send(BrokerEvent::Resync(resync_session)).ok();
+                }
+            }
         }
         Ok(())
     }
@@ -85,6 +101,10 @@ impl Syncer {
 
     pub(crate) async fn handle_milestone_data(&mut self, milestone_data: MilestoneData) {
         self.pending -= 1;
+        if !self.highest.eq(&0) {
+            self.sync_status.filled(milestone_data.milestone_index());
+            self.resyncs.progress(milestone_data.milestone_index(), true);
+        }
         self.milestones_data.push(Ascending::new(milestone_data));
         if self.highest.eq(&0) && self.pending.eq(&0) {
             // these are the first milestones data, which we didn't even request it.
@@ -123,13 +143,15 @@ impl Syncer {
         } else if !self.highest.eq(&0) && !self.skip {
             self.try_solidify_one_more();
             let upper_ms_limit = Some(self.initial_gap_end);
+            let relog = self.resyncs.relog();
             // check if we could send the next expected milestone_index
             while let Some(ms_data) = self.milestones_data.pop() {
                 let ms_index = ms_data.milestone_index();
                 if self.next.eq(&ms_index) {
-                    // push it to archiver
-                    let _ =
+                    // push it to archiver, unless it's resynced without relogging
+                    if relog {
                         self.try_send_to_archiver(ArchiverEvent::MilestoneData(ms_data.into_inner(), upper_ms_limit));
+                    }
                     self.next += 1;
                 } else {
                     // put it back and then break
@@ -164,12 +186,21 @@ impl Syncer {
                     // we just consume the range in order for the trigger_process_more to move further
                     while let Some(_) = range.next() {}
                 }
+                Active::Resync(ref mut range) => {
+                    error!("Resync: Skipping the remaining range: {:?}", range);
+                    // we just consume the range in order for the trigger_process_more to move further
+                    while let Some(_) = range.next() {}
+                }
             };
             // reset skip back to false
             self.skip = false;
         }
     }
     fn complete_or_fillgaps(&mut self) {
+        // the requested resyncs come first
+        if self.start_resync() {
+            return;
+        }
         match self.first_ask.as_ref() {
             Some(AskSyncer::Complete) => {
                 self.complete();
@@ -178,21 +209,29 @@ impl Syncer {
             _ => {}
         }
     }
-    fn try_send_to_archiver(&self, archiver_event: ArchiverEvent) {
+    fn try_send_to_archiver(&mut self, archiver_event: ArchiverEvent) {
+        if self.archiver_handle.is_some() {
+            self.archiver_outbox.push_back(archiver_event);
+        }
+    }
+    /// Send the queued archiver events in order, waiting on the archiver inbox if it's full
+    async fn flush_archiver_outbox(&mut self) {
         if let Some(archiver_handle) = self.archiver_handle.as_ref() {
-            let _ = archiver_handle.send(archiver_event);
+            while let Some(archiver_event) = self.archiver_outbox.pop_front() {
+                let _ = archiver_handle.send(archiver_event).await;
+            }
         }
     }
     fn close_log_file(&mut self) {
         let created_log_file = self.initial_gap_start != self.next;
         if self.prev_closed_log_filename != self.initial_gap_start && created_log_file {
-            if let Some(archiver_handle) = self.archiver_handle.as_ref() {
+            if self.archiver_handle.is_some() {
                 info!(
                     "Informing Archiver to close {}.part, and should be renamed to: {}to{}.log",
                     self.initial_gap_start, self.initial_gap_start, self.next
                 );
                 // We should close any part file related to the current gap
-                let _ = archiver_handle.send(ArchiverEvent::Close(self.next));
+                self.try_send_to_archiver(ArchiverEvent::Close(self.next));
             };
             self.prev_closed_log_filename = self.initial_gap_start;
         } else {
@@ -207,7 +246,7 @@ impl Syncer {
                     self.pending += 1;
                 }
             }
-            Active::FillGaps(ref mut range) => {
+            Active::FillGaps(ref mut range) | Active::Resync(ref mut range) => {
                 if let Some(milestone_index) = range.next() {
                     Self::request_solidify(self.solidifier_count, &self.solidifier_handles, milestone_index);
                     self.pending += 1;
@@ -231,7 +270,9 @@ impl Syncer {
                                 self.close_log_file();
                                 // Finished the current active range, therefore we drop it
                                 self.active.take();
-                                self.complete();
+                                if !self.start_resync() {
+                                    self.complete();
+                                }
                             }
                             break;
                         }
@@ -250,7 +291,29 @@ impl Syncer {
                                 self.close_log_file();
                                 // Finished the current active range, therefore we drop it
                                 self.active.take();
-                                self.fill_gaps();
+                                if !self.start_resync() {
+                                    self.fill_gaps();
+                                }
+                            }
+                            break;
+                        }
+                    }
+                }
+                Active::Resync(range) => {
+                    for _ in 0..self.parallelism {
+                        if let Some(milestone_index) = range.next() {
+                            Self::request_solidify(self.solidifier_count, &self.solidifier_handles, milestone_index);
+                            // update pending
+                            self.pending += 1;
+                        } else {
+                            // move to the next resync or gap (only if pending is zero)
+                            if self.pending.eq(&0) {
+                                if self.resyncs.relog() {
+                                    self.close_log_file();
+                                }
+                                self.active.take();
+                                self.resyncs.finish();
+                                self.complete_or_fillgaps();
                             }
                             break;
                         }
@@ -270,7 +333,7 @@ impl Syncer {
         let update_sync = async move {
             tokio::time::sleep(update_sync_data_every).await;
             let ask = AskSyncer::UpdateSyncData;
-            let _ = handle.send(SyncerEvent::Ask(ask));
+            let _ = handle.send(SyncerEvent::Ask(ask)).await;
         };
         tokio::spawn(update_sync);
     }
@@ -282,7 +345,9 @@ impl Syncer {
         let solidifier_id = (milestone_index % (solidifier_count as u32)) as u8;
         let solidifier_handle = solidifier_handles.get(&solidifier_id).unwrap();
         let solidify_event = SolidifierEvent::Solidify(Ok(milestone_index));
-        let _ = solidifier_handle.send(solidify_event);
+        // solidify requests are bounded by the parallelism and must not be lost, and the solidifier may be waiting on
+        // us, so they go on the control lane
+        solidifier_handle.send_control(solidify_event);
     }
     fn trigger_process_more(&mut self) {
         // move to next range (only if pending is zero)
@@ -292,75 +357,49 @@ impl Syncer {
         }
     }
     pub(crate) fn complete(&mut self) {
-        // start from the lowest uncomplete
-        if let Some(mut gap) = self.sync_data.take_lowest_uncomplete() {
-            // ensure gap.end != i32::MAX
-            if !gap.end.eq(&(i32::MAX as u32)) {
-                info!("Completing the gap {:?}", gap);
-                // set next to be the start
-                self.next = gap.start;
-                self.initial_gap_start = self.next;
-                self.initial_gap_end = gap.end;
-                self.active.replace(Active::Complete(gap));
-                self.trigger_process_more();
-            } else {
-                // fill this with the gap.start up to self.highest
-                // this is the last gap in our sync data
-                // First we ensure highest is larger than gap.start
-                if self.highest > gap.start {
-                    // set next to be the start
-                    self.next = gap.start;
-                    self.initial_gap_start = self.next;
-                    // update the end of the gap
-                    gap.end = self.highest;
-                    self.initial_gap_end = gap.end;
-                    info!("Completing the last gap {:?}", gap);
-                    self.active.replace(Active::Complete(gap));
-                    self.trigger_process_more();
-                } else {
-                    info!("There are no more gaps neither unlogged in the current sync data");
-                    self.trigger_process_more();
-                }
-            }
+        // take the next uncomplete range according to the sync strategy
+        if let Some(gap) = self.take_next_range(true) {
+            info!("Completing the gap {:?}", gap);
+            self.active.replace(Active::Complete(gap));
         } else {
             info!("There are no more gaps neither unlogged in the current sync data");
+        }
+        self.trigger_process_more();
+    }
+    /// Take the next range of the sync data, which is processed from its start
+    fn take_next_range(&mut self, with_unlogged: bool) -> Option<std::ops::Range<u32>> {
+        let gap = self
+            .sync_data
+            .take_next(&self.sync_strategy, with_unlogged, self.highest, self.turn)?;
+        self.turn += 1;
+        // set next to be the start
+        self.next = gap.start;
+        self.initial_gap_start = self.next;
+        self.initial_gap_end = gap.end;
+        Some(gap)
+    }
+    /// Start the next requested resync, if any
+    fn start_resync(&mut self) -> bool {
+        if let Some(range) = self.resyncs.start() {
+            info!("Resyncing the milestone range {:?}", range);
+            self.next = range.start;
+            self.initial_gap_start = self.next;
+            self.initial_gap_end = range.end;
+            self.active.replace(Active::Resync(range));
             self.trigger_process_more();
+            true
+        } else {
+            false
         }
     }
     pub(crate) fn fill_gaps(&mut self) {
-        // start from the lowest gap
-        if let Some(mut gap) = self.sync_data.take_lowest_gap() {
-            // ensure gap.end != i32::MAX
-            if !gap.end.eq(&(i32::MAX as u32)) {
-                info!("Filling the gap {:?}", gap);
-                // set next to be the start
-                self.next = gap.start;
-                self.initial_gap_start = self.next;
-                self.initial_gap_end = gap.end;
-                self.active.replace(Active::FillGaps(gap));
-                self.trigger_process_more();
-            } else {
-                // fill this with the gap.start up to self.highest
-                // this is the last gap in our sync data
-                // First we ensure highest is larger than gap.start
-                if self.highest > gap.start {
-                    info!("Filling the last gap {:?}", gap);
-                    // set next to be the start
-                    self.next = gap.start;
-                    self.initial_gap_start = self.next;
-                    // update the end of the gap
-                    gap.end = self.highest;
-                    self.initial_gap_end = gap.end;
-                    self.active.replace(Active::FillGaps(gap));
-                    self.trigger_process_more();
-                } else {
-                    info!("There are no more gaps in the current sync data");
-                    self.trigger_process_more();
-                }
-            }
+        // take the next gap according to the sync strategy
+        if let Some(gap) = self.take_next_range(false) {
+            info!("Filling the gap {:?}", gap);
+            self.active.replace(Active::FillGaps(gap));
         } else {
             info!("There are no more gaps in the current sync data");
-            self.trigger_process_more();
         }
+        self.trigger_process_more();
     }
 }
@@ -11,7 +11,10 @@ use super::{
     },
     *,
 };
-use chronicle_common::Wrapper;
+use chronicle_common::{
+    config::SyncStrategy,
+    Wrapper,
+};
 use chronicle_storage::keyspaces::ChronicleKeyspace;
 use std::{
     ops::{
@@ -32,11 +35,14 @@ builder!(SyncerBuilder {
     sync_range: SyncRange,
     solidifier_handles: HashMap<u8, SolidifierHandle>,
     parallelism: u8,
+    sync_strategy: SyncStrategy,
     archiver_handle: ArchiverHandle,
     first_ask: AskSyncer,
     oneshot: Sender<u32>,
     handle: SyncerHandle,
-    inbox: SyncerInbox
+    inbox: SyncerInbox,
+    keyspace: ChronicleKeyspace,
+    network: String
 });
 
 /// Syncer events
@@ -47,6 +53,13 @@ pub enum SyncerEvent {
     MilestoneData(MilestoneData),
     /// Notify of an unreachable cluster
     Unreachable(u32),
+    /// Solidify a milestone range again, once the current gap is processed
+    Resync {
+        /// The milestone range to resync
+        range: std::ops::Range<u32>,
+        /// Push the resynced milestones data to the archiver
+        relog: bool,
+    },
     /// Shutdown the syncer
     Shutdown,
 }
@@ -67,11 +80,11 @@ pub enum AskSyncer {
 /// Syncer handle
 #[derive(Clone)]
 pub struct SyncerHandle {
-    pub(crate) tx: tokio::sync::mpsc::UnboundedSender<SyncerEvent>,
+    pub(crate) tx: BoundedSender<SyncerEvent>,
 }
 
 impl Deref for SyncerHandle {
-    type Target = tokio::sync::mpsc::UnboundedSender<SyncerEvent>;
+    type Target = BoundedSender<SyncerEvent>;
 
     fn deref(&self) -> &Self::Target {
         &self.tx
@@ -86,11 +99,11 @@ impl DerefMut for SyncerHandle {
 
 /// SyncerInbox is used to recv requests from collector
 pub struct SyncerInbox {
-    pub(crate) rx: tokio::sync::mpsc::UnboundedReceiver<SyncerEvent>,
+    pub(crate) rx: BoundedReceiver<SyncerEvent>,
 }
 
 impl Deref for SyncerInbox {
-    type Target = tokio::sync::mpsc::UnboundedReceiver<SyncerEvent>;
+    type Target = BoundedReceiver<SyncerEvent>;
 
     fn deref(&self) -> &Self::Target {
         &self.rx
@@ -108,7 +121,7 @@ impl Shutdown for SyncerHandle {
     where
         Self: Sized,
     {
-        self.send(SyncerEvent::Shutdown).ok();
+        self.send_control(SyncerEvent::Shutdown);
         None
     }
 }
@@ -123,9 +136,17 @@ pub struct Syncer {
     solidifier_handles: HashMap<u8, SolidifierHandle>,
     solidifier_count: u8,
     parallelism: u8,
+    /// The order in which the ranges of the sync data are processed
+    sync_strategy: SyncStrategy,
+    /// The number of ranges taken from the sync data so far
+    turn: u64,
     active: Option<Active>,
     first_ask: Option<AskSyncer>,
     archiver_handle: Option<ArchiverHandle>,
+    /// Archiver events queued by the current event, flushed in order once it's handled
+    archiver_outbox: std::collections::VecDeque<ArchiverEvent>,
+    /// The requested resyncs, along with their sessions queued by the current event
+    resyncs: Resyncs,
     milestones_data: std::collections::BinaryHeap<Ascending<MilestoneData>>,
     highest: u32,
     pending: u32,
@@ -138,6 +159,10 @@ pub struct Syncer {
     oneshot: Option<Sender<u32>>,
     handle: SyncerHandle,
     inbox: SyncerInbox,
+    /// The sync status of the keyspace, it records the unreachable and filled milestones
+    sync_status: SyncStatus,
+    /// The network this syncer belongs to
+    network: String,
 }
 
 impl<H: ChronicleBrokerScope> ActorBuilder<BrokerHandle<H>> for SyncerBuilder {}
@@ -149,15 +174,18 @@ impl Builder for SyncerBuilder {
         let solidifier_handles = self.solidifier_handles.unwrap();
         let solidifier_count = solidifier_handles.len() as u8;
         let sync_data = self.sync_data.unwrap();
-        let config = chronicle_common::get_config();
-        let keyspace = ChronicleKeyspace::new(
-            config
-                .storage_config
-                .keyspaces
-                .first()
-                .and_then(|keyspace| Some(keyspace.name.clone()))
-                .unwrap_or("permanode".to_owned()),
-        );
+        let keyspace = self.keyspace.unwrap_or_else(|| {
+            let config = chronicle_common::get_config();
+            ChronicleKeyspace::new(
+                config
+                    .storage_config
+                    .keyspaces
+                    .first()
+                    .and_then(|keyspace| Some(keyspace.name.clone()))
+                    .unwrap_or("permanode".to_owned()),
+            )
+        });
+        let sync_status = SyncStatus::of(keyspace.name());
         Self::State {
             service: Service::new(),
             sync_data,
@@ -169,9 +197,13 @@ impl Builder for SyncerBuilder {
                 .update_sync_data_every
                 .unwrap_or(std::time::Duration::from_secs(60 * 60)),
             parallelism: self.parallelism.unwrap_or(solidifier_count),
+            sync_strategy: self.sync_strategy.unwrap_or_default(),
+            turn: 0,
             active: None,
             first_ask: self.first_ask,
             archiver_handle: self.archiver_handle,
+            archiver_outbox: std::collections::VecDeque::new(),
+            resyncs: Resyncs::default(),
             milestones_data: std::collections::BinaryHeap::new(),
             highest: 0,
             pending: solidifier_count as u32,
@@ -184,6 +216,8 @@ impl Builder for SyncerBuilder {
             oneshot: self.oneshot,
             handle: self.handle.unwrap(),
             inbox: self.inbox.unwrap(),
+            sync_status,
+            network: self.network.unwrap_or_default(),
         }
         .set_name()
     }
@@ -192,11 +226,93 @@ impl Builder for SyncerBuilder {
 enum Active {
     Complete(std::ops::Range<u32>),
     FillGaps(std::ops::Range<u32>),
+    Resync(std::ops::Range<u32>),
+}
+
+/// A requested resync of a milestone range
+#[derive(Debug)]
+struct Resync {
+    range: std::ops::Range<u32>,
+    relog: bool,
+    resynced: u32,
+}
+
+/// The requested resyncs of a syncer, which are processed one at a time in order.
+/// The sessions of the active resync are queued until they're sent to the broker.
+#[derive(Debug, Default)]
+pub struct Resyncs {
+    queued: std::collections::VecDeque<Resync>,
+    active: Option<Resync>,
+    outbox: std::collections::VecDeque<ResyncSession>,
+}
+
+impl Resyncs {
+    /// Queue the resync of a milestone range
+    pub fn push(&mut self, range: std::ops::Range<u32>, relog: bool) {
+        self.queued.push_back(Resync {
+            range,
+            relog,
+            resynced: 0,
+        });
+    }
+    /// Activate the next queued resync, returning its range
+    pub fn start(&mut self) -> Option<std::ops::Range<u32>> {
+        let resync = self.queued.pop_front()?;
+        let range = resync.range.clone();
+        self.active.replace(resync);
+        Some(range)
+    }
+    /// Whether the solidified milestones are pushed to the archiver, which is the case unless the active resync
+    /// was requested without relogging
+    pub fn relog(&self) -> bool {
+        self.active.as_ref().map_or(true, |resync| resync.relog)
+    }
+    /// Record a milestone of the active resync range as resynced, or skipped as it was unreachable
+    pub fn progress(&mut self, milestone_index: u32, resynced: bool) {
+        if let Some(resync) = self.active.as_mut() {
+            if resync.range.contains(&milestone_index) {
+                if resynced {
+                    resync.resynced += 1;
+                }
+                self.outbox.push_back(ResyncSession::Progress {
+                    from_ms: resync.range.start,
+                    to_ms: resync.range.end,
+                    milestone_index,
+                    resynced,
+                });
+            }
+        }
+    }
+    /// Finish the active resync, reporting the milestones which are left as gaps
+    pub fn finish(&mut self) {
+        if let Some(resync) = self.active.take() {
+            let skipped = resync.range.len() as u32 - resync.resynced;
+            let msg = if skipped == 0 {
+                format!("Resynced the milestone range {:?}", resync.range)
+            } else {
+                format!(
+                    "Unable to resync {} milestones of the range {:?}, they're left as gaps",
+                    skipped, resync.range
+                )
+            };
+            info!("{}", msg);
+            self.outbox.push_back(ResyncSession::Finish {
+                from_ms: resync.range.start,
+                to_ms: resync.range.end,
+                resynced: resync.resynced,
+                msg,
+            });
+        }
+    }
+    /// Take the next queued session
+    pub fn pop_session(&mut self) -> Option<ResyncSession> {
+        self.outbox.pop_front()
+    }
 }
 /// impl name of the Syncer
 impl Name for Syncer {
     fn set_name(mut self) -> Self {
-        let name = format!("Syncer");
+        let name = network_scoped_name(&self.network, "Syncer");
         self.service.update_name(name);
         self
     }
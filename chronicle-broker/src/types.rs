@@ -3,20 +3,32 @@
 
 use bee_message::{
     prelude::{
+        Address,
+        Ed25519Address,
         Essence,
         MilestonePayload,
         Output,
         Payload,
+        SignatureUnlock,
+        UnlockBlock,
     },
+    Message,
     MessageId,
 };
 use chronicle_storage::access::{
+    AnalyticMetrics,
     AnalyticRecord,
     FullMessage,
     LedgerInclusionState,
     MessageCount,
+    SnapshotKind,
     TransactionCount,
     TransferredTokens,
+    ANALYTIC_METRICS_VERSION,
+};
+use crypto::hashes::{
+    blake2b::Blake2b256,
+    Digest,
 };
 #[cfg(feature = "scylla-rs")]
 use scylla_rs::cql::Rows;
@@ -25,7 +37,11 @@ use serde::{
     Serialize,
 };
 use std::{
-    collections::HashMap,
+    collections::{
+        BTreeSet,
+        HashMap,
+        HashSet,
+    },
     ops::Range,
     path::PathBuf,
 };
@@ -43,8 +59,17 @@ pub enum BrokerSocketMsg<T> {
 pub enum ChronicleBrokerThrough {
     /// Shutdown json to gracefully shutdown broker app
     Shutdown,
-    /// Alter the topology of the broker app
+    /// Alter the topology of the broker app, for the first network
     Topology(BrokerTopology),
+    /// Alter the topology of the broker app, for the network which stores in the keyspace
+    NetworkTopology {
+        /// The keyspace of the network
+        keyspace: String,
+        /// The topology change
+        topology: BrokerTopology,
+    },
+    /// Request the sync reports of the networks
+    SyncReport,
     /// Exit the broker app
     ExitProgram,
 }
@@ -70,6 +95,41 @@ pub enum BrokerTopology {
         import_range: Option<Range<u32>>,
         /// The type of import requested
         import_type: ImportType,
+        /// Fetch the log files from the archive sink into the path, which is either a log file or a directory
+        #[serde(default)]
+        from_sink: bool,
+        /// Download the log files from this HTTP(S) archive mirror into the path, which is a directory
+        #[serde(default)]
+        mirror: Option<Url>,
+        /// Only import the messages which match this filter
+        #[serde(default)]
+        filter: Option<ImportFilter>,
+    },
+    /// Export a milestone range from the database into log files
+    Export {
+        /// The directory of the exported log files
+        path: PathBuf,
+        /// The milestone range to export
+        export_range: Range<u32>,
+    },
+    /// Mark a milestone range unsynced and have the syncer solidify it again
+    Resync {
+        /// The milestone range to resync
+        range: Range<u32>,
+        /// Write the resynced milestones data into new log files, otherwise the range is left synced but unlogged
+        relog: bool,
+        /// Delete the analytics and milestones rows of the range before resyncing it
+        #[serde(default)]
+        purge: bool,
+    },
+    /// Replay the ledger state from the latest checkpoint, writing a checkpoint every configured interval
+    ReplayLedger {
+        /// The milestone index up to which the ledger is replayed, or the latest available milestone if none is
+        /// provided
+        to: Option<u32>,
+        /// Seed the checkpoints with this bee full snapshot before replaying the ledger
+        #[serde(default)]
+        snapshot: Option<PathBuf>,
     },
     /// Add Endpoint
     Requesters(RequesterTopology),
@@ -82,6 +142,145 @@ pub enum ImportType {
     All,
     /// Import only Analytics data
     Analytics,
+    /// Compare the milestone data with the database without writing anything
+    Verify,
+    /// Import the Hornet/Bee snapshot files and cross-check them with the stored transactions
+    Snapshot,
+}
+
+/// The payload kinds of the messages
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PayloadKind {
+    /// A message without payload
+    NoPayload,
+    /// A transaction payload
+    Transaction,
+    /// A milestone payload
+    Milestone,
+    /// An indexation payload
+    Indexation,
+    /// Any other payload, ie a receipt or a treasury transaction
+    Other,
+}
+
+impl PayloadKind {
+    /// Get the kind of an optional payload
+    pub fn of(payload: Option<&Payload>) -> Self {
+        match payload {
+            None => PayloadKind::NoPayload,
+            Some(Payload::Transaction(_)) => PayloadKind::Transaction,
+            Some(Payload::Milestone(_)) => PayloadKind::Milestone,
+            Some(Payload::Indexation(_)) => PayloadKind::Indexation,
+            Some(_) => PayloadKind::Other,
+        }
+    }
+}
+
+impl std::str::FromStr for PayloadKind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(PayloadKind::NoPayload),
+            "transaction" => Ok(PayloadKind::Transaction),
+            "milestone" => Ok(PayloadKind::Milestone),
+            "indexation" => Ok(PayloadKind::Indexation),
+            "other" => Ok(PayloadKind::Other),
+            _ => Err(format!("Invalid payload kind: {}", s)),
+        }
+    }
+}
+
+/// The filter of the messages to import, a message is imported if it matches all the provided criteria.
+/// The milestone range is the import range, and the milestone messages are always imported.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ImportFilter {
+    /// The payload kinds of the messages to import
+    #[serde(default)]
+    pub payload_kinds: Vec<PayloadKind>,
+    /// The hex encoded prefix of the indexation index, either of the message payload or of its transaction payload
+    #[serde(default)]
+    pub index_prefix: Option<String>,
+    /// The hex encoded Ed25519 addresses, which the imported transactions have outputs to
+    #[serde(default)]
+    pub addresses: HashSet<String>,
+}
+
+impl ImportFilter {
+    /// Check whether the filter has no criteria
+    pub fn is_empty(&self) -> bool {
+        self.payload_kinds.is_empty() && self.index_prefix.is_none() && self.addresses.is_empty()
+    }
+    /// Drop the messages of the milestone data which don't match the filter
+    pub fn apply(&self, milestone_data: &mut MilestoneData) {
+        milestone_data
+            .messages
+            .retain(|_, FullMessage(message, _)| self.matches(message));
+    }
+    /// Check whether a message is imported
+    pub fn matches(&self, message: &Message) -> bool {
+        let payload = message.payload().as_ref();
+        let kind = PayloadKind::of(payload);
+        if kind == PayloadKind::Milestone {
+            return true;
+        }
+        let index_prefix = self.index_prefix.as_ref().map(|prefix| prefix.to_lowercase());
+        (self.payload_kinds.is_empty() || self.payload_kinds.contains(&kind))
+            && index_prefix.map_or(true, |prefix| {
+                indexation_index(payload).map_or(false, |index| hex::encode(index).starts_with(&prefix))
+            })
+            && (self.addresses.is_empty()
+                || output_addresses(payload)
+                    .iter()
+                    .any(|address| self.addresses.contains(address)))
+    }
+}
+
+/// Get the indexation index of a payload, or of the payload of a transaction
+fn indexation_index(payload: Option<&Payload>) -> Option<&[u8]> {
+    match payload {
+        Some(Payload::Indexation(indexation)) => Some(indexation.index()),
+        Some(Payload::Transaction(transaction)) => {
+            let Essence::Regular(regular) = transaction.essence();
+            indexation_index(regular.payload().as_ref())
+        }
+        _ => None,
+    }
+}
+
+/// Get the hex encoded Ed25519 addresses of the outputs of a transaction payload
+fn output_addresses(payload: Option<&Payload>) -> Vec<String> {
+    let mut addresses = Vec::new();
+    if let Some(Payload::Transaction(transaction)) = payload {
+        let Essence::Regular(regular) = transaction.essence();
+        for output in regular.outputs() {
+            let address = match output {
+                Output::SignatureLockedSingle(output) => output.address(),
+                Output::SignatureLockedDustAllowance(output) => output.address(),
+                _ => continue,
+            };
+            let Address::Ed25519(address) = address;
+            addresses.push(address.to_string());
+        }
+    }
+    addresses
+}
+
+/// The number of indexation tags kept by the analytic metrics
+const TOP_INDEXATION_TAGS: usize = 10;
+
+/// Get the bytes of an Ed25519 address
+fn address_bytes(address: &Ed25519Address) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(address.as_ref());
+    bytes
+}
+
+/// Get the most used indexation tags by descending count, then by tag
+fn top_indexation_tags(tags: HashMap<String, u32>) -> Vec<(String, u32)> {
+    let mut tags = tags.into_iter().collect::<Vec<_>>();
+    tags.sort_by(|(a_tag, a_count), (b_tag, b_count)| b_count.cmp(a_count).then_with(|| a_tag.cmp(b_tag)));
+    tags.truncate(TOP_INDEXATION_TAGS);
+    tags
 }
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
@@ -101,6 +300,9 @@ pub struct MilestoneData {
     pub(crate) messages: HashMap<MessageId, FullMessage>,
     pub(crate) pending: HashMap<MessageId, ()>,
     pub(crate) created_by: CreatedBy,
+    /// The receivers which received outputs before the milestone, once looked up
+    #[serde(skip)]
+    pub(crate) known_addresses: Option<BTreeSet<[u8; 32]>>,
 }
 
 impl MilestoneData {
@@ -112,38 +314,120 @@ impl MilestoneData {
             messages: HashMap::new(),
             pending: HashMap::new(),
             created_by,
+            known_addresses: None,
         }
     }
     /// Get the milestone index from this milestone data
     pub fn milestone_index(&self) -> u32 {
         self.milestone_index
     }
-    /// Get the analytics from the collected messages
+    /// Get the distinct addresses which receive outputs through the included transactions
+    pub fn receivers(&self) -> Vec<Ed25519Address> {
+        let mut receivers = BTreeSet::new();
+        for FullMessage(message, metadata) in self.messages.values() {
+            if let (Some(LedgerInclusionState::Included), Some(Payload::Transaction(payload))) =
+                (metadata.ledger_inclusion_state, message.payload())
+            {
+                let Essence::Regular(regular_essence) = payload.essence();
+                for output in regular_essence.outputs() {
+                    let address = match output {
+                        Output::SignatureLockedSingle(output) => output.address(),
+                        Output::SignatureLockedDustAllowance(output) => output.address(),
+                        _ => continue,
+                    };
+                    let Address::Ed25519(address) = address;
+                    receivers.insert(address_bytes(address));
+                }
+            }
+        }
+        receivers.into_iter().map(Ed25519Address::new).collect()
+    }
+    /// Set the receivers which received outputs before the milestone, so the analytics count the others as new
+    /// addresses
+    pub fn set_known_addresses(&mut self, known_addresses: Vec<Ed25519Address>) {
+        self.known_addresses
+            .replace(known_addresses.iter().map(address_bytes).collect());
+    }
+    /// Get the analytics from the collected messages.
+    /// The known addresses must be set beforehand, to tell the new addresses apart.
     pub fn get_analytic_record(&self) -> anyhow::Result<AnalyticRecord> {
         if !self.check_if_completed() {
             anyhow::bail!("cannot get analytics for uncompleted milestone data")
         }
+        let known_addresses = self.known_addresses.as_ref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "cannot get analytics before looking up the address history of milestone {}",
+                self.milestone_index
+            )
+        })?;
         // The accumulators
         let mut transaction_count: u32 = 0;
         let mut message_count: u32 = 0;
         let mut transferred_tokens: u64 = 0;
+        let mut metrics = AnalyticMetrics {
+            version: ANALYTIC_METRICS_VERSION,
+            ..Default::default()
+        };
+        let mut senders = BTreeSet::new();
+        let mut receivers = BTreeSet::new();
+        let mut indexation_tags = HashMap::<String, u32>::new();
 
         // Iterate the messages to calculate analytics
         for (_, FullMessage(message, metadata)) in &self.messages {
             // Accumulate the message count
             message_count += 1;
+            metrics.parents += message.parents().len() as u32;
+            if let Some(index) = indexation_index(message.payload()) {
+                *indexation_tags.entry(hex::encode(index)).or_default() += 1;
+            }
+            match message.payload() {
+                Some(Payload::Indexation(_)) => metrics.indexation_payloads += 1,
+                Some(Payload::Transaction(_)) => metrics.transaction_payloads += 1,
+                Some(Payload::Milestone(milestone)) => {
+                    metrics.milestone_payloads += 1;
+                    if milestone.essence().receipt().is_some() {
+                        metrics.receipt_payloads += 1;
+                    }
+                }
+                Some(Payload::Receipt(_)) => metrics.receipt_payloads += 1,
+                Some(_) => (),
+                None => metrics.no_payload += 1,
+            }
+            if let (Some(LedgerInclusionState::Conflicting), Some(Payload::Transaction(_))) =
+                (metadata.ledger_inclusion_state, message.payload())
+            {
+                metrics.conflicting_transactions += 1;
+            }
             // Accumulate confirmed(included) transaction value
             if let Some(LedgerInclusionState::Included) = metadata.ledger_inclusion_state {
                 if let Some(Payload::Transaction(payload)) = message.payload() {
                     // Accumulate the transaction count
                     transaction_count += 1;
+                    // The senders are the addresses of the signature unlock blocks' public keys
+                    for unlock_block in payload.unlock_blocks().iter() {
+                        if let UnlockBlock::Signature(SignatureUnlock::Ed25519(signature)) = unlock_block {
+                            let mut address = [0u8; 32];
+                            address.copy_from_slice(&Blake2b256::digest(signature.public_key()));
+                            senders.insert(address);
+                        }
+                    }
                     let Essence::Regular(regular_essence) = payload.essence();
                     {
                         for output in regular_essence.outputs() {
                             match output {
                                 // Accumulate the transferred token amount
-                                Output::SignatureLockedSingle(output) => transferred_tokens += output.amount(),
-                                Output::SignatureLockedDustAllowance(output) => transferred_tokens += output.amount(),
+                                Output::SignatureLockedSingle(output) => {
+                                    transferred_tokens += output.amount();
+                                    let Address::Ed25519(address) = output.address();
+                                    receivers.insert(address_bytes(address));
+                                }
+                                Output::SignatureLockedDustAllowance(output) => {
+                                    transferred_tokens += output.amount();
+                                    metrics.dust_allowance_outputs += 1;
+                                    metrics.dust_allowance_tokens += output.amount();
+                                    let Address::Ed25519(address) = output.address();
+                                    receivers.insert(address_bytes(address));
+                                }
                                 // Note that the transaction payload don't have Treasury
                                 _ => anyhow::bail!("Unexpected Output variant in transaction payload"),
                             }
@@ -152,13 +436,18 @@ impl MilestoneData {
                 }
             }
         }
+        // The receivers without any output before the milestone are new
+        let new_addresses = receivers.difference(known_addresses).copied().collect();
+        metrics.set_addresses(senders.union(&receivers).copied().collect(), new_addresses);
+        metrics.top_indexation_tags = top_indexation_tags(indexation_tags);
         let milestone_index = self.milestone_index();
         let analytic_record = AnalyticRecord::new(
             bee_message::milestone::MilestoneIndex(milestone_index),
             MessageCount(message_count),
             TransactionCount(transaction_count),
             TransferredTokens(transferred_tokens),
-        );
+        )
+        .with_metrics(metrics);
         // Return the analytic record
         Ok(analytic_record)
     }
@@ -170,6 +459,57 @@ impl MilestoneData {
     pub fn milestone_exist(&self) -> bool {
         self.milestone.is_some()
     }
+    /// Get the unix timestamp of the milestone, if it exists
+    pub fn milestone_timestamp(&self) -> Option<u64> {
+        self.milestone.as_ref().map(|milestone| milestone.essence().timestamp())
+    }
+    /// Get the white-flag order of the cone, which is the post-order traversal of the parents from the milestone
+    /// message, in the order of the parents. The milestone message comes last.
+    pub fn white_flag_order(&self) -> Vec<MessageId> {
+        let milestone_index = self.milestone_index;
+        let root = self
+            .messages
+            .iter()
+            .find_map(|(message_id, FullMessage(message, _))| match message.payload() {
+                Some(Payload::Milestone(milestone)) if milestone.essence().index().0 == milestone_index => {
+                    Some(*message_id)
+                }
+                _ => None,
+            });
+        let mut order = Vec::with_capacity(self.messages.len());
+        let mut visited = HashSet::new();
+        // each message is pushed once to be expanded, then once more to be emitted after its parents
+        let mut stack: Vec<(MessageId, bool)> = root.into_iter().map(|root| (root, false)).collect();
+        while let Some((message_id, expanded)) = stack.pop() {
+            if expanded {
+                order.push(message_id);
+                continue;
+            }
+            if !visited.insert(message_id) {
+                continue;
+            }
+            stack.push((message_id, true));
+            if let Some(FullMessage(message, _)) = self.messages.get(&message_id) {
+                // only the messages of the cone are traversed, the rest were referenced by previous milestones
+                for parent in message.parents().iter().rev() {
+                    if self.messages.contains_key(parent) && !visited.contains(parent) {
+                        stack.push((*parent, false));
+                    }
+                }
+            }
+        }
+        order
+    }
+    /// Set the white-flag index and the referenced milestone timestamp of the messages of a completed cone
+    pub fn confirm(&mut self) {
+        let timestamp = self.milestone_timestamp();
+        for (white_flag_index, message_id) in self.white_flag_order().into_iter().enumerate() {
+            if let Some(FullMessage(_, metadata)) = self.messages.get_mut(&message_id) {
+                metadata.white_flag_index = Some(white_flag_index as u32);
+                metadata.milestone_timestamp_referenced = timestamp;
+            }
+        }
+    }
     /// Add full message into the milestone messages
     pub fn add_full_message(&mut self, full_message: FullMessage) {
         self.messages.insert(*full_message.message_id(), full_message);
@@ -221,6 +561,8 @@ pub enum CreatedBy {
     Expected = 1,
     /// Created by solidifiy/sync request from syncer
     Syncer = 2,
+    /// Reconstructed from the database by the exporter
+    Exporter = 3,
 }
 
 impl From<CreatedBy> for u8 {
@@ -256,6 +598,36 @@ pub enum ImporterSession {
         /// Finish the progress bar using this msg.
         msg: String,
     },
+    /// Aggregate progress of the scheduled import shards
+    Progress {
+        /// The number of imported milestones
+        imported: u64,
+        /// The number of milestones skipped as they were already imported
+        skipped: u64,
+        /// The total number of milestones of the scheduled shards
+        total: u64,
+        /// The number of shards being imported
+        running_shards: usize,
+        /// The number of shards waiting for an importer
+        pending_shards: usize,
+        /// The import rate since the session started
+        milestones_per_sec: f64,
+        /// The estimated time to import the remaining milestones
+        eta_secs: Option<u64>,
+    },
+    /// The differences found by a verify import
+    Verified(VerifyReport),
+    /// Progress of a snapshot import
+    SnapshotProgress {
+        /// The snapshot file
+        path: PathBuf,
+        /// The number of imported outputs and milestone diffs
+        imported: u64,
+        /// The total number of outputs and milestone diffs of the snapshot
+        total: u64,
+    },
+    /// A snapshot file was imported and cross-checked
+    SnapshotImported(SnapshotReport),
     /// Return error
     PathError {
         /// Invalid dir or file path
@@ -267,13 +639,317 @@ pub enum ImporterSession {
     Close,
 }
 
+/// Enum used by exporter to keep the sockets up to date with most recent progress.
+#[derive(Deserialize, Serialize, Debug)]
+pub enum ExporterSession {
+    /// A milestone was exported, or skipped as it couldn't be reconstructed
+    Progress {
+        /// The export range start
+        from_ms: u32,
+        /// The export range end
+        to_ms: u32,
+        /// Milestone index
+        milestone_index: u32,
+        /// Identify whether the milestone was exported or skipped
+        exported: bool,
+    },
+    /// A log file was finalized
+    LogFile {
+        /// The path of the finalized log file
+        path: PathBuf,
+    },
+    /// Finish the export with message
+    Finish {
+        /// The export range start
+        from_ms: u32,
+        /// The export range end
+        to_ms: u32,
+        /// The number of exported milestones
+        exported: u64,
+        /// The milestones which couldn't be reconstructed from the database
+        missing: MilestoneRanges,
+        /// Finish the export using this msg
+        msg: String,
+    },
+}
+
+/// Enum used by syncer to keep the sockets up to date with the progress of a resync.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum ResyncSession {
+    /// A milestone was solidified again, or skipped as it was unreachable
+    Progress {
+        /// The resync range start
+        from_ms: u32,
+        /// The resync range end
+        to_ms: u32,
+        /// Milestone index
+        milestone_index: u32,
+        /// Identify whether the milestone was resynced or skipped
+        resynced: bool,
+    },
+    /// Finish the resync with message
+    Finish {
+        /// The resync range start
+        from_ms: u32,
+        /// The resync range end
+        to_ms: u32,
+        /// The number of resynced milestones
+        resynced: u32,
+        /// Finish the resync using this msg
+        msg: String,
+    },
+}
+
+/// Enum used to keep the sockets up to date with the progress of a ledger replay.
+#[derive(Deserialize, Serialize, Debug)]
+pub enum LedgerSession {
+    /// A milestone was applied to the ledger state
+    Progress {
+        /// The index of the milestone which confirmed the ledger state
+        ledger_index: u32,
+    },
+    /// Finish the ledger replay with message
+    Finish {
+        /// The index of the milestone which confirmed the replayed ledger state
+        ledger_index: Option<u32>,
+        /// Finish the ledger replay using this msg
+        msg: String,
+    },
+}
+
+/// An action of the archive maintainer, which is reported to the sockets
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub enum MaintenanceAction {
+    /// Contiguous small log files were merged into a single one
+    Merged {
+        /// The merged log files
+        files: Vec<PathBuf>,
+        /// The resulting log file
+        into: PathBuf,
+    },
+    /// An old log file was compressed to zstd
+    Compressed {
+        /// The compressed log file
+        path: PathBuf,
+    },
+    /// An old log file was moved
+    Moved {
+        /// The previous path of the log file
+        from: PathBuf,
+        /// The new path of the log file
+        to: PathBuf,
+    },
+    /// A log file which wasn't marked as stored, ie a merged or compressed one, was stored in the archive sink
+    Stored {
+        /// The stored log file
+        path: PathBuf,
+    },
+    /// A log file verified in the archive sink was deleted
+    Deleted {
+        /// The deleted log file
+        path: PathBuf,
+    },
+    /// The logs directory exceeds the maximum disk usage
+    DiskUsageExceeded {
+        /// The disk usage in bytes
+        used: u64,
+        /// The maximum disk usage in bytes
+        max: u64,
+    },
+    /// A maintenance action failed
+    Failed {
+        /// The log file, if the action is about a single one
+        path: Option<PathBuf>,
+        /// Useful debug message
+        msg: String,
+    },
+}
+
+/// The report of an archive maintenance action in the logs directory of a network
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct MaintenanceReport {
+    /// The network name, empty for the network described by the top level broker config
+    pub network: String,
+    /// The maintenance action
+    pub action: MaintenanceAction,
+}
+
+/// The differences between the milestone data of an archive and the database, found by a verify import
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct VerifyReport {
+    /// The first verified milestone index
+    pub from_ms: u32,
+    /// The milestone index after the last verified one
+    pub to_ms: u32,
+    /// The `messages` table rows
+    pub messages: RecordDiff,
+    /// The message metadata of the `messages` table rows
+    pub metadata: RecordDiff,
+    /// The `sync` table rows
+    pub sync: RecordDiff,
+    /// The `analytics` table rows
+    pub analytics: RecordDiff,
+}
+
+impl VerifyReport {
+    /// Create an empty report for the given milestone range
+    pub fn new(range: Range<u32>) -> Self {
+        Self {
+            from_ms: range.start,
+            to_ms: range.end,
+            ..Default::default()
+        }
+    }
+    /// Merge the report of another milestone range into this one
+    pub fn merge(&mut self, other: VerifyReport) {
+        if self.from_ms == self.to_ms {
+            self.from_ms = other.from_ms;
+            self.to_ms = other.to_ms;
+        } else if other.from_ms != other.to_ms {
+            self.from_ms = self.from_ms.min(other.from_ms);
+            self.to_ms = self.to_ms.max(other.to_ms);
+        }
+        self.messages.merge(other.messages);
+        self.metadata.merge(other.metadata);
+        self.sync.merge(other.sync);
+        self.analytics.merge(other.analytics);
+    }
+    /// Check whether the database matches the archive
+    pub fn is_consistent(&self) -> bool {
+        [&self.messages, &self.metadata, &self.sync, &self.analytics]
+            .iter()
+            .all(|diff| diff.is_consistent())
+    }
+}
+
+/// The outcome of a snapshot import, cross-checked against the stored transactions
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct SnapshotReport {
+    /// The snapshot file
+    pub path: PathBuf,
+    /// The kind of the snapshot
+    pub kind: SnapshotKind,
+    /// The index of the milestone which confirmed the ledger state of the snapshot
+    pub ledger_index: u32,
+    /// The solid entry point index of the snapshot
+    pub sep_index: u32,
+    /// The number of imported unspent outputs
+    pub outputs: u64,
+    /// The number of imported milestone diffs
+    pub milestone_diffs: u64,
+    /// The number of records which match the stored transactions and milestones
+    pub matched: u64,
+    /// The number of records which aren't stored yet
+    pub unknown: u64,
+    /// The number of records which differ from the stored transactions and milestones
+    pub mismatched: u64,
+    /// The first mismatches found
+    pub mismatches: Vec<String>,
+}
+
+impl SnapshotReport {
+    /// Check whether the snapshot matches the stored transactions and milestones
+    pub fn is_consistent(&self) -> bool {
+        self.mismatched == 0
+    }
+}
+
+/// The differences of a table between an archive and the database
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct RecordDiff {
+    /// The number of records which match the archive
+    pub matched: u64,
+    /// The records of the archive which are missing from the database
+    pub missing: MilestoneRanges,
+    /// The records which differ from the archive
+    pub mismatched: MilestoneRanges,
+    /// The records of the database which are missing from the archive
+    pub extra: MilestoneRanges,
+}
+
+impl RecordDiff {
+    /// Merge the differences of another milestone range into this one
+    pub fn merge(&mut self, other: RecordDiff) {
+        self.matched += other.matched;
+        self.missing.merge(other.missing);
+        self.mismatched.merge(other.mismatched);
+        self.extra.merge(other.extra);
+    }
+    /// Check whether no differences were found
+    pub fn is_consistent(&self) -> bool {
+        self.missing.is_empty() && self.mismatched.is_empty() && self.extra.is_empty()
+    }
+}
+
+/// A number of records along with the milestone ranges they belong to
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+pub struct MilestoneRanges {
+    /// The number of records
+    pub count: u64,
+    /// The sorted and disjoint milestone ranges of the records
+    pub ranges: Vec<Range<u32>>,
+}
+
+impl MilestoneRanges {
+    /// Add a record of the given milestone index
+    pub fn add(&mut self, milestone_index: u32) {
+        self.count += 1;
+        self.insert(milestone_index..milestone_index + 1);
+    }
+    /// Check whether the ranges contain the given milestone index
+    pub fn contains(&self, milestone_index: u32) -> bool {
+        let position = self.ranges.partition_point(|range| range.end <= milestone_index);
+        self.ranges
+            .get(position)
+            .map_or(false, |range| range.contains(&milestone_index))
+    }
+    /// Check whether there are no records
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+    /// Merge the records of other milestone ranges into these ones
+    pub fn merge(&mut self, other: MilestoneRanges) {
+        self.count += other.count;
+        for range in other.ranges {
+            self.insert(range);
+        }
+    }
+    fn insert(&mut self, mut range: Range<u32>) {
+        if range.is_empty() {
+            return;
+        }
+        // the ranges which overlap or touch the inserted one are merged into it
+        let start = self.ranges.partition_point(|r| r.end < range.start);
+        let end = start + self.ranges[start..].partition_point(|r| r.start <= range.end);
+        if start < end {
+            range.start = range.start.min(self.ranges[start].start);
+            range.end = range.end.max(self.ranges[end - 1].end);
+        }
+        self.ranges.splice(start..end, std::iter::once(range));
+    }
+}
+
 #[cfg(feature = "sync")]
 pub use sync::*;
 #[cfg(feature = "sync")]
 mod sync {
     use super::*;
-    use chronicle_common::SyncRange;
-    use chronicle_storage::access::SyncRecord;
+    use bee_message::milestone::{
+        Milestone,
+        MilestoneIndex,
+    };
+    use chronicle_common::{
+        config::SyncStrategy,
+        SyncRange,
+    };
+    use chronicle_storage::{
+        access::SyncRecord,
+        keyspaces::ChronicleKeyspace,
+    };
+    use futures::stream::{
+        self,
+        StreamExt,
+    };
     use scylla_rs::prelude::{
         Consistency,
         GetSelectRequest,
@@ -282,10 +958,129 @@ mod sync {
         ValueWorker,
     };
     use std::{
-        collections::VecDeque,
+        collections::{
+            BTreeMap,
+            VecDeque,
+        },
         ops::Range,
+        sync::Arc,
+        time::Instant,
     };
 
+    /// Select a single row from the keyspace
+    pub(crate) async fn select_one<S, K, V>(keyspace: &S, key: K, retries: usize) -> anyhow::Result<Option<V>>
+    where
+        S: 'static + Select<K, V>,
+        K: 'static + Send + Clone,
+        V: 'static + Send + Clone,
+    {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        keyspace
+            .select::<V>(&key)
+            .consistency(Consistency::One)
+            .build()?
+            .send_local(ValueWorker::boxed(
+                tx,
+                keyspace.clone(),
+                key,
+                retries,
+                std::marker::PhantomData,
+            ));
+        Ok(rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No response for the select query"))??)
+    }
+
+    /// Reconstruct the milestone data of a milestone from the `milestones` and `messages` tables, by walking the
+    /// parents of the milestone message down to the messages referenced by previous milestones.
+    /// Returns None if the milestone isn't stored or its cone is incomplete.
+    pub async fn reconstruct_milestone_data(
+        keyspace: ChronicleKeyspace,
+        milestone_index: u32,
+        retries: usize,
+        parallelism: usize,
+    ) -> anyhow::Result<Option<MilestoneData>> {
+        let milestone: Milestone = match select_one(&keyspace, MilestoneIndex(milestone_index), retries).await? {
+            Some(milestone) => milestone,
+            None => return Ok(None),
+        };
+        let milestone_data = collect_cone(&keyspace, &milestone, milestone_index, retries, parallelism).await?;
+        if !milestone_data.milestone_exist() {
+            log::warn!(
+                "Unable to find the milestone message of milestone index: {}",
+                milestone_index
+            );
+            return Ok(None);
+        }
+        // the cone is incomplete if the milestone analytics counted more messages
+        let sync_range = SyncRange {
+            from: milestone_index,
+            to: milestone_index + 1,
+        };
+        let analytic_record = select_one::<_, _, Iter<AnalyticRecord>>(&keyspace, sync_range, retries)
+            .await?
+            .and_then(|mut records| records.next());
+        if let Some(analytic_record) = analytic_record {
+            let message_count = **analytic_record.message_count() as usize;
+            if message_count != milestone_data.messages().len() {
+                log::warn!(
+                    "Incomplete cone of milestone index: {}, found {} of {} messages",
+                    milestone_index,
+                    milestone_data.messages().len(),
+                    message_count
+                );
+                return Ok(None);
+            }
+        }
+        Ok(Some(milestone_data))
+    }
+
+    /// Collect the stored messages of the cone of a milestone, by walking the parents of the milestone message down to
+    /// the messages referenced by previous milestones. The cone is incomplete if some of its messages aren't stored.
+    pub async fn collect_cone(
+        keyspace: &ChronicleKeyspace,
+        milestone: &Milestone,
+        milestone_index: u32,
+        retries: usize,
+        parallelism: usize,
+    ) -> anyhow::Result<MilestoneData> {
+        let mut milestone_data = MilestoneData::new(milestone_index, CreatedBy::Exporter);
+        let mut visited = HashSet::new();
+        visited.insert(*milestone.message_id());
+        let mut next = vec![*milestone.message_id()];
+        while !next.is_empty() {
+            let mut messages = stream::iter(std::mem::take(&mut next).into_iter().map(|message_id| {
+                let keyspace = keyspace.clone();
+                async move { select_one::<_, _, FullMessage>(&keyspace, message_id, retries).await }
+            }))
+            .buffer_unordered(parallelism);
+            while let Some(full_message) = messages.next().await {
+                // the messages referenced by previous milestones, or which are not stored, are out of the cone
+                let full_message = match full_message? {
+                    Some(full_message)
+                        if full_message.metadata().referenced_by_milestone_index == Some(milestone_index) =>
+                    {
+                        full_message
+                    }
+                    _ => continue,
+                };
+                for parent_id in full_message.0.parents().iter() {
+                    if visited.insert(*parent_id) {
+                        next.push(*parent_id);
+                    }
+                }
+                if let Some(Payload::Milestone(milestone_payload)) = full_message.0.payload() {
+                    if *milestone_payload.essence().index() == milestone_index {
+                        milestone_data.set_milestone(milestone_payload.clone());
+                    }
+                }
+                milestone_data.add_full_message(full_message);
+            }
+        }
+        Ok(milestone_data)
+    }
+
     /// Representation of the database sync data
     #[derive(Debug, Clone, Default, Serialize)]
     pub struct SyncData {
@@ -375,13 +1170,17 @@ mod sync {
                     break;
                 }
             }
+            Ok(Self::from_records(sync_range, pages))
+        }
+        /// Build the sync data of the sync range from its sync records, which are ordered by descending milestone index
+        pub fn from_records(sync_range: &SyncRange, mut records: impl Iterator<Item = SyncRecord>) -> SyncData {
             let mut sync_data = SyncData::default();
             // Get the first row, note: the first row is always with the largest milestone_index
             if let Some(SyncRecord {
                 milestone_index,
                 logged_by,
                 ..
-            }) = pages.next()
+            }) = records.next()
             {
                 // push missing row/gap (if any)
                 sync_data.process_gaps(sync_range.to, *milestone_index);
@@ -393,7 +1192,7 @@ mod sync {
                     milestone_index,
                     logged_by,
                     ..
-                }) = pages.next()
+                }) = records.next()
                 {
                     // check if there are any missings
                     sync_data.process_gaps(*pre_ms, *milestone_index);
@@ -407,12 +1206,11 @@ mod sync {
                 // (lower provided sync bound) are missing
                 // push missing row/gap (if any)
                 sync_data.process_gaps(*pre_ms, sync_range.from - 1);
-                Ok(sync_data)
             } else {
                 // Everything is missing as gaps
                 sync_data.process_gaps(sync_range.to, sync_range.from - 1);
-                Ok(sync_data)
             }
+            sync_data
         }
         /// Takes the lowest gap from the sync_data
         pub fn take_lowest_gap(&mut self) -> Option<Range<u32>> {
@@ -424,53 +1222,139 @@ mod sync {
         }
         /// Takes the lowest unlogged or gap from the sync_data
         pub fn take_lowest_gap_or_unlogged(&mut self) -> Option<Range<u32>> {
-            let lowest_gap = self.gaps.last();
-            let lowest_unlogged = self.synced_but_unlogged.last();
-            match (lowest_gap, lowest_unlogged) {
-                (Some(gap), Some(unlogged)) => {
-                    if gap.start < unlogged.start {
-                        self.gaps.pop()
-                    } else {
-                        self.synced_but_unlogged.pop()
-                    }
-                }
-                (Some(_), None) => self.gaps.pop(),
-                (None, Some(_)) => self.synced_but_unlogged.pop(),
-                _ => None,
-            }
+            self.take_gap_or_unlogged(true, false)
         }
         /// Takes the lowest uncomplete(mixed range for unlogged and gap) from the sync_data
         pub fn take_lowest_uncomplete(&mut self) -> Option<Range<u32>> {
-            if let Some(mut pre_range) = self.take_lowest_gap_or_unlogged() {
-                loop {
-                    if let Some(next_range) = self.get_lowest_gap_or_unlogged() {
-                        if next_range.start.eq(&pre_range.end) {
-                            pre_range.end = next_range.end;
-                            let _ = self.take_lowest_gap_or_unlogged();
-                        } else {
-                            return Some(pre_range);
+            self.take_uncomplete(true, false)
+        }
+        /// Takes the next range to sync according to the sync strategy, which is either an uncomplete range or a gap
+        /// only. The ranges are clipped to the highest milestone index, the milestones above it are not synced yet.
+        /// The `turn` is the number of ranges taken so far, which interleaves the highest and lowest ranges.
+        pub fn take_next(
+            &mut self,
+            strategy: &SyncStrategy,
+            with_unlogged: bool,
+            highest: u32,
+            turn: u64,
+        ) -> Option<Range<u32>> {
+            self.clip(highest);
+            match *strategy {
+                SyncStrategy::LowestFirst => self.take_uncomplete(with_unlogged, false),
+                SyncStrategy::HighestFirst { chunk_size } => self.take_chunk(with_unlogged, true, chunk_size),
+                SyncStrategy::RecentWindowFirst { window } => {
+                    let window_start = highest.saturating_sub(window);
+                    match self.take_uncomplete(with_unlogged, true) {
+                        Some(range) if range.end > window_start => {
+                            if range.start < window_start {
+                                self.put_back(range.start..window_start);
+                                Some(window_start..range.end)
+                            } else {
+                                Some(range)
+                            }
                         }
-                    } else {
-                        return Some(pre_range);
+                        Some(range) => {
+                            self.put_back(range);
+                            self.take_uncomplete(with_unlogged, false)
+                        }
+                        None => None,
                     }
                 }
+                SyncStrategy::WeightedInterleave {
+                    recent,
+                    old,
+                    chunk_size,
+                } => {
+                    let highest_first = turn % (recent as u64 + old as u64) < recent as u64;
+                    self.take_chunk(with_unlogged, highest_first, chunk_size)
+                }
+            }
+        }
+        /// Drop the ranges above the highest milestone index
+        fn clip(&mut self, highest: u32) {
+            for ranges in [&mut self.gaps, &mut self.synced_but_unlogged] {
+                ranges.retain(|range| range.start < highest);
+                ranges.iter_mut().for_each(|range| range.end = range.end.min(highest));
+            }
+        }
+        /// Takes a chunk of at most `chunk_size` milestones from the highest or lowest uncomplete range
+        fn take_chunk(&mut self, with_unlogged: bool, highest_first: bool, chunk_size: u32) -> Option<Range<u32>> {
+            let range = self.take_uncomplete(with_unlogged, highest_first)?;
+            if range.len() as u32 <= chunk_size {
+                Some(range)
+            } else if highest_first {
+                let chunk_start = range.end - chunk_size;
+                self.put_back(range.start..chunk_start);
+                Some(chunk_start..range.end)
             } else {
-                None
+                let chunk_end = range.start + chunk_size;
+                self.put_back(chunk_end..range.end);
+                Some(range.start..chunk_end)
             }
         }
-        fn get_lowest_gap_or_unlogged(&self) -> Option<&Range<u32>> {
-            let lowest_gap = self.gaps.last();
-            let lowest_unlogged = self.synced_but_unlogged.last();
-            match (lowest_gap, lowest_unlogged) {
-                (Some(gap), Some(unlogged)) => {
-                    if gap.start < unlogged.start {
-                        self.gaps.last()
-                    } else {
-                        self.synced_but_unlogged.last()
-                    }
+        /// Put back the remaining part of a taken range as a gap, which may have merged unlogged ranges but is synced
+        /// the same way
+        fn put_back(&mut self, range: Range<u32>) {
+            // the gaps are ordered by descending milestone index
+            let position = self
+                .gaps
+                .iter()
+                .position(|gap| gap.start < range.start)
+                .unwrap_or(self.gaps.len());
+            self.gaps.insert(position, range);
+        }
+        /// Takes the highest or lowest gap, merged with the adjacent unlogged ranges if `with_unlogged` is set
+        fn take_uncomplete(&mut self, with_unlogged: bool, highest_first: bool) -> Option<Range<u32>> {
+            let mut range = self.take_gap_or_unlogged(with_unlogged, highest_first)?;
+            while let Some(next_range) = self.get_gap_or_unlogged(with_unlogged, highest_first) {
+                if highest_first && next_range.end == range.start {
+                    range.start = next_range.start;
+                } else if !highest_first && next_range.start == range.end {
+                    range.end = next_range.end;
+                } else {
+                    break;
                 }
-                (Some(_), None) => self.gaps.last(),
-                (None, Some(_)) => self.synced_but_unlogged.last(),
+                self.take_gap_or_unlogged(with_unlogged, highest_first);
+            }
+            Some(range)
+        }
+        fn take_gap_or_unlogged(&mut self, with_unlogged: bool, highest_first: bool) -> Option<Range<u32>> {
+            let from_gaps = self.gap_or_unlogged(with_unlogged, highest_first)?;
+            let ranges = if from_gaps {
+                &mut self.gaps
+            } else {
+                &mut self.synced_but_unlogged
+            };
+            if highest_first {
+                Some(ranges.remove(0))
+            } else {
+                ranges.pop()
+            }
+        }
+        fn get_gap_or_unlogged(&self, with_unlogged: bool, highest_first: bool) -> Option<&Range<u32>> {
+            let from_gaps = self.gap_or_unlogged(with_unlogged, highest_first)?;
+            let ranges = if from_gaps {
+                &self.gaps
+            } else {
+                &self.synced_but_unlogged
+            };
+            if highest_first {
+                ranges.first()
+            } else {
+                ranges.last()
+            }
+        }
+        /// Whether the highest or lowest range is a gap or an unlogged range, if any
+        fn gap_or_unlogged(&self, with_unlogged: bool, highest_first: bool) -> Option<bool> {
+            let (gap, unlogged) = if highest_first {
+                (self.gaps.first(), self.synced_but_unlogged.first())
+            } else {
+                (self.gaps.last(), self.synced_but_unlogged.last())
+            };
+            match (gap, unlogged.filter(|_| with_unlogged)) {
+                (Some(gap), Some(unlogged)) => Some((gap.start > unlogged.start) == highest_first),
+                (Some(_), None) => Some(true),
+                (None, Some(_)) => Some(false),
                 _ => None,
             }
         }
@@ -516,6 +1400,219 @@ mod sync {
             };
         }
     }
+
+    /// The number of the most recent gap fills used to estimate the fill rate
+    const FILL_RATE_WINDOW: usize = 1000;
+    /// The number of milestones whose failed attempts are recorded, the lowest ones are pruned beyond it
+    const MAX_ATTEMPTED_MILESTONES: usize = 10000;
+
+    lazy_static::lazy_static! {
+        /// The sync status of the keyspaces, shared by the actors of this process
+        static ref SYNC_STATUS: std::sync::Mutex<HashMap<String, SyncStatus>> = Default::default();
+    }
+
+    /// The failed attempts to request the data of a milestone
+    #[derive(Debug, Default)]
+    struct MilestoneAttempts {
+        failures: u32,
+        unreachable: u32,
+        /// The error of the most recent failed request and its sequence number
+        last_error: Option<(u64, String)>,
+        endpoints: Vec<Url>,
+    }
+
+    #[derive(Debug, Default)]
+    struct SyncAttempts {
+        milestones: BTreeMap<u32, MilestoneAttempts>,
+        failures: u64,
+        fills: VecDeque<Instant>,
+    }
+
+    impl SyncAttempts {
+        /// Forget the lowest milestones beyond the recorded limit
+        fn prune(&mut self) {
+            while self.milestones.len() > MAX_ATTEMPTED_MILESTONES {
+                let lowest = *self.milestones.keys().next().unwrap();
+                self.milestones.remove(&lowest);
+            }
+        }
+    }
+
+    /// The in-memory sync status of a keyspace, it records why the milestones are not synced yet
+    #[derive(Debug, Clone, Default)]
+    pub struct SyncStatus {
+        attempts: Arc<std::sync::Mutex<SyncAttempts>>,
+    }
+
+    impl SyncStatus {
+        /// Get the shared sync status of a keyspace
+        pub fn of(keyspace: &str) -> Self {
+            SYNC_STATUS
+                .lock()
+                .unwrap()
+                .entry(keyspace.to_owned())
+                .or_default()
+                .clone()
+        }
+        /// Record a failed request for the data of a milestone
+        pub fn failed(&self, milestone_index: u32, endpoint: &Url, error: impl std::fmt::Display) {
+            let mut attempts = self.attempts.lock().unwrap();
+            attempts.failures += 1;
+            let seq = attempts.failures;
+            let milestone = attempts.milestones.entry(milestone_index).or_default();
+            milestone.failures += 1;
+            milestone.last_error = Some((seq, format!("{}: {}", endpoint, error)));
+            if !milestone.endpoints.contains(endpoint) {
+                milestone.endpoints.push(endpoint.clone());
+            }
+            attempts.prune();
+        }
+        /// Record that the syncer gave up on a milestone
+        pub fn unreachable(&self, milestone_index: u32) {
+            let mut attempts = self.attempts.lock().unwrap();
+            attempts.milestones.entry(milestone_index).or_default().unreachable += 1;
+            attempts.prune();
+        }
+        /// Record that a milestone got synced
+        pub fn synced(&self, milestone_index: u32) {
+            self.attempts.lock().unwrap().milestones.remove(&milestone_index);
+        }
+        /// Record that the syncer filled a milestone of a gap
+        pub fn filled(&self, milestone_index: u32) {
+            self.filled_at(milestone_index, Instant::now());
+        }
+        /// Record that the syncer filled a milestone of a gap at the given instant
+        pub fn filled_at(&self, milestone_index: u32, instant: Instant) {
+            let mut attempts = self.attempts.lock().unwrap();
+            attempts.milestones.remove(&milestone_index);
+            if attempts.fills.len() == FILL_RATE_WINDOW {
+                attempts.fills.pop_front();
+            }
+            attempts.fills.push_back(instant);
+        }
+        /// The milestones per second recently filled by the syncer
+        fn fill_rate(&self) -> Option<f64> {
+            let attempts = self.attempts.lock().unwrap();
+            let (first, last) = (attempts.fills.front()?, attempts.fills.back()?);
+            let elapsed = last.duration_since(*first).as_secs_f64();
+            // a stalled syncer does not fill anything
+            let idle = last.elapsed().as_secs_f64();
+            if elapsed > 0.0 && idle < elapsed.max(60.0) {
+                Some((attempts.fills.len() - 1) as f64 / elapsed)
+            } else {
+                None
+            }
+        }
+        /// Summarize the sync data of the keyspace with the recorded attempts
+        pub fn report(&self, keyspace: &str, sync_data: &SyncData) -> SyncReport {
+            let count = |ranges: &Vec<Range<u32>>| ranges.iter().map(|r| (r.end - r.start) as u64).sum::<u64>();
+            // the gap above the most recent synced milestone is not missing yet
+            let upper = sync_data
+                .completed
+                .iter()
+                .chain(sync_data.synced_but_unlogged.iter())
+                .map(|r| r.end)
+                .max()
+                .unwrap_or_default();
+            let gaps: Vec<Range<u32>> = sync_data
+                .gaps
+                .iter()
+                .filter(|gap| gap.start < upper)
+                .map(|gap| gap.start..gap.end.min(upper))
+                .collect();
+            let logged = count(&sync_data.completed);
+            let synced = logged + count(&sync_data.synced_but_unlogged);
+            let missing = count(&gaps);
+            let percentage = if synced + missing > 0 {
+                synced as f64 * 100.0 / (synced + missing) as f64
+            } else {
+                0.0
+            };
+            let fill_rate = self.fill_rate();
+            let eta_secs = fill_rate.map(|rate| (missing as f64 / rate).ceil() as u64);
+            let mut attempts = self.attempts.lock().unwrap();
+            // the milestones synced by other processes are neither missing nor being synced anymore
+            attempts.milestones.retain(|milestone_index, _| {
+                *milestone_index >= upper || gaps.iter().any(|gap| gap.contains(milestone_index))
+            });
+            let gaps = gaps
+                .into_iter()
+                .rev()
+                .map(|range| {
+                    let mut gap = GapReport {
+                        milestones: range.end - range.start,
+                        range: range.clone(),
+                        ..Default::default()
+                    };
+                    let mut last_error: Option<&(u64, String)> = None;
+                    for milestone in attempts.milestones.range(range).map(|(_, m)| m) {
+                        gap.failures += milestone.failures;
+                        gap.unreachable += milestone.unreachable;
+                        if let Some(error) = milestone.last_error.as_ref() {
+                            if last_error.map_or(true, |last| last.0 < error.0) {
+                                last_error.replace(error);
+                            }
+                        }
+                        for endpoint in milestone.endpoints.iter() {
+                            if !gap.endpoints.contains(endpoint) {
+                                gap.endpoints.push(endpoint.clone());
+                            }
+                        }
+                    }
+                    gap.last_error = last_error.map(|(_, error)| error.clone());
+                    gap
+                })
+                .collect();
+            SyncReport {
+                keyspace: keyspace.to_owned(),
+                logged,
+                synced,
+                missing,
+                percentage,
+                fill_rate,
+                eta_secs,
+                gaps,
+            }
+        }
+    }
+
+    /// The sync report of a keyspace
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct SyncReport {
+        /// The keyspace of the report
+        pub keyspace: String,
+        /// The number of the synced and logged milestones
+        pub logged: u64,
+        /// The number of the synced milestones
+        pub synced: u64,
+        /// The number of the missing milestones below the most recent synced one
+        pub missing: u64,
+        /// The percentage of the synced milestones
+        pub percentage: f64,
+        /// The milestones per second recently filled by the syncer
+        pub fill_rate: Option<f64>,
+        /// The estimated seconds to fill the gaps at the recent fill rate
+        pub eta_secs: Option<u64>,
+        /// The gaps from the lowest to the highest
+        pub gaps: Vec<GapReport>,
+    }
+
+    /// The sync report of a gap
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    pub struct GapReport {
+        /// The milestone range of the gap
+        pub range: Range<u32>,
+        /// The number of the missing milestones
+        pub milestones: u32,
+        /// The number of the failed requests for the milestones of the gap
+        pub failures: u32,
+        /// The number of times the syncer gave up on a milestone of the gap
+        pub unreachable: u32,
+        /// The error of the most recent failed request, if any was recorded by this process
+        pub last_error: Option<String>,
+        /// The requester endpoints which were tried
+        pub endpoints: Vec<Url>,
+    }
 }
 
 #[cfg(feature = "analytic")]
@@ -524,6 +1621,12 @@ pub use analytic::*;
 mod analytic {
     use super::*;
     use chronicle_common::SyncRange;
+    use chronicle_storage::access::{
+        AddressSketch,
+        AnalyticRollup,
+        RollupInterval,
+        RollupRange,
+    };
     use scylla_rs::prelude::{
         Consistency,
         GetSelectRequest,
@@ -549,6 +1652,38 @@ mod analytic {
         message_count: u128,
         transaction_count: u128,
         transferred_tokens: u128,
+        /// The accumulated metrics, which are missing from the ranges analyzed before they were introduced
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        metrics: Option<AnalyticMetricsData>,
+    }
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    /// The accumulated analytic metrics of a continuous range
+    pub struct AnalyticMetricsData {
+        /// The lowest metrics version within the range
+        version: u8,
+        /// The distinct active addresses within the range, as estimated by their merged sketches
+        active_addresses: u128,
+        /// The distinct new addresses within the range, as estimated by their merged sketches
+        new_addresses: u128,
+        dust_allowance_outputs: u128,
+        dust_allowance_tokens: u128,
+        indexation_payloads: u128,
+        transaction_payloads: u128,
+        milestone_payloads: u128,
+        receipt_payloads: u128,
+        no_payload: u128,
+        conflicting_transactions: u128,
+        /// The average number of parents per message
+        average_parents: f64,
+        #[serde(skip)]
+        parents: u128,
+        /// The most used indexation tags among the top tags of each milestone
+        top_indexation_tags: Vec<(String, u128)>,
+        #[serde(skip)]
+        active_address_sketch: AddressSketch,
+        #[serde(skip)]
+        new_address_sketch: AddressSketch,
     }
     impl From<AnalyticRecord> for AnalyticData {
         fn from(record: AnalyticRecord) -> Self {
@@ -561,7 +1696,16 @@ mod analytic {
                 start: milestone_index,
                 end: milestone_index + 1,
             };
-            AnalyticData::new(range, message_count, transaction_count, transferred_tokens)
+            let mut analytic_data = AnalyticData::new(range, message_count, transaction_count, transferred_tokens);
+            if let Some(metrics) = record.metrics {
+                let mut metrics_data = AnalyticMetricsData {
+                    version: metrics.version,
+                    ..Default::default()
+                };
+                metrics_data.acc(metrics, message_count);
+                analytic_data.metrics.replace(metrics_data);
+            }
+            analytic_data
         }
     }
     impl AnalyticData {
@@ -576,6 +1720,7 @@ mod analytic {
                 message_count,
                 transaction_count,
                 transferred_tokens,
+                metrics: None,
             }
         }
         async fn process(mut self, analytics_data: &mut AnalyticsData, records: &mut Iter<AnalyticRecord>) {
@@ -585,7 +1730,8 @@ mod analytic {
             analytics_data.add_analytic_data(self);
         }
         fn process_record(mut self, record: AnalyticRecord, analytics_data: &mut AnalyticsData) -> Self {
-            if self.start() - 1 == **record.milestone_index() {
+            // the ranges are split where the metrics start or stop being available
+            if self.start() - 1 == **record.milestone_index() && self.metrics.is_some() == record.metrics().is_some() {
                 self.acc(record);
             } else {
                 // there is gap, therefore we finish self
@@ -603,6 +1749,40 @@ mod analytic {
             self.message_count += **record.message_count() as u128;
             self.transaction_count += **record.transaction_count() as u128;
             self.transferred_tokens += **record.transferred_tokens() as u128;
+            if let (Some(metrics_data), Some(metrics)) = (self.metrics.as_mut(), record.metrics) {
+                metrics_data.acc(metrics, self.message_count);
+            }
+        }
+    }
+
+    impl AnalyticMetricsData {
+        /// Accumulate the metrics of a milestone, given the message count of the whole range
+        fn acc(&mut self, metrics: AnalyticMetrics, message_count: u128) {
+            self.version = self.version.min(metrics.version);
+            self.active_address_sketch.merge(&metrics.active_address_sketch);
+            self.new_address_sketch.merge(&metrics.new_address_sketch);
+            self.active_addresses = self.active_address_sketch.estimate() as u128;
+            self.new_addresses = self.new_address_sketch.estimate() as u128;
+            self.dust_allowance_outputs += metrics.dust_allowance_outputs as u128;
+            self.dust_allowance_tokens += metrics.dust_allowance_tokens as u128;
+            self.indexation_payloads += metrics.indexation_payloads as u128;
+            self.transaction_payloads += metrics.transaction_payloads as u128;
+            self.milestone_payloads += metrics.milestone_payloads as u128;
+            self.receipt_payloads += metrics.receipt_payloads as u128;
+            self.no_payload += metrics.no_payload as u128;
+            self.conflicting_transactions += metrics.conflicting_transactions as u128;
+            self.parents += metrics.parents as u128;
+            if message_count > 0 {
+                self.average_parents = self.parents as f64 / message_count as f64;
+            }
+            let mut tags = self.top_indexation_tags.drain(..).collect::<HashMap<_, _>>();
+            for (tag, count) in metrics.top_indexation_tags {
+                *tags.entry(tag).or_default() += count as u128;
+            }
+            let mut tags = tags.into_iter().collect::<Vec<_>>();
+            tags.sort_by(|(a_tag, a_count), (b_tag, b_count)| b_count.cmp(a_count).then_with(|| a_tag.cmp(b_tag)));
+            tags.truncate(TOP_INDEXATION_TAGS);
+            self.top_indexation_tags = tags;
         }
     }
 
@@ -685,4 +1865,70 @@ mod analytic {
             self.analytics.push(analytic_data);
         }
     }
+
+    /// The analytics rollups of an interval within a time range, one per bucket
+    #[derive(Debug, Clone, Serialize)]
+    pub struct AnalyticsTimeseries {
+        /// The interval of the buckets
+        pub interval: RollupInterval,
+        /// The rollups by ascending bucket, the buckets without analyzed milestones are empty
+        pub series: Vec<AnalyticRollup>,
+    }
+
+    impl AnalyticsTimeseries {
+        /// Try to fetch the rollups of the provided range from the analytics rollups table
+        pub async fn try_fetch<S: 'static + Select<RollupRange, Iter<AnalyticRollup>>>(
+            keyspace: &S,
+            range: RollupRange,
+            retries: usize,
+            page_size: i32,
+        ) -> anyhow::Result<AnalyticsTimeseries> {
+            let mut rollups = Vec::new();
+            let mut paging_state = None;
+            loop {
+                let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+                let req = keyspace
+                    .select(&range)
+                    .consistency(Consistency::One)
+                    .page_size(page_size)
+                    .paging_state(&paging_state)
+                    .build()?;
+                let worker = ValueWorker::new(tx, keyspace.clone(), range, retries, std::marker::PhantomData)
+                    .with_paging(page_size, paging_state);
+                req.send_local(Box::new(worker));
+                match rx
+                    .recv()
+                    .await
+                    .ok_or_else(|| anyhow::anyhow!("Unable to fetch the analytics rollups response"))??
+                {
+                    Some(mut page) => {
+                        paging_state = page.take_paging_state();
+                        rollups.extend(&mut page);
+                        if paging_state.is_none() {
+                            break;
+                        }
+                    }
+                    None => break,
+                }
+            }
+            Ok(Self::fill(range, rollups))
+        }
+        /// Fill the buckets of the range which have no rollup with empty ones, given the rollups by ascending bucket
+        pub fn fill(range: RollupRange, rollups: Vec<AnalyticRollup>) -> Self {
+            let mut rollups = rollups.into_iter().peekable();
+            let mut series = Vec::new();
+            let mut bucket = range.interval.bucket(range.from);
+            while bucket < range.to {
+                match rollups.next_if(|rollup| rollup.bucket == bucket) {
+                    Some(rollup) => series.push(rollup),
+                    None => series.push(AnalyticRollup::new(bucket)),
+                }
+                bucket += range.interval.secs();
+            }
+            Self {
+                interval: range.interval,
+                series,
+            }
+        }
+    }
 }
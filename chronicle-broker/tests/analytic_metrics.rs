@@ -0,0 +1,229 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+mod common;
+
+use bee_message::prelude::*;
+use bee_pow::providers::miner::Miner;
+use chronicle_broker::{
+    indexer::lookup_address_history,
+    CreatedBy,
+    MilestoneData,
+};
+use chronicle_storage::access::{
+    AddressSketch,
+    FullMessage,
+    LedgerInclusionState,
+    ANALYTIC_METRICS_VERSION,
+};
+use common::{
+    metadata,
+    parents,
+};
+use crypto::hashes::{
+    blake2b::Blake2b256,
+    Digest,
+};
+use std::collections::BTreeSet;
+
+const MILESTONE_INDEX: u32 = 10;
+
+fn address(byte: u8) -> Address {
+    Address::Ed25519(Ed25519Address::new([byte; 32]))
+}
+
+/// The address of the signer of a transaction
+fn sender(public_key: [u8; 32]) -> Address {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&Blake2b256::digest(&public_key));
+    Address::Ed25519(Ed25519Address::new(hash))
+}
+
+fn indexation(index: &[u8]) -> Payload {
+    Payload::Indexation(Box::new(IndexationPayload::new(index, &[]).unwrap()))
+}
+
+/// A transaction signed by the given public key, with a single output
+fn transaction(public_key: [u8; 32], output: Output) -> Payload {
+    let essence = RegularEssenceBuilder::new()
+        .with_inputs(vec![UtxoInput::new(TransactionId::new([3; 32]), 1).unwrap().into()])
+        .with_outputs(vec![output])
+        .finish()
+        .unwrap();
+    let unlock_block = UnlockBlock::Signature(SignatureUnlock::Ed25519(Ed25519Signature::new(
+        public_key,
+        Box::new([6; 64]),
+    )));
+    Payload::Transaction(Box::new(
+        TransactionPayloadBuilder::new()
+            .with_essence(Essence::Regular(essence))
+            .with_unlock_blocks(UnlockBlocks::new(vec![unlock_block]).unwrap())
+            .finish()
+            .unwrap(),
+    ))
+}
+
+fn single(address: Address) -> Output {
+    SignatureLockedSingleOutput::new(address, 1_000_000).unwrap().into()
+}
+
+fn milestone_payload() -> Box<MilestonePayload> {
+    let essence = MilestonePayloadEssence::new(
+        MilestoneIndex(MILESTONE_INDEX),
+        0,
+        parents(),
+        [0; 32],
+        0,
+        0,
+        vec![[7; 32]],
+        None,
+    )
+    .unwrap();
+    Box::new(MilestonePayload::new(essence, vec![Box::new([8; 64])]).unwrap())
+}
+
+fn message(payload: Option<Payload>) -> Message {
+    let mut builder = MessageBuilder::<Miner>::new()
+        .with_network_id(0)
+        .with_parents(parents());
+    if let Some(payload) = payload {
+        builder = builder.with_payload(payload);
+    }
+    builder.finish().unwrap()
+}
+
+fn milestone_data() -> MilestoneData {
+    let mut milestone_data = MilestoneData::new(MILESTONE_INDEX, CreatedBy::Syncer);
+    let dust_allowance = SignatureLockedDustAllowanceOutput::new(address(8), 1_000_000)
+        .unwrap()
+        .into();
+    let messages = vec![
+        (
+            Some(Payload::Milestone(milestone_payload())),
+            LedgerInclusionState::NoTransaction,
+        ),
+        (Some(indexation(&[0xab])), LedgerInclusionState::NoTransaction),
+        (Some(indexation(&[0xab])), LedgerInclusionState::NoTransaction),
+        (Some(indexation(&[0xcd])), LedgerInclusionState::NoTransaction),
+        (
+            Some(transaction([5; 32], single(address(7)))),
+            LedgerInclusionState::Included,
+        ),
+        (
+            Some(transaction([6; 32], dust_allowance)),
+            LedgerInclusionState::Included,
+        ),
+        // the sender of a conflicting transaction isn't active
+        (
+            Some(transaction([9; 32], single(address(9)))),
+            LedgerInclusionState::Conflicting,
+        ),
+        // the receiver signs the dust allowance transaction, so it had outputs before
+        (
+            Some(transaction([5; 32], single(sender([6; 32])))),
+            LedgerInclusionState::Included,
+        ),
+        (None, LedgerInclusionState::NoTransaction),
+    ];
+    for (id, (payload, ledger_inclusion_state)) in messages.into_iter().enumerate() {
+        let message_id = MessageId::new([id as u8 + 11; 32]);
+        let metadata = metadata(message_id, MILESTONE_INDEX, ledger_inclusion_state);
+        milestone_data.add_full_message(FullMessage::new(message(payload), metadata));
+    }
+    milestone_data.set_milestone(milestone_payload());
+    milestone_data
+}
+
+/// The milestone data along with its address history, where the signer of the dust allowance transaction received
+/// outputs before
+fn analyzable_milestone_data() -> MilestoneData {
+    let mut milestone_data = milestone_data();
+    milestone_data.set_known_addresses(vec![ed25519(sender([6; 32]))]);
+    milestone_data
+}
+
+fn ed25519(address: Address) -> Ed25519Address {
+    let Address::Ed25519(address) = address;
+    address
+}
+
+fn bytes(address: Address) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(ed25519(address).as_ref());
+    bytes
+}
+
+#[test]
+fn test_analytic_metrics() {
+    let analytic_record = analyzable_milestone_data().get_analytic_record().unwrap();
+    assert_eq!(**analytic_record.message_count(), 9);
+    assert_eq!(**analytic_record.transaction_count(), 3);
+    assert_eq!(**analytic_record.transferred_tokens(), 3_000_000);
+    let metrics = analytic_record.metrics().unwrap();
+    assert_eq!(metrics.version, ANALYTIC_METRICS_VERSION);
+    assert_eq!(metrics.indexation_payloads, 3);
+    assert_eq!(metrics.transaction_payloads, 4);
+    assert_eq!(metrics.milestone_payloads, 1);
+    assert_eq!(metrics.receipt_payloads, 0);
+    assert_eq!(metrics.no_payload, 1);
+    assert_eq!(metrics.conflicting_transactions, 1);
+    assert_eq!(metrics.dust_allowance_outputs, 1);
+    assert_eq!(metrics.dust_allowance_tokens, 1_000_000);
+    assert_eq!(metrics.parents, 18);
+    assert_eq!(
+        metrics.top_indexation_tags,
+        vec![("ab".to_owned(), 2), ("cd".to_owned(), 1)]
+    );
+}
+
+#[test]
+fn test_active_and_new_addresses() {
+    let analytic_record = analyzable_milestone_data().get_analytic_record().unwrap();
+    let metrics = analytic_record.metrics().unwrap();
+    // the senders and the receivers of the included transactions
+    let active = vec![address(7), address(8), sender([5; 32]), sender([6; 32])]
+        .into_iter()
+        .map(bytes)
+        .collect::<BTreeSet<_>>();
+    assert_eq!(metrics.active_addresses, 4);
+    assert_eq!(metrics.active_address_sketch, active.iter().collect::<AddressSketch>());
+    // the receivers without outputs before the milestone
+    let new = vec![address(7), address(8)]
+        .into_iter()
+        .map(bytes)
+        .collect::<BTreeSet<_>>();
+    assert_eq!(metrics.new_addresses, 2);
+    assert_eq!(metrics.new_address_sketch, new.iter().collect::<AddressSketch>());
+}
+
+#[tokio::test]
+async fn test_lookup_address_history() {
+    let mut milestone_data = milestone_data();
+    // the receivers of the included transactions only
+    let mut receivers = vec![ed25519(address(7)), ed25519(address(8)), ed25519(sender([6; 32]))];
+    receivers.sort_by_key(|address| bytes(Address::Ed25519(*address)));
+    assert_eq!(milestone_data.receivers(), receivers);
+    // an address which received before, even within the same milestone as it spends, isn't new
+    lookup_address_history(&mut milestone_data, 2, |receiver| async move {
+        Ok::<_, anyhow::Error>(receiver == ed25519(address(7)))
+    })
+    .await
+    .unwrap();
+    let metrics = milestone_data.get_analytic_record().unwrap().metrics.unwrap();
+    assert_eq!(metrics.new_addresses, 2);
+    assert_eq!(
+        metrics.new_address_sketch,
+        [bytes(address(8)), bytes(sender([6; 32]))]
+            .iter()
+            .collect::<AddressSketch>()
+    );
+    // a failed lookup fails the analytics
+    let mut milestone_data = milestone_data();
+    let lookup = lookup_address_history(&mut milestone_data, 2, |_| async {
+        Err::<bool, _>(anyhow::anyhow!("unreachable"))
+    });
+    assert!(lookup.await.is_err());
+    assert!(milestone_data.get_analytic_record().is_err());
+}
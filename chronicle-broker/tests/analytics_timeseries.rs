@@ -0,0 +1,61 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "analytic")]
+
+use chronicle_broker::AnalyticsTimeseries;
+use chronicle_storage::access::{
+    AnalyticRollup,
+    RollupInterval,
+    RollupRange,
+};
+
+const HOUR: u64 = 3600;
+
+fn rollup(bucket: u64, message_count: u64) -> AnalyticRollup {
+    AnalyticRollup {
+        bucket,
+        milestone_count: 1,
+        message_count,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_fill_timeseries() {
+    let range = RollupRange {
+        interval: RollupInterval::Hour,
+        from: 10 * HOUR,
+        to: 15 * HOUR,
+    };
+    let AnalyticsTimeseries { interval, series } =
+        AnalyticsTimeseries::fill(range, vec![rollup(11 * HOUR, 5), rollup(13 * HOUR, 7)]);
+    assert_eq!(interval, RollupInterval::Hour);
+    // every bucket of the range is returned, the ones without rollup are empty
+    assert_eq!(
+        series,
+        vec![
+            AnalyticRollup::new(10 * HOUR),
+            rollup(11 * HOUR, 5),
+            AnalyticRollup::new(12 * HOUR),
+            rollup(13 * HOUR, 7),
+            AnalyticRollup::new(14 * HOUR),
+        ]
+    );
+
+    // the range starts at the bucket of its first timestamp, and ends before its end
+    let range = RollupRange {
+        interval: RollupInterval::Day,
+        from: 86400 + 100,
+        to: 3 * 86400,
+    };
+    let AnalyticsTimeseries { series, .. } = AnalyticsTimeseries::fill(range, vec![rollup(2 * 86400, 3)]);
+    assert_eq!(series, vec![AnalyticRollup::new(86400), rollup(2 * 86400, 3)]);
+
+    let range = RollupRange {
+        interval: RollupInterval::Day,
+        from: 86400,
+        to: 86400,
+    };
+    assert!(AnalyticsTimeseries::fill(range, Vec::new()).series.is_empty());
+}
@@ -0,0 +1,260 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use chronicle_broker::{
+    archive::{
+        index_log_files,
+        manifest::{
+            file_checksum,
+            Manifest,
+            ManifestOp,
+            GENESIS_HASH,
+        },
+        ArchiveFormat,
+        ArchiveReader,
+        ArchiveRecord,
+        BlockIndex,
+        LogEncoder,
+        RecordDecoder,
+        ZSTD_LOG_HEADER_LEN,
+    },
+    merge::LogPaths,
+    CreatedBy,
+    MilestoneData,
+};
+use tokio::io::BufReader;
+
+#[tokio::test]
+async fn test_zstd_log_round_trip() {
+    let mut encoder = LogEncoder::new(ArchiveFormat::Zstd, 3, 4);
+    let mut bytes = Vec::new();
+    for milestone_index in 10..20 {
+        let record = ArchiveRecord::new(
+            &MilestoneData::new(milestone_index, CreatedBy::Incoming),
+            ArchiveFormat::Zstd,
+        )
+        .unwrap();
+        bytes.extend(encoder.encode(&record).unwrap());
+    }
+    bytes.extend(encoder.finish().unwrap());
+    assert_eq!(encoder.encoded_len(), bytes.len() as u64);
+    assert_eq!(
+        ArchiveFormat::detect(&mut std::io::Cursor::new(&bytes)).unwrap(),
+        ArchiveFormat::Zstd
+    );
+
+    let mut reader = BufReader::new(&bytes[..]);
+    let mut decoder = RecordDecoder::new(ArchiveFormat::Zstd);
+    let mut milestone_index = 10;
+    while let Some((record, _)) = decoder.next_record(&mut reader).await.unwrap() {
+        let milestone_data = record.milestone_data().unwrap();
+        assert_eq!(milestone_data.milestone_index(), milestone_index);
+        assert_eq!(record.milestone_index().unwrap(), milestone_index);
+        milestone_index += 1;
+    }
+    assert_eq!(milestone_index, 20);
+
+    let (index, blocks_end) = BlockIndex::load(&mut std::io::Cursor::new(&bytes)).await.unwrap();
+    assert_eq!(index.entries().len(), 10);
+    assert_eq!(index.block_offset(10), Some(ZSTD_LOG_HEADER_LEN));
+    assert_eq!(index.block_offset(13), index.block_offset(10));
+    assert!(index.block_offset(14) > index.block_offset(13));
+    assert_eq!(index.block_offset(20), None);
+    assert!(blocks_end < bytes.len() as u64);
+}
+
+#[tokio::test]
+async fn test_unfinished_zstd_log_index() {
+    // an unfinished log file has no footer, so its index is rebuilt from its blocks
+    let mut encoder = LogEncoder::new(ArchiveFormat::Zstd, 3, 2);
+    let mut bytes = Vec::new();
+    for milestone_index in [10, 12, 15, 16, 20] {
+        let record = ArchiveRecord::new(
+            &MilestoneData::new(milestone_index, CreatedBy::Incoming),
+            ArchiveFormat::Zstd,
+        )
+        .unwrap();
+        bytes.extend(encoder.encode(&record).unwrap());
+    }
+    let (index, blocks_end) = BlockIndex::load(&mut std::io::Cursor::new(&bytes)).await.unwrap();
+    // the milestones of a block are not assumed to be consecutive
+    let milestone_indexes = index.entries().iter().map(|(milestone_index, _)| *milestone_index);
+    assert_eq!(milestone_indexes.collect::<Vec<_>>(), vec![10, 12, 15, 16]);
+    assert_eq!(index.block_offset(11), None);
+    assert_eq!(blocks_end, bytes.len() as u64);
+    // a partially written trailing block is left out
+    let mut truncated = bytes.clone();
+    truncated.extend(&encoder.finish().unwrap()[..10]);
+    let (index, blocks_end) = BlockIndex::load(&mut std::io::Cursor::new(&truncated)).await.unwrap();
+    assert_eq!(index.entries().len(), 4);
+    assert_eq!(blocks_end, bytes.len() as u64);
+    // a corrupt block header is an error rather than an overflowing range
+    let header = ZSTD_LOG_HEADER_LEN as usize;
+    let mut corrupt = bytes.clone();
+    corrupt[header + 1..header + 9].copy_from_slice(&[0xff; 8]);
+    assert!(BlockIndex::load(&mut std::io::Cursor::new(&corrupt)).await.is_err());
+    // so is a corrupt block
+    let mut corrupt = bytes;
+    corrupt[header + 13] ^= 0xff;
+    assert!(BlockIndex::load(&mut std::io::Cursor::new(&corrupt)).await.is_err());
+}
+
+async fn write_log_file(logs_dir: &std::path::Path, format: ArchiveFormat, start: u32) {
+    let mut encoder = LogEncoder::new(format, 3, 4);
+    let mut bytes = Vec::new();
+    for milestone_index in start..start + 10 {
+        let record = ArchiveRecord::new(&MilestoneData::new(milestone_index, CreatedBy::Incoming), format).unwrap();
+        bytes.extend(encoder.encode(&record).unwrap());
+    }
+    bytes.extend(encoder.finish().unwrap());
+    let log_path = logs_dir.join(format!("{}to{}.log", start, start + 10));
+    tokio::fs::write(&log_path, bytes).await.unwrap();
+    Manifest::record(&log_path).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_archive_reader_get() {
+    let logs_dir = std::env::temp_dir().join(format!("chronicle-archive-reader-{}", std::process::id()));
+    tokio::fs::create_dir_all(&logs_dir).await.unwrap();
+    write_log_file(&logs_dir, ArchiveFormat::Json, 10).await;
+    write_log_file(&logs_dir, ArchiveFormat::Zstd, 20).await;
+    let reader = ArchiveReader::new(logs_dir.clone());
+    // the reads don't write into the logs directory
+    for milestone_index in [10, 15, 19, 20, 25, 29] {
+        let milestone_data = reader.get(milestone_index).await.unwrap().unwrap();
+        assert_eq!(milestone_data.milestone_index(), milestone_index);
+    }
+    assert!(!BlockIndex::sidecar_path(&logs_dir.join("10to20.log")).exists());
+    assert!(reader.get(30).await.unwrap().is_none());
+    // the sidecar indexes are written by the archiver
+    assert_eq!(index_log_files(&logs_dir).await.unwrap(), 2);
+    assert_eq!(index_log_files(&logs_dir).await.unwrap(), 0);
+    assert!(BlockIndex::sidecar_path(&logs_dir.join("10to20.log")).exists());
+    assert_eq!(reader.get(15).await.unwrap().unwrap().milestone_index(), 15);
+    // the reader loads the log files again once the manifest is modified
+    write_log_file(&logs_dir, ArchiveFormat::Zstd, 30).await;
+    assert_eq!(reader.get(35).await.unwrap().unwrap().milestone_index(), 35);
+    tokio::fs::remove_dir_all(&logs_dir).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_manifest_hash_chain() {
+    let logs_dir = std::env::temp_dir().join(format!("chronicle-archive-manifest-{}", std::process::id()));
+    tokio::fs::create_dir_all(&logs_dir).await.unwrap();
+    for (start, end) in [(20, 30), (0, 10), (10, 20)] {
+        let log_path = logs_dir.join(format!("{}to{}.log", start, end));
+        tokio::fs::write(&log_path, format!("{}\n", start)).await.unwrap();
+        Manifest::record(&log_path).await.unwrap();
+    }
+    let manifest = Manifest::load(&logs_dir).await.unwrap();
+    // the entries are appended in recording order, the files are sorted by range
+    let entries = manifest.entries();
+    assert_eq!(
+        entries.iter().map(|entry| entry.start).collect::<Vec<_>>(),
+        vec![20, 0, 10]
+    );
+    let files = manifest.files();
+    assert_eq!(
+        files.iter().map(|entry| entry.start).collect::<Vec<_>>(),
+        vec![0, 10, 20]
+    );
+    let mut prev_hash = GENESIS_HASH;
+    for entry in entries {
+        assert!(entry.is_linked_to(prev_hash));
+        prev_hash = entry.hash.as_str();
+    }
+    assert_eq!(manifest.head(), prev_hash);
+
+    // a merged file supersedes the overlapping files through a new entry, the earlier ones are left as is
+    let mut merged = manifest.clone();
+    assert!(merged.insert("0to20.log".to_owned(), 0..20, "merged".to_owned()));
+    assert!(!merged.insert("0to20.log".to_owned(), 0..20, "merged".to_owned()));
+    assert_eq!(&merged.entries()[..3], manifest.entries());
+    assert_eq!(merged.entries().len(), 4);
+    assert!(merged.entries()[3].is_linked_to(manifest.head()));
+    assert_eq!(
+        merged
+            .files()
+            .iter()
+            .map(|entry| entry.file_name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["0to20.log", "20to30.log"]
+    );
+
+    // the stored and the removed files are recorded by new entries too
+    assert!(merged.set_stored("0to20.log", "merged"));
+    assert!(!merged.set_stored("0to20.log", "merged"));
+    assert!(merged.is_stored("0to20.log"));
+    assert!(merged.remove("20to30.log"));
+    assert!(!merged.remove("20to30.log"));
+    assert_eq!(
+        merged.entries()[4..].iter().map(|entry| entry.op).collect::<Vec<_>>(),
+        vec![ManifestOp::Store, ManifestOp::Remove]
+    );
+    assert_eq!(&merged.entries()[..3], manifest.entries());
+    assert_eq!(merged.files().len(), 1);
+    assert!(merged.entries()[5].is_linked_to(&merged.entries()[4].hash));
+
+    // a modified file no longer matches its checksum
+    let log_path = logs_dir.join("10to20.log");
+    tokio::fs::write(&log_path, b"tampered\n").await.unwrap();
+    assert_ne!(file_checksum(&log_path).await.unwrap(), files[1].sha256);
+    tokio::fs::remove_dir_all(&logs_dir).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_manifest_concurrent_records() {
+    let logs_dir = std::env::temp_dir().join(format!("chronicle-archive-manifest-race-{}", std::process::id()));
+    tokio::fs::create_dir_all(&logs_dir).await.unwrap();
+    let mut tasks = Vec::new();
+    for start in (0..200).step_by(10) {
+        let log_path = logs_dir.join(format!("{}to{}.log", start, start + 10));
+        tokio::fs::write(&log_path, format!("{}\n", start)).await.unwrap();
+        tasks.push(tokio::spawn(async move { Manifest::record(&log_path).await.unwrap() }));
+    }
+    for task in tasks {
+        task.await.unwrap();
+    }
+    // none of the concurrent updates is lost
+    let manifest = Manifest::load(&logs_dir).await.unwrap();
+    assert_eq!(manifest.entries().len(), 20);
+    let paths = LogPaths::new(&logs_dir, true).unwrap();
+    paths.verify_manifest(&logs_dir, false).await.unwrap();
+
+    // a modified file fails the verification
+    tokio::fs::write(logs_dir.join("50to60.log"), b"tampered\n")
+        .await
+        .unwrap();
+    assert!(paths.verify_manifest(&logs_dir, false).await.is_err());
+    tokio::fs::write(logs_dir.join("50to60.log"), b"50\n").await.unwrap();
+    paths.verify_manifest(&logs_dir, false).await.unwrap();
+
+    // a removed file is recorded by a new entry, which still verifies
+    tokio::fs::remove_file(logs_dir.join("190to200.log")).await.unwrap();
+    Manifest::forget(&logs_dir.join("190to200.log")).await.unwrap();
+    let paths = LogPaths::new(&logs_dir, true).unwrap();
+    paths.verify_manifest(&logs_dir, false).await.unwrap();
+
+    // deleting or replacing an entry breaks the hash chain
+    let manifest = Manifest::load(&logs_dir).await.unwrap();
+    let json = serde_json::to_value(&manifest).unwrap();
+    let mut deleted = json.clone();
+    deleted["entries"].as_array_mut().unwrap().remove(5);
+    tokio::fs::write(Manifest::path(&logs_dir), deleted.to_string())
+        .await
+        .unwrap();
+    assert!(paths.verify_manifest(&logs_dir, false).await.is_err());
+    let mut replaced = json.clone();
+    replaced["entries"][5]["sha256"] = serde_json::Value::String("0".repeat(64));
+    tokio::fs::write(Manifest::path(&logs_dir), replaced.to_string())
+        .await
+        .unwrap();
+    assert!(paths.verify_manifest(&logs_dir, false).await.is_err());
+    manifest.save(&logs_dir).await.unwrap();
+
+    // an unlisted file fails the verification
+    tokio::fs::write(logs_dir.join("200to210.log"), b"200\n").await.unwrap();
+    let paths = LogPaths::new(&logs_dir, true).unwrap();
+    assert!(paths.verify_manifest(&logs_dir, false).await.is_err());
+    tokio::fs::remove_dir_all(&logs_dir).await.unwrap();
+}
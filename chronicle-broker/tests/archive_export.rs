@@ -0,0 +1,76 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+use chronicle_broker::{
+    archive::{
+        ArchiveFormat,
+        ArchiveReader,
+        ArchiveRecord,
+    },
+    exporter::ExportWriter,
+    merge::LogPaths,
+    CreatedBy,
+    MilestoneData,
+};
+
+fn milestone_data(milestone_index: u32) -> MilestoneData {
+    MilestoneData::new(milestone_index, CreatedBy::Exporter)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_export_and_validate() {
+    let dir_path = std::env::temp_dir().join(format!("chronicle-archive-export-{}", std::process::id()));
+    tokio::fs::create_dir_all(&dir_path).await.unwrap();
+    // a log file holds up to 4 records
+    let record_len = ArchiveRecord::new(&milestone_data(10), ArchiveFormat::Json)
+        .unwrap()
+        .encoded_len() as u64;
+    let max_log_size = record_len * 4;
+    let mut writer = ExportWriter::new(dir_path.clone(), max_log_size, None);
+    let mut finished = Vec::new();
+    for milestone_index in 10..20 {
+        finished.extend(writer.write(&milestone_data(milestone_index)).await.unwrap());
+    }
+    finished.extend(writer.finish().await.unwrap());
+    assert!(writer.finish().await.unwrap().is_none());
+    assert_eq!(
+        finished,
+        ["10to14.log", "14to18.log", "18to20.log"]
+            .iter()
+            .map(|file_name| dir_path.join(file_name))
+            .collect::<Vec<_>>()
+    );
+
+    // the exported log files are valid, recorded in the manifest and readable by the archive reader
+    LogPaths::new(&dir_path, false)
+        .unwrap()
+        .validate(max_log_size, false)
+        .await
+        .unwrap();
+    let paths = LogPaths::new(&dir_path, false).unwrap();
+    paths.verify_manifest(&dir_path, false).await.unwrap();
+    let reader = ArchiveReader::new(dir_path.clone());
+    for milestone_index in 10..20 {
+        let milestone_data = reader.get(milestone_index).await.unwrap().unwrap();
+        assert_eq!(milestone_data.milestone_index(), milestone_index);
+    }
+
+    // the milestones of a log file are contiguous
+    writer.write(&milestone_data(25)).await.unwrap();
+    assert!(writer.write(&milestone_data(27)).await.is_err());
+    assert_eq!(writer.finish().await.unwrap(), Some(dir_path.join("25to26.log")));
+    // the gap fails the validation, with or without a progress bar
+    let result = LogPaths::new(&dir_path, false)
+        .unwrap()
+        .validate(max_log_size, false)
+        .await;
+    assert!(result.is_err());
+    let result = LogPaths::new(&dir_path, false)
+        .unwrap()
+        .validate(max_log_size, true)
+        .await;
+    assert!(result.is_err());
+    tokio::fs::remove_dir_all(&dir_path).await.unwrap();
+}
@@ -0,0 +1,170 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+use chronicle_broker::{
+    archive::{
+        manifest::Manifest,
+        sink::ArchiveSink,
+        ArchiveFormat,
+        ArchiveReader,
+        ArchiveRecord,
+        LogEncoder,
+    },
+    maintainer::{
+        disk_usage,
+        is_old,
+        merge_logs,
+        plan_merges,
+        store_unstored,
+        FinalizedLog,
+    },
+    CreatedBy,
+    MaintenanceAction,
+    MilestoneData,
+};
+use chronicle_common::config::{
+    ArchiveSinkConfig,
+    ArchiveSinkTarget,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+fn log(start: u32, end: u32, size: u64) -> FinalizedLog {
+    FinalizedLog {
+        path: PathBuf::from(format!("{}to{}.log", start, end)),
+        range: start..end,
+        size,
+    }
+}
+
+#[test]
+fn test_plan_merges() {
+    let logs = vec![
+        log(0, 10, 10),
+        log(10, 20, 10),
+        log(20, 30, 500),
+        log(30, 40, 10),
+        log(40, 50, 10),
+        log(50, 60, 10),
+        log(70, 80, 10),
+        log(80, 90, 10),
+    ];
+    // the runs of contiguous small log files are merged, a large file or a gap ends a run
+    assert_eq!(plan_merges(&logs, 100, 1000), vec![0..2, 3..6, 6..8]);
+    // a run is split once the merged size would exceed the maximum log size
+    assert_eq!(plan_merges(&logs, 100, 20), vec![0..2, 3..5, 6..8]);
+    // a single small log file is left as is
+    assert!(plan_merges(&logs[..3], 100, 15).is_empty());
+}
+
+#[test]
+fn test_is_old() {
+    assert!(is_old(&(0..100), 1100, 1000));
+    assert!(!is_old(&(0..100), 1099, 1000));
+    // the age doesn't overflow near the end of the milestone range
+    assert!(!is_old(&(0..u32::MAX - 1), u32::MAX - 1, u32::MAX));
+}
+
+/// Write a finalized log file of empty milestones and record it in the manifest
+async fn write_log_file(logs_dir: &Path, start: u32, end: u32) -> FinalizedLog {
+    let mut encoder = LogEncoder::new(ArchiveFormat::Json, 3, 4);
+    let mut bytes = Vec::new();
+    for milestone_index in start..end {
+        let record = ArchiveRecord::new(
+            &MilestoneData::new(milestone_index, CreatedBy::Incoming),
+            ArchiveFormat::Json,
+        )
+        .unwrap();
+        bytes.extend(encoder.encode(&record).unwrap());
+    }
+    bytes.extend(encoder.finish().unwrap());
+    let path = logs_dir.join(format!("{}to{}.log", start, end));
+    tokio::fs::write(&path, &bytes).await.unwrap();
+    Manifest::record(&path).await.unwrap();
+    FinalizedLog {
+        path,
+        range: start..end,
+        size: bytes.len() as u64,
+    }
+}
+
+#[tokio::test]
+async fn test_merge_and_store() {
+    let dir = std::env::temp_dir().join(format!("chronicle-archive-maintenance-{}", std::process::id()));
+    let logs_dir = dir.join("logs");
+    tokio::fs::create_dir_all(&logs_dir).await.unwrap();
+    let config = ArchiveSinkConfig {
+        target: ArchiveSinkTarget::Local(dir.join("sink").to_string_lossy().into_owned()),
+        delete_local: false,
+    };
+    let sink = ArchiveSink::new(&config, "");
+    let mut logs = Vec::new();
+    for start in [0, 10, 20] {
+        logs.push(write_log_file(&logs_dir, start, start + 10).await);
+    }
+    // the stored log files are left as is
+    assert_eq!(
+        store_unstored(&sink, &logs_dir, &logs[..1]).await,
+        vec![MaintenanceAction::Stored {
+            path: logs[0].path.clone()
+        }]
+    );
+    assert!(store_unstored(&sink, &logs_dir, &logs[..1]).await.is_empty());
+
+    // the merged log file replaces the merged ones in the manifest, and isn't stored yet
+    let merged = merge_logs(&logs_dir, &logs[..2], None).await.unwrap();
+    assert_eq!(merged.path, logs_dir.join("0to20.log"));
+    assert_eq!(merged.range, 0..20);
+    assert!(!logs[0].path.exists() && !logs[1].path.exists());
+    let manifest = Manifest::load(&logs_dir).await.unwrap();
+    assert_eq!(
+        manifest
+            .files()
+            .iter()
+            .map(|entry| entry.file_name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["0to20.log", "20to30.log"]
+    );
+    assert!(!manifest.is_stored("0to20.log"));
+    let reader = ArchiveReader::new(logs_dir.clone());
+    for milestone_index in [0, 15, 25] {
+        let milestone_data = reader.get(milestone_index).await.unwrap().unwrap();
+        assert_eq!(milestone_data.milestone_index(), milestone_index);
+    }
+
+    // the maintainer stores the merged log file again
+    let kept = vec![merged, logs[2].clone()];
+    let actions = store_unstored(&sink, &logs_dir, &kept).await;
+    assert_eq!(
+        actions,
+        kept.iter()
+            .map(|log| MaintenanceAction::Stored { path: log.path.clone() })
+            .collect::<Vec<_>>()
+    );
+    let manifest = Manifest::load(&logs_dir).await.unwrap();
+    assert!(manifest.is_stored("0to20.log") && manifest.is_stored("20to30.log"));
+    assert!(sink.is_stored(&kept[0].path).await.unwrap());
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_disk_usage() {
+    let dir = std::env::temp_dir().join(format!("chronicle-archive-disk-usage-{}", std::process::id()));
+    tokio::fs::create_dir_all(dir.join("exports").join("nested"))
+        .await
+        .unwrap();
+    tokio::fs::write(dir.join("0to10.log"), vec![0; 100]).await.unwrap();
+    tokio::fs::write(dir.join("exports").join("10to20.log"), vec![0; 20])
+        .await
+        .unwrap();
+    tokio::fs::write(dir.join("exports").join("nested").join("20to30.log"), vec![0; 3])
+        .await
+        .unwrap();
+    // the files of the subdirectories are accounted for too
+    assert_eq!(disk_usage(&dir).await.unwrap(), 123);
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
@@ -0,0 +1,178 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+use chronicle_broker::archive::{
+    manifest::{
+        file_checksum,
+        Manifest,
+    },
+    mirror::ArchiveMirror,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+use tokio::{
+    io::{
+        AsyncBufReadExt,
+        AsyncWriteExt,
+        BufReader,
+    },
+    net::{
+        TcpListener,
+        TcpStream,
+    },
+};
+use url::Url;
+
+/// The served files by path, along with the range headers of the requests
+#[derive(Default, Clone)]
+struct Mirror {
+    files: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    ranges: Arc<Mutex<Vec<String>>>,
+}
+
+/// A minimal static file server, which supports the open ended range requests used by the mirror
+async fn serve_mirror(listener: TcpListener, mirror: Mirror) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let mirror = mirror.clone();
+        tokio::spawn(async move { handle_connection(stream, mirror).await.ok() });
+    }
+}
+
+async fn handle_connection(stream: TcpStream, mirror: Mirror) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).await? == 0 {
+            return Ok(());
+        }
+        let target = request_line.split_whitespace().nth(1).unwrap_or_default().to_owned();
+        let mut range = None;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).await?;
+            if line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("range") {
+                    range = Some(value.trim().to_owned());
+                }
+            }
+        }
+        let (status, extra_headers, response) = match mirror.files.lock().unwrap().get(&target) {
+            Some(data) => match range {
+                Some(range) => {
+                    mirror.ranges.lock().unwrap().push(range.clone());
+                    let offset: usize = range
+                        .strip_prefix("bytes=")
+                        .and_then(|range| range.strip_suffix('-'))
+                        .and_then(|offset| offset.parse().ok())
+                        .unwrap_or_default();
+                    if offset >= data.len() {
+                        ("416 Range Not Satisfiable", String::new(), Vec::new())
+                    } else {
+                        (
+                            "206 Partial Content",
+                            format!("content-range: bytes {}-{}/{}\r\n", offset, data.len() - 1, data.len()),
+                            data[offset..].to_vec(),
+                        )
+                    }
+                }
+                None => ("200 OK", String::new(), data.clone()),
+            },
+            None => ("404 Not Found", String::new(), Vec::new()),
+        };
+        let stream = reader.get_mut();
+        stream
+            .write_all(
+                format!(
+                    "HTTP/1.1 {}\r\ncontent-length: {}\r\n{}\r\n",
+                    status,
+                    response.len(),
+                    extra_headers
+                )
+                .as_bytes(),
+            )
+            .await?;
+        stream.write_all(&response).await?;
+    }
+}
+
+#[tokio::test]
+async fn test_mirror_resumed_and_verified_download() {
+    let dir = std::env::temp_dir().join(format!("chronicle-archive-mirror-{}", std::process::id()));
+    let served_dir = dir.join("served");
+    let imports_dir = dir.join("imports");
+    tokio::fs::create_dir_all(&served_dir).await.unwrap();
+    tokio::fs::create_dir_all(&imports_dir).await.unwrap();
+    let mirror = Mirror::default();
+    for (name, data) in [
+        ("10to20.log", b"{\"milestone_index\":10}\n".to_vec()),
+        ("20to30.log", b"{\"milestone_index\":20}\n".to_vec()),
+    ] {
+        let log_path = served_dir.join(name);
+        tokio::fs::write(&log_path, &data).await.unwrap();
+        Manifest::record(&log_path).await.unwrap();
+        mirror.files.lock().unwrap().insert(format!("/archive/{}", name), data);
+    }
+    let manifest = tokio::fs::read(Manifest::path(&served_dir)).await.unwrap();
+    mirror
+        .files
+        .lock()
+        .unwrap()
+        .insert("/archive/manifest.json".to_owned(), manifest.clone());
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url: Url = format!("http://{}/archive", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+    tokio::spawn(serve_mirror(listener, mirror.clone()));
+
+    let archive_mirror = ArchiveMirror::connect(&url).await.unwrap();
+    assert_eq!(
+        archive_mirror.files(),
+        vec![("10to20.log".to_owned(), 10..20), ("20to30.log".to_owned(), 20..30)]
+    );
+
+    // a partial download is resumed from where it stopped
+    let log_path = imports_dir.join("10to20.log");
+    tokio::fs::write(imports_dir.join("10to20.log.part"), b"{\"milestone_")
+        .await
+        .unwrap();
+    archive_mirror.download("10to20.log", &log_path).await.unwrap();
+    assert_eq!(mirror.ranges.lock().unwrap().as_slice(), &["bytes=12-".to_owned()]);
+    assert_eq!(
+        file_checksum(&log_path).await.unwrap(),
+        file_checksum(&served_dir.join("10to20.log")).await.unwrap()
+    );
+    assert!(!imports_dir.join("10to20.log.part").exists());
+
+    // a corrupted log file is not moved into the imports directory
+    mirror
+        .files
+        .lock()
+        .unwrap()
+        .get_mut("/archive/20to30.log")
+        .unwrap()
+        .push(b'\n');
+    let log_path = imports_dir.join("20to30.log");
+    assert!(archive_mirror.download("20to30.log", &log_path).await.is_err());
+    assert!(!log_path.exists());
+
+    // a tampered manifest is rejected
+    let tampered = String::from_utf8(manifest).unwrap().replace("20to30", "20to31");
+    mirror
+        .files
+        .lock()
+        .unwrap()
+        .insert("/archive/manifest.json".to_owned(), tampered.into_bytes());
+    assert!(ArchiveMirror::connect(&url).await.is_err());
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
@@ -0,0 +1,129 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+mod common;
+
+use bee_message::prelude::*;
+use bee_pow::providers::miner::Miner;
+use chronicle_broker::{
+    archive::{
+        ArchiveFormat,
+        ArchiveRecord,
+        LogEncoder,
+        RecordDecoder,
+    },
+    CreatedBy,
+    MilestoneData,
+};
+use chronicle_storage::access::{
+    FullMessage,
+    LedgerInclusionState,
+    MessageMetadata,
+};
+use common::{
+    metadata,
+    parents,
+};
+use tokio::io::BufReader;
+
+/// A milestone data with its milestone payload and indexation messages
+fn populated_milestone_data(milestone_index: u32) -> MilestoneData {
+    let mut milestone_data = MilestoneData::new(milestone_index, CreatedBy::Incoming);
+    for i in 0..3u8 {
+        let message = MessageBuilder::<Miner>::new()
+            .with_network_id(0)
+            .with_parents(parents())
+            .with_payload(Payload::Indexation(Box::new(
+                IndexationPayload::new(&[i; 8], b"chronicle").unwrap(),
+            )))
+            .finish()
+            .unwrap();
+        let mut message_id = [0; 32];
+        message_id[..4].copy_from_slice(&milestone_index.to_le_bytes());
+        message_id[4] = i;
+        let metadata = MessageMetadata {
+            white_flag_index: Some(i as u32),
+            milestone_timestamp_referenced: Some(1_600_000_000),
+            ..metadata(
+                MessageId::new(message_id),
+                milestone_index,
+                LedgerInclusionState::NoTransaction,
+            )
+        };
+        milestone_data.add_full_message(FullMessage::new(message, metadata));
+    }
+    let essence = MilestonePayloadEssence::new(
+        MilestoneIndex(milestone_index),
+        1_600_000_000,
+        parents(),
+        [0; 32],
+        0,
+        0,
+        vec![[7; 32]],
+        None,
+    )
+    .unwrap();
+    milestone_data.set_milestone(Box::new(
+        MilestonePayload::new(essence, vec![Box::new([8; 64])]).unwrap(),
+    ));
+    milestone_data
+}
+
+#[tokio::test]
+async fn test_populated_milestone_data_round_trip() {
+    for format in [ArchiveFormat::Json, ArchiveFormat::Zstd] {
+        let mut encoder = LogEncoder::new(format, 3, 4);
+        let mut bytes = Vec::new();
+        for milestone_index in 10..16 {
+            let record = ArchiveRecord::new(&populated_milestone_data(milestone_index), format).unwrap();
+            bytes.extend(encoder.encode(&record).unwrap());
+        }
+        bytes.extend(encoder.finish().unwrap());
+        let mut reader = BufReader::new(&bytes[..]);
+        let mut decoder = RecordDecoder::new(format);
+        let mut milestone_index = 10;
+        while let Some((record, _)) = decoder.next_record(&mut reader).await.unwrap() {
+            let decoded = record.milestone_data().unwrap();
+            let expected = populated_milestone_data(milestone_index);
+            assert_eq!(decoded.milestone_index(), milestone_index);
+            assert_eq!(decoded.milestone_timestamp(), Some(1_600_000_000));
+            assert_eq!(decoded.messages().len(), 3);
+            for (message_id, FullMessage(message, metadata)) in expected.messages() {
+                let FullMessage(decoded_message, decoded_metadata) = decoded.messages().get(message_id).unwrap();
+                assert_eq!(decoded_message, message);
+                assert_eq!(decoded_metadata, metadata);
+            }
+            milestone_index += 1;
+        }
+        assert_eq!(milestone_index, 16);
+    }
+}
+
+#[tokio::test]
+async fn test_zstd_log_blocks_and_length() {
+    let mut encoder = LogEncoder::new(ArchiveFormat::Zstd, 3, 4);
+    let mut written = Vec::new();
+    let mut raw_len = 0;
+    for milestone_index in 10..18 {
+        let record = ArchiveRecord::new(&populated_milestone_data(milestone_index), ArchiveFormat::Zstd).unwrap();
+        raw_len += record.encoded_len() as u64;
+        let bytes = encoder.encode(&record).unwrap();
+        // the records are buffered until their block of 4 milestones is full
+        if (milestone_index - 10) % 4 == 3 {
+            assert!(!bytes.is_empty());
+            assert_eq!(encoder.buffered_milestones(), 0);
+        } else {
+            assert_eq!(encoder.buffered_milestones(), (milestone_index - 10) % 4 + 1);
+        }
+        written.extend(bytes);
+    }
+    // the length is the compressed one, not the length of the packed records
+    assert_eq!(encoder.encoded_len(), written.len() as u64);
+    assert!(encoder.encoded_len() < raw_len);
+    let record = ArchiveRecord::new(&populated_milestone_data(18), ArchiveFormat::Zstd).unwrap();
+    let projected_len = encoder.projected_len(record.encoded_len());
+    assert!(projected_len > encoder.encoded_len());
+    assert!(projected_len < encoder.encoded_len() + record.encoded_len() as u64);
+}
@@ -0,0 +1,40 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+//! The fixtures shared by the broker tests, each test only uses some of them
+#![allow(dead_code)]
+
+use bee_message::prelude::{
+    MessageId,
+    Parents,
+};
+use chronicle_storage::access::{
+    LedgerInclusionState,
+    MessageMetadata,
+};
+
+/// The parents of the test messages
+pub fn parents() -> Parents {
+    Parents::new(vec![MessageId::new([1; 32]), MessageId::new([2; 32])]).unwrap()
+}
+
+/// The metadata of a solid message with the test parents, which is referenced by the given milestone at white-flag
+/// index 0. The other fields are overridden with the struct update syntax.
+pub fn metadata(
+    message_id: MessageId,
+    milestone_index: u32,
+    ledger_inclusion_state: LedgerInclusionState,
+) -> MessageMetadata {
+    MessageMetadata {
+        message_id,
+        parent_message_ids: parents().to_vec(),
+        is_solid: true,
+        referenced_by_milestone_index: Some(milestone_index),
+        ledger_inclusion_state: Some(ledger_inclusion_state),
+        should_promote: Some(false),
+        should_reattach: Some(false),
+        conflict_reason: None,
+        white_flag_index: Some(0),
+        milestone_timestamp_referenced: Some(0),
+    }
+}
@@ -0,0 +1,180 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+mod common;
+
+use bee_message::prelude::*;
+use bee_pow::providers::miner::Miner;
+use chronicle_broker::{
+    CreatedBy,
+    ImportFilter,
+    MilestoneData,
+    PayloadKind,
+};
+use chronicle_storage::access::{
+    FullMessage,
+    LedgerInclusionState,
+};
+use common::{
+    metadata,
+    parents,
+};
+
+const MILESTONE_INDEX: u32 = 10;
+
+fn address(byte: u8) -> Address {
+    Address::Ed25519(Ed25519Address::new([byte; 32]))
+}
+
+fn indexation(index: &[u8]) -> Payload {
+    Payload::Indexation(Box::new(IndexationPayload::new(index, &[]).unwrap()))
+}
+
+/// A transaction to the given address, which embeds an optional indexation payload
+fn transaction(byte: u8, payload: Option<Payload>) -> Payload {
+    let mut essence = RegularEssenceBuilder::new()
+        .with_inputs(vec![UtxoInput::new(TransactionId::new([3; 32]), 1).unwrap().into()])
+        .with_outputs(vec![SignatureLockedSingleOutput::new(address(byte), 1_000_000)
+            .unwrap()
+            .into()]);
+    if let Some(payload) = payload {
+        essence = essence.with_payload(payload);
+    }
+    let unlock_block = UnlockBlock::Signature(SignatureUnlock::Ed25519(Ed25519Signature::new(
+        [5; 32],
+        Box::new([6; 64]),
+    )));
+    Payload::Transaction(Box::new(
+        TransactionPayloadBuilder::new()
+            .with_essence(Essence::Regular(essence.finish().unwrap()))
+            .with_unlock_blocks(UnlockBlocks::new(vec![unlock_block]).unwrap())
+            .finish()
+            .unwrap(),
+    ))
+}
+
+fn milestone_payload() -> Box<MilestonePayload> {
+    let essence = MilestonePayloadEssence::new(
+        MilestoneIndex(MILESTONE_INDEX),
+        0,
+        parents(),
+        [0; 32],
+        0,
+        0,
+        vec![[7; 32]],
+        None,
+    )
+    .unwrap();
+    Box::new(MilestonePayload::new(essence, vec![Box::new([8; 64])]).unwrap())
+}
+
+fn message(payload: Option<Payload>) -> Message {
+    let mut builder = MessageBuilder::<Miner>::new()
+        .with_network_id(0)
+        .with_parents(parents());
+    if let Some(payload) = payload {
+        builder = builder.with_payload(payload);
+    }
+    builder.finish().unwrap()
+}
+
+/// The milestone data of a milestone message, two indexations, a transaction and a message without payload
+fn milestone_data() -> MilestoneData {
+    let mut milestone_data = MilestoneData::new(MILESTONE_INDEX, CreatedBy::Syncer);
+    let payloads = vec![
+        Some(Payload::Milestone(milestone_payload())),
+        Some(indexation(&[0xab, 0xcd])),
+        Some(indexation(&[0xff, 0x00])),
+        Some(transaction(7, Some(indexation(&[0xab, 0x01])))),
+        None,
+    ];
+    for (id, payload) in payloads.into_iter().enumerate() {
+        let message_id = MessageId::new([id as u8 + 11; 32]);
+        let metadata = metadata(message_id, MILESTONE_INDEX, LedgerInclusionState::Included);
+        milestone_data.add_full_message(FullMessage::new(message(payload), metadata));
+    }
+    milestone_data.set_milestone(milestone_payload());
+    milestone_data
+}
+
+/// The payload kinds of the messages which are kept by the filter
+fn filtered(filter: &ImportFilter) -> Vec<PayloadKind> {
+    let mut milestone_data = milestone_data();
+    filter.apply(&mut milestone_data);
+    let mut kinds = milestone_data
+        .messages()
+        .values()
+        .map(|FullMessage(message, _)| PayloadKind::of(message.payload().as_ref()))
+        .collect::<Vec<_>>();
+    kinds.sort_by_key(|kind| *kind as u8);
+    kinds
+}
+
+#[test]
+fn test_import_filter_matches() {
+    let filter = ImportFilter::default();
+    assert!(filter.is_empty());
+    assert_eq!(filtered(&filter).len(), 5);
+
+    // the milestone messages are always imported
+    let filter = ImportFilter {
+        payload_kinds: vec!["indexation".parse().unwrap()],
+        ..Default::default()
+    };
+    assert_eq!(
+        filtered(&filter),
+        vec![PayloadKind::Milestone, PayloadKind::Indexation, PayloadKind::Indexation]
+    );
+    assert!("receipt".parse::<PayloadKind>().is_err());
+
+    // the prefix is matched case insensitively, against the index of the transaction payload too
+    let filter = ImportFilter {
+        index_prefix: Some("AB".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        filtered(&filter),
+        vec![
+            PayloadKind::Transaction,
+            PayloadKind::Milestone,
+            PayloadKind::Indexation
+        ]
+    );
+
+    let filter = ImportFilter {
+        addresses: vec![address(7).to_string()].into_iter().collect(),
+        ..Default::default()
+    };
+    assert_eq!(
+        filtered(&filter),
+        vec![PayloadKind::Transaction, PayloadKind::Milestone]
+    );
+    let filter = ImportFilter {
+        payload_kinds: vec![PayloadKind::Indexation],
+        addresses: vec![address(7).to_string()].into_iter().collect(),
+        ..Default::default()
+    };
+    assert_eq!(filtered(&filter), vec![PayloadKind::Milestone]);
+}
+
+#[test]
+fn test_filtered_analytic_record() {
+    // the analytics of a filtered import only account for the imported messages
+    let filter = ImportFilter {
+        payload_kinds: vec![PayloadKind::Transaction],
+        ..Default::default()
+    };
+    let mut milestone_data = milestone_data();
+    filter.apply(&mut milestone_data);
+    milestone_data.set_known_addresses(Vec::new());
+    let analytic_record = milestone_data.get_analytic_record().unwrap();
+    assert_eq!(**analytic_record.message_count(), 2);
+    assert_eq!(**analytic_record.transaction_count(), 1);
+    let metrics = analytic_record.metrics().unwrap();
+    assert_eq!(metrics.transaction_payloads, 1);
+    assert_eq!(metrics.milestone_payloads, 1);
+    assert_eq!(metrics.indexation_payloads, 0);
+    assert_eq!(metrics.no_payload, 0);
+}
@@ -0,0 +1,129 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+use chronicle_broker::{
+    importer::scheduler::{
+        plan_shards,
+        ImportJob,
+        ImportScheduler,
+        ImportShard,
+        LogImportType,
+    },
+    ImporterSession,
+};
+use std::path::PathBuf;
+
+fn shard(file: &str, start: u32, end: u32) -> ImportShard {
+    ImportShard {
+        path: PathBuf::from(file),
+        range: start..end,
+    }
+}
+
+#[test]
+fn test_plan_shards() {
+    let files = vec![
+        (PathBuf::from("250to400.log"), 250..400),
+        (PathBuf::from("0to250.log"), 0..250),
+    ];
+    // the import range is split across the files, in shards of at most 100 milestones
+    assert_eq!(
+        plan_shards(files.clone(), &(50..300), Some(100), &[]),
+        vec![
+            shard("0to250.log", 50, 150),
+            shard("0to250.log", 150, 250),
+            shard("250to400.log", 250, 300),
+        ]
+    );
+    // a file is a single shard if no shard size is provided
+    assert_eq!(
+        plan_shards(files.clone(), &(0..1000), None, &[]),
+        vec![shard("0to250.log", 0, 250), shard("250to400.log", 250, 400)]
+    );
+    // the completed ranges are trimmed from both ends of the shards, and the completed shards are dropped
+    assert_eq!(
+        plan_shards(files, &(0..1000), Some(100), &[0..120, 180..320, 390..400]),
+        vec![
+            shard("0to250.log", 120, 180),
+            shard("250to400.log", 320, 350),
+            shard("250to400.log", 350, 390),
+        ]
+    );
+}
+
+fn job() -> ImportJob {
+    ImportJob {
+        network: 0,
+        resume: false,
+        import_type: LogImportType::All,
+        from_sink: false,
+        mirror: None,
+        filter: None,
+    }
+}
+
+#[test]
+fn test_schedule_shards() {
+    let mut scheduler = ImportScheduler::new(2);
+    assert!(scheduler.is_idle());
+    scheduler.schedule(
+        vec![
+            shard("0to30.log", 0, 10),
+            shard("0to30.log", 10, 20),
+            shard("0to30.log", 20, 30),
+        ],
+        job(),
+    );
+    // the insert concurrency budget is shared by the workers
+    let (first, _, parallelism) = scheduler.next(10, 10).unwrap();
+    assert_eq!((first, parallelism), (shard("0to30.log", 0, 10), 5));
+    let (second, _, parallelism) = scheduler.next(5, 10).unwrap();
+    assert_eq!((second, parallelism), (shard("0to30.log", 10, 20), 5));
+    // no worker is available
+    assert!(scheduler.next(10, 10).is_none());
+    scheduler.finished();
+    // no parallelism point is available
+    assert!(scheduler.next(0, 10).is_none());
+    let (third, _, _) = scheduler.next(5, 10).unwrap();
+    assert_eq!(third, shard("0to30.log", 20, 30));
+    assert!(scheduler.next(10, 10).is_none());
+    scheduler.finished();
+    scheduler.finished();
+    assert!(scheduler.is_idle());
+}
+
+#[test]
+fn test_import_progress() {
+    let mut scheduler = ImportScheduler::new(2);
+    scheduler.schedule(vec![shard("0to20.log", 0, 10), shard("0to20.log", 10, 20)], job());
+    // the initial progress of an importer has no milestone
+    scheduler.progress(0..10, 0, true);
+    // the milestones of a zstd block are counted even though their bytes are attributed to the first one
+    for milestone_index in 1..5 {
+        scheduler.progress(0..10, milestone_index, false);
+    }
+    scheduler.progress(10..20, 10, true);
+    // the milestones outside of the shard range are not counted
+    scheduler.progress(10..20, 25, false);
+    match scheduler.report(true) {
+        Some(ImporterSession::Progress {
+            imported,
+            skipped,
+            total,
+            pending_shards,
+            ..
+        }) => {
+            assert_eq!((imported, skipped, total, pending_shards), (4, 1, 20, 2));
+        }
+        _ => panic!("Expected a progress report"),
+    }
+    // the reports are throttled unless forced
+    assert!(scheduler.report(false).is_none());
+    scheduler.reset();
+    match scheduler.report(true) {
+        Some(ImporterSession::Progress { imported, total, .. }) => assert_eq!((imported, total), (0, 0)),
+        _ => panic!("Expected a progress report"),
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+use chronicle_broker::channel::bounded_channel;
+use chronicle_common::metrics::INBOX_DROPPED_EVENTS;
+
+#[tokio::test]
+async fn full_inbox_defers_in_order() {
+    let (tx, mut rx) = bounded_channel::<u32>(2, "test_defer_order");
+    for event in 0..3 {
+        assert!(tx.send_or_defer(event));
+    }
+    // the inbox holds two events, the third waits behind them
+    assert_eq!(tx.deferred(), 1);
+    // a clone shares the deferred events, so its events queue behind them too
+    assert!(tx.clone().send_or_defer(3));
+    assert_eq!(tx.deferred(), 2);
+    let mut received = Vec::new();
+    for _ in 0..4 {
+        received.push(rx.recv().await.unwrap());
+    }
+    assert_eq!(received, vec![0, 1, 2, 3]);
+    assert_eq!(tx.deferred(), 0);
+    assert!(tx.send_or_defer(4));
+    assert_eq!(rx.recv().await, Some(4));
+}
+
+#[tokio::test]
+async fn full_inbox_drops_and_counts() {
+    let (tx, mut rx) = bounded_channel::<u32>(1, "test_defer_drop");
+    let dropped = INBOX_DROPPED_EVENTS.with_label_values(&["test_defer_drop"]);
+    assert!(tx.send_or_defer(0));
+    assert!(tx.send_or_defer(1));
+    // the inbox and its deferred events are full
+    assert!(!tx.send_or_defer(2));
+    assert_eq!(dropped.get(), 1);
+    assert_eq!(rx.recv().await, Some(0));
+    assert_eq!(rx.recv().await, Some(1));
+    // once drained, events are accepted again
+    assert!(tx.send_or_defer(3));
+    assert_eq!(rx.recv().await, Some(3));
+    assert_eq!(dropped.get(), 1);
+}
+
+#[tokio::test]
+async fn closed_inbox_rejects_events() {
+    let (tx, rx) = bounded_channel::<u32>(1, "test_defer_closed");
+    drop(rx);
+    assert!(!tx.send_or_defer(0));
+    assert_eq!(tx.deferred(), 0);
+}
+
+#[tokio::test]
+async fn control_events_are_never_dropped() {
+    let (tx, mut rx) = bounded_channel::<u32>(1, "test_control");
+    let dropped = INBOX_DROPPED_EVENTS.with_label_values(&["test_control"]);
+    assert!(tx.send_or_defer(0));
+    assert!(tx.send_or_defer(1));
+    assert!(!tx.send_or_defer(2));
+    // the inbox and its deferred events are full, yet the control events are accepted
+    for event in 10..13 {
+        assert!(tx.send_control(event));
+    }
+    assert_eq!(dropped.get(), 1);
+    // and received ahead of the bounded events, in order
+    let mut received = Vec::new();
+    for _ in 0..5 {
+        received.push(rx.recv().await.unwrap());
+    }
+    assert_eq!(received, vec![10, 11, 12, 0, 1]);
+    drop(rx);
+    assert!(!tx.send_control(13));
+}
+
+#[test]
+fn full_inbox_drops_without_runtime() {
+    let (tx, _rx) = bounded_channel::<u32>(1, "test_defer_no_runtime");
+    let dropped = INBOX_DROPPED_EVENTS.with_label_values(&["test_defer_no_runtime"]);
+    assert!(tx.send_or_defer(0));
+    // there's no runtime to forward a deferred event, so it's dropped rather than panicking
+    assert!(!tx.send_or_defer(1));
+    assert_eq!(tx.deferred(), 0);
+    assert_eq!(dropped.get(), 1);
+    // control events don't need a runtime
+    assert!(tx.send_control(2));
+}
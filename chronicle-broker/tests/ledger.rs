@@ -0,0 +1,406 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+mod common;
+
+use bee_common::packable::Packable;
+use bee_message::prelude::*;
+use bee_pow::providers::miner::Miner;
+use chronicle_broker::{
+    ledger::{
+        self,
+        snapshot::{
+            Snapshot,
+            SnapshotOutput,
+            SnapshotReader,
+        },
+        Balance,
+        LedgerCheckpoints,
+        LedgerState,
+        StateAtError,
+    },
+    CreatedBy,
+    MilestoneData,
+};
+use chronicle_storage::access::{
+    FullMessage,
+    LedgerInclusionState,
+    SnapshotKind,
+};
+use common::{
+    metadata,
+    parents,
+};
+
+const MILESTONE_INDEX: u32 = 10;
+
+fn address(byte: u8) -> Address {
+    Address::Ed25519(Ed25519Address::new([byte; 32]))
+}
+
+fn output(byte: u8, amount: u64) -> Output {
+    SignatureLockedSingleOutput::new(address(byte), amount).unwrap().into()
+}
+
+/// The unspent outputs confirmed by the milestone before `MILESTONE_INDEX`
+fn genesis() -> Vec<(OutputId, Output)> {
+    vec![
+        (
+            OutputId::new(TransactionId::new([3; 32]), 1).unwrap(),
+            output(9, 1_000_000),
+        ),
+        (
+            OutputId::new(TransactionId::new([3; 32]), 2).unwrap(),
+            output(4, 2_000_000),
+        ),
+    ]
+}
+
+fn transaction_payload(outputs: Vec<Output>) -> Box<TransactionPayload> {
+    let essence = RegularEssenceBuilder::new()
+        .with_inputs(vec![UtxoInput::new(TransactionId::new([3; 32]), 1).unwrap().into()])
+        .with_outputs(outputs)
+        .finish()
+        .unwrap();
+    let unlock_block = UnlockBlock::Signature(SignatureUnlock::Ed25519(Ed25519Signature::new(
+        [5; 32],
+        Box::new([6; 64]),
+    )));
+    Box::new(
+        TransactionPayloadBuilder::new()
+            .with_essence(Essence::Regular(essence))
+            .with_unlock_blocks(UnlockBlocks::new(vec![unlock_block]).unwrap())
+            .finish()
+            .unwrap(),
+    )
+}
+
+fn full_message(message_id: MessageId, payload: Payload, ledger_inclusion_state: LedgerInclusionState) -> FullMessage {
+    let message = MessageBuilder::<Miner>::new()
+        .with_network_id(0)
+        .with_parents(parents())
+        .with_payload(payload)
+        .finish()
+        .unwrap();
+    FullMessage::new(message, metadata(message_id, MILESTONE_INDEX, ledger_inclusion_state))
+}
+
+/// The milestone data which spends the first genesis output, along with a conflicting double spend
+fn milestone_data(transaction: Box<TransactionPayload>) -> MilestoneData {
+    let mut milestone_data = MilestoneData::new(MILESTONE_INDEX, CreatedBy::Syncer);
+    milestone_data.add_full_message(full_message(
+        MessageId::new([11; 32]),
+        Payload::Transaction(transaction),
+        LedgerInclusionState::Included,
+    ));
+    milestone_data.add_full_message(full_message(
+        MessageId::new([12; 32]),
+        Payload::Transaction(transaction_payload(vec![output(7, 1_000_000)])),
+        LedgerInclusionState::Conflicting,
+    ));
+    milestone_data.set_milestone(milestone_payload());
+    milestone_data
+}
+
+fn milestone_payload() -> Box<MilestonePayload> {
+    let essence = MilestonePayloadEssence::new(
+        MilestoneIndex(MILESTONE_INDEX),
+        0,
+        parents(),
+        [0; 32],
+        0,
+        0,
+        vec![[7; 32]],
+        None,
+    )
+    .unwrap();
+    Box::new(MilestonePayload::new(essence, vec![Box::new([8; 64])]).unwrap())
+}
+
+fn replayed() -> LedgerState {
+    let transaction = transaction_payload(vec![output(4, 600_000), output(9, 400_000)]);
+    let mut state = LedgerState::new(MILESTONE_INDEX - 1, genesis()).unwrap();
+    state.apply(&milestone_data(transaction)).unwrap();
+    state
+}
+
+#[test]
+fn test_apply_milestone() {
+    let transaction = transaction_payload(vec![output(4, 600_000), output(9, 400_000)]);
+    let transaction_id = transaction.id();
+    let mut state = LedgerState::new(MILESTONE_INDEX - 1, genesis()).unwrap();
+    assert_eq!(state.supply(), 3_000_000);
+    state.apply(&milestone_data(transaction)).unwrap();
+    assert_eq!(state.ledger_index(), MILESTONE_INDEX);
+    // the conflicting transaction is ignored
+    assert_eq!(state.supply(), 3_000_000);
+    assert_eq!(state.balance(&address(7)), Balance::default());
+    assert_eq!(
+        state.balance(&address(4)),
+        Balance {
+            amount: 2_600_000,
+            dust_allowance: 0,
+            output_count: 2,
+        }
+    );
+    assert_eq!(state.balance(&address(9)).amount, 400_000);
+    assert!(state.outputs().contains_key(&OutputId::new(transaction_id, 1).unwrap()));
+    assert!(!state
+        .outputs()
+        .contains_key(&OutputId::new(TransactionId::new([3; 32]), 1).unwrap()));
+
+    // the milestones are applied in order, and an unknown input is rejected
+    let transaction = transaction_payload(vec![output(4, 1_000_000)]);
+    assert!(state.clone().apply(&milestone_data(transaction.clone())).is_err());
+    let mut genesis_state = LedgerState::new(MILESTONE_INDEX - 1, Vec::new()).unwrap();
+    assert!(genesis_state.apply(&milestone_data(transaction)).is_err());
+}
+
+#[tokio::test]
+async fn test_checkpoints() {
+    let state = replayed();
+    assert_eq!(
+        LedgerState::from_checkpoint(&state.to_checkpoint().unwrap()).unwrap(),
+        state
+    );
+
+    let dir = std::env::temp_dir().join(format!("chronicle-ledger-checkpoints-{}", std::process::id()));
+    let checkpoints = LedgerCheckpoints::new(dir.clone());
+    assert!(checkpoints.load_latest(u32::MAX).await.unwrap().is_none());
+    checkpoints
+        .write(&LedgerState::new(MILESTONE_INDEX - 1, genesis()).unwrap())
+        .await
+        .unwrap();
+    checkpoints.write(&state).await.unwrap();
+    assert_eq!(
+        checkpoints.list().await.unwrap(),
+        vec![MILESTONE_INDEX - 1, MILESTONE_INDEX]
+    );
+    assert_eq!(checkpoints.load_latest(MILESTONE_INDEX + 5).await.unwrap(), Some(state));
+    assert_eq!(
+        checkpoints
+            .load_latest(MILESTONE_INDEX - 1)
+            .await
+            .unwrap()
+            .map(|state| state.ledger_index()),
+        Some(MILESTONE_INDEX - 1)
+    );
+    assert!(checkpoints.load_latest(MILESTONE_INDEX - 2).await.unwrap().is_none());
+    tokio::fs::remove_dir_all(dir).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_latest_checkpoint() {
+    let dir = std::env::temp_dir().join(format!("chronicle-ledger-latest-{}", std::process::id()));
+    let checkpoints = LedgerCheckpoints::new(dir.clone());
+    assert!(checkpoints.latest().await.unwrap().is_none());
+    let genesis_state = LedgerState::new(MILESTONE_INDEX - 1, genesis()).unwrap();
+    // the concurrent writes of a checkpoint don't share their temporary file
+    let writes = (0..4).map(|_| {
+        let checkpoints = checkpoints.clone();
+        let state = genesis_state.clone();
+        tokio::spawn(async move { checkpoints.write(&state).await })
+    });
+    for write in futures::future::join_all(writes).await {
+        write.unwrap().unwrap();
+    }
+    let mut entries = tokio::fs::read_dir(&dir).await.unwrap();
+    while let Some(entry) = entries.next_entry().await.unwrap() {
+        assert!(!entry.file_name().to_string_lossy().ends_with(".tmp"));
+    }
+    let latest = checkpoints.latest().await.unwrap().unwrap();
+    assert_eq!(*latest, genesis_state);
+    // the latest state is shared until a newer checkpoint is written
+    assert!(std::sync::Arc::ptr_eq(
+        &latest,
+        &checkpoints.clone().latest().await.unwrap().unwrap()
+    ));
+    let state = replayed();
+    checkpoints.write(&state).await.unwrap();
+    assert_eq!(*checkpoints.latest().await.unwrap().unwrap(), state);
+    tokio::fs::remove_dir_all(dir).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_state_at() {
+    let dir = std::env::temp_dir().join(format!("chronicle-ledger-state-at-{}", std::process::id()));
+    let checkpoints = LedgerCheckpoints::new(dir.clone());
+    checkpoints
+        .write(&LedgerState::new(MILESTONE_INDEX - 1, genesis()).unwrap())
+        .await
+        .unwrap();
+    let fetch = |milestone_index| async move {
+        let transaction = transaction_payload(vec![output(4, 600_000), output(9, 400_000)]);
+        Ok::<_, anyhow::Error>((milestone_index == MILESTONE_INDEX).then(|| milestone_data(transaction)))
+    };
+    let state = ledger::state_at(&checkpoints, MILESTONE_INDEX, 1, fetch).await.unwrap();
+    assert_eq!(state, replayed());
+    // no checkpoint covers the milestone
+    assert!(matches!(
+        ledger::state_at(&checkpoints, MILESTONE_INDEX - 2, 1, fetch).await,
+        Err(StateAtError::NoCheckpoint(_))
+    ));
+    // the milestone is too far above the checkpoint, nothing is fetched
+    let no_fetch = |_| async { Err::<Option<MilestoneData>, _>(anyhow::anyhow!("Unexpected fetch")) };
+    assert!(matches!(
+        ledger::state_at(&checkpoints, MILESTONE_INDEX + 1, 1, no_fetch).await,
+        Err(StateAtError::TooDistant {
+            distance: 2,
+            max_distance: 1,
+            ..
+        })
+    ));
+    // the milestones above the checkpoint must be fetched
+    assert!(matches!(
+        ledger::state_at(&checkpoints, MILESTONE_INDEX + 1, 2, fetch).await,
+        Err(StateAtError::Other(_))
+    ));
+    tokio::fs::remove_dir_all(dir).await.unwrap();
+}
+
+/// Encode a bee full snapshot of the given unspent outputs
+fn snapshot_bytes(outputs: &[(OutputId, Output)]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    1u8.pack(&mut bytes).unwrap();
+    0u8.pack(&mut bytes).unwrap();
+    1_622_555_110u64.pack(&mut bytes).unwrap();
+    0u64.pack(&mut bytes).unwrap();
+    MILESTONE_INDEX.pack(&mut bytes).unwrap();
+    MILESTONE_INDEX.pack(&mut bytes).unwrap();
+    1u64.pack(&mut bytes).unwrap();
+    (outputs.len() as u64).pack(&mut bytes).unwrap();
+    0u64.pack(&mut bytes).unwrap();
+    [0u8; 32].pack(&mut bytes).unwrap();
+    5_000u64.pack(&mut bytes).unwrap();
+    MessageId::new([0; 32]).pack(&mut bytes).unwrap();
+    for (output_id, output) in outputs {
+        MessageId::new([1; 32]).pack(&mut bytes).unwrap();
+        output_id.pack(&mut bytes).unwrap();
+        output.pack(&mut bytes).unwrap();
+    }
+    bytes
+}
+
+#[test]
+fn test_snapshot_diff() {
+    let state = replayed();
+    let mut outputs = state.outputs().iter().map(|(k, v)| (*k, v.clone())).collect::<Vec<_>>();
+    let snapshot = Snapshot::read(&mut snapshot_bytes(&outputs).as_slice()).unwrap();
+    assert_eq!(snapshot.kind, SnapshotKind::Full);
+    assert_eq!(snapshot.ledger_index, MILESTONE_INDEX);
+    assert_eq!(snapshot.treasury_amount, Some(5_000));
+    assert_eq!(snapshot.ledger_state().unwrap(), state);
+    assert!(state.diff(&snapshot).unwrap().is_consistent());
+
+    let (changed_id, _) = outputs[0];
+    outputs[0].1 = output(4, 1);
+    let (dropped_id, _) = outputs.pop().unwrap();
+    let extra_id = OutputId::new(TransactionId::new([8; 32]), 0).unwrap();
+    outputs.push((extra_id, output(8, 1)));
+    let snapshot = Snapshot::read(&mut snapshot_bytes(&outputs).as_slice()).unwrap();
+    let diff = state.diff(&snapshot).unwrap();
+    assert!(!diff.is_consistent());
+    assert_eq!(diff.mismatched, vec![changed_id.to_string()]);
+    assert_eq!(diff.missing, vec![extra_id.to_string()]);
+    assert_eq!(diff.unexpected, vec![dropped_id.to_string()]);
+}
+
+/// Encode a delta snapshot with the milestone diff of `MILESTONE_INDEX`
+fn delta_snapshot_bytes(transaction_id: TransactionId) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    1u8.pack(&mut bytes).unwrap();
+    1u8.pack(&mut bytes).unwrap();
+    1_622_555_110u64.pack(&mut bytes).unwrap();
+    0u64.pack(&mut bytes).unwrap();
+    MILESTONE_INDEX.pack(&mut bytes).unwrap();
+    (MILESTONE_INDEX - 1).pack(&mut bytes).unwrap();
+    1u64.pack(&mut bytes).unwrap();
+    1u64.pack(&mut bytes).unwrap();
+    MessageId::new([0; 32]).pack(&mut bytes).unwrap();
+    let milestone = Payload::Milestone(milestone_payload());
+    (milestone.packed_len() as u32).pack(&mut bytes).unwrap();
+    milestone.pack(&mut bytes).unwrap();
+    1u64.pack(&mut bytes).unwrap();
+    MessageId::new([11; 32]).pack(&mut bytes).unwrap();
+    OutputId::new(transaction_id, 0).unwrap().pack(&mut bytes).unwrap();
+    output(4, 1_000_000).pack(&mut bytes).unwrap();
+    1u64.pack(&mut bytes).unwrap();
+    let (output_id, output) = genesis().remove(0);
+    MessageId::new([1; 32]).pack(&mut bytes).unwrap();
+    output_id.pack(&mut bytes).unwrap();
+    output.pack(&mut bytes).unwrap();
+    transaction_id.pack(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn test_delta_snapshot() {
+    let transaction_id = TransactionId::new([10; 32]);
+    let snapshot = Snapshot::read(&mut delta_snapshot_bytes(transaction_id).as_slice()).unwrap();
+    assert_eq!(snapshot.kind, SnapshotKind::Delta);
+    assert_eq!(snapshot.sep_index, MILESTONE_INDEX);
+    assert_eq!(snapshot.treasury_amount, None);
+    assert!(snapshot.outputs.is_empty());
+    assert_eq!(snapshot.milestone_diffs.len(), 1);
+    let info = snapshot.info();
+    assert_eq!((info.output_count, info.milestone_diff_count), (0, 1));
+
+    let milestone_diff = &snapshot.milestone_diffs[0];
+    assert_eq!(milestone_diff.milestone_index(), MILESTONE_INDEX);
+    assert_eq!(milestone_diff.consumed_treasury, None);
+    assert_eq!(
+        milestone_diff.created,
+        vec![SnapshotOutput {
+            message_id: MessageId::new([11; 32]),
+            output_id: OutputId::new(transaction_id, 0).unwrap(),
+            output: output(4, 1_000_000),
+        }]
+    );
+    let (consumed, consumed_by) = &milestone_diff.consumed[0];
+    assert_eq!(consumed.output_id, genesis()[0].0);
+    assert_eq!(*consumed_by, transaction_id);
+
+    // only a full snapshot holds a ledger state
+    assert!(snapshot.ledger_state().is_err());
+    assert!(LedgerState::new(MILESTONE_INDEX - 1, genesis())
+        .unwrap()
+        .diff(&snapshot)
+        .is_err());
+    let mut truncated = delta_snapshot_bytes(transaction_id);
+    truncated.truncate(truncated.len() - 1);
+    assert!(Snapshot::read(&mut truncated.as_slice()).is_err());
+}
+
+#[test]
+fn test_snapshot_reader() {
+    let outputs = genesis();
+    let bytes = snapshot_bytes(&outputs);
+    let mut reader = SnapshotReader::new(bytes.as_slice()).unwrap();
+    assert_eq!(reader.info(), &Snapshot::read(&mut bytes.as_slice()).unwrap().info());
+    assert_eq!(reader.solid_entry_points(), &[MessageId::new([0; 32])]);
+    // the outputs are read one at a time, before the milestone diffs
+    let (output_id, output) = &outputs[0];
+    let first = reader.next_output().unwrap().unwrap();
+    assert_eq!((&first.output_id, &first.output), (output_id, output));
+    assert!(reader.next_milestone_diff().is_err());
+    let mut read = 1;
+    while reader.next_output().unwrap().is_some() {
+        read += 1;
+    }
+    assert_eq!(read, outputs.len());
+    assert!(reader.next_milestone_diff().unwrap().is_none());
+
+    let transaction_id = TransactionId::new([10; 32]);
+    let bytes = delta_snapshot_bytes(transaction_id);
+    let mut reader = SnapshotReader::new(bytes.as_slice()).unwrap();
+    assert!(reader.next_output().unwrap().is_none());
+    let milestone_diff = reader.next_milestone_diff().unwrap().unwrap();
+    assert_eq!(milestone_diff.milestone_index(), MILESTONE_INDEX);
+    assert_eq!(milestone_diff.consumed[0].1, transaction_id);
+    assert!(reader.next_milestone_diff().unwrap().is_none());
+    // a truncated milestone diff fails to read
+    let mut reader = SnapshotReader::new(&bytes[..bytes.len() - 1]).unwrap();
+    assert!(reader.next_milestone_diff().is_err());
+}
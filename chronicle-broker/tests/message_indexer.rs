@@ -0,0 +1,269 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+mod common;
+
+use bee_message::prelude::*;
+use bee_pow::providers::miner::Miner;
+use chronicle_broker::indexer::{
+    IndexRow,
+    MessageIndexer,
+};
+use chronicle_common::config::PartitionConfig;
+use chronicle_storage::access::{
+    AddressRecord,
+    Hint,
+    Indexation,
+    IndexationRecord,
+    InputData,
+    LedgerInclusionState,
+    ParentRecord,
+    Partition,
+    Partitioned,
+    TransactionRecord,
+    UnlockData,
+};
+use common::{
+    metadata,
+    parents,
+};
+
+const MILESTONE_INDEX: u32 = 1234;
+/// The partition id of `MILESTONE_INDEX` with the test partition config
+const PARTITION_ID: u16 = 2;
+
+fn indexer() -> MessageIndexer {
+    MessageIndexer::new(PartitionConfig {
+        partition_count: 10,
+        milestone_chunk_size: 100,
+    })
+}
+
+fn message(payload: Option<Payload>) -> Message {
+    let builder = MessageBuilder::<Miner>::new()
+        .with_network_id(0)
+        .with_parents(parents());
+    match payload {
+        Some(payload) => builder.with_payload(payload),
+        None => builder,
+    }
+    .finish()
+    .unwrap()
+}
+
+fn indexation_payload() -> Payload {
+    Payload::Indexation(Box::new(IndexationPayload::new(b"chronicle", &[]).unwrap()))
+}
+
+fn transaction_payload() -> Payload {
+    let essence = RegularEssenceBuilder::new()
+        .with_inputs(vec![UtxoInput::new(TransactionId::new([3; 32]), 1).unwrap().into()])
+        .with_outputs(vec![SignatureLockedSingleOutput::new(
+            Address::Ed25519(Ed25519Address::new([4; 32])),
+            1_000_000,
+        )
+        .unwrap()
+        .into()])
+        .with_payload(indexation_payload())
+        .finish()
+        .unwrap();
+    let unlock_block = UnlockBlock::Signature(SignatureUnlock::Ed25519(Ed25519Signature::new(
+        [5; 32],
+        Box::new([6; 64]),
+    )));
+    Payload::Transaction(Box::new(
+        TransactionPayloadBuilder::new()
+            .with_essence(Essence::Regular(essence))
+            .with_unlock_blocks(UnlockBlocks::new(vec![unlock_block]).unwrap())
+            .finish()
+            .unwrap(),
+    ))
+}
+
+fn milestone_payload(parents: Parents) -> Payload {
+    let essence = MilestonePayloadEssence::new(
+        MilestoneIndex(MILESTONE_INDEX),
+        0,
+        parents,
+        [0; 32],
+        0,
+        0,
+        vec![[7; 32]],
+        None,
+    )
+    .unwrap();
+    Payload::Milestone(Box::new(
+        MilestonePayload::new(essence, vec![Box::new([8; 64])]).unwrap(),
+    ))
+}
+
+fn partitioned<T>(inner: T) -> Partitioned<T> {
+    Partitioned::new(inner, PARTITION_ID, MILESTONE_INDEX)
+}
+
+fn hint(hint: Hint) -> IndexRow {
+    IndexRow::Hint(hint, Partition::new(PARTITION_ID, MILESTONE_INDEX))
+}
+
+/// The `parents` and `hints` rows of every message
+fn parent_rows(message_id: MessageId, inclusion_state: Option<LedgerInclusionState>) -> Vec<IndexRow> {
+    parents()
+        .iter()
+        .flat_map(|parent_id| {
+            vec![
+                IndexRow::Parent(partitioned(*parent_id), ParentRecord::new(message_id, inclusion_state)),
+                hint(Hint::parent(parent_id.to_string())),
+            ]
+        })
+        .collect()
+}
+
+/// The `indexes` and `hints` rows of the indexation payload
+fn indexation_rows(message_id: MessageId, inclusion_state: Option<LedgerInclusionState>) -> Vec<IndexRow> {
+    let index = hex::encode("chronicle");
+    vec![
+        IndexRow::Index(
+            partitioned(Indexation(index.clone())),
+            IndexationRecord::new(message_id, inclusion_state),
+        ),
+        hint(Hint::index(index)),
+    ]
+}
+
+/// The `transactions`, `addresses` and `hints` rows of the transaction payload, along with its indexation rows
+fn transaction_rows(
+    message_id: MessageId,
+    payload: &Payload,
+    inclusion_state: Option<LedgerInclusionState>,
+) -> Vec<IndexRow> {
+    let transaction = match payload {
+        Payload::Transaction(transaction) => transaction,
+        _ => unreachable!(),
+    };
+    let transaction_id = transaction.id();
+    let Essence::Regular(essence) = transaction.essence();
+    let unlock_block = transaction.unlock_blocks()[0].clone();
+    let utxo_input = match &essence.inputs()[0] {
+        Input::Utxo(utxo_input) => utxo_input.clone(),
+        _ => unreachable!(),
+    };
+    let output = essence.outputs()[0].clone();
+    let milestone_index = inclusion_state.map(|_| MilestoneIndex(MILESTONE_INDEX));
+    let ed_address = Ed25519Address::new([4; 32]);
+    let mut rows = vec![
+        IndexRow::Transaction(
+            (transaction_id, 0),
+            TransactionRecord::input(
+                message_id,
+                InputData::utxo(utxo_input, unlock_block.clone()),
+                inclusion_state,
+                milestone_index,
+            ),
+        ),
+        IndexRow::Transaction(
+            (TransactionId::new([3; 32]), 1),
+            TransactionRecord::unlock(
+                message_id,
+                UnlockData::new(transaction_id, 0, unlock_block),
+                inclusion_state,
+                milestone_index,
+            ),
+        ),
+        IndexRow::Transaction(
+            (transaction_id, 0),
+            TransactionRecord::output(message_id, output.clone(), inclusion_state, milestone_index),
+        ),
+        IndexRow::Address(
+            partitioned(ed_address),
+            AddressRecord::new(output.kind(), transaction_id, 0, 1_000_000, inclusion_state),
+        ),
+        hint(Hint::address(ed_address.to_string())),
+    ];
+    rows.extend(indexation_rows(message_id, inclusion_state));
+    rows
+}
+
+#[test]
+fn test_index_payload_kinds() {
+    let message_id = MessageId::new([9; 32]);
+    let included = Some(LedgerInclusionState::Included);
+    let no_transaction = Some(LedgerInclusionState::NoTransaction);
+    let transaction = transaction_payload();
+    let milestone = milestone_payload(parents());
+    // the payload, the ledger inclusion state of the referenced message, and the expected payload rows
+    let cases: Vec<(&str, Option<Payload>, Option<LedgerInclusionState>, Vec<IndexRow>)> = vec![
+        ("no payload", None, no_transaction, vec![]),
+        ("unreferenced, no payload", None, None, vec![]),
+        (
+            "indexation",
+            Some(indexation_payload()),
+            no_transaction,
+            indexation_rows(message_id, no_transaction),
+        ),
+        (
+            "unreferenced indexation",
+            Some(indexation_payload()),
+            None,
+            indexation_rows(message_id, None),
+        ),
+        (
+            "transaction",
+            Some(transaction.clone()),
+            included,
+            transaction_rows(message_id, &transaction, included),
+        ),
+        (
+            "unreferenced transaction",
+            Some(transaction.clone()),
+            None,
+            transaction_rows(message_id, &transaction, None),
+        ),
+        (
+            "milestone",
+            Some(milestone.clone()),
+            no_transaction,
+            match &milestone {
+                Payload::Milestone(payload) => vec![IndexRow::Milestone(
+                    MilestoneIndex(MILESTONE_INDEX),
+                    (message_id, payload.clone()),
+                )],
+                _ => unreachable!(),
+            },
+        ),
+        ("unreferenced milestone", Some(milestone.clone()), None, vec![]),
+        (
+            "milestone with other parents",
+            Some(milestone_payload(Parents::new(vec![MessageId::new([1; 32])]).unwrap())),
+            no_transaction,
+            vec![],
+        ),
+    ];
+    for (name, payload, inclusion_state, payload_rows) in cases {
+        let message = message(payload);
+        let metadata = inclusion_state.map(|inclusion_state| metadata(message_id, MILESTONE_INDEX, inclusion_state));
+        let rows = indexer()
+            .index(
+                &message_id,
+                &message,
+                MilestoneIndex(MILESTONE_INDEX),
+                metadata.as_ref(),
+            )
+            .unwrap();
+        let mut expected = parent_rows(message_id, inclusion_state);
+        expected.extend(payload_rows);
+        expected.push(IndexRow::Message(message_id, message.clone(), metadata));
+        assert_eq!(rows, expected, "{}", name);
+    }
+}
+
+#[test]
+fn test_index_metadata() {
+    let message_id = MessageId::new([9; 32]);
+    let metadata = metadata(message_id, MILESTONE_INDEX, LedgerInclusionState::Conflicting);
+    let rows = indexer().index_metadata(&metadata, MilestoneIndex(MILESTONE_INDEX));
+    let mut expected = parent_rows(message_id, Some(LedgerInclusionState::Conflicting));
+    expected.push(IndexRow::Metadata(message_id, metadata));
+    assert_eq!(rows, expected);
+}
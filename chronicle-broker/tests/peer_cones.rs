@@ -0,0 +1,186 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+mod common;
+
+use bee_message::prelude::*;
+use bee_pow::providers::miner::Miner;
+use chronicle_broker::{
+    peer::PeerCones,
+    CreatedBy,
+    MilestoneData,
+};
+use chronicle_storage::access::{
+    FullMessage,
+    LedgerInclusionState,
+};
+use common::{
+    metadata,
+    parents,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+use tokio::{
+    io::{
+        AsyncBufReadExt,
+        AsyncWriteExt,
+        BufReader,
+    },
+    net::{
+        TcpListener,
+        TcpStream,
+    },
+};
+use url::Url;
+
+const MILESTONE_INDEX: u32 = 10;
+
+/// The served json bodies by path, along with the requested paths
+#[derive(Default, Clone)]
+struct Peer {
+    bodies: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+/// A minimal stand-in for the archive API of a peer Chronicle
+async fn serve_peer(listener: TcpListener, peer: Peer) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let peer = peer.clone();
+        tokio::spawn(async move { handle_connection(stream, peer).await.ok() });
+    }
+}
+
+async fn handle_connection(stream: TcpStream, peer: Peer) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).await? == 0 {
+            return Ok(());
+        }
+        let target = request_line.split_whitespace().nth(1).unwrap_or_default().to_owned();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).await?;
+            if line.trim().is_empty() {
+                break;
+            }
+        }
+        peer.requests.lock().unwrap().push(target.clone());
+        let (status, body) = match peer.bodies.lock().unwrap().get(&target) {
+            Some(body) => ("200 OK", body.clone()),
+            None => (
+                "404 Not Found",
+                br#"{"error":{"code":404,"message":"Not found"}}"#.to_vec(),
+            ),
+        };
+        let stream = reader.get_mut();
+        stream
+            .write_all(
+                format!(
+                    "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n",
+                    status,
+                    body.len()
+                )
+                .as_bytes(),
+            )
+            .await?;
+        stream.write_all(&body).await?;
+    }
+}
+
+fn milestone_payload() -> Box<MilestonePayload> {
+    let essence = MilestonePayloadEssence::new(
+        MilestoneIndex(MILESTONE_INDEX),
+        0,
+        parents(),
+        [0; 32],
+        0,
+        0,
+        vec![[7; 32]],
+        None,
+    )
+    .unwrap();
+    Box::new(MilestonePayload::new(essence, vec![Box::new([8; 64])]).unwrap())
+}
+
+/// The cone of a milestone message and a message without payload
+fn milestone_data() -> MilestoneData {
+    let mut milestone_data = MilestoneData::new(MILESTONE_INDEX, CreatedBy::Syncer);
+    for (id, payload) in vec![Some(Payload::Milestone(milestone_payload())), None]
+        .into_iter()
+        .enumerate()
+    {
+        let mut builder = MessageBuilder::<Miner>::new()
+            .with_network_id(0)
+            .with_parents(parents());
+        if let Some(payload) = payload {
+            builder = builder.with_payload(payload);
+        }
+        let metadata = metadata(
+            MessageId::new([id as u8 + 11; 32]),
+            MILESTONE_INDEX,
+            LedgerInclusionState::NoTransaction,
+        );
+        milestone_data.add_full_message(FullMessage::new(builder.finish().unwrap(), metadata));
+    }
+    milestone_data.set_milestone(milestone_payload());
+    milestone_data
+}
+
+#[tokio::test]
+async fn test_fetch_wrapped_peer_cones() {
+    let peer = Peer::default();
+    let milestone_data = milestone_data();
+    // the peer wraps the archived cone in a `data` object, like every response of the Chronicle API
+    peer.bodies.lock().unwrap().insert(
+        format!("/api/permanode/archive/milestones/{}", MILESTONE_INDEX),
+        serde_json::to_vec(&serde_json::json!({ "data": &milestone_data })).unwrap(),
+    );
+    // an unwrapped cone is malformatted
+    peer.bodies.lock().unwrap().insert(
+        format!("/api/permanode/archive/milestones/{}", MILESTONE_INDEX + 2),
+        serde_json::to_vec(&MilestoneData::new(MILESTONE_INDEX + 2, CreatedBy::Syncer)).unwrap(),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let peer_url = Url::parse(&format!("http://{}/api/permanode/", listener.local_addr().unwrap())).unwrap();
+    tokio::spawn(serve_peer(listener, peer.clone()));
+    let client = reqwest::Client::new();
+    let cones = PeerCones::default();
+
+    let message_id = MessageId::new([12; 32]);
+    let full_message = cones
+        .message(&client, &peer_url, MILESTONE_INDEX, &message_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(full_message.message_id(), &message_id);
+    assert!(full_message.0.payload().is_none());
+    let milestone = cones
+        .milestone(&client, &peer_url, MILESTONE_INDEX)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(milestone.message_id(), &MessageId::new([11; 32]));
+    assert!(cones
+        .message(&client, &peer_url, MILESTONE_INDEX, &MessageId::new([13; 32]))
+        .await
+        .unwrap()
+        .is_none());
+    // the cone is fetched once, then the messages are served from it
+    assert_eq!(peer.requests.lock().unwrap().len(), 1);
+
+    // the peer did not archive the milestone
+    assert!(cones
+        .milestone(&client, &peer_url, MILESTONE_INDEX + 1)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(cones.milestone(&client, &peer_url, MILESTONE_INDEX + 2).await.is_err());
+}
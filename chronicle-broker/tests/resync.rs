@@ -0,0 +1,259 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+mod common;
+
+use bee_message::prelude::*;
+use bee_pow::providers::miner::Miner;
+use chronicle_broker::{
+    indexer::{
+        IndexRow,
+        MessageIndexer,
+    },
+    resync::{
+        archived_overlaps,
+        purge_cone,
+        unsync,
+        Invalidation,
+    },
+    syncer::Resyncs,
+    CreatedBy,
+    MilestoneData,
+    ResyncSession,
+};
+use chronicle_common::config::PartitionConfig;
+use chronicle_storage::access::{
+    FullMessage,
+    LedgerInclusionState,
+    MessageMetadata,
+};
+use common::{
+    metadata,
+    parents,
+};
+
+const MILESTONE_INDEX: u32 = 10;
+const TIMESTAMP: u64 = 1_600_000_000;
+
+fn indexer() -> MessageIndexer {
+    MessageIndexer::new(PartitionConfig {
+        partition_count: 10,
+        milestone_chunk_size: 100,
+    })
+}
+
+fn transaction() -> Payload {
+    let essence = RegularEssenceBuilder::new()
+        .with_inputs(vec![UtxoInput::new(TransactionId::new([3; 32]), 1).unwrap().into()])
+        .with_outputs(vec![SignatureLockedSingleOutput::new(
+            Address::Ed25519(Ed25519Address::new([7; 32])),
+            1_000_000,
+        )
+        .unwrap()
+        .into()])
+        .finish()
+        .unwrap();
+    let unlock_block = UnlockBlock::Signature(SignatureUnlock::Ed25519(Ed25519Signature::new(
+        [5; 32],
+        Box::new([6; 64]),
+    )));
+    Payload::Transaction(Box::new(
+        TransactionPayloadBuilder::new()
+            .with_essence(Essence::Regular(essence))
+            .with_unlock_blocks(UnlockBlocks::new(vec![unlock_block]).unwrap())
+            .finish()
+            .unwrap(),
+    ))
+}
+
+/// The cone of an indexation, a transaction and a message without payload
+fn milestone_data() -> MilestoneData {
+    let mut milestone_data = MilestoneData::new(MILESTONE_INDEX, CreatedBy::Syncer);
+    let payloads = vec![
+        Some(Payload::Indexation(Box::new(
+            IndexationPayload::new(&[0xab], &[]).unwrap(),
+        ))),
+        Some(transaction()),
+        None,
+    ];
+    for (id, payload) in payloads.into_iter().enumerate() {
+        let mut builder = MessageBuilder::<Miner>::new()
+            .with_network_id(0)
+            .with_parents(parents());
+        if let Some(payload) = payload {
+            builder = builder.with_payload(payload);
+        }
+        let metadata = MessageMetadata {
+            milestone_timestamp_referenced: Some(TIMESTAMP),
+            ..metadata(
+                MessageId::new([id as u8 + 11; 32]),
+                MILESTONE_INDEX,
+                LedgerInclusionState::Included,
+            )
+        };
+        milestone_data.add_full_message(FullMessage::new(builder.finish().unwrap(), metadata));
+    }
+    milestone_data
+}
+
+#[test]
+fn test_purge_cone() {
+    let milestone_data = milestone_data();
+    let invalidations = purge_cone(&indexer(), &milestone_data, Some(TIMESTAMP)).unwrap();
+    // the parents, indexes and addresses rows come first, then the message rows and the rollups last
+    let rows = invalidations
+        .iter()
+        .filter_map(|invalidation| match invalidation {
+            Invalidation::Row(row) => Some(row),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(rows.len() + 1, invalidations.len());
+    assert_eq!(
+        invalidations.last(),
+        Some(&Invalidation::Rollups(MILESTONE_INDEX, TIMESTAMP))
+    );
+    let first_message = rows
+        .iter()
+        .position(|row| matches!(row, IndexRow::Message(..)))
+        .unwrap();
+    assert_eq!(first_message, rows.len() - 3);
+    assert!(rows[first_message..]
+        .iter()
+        .all(|row| matches!(row, IndexRow::Message(..))));
+    // 2 parents rows per message, 1 indexes row and 1 addresses row
+    assert_eq!(rows.iter().filter(|row| matches!(row, IndexRow::Parent(..))).count(), 6);
+    assert_eq!(rows.iter().filter(|row| matches!(row, IndexRow::Index(..))).count(), 1);
+    assert_eq!(
+        rows.iter().filter(|row| matches!(row, IndexRow::Address(..))).count(),
+        1
+    );
+    // the transaction and hint rows are overwritten once the milestone is solidified again
+    assert!(rows
+        .iter()
+        .all(|row| !matches!(row, IndexRow::Transaction(..) | IndexRow::Hint(..))));
+
+    // the rollups are left as they are without the milestone timestamp
+    let invalidations = purge_cone(&indexer(), &milestone_data, None).unwrap();
+    assert!(invalidations
+        .iter()
+        .all(|invalidation| matches!(invalidation, Invalidation::Row(_))));
+}
+
+#[test]
+fn test_unsync() {
+    assert_eq!(unsync(&(10..12), false), vec![Invalidation::Sync(10..12)]);
+    // the sync records are deleted last
+    assert_eq!(
+        unsync(&(10..12), true),
+        vec![
+            Invalidation::Analytics(10..12),
+            Invalidation::Milestone(10),
+            Invalidation::Milestone(11),
+            Invalidation::Sync(10..12),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_archived_overlaps() {
+    let dir_path = std::env::temp_dir().join(format!("chronicle-resync-{}", std::process::id()));
+    tokio::fs::create_dir_all(&dir_path).await.unwrap();
+    for file_name in &[
+        "1to10.log",
+        "1to10.log.idx",
+        "10to20.log.fin",
+        "20.log.active",
+        "manifest.json",
+    ] {
+        tokio::fs::write(dir_path.join(file_name), b"").await.unwrap();
+    }
+    assert_eq!(
+        archived_overlaps(&dir_path, &(5..12)).await.unwrap(),
+        vec!["10to20.log.fin".to_owned(), "1to10.log".to_owned()]
+    );
+    assert_eq!(
+        archived_overlaps(&dir_path, &(10..20)).await.unwrap(),
+        vec!["10to20.log.fin".to_owned()]
+    );
+    // the active log file is appended from its start on
+    assert_eq!(
+        archived_overlaps(&dir_path, &(25..30)).await.unwrap(),
+        vec!["20.log.active".to_owned()]
+    );
+    tokio::fs::remove_dir_all(&dir_path).await.unwrap();
+    assert!(archived_overlaps(&dir_path, &(5..12)).await.unwrap().is_empty());
+}
+
+#[test]
+fn test_resyncs() {
+    let mut resyncs = Resyncs::default();
+    // the solidified milestones are logged unless a resync without relogging is active
+    assert!(resyncs.relog());
+    assert!(resyncs.start().is_none());
+    resyncs.push(10..13, false);
+    resyncs.push(20..22, true);
+    // nothing is reported before the resync is started
+    resyncs.progress(10, true);
+    assert!(resyncs.pop_session().is_none());
+
+    assert_eq!(resyncs.start(), Some(10..13));
+    assert!(!resyncs.relog());
+    resyncs.progress(10, true);
+    // the milestones out of the resync range are not reported
+    resyncs.progress(15, true);
+    resyncs.progress(11, false);
+    resyncs.progress(12, true);
+    resyncs.finish();
+    assert_eq!(
+        std::iter::from_fn(|| resyncs.pop_session()).collect::<Vec<_>>(),
+        vec![
+            ResyncSession::Progress {
+                from_ms: 10,
+                to_ms: 13,
+                milestone_index: 10,
+                resynced: true,
+            },
+            ResyncSession::Progress {
+                from_ms: 10,
+                to_ms: 13,
+                milestone_index: 11,
+                resynced: false,
+            },
+            ResyncSession::Progress {
+                from_ms: 10,
+                to_ms: 13,
+                milestone_index: 12,
+                resynced: true,
+            },
+            ResyncSession::Finish {
+                from_ms: 10,
+                to_ms: 13,
+                resynced: 2,
+                msg: "Unable to resync 1 milestones of the range 10..13, they're left as gaps".to_owned(),
+            },
+        ]
+    );
+    assert!(resyncs.relog());
+
+    // the next resync is processed once the previous one is finished
+    assert_eq!(resyncs.start(), Some(20..22));
+    assert!(resyncs.relog());
+    resyncs.progress(20, true);
+    resyncs.progress(21, true);
+    resyncs.finish();
+    // finishing again without an active resync reports nothing
+    resyncs.finish();
+    assert_eq!(
+        std::iter::from_fn(|| resyncs.pop_session()).last(),
+        Some(ResyncSession::Finish {
+            from_ms: 20,
+            to_ms: 22,
+            resynced: 2,
+            msg: "Resynced the milestone range 20..22".to_owned(),
+        })
+    );
+    assert!(resyncs.start().is_none());
+}
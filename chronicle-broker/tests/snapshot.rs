@@ -0,0 +1,136 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+mod common;
+
+use bee_message::prelude::*;
+use bee_pow::providers::miner::Miner;
+use chronicle_broker::{
+    snapshot::{
+        self,
+        CollectorSnapshot,
+        SolidifierSnapshot,
+    },
+    CreatedBy,
+    MilestoneData,
+};
+use chronicle_storage::access::{
+    LedgerInclusionState,
+    MessageMetadata,
+};
+use common::metadata;
+use lru::LruCache;
+use std::time::Instant;
+
+fn message(parent: u8) -> Message {
+    MessageBuilder::<Miner>::new()
+        .with_network_id(0)
+        .with_parents(Parents::new(vec![MessageId::new([parent; 32])]).unwrap())
+        .finish()
+        .unwrap()
+}
+
+/// The metadata of a solid message which is not referenced by a milestone yet
+fn unreferenced_metadata(message_id: MessageId) -> MessageMetadata {
+    MessageMetadata {
+        referenced_by_milestone_index: None,
+        ledger_inclusion_state: None,
+        white_flag_index: None,
+        milestone_timestamp_referenced: None,
+        ..metadata(message_id, 0, LedgerInclusionState::NoTransaction)
+    }
+}
+
+#[tokio::test]
+async fn collector_snapshot_round_trip() {
+    let dir = std::env::temp_dir().join(format!("chronicle-collector-snapshot-{}", std::process::id()));
+    let mut lru_msg = LruCache::<MessageId, (Option<Instant>, MilestoneIndex, Message)>::new(10);
+    let mut lru_msg_ref = LruCache::<MessageId, MessageMetadata>::new(10);
+    for i in 1..=3u8 {
+        let message_id = MessageId::new([i; 32]);
+        lru_msg.put(
+            message_id,
+            (Some(Instant::now()), MilestoneIndex(100 + i as u32), message(i)),
+        );
+        lru_msg_ref.put(message_id, unreferenced_metadata(message_id));
+    }
+    // make the first message the most recently used one
+    lru_msg.get(&MessageId::new([1; 32]));
+    let snapshot = CollectorSnapshot::capture(2, 103, 102, &lru_msg, &lru_msg_ref);
+    snapshot::save(&dir, "Collector_0", &snapshot).await.unwrap();
+    assert!(!dir.join("Collector_0.snapshot.tmp").exists());
+
+    let restored = snapshot::take::<CollectorSnapshot>(&dir, "Collector_0")
+        .await
+        .unwrap()
+        .unwrap();
+    // a snapshot is restored only once
+    assert!(snapshot::take::<CollectorSnapshot>(&dir, "Collector_0")
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!((restored.est_ms, restored.ref_ms), (103, 102));
+    let (mut new_msg, mut new_msg_ref) = (LruCache::new(10), LruCache::new(10));
+    assert!(restored.restore(2, &mut new_msg, &mut new_msg_ref));
+    let order = new_msg.iter().map(|(message_id, _)| *message_id).collect::<Vec<_>>();
+    assert_eq!(
+        order,
+        vec![
+            MessageId::new([1; 32]),
+            MessageId::new([3; 32]),
+            MessageId::new([2; 32])
+        ]
+    );
+    let (_, est_ms, cached) = new_msg.get(&MessageId::new([2; 32])).unwrap();
+    assert_eq!(*est_ms, MilestoneIndex(102));
+    assert_eq!(*cached, message(2));
+    assert_eq!(new_msg_ref.len(), 3);
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
+
+#[test]
+fn collector_snapshot_with_other_collector_count() {
+    let mut lru_msg = LruCache::new(10);
+    let lru_msg_ref = LruCache::<MessageId, MessageMetadata>::new(10);
+    lru_msg.put(MessageId::new([1; 32]), (None, MilestoneIndex(1), message(1)));
+    let snapshot = CollectorSnapshot::capture(2, 1, 1, &lru_msg, &lru_msg_ref);
+    let (mut new_msg, mut new_msg_ref) = (LruCache::new(10), LruCache::new(10));
+    assert!(!snapshot.restore(3, &mut new_msg, &mut new_msg_ref));
+    assert!(new_msg.is_empty());
+}
+
+#[tokio::test]
+async fn solidifier_snapshot_round_trip() {
+    let dir = std::env::temp_dir().join(format!("chronicle-solidifier-snapshot-{}", std::process::id()));
+    let snapshot = SolidifierSnapshot {
+        collector_count: 2,
+        milestones_data: vec![
+            MilestoneData::new(9, CreatedBy::Incoming),
+            MilestoneData::new(10, CreatedBy::Incoming),
+            MilestoneData::new(11, CreatedBy::Syncer),
+            MilestoneData::new(12, CreatedBy::Expected),
+        ],
+    };
+    snapshot::save(&dir, "Solidifier_0", &snapshot).await.unwrap();
+    let restored = snapshot::take::<SolidifierSnapshot>(&dir, "Solidifier_0")
+        .await
+        .unwrap()
+        .unwrap();
+    // the milestones below the gap start and the syncer ones are left to the syncer
+    let mut milestones = restored
+        .restorable(2, 10)
+        .unwrap()
+        .iter()
+        .map(|milestone_data| milestone_data.milestone_index())
+        .collect::<Vec<_>>();
+    milestones.sort_unstable();
+    assert_eq!(milestones, vec![10, 12]);
+    let other = SolidifierSnapshot {
+        collector_count: 2,
+        milestones_data: vec![MilestoneData::new(10, CreatedBy::Incoming)],
+    };
+    assert!(other.restorable(1, 0).is_none());
+    tokio::fs::remove_dir_all(&dir).await.unwrap();
+}
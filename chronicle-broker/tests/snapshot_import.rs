@@ -0,0 +1,130 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+use bee_message::{
+    milestone::Milestone,
+    prelude::*,
+};
+use chronicle_broker::{
+    importer::snapshot::{
+        check_milestone,
+        check_output,
+        Check,
+    },
+    ledger::snapshot::SnapshotOutput,
+};
+use chronicle_storage::access::{
+    LedgerInclusionState,
+    OutputRes,
+    UnlockRes,
+};
+
+const MILESTONE_INDEX: u32 = 10;
+const TIMESTAMP: u64 = 1_622_555_110;
+
+fn output(amount: u64) -> Output {
+    SignatureLockedSingleOutput::new(Address::Ed25519(Ed25519Address::new([7; 32])), amount)
+        .unwrap()
+        .into()
+}
+
+fn snapshot_output() -> SnapshotOutput {
+    SnapshotOutput {
+        message_id: MessageId::new([1; 32]),
+        output_id: OutputId::new(TransactionId::new([3; 32]), 0).unwrap(),
+        output: output(1_000_000),
+    }
+}
+
+/// The stored output of the snapshot output, unlocked by the given messages
+fn stored(unlocks: &[(u8, LedgerInclusionState)]) -> OutputRes {
+    OutputRes {
+        message_id: MessageId::new([1; 32]),
+        output: output(1_000_000),
+        unlock_blocks: unlocks
+            .iter()
+            .map(|(id, inclusion_state)| UnlockRes {
+                message_id: MessageId::new([*id; 32]),
+                block: UnlockBlock::Signature(SignatureUnlock::Ed25519(Ed25519Signature::new(
+                    [5; 32],
+                    Box::new([6; 64]),
+                ))),
+                inclusion_state: Some(*inclusion_state),
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn test_check_unspent_output() {
+    let output = snapshot_output();
+    assert_eq!(check_output(&output, None, Some(&stored(&[])), None), Check::Matched);
+    assert_eq!(check_output(&output, None, None, None), Check::Unknown);
+    // created by another message
+    let mut other = stored(&[]);
+    other.message_id = MessageId::new([2; 32]);
+    assert!(matches!(
+        check_output(&output, None, Some(&other), None),
+        Check::Mismatched(_)
+    ));
+    // of another amount
+    let mut other = stored(&[]);
+    other.output = self::output(1);
+    assert!(matches!(
+        check_output(&output, None, Some(&other), None),
+        Check::Mismatched(_)
+    ));
+}
+
+#[test]
+fn test_check_consumed_output() {
+    let output = snapshot_output();
+    let consumed_by = Some(TransactionId::new([9; 32]));
+    // the included message of the spending transaction unlocks the output
+    let spent = stored(&[
+        (20, LedgerInclusionState::Conflicting),
+        (21, LedgerInclusionState::Included),
+    ]);
+    let spent_by = Some(MessageId::new([21; 32]));
+    assert_eq!(
+        check_output(&output, consumed_by, Some(&spent), spent_by),
+        Check::Matched
+    );
+    // the output is spent by another included transaction
+    assert!(matches!(
+        check_output(&output, consumed_by, Some(&spent), Some(MessageId::new([22; 32]))),
+        Check::Mismatched(_)
+    ));
+    // the spending transaction is not stored as included
+    assert!(matches!(
+        check_output(&output, consumed_by, Some(&spent), None),
+        Check::Mismatched(_)
+    ));
+    // the spending message is stored, but conflicting
+    assert!(matches!(
+        check_output(&output, consumed_by, Some(&spent), Some(MessageId::new([20; 32]))),
+        Check::Mismatched(_)
+    ));
+    // the stored output is unspent
+    assert!(matches!(
+        check_output(&output, consumed_by, Some(&stored(&[])), spent_by),
+        Check::Mismatched(_)
+    ));
+    assert_eq!(check_output(&output, consumed_by, None, spent_by), Check::Unknown);
+}
+
+#[test]
+fn test_check_milestone() {
+    let milestone = Milestone::new(MessageId::new([11; 32]), TIMESTAMP);
+    assert_eq!(
+        check_milestone(MILESTONE_INDEX, TIMESTAMP, Some(&milestone)),
+        Check::Matched
+    );
+    assert!(matches!(
+        check_milestone(MILESTONE_INDEX, TIMESTAMP + 1, Some(&milestone)),
+        Check::Mismatched(_)
+    ));
+    assert_eq!(check_milestone(MILESTONE_INDEX, TIMESTAMP, None), Check::Unknown);
+}
@@ -0,0 +1,113 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "sync")]
+
+use bee_message::milestone::MilestoneIndex;
+use chronicle_broker::{
+    SyncData,
+    SyncStatus,
+};
+use chronicle_common::SyncRange;
+use chronicle_storage::access::SyncRecord;
+use std::time::{
+    Duration,
+    Instant,
+};
+use url::Url;
+
+/// The sync table of the range 1..101, where 41..51 and 81..91 are logged, 61..71 is synced but unlogged and the
+/// rest is missing
+fn sync_data() -> SyncData {
+    let record = |milestone_index, logged| SyncRecord::new(MilestoneIndex(milestone_index), Some(0), logged);
+    let records = (81..91)
+        .rev()
+        .map(|ms| record(ms, Some(0)))
+        .chain((61..71).rev().map(|ms| record(ms, None)))
+        .chain((41..51).rev().map(|ms| record(ms, Some(0))));
+    SyncData::from_records(&SyncRange::from(1..101), records)
+}
+
+fn ago(now: Instant, secs: u64) -> Instant {
+    now.checked_sub(Duration::from_secs(secs)).unwrap()
+}
+
+#[test]
+fn test_sync_report() {
+    let status = SyncStatus::default();
+    let endpoint = Url::parse("http://localhost:14265/api/v1/").unwrap();
+    status.failed(75, &endpoint, "timeout");
+    status.unreachable(75);
+    status.failed(95, &endpoint, "not found");
+    let report = status.report("permanode", &sync_data());
+    assert_eq!(report.keyspace, "permanode");
+    assert_eq!(report.logged, 20);
+    assert_eq!(report.synced, 30);
+    // the gap above the most recent synced milestone is not missing yet
+    assert_eq!(report.missing, 60);
+    assert!((report.percentage - 100.0 / 3.0).abs() < 1e-9);
+    assert_eq!(
+        report.gaps.iter().map(|gap| gap.range.clone()).collect::<Vec<_>>(),
+        vec![71..81, 51..61, 1..41]
+    );
+    let gap = &report.gaps[0];
+    assert_eq!(gap.milestones, 10);
+    assert_eq!(gap.failures, 1);
+    assert_eq!(gap.unreachable, 1);
+    assert_eq!(
+        gap.last_error.as_deref(),
+        Some("http://localhost:14265/api/v1/: timeout")
+    );
+    assert_eq!(gap.endpoints, vec![endpoint]);
+    assert_eq!(report.gaps[1].failures, 0);
+    // nothing was filled, so there is no estimate
+    assert!(report.fill_rate.is_none());
+    assert!(report.eta_secs.is_none());
+
+    // an empty sync table is not synced at all
+    let report = status.report("permanode", &SyncData::default());
+    assert_eq!(report.missing, 0);
+    assert_eq!(report.percentage, 0.0);
+    assert!(report.gaps.is_empty());
+}
+
+#[test]
+fn test_sync_report_eta() {
+    // a milestone filled every 5 seconds
+    let now = Instant::now();
+    let status = SyncStatus::default();
+    status.filled_at(1, ago(now, 10));
+    status.filled_at(2, ago(now, 5));
+    let report = status.report("permanode", &sync_data());
+    assert!((report.fill_rate.unwrap() - 0.2).abs() < 1e-6);
+    assert_eq!(report.eta_secs, Some(300));
+
+    // a stalled syncer has no fill rate
+    let status = SyncStatus::default();
+    status.filled_at(1, ago(now, 300));
+    status.filled_at(2, ago(now, 250));
+    let report = status.report("permanode", &sync_data());
+    assert!(report.fill_rate.is_none());
+    assert!(report.eta_secs.is_none());
+}
+
+#[test]
+fn test_prune_sync_attempts() {
+    let status = SyncStatus::default();
+    let endpoint = Url::parse("http://localhost:14265/api/v1/").unwrap();
+    status.failed(5, &endpoint, "timeout");
+    status.failed(95, &endpoint, "timeout");
+    // the milestone 5 got synced by another process
+    let record = |milestone_index| SyncRecord::new(MilestoneIndex(milestone_index), Some(0), Some(0));
+    let synced = SyncData::from_records(&SyncRange::from(1..101), (41..51).rev().chain(5..6).map(record));
+    status.report("permanode", &synced);
+    // so its failures are forgotten, while the ones above the synced milestones are kept
+    let report = status.report("permanode", &sync_data());
+    assert_eq!(report.gaps.last().unwrap().failures, 0);
+    let report = status.report(
+        "permanode",
+        &SyncData::from_records(&SyncRange::from(1..101), (96..100).rev().map(record)),
+    );
+    assert_eq!(report.gaps.last().unwrap().range, 1..96);
+    assert_eq!(report.gaps.last().unwrap().failures, 1);
+}
@@ -0,0 +1,87 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "sync")]
+
+use bee_message::milestone::MilestoneIndex;
+use chronicle_broker::SyncData;
+use chronicle_common::{
+    config::SyncStrategy,
+    SyncRange,
+};
+use chronicle_storage::access::SyncRecord;
+use std::ops::Range;
+
+/// The highest milestone index observed by the syncer
+const HIGHEST: u32 = 96;
+
+/// The sync table of the range 1..101, where 41..51 and 81..91 are logged, 61..71 is synced but unlogged and the
+/// rest is missing
+fn sync_data() -> SyncData {
+    let record = |milestone_index, logged| SyncRecord::new(MilestoneIndex(milestone_index), Some(0), logged);
+    let records = (81..91)
+        .rev()
+        .map(|ms| record(ms, Some(0)))
+        .chain((61..71).rev().map(|ms| record(ms, None)))
+        .chain((41..51).rev().map(|ms| record(ms, Some(0))));
+    SyncData::from_records(&SyncRange::from(1..101), records)
+}
+
+fn take_all(strategy: SyncStrategy, with_unlogged: bool, highest: u32) -> Vec<Range<u32>> {
+    let mut sync_data = sync_data();
+    let mut ranges = Vec::new();
+    while let Some(range) = sync_data.take_next(&strategy, with_unlogged, highest, ranges.len() as u64) {
+        ranges.push(range);
+    }
+    ranges
+}
+
+#[test]
+fn test_lowest_first() {
+    assert_eq!(
+        take_all(SyncStrategy::LowestFirst, false, HIGHEST),
+        vec![1..41, 51..61, 71..81, 91..96]
+    );
+    // the unlogged ranges are merged with their adjacent gaps
+    assert_eq!(
+        take_all(SyncStrategy::LowestFirst, true, HIGHEST),
+        vec![1..41, 51..81, 91..96]
+    );
+    // the milestones above the highest one are not synced yet
+    assert_eq!(
+        take_all(SyncStrategy::LowestFirst, false, 75),
+        vec![1..41, 51..61, 71..75]
+    );
+}
+
+#[test]
+fn test_highest_first() {
+    assert_eq!(
+        take_all(SyncStrategy::HighestFirst { chunk_size: 20 }, true, HIGHEST),
+        vec![91..96, 61..81, 51..61, 21..41, 1..21]
+    );
+}
+
+#[test]
+fn test_recent_window_first() {
+    // the uncomplete range 51..81 is split at the start of the window
+    assert_eq!(
+        take_all(SyncStrategy::RecentWindowFirst { window: 30 }, true, HIGHEST),
+        vec![91..96, 66..81, 1..41, 51..66]
+    );
+}
+
+#[test]
+fn test_weighted_interleave() {
+    let strategy = SyncStrategy::WeightedInterleave {
+        recent: 1,
+        old: 2,
+        chunk_size: 20,
+    };
+    assert_eq!(
+        take_all(strategy, false, HIGHEST),
+        vec![91..96, 1..21, 21..41, 71..81, 51..61]
+    );
+    assert!(strategy.verify().is_ok());
+    assert!(SyncStrategy::HighestFirst { chunk_size: 0 }.verify().is_err());
+}
@@ -0,0 +1,89 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+mod common;
+
+use bee_message::prelude::*;
+use bee_pow::providers::miner::Miner;
+use chronicle_broker::solidifier::validation::{
+    white_flag_included_messages,
+    MerkleHasher,
+};
+use chronicle_storage::access::{
+    FullMessage,
+    LedgerInclusionState,
+    MessageMetadata,
+};
+use common::metadata;
+use std::collections::HashMap;
+
+fn message_id(hex: &str) -> MessageId {
+    hex.parse().unwrap()
+}
+
+fn id(byte: u8) -> MessageId {
+    MessageId::new([byte; 32])
+}
+
+/// A cone message, whose parents are only taken from its metadata by the white-flag traversal
+fn full_message(message_id: MessageId, parents: Vec<MessageId>, state: LedgerInclusionState) -> FullMessage {
+    let message = MessageBuilder::<Miner>::new()
+        .with_network_id(0)
+        .with_parents(Parents::new(vec![MessageId::new([0; 32])]).unwrap())
+        .finish()
+        .unwrap();
+    let metadata = MessageMetadata {
+        parent_message_ids: parents,
+        white_flag_index: None,
+        ..metadata(message_id, 1, state)
+    };
+    FullMessage::new(message, metadata)
+}
+
+#[test]
+fn merkle_root_of_the_rfc_vector() {
+    // the test vector of the milestone merkle validation RFC
+    let message_ids = [
+        "52fdfc072182654f163f5f0f9a621d729566c74d10037c4d7bbb0407d1e2c649",
+        "81855ad8681d0d86d1e91e00167939cb6694d2c422acd208a0072939487f6999",
+        "eb9d18a44784045d87f3c67cf22746e995af5a25367951baa2ff6cd471c483f1",
+        "5fb90badb37c5821b6d95526a41a9504680b4e7c8b763a1b1d49d4955c848621",
+        "6325253fec738dd7a9e28bf921119c160f0702448615bbda08313f6a8eb668d2",
+        "0bf5059875921e668a5bdf2c7fc4844592d2572bcd0668d2d6c52f5054e2d083",
+        "6bf84c7174cb7476364cc3dbd968b0f7172ed85794bb358b0c3b525da1786f9f",
+    ]
+    .iter()
+    .map(|hex| message_id(hex))
+    .collect::<Vec<_>>();
+    assert_eq!(
+        hex::encode(MerkleHasher::new().digest(&message_ids)),
+        "bf67ce7ba23e8c0951b5abaec4f5524360d2c26d971ff226d3359fa70cdb0beb"
+    );
+}
+
+#[test]
+fn merkle_root_of_no_messages() {
+    // the BLAKE2b-256 hash of the empty input
+    assert_eq!(
+        hex::encode(MerkleHasher::new().digest(&[])),
+        "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+    );
+}
+
+#[test]
+fn white_flag_order() {
+    // milestone -> [a, b], a -> [c, d], b -> [d, e], d -> [c], c and e reference older milestones
+    let (a, b, c, d, e) = (id(1), id(2), id(3), id(4), id(5));
+    let mut messages = HashMap::new();
+    messages.insert(a, full_message(a, vec![c, d], LedgerInclusionState::Included));
+    messages.insert(b, full_message(b, vec![d, e], LedgerInclusionState::Included));
+    messages.insert(c, full_message(c, vec![id(6)], LedgerInclusionState::Included));
+    messages.insert(d, full_message(d, vec![c], LedgerInclusionState::Conflicting));
+    messages.insert(e, full_message(e, vec![id(7)], LedgerInclusionState::Included));
+    // post-order depth first, parents from left to right, only the included transactions
+    assert_eq!(white_flag_included_messages(&[a, b], &messages), vec![c, a, e, b]);
+    // the order of the milestone parents matters
+    assert_eq!(white_flag_included_messages(&[b, a], &messages), vec![c, e, b, a]);
+}
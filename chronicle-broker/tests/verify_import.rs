@@ -0,0 +1,123 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+mod common;
+
+use bee_message::prelude::*;
+use bee_pow::providers::miner::Miner;
+use chronicle_broker::{
+    importer::verify::verify_cone,
+    VerifyReport,
+};
+use chronicle_storage::access::{
+    FullMessage,
+    LedgerInclusionState,
+    MessageMetadata,
+};
+use common::metadata;
+use std::{
+    collections::HashMap,
+    sync::Arc,
+};
+
+const MILESTONE_INDEX: u32 = 10;
+
+/// A message of the given parents, which is told apart by its indexation payload
+fn full_message(id: u8, parents: &[u8], referenced_by: u32) -> (MessageId, FullMessage) {
+    let message_id = MessageId::new([id; 32]);
+    let parents = parents
+        .iter()
+        .map(|parent| MessageId::new([*parent; 32]))
+        .collect::<Vec<_>>();
+    let message = MessageBuilder::<Miner>::new()
+        .with_network_id(0)
+        .with_parents(Parents::new(parents.clone()).unwrap())
+        .with_payload(Payload::Indexation(Box::new(
+            IndexationPayload::new(&[id], &[]).unwrap(),
+        )))
+        .finish()
+        .unwrap();
+    let metadata = MessageMetadata {
+        parent_message_ids: parents,
+        ..metadata(message_id, referenced_by, LedgerInclusionState::NoTransaction)
+    };
+    (message_id, FullMessage::new(message, metadata))
+}
+
+#[tokio::test]
+async fn test_verify_cone() {
+    // the cone of the milestone is 1 <- [2, 3], 3 <- [4, 9] and 4 <- [9], where 9 belongs to the previous milestone
+    let messages = vec![
+        full_message(1, &[2, 3], MILESTONE_INDEX),
+        full_message(2, &[9], MILESTONE_INDEX),
+        full_message(3, &[4, 9], MILESTONE_INDEX),
+        full_message(4, &[9], MILESTONE_INDEX),
+        full_message(5, &[9], MILESTONE_INDEX),
+        full_message(9, &[8], MILESTONE_INDEX - 1),
+    ]
+    .into_iter()
+    .collect::<HashMap<_, _>>();
+    let archived = |ids: &[u8]| {
+        ids.iter()
+            .map(|id| MessageId::new([*id; 32]))
+            .map(|message_id| (message_id, messages[&message_id].clone()))
+            .collect::<HashMap<_, _>>()
+    };
+    let mut stored = archived(&[1, 2, 3, 4, 9]);
+    // the metadata of an archived message differs
+    if let Some(FullMessage(_, metadata)) = stored.get_mut(&MessageId::new([2; 32])) {
+        metadata.ledger_inclusion_state = Some(LedgerInclusionState::Conflicting);
+    }
+    let stored = Arc::new(stored);
+    let fetch = |message_id: MessageId| {
+        let stored = stored.clone();
+        async move {
+            Ok(stored
+                .get(&message_id)
+                .map(|FullMessage(message, metadata)| (Some(message.clone()), Some(metadata.clone()))))
+        }
+    };
+
+    let report = verify_cone(MILESTONE_INDEX, archived(&[1, 2]), 2, fetch).await.unwrap();
+    assert_eq!(report.messages.matched, 2);
+    assert_eq!(report.metadata.matched, 1);
+    assert_eq!(report.metadata.mismatched.count, 1);
+    assert!(report.messages.missing.is_empty());
+    // the stored messages 3 and 4 are referenced by the milestone but not archived, unlike 9
+    assert_eq!(report.messages.extra.count, 2);
+    assert_eq!(report.metadata.extra.count, 2);
+    assert!(report.messages.extra.contains(MILESTONE_INDEX));
+    assert!(!report.is_consistent());
+
+    // an archived message which is not stored is missing, along with its metadata
+    let report = verify_cone(MILESTONE_INDEX, archived(&[5]), 2, fetch).await.unwrap();
+    assert_eq!(report.messages.missing.count, 1);
+    assert_eq!(report.metadata.missing.count, 1);
+    assert!(report.messages.extra.is_empty());
+}
+
+#[test]
+fn test_verify_report_merge() {
+    let mut report = VerifyReport::new(100..110);
+    report.messages.matched = 10;
+    for milestone_index in [103, 101, 102, 108] {
+        report.messages.missing.add(milestone_index);
+    }
+    assert_eq!(report.messages.missing.ranges, vec![101..104, 108..109]);
+    // the shard reports are merged into a single report of the whole range
+    let mut other = VerifyReport::new(110..120);
+    other.messages.missing.add(109);
+    other.messages.missing.add(110);
+    other.sync.extra.add(115);
+    report.merge(other);
+    assert_eq!(report.from_ms, 100);
+    assert_eq!(report.to_ms, 120);
+    assert_eq!(report.messages.missing.count, 6);
+    assert_eq!(report.messages.missing.ranges, vec![101..104, 108..111]);
+    assert!(report.messages.missing.contains(110));
+    assert!(!report.messages.missing.contains(104));
+    assert!(!report.is_consistent());
+    assert!(VerifyReport::new(0..10).is_consistent());
+}
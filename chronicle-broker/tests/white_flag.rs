@@ -0,0 +1,103 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+#![cfg(feature = "application")]
+
+mod common;
+
+use bee_message::prelude::*;
+use bee_pow::providers::miner::Miner;
+use chronicle_broker::{
+    CreatedBy,
+    MilestoneData,
+};
+use chronicle_storage::access::{
+    FullMessage,
+    LedgerInclusionState,
+    MessageMetadata,
+};
+use common::metadata;
+
+const MILESTONE_INDEX: u32 = 10;
+const TIMESTAMP: u64 = 1_600_000_000;
+
+fn id(byte: u8) -> MessageId {
+    MessageId::new([byte; 32])
+}
+
+fn parents(bytes: &[u8]) -> Parents {
+    Parents::new(bytes.iter().copied().map(id).collect()).unwrap()
+}
+
+fn milestone_payload() -> Box<MilestonePayload> {
+    let essence = MilestonePayloadEssence::new(
+        MilestoneIndex(MILESTONE_INDEX),
+        TIMESTAMP,
+        parents(&[12, 13]),
+        [0; 32],
+        0,
+        0,
+        vec![[7; 32]],
+        None,
+    )
+    .unwrap();
+    Box::new(MilestonePayload::new(essence, vec![Box::new([8; 64])]).unwrap())
+}
+
+fn full_message(message_id: u8, parent_ids: &[u8], payload: Option<Payload>) -> FullMessage {
+    let mut builder = MessageBuilder::<Miner>::new()
+        .with_network_id(0)
+        .with_parents(parents(parent_ids));
+    if let Some(payload) = payload {
+        builder = builder.with_payload(payload);
+    }
+    // the white-flag index and the milestone timestamp are left for the solidifier to set
+    let metadata = MessageMetadata {
+        parent_message_ids: parents(parent_ids).to_vec(),
+        white_flag_index: None,
+        milestone_timestamp_referenced: None,
+        ..metadata(id(message_id), MILESTONE_INDEX, LedgerInclusionState::NoTransaction)
+    };
+    FullMessage::new(builder.finish().unwrap(), metadata)
+}
+
+/// The cone of the milestone message 11, where 1 was referenced by a previous milestone:
+/// 11 -> 12, 13; 12 -> 1, 14; 13 -> 14; 14 -> 1
+fn milestone_data() -> MilestoneData {
+    let mut milestone_data = MilestoneData::new(MILESTONE_INDEX, CreatedBy::Syncer);
+    milestone_data.add_full_message(full_message(
+        11,
+        &[12, 13],
+        Some(Payload::Milestone(milestone_payload())),
+    ));
+    milestone_data.add_full_message(full_message(12, &[1, 14], None));
+    milestone_data.add_full_message(full_message(13, &[14], None));
+    milestone_data.add_full_message(full_message(14, &[1], None));
+    milestone_data.set_milestone(milestone_payload());
+    milestone_data
+}
+
+#[test]
+fn test_white_flag_order() {
+    // the parents come first, in their order, and the milestone message last
+    assert_eq!(
+        milestone_data().white_flag_order(),
+        vec![id(14), id(12), id(13), id(11)]
+    );
+    // without the milestone message there's nothing to traverse
+    let mut milestone_data = MilestoneData::new(MILESTONE_INDEX, CreatedBy::Syncer);
+    milestone_data.add_full_message(full_message(14, &[1], None));
+    assert!(milestone_data.white_flag_order().is_empty());
+}
+
+#[test]
+fn test_confirm_solidified_milestone() {
+    let mut milestone_data = milestone_data();
+    assert!(milestone_data.check_if_completed());
+    milestone_data.confirm();
+    let metadata = |byte| milestone_data.messages()[&id(byte)].1.clone();
+    for (white_flag_index, byte) in [14, 12, 13, 11].iter().enumerate() {
+        assert_eq!(metadata(*byte).white_flag_index, Some(white_flag_index as u32));
+        assert_eq!(metadata(*byte).milestone_timestamp_referenced, Some(TIMESTAMP));
+    }
+}
@@ -10,6 +10,17 @@ use chronicle::{
     SocketMsg,
 };
 use chronicle_broker::{
+    archive::{
+        self,
+        ArchiveFormat,
+        ArchiveReader,
+        DEFAULT_COMPRESSION_LEVEL,
+    },
+    ledger::{
+        self,
+        snapshot::Snapshot,
+        LedgerCheckpoints,
+    },
     merge::{
         LogPaths,
         Merger,
@@ -34,6 +45,7 @@ use futures::{
     StreamExt,
 };
 use indicatif::{
+    HumanDuration,
     ProgressBar,
     ProgressStyle,
 };
@@ -45,6 +57,7 @@ use std::{
         PathBuf,
     },
     process::Command,
+    time::Duration,
 };
 use tokio_tungstenite::{
     connect_async,
@@ -137,6 +150,9 @@ async fn process() -> anyhow::Result<()> {
         ("nodes", Some(matches)) => nodes(matches).await?,
         ("brokers", Some(matches)) => brokers(matches).await?,
         ("archive", Some(matches)) => archive(matches).await?,
+        ("sync", Some(matches)) => sync(matches).await?,
+        ("ledger", Some(matches)) => ledger(matches).await?,
+        ("snapshot", Some(matches)) => snapshot(matches).await?,
         _ => (),
     }
     Ok(())
@@ -187,8 +203,20 @@ async fn nodes<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Wrap a topology change, which targets the network storing in the given keyspace, or the first network
+fn topology(keyspace: Option<&str>, topology: BrokerTopology) -> ChronicleBrokerThrough {
+    match keyspace {
+        Some(keyspace) => ChronicleBrokerThrough::NetworkTopology {
+            keyspace: keyspace.to_owned(),
+            topology,
+        },
+        None => ChronicleBrokerThrough::Topology(topology),
+    }
+}
+
 async fn brokers<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
     let mut config = VersionedConfig::load(None)?.verify().await?;
+    let keyspace = matches.value_of("keyspace");
     match matches.subcommand() {
         ("add", Some(subcommand)) => {
             let mqtt_addresses = subcommand
@@ -203,12 +231,14 @@ async fn brokers<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
                 let mut messages = Vec::new();
                 for mqtt_address in mqtt_addresses.clone() {
                     messages.push(Message::text(serde_json::to_string(
-                        &BrokerSocketMsg::ChronicleBroker(ChronicleBrokerThrough::Topology(
+                        &BrokerSocketMsg::ChronicleBroker(topology(
+                            keyspace,
                             BrokerTopology::AddMqttMessages(mqtt_address.clone()),
                         )),
                     )?));
                     messages.push(Message::text(serde_json::to_string(
-                        &BrokerSocketMsg::ChronicleBroker(ChronicleBrokerThrough::Topology(
+                        &BrokerSocketMsg::ChronicleBroker(topology(
+                            keyspace,
                             BrokerTopology::AddMqttMessagesReferenced(mqtt_address),
                         )),
                     )?));
@@ -244,12 +274,14 @@ async fn brokers<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
             if !matches.is_present("skip-connection") {
                 let mut messages = Vec::new();
                 for mqtt_address in mqtt_addresses.clone() {
-                    messages.push(Message::text(serde_json::to_string(&SocketMsg::Broker(
-                        ChronicleBrokerThrough::Topology(BrokerTopology::RemoveMqttMessages(mqtt_address.clone())),
-                    ))?));
-                    messages.push(Message::text(serde_json::to_string(&SocketMsg::Broker(
-                        ChronicleBrokerThrough::Topology(BrokerTopology::RemoveMqttMessagesReferenced(mqtt_address)),
-                    ))?));
+                    messages.push(Message::text(serde_json::to_string(&SocketMsg::Broker(topology(
+                        keyspace,
+                        BrokerTopology::RemoveMqttMessages(mqtt_address.clone()),
+                    )))?));
+                    messages.push(Message::text(serde_json::to_string(&SocketMsg::Broker(topology(
+                        keyspace,
+                        BrokerTopology::RemoveMqttMessagesReferenced(mqtt_address),
+                    )))?));
                 }
                 let (mut stream, _) =
                     connect_async(Url::parse(&format!("ws://{}/", config.websocket_address))?).await?;
@@ -296,7 +328,11 @@ async fn archive<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
     let config = VersionedConfig::load(None)?.verify().await?;
     match matches.subcommand() {
         ("import", Some(subcommand)) => {
-            let dir = subcommand.value_of("directory").unwrap_or("");
+            let from_sink = subcommand.is_present("from-sink");
+            let mirror = subcommand.value_of("mirror").map(Url::parse).transpose()?;
+            let dir = subcommand
+                .value_of("directory")
+                .unwrap_or(if from_sink || mirror.is_some() { "imports" } else { "" });
             let mut path = PathBuf::from(dir);
             if path.is_relative() {
                 if let Some(logs_dir) = config.broker_config.logs_dir.as_ref() {
@@ -329,16 +365,34 @@ async fn archive<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
                 range
             );
             if is_url {
-                panic!("URL imports are not currently supported!");
+                bail!("The directory can't be a URL, use --mirror to import from an archive mirror!");
             }
             let import_type = if subcommand.is_present("analytics") {
                 ImportType::Analytics
+            } else if subcommand.is_present("verify") {
+                ImportType::Verify
             } else {
                 ImportType::All
             };
+            let report_path = subcommand.value_of("report").map(PathBuf::from);
+            let filter = ImportFilter {
+                payload_kinds: subcommand
+                    .values_of("payload")
+                    .map(|kinds| kinds.map(|kind| kind.parse()).collect::<Result<Vec<PayloadKind>, _>>())
+                    .transpose()
+                    .map_err(|e| anyhow!(e))?
+                    .unwrap_or_default(),
+                index_prefix: subcommand
+                    .value_of("index-prefix")
+                    .map(|prefix| prefix.bytes().map(|byte| format!("{:02x}", byte)).collect()),
+                addresses: subcommand
+                    .values_of("address")
+                    .map(|addresses| addresses.map(|address| address.to_lowercase()).collect())
+                    .unwrap_or_default(),
+            };
             let sty = ProgressStyle::default_bar()
                 .template(
-                    "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} {msg} ({eta})",
+                    "{spinner:.green} {prefix} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} {msg} ({eta})",
                 )
                 .progress_chars("##-");
             let mut active_progress_bars: std::collections::HashMap<(u32, u32), ()> = std::collections::HashMap::new();
@@ -346,14 +400,18 @@ async fn archive<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
             pb.set_style(sty.clone());
             let (mut stream, _) = connect_async(Url::parse(&format!("ws://{}/", config.websocket_address))?).await?;
             stream
-                .send(Message::text(serde_json::to_string(&SocketMsg::Broker(
-                    ChronicleBrokerThrough::Topology(BrokerTopology::Import {
+                .send(Message::text(serde_json::to_string(&SocketMsg::Broker(topology(
+                    subcommand.value_of("keyspace"),
+                    BrokerTopology::Import {
                         path,
                         resume,
                         import_range: Some(range),
                         import_type,
-                    }),
-                ))?))
+                        from_sink,
+                        mirror,
+                        filter: Some(filter).filter(|filter| !filter.is_empty()),
+                    },
+                )))?))
                 .await?;
             while let Some(msg) = stream.next().await {
                 match msg {
@@ -413,9 +471,46 @@ async fn archive<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
                                                         pb.println(m);
                                                     }
                                                 }
+                                                ImporterSession::Progress {
+                                                    imported,
+                                                    skipped,
+                                                    total,
+                                                    running_shards,
+                                                    pending_shards,
+                                                    milestones_per_sec,
+                                                    eta_secs,
+                                                } => {
+                                                    pb.set_prefix(format!(
+                                                        "{}/{} milestones ({} skipped, {:.1}/s, {} running and {} pending shards, ETA: {})",
+                                                        imported + skipped,
+                                                        total,
+                                                        skipped,
+                                                        milestones_per_sec,
+                                                        running_shards,
+                                                        pending_shards,
+                                                        eta_secs
+                                                            .map(|secs| HumanDuration(Duration::from_secs(secs)).to_string())
+                                                            .unwrap_or_else(|| "unknown".to_owned())
+                                                    ));
+                                                }
+                                                ImporterSession::Verified(report) => {
+                                                    pb.println(format_verify_report(&report));
+                                                    if let Some(report_path) = report_path.as_ref() {
+                                                        std::fs::write(
+                                                            report_path,
+                                                            serde_json::to_vec_pretty(&report)?,
+                                                        )?;
+                                                        pb.println(format!(
+                                                            "Wrote the verification report to {}",
+                                                            report_path.to_string_lossy()
+                                                        ));
+                                                    }
+                                                }
                                                 ImporterSession::PathError { path, msg } => {
                                                     pb.println(format!("ErrorPath: {:?}, msg: {:?}", path, msg))
                                                 }
+                                                ImporterSession::SnapshotProgress { .. }
+                                                | ImporterSession::SnapshotImported(_) => (),
                                                 ImporterSession::Close => {
                                                     pb.finish_with_message("done");
                                                     break;
@@ -445,13 +540,517 @@ async fn archive<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
                 }
             }
         }
+        ("export", Some(matches)) => export_archive(matches).await?,
         ("cleanup", Some(matches)) => cleanup_archive(matches).await?,
-        ("validate", Some(_matches)) => validate_archive().await?,
+        ("validate", Some(matches)) => validate_archive(matches).await?,
+        ("convert", Some(matches)) => convert_archive(matches).await?,
+        ("get", Some(matches)) => get_archived_milestone(matches).await?,
+        _ => (),
+    }
+    Ok(())
+}
+
+fn format_verify_report(report: &VerifyReport) -> String {
+    let mut lines = vec![format!(
+        "Verified milestones {} to {}: {}",
+        report.from_ms,
+        report.to_ms,
+        if report.is_consistent() {
+            "the database matches the archive"
+        } else {
+            "the database differs from the archive"
+        }
+    )];
+    for (table, diff) in [
+        ("messages", &report.messages),
+        ("metadata", &report.metadata),
+        ("sync", &report.sync),
+        ("analytics", &report.analytics),
+    ] {
+        lines.push(format!(
+            "  {}: {} matched, {} missing, {} mismatched, {} extra",
+            table, diff.matched, diff.missing.count, diff.mismatched.count, diff.extra.count
+        ));
+        for (kind, ranges) in [
+            ("missing", &diff.missing),
+            ("mismatched", &diff.mismatched),
+            ("extra", &diff.extra),
+        ] {
+            if !ranges.is_empty() {
+                let ranges = ranges
+                    .ranges
+                    .iter()
+                    .map(|range| format!("{}..{}", range.start, range.end))
+                    .collect::<Vec<_>>();
+                lines.push(format!("    {} in milestones: {}", kind, ranges.join(", ")));
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+async fn export_archive<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
+    let config = VersionedConfig::load(None)?.verify().await?;
+    let range = Regex::new(r"(\d+)\D+(\d+)")?
+        .captures(matches.value_of("range").unwrap())
+        .ok_or_else(|| anyhow!("Malformatted range!"))
+        .and_then(|c| {
+            let start = c.get(1).unwrap().as_str().parse::<u32>()?;
+            let end = c.get(2).unwrap().as_str().parse::<u32>()?;
+            Ok(start..end)
+        })?;
+    let mut path = PathBuf::from(matches.value_of("directory").unwrap_or("exports"));
+    if path.is_relative() {
+        if let Some(logs_dir) = config.broker_config.logs_dir.as_ref() {
+            path = Path::new(&logs_dir).join(path);
+        }
+    }
+    println!("Path: {}, range: {:?}", path.to_string_lossy(), range);
+    let pb = ProgressBar::new((range.end - range.start) as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg} ({eta})")
+            .progress_chars("##-"),
+    );
+    let (mut stream, _) = connect_async(Url::parse(&format!("ws://{}/", config.websocket_address))?).await?;
+    stream
+        .send(Message::text(serde_json::to_string(&SocketMsg::Broker(topology(
+            matches.value_of("keyspace"),
+            BrokerTopology::Export {
+                path: path.clone(),
+                export_range: range.clone(),
+            },
+        )))?))
+        .await?;
+    while let Some(msg) = stream.next().await {
+        match msg {
+            Ok(Message::Text(ref s)) => {
+                let session = serde_json::from_str::<serde_json::Value>(s)
+                    .ok()
+                    .and_then(|json| json.get("ChronicleBroker").cloned())
+                    .and_then(|json| serde_json::from_value::<ExporterSession>(json).ok());
+                match session {
+                    Some(ExporterSession::Progress {
+                        from_ms,
+                        to_ms,
+                        milestone_index,
+                        exported,
+                    }) if from_ms == range.start && to_ms == range.end => {
+                        let exported_or_missing = if exported { "exported" } else { "missing" };
+                        pb.set_message(format!("{} #{}", exported_or_missing, milestone_index));
+                        pb.inc(1);
+                    }
+                    Some(ExporterSession::LogFile { path }) => {
+                        pb.println(format!("LogFile: {}", path.to_string_lossy()));
+                    }
+                    Some(ExporterSession::Finish {
+                        from_ms,
+                        to_ms,
+                        exported,
+                        missing,
+                        msg,
+                    }) if from_ms == range.start && to_ms == range.end => {
+                        pb.finish_with_message(msg);
+                        println!("Exported {} milestones", exported);
+                        if !missing.is_empty() {
+                            let ranges = missing
+                                .ranges
+                                .iter()
+                                .map(|range| format!("{}..{}", range.start, range.end))
+                                .collect::<Vec<_>>();
+                            println!(
+                                "Unable to reconstruct {} milestones: {}",
+                                missing.count,
+                                ranges.join(", ")
+                            );
+                        }
+                        break;
+                    }
+                    _ => (),
+                }
+            }
+            Ok(Message::Close(c)) => {
+                if let Some(c) = c {
+                    println!("Closed connection: {}", c);
+                }
+                break;
+            }
+            Ok(_) => (),
+            Err(e) => {
+                println!("Error received from Chronicle: {}", e);
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn sync<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
+    match matches.subcommand() {
+        ("status", Some(subcommand)) => sync_status(subcommand).await?,
+        ("resync", Some(subcommand)) => resync(subcommand).await?,
+        _ => (),
+    }
+    Ok(())
+}
+
+async fn sync_status<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
+    let config = VersionedConfig::load(None)?.verify().await?;
+    let (mut stream, _) = connect_async(Url::parse(&format!("ws://{}/", config.websocket_address))?).await?;
+    stream
+        .send(Message::text(serde_json::to_string(&SocketMsg::Broker(
+            ChronicleBrokerThrough::SyncReport,
+        ))?))
+        .await?;
+    while let Some(msg) = stream.next().await {
+        match msg {
+            Ok(Message::Text(ref s)) => {
+                let reports = serde_json::from_str::<serde_json::Value>(s)
+                    .ok()
+                    .and_then(|json| json.get("ChronicleBroker").cloned())
+                    .and_then(|json| serde_json::from_value::<Vec<SyncReport>>(json).ok());
+                if let Some(reports) = reports {
+                    if matches.is_present("json") {
+                        println!("{}", serde_json::to_string_pretty(&reports)?);
+                    } else {
+                        reports.iter().for_each(print_sync_report);
+                    }
+                    break;
+                }
+            }
+            Ok(Message::Close(c)) => {
+                if let Some(c) = c {
+                    println!("Closed connection: {}", c);
+                }
+                break;
+            }
+            Ok(_) => (),
+            Err(e) => {
+                println!("Error received from Chronicle: {}", e);
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn resync<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
+    let config = VersionedConfig::load(None)?.verify().await?;
+    let range = Regex::new(r"(\d+)\D+(\d+)")?
+        .captures(matches.value_of("range").unwrap())
+        .ok_or_else(|| anyhow!("Malformatted range!"))
+        .and_then(|c| {
+            let start = c.get(1).unwrap().as_str().parse::<u32>()?;
+            let end = c.get(2).unwrap().as_str().parse::<u32>()?;
+            Ok(start..end)
+        })?;
+    let relog = matches.is_present("relog");
+    let purge = matches.is_present("purge");
+    println!("Resync range: {:?}, relog: {}, purge: {}", range, relog, purge);
+    let pb = ProgressBar::new((range.end - range.start) as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg} ({eta})")
+            .progress_chars("##-"),
+    );
+    let (mut stream, _) = connect_async(Url::parse(&format!("ws://{}/", config.websocket_address))?).await?;
+    stream
+        .send(Message::text(serde_json::to_string(&SocketMsg::Broker(
+            ChronicleBrokerThrough::Topology(BrokerTopology::Resync {
+                range: range.clone(),
+                relog,
+                purge,
+            }),
+        ))?))
+        .await?;
+    while let Some(msg) = stream.next().await {
+        match msg {
+            Ok(Message::Text(ref s)) => {
+                let session = serde_json::from_str::<serde_json::Value>(s)
+                    .ok()
+                    .and_then(|json| json.get("ChronicleBroker").cloned())
+                    .and_then(|json| serde_json::from_value::<ResyncSession>(json).ok());
+                match session {
+                    Some(ResyncSession::Progress {
+                        from_ms,
+                        to_ms,
+                        milestone_index,
+                        resynced,
+                    }) if from_ms == range.start && to_ms == range.end => {
+                        let resynced_or_skipped = if resynced { "resynced" } else { "skipped" };
+                        pb.set_message(format!("{} #{}", resynced_or_skipped, milestone_index));
+                        pb.inc(1);
+                    }
+                    Some(ResyncSession::Finish {
+                        from_ms,
+                        to_ms,
+                        resynced,
+                        msg,
+                    }) if from_ms == range.start && to_ms == range.end => {
+                        pb.finish_with_message(msg);
+                        println!("Resynced {} milestones", resynced);
+                        break;
+                    }
+                    _ => (),
+                }
+            }
+            Ok(Message::Close(c)) => {
+                if let Some(c) = c {
+                    println!("Closed connection: {}", c);
+                }
+                break;
+            }
+            Ok(_) => (),
+            Err(e) => {
+                println!("Error received from Chronicle: {}", e);
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn ledger<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
+    match matches.subcommand() {
+        ("replay", Some(subcommand)) => replay_ledger(subcommand).await?,
+        ("verify", Some(subcommand)) => verify_ledger(subcommand).await?,
+        _ => (),
+    }
+    Ok(())
+}
+
+async fn snapshot<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
+    match matches.subcommand() {
+        ("import", Some(subcommand)) => import_snapshot(subcommand).await?,
         _ => (),
     }
     Ok(())
 }
 
+async fn import_snapshot<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
+    let config = VersionedConfig::load(None)?.verify().await?;
+    let path = PathBuf::from(matches.value_of("path").unwrap());
+    let json = matches.is_present("json");
+    let pb = ProgressBar::new(0);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+            .progress_chars("##-"),
+    );
+    let (mut stream, _) = connect_async(Url::parse(&format!("ws://{}/", config.websocket_address))?).await?;
+    stream
+        .send(Message::text(serde_json::to_string(&SocketMsg::Broker(
+            ChronicleBrokerThrough::Topology(BrokerTopology::Import {
+                path,
+                resume: false,
+                import_range: None,
+                import_type: ImportType::Snapshot,
+                from_sink: false,
+                mirror: None,
+                filter: None,
+            }),
+        ))?))
+        .await?;
+    let mut consistent = true;
+    while let Some(msg) = stream.next().await {
+        match msg {
+            Ok(Message::Text(ref s)) => {
+                let session = serde_json::from_str::<serde_json::Value>(s)
+                    .ok()
+                    .and_then(|json| json.get("ChronicleBroker").cloned())
+                    .and_then(|json| serde_json::from_value::<ImporterSession>(json).ok());
+                match session {
+                    Some(ImporterSession::SnapshotProgress { path, imported, total }) => {
+                        pb.set_length(total);
+                        pb.set_position(imported);
+                        pb.set_message(path.to_string_lossy().into_owned());
+                    }
+                    Some(ImporterSession::SnapshotImported(report)) => {
+                        consistent &= report.is_consistent();
+                        if json {
+                            pb.println(serde_json::to_string_pretty(&report)?);
+                        } else {
+                            pb.println(format!(
+                                "Imported the {} snapshot {} of milestone {}: {} outputs, {} milestone diffs, {} matched, {} unknown and {} mismatched records",
+                                report.kind.as_str(),
+                                report.path.to_string_lossy(),
+                                report.ledger_index,
+                                report.outputs,
+                                report.milestone_diffs,
+                                report.matched,
+                                report.unknown,
+                                report.mismatched
+                            ));
+                            for mismatch in report.mismatches.iter() {
+                                pb.println(format!("  {}", mismatch));
+                            }
+                        }
+                    }
+                    Some(ImporterSession::PathError { path, msg }) => {
+                        pb.println(format!("ErrorPath: {:?}, msg: {:?}", path, msg));
+                        consistent = false;
+                    }
+                    Some(ImporterSession::Close) => {
+                        pb.finish_with_message("done");
+                        break;
+                    }
+                    _ => (),
+                }
+            }
+            Ok(Message::Close(c)) => {
+                if let Some(c) = c {
+                    println!("Closed connection: {}", c);
+                }
+                break;
+            }
+            Ok(_) => (),
+            Err(e) => {
+                println!("Error received from Chronicle: {}", e);
+                break;
+            }
+        }
+    }
+    if !consistent {
+        bail!("The snapshots don't match the stored transactions");
+    }
+    Ok(())
+}
+
+async fn replay_ledger<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
+    let config = VersionedConfig::load(None)?.verify().await?;
+    let to = matches.value_of("to").map(|to| to.parse::<u32>()).transpose()?;
+    let snapshot = matches.value_of("snapshot").map(PathBuf::from);
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(ProgressStyle::default_spinner().template("{spinner:.green} [{elapsed_precise}] {pos} {msg}"));
+    let (mut stream, _) = connect_async(Url::parse(&format!("ws://{}/", config.websocket_address))?).await?;
+    stream
+        .send(Message::text(serde_json::to_string(&SocketMsg::Broker(
+            ChronicleBrokerThrough::Topology(BrokerTopology::ReplayLedger { to, snapshot }),
+        ))?))
+        .await?;
+    while let Some(msg) = stream.next().await {
+        match msg {
+            Ok(Message::Text(ref s)) => {
+                let session = serde_json::from_str::<serde_json::Value>(s)
+                    .ok()
+                    .and_then(|json| json.get("ChronicleBroker").cloned())
+                    .and_then(|json| serde_json::from_value::<LedgerSession>(json).ok());
+                match session {
+                    Some(LedgerSession::Progress { ledger_index }) => {
+                        pb.set_message(format!("replayed #{}", ledger_index));
+                        pb.inc(1);
+                    }
+                    Some(LedgerSession::Finish { ledger_index, msg }) => {
+                        pb.finish_with_message(msg);
+                        if let Some(ledger_index) = ledger_index {
+                            println!("Replayed the ledger up to milestone {}", ledger_index);
+                        }
+                        break;
+                    }
+                    _ => (),
+                }
+            }
+            Ok(Message::Close(c)) => {
+                if let Some(c) = c {
+                    println!("Closed connection: {}", c);
+                }
+                break;
+            }
+            Ok(_) => (),
+            Err(e) => {
+                println!("Error received from Chronicle: {}", e);
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn verify_ledger<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
+    let config = VersionedConfig::load(None)?.verify().await?;
+    let ledger_config = config
+        .broker_config
+        .ledger
+        .as_ref()
+        .ok_or_else(|| anyhow!("No ledger in the config, the ledger replay is not configured"))?;
+    let logs_dir = config
+        .broker_config
+        .logs_dir
+        .as_ref()
+        .ok_or_else(|| anyhow!("No LogsDir in the config, Chronicle is running without archiver"))?;
+    let keyspace = match matches.value_of("keyspace") {
+        Some(keyspace) => keyspace.to_owned(),
+        None => config
+            .storage_config
+            .keyspaces
+            .first()
+            .map(|keyspace| keyspace.name.clone())
+            .unwrap_or("permanode".to_owned()),
+    };
+    let snapshot = Snapshot::from_file(Path::new(matches.value_of("snapshot").unwrap()))?;
+    let checkpoints = LedgerCheckpoints::of_keyspace(&ledger_config.checkpoints_dir, &keyspace);
+    let reader = ArchiveReader::new(PathBuf::from(logs_dir));
+    // the verification replays the archived milestones regardless of the distance to the checkpoint
+    let state = ledger::state_at(&checkpoints, snapshot.ledger_index, u32::MAX, |milestone_index| {
+        reader.get(milestone_index)
+    })
+    .await?;
+    let diff = state.diff(&snapshot)?;
+    if matches.is_present("json") {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        println!(
+            "Ledger at milestone {}: {} unspent outputs, supply {}",
+            diff.ledger_index,
+            state.outputs().len(),
+            state.supply()
+        );
+        for (kind, output_ids) in [
+            ("Missing", &diff.missing),
+            ("Unexpected", &diff.unexpected),
+            ("Mismatched", &diff.mismatched),
+        ] {
+            for output_id in output_ids.iter() {
+                println!("{} output {}", kind, output_id);
+            }
+        }
+    }
+    if !diff.is_consistent() {
+        bail!("The replayed ledger doesn't match the snapshot");
+    }
+    println!("The replayed ledger matches the snapshot");
+    Ok(())
+}
+
+fn print_sync_report(report: &SyncReport) {
+    println!("Keyspace: {}", report.keyspace);
+    println!(
+        "Synced: {} milestones ({} logged), missing: {}, complete: {:.2}%",
+        report.synced, report.logged, report.missing, report.percentage
+    );
+    match (report.fill_rate, report.eta_secs) {
+        (Some(rate), Some(eta)) => println!(
+            "Filling {:.2} milestones/s, estimated time to completion: {}",
+            rate,
+            HumanDuration(Duration::from_secs(eta))
+        ),
+        _ => println!("The syncer is not filling any gap"),
+    }
+    for gap in report.gaps.iter() {
+        println!(
+            "Gap {}..{}: {} missing, {} failed requests, {} unreachable",
+            gap.range.start, gap.range.end, gap.milestones, gap.failures, gap.unreachable
+        );
+        if !gap.endpoints.is_empty() {
+            let endpoints = gap.endpoints.iter().map(Url::as_str).collect::<Vec<_>>();
+            println!("\tTried: {}", endpoints.join(", "));
+        }
+        if let Some(error) = gap.last_error.as_ref() {
+            println!("\tLast error: {}", error);
+        }
+    }
+}
+
 async fn cleanup_archive<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
     let backup_logs = !matches.is_present("no-backup");
     let val_level = matches
@@ -479,9 +1078,14 @@ async fn cleanup_archive<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
         println!("No LogsDir in the config, Chronicle is running without archiver");
         return Ok(());
     }
+    let compression_level = config
+        .broker_config
+        .log_compression_level
+        .unwrap_or(DEFAULT_COMPRESSION_LEVEL);
     Merger::new(
         logs_dir,
         max_log_size,
+        compression_level,
         backup_logs,
         true,
         val_level,
@@ -493,7 +1097,7 @@ async fn cleanup_archive<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn validate_archive() -> anyhow::Result<()> {
+async fn validate_archive<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
     let config = VersionedConfig::load(None)?.verify().await?;
     let logs_dir;
     let max_log_size = config.broker_config.max_log_size.clone().unwrap_or(u32::MAX as u64);
@@ -503,5 +1107,75 @@ async fn validate_archive() -> anyhow::Result<()> {
         println!("No LogsDir in the config, Chronicle is running without archiver");
         return Ok(());
     }
-    LogPaths::new(&logs_dir, true)?.validate(max_log_size, true).await
+    let paths = LogPaths::new(&logs_dir, true)?;
+    if matches.is_present("deep") {
+        paths.verify_manifest(&logs_dir, true).await?;
+    }
+    paths.validate(max_log_size, true).await
+}
+
+async fn convert_archive<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
+    let format = matches.value_of("format").unwrap().parse::<ArchiveFormat>()?;
+    let config = VersionedConfig::load(None)?.verify().await?;
+    let compression_level = match matches.value_of("level") {
+        Some(level) => {
+            let level = level.parse::<i32>()?;
+            if !(1..=22).contains(&level) {
+                bail!(
+                    "Invalid compression level {}, please provide a zstd level from 1 to 22",
+                    level
+                );
+            }
+            level
+        }
+        None => config
+            .broker_config
+            .log_compression_level
+            .unwrap_or(DEFAULT_COMPRESSION_LEVEL),
+    };
+    let logs_dir;
+    if let Some(dir) = matches.value_of("directory") {
+        logs_dir = PathBuf::from(dir);
+    } else if let Some(dir) = config.broker_config.logs_dir.as_ref() {
+        logs_dir = PathBuf::from(dir);
+    } else {
+        println!("No LogsDir in the config, Chronicle is running without archiver");
+        return Ok(());
+    }
+    for (_, _, path) in LogPaths::new(&logs_dir, true)?.into_iter() {
+        if archive::convert(&path, format, compression_level).await? {
+            println!("Converted {} to {}", path.to_string_lossy(), format);
+        } else {
+            println!("Skipped {}, already in {} format", path.to_string_lossy(), format);
+        }
+    }
+    Ok(())
+}
+
+async fn get_archived_milestone<'a>(matches: &ArgMatches<'a>) -> anyhow::Result<()> {
+    let milestone_index = matches.value_of("milestone").unwrap().parse::<u32>()?;
+    let logs_dir;
+    if let Some(dir) = matches.value_of("directory") {
+        logs_dir = PathBuf::from(dir);
+    } else {
+        let config = VersionedConfig::load(None)?.verify().await?;
+        if let Some(dir) = config.broker_config.logs_dir.as_ref() {
+            logs_dir = PathBuf::from(dir);
+        } else {
+            println!("No LogsDir in the config, Chronicle is running without archiver");
+            return Ok(());
+        }
+    }
+    let milestone_data = ArchiveReader::new(logs_dir)
+        .get(milestone_index)
+        .await?
+        .ok_or_else(|| anyhow!("Milestone {} is not archived", milestone_index))?;
+    let line = serde_json::to_string(&milestone_data)?;
+    if let Some(output) = matches.value_of("output") {
+        tokio::fs::write(output, format!("{}\n", line)).await?;
+        println!("Wrote milestone {} to {}", milestone_index, output);
+    } else {
+        println!("{}", line);
+    }
+    Ok(())
 }
@@ -27,6 +27,10 @@ pub struct BrokerConfig {
     pub mqtt_stream_capacity: usize,
     /// API endpoints the broker will use to request missing data
     pub api_endpoints: HashSet<Url>,
+    /// Trusted peer Chronicle API endpoints, ie `http://localhost:8080/api/permanode/`, which are used to request
+    /// missing data along with the api endpoints
+    #[serde(default)]
+    pub chronicle_endpoints: HashSet<Url>,
     /// Retries per api endpoint.
     pub retries_per_endpoint: usize,
     /// Retries per scylla query.
@@ -45,10 +49,361 @@ pub struct BrokerConfig {
     pub sync_range: Option<SyncRange>,
     /// Complete gaps interval in seconds
     pub complete_gaps_interval_secs: u64,
+    /// The order in which the syncer processes the missing milestone ranges
+    #[serde(default)]
+    pub sync_strategy: SyncStrategy,
     /// Archive directory
     pub logs_dir: Option<String>,
     /// The maximum log file size
     pub max_log_size: Option<u64>,
+    /// Optional zstd compression level of the archived log files, which are written as newline delimited json if
+    /// none is provided
+    #[serde(default)]
+    pub log_compression_level: Option<i32>,
+    /// Optional sink where the finalized log files are stored, they are only kept in the logs dir if none is provided
+    #[serde(default)]
+    pub archive_sink: Option<ArchiveSinkConfig>,
+    /// Optional background maintenance of the finalized log files, which are left untouched if none is provided
+    #[serde(default)]
+    pub archive_maintenance: Option<ArchiveMaintenanceConfig>,
+    /// Optional number of importers which run concurrently, sharing the `parallelism` budget
+    #[serde(default)]
+    pub import_workers: Option<u8>,
+    /// Optional maximum number of milestones imported by a single importer, the log files are imported as a whole if
+    /// none is provided
+    #[serde(default)]
+    pub import_shard_size: Option<u32>,
+    /// The capacities of the broker actors' inboxes
+    #[serde(default)]
+    pub inbox_capacities: InboxCapacities,
+    /// Optional directory used to snapshot the collectors and solidifiers state on graceful shutdown,
+    /// which is restored on the next start
+    #[serde(default)]
+    pub snapshot_dir: Option<String>,
+    /// Optional validation of the milestones data before it's marked as synced or archived
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    /// Optional ledger replay, which answers the balances at a milestone from its checkpoints and the archive
+    #[serde(default)]
+    pub ledger: Option<LedgerConfig>,
+    /// Optional named network profiles, each one supervised as a separate actor tree.
+    /// If none is provided, the top level feeds, endpoints, sync range, logs dir and validation
+    /// describe the single network stored in the first configured keyspace.
+    #[serde(default)]
+    pub networks: Vec<NetworkConfig>,
+}
+
+/// A named network profile
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct NetworkConfig {
+    /// The network name, used to scope the names of its actors
+    pub name: String,
+    /// The keyspace which stores the network data, it must be one of the configured storage keyspaces
+    pub keyspace: String,
+    /// MQTT addresses the network will use as feed sources separated by type
+    pub mqtt_brokers: HashMap<MqttType, HashSet<Url>>,
+    /// API endpoints the network will use to request missing data
+    pub api_endpoints: HashSet<Url>,
+    /// Trusted peer Chronicle API endpoints the network will use to request missing data
+    #[serde(default)]
+    pub chronicle_endpoints: HashSet<Url>,
+    /// Desired range of milestone indexes to sync if missing
+    pub sync_range: Option<SyncRange>,
+    /// Archive directory
+    pub logs_dir: Option<String>,
+    /// Optional validation of the network milestones data
+    #[serde(default)]
+    pub validation: ValidationConfig,
+    /// The order in which the network syncer processes the missing milestone ranges, the top level one if none is
+    /// provided
+    #[serde(default)]
+    pub sync_strategy: Option<SyncStrategy>,
+    /// The maximum log file size of the network, the top level one if none is provided
+    #[serde(default)]
+    pub max_log_size: Option<u64>,
+    /// The zstd compression level of the network log files, the top level one if none is provided
+    #[serde(default)]
+    pub log_compression_level: Option<i32>,
+    /// The background maintenance of the network log files, the top level one if none is provided
+    #[serde(default)]
+    pub archive_maintenance: Option<ArchiveMaintenanceConfig>,
+    /// The capacities of the network actors' inboxes, the top level ones if none is provided
+    #[serde(default)]
+    pub inbox_capacities: Option<InboxCapacities>,
+}
+
+/// The sink where the archiver stores the finalized log files
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct ArchiveSinkConfig {
+    /// The storage of the log files
+    pub target: ArchiveSinkTarget,
+    /// Delete the local log files once they are stored and their checksum is verified
+    #[serde(default)]
+    pub delete_local: bool,
+}
+
+/// The storages supported by the archive sink
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub enum ArchiveSinkTarget {
+    /// Copy the log files to a local directory, ie a mounted network drive
+    Local(String),
+    /// Upload the log files to an S3 compatible object store
+    S3(S3Config),
+}
+
+/// The bucket of an S3 compatible object store, which is addressed in path style
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct S3Config {
+    /// The object store endpoint, ie `https://s3.eu-central-1.amazonaws.com`
+    pub endpoint: Url,
+    /// The region used to sign the requests
+    pub region: String,
+    /// The bucket name
+    pub bucket: String,
+    /// The prefix of the object keys
+    #[serde(default)]
+    pub prefix: String,
+    /// The access key id
+    pub access_key_id: String,
+    /// The secret access key
+    pub secret_access_key: String,
+}
+
+/// The policies applied to the finalized log files of the logs directories, every policy is disabled by default
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[serde(default)]
+pub struct ArchiveMaintenanceConfig {
+    /// The interval between two maintenance runs in seconds
+    pub interval_secs: u64,
+    /// Merge the contiguous finalized log files smaller than this size, up to the maximum log file size
+    pub merge_below_size: Option<u64>,
+    /// The log files which end more than this number of milestones before the newest archived milestone are old
+    pub old_after_milestones: Option<u32>,
+    /// Compress the old log files to zstd
+    pub compress_old: bool,
+    /// Move the old log files into this directory, along with their manifest entries
+    pub move_old_dir: Option<String>,
+    /// Delete the old log files, or all of them if no age is provided, once they are verified in the archive sink
+    pub delete_stored: bool,
+    /// The maximum disk usage of a logs directory in bytes. The oldest log files verified in the archive sink are
+    /// deleted to stay below it, and an alert is sent if it's still exceeded.
+    pub max_disk_usage: Option<u64>,
+}
+
+impl ArchiveMaintenanceConfig {
+    /// Verify that the archive maintenance config is valid
+    pub fn verify(&self) -> anyhow::Result<()> {
+        if self.interval_secs == 0 {
+            bail!("Error verifying archive maintenance interval, zero provided!\nPlease provide a non-zero interval");
+        }
+        if (self.compress_old || self.move_old_dir.is_some()) && self.old_after_milestones.is_none() {
+            bail!("Error verifying archive maintenance, no age of the old log files provided!\nPlease provide old_after_milestones");
+        }
+        Ok(())
+    }
+}
+
+impl Default for ArchiveMaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 60 * 60,
+            merge_below_size: None,
+            old_after_milestones: None,
+            compress_old: false,
+            move_old_dir: None,
+            delete_stored: false,
+            max_disk_usage: None,
+        }
+    }
+}
+
+/// The order in which the syncer processes the missing milestone ranges. A range is always synced from its lowest
+/// milestone, so its data is logged in order.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+pub enum SyncStrategy {
+    /// Sync the lowest ranges first
+    LowestFirst,
+    /// Sync the highest ranges first, split into chunks of at most `chunk_size` milestones
+    HighestFirst {
+        /// The maximum number of milestones synced at once
+        chunk_size: u32,
+    },
+    /// Sync the ranges within the `window` milestones below the highest one first, then the rest lowest first
+    RecentWindowFirst {
+        /// The number of most recent milestones
+        window: u32,
+    },
+    /// Alternate between `recent` chunks taken from the highest ranges and `old` chunks taken from the lowest ones
+    WeightedInterleave {
+        /// The number of chunks synced highest first per turn
+        recent: u32,
+        /// The number of chunks synced lowest first per turn
+        old: u32,
+        /// The maximum number of milestones synced at once
+        chunk_size: u32,
+    },
+}
+
+impl Default for SyncStrategy {
+    fn default() -> Self {
+        Self::LowestFirst
+    }
+}
+
+impl SyncStrategy {
+    /// Verify that the sync strategy is valid
+    pub fn verify(&self) -> anyhow::Result<()> {
+        match *self {
+            Self::HighestFirst { chunk_size: 0 } | Self::WeightedInterleave { chunk_size: 0, .. } => {
+                bail!("Error verifying sync strategy chunk size, zero provided!\nPlease provide a non-zero chunk size")
+            }
+            Self::RecentWindowFirst { window: 0 } => {
+                bail!("Error verifying sync strategy window, zero provided!\nPlease provide a non-zero window")
+            }
+            Self::WeightedInterleave { recent: 0, old: 0, .. } => {
+                bail!("Error verifying sync strategy weights, zero provided!\nPlease provide a non-zero recent or old weight")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The ledger replay of the confirmed transactions
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct LedgerConfig {
+    /// The directory of the ledger checkpoints, which are stored in a sub directory per keyspace
+    pub checkpoints_dir: String,
+    /// The number of milestones between two ledger checkpoints written by the replay
+    pub checkpoint_interval: u32,
+    /// Optional maximum number of milestones replayed above a checkpoint to answer a balance query, which is the
+    /// checkpoint interval if none is provided. Zero answers the milestones of the checkpoints only.
+    #[serde(default)]
+    pub max_replay_distance: Option<u32>,
+}
+
+impl LedgerConfig {
+    /// Verify that the ledger config is valid
+    pub fn verify(&self) -> anyhow::Result<()> {
+        if self.checkpoints_dir.is_empty() {
+            bail!("Error verifying ledger checkpoints dir, empty path provided!");
+        }
+        if self.checkpoint_interval == 0 {
+            bail!("Error verifying ledger checkpoint interval, zero provided!\nPlease provide a non-zero interval");
+        }
+        Ok(())
+    }
+    /// Get the maximum number of milestones replayed above a checkpoint to answer a balance query
+    pub fn replay_distance_limit(&self) -> u32 {
+        self.max_replay_distance.unwrap_or(self.checkpoint_interval)
+    }
+}
+
+/// Validation of the collected milestones data, every check is disabled by default
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct ValidationConfig {
+    /// The coordinator public keys used to verify the milestones signatures,
+    /// the signatures are not verified if none is provided
+    pub coordinator_public_keys: Vec<CoordinatorPublicKey>,
+    /// The minimum number of valid coordinator signatures per milestone
+    pub coordinator_threshold: usize,
+    /// Verify that the milestone merkle proof matches the included messages of the collected cone
+    pub verify_merkle_proof: bool,
+    /// The minimum PoW score of the collected messages, the score is not checked if none is provided
+    pub min_pow_score: Option<u32>,
+}
+
+/// A coordinator public key and the milestone range it's applicable to
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct CoordinatorPublicKey {
+    /// The hex encoded ed25519 public key
+    pub public_key: String,
+    /// The first milestone index signed by this key
+    pub start: u32,
+    /// The last milestone index signed by this key, zero means no upper bound
+    pub end: u32,
+}
+
+impl ValidationConfig {
+    /// Get the coordinator public keys applicable to the given milestone index
+    pub fn applicable_public_keys(&self, milestone_index: u32) -> Vec<String> {
+        self.coordinator_public_keys
+            .iter()
+            .filter(|key| key.start <= milestone_index && (key.end == 0 || milestone_index <= key.end))
+            .map(|key| key.public_key.clone())
+            .collect()
+    }
+    /// Verify that the validation config is valid
+    pub fn verify(&self) -> anyhow::Result<()> {
+        for key in self.coordinator_public_keys.iter() {
+            if key.public_key.len() != 64 || !key.public_key.chars().all(|c| c.is_ascii_hexdigit()) {
+                bail!(
+                    "Error verifying coordinator public key {}!\nPlease provide a hex encoded ed25519 public key",
+                    key.public_key
+                );
+            }
+            if key.end != 0 && key.end < key.start {
+                bail!(
+                    "Error verifying coordinator public key {} range, end lower than start provided!",
+                    key.public_key
+                );
+            }
+        }
+        if !self.coordinator_public_keys.is_empty() && self.coordinator_threshold == 0 {
+            bail!("Error verifying coordinator threshold, zero provided!\nPlease provide a non-zero threshold");
+        }
+        Ok(())
+    }
+}
+
+/// Bounded inbox capacities of the broker actors.
+/// Once an inbox is full its senders wait, which eventually slows down the MQTT feed streams.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(default)]
+pub struct InboxCapacities {
+    /// The capacity of each collector inbox
+    pub collector: usize,
+    /// The capacity of each solidifier inbox
+    pub solidifier: usize,
+    /// The capacity of the archiver inbox
+    pub archiver: usize,
+    /// The capacity of the syncer inbox
+    pub syncer: usize,
+    /// The capacity of each importer inbox
+    pub importer: usize,
+    /// The capacity of each exporter inbox
+    pub exporter: usize,
+}
+
+impl InboxCapacities {
+    /// Verify that the inbox capacities are valid
+    pub fn verify(&self) -> anyhow::Result<()> {
+        if [
+            self.collector,
+            self.solidifier,
+            self.archiver,
+            self.syncer,
+            self.importer,
+            self.exporter,
+        ]
+        .contains(&0)
+        {
+            bail!("Error verifying inbox capacities, zero provided!\nPlease provide non-zero capacities");
+        }
+        Ok(())
+    }
+}
+
+impl Default for InboxCapacities {
+    fn default() -> Self {
+        Self {
+            collector: 10000,
+            solidifier: 10000,
+            archiver: 1000,
+            syncer: 1000,
+            importer: 1000,
+            exporter: 1000,
+        }
+    }
 }
 
 /// Enumerated MQTT feed source type
@@ -70,6 +425,7 @@ impl Default for BrokerConfig {
             retries_per_endpoint: 5,
             retries_per_query: 100,
             complete_gaps_interval_secs: 60 * 60,
+            sync_strategy: Default::default(),
             websocket_address: ([127, 0, 0, 1], 9000).into(),
             mqtt_stream_capacity: 10000,
             mqtt_brokers: hashmap! {
@@ -87,9 +443,20 @@ impl Default for BrokerConfig {
                 url::Url::parse("https://api.hornet-1.testnet.chrysalis2.com/api/v1").unwrap(),
             ]
             .into(),
+            chronicle_endpoints: HashSet::new(),
             sync_range: Some(Default::default()),
             logs_dir: Some("chronicle/logs/".to_owned()),
             max_log_size: Some(4 * 1024 * 1024 * 1024),
+            log_compression_level: None,
+            archive_sink: None,
+            archive_maintenance: None,
+            import_workers: None,
+            import_shard_size: None,
+            inbox_capacities: Default::default(),
+            snapshot_dir: None,
+            validation: Default::default(),
+            ledger: None,
+            networks: Vec::new(),
         }
     }
 }
@@ -116,7 +483,119 @@ impl BrokerConfig {
         for endpoint in self.api_endpoints.iter() {
             Self::verify_endpoint(&client, endpoint).await?
         }
-        let sync_range = self.sync_range.get_or_insert_with(|| SyncRange::default());
+        self.chronicle_endpoints = self
+            .chronicle_endpoints
+            .drain()
+            .filter_map(|endpoint| Self::adjust_api_endpoint(endpoint))
+            .collect();
+        for endpoint in self.chronicle_endpoints.iter() {
+            Self::verify_endpoint(&client, endpoint).await?
+        }
+        self.inbox_capacities.verify()?;
+        Self::verify_log_compression_level(self.log_compression_level)?;
+        if let Some(ArchiveSinkConfig {
+            target: ArchiveSinkTarget::S3(s3),
+            ..
+        }) = self.archive_sink.as_ref()
+        {
+            if s3.bucket.is_empty() || s3.region.is_empty() {
+                bail!("Error verifying archive sink, empty S3 bucket or region provided!");
+            }
+            if !["http", "https"].contains(&s3.endpoint.scheme()) {
+                bail!(
+                    "Error verifying archive sink endpoint {}!\nPlease provide an http(s) endpoint",
+                    s3.endpoint
+                );
+            }
+        }
+        if let Some(maintenance) = self.archive_maintenance.as_ref() {
+            maintenance.verify()?;
+        }
+        if self.import_workers == Some(0) || self.import_shard_size == Some(0) {
+            bail!("Error verifying import workers and shard size, zero provided!\nPlease provide non-zero values");
+        }
+        self.validation.verify()?;
+        self.sync_strategy.verify()?;
+        if let Some(ledger) = self.ledger.as_ref() {
+            ledger.verify()?;
+        }
+        Self::verify_sync_range(self.sync_range.get_or_insert_with(|| SyncRange::default()))?;
+        self.verify_network_names()?;
+        for network in self.networks.iter_mut() {
+            for mqtt_broker in network.mqtt_brokers.values().flatten() {
+                let random_id: u64 = rand::random();
+                let create_opts = CreateOptionsBuilder::new()
+                    .server_uri(mqtt_broker.as_str())
+                    .client_id(&format!("{}|{}", "verifier", random_id))
+                    .persistence(None)
+                    .finalize();
+                let _client = AsyncClient::new(create_opts)
+                    .map_err(|e| anyhow!("Error verifying {} mqtt broker {}: {}", network.name, mqtt_broker, e))?;
+            }
+            network.api_endpoints = network
+                .api_endpoints
+                .drain()
+                .filter_map(|endpoint| Self::adjust_api_endpoint(endpoint))
+                .collect();
+            for endpoint in network.api_endpoints.iter() {
+                Self::verify_endpoint(&client, endpoint).await?
+            }
+            network.chronicle_endpoints = network
+                .chronicle_endpoints
+                .drain()
+                .filter_map(|endpoint| Self::adjust_api_endpoint(endpoint))
+                .collect();
+            for endpoint in network.chronicle_endpoints.iter() {
+                Self::verify_endpoint(&client, endpoint).await?
+            }
+            network.validation.verify()?;
+            Self::verify_sync_range(network.sync_range.get_or_insert_with(|| SyncRange::default()))?;
+            if let Some(sync_strategy) = network.sync_strategy.as_ref() {
+                sync_strategy.verify()?;
+            }
+            Self::verify_log_compression_level(network.log_compression_level)?;
+            if let Some(maintenance) = network.archive_maintenance.as_ref() {
+                maintenance.verify()?;
+            }
+            if let Some(capacities) = network.inbox_capacities.as_ref() {
+                capacities.verify()?;
+            }
+        }
+        Ok(())
+    }
+    /// Verify the network names are valid and unique
+    pub(crate) fn verify_network_names(&self) -> anyhow::Result<()> {
+        let mut names = HashSet::new();
+        for network in self.networks.iter() {
+            if network.name.is_empty()
+                || !network
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                bail!(
+                    "Error verifying network name \"{}\"!\nPlease provide a non-empty name made of alphanumeric characters, '-' or '_'",
+                    network.name
+                );
+            }
+            if !names.insert(network.name.clone()) {
+                bail!("Error verifying network name \"{}\", duplicate provided!", network.name);
+            }
+        }
+        Ok(())
+    }
+    fn verify_log_compression_level(level: Option<i32>) -> anyhow::Result<()> {
+        if let Some(level) = level {
+            if !(1..=22).contains(&level) {
+                bail!(
+                    "Error verifying log compression level {}!\nPlease provide a zstd level from 1 to 22",
+                    level
+                );
+            }
+        }
+        Ok(())
+    }
+    fn verify_sync_range(sync_range: &SyncRange) -> anyhow::Result<()> {
         if sync_range.from == 0 || sync_range.to == 0 {
             bail!("Error verifying sync from/to, zero provided!\nPlease provide non-zero milestone index");
         } else if sync_range.from >= sync_range.to {
@@ -124,6 +603,54 @@ impl BrokerConfig {
         }
         Ok(())
     }
+    /// Get the network profiles to supervise, which are either the configured ones or a single unnamed profile
+    /// described by the top level fields and stored in the provided default keyspace.
+    /// The sync strategy, log files and inbox settings which a profile doesn't provide are the top level ones.
+    pub fn network_profiles(&self, default_keyspace: &str) -> Vec<NetworkConfig> {
+        let profiles = if self.networks.is_empty() {
+            vec![NetworkConfig {
+                name: String::new(),
+                keyspace: default_keyspace.to_owned(),
+                mqtt_brokers: self.mqtt_brokers.clone(),
+                api_endpoints: self.api_endpoints.clone(),
+                chronicle_endpoints: self.chronicle_endpoints.clone(),
+                sync_range: self.sync_range,
+                logs_dir: self.logs_dir.clone(),
+                validation: self.validation.clone(),
+                sync_strategy: None,
+                max_log_size: None,
+                log_compression_level: None,
+                archive_maintenance: None,
+                inbox_capacities: None,
+            }]
+        } else {
+            self.networks.clone()
+        };
+        profiles
+            .into_iter()
+            .map(|mut profile| {
+                profile.sync_strategy.get_or_insert(self.sync_strategy);
+                profile.max_log_size = profile.max_log_size.or(self.max_log_size);
+                profile.log_compression_level = profile.log_compression_level.or(self.log_compression_level);
+                if profile.archive_maintenance.is_none() {
+                    profile.archive_maintenance = self.archive_maintenance.clone();
+                }
+                profile.inbox_capacities.get_or_insert(self.inbox_capacities);
+                profile
+            })
+            .collect()
+    }
+    /// Get the MQTT feed sources of the given network, where the empty name refers to the top level ones
+    pub fn mqtt_brokers_mut(&mut self, network: &str) -> Option<&mut HashMap<MqttType, HashSet<Url>>> {
+        if network.is_empty() {
+            Some(&mut self.mqtt_brokers)
+        } else {
+            self.networks
+                .iter_mut()
+                .find(|n| n.name == network)
+                .map(|n| &mut n.mqtt_brokers)
+        }
+    }
     /// Adjust IOTA api endpoint url and ensure it's correct or return None otherwise
     pub fn adjust_api_endpoint(endpoint: Url) -> Option<Url> {
         let path = endpoint.as_str();
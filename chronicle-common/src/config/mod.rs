@@ -290,8 +290,22 @@ impl Config {
         self.api_config.verify().await?;
         self.broker_config.verify().await?;
         self.alert_config.verify().await?;
+        self.verify_network_keyspaces()?;
         Ok(self)
     }
+
+    /// Verify the networks store in configured storage keyspaces
+    fn verify_network_keyspaces(&self) -> anyhow::Result<()> {
+        for network in self.broker_config.networks.iter() {
+            ensure!(
+                self.storage_config.keyspaces.iter().any(|k| k.name == network.keyspace),
+                "Error verifying network \"{}\", its keyspace \"{}\" is not a configured storage keyspace!",
+                network.name,
+                network.keyspace
+            );
+        }
+        Ok(())
+    }
 }
 
 impl Persist for Config {
@@ -342,6 +356,7 @@ mod test {
                 retries_per_endpoint: 5,
                 retries_per_query: 100,
                 complete_gaps_interval_secs: 3600,
+                sync_strategy: SyncStrategy::LowestFirst,
                 websocket_address: "localhost:9000".to_socket_addrs().unwrap().next().unwrap(),
                 mqtt_stream_capacity: 10000,
                 mqtt_brokers: hashmap! {
@@ -359,9 +374,27 @@ mod test {
                     url::Url::parse("https://api.hornet-1.testnet.chrysalis2.com/api/v1").unwrap(),
                 ]
                 .into(),
+                chronicle_endpoints: hashset![],
                 sync_range: Some(SyncRange::default()),
                 logs_dir: Some("chronicle/logs/".to_owned()),
                 max_log_size: Some(4294967296),
+                log_compression_level: None,
+                archive_sink: None,
+                archive_maintenance: None,
+                import_workers: None,
+                import_shard_size: None,
+                inbox_capacities: InboxCapacities {
+                    collector: 10000,
+                    solidifier: 10000,
+                    archiver: 1000,
+                    syncer: 1000,
+                    importer: 1000,
+                    exporter: 1000,
+                },
+                snapshot_dir: None,
+                validation: Default::default(),
+                ledger: None,
+                networks: Vec::new(),
             },
             historical_config_path: HISTORICAL_CONFIG_PATH.to_owned(),
             alert_config: Default::default(),
@@ -379,4 +412,85 @@ mod test {
 
         assert_eq!(config, deserialized_config);
     }
+
+    fn network(name: &str, keyspace: &str) -> NetworkConfig {
+        NetworkConfig {
+            name: name.to_owned(),
+            keyspace: keyspace.to_owned(),
+            mqtt_brokers: HashMap::new(),
+            api_endpoints: hashset![],
+            chronicle_endpoints: hashset![],
+            sync_range: None,
+            logs_dir: None,
+            validation: Default::default(),
+            sync_strategy: None,
+            max_log_size: None,
+            log_compression_level: None,
+            archive_maintenance: None,
+            inbox_capacities: None,
+        }
+    }
+
+    #[test]
+    pub fn network_names() {
+        let mut broker_config = BrokerConfig {
+            networks: vec![network("mainnet", "permanode"), network("dev_net-1", "devnet")],
+            ..Default::default()
+        };
+        assert!(broker_config.verify_network_names().is_ok());
+        broker_config.networks.push(network("mainnet", "other"));
+        assert!(broker_config.verify_network_names().is_err());
+        for name in ["", "main net", "mainnet/1"] {
+            broker_config.networks = vec![network(name, "permanode")];
+            assert!(broker_config.verify_network_names().is_err(), "{:?} is valid", name);
+        }
+    }
+
+    #[test]
+    pub fn network_profile_defaults() {
+        let mut devnet = network("devnet", "devnet");
+        devnet.sync_strategy = Some(SyncStrategy::HighestFirst { chunk_size: 100 });
+        devnet.max_log_size = Some(1024);
+        let broker_config = BrokerConfig {
+            log_compression_level: Some(3),
+            networks: vec![network("mainnet", "permanode"), devnet],
+            ..Default::default()
+        };
+        let profiles = broker_config.network_profiles("permanode");
+        // the settings a profile doesn't provide are the top level ones
+        assert_eq!(profiles[0].sync_strategy, Some(broker_config.sync_strategy));
+        assert_eq!(profiles[0].max_log_size, broker_config.max_log_size);
+        assert_eq!(profiles[0].inbox_capacities, Some(broker_config.inbox_capacities));
+        assert_eq!(
+            profiles[1].sync_strategy,
+            Some(SyncStrategy::HighestFirst { chunk_size: 100 })
+        );
+        assert_eq!(profiles[1].max_log_size, Some(1024));
+        assert_eq!(profiles[1].log_compression_level, Some(3));
+        // so are the ones of the unnamed profile
+        let profiles = BrokerConfig::default().network_profiles("permanode");
+        assert_eq!(profiles[0].max_log_size, BrokerConfig::default().max_log_size);
+        assert_eq!(profiles[0].sync_strategy, Some(SyncStrategy::default()));
+    }
+
+    #[test]
+    pub fn network_keyspaces() {
+        let mut config = Config {
+            storage_config: StorageConfig {
+                keyspaces: vec![KeyspaceConfig {
+                    name: "permanode".to_owned(),
+                    data_centers: HashMap::new(),
+                }],
+                ..Default::default()
+            },
+            broker_config: BrokerConfig {
+                networks: vec![network("mainnet", "permanode")],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(config.verify_network_keyspaces().is_ok());
+        config.broker_config.networks.push(network("devnet", "devnet"));
+        assert!(config.verify_network_keyspaces().is_err());
+    }
 }
@@ -9,6 +9,7 @@ use prometheus::{
     HistogramVec,
     IntCounter,
     IntCounterVec,
+    IntGaugeVec,
     Opts,
     Registry,
 };
@@ -33,6 +34,20 @@ lazy_static! {
         HistogramVec::new(HistogramOpts::new("response_time", "Response Times"), &["endpoint"])
             .expect("failed to create metric");
 
+    /// Broker actor inbox queue depth collector
+    pub static ref INBOX_QUEUE_DEPTH: IntGaugeVec = IntGaugeVec::new(
+        Opts::new("inbox_queue_depth", "Broker Actor Inbox Queue Depths"),
+        &["actor"]
+    )
+    .expect("failed to create metric");
+
+    /// Broker actor inbox dropped events collector
+    pub static ref INBOX_DROPPED_EVENTS: IntCounterVec = IntCounterVec::new(
+        Opts::new("inbox_dropped_events", "Broker Actor Inbox Dropped Events"),
+        &["actor"]
+    )
+    .expect("failed to create metric");
+
     /// Confirmation time collector
     pub static ref CONFIRMATION_TIME_COLLECTOR: Gauge =
         Gauge::new("confirmation_time", "Confirmation Times")
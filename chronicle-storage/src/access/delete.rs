@@ -168,3 +168,79 @@ impl Delete<ParentPK, ParentRecord> for ChronicleKeyspace {
             .value(&message_id.to_string())
     }
 }
+
+/// Delete the sync records of a milestone range, which marks it unsynced
+impl Delete<SyncRange, SyncRecord> for ChronicleKeyspace {
+    type QueryOrPrepared = PreparedStatement;
+    fn statement(&self) -> std::borrow::Cow<'static, str> {
+        format!(
+            "DELETE FROM {}.sync WHERE key = ? AND milestone_index >= ? AND milestone_index < ?",
+            self.name()
+        )
+        .into()
+    }
+    fn bind_values<T: Values>(builder: T, sync_range: &SyncRange) -> T::Return {
+        builder
+            .value(&"permanode")
+            .value(&sync_range.from)
+            .value(&sync_range.to)
+    }
+}
+
+/// Delete the analytic records of a milestone range
+impl Delete<SyncRange, AnalyticRecord> for ChronicleKeyspace {
+    type QueryOrPrepared = PreparedStatement;
+    fn statement(&self) -> std::borrow::Cow<'static, str> {
+        format!(
+            "DELETE FROM {}.analytics WHERE key = ? AND milestone_index >= ? AND milestone_index < ?",
+            self.name()
+        )
+        .into()
+    }
+    fn bind_values<T: Values>(builder: T, sync_range: &SyncRange) -> T::Return {
+        builder
+            .value(&"permanode")
+            .value(&sync_range.from)
+            .value(&sync_range.to)
+    }
+}
+
+/// Delete the analytic record of a milestone from its hour bucket
+impl Delete<(HourBucket, MilestoneIndex), AnalyticRecord> for ChronicleKeyspace {
+    type QueryOrPrepared = PreparedStatement;
+    fn statement(&self) -> std::borrow::Cow<'static, str> {
+        format!(
+            "DELETE FROM {}.analytics_hourly WHERE bucket = ? AND milestone_index = ?",
+            self.name()
+        )
+        .into()
+    }
+    fn bind_values<T: Values>(
+        builder: T,
+        (HourBucket(bucket), milestone_index): &(HourBucket, MilestoneIndex),
+    ) -> T::Return {
+        builder.value(bucket).value(&milestone_index.0)
+    }
+}
+
+/// Delete the milestone rows of a milestone index
+impl Delete<MilestoneIndex, (MessageId, Box<MilestonePayload>)> for ChronicleKeyspace {
+    type QueryOrPrepared = PreparedStatement;
+    fn statement(&self) -> std::borrow::Cow<'static, str> {
+        format!("DELETE FROM {}.milestones WHERE milestone_index = ?", self.name()).into()
+    }
+    fn bind_values<T: Values>(builder: T, milestone_index: &MilestoneIndex) -> T::Return {
+        builder.value(&milestone_index.0)
+    }
+}
+
+/// Delete the `messages` table row of a message, along with its metadata
+impl Delete<MessageId, Message> for ChronicleKeyspace {
+    type QueryOrPrepared = PreparedStatement;
+    fn statement(&self) -> std::borrow::Cow<'static, str> {
+        format!("DELETE FROM {}.messages WHERE message_id = ?", self.name()).into()
+    }
+    fn bind_values<T: Values>(builder: T, message_id: &MessageId) -> T::Return {
+        builder.value(&message_id.to_string())
+    }
+}
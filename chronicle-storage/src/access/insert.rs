@@ -295,7 +295,7 @@ impl Insert<Synckey, AnalyticRecord> for ChronicleKeyspace {
     type QueryOrPrepared = PreparedStatement;
     fn statement(&self) -> std::borrow::Cow<'static, str> {
         format!(
-            "INSERT INTO {}.analytics (key, milestone_index, message_count, transaction_count, transferred_tokens) VALUES (?, ?, ?, ?, ?)",
+            "INSERT INTO {}.analytics (key, milestone_index, message_count, transaction_count, transferred_tokens, metrics) VALUES (?, ?, ?, ?, ?, ?)",
             self.name()
         )
         .into()
@@ -308,6 +308,7 @@ impl Insert<Synckey, AnalyticRecord> for ChronicleKeyspace {
             message_count,
             transaction_count,
             transferred_tokens,
+            metrics,
         }: &AnalyticRecord,
     ) -> T::Return {
         builder
@@ -316,5 +317,164 @@ impl Insert<Synckey, AnalyticRecord> for ChronicleKeyspace {
             .value(&message_count.0)
             .value(&transaction_count.0)
             .value(&transferred_tokens.0)
+            .value(metrics)
+    }
+}
+
+impl Insert<HourBucket, AnalyticRecord> for ChronicleKeyspace {
+    type QueryOrPrepared = PreparedStatement;
+    fn statement(&self) -> std::borrow::Cow<'static, str> {
+        format!(
+            "INSERT INTO {}.analytics_hourly (bucket, milestone_index, message_count, transaction_count, transferred_tokens, metrics) VALUES (?, ?, ?, ?, ?, ?)",
+            self.name()
+        )
+        .into()
+    }
+    fn bind_values<T: Values>(
+        builder: T,
+        HourBucket(bucket): &HourBucket,
+        AnalyticRecord {
+            milestone_index,
+            message_count,
+            transaction_count,
+            transferred_tokens,
+            metrics,
+        }: &AnalyticRecord,
+    ) -> T::Return {
+        builder
+            .value(bucket)
+            .value(&milestone_index.0)
+            .value(&message_count.0)
+            .value(&transaction_count.0)
+            .value(&transferred_tokens.0)
+            .value(metrics)
+    }
+}
+
+impl Insert<RollupInterval, AnalyticRollup> for ChronicleKeyspace {
+    type QueryOrPrepared = PreparedStatement;
+    fn statement(&self) -> std::borrow::Cow<'static, str> {
+        format!(
+            "INSERT INTO {}.analytics_rollups (interval, bucket, milestone_count, message_count, transaction_count, transferred_tokens, metrics) VALUES (?, ?, ?, ?, ?, ?, ?)",
+            self.name()
+        )
+        .into()
+    }
+    fn bind_values<T: Values>(
+        builder: T,
+        interval: &RollupInterval,
+        AnalyticRollup {
+            bucket,
+            milestone_count,
+            message_count,
+            transaction_count,
+            transferred_tokens,
+            metrics,
+        }: &AnalyticRollup,
+    ) -> T::Return {
+        builder
+            .value(&interval.as_str())
+            .value(bucket)
+            .value(milestone_count)
+            .value(message_count)
+            .value(transaction_count)
+            .value(transferred_tokens)
+            .value(metrics)
+    }
+}
+
+impl Insert<Synckey, SnapshotInfo> for ChronicleKeyspace {
+    type QueryOrPrepared = PreparedStatement;
+    fn statement(&self) -> std::borrow::Cow<'static, str> {
+        format!(
+            "INSERT INTO {}.snapshots (key, ledger_index, kind, sep_index, timestamp, network_id, treasury_amount, output_count, milestone_diff_count) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            self.name()
+        )
+        .into()
+    }
+    fn bind_values<T: Values>(
+        builder: T,
+        _: &Synckey,
+        SnapshotInfo {
+            kind,
+            ledger_index,
+            sep_index,
+            timestamp,
+            network_id,
+            treasury_amount,
+            output_count,
+            milestone_diff_count,
+        }: &SnapshotInfo,
+    ) -> T::Return {
+        builder
+            .value(&"permanode")
+            .value(ledger_index)
+            .value(&kind.as_str())
+            .value(sep_index)
+            .value(timestamp)
+            .value(network_id)
+            .value(treasury_amount)
+            .value(output_count)
+            .value(milestone_diff_count)
+    }
+}
+
+impl Insert<OutputId, SnapshotOutputRecord> for ChronicleKeyspace {
+    type QueryOrPrepared = PreparedStatement;
+    fn statement(&self) -> std::borrow::Cow<'static, str> {
+        format!(
+            "INSERT INTO {}.snapshot_outputs (output_id, ledger_index, message_id, data) VALUES (?, ?, ?, ?)",
+            self.name()
+        )
+        .into()
+    }
+    fn bind_values<T: Values>(
+        builder: T,
+        output_id: &OutputId,
+        SnapshotOutputRecord {
+            ledger_index,
+            message_id,
+            output,
+        }: &SnapshotOutputRecord,
+    ) -> T::Return {
+        let mut output_bytes = Vec::new();
+        output.pack(&mut output_bytes).expect("Error occurred packing Output");
+        builder
+            .value(&output_id.to_string())
+            .value(ledger_index)
+            .value(&message_id.to_string())
+            .value(&output_bytes.as_slice())
+    }
+}
+
+impl Insert<MilestoneIndex, SnapshotDiffRecord> for ChronicleKeyspace {
+    type QueryOrPrepared = PreparedStatement;
+    fn statement(&self) -> std::borrow::Cow<'static, str> {
+        format!(
+            "INSERT INTO {}.snapshot_diffs (milestone_index, variant, output_id, message_id, data, consumed_by) VALUES (?, ?, ?, ?, ?, ?)",
+            self.name()
+        )
+        .into()
+    }
+    fn bind_values<T: Values>(
+        builder: T,
+        milestone_index: &MilestoneIndex,
+        SnapshotDiffRecord {
+            output_id,
+            message_id,
+            output,
+            consumed_by,
+        }: &SnapshotDiffRecord,
+    ) -> T::Return {
+        let mut output_bytes = Vec::new();
+        output.pack(&mut output_bytes).expect("Error occurred packing Output");
+        let variant = if consumed_by.is_some() { "consumed" } else { "created" };
+        builder
+            .value(&milestone_index.0)
+            .value(&variant)
+            .value(&output_id.to_string())
+            .value(&message_id.to_string())
+            .value(&output_bytes.as_slice())
+            .value(&consumed_by.map(|transaction_id| transaction_id.to_string()))
     }
 }
@@ -116,7 +116,7 @@ impl<T> Record<T> {
 
 /// A partitioned value marker. Wraps a key type to select
 /// using the partition id and milestone index.
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Partitioned<T> {
     inner: T,
     partition: Partition,
@@ -189,7 +189,7 @@ impl<T> TTL<T> {
 }
 
 /// A partition key
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Partition {
     id: u16,
     milestone_index: u32,
@@ -230,7 +230,7 @@ impl SyncRecord {
 }
 /// An `addresses` table row
 #[allow(missing_docs)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct AddressRecord {
     pub output_type: OutputType,
     pub transaction_id: TransactionId,
@@ -273,7 +273,7 @@ impl From<(OutputType, TransactionId, Index, Amount, Option<LedgerInclusionState
 
 /// An `indexes` table row
 #[allow(missing_docs)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct IndexationRecord {
     pub message_id: MessageId,
     pub ledger_inclusion_state: Option<LedgerInclusionState>,
@@ -291,7 +291,7 @@ impl IndexationRecord {
 
 /// A `parents` table row
 #[allow(missing_docs)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct ParentRecord {
     pub message_id: MessageId,
     pub ledger_inclusion_state: Option<LedgerInclusionState>,
@@ -309,7 +309,7 @@ impl ParentRecord {
 
 /// A `transactions` table row
 #[allow(missing_docs)]
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct TransactionRecord {
     pub variant: TransactionVariant,
     pub message_id: MessageId,
@@ -367,7 +367,7 @@ impl TransactionRecord {
 }
 /// Transaction variants. Can be Input, Output, or Unlock.
 #[repr(u8)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum TransactionVariant {
     /// A transaction's Input, which spends a prior Output
     Input = 0,
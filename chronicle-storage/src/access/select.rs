@@ -497,7 +497,7 @@ impl Select<SyncRange, Iter<AnalyticRecord>> for ChronicleKeyspace {
     type QueryOrPrepared = QueryStatement;
     fn statement(&self) -> std::borrow::Cow<'static, str> {
         format!(
-            "SELECT milestone_index, message_count, transaction_count, transferred_tokens FROM {}.analytics WHERE key = ? AND milestone_index >= ? AND milestone_index < ?",
+            "SELECT milestone_index, message_count, transaction_count, transferred_tokens, metrics FROM {}.analytics WHERE key = ? AND milestone_index >= ? AND milestone_index < ?",
             self.name()
         )
         .into()
@@ -524,6 +524,64 @@ impl RowsDecoder<SyncRange, Iter<AnalyticRecord>> for ChronicleKeyspace {
     }
 }
 
+impl Select<HourBucket, Iter<AnalyticRecord>> for ChronicleKeyspace {
+    type QueryOrPrepared = PreparedStatement;
+    fn statement(&self) -> std::borrow::Cow<'static, str> {
+        format!(
+            "SELECT milestone_index, message_count, transaction_count, transferred_tokens, metrics FROM {}.analytics_hourly WHERE bucket = ?",
+            self.name()
+        )
+        .into()
+    }
+    fn bind_values<T: Values>(builder: T, HourBucket(bucket): &HourBucket) -> T::Return {
+        builder.value(bucket)
+    }
+}
+
+impl RowsDecoder<HourBucket, Iter<AnalyticRecord>> for ChronicleKeyspace {
+    type Row = AnalyticRecord;
+    fn try_decode(decoder: Decoder) -> anyhow::Result<Option<Iter<AnalyticRecord>>> {
+        ensure!(decoder.is_rows()?, "Decoded response is not rows!");
+        let rows_iter = Self::Row::rows_iter(decoder)?;
+        if rows_iter.is_empty() && !rows_iter.has_more_pages() {
+            Ok(None)
+        } else {
+            Ok(Some(rows_iter))
+        }
+    }
+}
+
+impl Select<RollupRange, Iter<AnalyticRollup>> for ChronicleKeyspace {
+    type QueryOrPrepared = QueryStatement;
+    fn statement(&self) -> std::borrow::Cow<'static, str> {
+        format!(
+            "SELECT bucket, milestone_count, message_count, transaction_count, transferred_tokens, metrics FROM {}.analytics_rollups WHERE interval = ? AND bucket >= ? AND bucket < ?",
+            self.name()
+        )
+        .into()
+    }
+    fn bind_values<T: Values>(builder: T, range: &RollupRange) -> T::Return {
+        builder
+            .value(&range.interval.as_str())
+            .value(&range.from)
+            .value(&range.to)
+    }
+}
+
+impl RowsDecoder<RollupRange, Iter<AnalyticRollup>> for ChronicleKeyspace {
+    type Row = AnalyticRollup;
+    fn try_decode(decoder: Decoder) -> anyhow::Result<Option<Iter<AnalyticRollup>>> {
+        ensure!(decoder.is_rows()?, "Decoded response is not rows!");
+        let rows_iter = Self::Row::rows_iter(decoder)?;
+        if rows_iter.is_empty() && !rows_iter.has_more_pages() {
+            Ok(None)
+        } else {
+            // CQL specs states that the page result might be empty but has more pages to fetch.
+            Ok(Some(rows_iter))
+        }
+    }
+}
+
 // ###############
 // ROW DEFINITIONS
 // ###############
@@ -683,11 +741,21 @@ impl Row for AnalyticRecord {
         let message_count = MessageCount(rows.column_value::<u32>()?);
         let transaction_count = TransactionCount(rows.column_value::<u32>()?);
         let transferred_tokens = TransferredTokens(rows.column_value::<u64>()?);
-        Ok(AnalyticRecord::new(
-            milestone_index,
-            message_count,
-            transaction_count,
-            transferred_tokens,
-        ))
+        let mut record = AnalyticRecord::new(milestone_index, message_count, transaction_count, transferred_tokens);
+        record.metrics = rows.column_value::<Option<AnalyticMetrics>>()?;
+        Ok(record)
+    }
+}
+
+impl Row for AnalyticRollup {
+    fn try_decode_row<T: ColumnValue>(rows: &mut T) -> anyhow::Result<Self> {
+        Ok(AnalyticRollup {
+            bucket: rows.column_value::<u64>()?,
+            milestone_count: rows.column_value::<u32>()?,
+            message_count: rows.column_value::<u64>()?,
+            transaction_count: rows.column_value::<u64>()?,
+            transferred_tokens: rows.column_value::<u64>()?,
+            metrics: rows.column_value::<Option<AnalyticMetrics>>()?,
+        })
     }
 }
@@ -14,6 +14,7 @@ use bee_message::{
     MessageId,
 };
 use std::{
+    collections::BTreeSet,
     io::Cursor,
     ops::{
         Deref,
@@ -84,7 +85,7 @@ impl<P: Packable> ColumnDecoder for Bee<P> {
 
 /// A transaction's unlock data, to be stored in a `transactions` row.
 /// Holds a reference to the input which it signs.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct UnlockData {
     /// it holds the transaction_id of the input which created the unlock_block
     pub input_tx_id: TransactionId,
@@ -127,7 +128,7 @@ impl Packable for UnlockData {
 }
 
 /// A transaction's input data, to be stored in a `transactions` row.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum InputData {
     /// An regular Input which spends a prior Output and its unlock block
     Utxo(UtxoInput, UnlockBlock),
@@ -183,7 +184,7 @@ impl Packable for InputData {
 }
 
 // input unlocked my input
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 /// Chrysalis transaction data
 pub enum TransactionData {
     /// An unspent transaction input
@@ -243,7 +244,7 @@ impl ColumnDecoder for TransactionData {
 }
 /// MessageMetadata storage object
 #[allow(missing_docs)]
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MessageMetadata {
     #[serde(rename = "messageId")]
     pub message_id: MessageId,
@@ -259,6 +260,12 @@ pub struct MessageMetadata {
     pub should_promote: Option<bool>,
     #[serde(rename = "shouldReattach")]
     pub should_reattach: Option<bool>,
+    #[serde(rename = "conflictReason", default)]
+    pub conflict_reason: Option<u8>,
+    #[serde(rename = "whiteFlagIndex", default)]
+    pub white_flag_index: Option<u32>,
+    #[serde(rename = "milestoneTimestampReferenced", default)]
+    pub milestone_timestamp_referenced: Option<u64>,
 }
 
 /// A message's ledger inclusion state
@@ -289,9 +296,44 @@ impl ColumnDecoder for LedgerInclusionState {
     }
 }
 
+/// The original stored layout of the `MessageMetadata`
+type LegacyMessageMetadata = (
+    MessageId,
+    Vec<MessageId>,
+    bool,
+    Option<u32>,
+    Option<LedgerInclusionState>,
+    Option<bool>,
+    Option<bool>,
+);
+
+/// The fields appended to the original stored layout: conflict reason, white-flag index and referenced milestone
+/// timestamp
+type MessageMetadataExtension = (Option<u8>, Option<u32>, Option<u64>);
+
 impl ColumnEncoder for MessageMetadata {
     fn encode(&self, buffer: &mut Vec<u8>) {
-        let bytes = bincode_config().serialize(self).unwrap();
+        // The legacy layout is written first, followed by the extension, so existing blobs remain a valid prefix
+        let mut bytes = bincode_config()
+            .serialize(&(
+                &self.message_id,
+                &self.parent_message_ids,
+                self.is_solid,
+                self.referenced_by_milestone_index,
+                self.ledger_inclusion_state,
+                self.should_promote,
+                self.should_reattach,
+            ))
+            .unwrap();
+        bytes.extend(
+            bincode_config()
+                .serialize(&(
+                    self.conflict_reason,
+                    self.white_flag_index,
+                    self.milestone_timestamp_referenced,
+                ))
+                .unwrap(),
+        );
         buffer.extend(&i32::to_be_bytes(bytes.len() as i32));
         buffer.extend(bytes)
     }
@@ -299,9 +341,84 @@ impl ColumnEncoder for MessageMetadata {
 
 impl ColumnDecoder for MessageMetadata {
     fn try_decode(slice: &[u8]) -> anyhow::Result<Self> {
-        bincode_config().deserialize(slice).map_err(Into::into)
+        let mut reader = Cursor::new(slice);
+        let (
+            message_id,
+            parent_message_ids,
+            is_solid,
+            referenced_by_milestone_index,
+            ledger_inclusion_state,
+            should_promote,
+            should_reattach,
+        ): LegacyMessageMetadata = bincode_config().deserialize_from(&mut reader)?;
+        // Blobs stored before the extension end right after the legacy layout
+        let (conflict_reason, white_flag_index, milestone_timestamp_referenced): MessageMetadataExtension =
+            if (reader.position() as usize) < slice.len() {
+                bincode_config().deserialize_from(&mut reader)?
+            } else {
+                (None, None, None)
+            };
+        Ok(Self {
+            message_id,
+            parent_message_ids,
+            is_solid,
+            referenced_by_milestone_index,
+            ledger_inclusion_state,
+            should_promote,
+            should_reattach,
+            conflict_reason,
+            white_flag_index,
+            milestone_timestamp_referenced,
+        })
     }
 }
+
+/// The metadata is packed as its length prefixed column encoding, so packed archives decode the legacy layout too
+impl Packable for MessageMetadata {
+    type Error = anyhow::Error;
+    fn packed_len(&self) -> usize {
+        let mut bytes = Vec::new();
+        self.encode(&mut bytes);
+        bytes.len()
+    }
+    fn pack<W: std::io::Write>(&self, writer: &mut W) -> Result<(), Self::Error> {
+        let mut bytes = Vec::new();
+        self.encode(&mut bytes);
+        writer.write_all(&bytes)?;
+        Ok(())
+    }
+    fn unpack_inner<R: std::io::Read + ?Sized, const CHECK: bool>(reader: &mut R) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        let mut len = [0; 4];
+        reader.read_exact(&mut len)?;
+        let len = i32::from_be_bytes(len);
+        ensure!(len >= 0, "Tried to unpack a message metadata with a negative length!");
+        let mut bytes = vec![0; len as usize];
+        reader.read_exact(&mut bytes)?;
+        Self::try_decode(&bytes)
+    }
+}
+
+impl Packable for FullMessage {
+    type Error = anyhow::Error;
+    fn packed_len(&self) -> usize {
+        self.0.packed_len() + self.1.packed_len()
+    }
+    fn pack<W: std::io::Write>(&self, writer: &mut W) -> Result<(), Self::Error> {
+        self.0.pack(writer)?;
+        self.1.pack(writer)?;
+        Ok(())
+    }
+    fn unpack_inner<R: std::io::Read + ?Sized, const CHECK: bool>(reader: &mut R) -> Result<Self, Self::Error>
+    where
+        Self: Sized,
+    {
+        Ok(Self(Message::unpack(reader)?, MessageMetadata::unpack(reader)?))
+    }
+}
+
 impl ColumnEncoder for TransactionData {
     fn encode(&self, buffer: &mut Vec<u8>) {
         let mut bytes = Vec::new();
@@ -380,11 +497,11 @@ impl FullMessage {
 pub type PartitionId = u16;
 
 /// An index in plain-text, unhashed
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Indexation(pub String);
 
 /// A hint, used to lookup in the `hints` table
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Hint {
     /// The hint string
     pub hint: String,
@@ -419,7 +536,7 @@ impl Hint {
 }
 
 /// Hint variants
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum HintVariant {
     /// An address
     Address,
@@ -521,6 +638,258 @@ impl Deref for TransferredTokens {
         &self.0
     }
 }
+/// The version of the analytic metrics computed by this release.
+/// A new version only appends metrics, so the older metrics of a newer blob still decode.
+pub const ANALYTIC_METRICS_VERSION: u8 = 1;
+
+/// The number of leading address bits which select a register of an address sketch
+const ADDRESS_SKETCH_PRECISION: u32 = 10;
+/// The number of registers of a non empty address sketch
+const ADDRESS_SKETCH_REGISTERS: usize = 1 << ADDRESS_SKETCH_PRECISION;
+
+/// A HyperLogLog sketch of Ed25519 addresses, which estimates the number of distinct addresses of merged sketches
+/// within about 3%, in a fixed size whatever the number of addresses.
+/// The addresses are Blake2b hashes already, so their leading bytes are used as the hash.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressSketch {
+    /// The highest rank of each register, empty until an address is added so the rows without addresses stay small
+    registers: Vec<u8>,
+}
+
+impl AddressSketch {
+    /// Add an address
+    pub fn insert(&mut self, address: &[u8; 32]) {
+        if self.registers.is_empty() {
+            self.registers = vec![0; ADDRESS_SKETCH_REGISTERS];
+        }
+        let mut hash = [0u8; 8];
+        hash.copy_from_slice(&address[..8]);
+        let hash = u64::from_be_bytes(hash);
+        let register = (hash >> (64 - ADDRESS_SKETCH_PRECISION)) as usize;
+        // the position of the first set bit after the register bits, bounded by their number
+        let rank =
+            ((hash << ADDRESS_SKETCH_PRECISION) | (1 << (ADDRESS_SKETCH_PRECISION - 1))).leading_zeros() as u8 + 1;
+        self.registers[register] = self.registers[register].max(rank);
+    }
+    /// Merge another sketch, which then estimates the distinct addresses of both
+    pub fn merge(&mut self, other: &AddressSketch) {
+        if self.registers.is_empty() {
+            self.registers = other.registers.clone();
+        } else if !other.registers.is_empty() {
+            for (register, other) in self.registers.iter_mut().zip(other.registers.iter()) {
+                *register = (*register).max(*other);
+            }
+        }
+    }
+    /// Estimate the number of distinct addresses, which is exact for a few addresses
+    pub fn estimate(&self) -> u32 {
+        if self.registers.is_empty() {
+            return 0;
+        }
+        let registers = ADDRESS_SKETCH_REGISTERS as f64;
+        let sum: f64 = self.registers.iter().map(|rank| 2f64.powi(-(*rank as i32))).sum();
+        let estimate = 0.7213 / (1.0 + 1.079 / registers) * registers * registers / sum;
+        let zeros = self.registers.iter().filter(|rank| **rank == 0).count();
+        // the linear counting of the empty registers is more accurate for the small cardinalities
+        let estimate = if estimate <= 2.5 * registers && zeros > 0 {
+            registers * (registers / zeros as f64).ln()
+        } else {
+            estimate
+        };
+        estimate.round() as u32
+    }
+    fn check(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.registers.is_empty() || self.registers.len() == ADDRESS_SKETCH_REGISTERS,
+            "Invalid address sketch of {} registers",
+            self.registers.len()
+        );
+        Ok(())
+    }
+}
+
+impl<'a> FromIterator<&'a [u8; 32]> for AddressSketch {
+    fn from_iter<I: IntoIterator<Item = &'a [u8; 32]>>(addresses: I) -> Self {
+        let mut sketch = Self::default();
+        for address in addresses {
+            sketch.insert(address);
+        }
+        sketch
+    }
+}
+
+/// The versioned metrics of a milestone cone, which extend its analytics record
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnalyticMetrics {
+    /// The version of the computed metrics
+    pub version: u8,
+    /// The number of distinct addresses which sent or received tokens through the included transactions, estimated
+    /// once merged
+    pub active_addresses: u32,
+    /// The number of distinct addresses which received tokens through the included transactions without having
+    /// received any output before the milestone, estimated once merged
+    pub new_addresses: u32,
+    /// The number of dust allowance outputs created by the included transactions
+    pub dust_allowance_outputs: u32,
+    /// The IOTA tokens deposited in the dust allowance outputs
+    pub dust_allowance_tokens: u64,
+    /// The number of messages with an indexation payload, excluding the ones nested in transactions
+    pub indexation_payloads: u32,
+    /// The number of messages with a transaction payload
+    pub transaction_payloads: u32,
+    /// The number of messages with a milestone payload
+    pub milestone_payloads: u32,
+    /// The number of receipts, either as payload or within a milestone
+    pub receipt_payloads: u32,
+    /// The number of messages without payload
+    pub no_payload: u32,
+    /// The number of conflicting transactions
+    pub conflicting_transactions: u32,
+    /// The total number of parents of the messages
+    pub parents: u32,
+    /// The most used indexation tags (hex encoded) along with their number of messages, by descending count
+    pub top_indexation_tags: Vec<(String, u32)>,
+    /// The sketch of the active addresses, so that the merged metrics count every address about once
+    #[serde(skip)]
+    pub active_address_sketch: AddressSketch,
+    /// The sketch of the new addresses, so that the merged metrics count every address about once
+    #[serde(skip)]
+    pub new_address_sketch: AddressSketch,
+}
+
+/// The metrics of the first version
+type AnalyticMetricsV1 = (
+    u32,
+    u32,
+    AddressSketch,
+    AddressSketch,
+    u32,
+    u64,
+    u32,
+    u32,
+    u32,
+    u32,
+    u32,
+    u32,
+    u32,
+    Vec<(String, u32)>,
+);
+
+impl ColumnEncoder for AnalyticMetrics {
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        // The version byte comes first, followed by the metrics of each version in order
+        let mut bytes = vec![self.version];
+        bytes.extend(
+            bincode_config()
+                .serialize(&(
+                    self.active_addresses,
+                    self.new_addresses,
+                    &self.active_address_sketch,
+                    &self.new_address_sketch,
+                    self.dust_allowance_outputs,
+                    self.dust_allowance_tokens,
+                    self.indexation_payloads,
+                    self.transaction_payloads,
+                    self.milestone_payloads,
+                    self.receipt_payloads,
+                    self.no_payload,
+                    self.conflicting_transactions,
+                    self.parents,
+                    &self.top_indexation_tags,
+                ))
+                .unwrap(),
+        );
+        buffer.extend(&i32::to_be_bytes(bytes.len() as i32));
+        buffer.extend(bytes)
+    }
+}
+
+impl ColumnDecoder for AnalyticMetrics {
+    fn try_decode(slice: &[u8]) -> anyhow::Result<Self> {
+        let (version, slice) = slice
+            .split_first()
+            .ok_or_else(|| anyhow!("Empty analytic metrics blob"))?;
+        ensure!(*version >= 1, "Invalid analytic metrics version {}", version);
+        let (
+            active_addresses,
+            new_addresses,
+            active_address_sketch,
+            new_address_sketch,
+            dust_allowance_outputs,
+            dust_allowance_tokens,
+            indexation_payloads,
+            transaction_payloads,
+            milestone_payloads,
+            receipt_payloads,
+            no_payload,
+            conflicting_transactions,
+            parents,
+            top_indexation_tags,
+        ): AnalyticMetricsV1 = bincode_config().deserialize(slice)?;
+        active_address_sketch.check()?;
+        new_address_sketch.check()?;
+        Ok(Self {
+            version: *version,
+            active_addresses,
+            new_addresses,
+            dust_allowance_outputs,
+            dust_allowance_tokens,
+            indexation_payloads,
+            transaction_payloads,
+            milestone_payloads,
+            receipt_payloads,
+            no_payload,
+            conflicting_transactions,
+            parents,
+            top_indexation_tags,
+            active_address_sketch,
+            new_address_sketch,
+        })
+    }
+}
+
+impl AnalyticMetrics {
+    /// Set the exact counts of the distinct active and new addresses, along with their sketches
+    pub fn set_addresses(&mut self, active_address_set: BTreeSet<[u8; 32]>, new_address_set: BTreeSet<[u8; 32]>) {
+        self.active_addresses = active_address_set.len() as u32;
+        self.new_addresses = new_address_set.len() as u32;
+        self.active_address_sketch = active_address_set.iter().collect();
+        self.new_address_sketch = new_address_set.iter().collect();
+    }
+    /// Merge the metrics of another milestone cone, as the lowest version of both.
+    /// The addresses active in both cones are counted about once, as estimated by the merged sketches.
+    /// The top indexation tags are summed up, and as many of them are kept as the longest of both lists.
+    pub fn merge(&mut self, other: &AnalyticMetrics) {
+        self.version = self.version.min(other.version);
+        self.active_address_sketch.merge(&other.active_address_sketch);
+        self.new_address_sketch.merge(&other.new_address_sketch);
+        self.active_addresses = self.active_address_sketch.estimate();
+        self.new_addresses = self.new_address_sketch.estimate();
+        self.dust_allowance_outputs += other.dust_allowance_outputs;
+        self.dust_allowance_tokens += other.dust_allowance_tokens;
+        self.indexation_payloads += other.indexation_payloads;
+        self.transaction_payloads += other.transaction_payloads;
+        self.milestone_payloads += other.milestone_payloads;
+        self.receipt_payloads += other.receipt_payloads;
+        self.no_payload += other.no_payload;
+        self.conflicting_transactions += other.conflicting_transactions;
+        self.parents += other.parents;
+        let len = self.top_indexation_tags.len().max(other.top_indexation_tags.len());
+        let mut tags = std::collections::HashMap::<String, u32>::new();
+        for (tag, count) in self
+            .top_indexation_tags
+            .drain(..)
+            .chain(other.top_indexation_tags.iter().cloned())
+        {
+            *tags.entry(tag).or_default() += count;
+        }
+        let mut tags = tags.into_iter().collect::<Vec<_>>();
+        tags.sort_by(|(a_tag, a_count), (b_tag, b_count)| b_count.cmp(a_count).then_with(|| a_tag.cmp(b_tag)));
+        tags.truncate(len);
+        self.top_indexation_tags = tags;
+    }
+}
+
 #[derive(Clone, Debug)]
 /// MilestoneData analytics information.
 pub struct AnalyticRecord {
@@ -532,6 +901,8 @@ pub struct AnalyticRecord {
     pub transaction_count: TransactionCount,
     /// Transferred IOTA tokens volume within a milestone cone
     pub transferred_tokens: TransferredTokens,
+    /// The versioned metrics, which are missing from the records analyzed before they were introduced
+    pub metrics: Option<AnalyticMetrics>,
 }
 
 impl AnalyticRecord {
@@ -547,8 +918,14 @@ impl AnalyticRecord {
             message_count,
             transaction_count,
             transferred_tokens,
+            metrics: None,
         }
     }
+    /// Set the versioned metrics
+    pub fn with_metrics(mut self, metrics: AnalyticMetrics) -> Self {
+        self.metrics.replace(metrics);
+        self
+    }
     /// Gets the milestone index
     pub fn milestone_index(&self) -> &MilestoneIndex {
         &self.milestone_index
@@ -565,4 +942,184 @@ impl AnalyticRecord {
     pub fn transferred_tokens(&self) -> &TransferredTokens {
         &self.transferred_tokens
     }
+    /// Gets the versioned metrics, if any
+    pub fn metrics(&self) -> Option<&AnalyticMetrics> {
+        self.metrics.as_ref()
+    }
+}
+
+/// The time interval of the analytics rollups
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RollupInterval {
+    /// An hour bucket
+    Hour,
+    /// A day bucket (UTC)
+    Day,
+}
+
+impl RollupInterval {
+    /// The length of the interval in seconds
+    pub fn secs(&self) -> u64 {
+        match self {
+            RollupInterval::Hour => 3600,
+            RollupInterval::Day => 86400,
+        }
+    }
+    /// Get the bucket, ie the start unix timestamp of the interval, of a given unix timestamp
+    pub fn bucket(&self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.secs()
+    }
+    /// The name of the interval, as stored in the `analytics_rollups` table
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RollupInterval::Hour => "hour",
+            RollupInterval::Day => "day",
+        }
+    }
+}
+
+impl std::str::FromStr for RollupInterval {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hour" => Ok(RollupInterval::Hour),
+            "day" => Ok(RollupInterval::Day),
+            _ => bail!("Invalid rollup interval {}, expected hour or day", s),
+        }
+    }
+}
+
+/// The key of the analytics of the milestones within an hour bucket, in the `analytics_hourly` table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HourBucket(pub u64);
+
+/// The key of the rollups of an interval within a time range (unix timestamps, end exclusive)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RollupRange {
+    /// The interval of the rollups
+    pub interval: RollupInterval,
+    /// The first bucket
+    pub from: u64,
+    /// The end of the range, exclusive
+    pub to: u64,
+}
+
+/// The aggregated analytics of the milestones within a time bucket
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnalyticRollup {
+    /// The start unix timestamp of the bucket
+    pub bucket: u64,
+    /// The number of analyzed milestones within the bucket
+    pub milestone_count: u32,
+    /// The total number of messages
+    pub message_count: u64,
+    /// The total number of included transactions
+    pub transaction_count: u64,
+    /// The transferred IOTA tokens volume
+    pub transferred_tokens: u64,
+    /// The merged metrics, which are missing if any milestone of the bucket was analyzed before they were introduced
+    pub metrics: Option<AnalyticMetrics>,
+}
+
+impl AnalyticRollup {
+    /// Create an empty rollup of a bucket
+    pub fn new(bucket: u64) -> Self {
+        Self {
+            bucket,
+            ..Default::default()
+        }
+    }
+    /// Add the analytics of a milestone
+    pub fn acc_record(&mut self, record: &AnalyticRecord) {
+        self.acc_metrics(record.metrics(), 1);
+        self.message_count += record.message_count.0 as u64;
+        self.transaction_count += record.transaction_count.0 as u64;
+        self.transferred_tokens += record.transferred_tokens.0;
+    }
+    /// Add the analytics of a finer rollup
+    pub fn acc_rollup(&mut self, rollup: &AnalyticRollup) {
+        self.acc_metrics(rollup.metrics.as_ref(), rollup.milestone_count);
+        self.message_count += rollup.message_count;
+        self.transaction_count += rollup.transaction_count;
+        self.transferred_tokens += rollup.transferred_tokens;
+    }
+    fn acc_metrics(&mut self, metrics: Option<&AnalyticMetrics>, milestone_count: u32) {
+        self.metrics = match (self.milestone_count, self.metrics.take(), metrics) {
+            (0, _, metrics) => metrics.cloned(),
+            (_, Some(mut acc), Some(metrics)) => {
+                acc.merge(metrics);
+                Some(acc)
+            }
+            _ => None,
+        };
+        self.milestone_count += milestone_count;
+    }
+}
+
+/// The kind of a node snapshot file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotKind {
+    /// The unspent outputs confirmed by the ledger index, along with the milestone diffs down to the solid entry
+    /// point index
+    Full,
+    /// The milestone diffs from the ledger index of the full snapshot up to the solid entry point index
+    Delta,
+}
+
+impl SnapshotKind {
+    /// The name of the kind, as stored in the `snapshots` table
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SnapshotKind::Full => "full",
+            SnapshotKind::Delta => "delta",
+        }
+    }
+}
+
+/// The header of an imported node snapshot file, in the `snapshots` table
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    /// The kind of the snapshot
+    pub kind: SnapshotKind,
+    /// The index of the milestone which confirmed the ledger state of the snapshot
+    pub ledger_index: u32,
+    /// The solid entry point index of the snapshot
+    pub sep_index: u32,
+    /// The unix timestamp of the snapshot
+    pub timestamp: u64,
+    /// The network id of the snapshot
+    pub network_id: u64,
+    /// The amount of the treasury output, which is only part of the full snapshots
+    pub treasury_amount: Option<u64>,
+    /// The number of unspent outputs
+    pub output_count: u64,
+    /// The number of milestone diffs
+    pub milestone_diff_count: u64,
+}
+
+/// An unspent output of a full snapshot, in the `snapshot_outputs` table
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotOutputRecord {
+    /// The ledger index of the snapshot
+    pub ledger_index: u32,
+    /// The message which created the output
+    pub message_id: MessageId,
+    /// The output
+    pub output: Output,
+}
+
+/// An output created or consumed by a milestone diff of a snapshot, in the `snapshot_diffs` table
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnapshotDiffRecord {
+    /// The id of the output
+    pub output_id: OutputId,
+    /// The message which created the output
+    pub message_id: MessageId,
+    /// The output
+    pub output: Output,
+    /// The transaction which consumed the output, if the milestone consumed it
+    pub consumed_by: Option<TransactionId>,
 }
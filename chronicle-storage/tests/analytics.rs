@@ -0,0 +1,181 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use bee_message::milestone::MilestoneIndex;
+use chronicle_storage::access::{
+    AddressSketch,
+    AnalyticMetrics,
+    AnalyticRecord,
+    AnalyticRollup,
+    MessageCount,
+    RollupInterval,
+    TransactionCount,
+    TransferredTokens,
+    ANALYTIC_METRICS_VERSION,
+};
+use scylla_rs::prelude::{
+    ColumnDecoder,
+    ColumnEncoder,
+};
+
+/// The metrics of a cone where the addresses 1 to 12 were active, the addresses 1 to 3 being new
+fn metrics() -> AnalyticMetrics {
+    let mut metrics = AnalyticMetrics {
+        version: ANALYTIC_METRICS_VERSION,
+        dust_allowance_outputs: 2,
+        dust_allowance_tokens: 2_000_000,
+        indexation_payloads: 30,
+        transaction_payloads: 7,
+        milestone_payloads: 1,
+        receipt_payloads: 0,
+        no_payload: 4,
+        conflicting_transactions: 1,
+        parents: 84,
+        top_indexation_tags: vec![("6368726f6e69636c65".to_owned(), 20), ("696f7461".to_owned(), 10)],
+        ..Default::default()
+    };
+    metrics.set_addresses(
+        (1..=12).map(|byte| [byte; 32]).collect(),
+        (1..=3).map(|byte| [byte; 32]).collect(),
+    );
+    metrics
+}
+
+#[test]
+fn test_encode_decode_metrics() {
+    let metrics = metrics();
+    let mut buffer = Vec::new();
+    metrics.encode(&mut buffer);
+    // Skip the length prefix
+    let decoded = AnalyticMetrics::try_decode(&buffer[4..]).unwrap();
+    assert_eq!(decoded, metrics);
+    assert_eq!(decoded.active_addresses, 12);
+    assert_eq!(decoded.new_addresses, 3);
+}
+
+#[test]
+fn test_decode_newer_metrics() {
+    // A newer version appends its metrics after the ones of the previous versions
+    let mut buffer = Vec::new();
+    metrics().encode(&mut buffer);
+    let mut blob = buffer[4..].to_vec();
+    blob[0] = ANALYTIC_METRICS_VERSION + 1;
+    blob.extend(&[0, 0, 0, 5]);
+    let decoded = AnalyticMetrics::try_decode(&blob).unwrap();
+    assert_eq!(decoded.version, ANALYTIC_METRICS_VERSION + 1);
+    assert_eq!(decoded.top_indexation_tags, metrics().top_indexation_tags);
+    assert!(AnalyticMetrics::try_decode(&[]).is_err());
+}
+
+/// Distinct addresses, spread like the Blake2b hashes they are by the splitmix64 mixer
+fn addresses(range: std::ops::Range<u64>) -> Vec<[u8; 32]> {
+    range
+        .map(|i| {
+            let mut z = i.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            let mut address = [0u8; 32];
+            address[..8].copy_from_slice(&(z ^ (z >> 31)).to_be_bytes());
+            address
+        })
+        .collect()
+}
+
+#[test]
+fn test_address_sketch() {
+    let mut sketch = addresses(0..20_000).iter().collect::<AddressSketch>();
+    let estimate = sketch.estimate() as f64;
+    assert!((estimate - 20_000.0).abs() < 20_000.0 * 0.1, "estimate {}", estimate);
+    // the addresses in both sketches are counted once
+    sketch.merge(&addresses(10_000..30_000).iter().collect());
+    let estimate = sketch.estimate() as f64;
+    assert!((estimate - 30_000.0).abs() < 30_000.0 * 0.1, "estimate {}", estimate);
+    // a few addresses are counted exactly
+    let few = (1..=5).map(|byte| [byte; 32]).collect::<Vec<_>>();
+    assert_eq!(few.iter().collect::<AddressSketch>().estimate(), 5);
+    assert_eq!(AddressSketch::default().estimate(), 0);
+    // the size of the metrics doesn't depend on the number of addresses
+    let size = |count| {
+        let mut metrics = metrics();
+        metrics.set_addresses(addresses(0..count).into_iter().collect(), Default::default());
+        let mut buffer = Vec::new();
+        metrics.encode(&mut buffer);
+        buffer.len()
+    };
+    assert_eq!(size(10), size(100_000));
+}
+
+#[test]
+fn test_decode_invalid_sketch() {
+    let mut buffer = Vec::new();
+    metrics().encode(&mut buffer);
+    // truncate the registers of the active address sketch, whose length follows the version and the address counts
+    let mut blob = buffer[4..].to_vec();
+    let len_at = 1 + 4 + 4;
+    assert_eq!(blob[len_at..len_at + 8], 1024u64.to_be_bytes());
+    blob[len_at..len_at + 8].copy_from_slice(&1000u64.to_be_bytes());
+    blob.drain(len_at + 8 + 1000..len_at + 8 + 1024);
+    assert!(AnalyticMetrics::try_decode(&blob).is_err());
+}
+
+fn record(milestone_index: u32, metrics: Option<AnalyticMetrics>) -> AnalyticRecord {
+    let record = AnalyticRecord::new(
+        MilestoneIndex(milestone_index),
+        MessageCount(10),
+        TransactionCount(2),
+        TransferredTokens(1_000),
+    );
+    match metrics {
+        Some(metrics) => record.with_metrics(metrics),
+        None => record,
+    }
+}
+
+#[test]
+fn test_rollup_buckets() {
+    // 2021-06-01T13:45:10Z
+    let timestamp = 1_622_555_110;
+    assert_eq!(RollupInterval::Hour.bucket(timestamp), 1_622_552_400);
+    assert_eq!(RollupInterval::Day.bucket(timestamp), 1_622_505_600);
+    assert_eq!("day".parse::<RollupInterval>().unwrap(), RollupInterval::Day);
+    assert!("week".parse::<RollupInterval>().is_err());
+}
+
+#[test]
+fn test_rollup_accumulation() {
+    let mut hour = AnalyticRollup::new(3600);
+    hour.acc_record(&record(1, Some(metrics())));
+    hour.acc_record(&record(2, Some(metrics())));
+    assert_eq!(hour.milestone_count, 2);
+    assert_eq!(hour.message_count, 20);
+    assert_eq!(hour.transferred_tokens, 2_000);
+    let merged = hour.metrics.clone().unwrap();
+    // the addresses active in both milestones are counted once, as estimated by the merged sketches
+    assert_eq!(merged.active_addresses, 12);
+    assert_eq!(merged.new_addresses, 3);
+    assert_eq!(merged.parents, 168);
+    let mut other = metrics();
+    other.set_addresses(
+        (10..=20).map(|byte| [byte; 32]).collect(),
+        (20..=20).map(|byte| [byte; 32]).collect(),
+    );
+    hour.acc_record(&record(3, Some(other)));
+    let merged = hour.metrics.clone().unwrap();
+    assert_eq!(merged.active_addresses, 20);
+    assert_eq!(merged.new_addresses, 4);
+    assert_eq!(
+        merged.top_indexation_tags,
+        vec![("6368726f6e69636c65".to_owned(), 40), ("696f7461".to_owned(), 20)]
+    );
+
+    // the metrics of a bucket are missing once a milestone was analyzed without them
+    let mut day = AnalyticRollup::new(0);
+    day.acc_rollup(&hour);
+    assert!(day.metrics.is_some());
+    let mut legacy_hour = AnalyticRollup::new(7200);
+    legacy_hour.acc_record(&record(3, None));
+    day.acc_rollup(&legacy_hour);
+    assert_eq!(day.milestone_count, 3);
+    assert_eq!(day.transaction_count, 6);
+    assert!(day.metrics.is_none());
+}
@@ -0,0 +1,64 @@
+// Copyright 2021 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use bee_message::MessageId;
+use bincode::Options;
+use chronicle_storage::access::{
+    LedgerInclusionState,
+    MessageMetadata,
+};
+use scylla_rs::prelude::{
+    ColumnDecoder,
+    ColumnEncoder,
+};
+
+fn bincode_config() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_big_endian()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+}
+
+#[test]
+fn test_decode_legacy_metadata() {
+    let legacy = bincode_config()
+        .serialize(&(
+            MessageId::null(),
+            vec![MessageId::null()],
+            true,
+            Some(3u32),
+            Some(LedgerInclusionState::Included),
+            None::<bool>,
+            None::<bool>,
+        ))
+        .unwrap();
+    let metadata = MessageMetadata::try_decode(&legacy).unwrap();
+    assert_eq!(metadata.referenced_by_milestone_index, Some(3));
+    assert_eq!(metadata.ledger_inclusion_state, Some(LedgerInclusionState::Included));
+    assert!(metadata.conflict_reason.is_none());
+    assert!(metadata.white_flag_index.is_none());
+    assert!(metadata.milestone_timestamp_referenced.is_none());
+}
+
+#[test]
+fn test_encode_decode_metadata() {
+    let metadata = MessageMetadata {
+        message_id: MessageId::null(),
+        parent_message_ids: vec![MessageId::null()],
+        is_solid: true,
+        referenced_by_milestone_index: Some(3),
+        ledger_inclusion_state: Some(LedgerInclusionState::Conflicting),
+        should_promote: None,
+        should_reattach: None,
+        conflict_reason: Some(1),
+        white_flag_index: Some(7),
+        milestone_timestamp_referenced: Some(1_620_000_000),
+    };
+    let mut buffer = Vec::new();
+    metadata.encode(&mut buffer);
+    // Skip the length prefix
+    let decoded = MessageMetadata::try_decode(&buffer[4..]).unwrap();
+    assert_eq!(decoded.conflict_reason, Some(1));
+    assert_eq!(decoded.white_flag_index, Some(7));
+    assert_eq!(decoded.milestone_timestamp_referenced, Some(1_620_000_000));
+}
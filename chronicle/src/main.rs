@@ -135,12 +135,33 @@ fn register_metrics() {
     REGISTRY
         .register(Box::new(CONFIRMATION_TIME_COLLECTOR.clone()))
         .expect("Could not register collector");
+
+    REGISTRY
+        .register(Box::new(INBOX_QUEUE_DEPTH.clone()))
+        .expect("Could not register collector");
+
+    REGISTRY
+        .register(Box::new(INBOX_DROPPED_EVENTS.clone()))
+        .expect("Could not register collector");
 }
 
 async fn init_database() -> anyhow::Result<()> {
-    let storage_config = get_config_async().await.storage_config;
+    let config = get_config_async().await;
+    let storage_config = config.storage_config;
+    // the first keyspace is the default one, the others are created only if a network profile stores in them
+    let network_keyspaces = config
+        .broker_config
+        .networks
+        .iter()
+        .map(|network| network.keyspace.clone())
+        .collect::<std::collections::HashSet<_>>();
 
-    for keyspace_config in storage_config.keyspaces.first().iter() {
+    for (_, keyspace_config) in storage_config
+        .keyspaces
+        .iter()
+        .enumerate()
+        .filter(|(i, keyspace_config)| *i == 0 || network_keyspaces.contains(&keyspace_config.name))
+    {
         let keyspace = ChronicleKeyspace::new(keyspace_config.name.clone());
         let datacenters = keyspace_config
             .data_centers
@@ -251,8 +272,61 @@ async fn init_database() -> anyhow::Result<()> {
                 message_count int,
                 transaction_count int,
                 transferred_tokens bigint,
+                metrics blob,
                 PRIMARY KEY (key, milestone_index)
-            ) WITH CLUSTERING ORDER BY (milestone_index DESC);",
+            ) WITH CLUSTERING ORDER BY (milestone_index DESC);
+
+            CREATE TABLE IF NOT EXISTS {0}.analytics_hourly (
+                bucket bigint,
+                milestone_index int,
+                message_count int,
+                transaction_count int,
+                transferred_tokens bigint,
+                metrics blob,
+                PRIMARY KEY (bucket, milestone_index)
+            );
+
+            CREATE TABLE IF NOT EXISTS {0}.analytics_rollups (
+                interval text,
+                bucket bigint,
+                milestone_count int,
+                message_count bigint,
+                transaction_count bigint,
+                transferred_tokens bigint,
+                metrics blob,
+                PRIMARY KEY (interval, bucket)
+            ) WITH CLUSTERING ORDER BY (bucket ASC);
+
+            CREATE TABLE IF NOT EXISTS {0}.snapshots (
+                key text,
+                ledger_index int,
+                kind text,
+                sep_index int,
+                timestamp bigint,
+                network_id bigint,
+                treasury_amount bigint,
+                output_count bigint,
+                milestone_diff_count bigint,
+                PRIMARY KEY (key, ledger_index, kind)
+            ) WITH CLUSTERING ORDER BY (ledger_index DESC, kind ASC);
+
+            CREATE TABLE IF NOT EXISTS {0}.snapshot_outputs (
+                output_id text,
+                ledger_index int,
+                message_id text,
+                data blob,
+                PRIMARY KEY (output_id, ledger_index)
+            ) WITH CLUSTERING ORDER BY (ledger_index DESC);
+
+            CREATE TABLE IF NOT EXISTS {0}.snapshot_diffs (
+                milestone_index int,
+                variant text,
+                output_id text,
+                message_id text,
+                data blob,
+                consumed_by text,
+                PRIMARY KEY (milestone_index, variant, output_id)
+            );",
             keyspace.name()
         );
         for query in table_queries.split(";").map(str::trim).filter(|s| !s.is_empty()) {
@@ -268,6 +342,20 @@ async fn init_database() -> anyhow::Result<()> {
                 bail!("Could not verify if table was created!")
             }
         }
+        // The analytics tables created before the metrics column are migrated
+        let worker = BatchWorker::boxed(sender.clone());
+        let statement = Query::new()
+            .statement(&format!("ALTER TABLE {0}.analytics ADD metrics blob", keyspace.name()))
+            .consistency(Consistency::One)
+            .build()?;
+        send_local(token, statement.0, worker, keyspace.name().to_string());
+        match inbox.recv().await {
+            Some(Ok(_)) => debug!("Added the metrics column to the {}.analytics table", keyspace.name()),
+            // The table was created with the column, or migrated already
+            Some(Err(e)) if e.to_string().contains("conflicts with an existing column") => (),
+            Some(Err(e)) => bail!("Unable to add the metrics column to the analytics table: {}", e),
+            None => bail!("Could not verify if the analytics table was migrated!"),
+        }
     }
     Ok(())
 }